@@ -0,0 +1,35 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exposes the build metadata served by `/v1/version`. `GIT_SHA` can be set when building
+/// outside of the repository, e.g. in the Docker image which doesn't copy `.git`.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    for path in get_git_head_files() {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+
+    let git_sha = std::env::var("GIT_SHA").ok().filter(|sha| !sha.is_empty()).or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha.unwrap_or_default());
+
+    let build_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp);
+}
+
+/// Files which change along with the commit checked out: `HEAD` only changes when switching
+/// branches, a new commit changes the file of the branch instead, or `packed-refs` once the refs
+/// are packed. Missing files are skipped, cargo would otherwise run the script on every build.
+fn get_git_head_files() -> Vec<String> {
+    let mut paths = vec![".git/HEAD".to_string(), ".git/packed-refs".to_string()];
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD") {
+        if let Some(reference) = head.trim().strip_prefix("ref: ") {
+            paths.push(format!(".git/{}", reference));
+        }
+    }
+    paths.into_iter().filter(|path| Path::new(path).exists()).collect()
+}
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE audit_logs
+(
+    id         uuid PRIMARY KEY DEFAULT uuid_generate_v4(),
+    indexer_id uuid        NOT NULL,
+    action     VARCHAR     NOT NULL,
+    from_status VARCHAR,
+    to_status  VARCHAR,
+    reason     VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_logs_indexer_id_idx ON audit_logs (indexer_id);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX audit_logs_actor_idx;
+DROP INDEX audit_logs_action_idx;
+DROP INDEX audit_logs_created_at_id_idx;
+
+ALTER TABLE audit_logs DROP COLUMN severity;
+ALTER TABLE audit_logs DROP COLUMN actor;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+ALTER TABLE audit_logs ADD COLUMN actor VARCHAR;
+ALTER TABLE audit_logs ADD COLUMN severity VARCHAR NOT NULL DEFAULT 'Info';
+
+-- keyset pagination walks the logs by (created_at, id)
+CREATE INDEX audit_logs_created_at_id_idx ON audit_logs (created_at DESC, id DESC);
+CREATE INDEX audit_logs_action_idx ON audit_logs (action);
+CREATE INDEX audit_logs_actor_idx ON audit_logs (actor);
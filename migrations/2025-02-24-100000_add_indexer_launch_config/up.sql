@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- configuration the sink process was last launched with
+ALTER TABLE indexers ADD COLUMN launch_config JSONB;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX audit_logs_indexer_id_created_at_idx;
+ALTER TABLE audit_logs DROP COLUMN details;
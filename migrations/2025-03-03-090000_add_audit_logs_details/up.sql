@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+-- snapshot of the indexer config at the time of the event
+ALTER TABLE audit_logs ADD COLUMN details JSONB;
+
+CREATE INDEX audit_logs_indexer_id_created_at_idx ON audit_logs (indexer_id, created_at DESC);
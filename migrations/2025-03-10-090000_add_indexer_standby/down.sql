@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX indexers_standby_for_idx;
+ALTER TABLE indexers DROP COLUMN standby_for;
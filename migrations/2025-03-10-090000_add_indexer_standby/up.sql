@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- passive replicas point to the primary they take over from, a primary has at most one standby
+ALTER TABLE indexers ADD COLUMN standby_for UUID REFERENCES indexers (id) ON DELETE SET NULL;
+CREATE UNIQUE INDEX indexers_standby_for_idx ON indexers (standby_for);
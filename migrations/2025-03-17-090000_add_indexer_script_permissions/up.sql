@@ -0,0 +1,3 @@
+-- Your SQL goes here
+
+ALTER TABLE indexers ADD COLUMN script_permissions JSONB;
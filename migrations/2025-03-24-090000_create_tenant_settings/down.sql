@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX indexers_tenant_id_idx;
+ALTER TABLE indexers DROP COLUMN stream_url;
+ALTER TABLE indexers DROP COLUMN tenant_id;
+DROP TABLE tenant_settings;
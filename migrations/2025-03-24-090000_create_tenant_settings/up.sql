@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TABLE tenant_settings
+(
+    tenant_id             VARCHAR PRIMARY KEY,
+    default_stream_url    VARCHAR,
+    default_log_level     VARCHAR,
+    -- an empty list allows every indexer type
+    allowed_indexer_types JSONB       NOT NULL DEFAULT '[]',
+    updated_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE indexers ADD COLUMN tenant_id VARCHAR;
+ALTER TABLE indexers ADD COLUMN stream_url VARCHAR;
+
+CREATE INDEX indexers_tenant_id_idx ON indexers (tenant_id);
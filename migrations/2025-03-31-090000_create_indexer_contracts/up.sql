@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE indexer_contracts
+(
+    indexer_id uuid    NOT NULL REFERENCES indexers (id) ON DELETE CASCADE,
+    -- normalized to 64 lowercase hex characters so lookups don't depend on how the script wrote it
+    address    VARCHAR NOT NULL,
+    event_keys JSONB   NOT NULL DEFAULT '[]',
+    PRIMARY KEY (indexer_id, address)
+);
+
+CREATE INDEX indexer_contracts_address_idx ON indexer_contracts (address);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE maintenance_windows
+(
+    id         uuid PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- windows without a tenant apply to every indexer
+    tenant_id  VARCHAR,
+    kind       VARCHAR     NOT NULL,
+    starts_at  TIMESTAMPTZ NOT NULL,
+    ends_at    TIMESTAMPTZ NOT NULL,
+    reason     VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX maintenance_windows_ends_at_idx ON maintenance_windows (ends_at);
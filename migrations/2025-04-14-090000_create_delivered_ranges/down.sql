@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX indexers_backfill_for_idx;
+DROP TABLE delivered_ranges;
+ALTER TABLE indexers DROP COLUMN backfill_for;
+ALTER TABLE indexers DROP COLUMN ending_block;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+ALTER TABLE indexers ADD COLUMN ending_block BIGINT;
+ALTER TABLE indexers ADD COLUMN backfill_for uuid;
+
+CREATE TABLE delivered_ranges
+(
+    id          uuid PRIMARY KEY DEFAULT uuid_generate_v4(),
+    indexer_id  uuid        NOT NULL REFERENCES indexers (id) ON DELETE CASCADE,
+    -- both ends are included
+    start_block BIGINT      NOT NULL,
+    end_block   BIGINT      NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX delivered_ranges_indexer_id_idx ON delivered_ranges (indexer_id);
+CREATE INDEX indexers_backfill_for_idx ON indexers (backfill_for);
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE indexers ADD COLUMN sink_options JSONB;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE indexers ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
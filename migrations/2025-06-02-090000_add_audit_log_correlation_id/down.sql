@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE audit_logs DROP COLUMN correlation_id;
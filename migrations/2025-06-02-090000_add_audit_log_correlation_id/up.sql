@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE audit_logs ADD COLUMN correlation_id VARCHAR;
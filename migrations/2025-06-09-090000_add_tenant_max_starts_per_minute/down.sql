@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE tenant_settings DROP COLUMN max_starts_per_minute;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE script_index
+(
+    indexer_id      uuid        NOT NULL PRIMARY KEY REFERENCES indexers (id) ON DELETE CASCADE,
+    -- checksum of the indexed script, it's indexed again once the indexer points to another one
+    script_checksum VARCHAR,
+    content         TEXT        NOT NULL,
+    content_tsv     TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
+    indexed_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX script_index_content_tsv_idx ON script_index USING GIN (content_tsv);
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+CREATE TABLE scheduled_actions
+(
+    id           uuid        NOT NULL PRIMARY KEY,
+    indexer_id   uuid        NOT NULL REFERENCES indexers (id) ON DELETE CASCADE,
+    kind         VARCHAR     NOT NULL,
+    run_at       TIMESTAMPTZ NOT NULL,
+    attempts     INT         NOT NULL DEFAULT 0,
+    -- set while an executor runs the action, the action is picked again once it expires
+    locked_until TIMESTAMPTZ,
+    last_error   VARCHAR,
+    -- set once the action failed too many times, it isn't retried anymore
+    failed_at    TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX scheduled_actions_run_at_idx ON scheduled_actions (run_at) WHERE failed_at IS NULL;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TABLE tenant_usage
+(
+    tenant_id        VARCHAR     NOT NULL,
+    -- not a foreign key, the usage of deleted indexers is still billed
+    indexer_id       uuid        NOT NULL,
+    day              DATE        NOT NULL,
+    running_seconds  BIGINT      NOT NULL DEFAULT 0,
+    blocks_processed BIGINT      NOT NULL DEFAULT 0,
+    -- size of the script on the day, not summed across the samples
+    script_bytes     BIGINT      NOT NULL DEFAULT 0,
+    log_bytes        BIGINT      NOT NULL DEFAULT 0,
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, day, indexer_id)
+);
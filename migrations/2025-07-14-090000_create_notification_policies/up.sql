@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE notification_policies
+(
+    indexer_id               uuid        NOT NULL PRIMARY KEY REFERENCES indexers (id) ON DELETE CASCADE,
+    max_alerts_per_hour      INT         NOT NULL,
+    -- the same alert isn't sent again within this window
+    duplicate_window_seconds INT         NOT NULL,
+    updated_at               TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE indexer_annotations
+(
+    id           uuid        NOT NULL PRIMARY KEY,
+    indexer_id   uuid        NOT NULL REFERENCES indexers (id) ON DELETE CASCADE,
+    -- markdown written by the operator
+    body         TEXT        NOT NULL,
+    author       VARCHAR     NOT NULL,
+    incident_url VARCHAR,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX indexer_annotations_indexer_id_created_at_idx ON indexer_annotations (indexer_id, created_at);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE tenant_settings DROP COLUMN stale_created_action;
+ALTER TABLE tenant_settings DROP COLUMN stale_created_after_seconds;
+
+ALTER TABLE indexers DROP COLUMN created_at;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- existing rows get the time of the migration, they're only swept once the threshold passed
+ALTER TABLE indexers ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE tenant_settings ADD COLUMN stale_created_after_seconds INTEGER;
+ALTER TABLE tenant_settings ADD COLUMN stale_created_action VARCHAR;
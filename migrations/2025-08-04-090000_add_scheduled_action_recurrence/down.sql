@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE scheduled_actions DROP COLUMN timezone;
+ALTER TABLE scheduled_actions DROP COLUMN cron;
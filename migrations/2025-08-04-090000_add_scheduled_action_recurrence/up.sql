@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- recurring actions move to their next run instead of being deleted once they ran
+ALTER TABLE scheduled_actions ADD COLUMN cron VARCHAR;
+-- IANA name of the timezone the cron expression is evaluated in, UTC if not set
+ALTER TABLE scheduled_actions ADD COLUMN timezone VARCHAR;
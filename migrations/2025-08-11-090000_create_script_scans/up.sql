@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- findings of the vulnerability scan of each script version, shared by the indexers running it
+CREATE TABLE script_scans
+(
+    script_checksum VARCHAR PRIMARY KEY,
+    dependencies    JSONB       NOT NULL DEFAULT '[]',
+    findings        JSONB       NOT NULL DEFAULT '[]',
+    scanned_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE tenant_settings DROP COLUMN require_target_approval;
+
+DROP TABLE approved_targets;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- webhook destinations an admin approved for the indexers of a tenant
+CREATE TABLE approved_targets
+(
+    tenant_id   VARCHAR     NOT NULL,
+    target_url  VARCHAR     NOT NULL,
+    approved_by VARCHAR,
+    approved_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, target_url)
+);
+
+ALTER TABLE tenant_settings ADD COLUMN require_target_approval BOOLEAN NOT NULL DEFAULT false;
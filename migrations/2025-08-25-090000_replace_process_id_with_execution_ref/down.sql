@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE indexers ADD COLUMN process_id BIGINT;
+
+UPDATE indexers SET process_id = (execution_ref ->> 'pid')::BIGINT WHERE execution_ref ->> 'kind' = 'pid';
+
+ALTER TABLE indexers DROP COLUMN execution_ref;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE indexers ADD COLUMN execution_ref JSONB;
+
+UPDATE indexers SET execution_ref = jsonb_build_object('kind', 'pid', 'pid', process_id) WHERE process_id IS NOT NULL;
+
+ALTER TABLE indexers DROP COLUMN process_id;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- conditions over the indexers notified to channels once they hold long enough
+CREATE TABLE alert_rules
+(
+    id          UUID PRIMARY KEY,
+    name        VARCHAR     NOT NULL,
+    indexer_id  UUID REFERENCES indexers (id) ON DELETE CASCADE,
+    condition   JSONB       NOT NULL,
+    for_seconds BIGINT      NOT NULL,
+    channels    JSONB       NOT NULL,
+    enabled     BOOLEAN     NOT NULL DEFAULT true,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- bumped each time a process is started for the indexer, stops decided for an older one are dropped
+ALTER TABLE indexers ADD COLUMN execution_seq BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- single use tokens external orchestrators redeem to start an indexer, only the hash of the
+-- token is stored
+CREATE TABLE start_tokens
+(
+    id          uuid        NOT NULL PRIMARY KEY,
+    indexer_id  uuid        NOT NULL REFERENCES indexers (id) ON DELETE CASCADE,
+    token_hash  VARCHAR     NOT NULL UNIQUE,
+    created_by  VARCHAR,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    redeemed_at TIMESTAMPTZ,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX start_tokens_indexer_id_idx ON start_tokens (indexer_id);
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER bump_indexers_version ON indexers;
+
+DROP FUNCTION bump_indexers_version();
+
+DROP SEQUENCE indexers_version;
+
+DROP TRIGGER set_updated_at ON indexers;
+
+ALTER TABLE indexers DROP COLUMN updated_at;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+ALTER TABLE indexers ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+SELECT diesel_manage_updated_at('indexers');
+
+-- bumped by every statement changing the indexers, the listings are cached against it. A sequence
+-- so that concurrent changes don't wait on each other, a change rolled back still bumps it.
+CREATE SEQUENCE indexers_version;
+
+CREATE FUNCTION bump_indexers_version() RETURNS trigger AS
+$$
+BEGIN
+    PERFORM nextval('indexers_version');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bump_indexers_version
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE
+    ON indexers
+    FOR EACH STATEMENT
+EXECUTE PROCEDURE bump_indexers_version();
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE tenant_settings ADD COLUMN secret_backend VARCHAR;
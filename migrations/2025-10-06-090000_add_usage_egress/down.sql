@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE tenant_usage DROP COLUMN delivered_bytes;
+ALTER TABLE tenant_usage DROP COLUMN streamed_bytes;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE tenant_usage ADD COLUMN streamed_bytes BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE tenant_usage ADD COLUMN delivered_bytes BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX indexers_project_id_idx;
+ALTER TABLE indexers DROP COLUMN project_id;
+
+DROP TABLE projects;
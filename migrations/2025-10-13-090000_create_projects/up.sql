@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- groups of indexers of a tenant sharing defaults, a secrets scope and role bindings
+CREATE TABLE projects
+(
+    id                 UUID PRIMARY KEY,
+    tenant_id          VARCHAR     NOT NULL,
+    name               VARCHAR     NOT NULL,
+    description        VARCHAR,
+    default_stream_url VARCHAR,
+    default_log_level  VARCHAR,
+    secret_path_prefix VARCHAR,
+    role_bindings      JSONB       NOT NULL DEFAULT '[]',
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (tenant_id, name)
+);
+
+ALTER TABLE indexers ADD COLUMN project_id UUID REFERENCES projects (id);
+CREATE INDEX indexers_project_id_idx ON indexers (project_id);
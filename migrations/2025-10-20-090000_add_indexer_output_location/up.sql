@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- where the sinks writing files put them, e.g. s3://bucket/prefix
+ALTER TABLE indexers ADD COLUMN output_location VARCHAR;
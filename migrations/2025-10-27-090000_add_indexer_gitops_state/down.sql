@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE indexers DROP COLUMN managed_by, DROP COLUMN archived_at;
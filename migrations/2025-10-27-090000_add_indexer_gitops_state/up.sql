@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- what created the indexer and keeps it in sync, e.g. gitops, not set for the indexers of the API
+ALTER TABLE indexers ADD COLUMN managed_by VARCHAR;
+-- when its manager stopped the indexer as it was removed from the desired state
+ALTER TABLE indexers ADD COLUMN archived_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX delivered_ranges_indexer_id_range_idx;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- receivers may report the same range again, only the first report is kept
+DELETE
+FROM delivered_ranges duplicate USING delivered_ranges original
+WHERE duplicate.indexer_id = original.indexer_id
+  AND duplicate.start_block = original.start_block
+  AND duplicate.end_block = original.end_block
+  AND (duplicate.created_at, duplicate.id) > (original.created_at, original.id);
+
+CREATE UNIQUE INDEX delivered_ranges_indexer_id_range_idx ON delivered_ranges (indexer_id, start_block, end_block);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE upload_parts;
+DROP TABLE upload_sessions;
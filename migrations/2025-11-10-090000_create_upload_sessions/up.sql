@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- resumable uploads of script bundles, the parts themselves are staged in the object store
+CREATE TABLE upload_sessions
+(
+    id         UUID PRIMARY KEY,
+    tenant_id  VARCHAR,
+    status     VARCHAR     NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX upload_sessions_expires_at_idx ON upload_sessions (expires_at);
+
+CREATE TABLE upload_parts
+(
+    upload_id   UUID        NOT NULL REFERENCES upload_sessions (id) ON DELETE CASCADE,
+    part_number INTEGER     NOT NULL,
+    size        BIGINT      NOT NULL,
+    sha256      VARCHAR     NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (upload_id, part_number)
+);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- substring searches (e.g. part of an address) go through the trigrams instead of a full scan
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE INDEX script_index_content_trgm_idx ON script_index USING GIN (lower(content) gin_trgm_ops);
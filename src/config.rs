@@ -1,7 +1,10 @@
 use std::env;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use arc_swap::{ArcSwap, Guard};
+use deadpool_diesel::Runtime;
 // use aws_sdk_s3::Client as S3Client;
 #[cfg(test)]
 use diesel::{Connection, PgConnection, RunQueryDsl};
@@ -18,16 +21,43 @@ use futures_util::FutureExt;
 use object_store::aws::AmazonS3Builder;
 #[cfg(feature = "gcp")]
 use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::memory::InMemory;
 use object_store::ObjectStore;
+use strum::IntoEnumIterator;
 use tokio::sync::OnceCell;
 
+use crate::constants::db::{DEFAULT_BACKGROUND_POOL_MAX_SIZE, DEFAULT_CONSUMERS_POOL_MAX_SIZE};
+use crate::constants::indexers::{DEFAULT_ALLOWED_STREAM_DOMAINS, DEFAULT_METRICS_MAX_SERIES};
+#[cfg(not(test))]
+use crate::constants::indexers::{
+    DEFAULT_READINESS_WINDOW_SECONDS, GITOPS_DEFAULT_MANIFEST_PATH, GITOPS_DEFAULT_POLL_INTERVAL_SECONDS,
+    OSV_DEFAULT_API_URL,
+};
+#[cfg(not(test))]
+use crate::constants::runtime::{DEFAULT_MEMORY_PRESSURE_HYSTERESIS, VAULT_DEFAULT_KV_MOUNT};
+use crate::constants::runtime::{
+    DEFAULT_OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS, DEFAULT_OUTBOUND_HTTP_TIMEOUT_SECONDS, DEFAULT_SECRETS_FILE_ROOT,
+};
+use crate::constants::s3::DEFAULT_LOCAL_STORAGE_PATH;
+use crate::domain::models::grafana::MetricLabel;
+use crate::domain::models::notification::SigningKey;
+use crate::domain::models::runtime::{PoolName, StartupCompromise, StartupMode, StorageBootstrap};
+use crate::domain::models::script_scan::AdvisorySeverity;
+#[cfg(feature = "aws")]
+use crate::infra::bootstrap::bootstrap_s3_bucket;
 #[cfg(test)]
 use crate::run_migrations;
 #[cfg(test)]
-use crate::tests::common::constants::TEST_DB_NAME;
+use crate::tests::common::constants::{
+    TEST_ADMIN_API_KEY, TEST_DB_NAME, TEST_SIGNING_KEY_ID, TEST_SIGNING_KEY_SECRET, TEST_TENANT_KEY_SECRET,
+};
 #[cfg(test)]
 use crate::tests::common::utils::clear_db;
-use crate::utils::env::get_environment_variable;
+use crate::utils::env::try_get_environment_variable;
+use crate::utils::sandbox_policy::SandboxPolicy;
+use crate::utils::stream_policy::StreamPolicy;
+use crate::utils::target_policy::{Cidr, TargetPolicy};
 
 #[derive(Debug)]
 struct ServerConfig {
@@ -38,15 +68,170 @@ struct ServerConfig {
 #[derive(Debug)]
 struct DatabaseConfig {
     url: String,
+    pool: PoolSettings,
+}
+
+/// Settings of the connection pool, deadpool defaults are used for anything not set
+#[derive(Debug)]
+struct PoolSettings {
+    max_size: Option<usize>,
+    wait_timeout: Option<Duration>,
+    create_timeout: Option<Duration>,
+    recycle_timeout: Option<Duration>,
+}
+
+/// Indexers are started in batches of `batch_size` every `interval` when the service starts,
+/// rather than all at once
+#[derive(Debug, Clone, Copy)]
+pub struct StartupRampUp {
+    pub batch_size: usize,
+    pub interval: Duration,
 }
 
 pub struct Config {
     server: ServerConfig,
+    /// Listener of the callbacks of the sinks and sidecars, never exposed on the public interface
+    internal_server: ServerConfig,
+    internal_tls: bool,
     // s3_client: S3Client,
     object_store: Arc<dyn ObjectStore>,
     pool: Arc<Pool<AsyncPgConnection>>,
     db_config: DatabaseConfig,
+    consumers_pool: Arc<Pool<AsyncPgConnection>>,
+    background_pool: Arc<Pool<AsyncPgConnection>>,
+    replica_pool: Option<Arc<Pool<AsyncPgConnection>>>,
     is_dev: bool,
+    multiplexer_enabled: bool,
+    admin_api_key: Option<String>,
+    tenant_key_secret: Option<String>,
+    notifications: NotificationsConfig,
+    target_policy: TargetPolicy,
+    config_drift_auto_restart: bool,
+    sandbox_policy: SandboxPolicy,
+    stream_policy: StreamPolicy,
+    hook_allowed_commands: Vec<String>,
+    startup_ramp_up: StartupRampUp,
+    script_search_enabled: bool,
+    multipart_strict_mode: bool,
+    gitops: Option<GitOpsConfig>,
+    memory_pressure: Option<MemoryPressureConfig>,
+    warm_start_checks: bool,
+    strict_starts: bool,
+    readiness_window: Option<Duration>,
+    script_scan: Option<ScriptScanConfig>,
+    tls: Option<TlsConfig>,
+    outbound_http: OutboundHttpConfig,
+    metric_labels: MetricLabelsConfig,
+    secrets: SecretsConfig,
+    startup_mode: StartupMode,
+    /// Backends replaced by local ones, always empty in the strict mode
+    startup_compromises: Vec<StartupCompromise>,
+}
+
+/// Requests are served over TLS, without a fronting proxy, once a certificate and its key are set
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    /// Internal routes require a client certificate signed by this CA once it is set
+    pub client_ca_path: Option<String>,
+}
+
+/// Indexers are paused once the memory used on the host reaches `pause_ratio` and resumed once it
+/// gets back under `resume_ratio`
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryPressureConfig {
+    pub pause_ratio: f64,
+    pub resume_ratio: f64,
+}
+
+/// Fleet managed from a Git repository, the sync is disabled unless a repository is set
+#[derive(Debug, Clone)]
+pub struct GitOpsConfig {
+    /// Url the raw files of the synced branch are served under, e.g.
+    /// `https://raw.githubusercontent.com/org/repo/main/`
+    pub repository_url: String,
+    pub manifest_path: String,
+    pub poll_interval: Duration,
+    /// Webhooks of the repository are rejected unless this is set
+    pub webhook_secret: Option<String>,
+    /// Whether the sync takes over the unmarked indexers it recognizes as its own, e.g. the ones
+    /// it failed to mark after creating them
+    pub adopt_unmarked: bool,
+}
+
+/// Dependencies of the uploaded scripts are checked against the OSV advisories once enabled
+#[derive(Debug, Clone)]
+pub struct ScriptScanConfig {
+    pub osv_api_url: String,
+    /// Scripts with an advisory at or above this severity can't be deployed, findings are only
+    /// recorded if not set
+    pub block_severity: Option<AdvisorySeverity>,
+}
+
+/// Client of the outbound HTTP calls: notifications, hooks, script fetches, advisories and the
+/// multiplexer relay
+#[derive(Debug, Clone)]
+pub struct OutboundHttpConfig {
+    /// Every call goes through this proxy once it is set, except to the hosts of `no_proxy`
+    pub proxy_url: Option<String>,
+    /// Comma separated hosts, as in `NO_PROXY`
+    pub no_proxy: Option<String>,
+    /// Roots trusted on top of the native ones, e.g. the CA of an intercepting proxy
+    pub ca_cert_path: Option<String>,
+    pub connect_timeout: Duration,
+    /// Timeout of the calls to hosts without their own, calls with a timeout of their own (hooks,
+    /// script fetches) keep it
+    pub timeout: Duration,
+    /// Timeouts by host, a host also covers its subdomains
+    pub destination_timeouts: Vec<(String, Duration)>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<Duration>,
+}
+
+/// Backends the secret references of the sinks are resolved from, see `SecretReference`
+#[derive(Debug, Clone)]
+pub struct SecretsConfig {
+    /// `env://` and `file://` read from the host of the service, they are only enabled in dev
+    pub local_backends: bool,
+    /// Directory `file://` references are read under
+    pub file_root: String,
+    pub vault: Option<VaultConfig>,
+    pub aws_secrets_manager: Option<AwsSecretsManagerConfig>,
+}
+
+/// KV v2 engine of a Vault server, read with a token
+#[derive(Debug, Clone)]
+pub struct VaultConfig {
+    pub address: String,
+    pub token: String,
+    pub mount: String,
+    pub namespace: Option<String>,
+}
+
+/// Static credentials of Secrets Manager, the instance and task roles are not looked up
+#[derive(Debug, Clone)]
+pub struct AwsSecretsManagerConfig {
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    /// Replaces the regional endpoint, e.g. for localstack
+    pub endpoint: Option<String>,
+}
+
+/// Granularity of the series of the metrics served to Grafana
+#[derive(Debug, Clone, Copy)]
+pub struct MetricLabelsConfig {
+    /// Every metric is a single series if not set
+    pub label: Option<MetricLabel>,
+    pub max_series: usize,
+}
+
+#[derive(Debug, Default)]
+struct NotificationsConfig {
+    webhook_url: Option<String>,
+    signing_keys: Vec<SigningKey>,
 }
 
 impl Config {
@@ -58,21 +243,184 @@ impl Config {
         self.server.port
     }
 
+    pub fn internal_server_host(&self) -> &str {
+        &self.internal_server.host
+    }
+
+    pub fn internal_server_port(&self) -> u16 {
+        self.internal_server.port
+    }
+
+    /// Url the sinks of this host reach an internal route at, e.g. `/internal/preview/{id}`
+    pub fn internal_url(&self, path: &str) -> String {
+        let scheme = if self.internal_tls().is_some() { "https" } else { "http" };
+        // a listener bound to every interface is reached through the loopback
+        let host = match self.internal_server.host.as_str() {
+            "0.0.0.0" | "::" => "127.0.0.1",
+            host => host,
+        };
+        format!("{}://{}:{}{}", scheme, host, self.internal_server.port, path)
+    }
+
     pub fn object_store(&self) -> &Arc<dyn ObjectStore> {
         &self.object_store
     }
 
+    /// Pool of the API, other subsystems have their own
     pub fn pool(&self) -> &Arc<Pool<AsyncPgConnection>> {
         &self.pool
     }
 
+    /// Pool of the tasks reacting to the sinks
+    pub fn consumers_pool(&self) -> &Arc<Pool<AsyncPgConnection>> {
+        &self.consumers_pool
+    }
+
+    /// Pool of the periodic jobs
+    pub fn background_pool(&self) -> &Arc<Pool<AsyncPgConnection>> {
+        &self.background_pool
+    }
+
     pub fn db_url(&self) -> &str {
         &self.db_config.url
     }
 
+    /// Pool of the read only replica, reads use the primary pool if this isn't set
+    pub fn replica_pool(&self) -> Option<&Arc<Pool<AsyncPgConnection>>> {
+        self.replica_pool.as_ref()
+    }
+
+    pub fn named_pool(&self, name: PoolName) -> Option<&Arc<Pool<AsyncPgConnection>>> {
+        match name {
+            PoolName::Api => Some(&self.pool),
+            PoolName::Consumers => Some(&self.consumers_pool),
+            PoolName::Background => Some(&self.background_pool),
+            PoolName::Replica => self.replica_pool.as_ref(),
+        }
+    }
+
+    /// Name of a pool of the config, pools built elsewhere are attributed to the API
+    pub fn pool_name(&self, pool: &Pool<AsyncPgConnection>) -> PoolName {
+        PoolName::iter()
+            .find(|name| self.named_pool(*name).is_some_and(|named_pool| std::ptr::eq(named_pool.as_ref(), pool)))
+            .unwrap_or_default()
+    }
+
     pub fn is_dev(&self) -> bool {
         self.is_dev
     }
+
+    pub fn multiplexer_enabled(&self) -> bool {
+        self.multiplexer_enabled
+    }
+
+    pub fn admin_api_key(&self) -> Option<&str> {
+        self.admin_api_key.as_deref()
+    }
+
+    pub fn tenant_key_secret(&self) -> Option<&str> {
+        self.tenant_key_secret.as_deref()
+    }
+
+    pub fn notification_webhook_url(&self) -> Option<&str> {
+        self.notifications.webhook_url.as_deref()
+    }
+
+    pub fn signing_keys(&self) -> &[SigningKey] {
+        &self.notifications.signing_keys
+    }
+
+    pub fn target_policy(&self) -> &TargetPolicy {
+        &self.target_policy
+    }
+
+    pub fn config_drift_auto_restart(&self) -> bool {
+        self.config_drift_auto_restart
+    }
+
+    pub fn sandbox_policy(&self) -> &SandboxPolicy {
+        &self.sandbox_policy
+    }
+
+    pub fn stream_policy(&self) -> &StreamPolicy {
+        &self.stream_policy
+    }
+
+    pub fn hook_allowed_commands(&self) -> &[String] {
+        &self.hook_allowed_commands
+    }
+
+    pub fn startup_ramp_up(&self) -> StartupRampUp {
+        self.startup_ramp_up
+    }
+
+    pub fn script_search_enabled(&self) -> bool {
+        self.script_search_enabled
+    }
+
+    /// Create requests, multipart or JSON, with fields the indexer type doesn't accept are
+    /// rejected rather than having the fields ignored
+    pub fn multipart_strict_mode(&self) -> bool {
+        self.multipart_strict_mode
+    }
+
+    pub fn gitops(&self) -> Option<&GitOpsConfig> {
+        self.gitops.as_ref()
+    }
+
+    pub fn memory_pressure(&self) -> Option<MemoryPressureConfig> {
+        self.memory_pressure
+    }
+
+    /// The cursor lock, the cursor store and the stream are checked before an indexer is started
+    pub fn warm_start_checks(&self) -> bool {
+        self.warm_start_checks
+    }
+
+    /// Starts are ignored unless the indexer is still in a status it can be started from, or in
+    /// the one it was seen in when the start was issued
+    pub fn strict_starts(&self) -> bool {
+        self.strict_starts
+    }
+
+    /// Indexers are moved to `Running` as soon as their process is started if not set
+    pub fn readiness_window(&self) -> Option<Duration> {
+        self.readiness_window
+    }
+
+    pub fn script_scan(&self) -> Option<&ScriptScanConfig> {
+        self.script_scan.as_ref()
+    }
+
+    pub fn tls(&self) -> Option<&TlsConfig> {
+        self.tls.as_ref()
+    }
+
+    /// The internal listener is plain HTTP unless `INTERNAL_TLS` is set, e.g. once its callers
+    /// are sidecars presenting client certificates rather than the sinks of this host
+    pub fn internal_tls(&self) -> Option<&TlsConfig> {
+        self.tls.as_ref().filter(|_| self.internal_tls)
+    }
+
+    pub fn outbound_http(&self) -> &OutboundHttpConfig {
+        &self.outbound_http
+    }
+
+    pub fn metric_labels(&self) -> MetricLabelsConfig {
+        self.metric_labels
+    }
+
+    pub fn secrets(&self) -> &SecretsConfig {
+        &self.secrets
+    }
+
+    pub fn startup_mode(&self) -> StartupMode {
+        self.startup_mode
+    }
+
+    pub fn startup_compromises(&self) -> &[StartupCompromise] {
+        &self.startup_compromises
+    }
 }
 
 /// We are using `ArcSwap` as it allow us to replace the new `Config` with
@@ -88,21 +436,80 @@ async fn init_config() -> Config {
         host: env::var("HOST").unwrap_or_else(|_| String::from("127.0.0.1")),
         port: env::var("PORT").unwrap_or_else(|_| String::from("3000")).parse::<u16>().unwrap(),
     };
+    let internal_server_config = ServerConfig {
+        host: env::var("INTERNAL_HOST").unwrap_or_else(|_| String::from("127.0.0.1")),
+        port: env::var("INTERNAL_PORT").unwrap_or_else(|_| String::from("3001")).parse::<u16>().unwrap(),
+    };
+    let internal_tls =
+        env::var("INTERNAL_TLS").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
 
     // init database config
-    let database_config = DatabaseConfig { url: env::var("DATABASE_URL").expect("DATABASE_URL must be set") };
+    let database_config = DatabaseConfig {
+        url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+        pool: init_pool_settings("DATABASE_POOL", None),
+    };
 
-    // create a new connection pool with the default config
-    let mut config = ManagerConfig::default();
-    config.custom_setup = Box::new(establish_connection);
-    let manager =
-        AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(database_config.url.to_string(), config);
-    let pool = Pool::builder(manager).build().unwrap();
+    let pool = build_pool(&database_config);
+    let (consumers_pool, background_pool) = build_subsystem_pools(&database_config.url);
+
+    // dashboard reads are sent to the replica if one is set
+    let replica_pool = env::var("DATABASE_REPLICA_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| Arc::new(build_pool(&DatabaseConfig { url, pool: init_pool_settings("DATABASE_POOL", None) })));
 
     let is_dev = env::var("DEV_ENV").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
 
     tracing::info!("DEV environment: {}", is_dev);
 
+    let multiplexer_enabled =
+        env::var("MULTIPLEXER_MODE").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
+
+    // admin routes are disabled if no key is set
+    let admin_api_key = env::var("ADMIN_API_KEY").ok().filter(|key| !key.is_empty());
+
+    // tenants can only act through the admins if no secret is set
+    let tenant_key_secret = env::var("TENANT_KEY_SECRET").ok().filter(|secret| !secret.is_empty());
+
+    let notifications = init_notifications_config();
+
+    let target_policy = init_target_policy();
+
+    // drifted indexers are only reported unless this is set
+    let config_drift_auto_restart = env::var("CONFIG_DRIFT_AUTO_RESTART")
+        .unwrap_or_else(|_| String::from("false"))
+        .parse::<bool>()
+        .unwrap_or(false);
+
+    // scripts are only indexed for search if this is set
+    let script_search_enabled =
+        env::var("SCRIPT_SEARCH_ENABLED").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
+
+    let multipart_strict_mode =
+        env::var("MULTIPART_STRICT_MODE").unwrap_or_else(|_| String::from("true")).parse::<bool>().unwrap_or(true);
+
+    let warm_start_checks =
+        env::var("WARM_START_CHECKS").unwrap_or_else(|_| String::from("true")).parse::<bool>().unwrap_or(true);
+
+    let strict_starts =
+        env::var("STRICT_STARTS").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
+
+    // 0 turns the readiness gate off
+    let readiness_window = env::var("READINESS_WINDOW_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_READINESS_WINDOW_SECONDS);
+    let readiness_window = (readiness_window > 0).then(|| Duration::from_secs(readiness_window));
+
+    // missing infrastructure is only tolerated in dev unless set otherwise
+    let startup_mode = env::var("STARTUP_MODE")
+        .ok()
+        .filter(|mode| !mode.is_empty())
+        .map(|mode| StartupMode::from_str(&mode).expect("STARTUP_MODE must be strict or permissive"))
+        .unwrap_or(if is_dev { StartupMode::Permissive } else { StartupMode::Strict });
+    tracing::info!("Startup mode: {}", startup_mode);
+    let mut startup_compromises = vec![];
+
     // if !is_dev {
     //     // init AWS config
     //     let shared_config = aws_config::from_env().load().await;
@@ -125,19 +532,49 @@ async fn init_config() -> Config {
     //     Config { server: server_config, s3_client, pool: Arc::new(pool), db_config: database_config,
     // is_dev } }
 
-    #[cfg(feature = "gcp")]
-    let object_store = create_gcs_client().await;
-
-    #[cfg(feature = "aws")]
-    let object_store = create_s3_client().await;
+    let storage_bootstrap = env::var("STORAGE_BOOTSTRAP")
+        .ok()
+        .filter(|bootstrap| !bootstrap.is_empty())
+        .map(|bootstrap| StorageBootstrap::from_str(&bootstrap).expect("STORAGE_BOOTSTRAP must be create or verify"));
+    let object_store = init_object_store(startup_mode, storage_bootstrap, &mut startup_compromises).await;
 
     Config {
         server: server_config,
+        internal_server: internal_server_config,
+        internal_tls,
         // s3_client,
         object_store,
         pool: Arc::new(pool),
         db_config: database_config,
+        consumers_pool,
+        background_pool,
+        replica_pool,
         is_dev,
+        multiplexer_enabled,
+        admin_api_key,
+        tenant_key_secret,
+        notifications,
+        target_policy,
+        config_drift_auto_restart,
+        sandbox_policy: init_sandbox_policy(),
+        stream_policy: init_stream_policy(),
+        // command hooks are disabled unless their commands are listed
+        hook_allowed_commands: get_environment_list("HOOK_ALLOWED_COMMANDS"),
+        startup_ramp_up: init_startup_ramp_up(),
+        script_search_enabled,
+        multipart_strict_mode,
+        gitops: init_gitops_config(),
+        memory_pressure: init_memory_pressure_config(),
+        warm_start_checks,
+        strict_starts,
+        readiness_window,
+        script_scan: init_script_scan_config(),
+        tls: init_tls_config(),
+        outbound_http: init_outbound_http_config(),
+        metric_labels: init_metric_labels_config(),
+        secrets: init_secrets_config(is_dev),
+        startup_mode,
+        startup_compromises,
     }
 }
 
@@ -149,6 +586,10 @@ pub async fn init_config() -> Config {
         host: env::var("HOST").unwrap_or_else(|_| String::from("127.0.0.1")),
         port: env::var("PORT").unwrap_or_else(|_| String::from("3000")).parse::<u16>().unwrap(),
     };
+    let internal_server_config = ServerConfig {
+        host: env::var("INTERNAL_HOST").unwrap_or_else(|_| String::from("127.0.0.1")),
+        port: env::var("INTERNAL_PORT").unwrap_or_else(|_| String::from("3001")).parse::<u16>().unwrap(),
+    };
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     // First, connect to the db to be able to create our test database.
@@ -162,14 +603,12 @@ pub async fn init_config() -> Config {
         .unwrap_or_else(|e| panic!("Could not create database {}, error: {}", TEST_DB_NAME, e));
 
     // init database config
-    let database_config = DatabaseConfig { url: format!("{}/{}", database_url, TEST_DB_NAME) };
+    let database_config = DatabaseConfig {
+        url: format!("{}/{}", database_url, TEST_DB_NAME),
+        pool: init_pool_settings("DATABASE_POOL", None),
+    };
 
-    // create a new connection pool with the default config
-    let mut config = ManagerConfig::default();
-    config.custom_setup = Box::new(establish_connection);
-    let manager =
-        AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(database_config.url.to_string(), config);
-    let pool = Pool::builder(manager).build().unwrap();
+    let pool = build_pool(&database_config);
 
     // Add uuid-ossp extension to the test database
     let mut conn = pool.get().await.expect("Failed to get connection from pool");
@@ -179,43 +618,419 @@ pub async fn init_config() -> Config {
     // init tables
     run_migrations(database_config.url.clone()).await.expect("Failed to run migrations");
 
-    #[cfg(feature = "gcp")]
-    let object_store = create_gcs_client().await;
+    let (consumers_pool, background_pool) = build_subsystem_pools(&database_config.url);
 
-    #[cfg(feature = "aws")]
-    let object_store = create_s3_client().await;
+    // the tests run against the storage emulator, they fail rather than use another backend
+    let object_store = init_object_store(StartupMode::Strict, None, &mut vec![]).await;
 
     Config {
         server: server_config,
+        internal_server: internal_server_config,
+        internal_tls: false,
         // object_store,
         object_store,
         pool: Arc::new(pool),
         db_config: database_config,
+        consumers_pool,
+        background_pool,
+        replica_pool: None,
         is_dev: true,
+        multiplexer_enabled: false,
+        admin_api_key: Some(TEST_ADMIN_API_KEY.into()),
+        tenant_key_secret: Some(TEST_TENANT_KEY_SECRET.into()),
+        notifications: NotificationsConfig {
+            webhook_url: None,
+            signing_keys: vec![SigningKey {
+                id: TEST_SIGNING_KEY_ID.into(),
+                secret: TEST_SIGNING_KEY_SECRET.into(),
+                valid_from: None,
+                valid_until: None,
+            }],
+        },
+        // the tests serve the targets on the loopback
+        target_policy: TargetPolicy {
+            allowed_cidrs: vec![Cidr::from_str("127.0.0.0/8").unwrap(), Cidr::from_str("::1").unwrap()],
+            ..init_target_policy()
+        },
+        config_drift_auto_restart: false,
+        sandbox_policy: init_sandbox_policy(),
+        stream_policy: init_stream_policy(),
+        // command hooks are disabled unless their commands are listed
+        hook_allowed_commands: get_environment_list("HOOK_ALLOWED_COMMANDS"),
+        startup_ramp_up: init_startup_ramp_up(),
+        script_search_enabled: false,
+        multipart_strict_mode: true,
+        gitops: None,
+        memory_pressure: None,
+        // the tests don't run a cursor store nor a stream
+        warm_start_checks: false,
+        strict_starts: false,
+        // the tests check the status right after the starts
+        readiness_window: None,
+        // the tests don't reach the OSV API
+        script_scan: None,
+        tls: None,
+        outbound_http: init_outbound_http_config(),
+        metric_labels: MetricLabelsConfig { label: None, max_series: DEFAULT_METRICS_MAX_SERIES },
+        // the tests resolve references from their own environment only
+        secrets: SecretsConfig {
+            local_backends: true,
+            file_root: DEFAULT_SECRETS_FILE_ROOT.to_string(),
+            vault: None,
+            aws_secrets_manager: None,
+        },
+        startup_mode: StartupMode::Strict,
+        startup_compromises: vec![],
     }
 }
 
+#[cfg(not(test))]
+fn init_gitops_config() -> Option<GitOpsConfig> {
+    let mut repository_url = env::var("GITOPS_REPOSITORY_URL").ok().filter(|url| !url.is_empty())?;
+    // files are resolved relative to the repository
+    if !repository_url.ends_with('/') {
+        repository_url.push('/');
+    }
+    Some(GitOpsConfig {
+        repository_url,
+        manifest_path: env::var("GITOPS_MANIFEST_PATH").unwrap_or_else(|_| GITOPS_DEFAULT_MANIFEST_PATH.to_string()),
+        poll_interval: Duration::from_secs(
+            env::var("GITOPS_POLL_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(GITOPS_DEFAULT_POLL_INTERVAL_SECONDS),
+        ),
+        webhook_secret: env::var("GITOPS_WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty()),
+        adopt_unmarked: env::var("GITOPS_ADOPT_UNMARKED")
+            .unwrap_or_else(|_| String::from("false"))
+            .parse::<bool>()
+            .unwrap_or(false),
+    })
+}
+
+#[cfg(not(test))]
+fn init_script_scan_config() -> Option<ScriptScanConfig> {
+    let enabled =
+        env::var("SCRIPT_SCAN_ENABLED").unwrap_or_else(|_| String::from("false")).parse::<bool>().unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+    let block_severity =
+        env::var("SCRIPT_SCAN_BLOCK_SEVERITY").ok().filter(|severity| !severity.is_empty()).map(|severity| {
+            AdvisorySeverity::from_str(&severity)
+                .expect("SCRIPT_SCAN_BLOCK_SEVERITY must be one of low, moderate, high or critical")
+        });
+    Some(ScriptScanConfig {
+        osv_api_url: env::var("OSV_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| OSV_DEFAULT_API_URL.to_string()),
+        block_severity,
+    })
+}
+
+#[cfg(not(test))]
+fn init_tls_config() -> Option<TlsConfig> {
+    let cert_path = env::var("TLS_CERT_PATH").ok().filter(|path| !path.is_empty())?;
+    let key_path = env::var("TLS_KEY_PATH").ok().filter(|path| !path.is_empty()).expect("TLS_KEY_PATH must be set");
+    Some(TlsConfig {
+        cert_path,
+        key_path,
+        client_ca_path: env::var("TLS_CLIENT_CA_PATH").ok().filter(|path| !path.is_empty()),
+    })
+}
+
+/// `OUTBOUND_HTTP_DESTINATION_TIMEOUTS` lists timeouts by host, e.g.
+/// `hooks.example.com=5,api.osv.dev=20`
+fn init_outbound_http_config() -> OutboundHttpConfig {
+    let get_seconds = |name: &str| {
+        env::var(name).ok().filter(|seconds| !seconds.is_empty()).map(|seconds| {
+            Duration::from_secs(
+                seconds.parse::<u64>().unwrap_or_else(|_| panic!("{} must be a number of seconds", name)),
+            )
+        })
+    };
+    let destination_timeouts = get_environment_list("OUTBOUND_HTTP_DESTINATION_TIMEOUTS")
+        .into_iter()
+        .map(|entry| {
+            let (host, seconds) = entry
+                .split_once('=')
+                .and_then(|(host, seconds)| Some((host.trim().to_lowercase(), seconds.trim().parse::<u64>().ok()?)))
+                .unwrap_or_else(|| panic!("Invalid entry {} in OUTBOUND_HTTP_DESTINATION_TIMEOUTS", entry));
+            (host, Duration::from_secs(seconds))
+        })
+        .collect();
+    OutboundHttpConfig {
+        proxy_url: env::var("OUTBOUND_HTTP_PROXY").ok().filter(|url| !url.is_empty()),
+        no_proxy: env::var("OUTBOUND_HTTP_NO_PROXY").ok().filter(|hosts| !hosts.is_empty()),
+        ca_cert_path: env::var("OUTBOUND_HTTP_CA_CERT_PATH").ok().filter(|path| !path.is_empty()),
+        connect_timeout: get_seconds("OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS")
+            .unwrap_or(Duration::from_secs(DEFAULT_OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS)),
+        timeout: get_seconds("OUTBOUND_HTTP_TIMEOUT_SECONDS")
+            .unwrap_or(Duration::from_secs(DEFAULT_OUTBOUND_HTTP_TIMEOUT_SECONDS)),
+        destination_timeouts,
+        pool_max_idle_per_host: env::var("OUTBOUND_HTTP_POOL_MAX_IDLE_PER_HOST")
+            .ok()
+            .and_then(|size| size.parse().ok()),
+        pool_idle_timeout: get_seconds("OUTBOUND_HTTP_POOL_IDLE_TIMEOUT_SECONDS"),
+    }
+}
+
+/// Vault is enabled once `VAULT_ADDR` is set and Secrets Manager once
+/// `AWS_SECRETS_MANAGER_REGION` is set, `SECRETS_LOCAL_BACKENDS` enables `env://` and `file://`
+/// out of dev
+#[cfg(not(test))]
+fn init_secrets_config(is_dev: bool) -> SecretsConfig {
+    let get = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    let vault = get("VAULT_ADDR").map(|address| VaultConfig {
+        address,
+        token: get("VAULT_TOKEN").expect("VAULT_TOKEN must be set"),
+        mount: get("VAULT_KV_MOUNT").unwrap_or_else(|| VAULT_DEFAULT_KV_MOUNT.to_string()),
+        namespace: get("VAULT_NAMESPACE"),
+    });
+    let aws_secrets_manager = get("AWS_SECRETS_MANAGER_REGION").map(|region| AwsSecretsManagerConfig {
+        region,
+        access_key_id: get("AWS_ACCESS_KEY_ID").expect("AWS_ACCESS_KEY_ID must be set"),
+        secret_access_key: get("AWS_SECRET_ACCESS_KEY").expect("AWS_SECRET_ACCESS_KEY must be set"),
+        session_token: get("AWS_SESSION_TOKEN"),
+        endpoint: get("AWS_SECRETS_MANAGER_ENDPOINT"),
+    });
+    let local_backends = get("SECRETS_LOCAL_BACKENDS").map_or(is_dev, |enabled| enabled.parse().unwrap_or(false));
+    let file_root = get("SECRETS_FILE_ROOT").unwrap_or_else(|| DEFAULT_SECRETS_FILE_ROOT.to_string());
+    SecretsConfig { local_backends, file_root, vault, aws_secrets_manager }
+}
+
+/// `METRICS_LABEL` splits the metrics by `indexer`, `tenant` or `type`, each metric keeps at most
+/// `METRICS_MAX_SERIES` series
+#[cfg(not(test))]
+fn init_metric_labels_config() -> MetricLabelsConfig {
+    let label = env::var("METRICS_LABEL")
+        .ok()
+        .filter(|label| !label.is_empty())
+        .map(|label| MetricLabel::from_str(&label).expect("METRICS_LABEL must be one of indexer, tenant or type"));
+    let max_series = env::var("METRICS_MAX_SERIES")
+        .ok()
+        .filter(|max_series| !max_series.is_empty())
+        .map(|max_series| max_series.parse().expect("METRICS_MAX_SERIES must be a number"))
+        .unwrap_or(DEFAULT_METRICS_MAX_SERIES);
+    MetricLabelsConfig { label, max_series }
+}
+
+/// The guard is off unless `MEMORY_PRESSURE_PAUSE_RATIO` is set, e.g. to `0.9`
+#[cfg(not(test))]
+fn init_memory_pressure_config() -> Option<MemoryPressureConfig> {
+    let pause_ratio = env::var("MEMORY_PRESSURE_PAUSE_RATIO").ok()?.parse::<f64>().ok()?;
+    let resume_ratio = env::var("MEMORY_PRESSURE_RESUME_RATIO")
+        .ok()
+        .and_then(|ratio| ratio.parse::<f64>().ok())
+        .unwrap_or(pause_ratio - DEFAULT_MEMORY_PRESSURE_HYSTERESIS);
+    assert!(resume_ratio < pause_ratio, "MEMORY_PRESSURE_RESUME_RATIO must be lower than MEMORY_PRESSURE_PAUSE_RATIO");
+    Some(MemoryPressureConfig { pause_ratio, resume_ratio })
+}
+
+/// `NOTIFICATION_SIGNING_KEYS` is a JSON array of keys, e.g.
+/// `[{"id": "2024-01", "secret": "...", "valid_from": "2024-01-01T00:00:00Z", "valid_until":
+/// null}]`
+#[cfg(not(test))]
+fn init_notifications_config() -> NotificationsConfig {
+    let signing_keys = match env::var("NOTIFICATION_SIGNING_KEYS").ok().filter(|keys| !keys.is_empty()) {
+        Some(keys) => {
+            serde_json::from_str(keys.as_str()).expect("NOTIFICATION_SIGNING_KEYS must be a JSON array of keys")
+        }
+        None => vec![],
+    };
+    let webhook_url = env::var("NOTIFICATION_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+    NotificationsConfig { webhook_url, signing_keys }
+}
+
+/// Reads a comma separated list from the environment
+fn get_environment_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .map(|value| value.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn init_target_policy() -> TargetPolicy {
+    let parse_cidrs = |name: &str| -> Vec<Cidr> {
+        get_environment_list(name)
+            .iter()
+            .map(|cidr| Cidr::from_str(cidr).unwrap_or_else(|e| panic!("{} is invalid: {}", name, e)))
+            .collect()
+    };
+    TargetPolicy {
+        allowed_domains: get_environment_list("TARGET_URL_ALLOWED_DOMAINS"),
+        denied_domains: get_environment_list("TARGET_URL_DENIED_DOMAINS"),
+        allowed_cidrs: parse_cidrs("TARGET_URL_ALLOWED_CIDRS"),
+        denied_cidrs: parse_cidrs("TARGET_URL_DENIED_CIDRS"),
+        strict: env::var("TARGET_URL_STRICT_MODE")
+            .unwrap_or_else(|_| String::from("false"))
+            .parse::<bool>()
+            .unwrap_or(false),
+    }
+}
+
+/// Settings of a pool are read from `<prefix>_MAX_SIZE`, `<prefix>_WAIT_TIMEOUT_MS` etc.
+fn init_pool_settings(prefix: &str, default_max_size: Option<usize>) -> PoolSettings {
+    let get_duration = |name: &str| {
+        env::var(name).ok().filter(|millis| !millis.is_empty()).map(|millis| {
+            Duration::from_millis(millis.parse().unwrap_or_else(|e| panic!("{} is invalid: {}", name, e)))
+        })
+    };
+    let max_size_name = format!("{}_MAX_SIZE", prefix);
+    PoolSettings {
+        max_size: env::var(&max_size_name)
+            .ok()
+            .filter(|max_size| !max_size.is_empty())
+            .map(|max_size| max_size.parse().unwrap_or_else(|e| panic!("{} is invalid: {}", max_size_name, e)))
+            .or(default_max_size),
+        // requests fail with `DatabaseBusy` instead of waiting forever for a connection
+        wait_timeout: get_duration(&format!("{}_WAIT_TIMEOUT_MS", prefix)).or(Some(Duration::from_secs(5))),
+        create_timeout: get_duration(&format!("{}_CREATE_TIMEOUT_MS", prefix)),
+        recycle_timeout: get_duration(&format!("{}_RECYCLE_TIMEOUT_MS", prefix)),
+    }
+}
+
+/// Pools of the consumers and background jobs, they are kept small so that they can't take the
+/// connections the API needs
+fn build_subsystem_pools(url: &str) -> (Arc<Pool<AsyncPgConnection>>, Arc<Pool<AsyncPgConnection>>) {
+    let consumers_config = DatabaseConfig {
+        url: url.to_string(),
+        pool: init_pool_settings("DATABASE_CONSUMERS_POOL", Some(DEFAULT_CONSUMERS_POOL_MAX_SIZE)),
+    };
+    let background_config = DatabaseConfig {
+        url: url.to_string(),
+        pool: init_pool_settings("DATABASE_BACKGROUND_POOL", Some(DEFAULT_BACKGROUND_POOL_MAX_SIZE)),
+    };
+    (Arc::new(build_pool(&consumers_config)), Arc::new(build_pool(&background_config)))
+}
+
+fn init_startup_ramp_up() -> StartupRampUp {
+    let get_number = |name: &str, default: u64| {
+        env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().unwrap_or_else(|e| panic!("{} is invalid: {}", name, e)))
+            .unwrap_or(default)
+    };
+    StartupRampUp {
+        // a batch of 0 would never start anything
+        batch_size: get_number("STARTUP_BATCH_SIZE", 10).max(1) as usize,
+        interval: Duration::from_secs(get_number("STARTUP_BATCH_INTERVAL_SECONDS", 10)),
+    }
+}
+
+fn build_pool(database_config: &DatabaseConfig) -> Pool<AsyncPgConnection> {
+    let mut config = ManagerConfig::default();
+    config.custom_setup = Box::new(establish_connection);
+    let manager =
+        AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(database_config.url.to_string(), config);
+
+    let settings = &database_config.pool;
+    let mut builder = Pool::builder(manager)
+        .wait_timeout(settings.wait_timeout)
+        .create_timeout(settings.create_timeout)
+        .recycle_timeout(settings.recycle_timeout)
+        .runtime(Runtime::Tokio1);
+    if let Some(max_size) = settings.max_size {
+        builder = builder.max_size(max_size);
+    }
+    builder.build().unwrap()
+}
+
+/// Scripts can only be granted the permissions listed here
+/// Only the streams of Apibara are allowed unless `ALLOWED_STREAM_DOMAINS` is set
+fn init_stream_policy() -> StreamPolicy {
+    let allowed_domains = get_environment_list("ALLOWED_STREAM_DOMAINS");
+    StreamPolicy {
+        allowed_domains: match allowed_domains.is_empty() {
+            true => DEFAULT_ALLOWED_STREAM_DOMAINS.iter().map(|domain| domain.to_string()).collect(),
+            false => allowed_domains,
+        },
+    }
+}
+
+fn init_sandbox_policy() -> SandboxPolicy {
+    SandboxPolicy {
+        allowed_net_hosts: get_environment_list("SCRIPT_ALLOWED_NET_HOSTS"),
+        allowed_read_paths: get_environment_list("SCRIPT_ALLOWED_READ_PATHS"),
+        allowed_env_vars: get_environment_list("SCRIPT_ALLOWED_ENV_VARS"),
+    }
+}
+
+/// Object store of the scripts and uploads. Without its bucket the service refuses to start in the
+/// strict mode and falls back to a local directory, or to memory, in the permissive one.
+/// A bucket which fails its bootstrap is handled like an object store which can't be created
+async fn init_object_store(
+    mode: StartupMode,
+    bootstrap: Option<StorageBootstrap>,
+    compromises: &mut Vec<StartupCompromise>,
+) -> Arc<dyn ObjectStore> {
+    #[cfg(feature = "gcp")]
+    let object_store = create_gcs_client().await;
+    #[cfg(all(feature = "gcp", not(feature = "aws")))]
+    if let Some(bootstrap) = bootstrap {
+        tracing::warn!("STORAGE_BOOTSTRAP={} is only supported with S3, the GCS bucket is used as is", bootstrap);
+    }
+
+    #[cfg(feature = "aws")]
+    let object_store = match bootstrap {
+        Some(bootstrap) => bootstrap_s3_bucket(bootstrap).await,
+        None => Ok(()),
+    }
+    .map_err(|e| format!("Failed to bootstrap the S3 bucket: {}", e));
+    #[cfg(feature = "aws")]
+    let object_store = match object_store {
+        Ok(()) => create_s3_client().await,
+        Err(e) => Err(e),
+    };
+
+    let reason = match object_store {
+        Ok(object_store) => return object_store,
+        Err(e) if mode == StartupMode::Strict => panic!("Failed to create the object store: {}", e),
+        Err(e) => e,
+    };
+
+    let path = env::var("LOCAL_STORAGE_PATH").unwrap_or_else(|_| DEFAULT_LOCAL_STORAGE_PATH.to_string());
+    let local = std::fs::create_dir_all(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|_| LocalFileSystem::new_with_prefix(&path).map_err(|e| e.to_string()));
+    let (object_store, fallback): (Arc<dyn ObjectStore>, String) = match local {
+        Ok(local) => (Arc::new(local), format!("local directory {}", path)),
+        Err(e) => {
+            tracing::error!("Failed to use {} as the object store: {}", path, e);
+            (Arc::new(InMemory::new()), "memory".to_string())
+        }
+    };
+    tracing::warn!(
+        "PERMISSIVE STARTUP: the object store is not available ({}), scripts and uploads are stored in {} instead",
+        reason,
+        fallback
+    );
+    compromises.push(StartupCompromise { subsystem: "object_store".into(), fallback, reason });
+    object_store
+}
+
 #[cfg(feature = "gcp")]
-async fn create_gcs_client() -> Arc<dyn ObjectStore> {
-    let gcs_bucket_name = get_environment_variable("GCS_BUCKET_NAME");
-    let gcs_service_account = get_environment_variable("GCS_SERVICE_ACCOUNT");
+async fn create_gcs_client() -> Result<Arc<dyn ObjectStore>, String> {
+    let gcs_bucket_name = try_get_environment_variable("GCS_BUCKET_NAME")?;
+    let gcs_service_account = try_get_environment_variable("GCS_SERVICE_ACCOUNT")?;
 
     let gcs = GoogleCloudStorageBuilder::new()
         .with_bucket_name(gcs_bucket_name)
         .with_service_account_path(gcs_service_account)
         .build()
-        .expect("Failed to create gcs object store");
+        .map_err(|e| format!("Failed to create gcs object store: {}", e))?;
 
-    Arc::new(gcs)
+    Ok(Arc::new(gcs))
 }
 
 #[cfg(feature = "aws")]
-async fn create_s3_client() -> Arc<dyn ObjectStore> {
-    let aws_region = get_environment_variable("AWS_REGION");
-    let aws_access_key_id = get_environment_variable("AWS_ACCESS_KEY_ID");
-    let aws_secret_access_key = get_environment_variable("AWS_SECRET_ACCESS_KEY");
-    let aws_bucket_name = get_environment_variable("INDEXER_SERVICE_BUCKET");
-    let localstack_endpoint = get_environment_variable("LOCALSTACK_ENDPOINT");
+async fn create_s3_client() -> Result<Arc<dyn ObjectStore>, String> {
+    let aws_region = try_get_environment_variable("AWS_REGION")?;
+    let aws_access_key_id = try_get_environment_variable("AWS_ACCESS_KEY_ID")?;
+    let aws_secret_access_key = try_get_environment_variable("AWS_SECRET_ACCESS_KEY")?;
+    let aws_bucket_name = try_get_environment_variable("INDEXER_SERVICE_BUCKET")?;
+    let localstack_endpoint = try_get_environment_variable("LOCALSTACK_ENDPOINT")?;
 
     let s3 = AmazonS3Builder::new()
         .with_region(aws_region)
@@ -225,9 +1040,9 @@ async fn create_s3_client() -> Arc<dyn ObjectStore> {
         .with_endpoint(localstack_endpoint)
         .with_allow_http(true)
         .build()
-        .expect("Failed to create S3 object store");
+        .map_err(|e| format!("Failed to create S3 object store: {}", e))?;
 
-    Arc::new(s3)
+    Ok(Arc::new(s3))
 }
 
 pub async fn config() -> Guard<Arc<Config>> {
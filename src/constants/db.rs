@@ -0,0 +1,6 @@
+/// How long reads skip the replica after failing to reach it
+pub const REPLICA_RETRY_INTERVAL_SECONDS: i64 = 30;
+/// Default size of the pool of the tasks reacting to the sinks
+pub const DEFAULT_CONSUMERS_POOL_MAX_SIZE: usize = 4;
+/// Default size of the pool of the periodic jobs
+pub const DEFAULT_BACKGROUND_POOL_MAX_SIZE: usize = 4;
@@ -4,3 +4,120 @@ pub const WORKING_INDEXER_THRESHOLD_TIME_MINUTES: i64 = 5;
 pub const START_INDEXER_DELAY_SECONDS: u16 = 120;
 #[cfg(test)]
 pub const START_INDEXER_DELAY_SECONDS: u16 = 0;
+pub const CONFIG_DRIFT_CHECK_INTERVAL_SECONDS: u64 = 300;
+/// Backfill requests launch at most this many indexers, the remaining gaps need another request
+pub const MAX_BACKFILL_INDEXERS: usize = 10;
+/// Blocks before the head of the stream a preview runs over when no range is given
+pub const PREVIEW_BLOCK_RANGE: u64 = 100;
+pub const PREVIEW_TIMEOUT_SECONDS: u64 = 30;
+/// Previews running at once, each runs a sink of its own
+pub const MAX_CONCURRENT_PREVIEWS: usize = 4;
+/// Bytes of the response of the target kept by a simulated delivery
+pub const SIMULATED_DELIVERY_RESPONSE_BODY_LIMIT: usize = 4096;
+/// Attempts at reading a script which is missing or stale, the store may lag behind a write
+pub const SCRIPT_FETCH_MAX_ATTEMPTS: u32 = 5;
+#[cfg(not(test))]
+pub const SCRIPT_FETCH_RETRY_DELAY_MILLIS: u64 = 500;
+#[cfg(test)]
+pub const SCRIPT_FETCH_RETRY_DELAY_MILLIS: u64 = 0;
+/// Folder of the temp directory scripts are cached in by checksum
+pub const SCRIPT_CACHE_FOLDER: &str = "indexer-service-scripts";
+pub const SCRIPT_CACHE_CLEANUP_INTERVAL_SECONDS: u64 = 3600;
+/// Domains of the streams hosted by Apibara
+pub const DEFAULT_ALLOWED_STREAM_DOMAINS: [&str; 2] = ["*.a5a.ch", "*.apibara.com"];
+pub const DEFAULT_HOOK_TIMEOUT_SECONDS: u64 = 30;
+/// Pre-start hooks run under the lock of the indexer, a longer hook would hold its stop and
+/// updates back
+pub const MAX_HOOK_TIMEOUT_SECONDS: u64 = 120;
+pub const MAX_HOOK_COMMAND_ARGS: usize = 16;
+pub const MAX_HOOK_COMMAND_ARG_LENGTH: usize = 256;
+/// Failures of a primary within the window before its standby takes over, the primary is
+/// restarted in place until then
+pub const FAILOVER_FAILURE_THRESHOLD: usize = 3;
+pub const FAILOVER_WINDOW_SECONDS: u64 = 600;
+/// Pause between the restarts of a fleet-wide reconfiguration
+#[cfg(not(test))]
+pub const ROLLING_RESTART_INTERVAL_SECONDS: u64 = 10;
+#[cfg(test)]
+pub const ROLLING_RESTART_INTERVAL_SECONDS: u64 = 0;
+/// Lines of stdout and stderr kept in the snapshot of a sink once it exits
+pub const PROCESS_OUTPUT_TAIL_LINES: usize = 100;
+/// Time given to the output of an exited sink to be drained before its snapshot is taken
+pub const PROCESS_OUTPUT_DRAIN_TIMEOUT_MILLIS: u64 = 1000;
+/// Parsed lines of stdout kept per indexer for the logs endpoint
+pub const SINK_LOG_RECORDS_PER_INDEXER: usize = 1000;
+/// Records of the stdout of console sinks kept per indexer
+pub const CONSOLE_OUTPUT_RECORDS_PER_INDEXER: usize = 500;
+/// Window the starts of the indexers of a tenant are counted over
+pub const START_RATE_WINDOW_SECONDS: u64 = 60;
+/// Interval at which new and changed scripts are indexed for search
+pub const SCRIPT_INDEX_INTERVAL_SECONDS: u64 = 300;
+pub const SCRIPT_SEARCH_MAX_RESULTS: i64 = 50;
+/// Characters of the script kept on each side of the match in search snippets
+pub const SCRIPT_SEARCH_SNIPPET_CONTEXT: usize = 60;
+/// Webhook indexers are stopped once their target only answered 404 or 410 for this long
+pub const TARGET_GONE_STOP_AFTER_SECONDS: u64 = 600;
+/// Longest a stop waits for a sink to flush its batch, the indexer is locked meanwhile
+pub const MAX_STOP_FLUSH_TIMEOUT_SECONDS: u64 = 300;
+pub const STOP_FLUSH_POLL_INTERVAL_MILLIS: u64 = 200;
+/// Responses needed on top of the duration so that a couple of failed deliveries aren't enough
+pub const TARGET_GONE_MIN_RESPONSES: usize = 5;
+/// Interval at which due scheduled actions are picked, a random part of it is added each time
+pub const SCHEDULED_ACTIONS_POLL_INTERVAL_MILLIS: u64 = 5000;
+pub const SCHEDULED_ACTIONS_BATCH_SIZE: i64 = 10;
+/// Actions still running after this long are assumed lost, e.g. on a crash, and run again
+pub const SCHEDULED_ACTIONS_LEASE_SECONDS: i64 = 300;
+pub const SCHEDULED_ACTIONS_MAX_ATTEMPTS: i32 = 5;
+/// Delay before the first retry of a failed action, doubled on every attempt
+pub const SCHEDULED_ACTIONS_RETRY_DELAY_SECONDS: i64 = 30;
+/// Runs returned by the schedule preview of an indexer
+pub const SCHEDULE_PREVIEW_DEFAULT_COUNT: usize = 5;
+pub const SCHEDULE_PREVIEW_MAX_COUNT: usize = 100;
+/// Scripts fetched from a url larger than this are rejected
+pub const SCRIPT_URL_MAX_BYTES: usize = 5 * 1024 * 1024;
+pub const SCRIPT_URL_FETCH_TIMEOUT_SECONDS: u64 = 30;
+pub const GITOPS_DEFAULT_MANIFEST_PATH: &str = "indexers.json";
+pub const GITOPS_DEFAULT_POLL_INTERVAL_SECONDS: u64 = 300;
+/// Time the cursor store and the stream have to accept a connection before an indexer starts
+pub const WARM_START_CHECK_TIMEOUT_SECONDS: u64 = 5;
+/// Interval at which the usage of the running indexers is metered for billing
+pub const USAGE_METERING_INTERVAL_SECONDS: u64 = 300;
+/// Days of metered usage the log volume of the estimates is based on
+pub const ESTIMATE_SAMPLE_DAYS: i64 = 7;
+/// Blocks of the stream the filter of an estimate is run on to measure its event density
+pub const ESTIMATE_SAMPLE_BLOCKS: u64 = 1000;
+pub const ESTIMATE_SAMPLE_TIMEOUT_SECONDS: u64 = 30;
+/// Lifecycle webhooks sent per indexer and hour, unless the indexer overrides it
+pub const DEFAULT_MAX_ALERTS_PER_HOUR: u32 = 3;
+/// Window the same lifecycle webhook of an indexer isn't sent again within, unless the indexer
+/// overrides it
+pub const DEFAULT_DUPLICATE_ALERT_WINDOW_SECONDS: u64 = 1800;
+/// Interval at which the alert rules are evaluated, it's also the sampling interval of the
+/// block rates
+pub const ALERT_EVALUATION_INTERVAL_SECONDS: u64 = 30;
+/// Indexers loaded at once by the inventory exports, which stream them page by page
+pub const INVENTORY_EXPORT_PAGE_SIZE: i64 = 500;
+/// Time the status server of a running indexer has to report its block in inventory exports
+pub const INVENTORY_EXPORT_STATUS_TIMEOUT_MILLIS: u64 = 500;
+/// Interval at which indexers stuck in `Created` are looked for
+pub const STALE_CREATED_SWEEP_INTERVAL_SECONDS: u64 = 60;
+/// Time an indexer can stay in `Created`, unless its tenant sets another one
+pub const DEFAULT_STALE_CREATED_AFTER_SECONDS: u32 = 900;
+/// Below it the sweep could replay the start of an indexer still being created
+pub const MIN_STALE_CREATED_AFTER_SECONDS: u32 = 60;
+/// Starts replayed for an indexer before it's abandoned
+pub const MAX_STALE_CREATED_REPLAYS: u32 = 3;
+pub const OSV_DEFAULT_API_URL: &str = "https://api.osv.dev";
+pub const OSV_QUERY_TIMEOUT_SECONDS: u64 = 10;
+/// Scans of a script are reused for this long, advisories are published after the upload
+pub const SCRIPT_SCAN_MAX_AGE_SECONDS: i64 = 24 * 3600;
+/// Events returned as annotations to Grafana for a range, the most recent ones are kept
+pub const MAX_GRAFANA_ANNOTATIONS: i64 = 1000;
+/// Series a labeled metric is split into at most, the smallest label values are aggregated above it
+pub const DEFAULT_METRICS_MAX_SERIES: usize = 50;
+/// Indexers stay `Starting` until their sink processed a block or survived this long
+pub const DEFAULT_READINESS_WINDOW_SECONDS: u64 = 15;
+pub const READINESS_POLL_INTERVAL_MILLIS: u64 = 1000;
+/// Lifetime of a start token when the request doesn't set one
+pub const DEFAULT_START_TOKEN_TTL_SECONDS: i64 = 900;
+pub const MAX_START_TOKEN_TTL_SECONDS: i64 = 86400;
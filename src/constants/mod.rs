@@ -1,2 +1,4 @@
+pub mod db;
 pub mod indexers;
+pub mod runtime;
 pub mod s3;
@@ -0,0 +1,37 @@
+pub const RUNTIME_MONITOR_INTERVAL_SECONDS: u64 = 60;
+/// Ratio of the open files limit above which we start warning, each sink consumes FDs
+pub const OPEN_FDS_WARNING_RATIO: f64 = 0.8;
+/// Interval at which zombies left by the sinks are reaped
+pub const REAPER_INTERVAL_SECONDS: u64 = 30;
+/// Delay before restarting a background task which panicked, doubled on every restart
+#[cfg(not(test))]
+pub const SUPERVISOR_RESTART_DELAY_MILLIS: u64 = 1000;
+#[cfg(test)]
+pub const SUPERVISOR_RESTART_DELAY_MILLIS: u64 = 0;
+pub const SUPERVISOR_MAX_RESTART_DELAY_MILLIS: u64 = 60_000;
+/// A task panicking this many times within the window is crash looping
+pub const CRASH_LOOP_MAX_PANICS: usize = 5;
+pub const CRASH_LOOP_WINDOW_SECONDS: u64 = 300;
+/// Interval at which the service checks it can still start indexers
+pub const SELF_HEALTH_CHECK_INTERVAL_SECONDS: u64 = 30;
+/// Free space needed in the temporary directory, the scripts of the sinks are written there
+pub const MIN_FREE_DISK_BYTES: u64 = 512 * 1024 * 1024;
+pub const OBJECT_STORE_CHECK_TIMEOUT_SECONDS: u64 = 10;
+/// Interval at which the memory of the host is checked when the memory pressure guard is on, at
+/// most one indexer is paused or resumed per check
+pub const MEMORY_PRESSURE_CHECK_INTERVAL_SECONDS: u64 = 15;
+/// Gap between the ratios pausing and resuming indexers when only the first one is set
+pub const DEFAULT_MEMORY_PRESSURE_HYSTERESIS: f64 = 0.1;
+/// Audit events buffered for the subscribers of the live tail
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+/// Outbound HTTP calls give up after these unless configured otherwise
+pub const DEFAULT_OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+pub const DEFAULT_OUTBOUND_HTTP_TIMEOUT_SECONDS: u64 = 30;
+/// Mount of the KV v2 engine of Vault, the default one of a dev server
+pub const VAULT_DEFAULT_KV_MOUNT: &str = "secret";
+/// Scope of the secret references of the indexers of no tenant or project
+pub const UNSCOPED_SECRETS_PREFIX: &str = "shared";
+/// `env://` references only read the variables with this prefix, never the ones of the service
+pub const SECRETS_ENV_PREFIX: &str = "INDEXER_SECRET_";
+/// `file://` references are read under this directory unless configured otherwise
+pub const DEFAULT_SECRETS_FILE_ROOT: &str = "/run/secrets/indexers";
@@ -1 +1,13 @@
 pub const INDEXER_SERVICE_SCRIPTS_FOLDER: &str = "apibara-scripts";
+pub const INDEXER_SERVICE_UPLOADS_FOLDER: &str = "apibara-uploads";
+/// Upload sessions, their staged parts and their assembled upload are deleted once expired
+pub const UPLOAD_SESSION_TTL_SECONDS: i64 = 86400;
+pub const UPLOAD_SESSION_CLEANUP_INTERVAL_SECONDS: u64 = 3600;
+/// Scripts downloaded at once when checking that the store is in sync
+pub const SCRIPT_SYNC_CONCURRENCY: usize = 16;
+pub const INDEXER_SERVICE_DIAGNOSTICS_FOLDER: &str = "apibara-diagnostics";
+/// Directory the object store falls back to in the permissive startup mode unless
+/// `LOCAL_STORAGE_PATH` is set
+pub const DEFAULT_LOCAL_STORAGE_PATH: &str = "local/storage";
+/// Tags added to the bucket by `STORAGE_BOOTSTRAP=create`, along with the ones it has
+pub const BOOTSTRAP_BUCKET_TAGS: &[(&str, &str)] = &[("service", "indexer-service"), ("managed-by", "indexer-service")];
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+/// Permissions of the caller of a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, Serialize, Deserialize, Display)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActorScope {
+    /// Granted by the `x-admin-api-key` header
+    Admin,
+}
+
+/// Who the service works for. It's built from the headers of API requests and passed down to
+/// the service functions, which record it in the audit logs. Work the service starts on its own
+/// (boot, reaper, schedules) runs as the system. The default context is an anonymous caller.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActorContext {
+    /// Tenant the caller acts for, as declared with `x-tenant-id` and proven with `x-tenant-key`
+    pub tenant_id: Option<String>,
+    /// Who is acting, as declared with `x-admin-actor`. Only kept for admins.
+    pub actor_id: Option<String>,
+    pub scopes: Vec<ActorScope>,
+    /// Correlation id of the request, see `x-correlation-id`
+    pub request_id: Option<String>,
+    /// Set for the work the service does on its own, never for API requests
+    #[serde(default)]
+    pub system: bool,
+}
+
+impl ActorContext {
+    pub fn system() -> Self {
+        Self { system: true, ..Self::default() }
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.system
+    }
+
+    /// Context of the work the service then does on its own, e.g. handling the exit of a process
+    /// started for this one. The correlation id is kept to trace it back.
+    pub fn as_system(&self) -> Self {
+        Self { request_id: self.request_id.clone(), ..Self::system() }
+    }
+
+    pub fn has_scope(&self, scope: ActorScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Whether the caller may act on the resources of the tenant: admins and the service on its
+    /// own act for any tenant, the other callers for their own only
+    pub fn can_act_for_tenant(&self, tenant_id: &str) -> bool {
+        self.has_scope(ActorScope::Admin) || self.is_system() || self.tenant_id.as_deref() == Some(tenant_id)
+    }
+
+    /// Whether the caller may act on a resource of the tenant, resources of no tenant are left to
+    /// the admins and the service
+    pub fn can_act_for_owner(&self, tenant_id: Option<&str>) -> bool {
+        match tenant_id {
+            Some(tenant_id) => self.can_act_for_tenant(tenant_id),
+            None => self.has_scope(ActorScope::Admin) || self.is_system(),
+        }
+    }
+
+    /// Name of the actor in the reasons of the audit logs
+    pub fn actor_name(&self) -> &str {
+        match (&self.actor_id, self.has_scope(ActorScope::Admin)) {
+            (Some(actor_id), _) => actor_id,
+            (None, true) => "admin",
+            (None, false) => "system",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_act_for_tenant() {
+        let caller =
+            ActorContext { tenant_id: Some("acme".into()), request_id: Some("1".into()), ..Default::default() };
+        assert!(caller.can_act_for_tenant("acme"));
+        assert!(!caller.can_act_for_tenant("other"));
+        let anonymous = ActorContext { request_id: Some("2".into()), ..Default::default() };
+        assert!(!anonymous.can_act_for_tenant("acme"));
+        let admin = ActorContext { scopes: vec![ActorScope::Admin], ..anonymous };
+        assert!(admin.can_act_for_tenant("acme"));
+        assert!(ActorContext::system().can_act_for_tenant("acme"));
+    }
+
+    #[test]
+    fn test_can_act_for_owner() {
+        let caller =
+            ActorContext { tenant_id: Some("acme".into()), request_id: Some("1".into()), ..Default::default() };
+        assert!(caller.can_act_for_owner(Some("acme")));
+        assert!(!caller.can_act_for_owner(Some("other")));
+        assert!(!caller.can_act_for_owner(None));
+        let admin = ActorContext { scopes: vec![ActorScope::Admin], ..caller };
+        assert!(admin.can_act_for_owner(None));
+        assert!(ActorContext::system().can_act_for_owner(None));
+    }
+
+    #[test]
+    fn test_is_system() {
+        assert!(ActorContext::system().is_system());
+        assert!(!ActorContext::default().is_system());
+
+        // the correlation id of the request doesn't make the work it causes any less the system's
+        let caller = ActorContext { request_id: Some("request-1".into()), ..Default::default() };
+        assert!(!caller.can_act_for_owner(None));
+        let context = caller.as_system();
+        assert!(context.is_system());
+        assert_eq!(context.request_id.as_deref(), Some("request-1"));
+        assert!(context.can_act_for_tenant("acme"));
+        assert!(context.can_act_for_owner(None));
+    }
+
+    #[test]
+    fn test_actor_name() {
+        assert_eq!(ActorContext::system().actor_name(), "system");
+        let admin = ActorContext { scopes: vec![ActorScope::Admin], ..Default::default() };
+        assert!(admin.has_scope(ActorScope::Admin));
+        assert_eq!(admin.actor_name(), "admin");
+        let admin = ActorContext { actor_id: Some("alice".into()), ..admin };
+        assert_eq!(admin.actor_name(), "alice");
+
+        let context = ActorContext { request_id: Some("request-1".into()), ..admin };
+        assert_eq!(
+            context.as_system(),
+            ActorContext { request_id: Some("request-1".into()), ..ActorContext::system() }
+        );
+    }
+}
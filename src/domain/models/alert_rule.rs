@@ -0,0 +1,210 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use uuid::Uuid;
+
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+
+/// What a rule watches on an indexer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The indexer has this status, e.g. `FailedRunning`
+    Status { status: IndexerStatus },
+    /// The indexer runs but processes fewer blocks per minute than the threshold
+    BlocksPerMinuteBelow { threshold: f64 },
+}
+
+/// Where the alerts of a rule are sent, as signed webhooks
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertChannel {
+    Webhook {
+        url: String,
+    },
+    /// The notification webhook of the service, the one of the lifecycle webhooks
+    NotificationWebhook,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertRuleModel {
+    pub id: Uuid,
+    pub name: String,
+    /// Every indexer is watched if not set
+    pub indexer_id: Option<Uuid>,
+    pub condition: AlertCondition,
+    /// Time the condition has to hold before the alert fires
+    pub for_seconds: u64,
+    pub channels: Vec<AlertChannel>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AlertRuleRequest {
+    pub name: String,
+    pub indexer_id: Option<Uuid>,
+    pub condition: AlertCondition,
+    #[serde(default)]
+    pub for_seconds: u64,
+    pub channels: Vec<AlertChannel>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl AlertRuleRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("the name is empty".into());
+        }
+        if self.channels.is_empty() {
+            return Err("the rule has no channel".into());
+        }
+        if i64::try_from(self.for_seconds).is_err() {
+            return Err("for_seconds is too large".into());
+        }
+        if let AlertCondition::BlocksPerMinuteBelow { threshold } = self.condition {
+            if !threshold.is_finite() || threshold <= 0.0 {
+                return Err(format!("threshold {} is not a positive number", threshold));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AlertRuleModel {
+    pub fn watches(&self, indexer_id: Uuid) -> bool {
+        self.indexer_id.map_or(true, |watched| watched == indexer_id)
+    }
+
+    /// `blocks_per_minute` is the rate measured over the last evaluation interval, if known
+    pub fn holds(&self, indexer_model: &IndexerModel, blocks_per_minute: Option<f64>) -> bool {
+        match self.condition {
+            AlertCondition::Status { status } => indexer_model.status == status,
+            AlertCondition::BlocksPerMinuteBelow { threshold } => {
+                indexer_model.status == IndexerStatus::Running
+                    && blocks_per_minute.is_some_and(|blocks_per_minute| blocks_per_minute < threshold)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// Payload of the webhooks sent when a rule fires or resolves for an indexer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertEvent {
+    /// Version of the schema published at `/v1/schemas/events/<version>`
+    pub schema_version: String,
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub indexer_id: Uuid,
+    pub state: AlertState,
+    pub condition: AlertCondition,
+    pub happened_at: DateTime<Utc>,
+}
+
+/// Progress of a rule on an indexer between evaluations
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlertEvaluation {
+    holding_since: Option<DateTime<Utc>>,
+    firing: bool,
+}
+
+impl AlertEvaluation {
+    /// Records whether the condition holds, returns the state the alert moved to if it did
+    pub fn observe(&mut self, holds: bool, now: DateTime<Utc>, for_seconds: u64) -> Option<AlertState> {
+        if !holds {
+            self.holding_since = None;
+            return std::mem::take(&mut self.firing).then_some(AlertState::Resolved);
+        }
+        let holding_since = *self.holding_since.get_or_insert(now);
+        if self.firing || now - holding_since < Duration::seconds(for_seconds as i64) {
+            return None;
+        }
+        self.firing = true;
+        Some(AlertState::Firing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_alert_evaluation_observe() {
+        let start = Utc.with_ymd_and_hms(2025, 9, 1, 0, 0, 0).unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let mut evaluation = AlertEvaluation::default();
+
+        assert_eq!(evaluation.observe(true, at(0), 300), None);
+        // the condition stopped holding before 5 minutes
+        assert_eq!(evaluation.observe(false, at(3), 300), None);
+        assert_eq!(evaluation.observe(true, at(4), 300), None);
+        assert_eq!(evaluation.observe(true, at(9), 300), Some(AlertState::Firing));
+        // fires once
+        assert_eq!(evaluation.observe(true, at(10), 300), None);
+        assert_eq!(evaluation.observe(false, at(11), 300), Some(AlertState::Resolved));
+        assert_eq!(evaluation.observe(false, at(12), 300), None);
+        // without a duration the alert fires on the first evaluation
+        assert_eq!(evaluation.observe(true, at(13), 0), Some(AlertState::Firing));
+    }
+
+    #[rstest]
+    #[case(AlertCondition::Status { status: IndexerStatus::FailedRunning }, IndexerStatus::FailedRunning, None, true)]
+    #[case(AlertCondition::Status { status: IndexerStatus::FailedRunning }, IndexerStatus::Running, None, false)]
+    #[case(AlertCondition::BlocksPerMinuteBelow { threshold: 1.0 }, IndexerStatus::Running, Some(0.5), true)]
+    #[case(AlertCondition::BlocksPerMinuteBelow { threshold: 1.0 }, IndexerStatus::Running, Some(2.0), false)]
+    #[case(AlertCondition::BlocksPerMinuteBelow { threshold: 1.0 }, IndexerStatus::Running, None, false)]
+    #[case(AlertCondition::BlocksPerMinuteBelow { threshold: 1.0 }, IndexerStatus::Stopped, Some(0.0), false)]
+    fn test_alert_rule_holds(
+        #[case] condition: AlertCondition,
+        #[case] status: IndexerStatus,
+        #[case] blocks_per_minute: Option<f64>,
+        #[case] expected: bool,
+    ) {
+        let rule = AlertRuleModel {
+            id: Uuid::new_v4(),
+            name: "rule".into(),
+            indexer_id: None,
+            condition,
+            for_seconds: 0,
+            channels: vec![AlertChannel::NotificationWebhook],
+            enabled: true,
+            created_at: Utc::now(),
+        };
+        let indexer_model = IndexerModel { status, ..Default::default() };
+        assert_eq!(rule.holds(&indexer_model, blocks_per_minute), expected);
+    }
+
+    #[test]
+    fn test_validate_alert_rule_request() {
+        let request: AlertRuleRequest = serde_json::from_value(serde_json::json!({
+            "name": "slow",
+            "condition": { "metric": "blocks_per_minute_below", "threshold": 1.0 },
+            "for_seconds": 600,
+            "channels": [{ "kind": "webhook", "url": "https://example.com/alerts" }],
+        }))
+        .unwrap();
+        assert!(request.enabled);
+        assert_eq!(request.validate(), Ok(()));
+
+        let invalid =
+            AlertRuleRequest { condition: AlertCondition::BlocksPerMinuteBelow { threshold: 0.0 }, ..request.clone() };
+        assert!(invalid.validate().is_err());
+        assert!(AlertRuleRequest { channels: vec![], ..request }.validate().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::audit::AuditLogModel;
+
+/// Note left by an operator on an indexer, e.g. during an incident
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationModel {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    /// Markdown, rendered by the dashboards
+    pub body: String,
+    pub author: String,
+    pub incident_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Entry of the history of an indexer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryEntry {
+    Event(AuditLogModel),
+    Annotation(AnnotationModel),
+}
+
+impl HistoryEntry {
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Event(audit_log) => audit_log.created_at,
+            Self::Annotation(annotation) => annotation.created_at,
+        }
+    }
+}
+
+/// Interleaves the events and the annotations from the most recent to the oldest, keeping the
+/// `limit` most recent entries
+pub fn interleave_history(
+    events: Vec<AuditLogModel>,
+    annotations: Vec<AnnotationModel>,
+    limit: usize,
+) -> Vec<HistoryEntry> {
+    let mut history: Vec<HistoryEntry> = events
+        .into_iter()
+        .map(HistoryEntry::Event)
+        .chain(annotations.into_iter().map(HistoryEntry::Annotation))
+        .collect();
+    // stable, so entries recorded at the same time keep the events first
+    history.sort_by_key(|entry| std::cmp::Reverse(entry.created_at()));
+    history.truncate(limit);
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::domain::models::audit::{AuditAction, AuditSeverity};
+
+    #[test]
+    fn test_interleave_history() {
+        let start = Utc.with_ymd_and_hms(2025, 7, 21, 0, 0, 0).unwrap();
+        let indexer_id = Uuid::new_v4();
+        let event = |minutes: i64| AuditLogModel {
+            id: Uuid::new_v4(),
+            indexer_id,
+            action: AuditAction::StatusChange,
+            from_status: None,
+            to_status: None,
+            reason: None,
+            created_at: start + Duration::minutes(minutes),
+            actor: None,
+            severity: AuditSeverity::Info,
+            details: None,
+            correlation_id: None,
+        };
+        let annotation = |minutes: i64| AnnotationModel {
+            id: Uuid::new_v4(),
+            indexer_id,
+            body: "Restarted after the stream outage".into(),
+            author: "ops".into(),
+            incident_url: None,
+            created_at: start + Duration::minutes(minutes),
+        };
+
+        let history = interleave_history(vec![event(3), event(1)], vec![annotation(2), annotation(0)], 3);
+        let kinds: Vec<(bool, i64)> = history
+            .iter()
+            .map(|entry| (matches!(entry, HistoryEntry::Event(_)), (entry.created_at() - start).num_minutes()))
+            .collect();
+        assert_eq!(kinds, vec![(true, 3), (false, 2), (true, 1)]);
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Webhook destination an admin approved for the indexers of a tenant
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApprovedTargetModel {
+    pub tenant_id: String,
+    pub target_url: String,
+    pub approved_by: Option<String>,
+    pub approved_at: DateTime<Utc>,
+}
+
+/// Form the destinations are registered and compared in, so that e.g. `https://Example.com:443`
+/// matches `https://example.com/`. The fragment is never sent and is dropped.
+pub fn normalize_target_url(target_url: &str) -> Result<String, String> {
+    let mut url = Url::parse(target_url.trim()).map_err(|e| format!("{}: {}", target_url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{}: unsupported scheme", target_url));
+    }
+    url.set_fragment(None);
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("https://Example.com:443", Some("https://example.com/"))]
+    #[case(" https://example.com/hooks?id=1#top ", Some("https://example.com/hooks?id=1"))]
+    #[case("http://example.com:8080/hooks", Some("http://example.com:8080/hooks"))]
+    #[case("ftp://example.com", None)]
+    #[case("example.com", None)]
+    fn test_normalize_target_url(#[case] target_url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(normalize_target_url(target_url).ok().as_deref(), expected);
+    }
+}
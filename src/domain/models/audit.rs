@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domain::models::indexer::IndexerStatus;
+
+#[derive(Clone, Debug, PartialEq, EnumString, Serialize, Deserialize, Display, Copy)]
+pub enum AuditAction {
+    ForceStatus,
+    ConfigDrift,
+    StatusChange,
+    ConfigChange,
+    Failover,
+    StartFailed,
+    LifecycleHook,
+    ProcessExit,
+    /// A start was dropped as the indexer wasn't in a status it could be started from anymore
+    IgnoredStart,
+    /// A stop was dropped as the indexer got restarted since it was decided
+    IgnoredStop,
+    /// An admin approved the webhook destination of an indexer pending approval
+    TargetApproved,
+    /// A start token was redeemed to start the indexer
+    StartTokenRedeemed,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, EnumString, Serialize, Deserialize, Display, Copy)]
+pub enum AuditSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditLogModel {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub action: AuditAction,
+    pub from_status: Option<IndexerStatus>,
+    pub to_status: Option<IndexerStatus>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub severity: AuditSeverity,
+    pub details: Option<serde_json::Value>,
+    /// Id of the API request which caused the event, see `x-correlation-id`
+    pub correlation_id: Option<String>,
+}
+
+/// Position in the audit logs used for keyset pagination, logs are walked from the most
+/// recent to the oldest
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditLogCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl AuditLogCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (micros, id) = cursor.split_once('_')?;
+        let created_at = DateTime::<Utc>::from_timestamp_micros(micros.parse().ok()?)?;
+        Some(Self { created_at, id: Uuid::parse_str(id).ok()? })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub items: Vec<AuditLogModel>,
+    pub next_cursor: Option<String>,
+}
+
+/// Events of the live tail, every set criteria must match
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct AuditEventFilter {
+    pub tenant_id: Option<String>,
+    pub indexer_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub severity: Option<AuditSeverity>,
+}
+
+impl AuditEventFilter {
+    /// `tenant_id` is the tenant of the indexer of the event
+    pub fn matches(&self, log: &AuditLogModel, tenant_id: Option<&str>) -> bool {
+        self.tenant_id.as_deref().map_or(true, |expected| tenant_id == Some(expected))
+            && self.indexer_id.map_or(true, |expected| log.indexer_id == expected)
+            && self.action.map_or(true, |expected| log.action == expected)
+            && self.severity.map_or(true, |expected| log.severity == expected)
+    }
+}
+
+impl From<&AuditLogModel> for AuditLogCursor {
+    fn from(value: &AuditLogModel) -> Self {
+        Self { created_at: value.created_at, id: value.id }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = AuditLogCursor {
+            created_at: DateTime::<Utc>::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(AuditLogCursor::decode(cursor.encode().as_str()), Some(cursor));
+        assert_eq!(AuditLogCursor::decode("not_a_cursor"), None);
+        assert_eq!(AuditLogCursor::decode("garbage"), None);
+    }
+
+    #[test]
+    fn test_audit_event_filter() {
+        let log = AuditLogModel {
+            id: Uuid::new_v4(),
+            indexer_id: Uuid::new_v4(),
+            action: AuditAction::ForceStatus,
+            from_status: None,
+            to_status: None,
+            reason: None,
+            created_at: Utc::now(),
+            actor: None,
+            severity: AuditSeverity::Warning,
+            details: None,
+            correlation_id: None,
+        };
+        assert!(AuditEventFilter::default().matches(&log, None));
+
+        let filter = AuditEventFilter {
+            tenant_id: Some("tenant".into()),
+            severity: Some(AuditSeverity::Warning),
+            ..Default::default()
+        };
+        assert!(filter.matches(&log, Some("tenant")));
+        assert!(!filter.matches(&log, Some("other")));
+        assert!(!filter.matches(&log, None));
+
+        let filter = AuditEventFilter { action: Some(AuditAction::StatusChange), ..Default::default() };
+        assert!(!filter.matches(&log, Some("tenant")));
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::indexer::IndexerType;
+use crate::domain::models::runtime::{StartupCompromise, StartupMode};
+
+/// What this deployment supports, clients check it to hide what isn't available rather than
+/// failing when the request is made
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapabilitiesModel {
+    pub indexer_types: Vec<IndexerType>,
+    /// Not set as indexers can stream from any DNA server given by their `stream_url`
+    pub networks: Option<Vec<String>>,
+    pub execution_backends: Vec<String>,
+    pub subsystems: SubsystemsModel,
+    pub startup_mode: StartupMode,
+    /// Backends replaced by local ones as the service started in the permissive mode, what they
+    /// hold is lost with the host
+    pub startup_compromises: Vec<StartupCompromise>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemsModel {
+    /// Secret references are resolved from a secret backend when the sinks are launched
+    pub secrets: bool,
+    /// Maintenance windows deferring restarts, managed through the admin API
+    pub scheduling: bool,
+    /// Indexers are cloned from one another, there are no templates
+    pub templates: bool,
+    pub admin: bool,
+    pub multiplexer: bool,
+    pub script_search: bool,
+    pub notifications: bool,
+    pub command_hooks: bool,
+    pub gitops: bool,
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::indexer::IndexerModel;
+use crate::domain::models::launch_command::is_secret_env;
+
+/// Resources removed along with an indexer. Audit logs and usage records are kept, they
+/// outlive the indexers they describe.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupPlanModel {
+    pub indexer_id: Uuid,
+    /// Keys in the object store: the script and the diagnostics snapshots of the sink exits
+    pub objects: Vec<String>,
+    /// Files on the disk of the service. Sinks log to the service, only the resolved script
+    /// they were last started with is on disk.
+    pub local_files: Vec<String>,
+    /// Rows deleted with the indexer by table, e.g. its annotations and delivered ranges
+    pub rows: BTreeMap<String, i64>,
+    /// Secrets held by the indexer, by name only
+    pub secrets: Vec<String>,
+    pub scheduled_actions: Vec<Uuid>,
+}
+
+/// Names of the secrets stored on the indexer: the connection string of the sink and the
+/// environment variables it was launched with which look like credentials
+pub fn get_secret_names(indexer_model: &IndexerModel) -> Vec<String> {
+    let mut secrets = vec![];
+    if indexer_model.custom_connection_string.is_some() {
+        secrets.push("custom_connection_string".to_string());
+    }
+    if let Some(launch_config) = &indexer_model.launch_config {
+        secrets.extend(launch_config.env.keys().filter(|name| is_secret_env(name)).map(|name| format!("env.{}", name)));
+    }
+    secrets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::indexer::LaunchConfig;
+
+    #[test]
+    fn test_get_secret_names() {
+        let mut indexer_model = IndexerModel::default();
+        assert!(get_secret_names(&indexer_model).is_empty());
+
+        indexer_model.custom_connection_string = Some("postgres://user:secret@db".into());
+        indexer_model.launch_config = Some(LaunchConfig {
+            env: BTreeMap::from([
+                ("AUTH_TOKEN".to_string(), "dna_secret".to_string()),
+                ("RUST_LOG".to_string(), "info".to_string()),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(get_secret_names(&indexer_model), vec!["custom_connection_string", "env.AUTH_TOKEN"]);
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Data printed by a console sink, as returned by the script. Lines which aren't JSON are kept as
+/// their text.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleOutputRecord {
+    pub received_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ConsoleOutputQuery {
+    /// Keeps the records received after this time, e.g. the last one already fetched
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl ConsoleOutputRecord {
+    /// The sink logs on the same stdout as it prints the data, its structured logs are left out
+    pub fn parse(line: &str, received_at: DateTime<Utc>) -> Option<Self> {
+        let data = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(object)) if object.contains_key("level") && object.contains_key("target") => return None,
+            Ok(data) => data,
+            Err(_) => Value::String(line.to_string()),
+        };
+        Some(Self { received_at, data })
+    }
+}
+
+impl ConsoleOutputQuery {
+    pub fn matches(&self, record: &ConsoleOutputRecord) -> bool {
+        self.since.map_or(true, |since| record.received_at > since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    #[rstest]
+    #[case(r#"{"block_number":900150,"transfers":[]}"#, Some(json!({"block_number": 900150, "transfers": []})))]
+    #[case(r#"[{"from":"0x1"}]"#, Some(json!([{"from": "0x1"}])))]
+    #[case("batch 3 printed", Some(json!("batch 3 printed")))]
+    #[case(r#"{"level":"INFO","target":"apibara_sink_console","fields":{"message":"starting"}}"#, None)]
+    fn test_parse(#[case] line: &str, #[case] expected: Option<Value>) {
+        assert_eq!(ConsoleOutputRecord::parse(line, Utc::now()).map(|record| record.data), expected);
+    }
+}
@@ -0,0 +1,74 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::types::AxumErrorResponse;
+use crate::infra::errors::InfraError;
+
+/// A contract covered by the filter of an indexer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContractFilter {
+    pub address: String,
+    /// Only the keys written as literals in the script, an empty list can also mean every event
+    pub event_keys: Vec<String>,
+}
+
+/// Normalizes a felt to 64 lowercase hex characters, returns `None` if it isn't an hex string
+pub fn normalize_felt(value: &str) -> Option<String> {
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    if digits.is_empty() || digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("0x{:0>64}", digits.to_lowercase()))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContractError {
+    #[error("invalid contract address {0}")]
+    InvalidAddress(String),
+    #[error("infra error : {0}")]
+    InfraError(InfraError),
+}
+
+impl IntoResponse for ContractError {
+    fn into_response(self) -> axum::response::Response {
+        tracing::error!("Error: {:?}", self);
+        let (status, err_msg) = match self {
+            Self::InvalidAddress(_) => (StatusCode::BAD_REQUEST, format!("Bad request: {}", self)),
+            Self::InfraError(InfraError::DatabaseBusy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", self))
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", self)),
+        };
+        (
+            status,
+            Json(AxumErrorResponse {
+                resource: "ContractFilter".into(),
+                message: err_msg,
+                happened_at: chrono::Utc::now(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_felt() {
+        assert_eq!(
+            normalize_felt("0x049D36570D4e46f48e99674bd3fcc84644DdD6b96F7C741B1562B82f9e004dC7"),
+            Some("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7".into())
+        );
+        assert_eq!(
+            normalize_felt("0x1"),
+            Some("0x0000000000000000000000000000000000000000000000000000000000000001".into())
+        );
+        assert_eq!(normalize_felt("0x"), None);
+        assert_eq!(normalize_felt("0xzz"), None);
+        assert_eq!(normalize_felt("Transfer"), None);
+    }
+}
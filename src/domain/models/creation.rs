@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::domain::models::indexer::IndexerModel;
+
+/// Side effects of the creation of an indexer, in the order they run. The record and the script
+/// are stored together, either both are kept or neither is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CreationStep {
+    Recorded,
+    ScriptStored,
+    Started,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CreationStepResult {
+    pub step: CreationStep,
+    pub completed: bool,
+    pub error: Option<String>,
+}
+
+/// Answer to a create whose indexer was stored but not started, sent with `207 Multi-Status`.
+/// The start is retried with `POST /v1/indexers/:id/create/complete`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartialCreationModel {
+    pub indexer: IndexerModel,
+    pub steps: Vec<CreationStepResult>,
+}
+
+impl PartialCreationModel {
+    pub fn start_failed(indexer: IndexerModel, error: String) -> Self {
+        let completed = |step| CreationStepResult { step, completed: true, error: None };
+        Self {
+            indexer,
+            steps: vec![
+                completed(CreationStep::Recorded),
+                completed(CreationStep::ScriptStored),
+                CreationStepResult { step: CreationStep::Started, completed: false, error: Some(error) },
+            ],
+        }
+    }
+}
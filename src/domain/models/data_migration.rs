@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Legacy value of a column rewritten to its current value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MigratedValue {
+    pub column: String,
+    pub from: String,
+    pub to: String,
+    pub rows: usize,
+}
+
+/// Row holding a value with no current equivalent, it has to be fixed by hand
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnmappableRow {
+    pub indexer_id: Uuid,
+    pub column: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataMigrationReport {
+    pub dry_run: bool,
+    pub migrated: Vec<MigratedValue>,
+    pub unmappable: Vec<UnmappableRow>,
+}
+
+impl DataMigrationReport {
+    /// Plans the migration of a column given the value of every row. Values which are already
+    /// current are left as is.
+    pub fn plan_column(
+        &mut self,
+        column: &str,
+        rows: &[(Uuid, String)],
+        is_current: impl Fn(&str) -> bool,
+        from_legacy: impl Fn(&str) -> Option<String>,
+    ) {
+        let mut migrated: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for (indexer_id, value) in rows {
+            if is_current(value) {
+                continue;
+            }
+            match from_legacy(value) {
+                Some(to) => *migrated.entry((value.clone(), to)).or_default() += 1,
+                None => self.unmappable.push(UnmappableRow {
+                    indexer_id: *indexer_id,
+                    column: column.to_string(),
+                    value: value.clone(),
+                }),
+            }
+        }
+        self.migrated.extend(migrated.into_iter().map(|((from, to), rows)| MigratedValue {
+            column: column.to_string(),
+            from,
+            to,
+            rows,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::domain::models::indexer::IndexerStatus;
+
+    #[test]
+    fn test_plan_column() {
+        let (a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let rows = [
+            (a, "Running".to_string()),
+            (b, "Starting".to_string()),
+            (c, "Starting".to_string()),
+            (d, "Paused".to_string()),
+        ];
+        let mut report = DataMigrationReport::default();
+        report.plan_column(
+            "status",
+            &rows,
+            |value| IndexerStatus::from_str(value).is_ok(),
+            |value| IndexerStatus::from_legacy(value).map(|status| status.to_string()),
+        );
+        assert_eq!(
+            report.migrated,
+            vec![MigratedValue {
+                column: "status".into(),
+                from: "Starting".into(),
+                to: "FailedRunning".into(),
+                rows: 2
+            }]
+        );
+        assert_eq!(
+            report.unmappable,
+            vec![UnmappableRow { indexer_id: d, column: "status".into(), value: "Paused".into() }]
+        );
+    }
+}
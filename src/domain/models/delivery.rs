@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Blocks delivered to the target of an indexer, both ends are included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BlockRange {
+    pub start_block: i64,
+    pub end_block: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexerGapsModel {
+    pub indexer_id: Uuid,
+    pub start_block: i64,
+    /// `None` if the indexer didn't deliver anything and isn't running
+    pub head_block: Option<i64>,
+    pub gaps: Vec<BlockRange>,
+}
+
+/// Returns the ranges between `start_block` and `head_block` which weren't delivered
+pub fn find_gaps(start_block: i64, head_block: i64, delivered: &[BlockRange]) -> Vec<BlockRange> {
+    let mut delivered = delivered.to_vec();
+    delivered.sort();
+
+    let mut gaps = vec![];
+    let mut next_block = start_block;
+    for range in delivered {
+        if next_block > head_block {
+            break;
+        }
+        if range.start_block > next_block {
+            gaps.push(BlockRange { start_block: next_block, end_block: (range.start_block - 1).min(head_block) });
+        }
+        next_block = next_block.max(range.end_block + 1);
+    }
+    if next_block <= head_block {
+        gaps.push(BlockRange { start_block: next_block, end_block: head_block });
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_block: i64, end_block: i64) -> BlockRange {
+        BlockRange { start_block, end_block }
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        assert_eq!(find_gaps(1, 100, &[]), vec![range(1, 100)]);
+        assert_eq!(find_gaps(1, 100, &[range(1, 100)]), vec![]);
+        // overlapping and unordered ranges
+        assert_eq!(find_gaps(1, 100, &[range(60, 100), range(1, 20), range(10, 30)]), vec![range(31, 59)]);
+        assert_eq!(find_gaps(10, 100, &[range(1, 50), range(52, 90)]), vec![range(51, 51), range(91, 100)]);
+        // deliveries past the head are ignored
+        assert_eq!(find_gaps(1, 50, &[range(40, 200)]), vec![range(1, 39)]);
+    }
+}
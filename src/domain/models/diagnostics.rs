@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Last lines a sink printed and how it exited, kept in the store so that they outlive the logs
+/// of the host
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessExitSnapshot {
+    pub indexer_id: Uuid,
+    pub process_id: u32,
+    /// Not set if the process was killed by a signal, e.g. on stop
+    pub exit_code: Option<i32>,
+    pub exited_at: DateTime<Utc>,
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+/// Keeps the last `capacity` lines of an output
+#[derive(Clone, Debug, Default)]
+pub struct OutputTail {
+    capacity: usize,
+    lines: VecDeque<String>,
+}
+
+impl OutputTail {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, lines: VecDeque::with_capacity(capacity) }
+    }
+
+    pub fn push(&mut self, line: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_tail() {
+        let mut tail = OutputTail::new(2);
+        for line in ["a", "b", "c"] {
+            tail.push(line.to_string());
+        }
+        assert_eq!(tail.into_lines(), vec!["b".to_string(), "c".to_string()]);
+
+        let mut tail = OutputTail::new(0);
+        tail.push("a".to_string());
+        assert!(tail.into_lines().is_empty());
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of every v2 response. v1 responses return the resource itself.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub data: T,
+    /// Only set on listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageInfo>,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(data: T) -> Self {
+        Self { data, page: None }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PageInfo {
+    pub limit: i64,
+    /// Passed as `cursor` to get the next page, not set on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Body of the v2 error responses
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub error: ErrorBody,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// Stable identifier of the error, e.g. `INVALID_INDEXER_STATUS`, messages may change
+    pub code: String,
+    pub message: String,
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::domain::models::contract::ContractFilter;
+use crate::domain::models::usage::UsageRecordModel;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EstimateRequest {
+    /// Stream of the network the indexer would run on, the tenant default isn't applied
+    pub stream_url: Option<String>,
+    pub starting_block: u64,
+    /// The head of the stream by default
+    pub ending_block: Option<u64>,
+    /// Contracts the indexer would watch, their events are counted on a sample of the stream
+    #[serde(default)]
+    pub filter: Vec<ContractFilter>,
+}
+
+/// Events matching the filter of an estimate in a window of the stream, counted by running the
+/// filter in a sink of its own
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventSample {
+    pub starting_block: u64,
+    /// Block the sample reached, the end of its window unless it timed out
+    pub ending_block: u64,
+    pub events: u64,
+    pub duration_ms: u64,
+}
+
+impl EventSample {
+    pub fn blocks(&self) -> u64 {
+        self.ending_block.saturating_sub(self.starting_block)
+    }
+
+    /// Counts the events of a payload of the sample script, the sink sends one report per block
+    /// or a batch of them
+    pub fn record(&mut self, payload: &serde_json::Value) {
+        let reports = match payload {
+            serde_json::Value::Array(reports) => reports.iter().collect(),
+            report => vec![report],
+        };
+        for report in reports {
+            self.events += report.get("events").and_then(serde_json::Value::as_u64).unwrap_or_default();
+            if let Some(block_number) = report.get("block_number").and_then(serde_json::Value::as_u64) {
+                self.ending_block = self.ending_block.max(block_number + 1);
+            }
+        }
+    }
+}
+
+/// Script of the sink sampling the stream, it only reports the number of events matching the
+/// filter in the blocks which have some
+pub fn get_sample_script(filter: &[ContractFilter]) -> String {
+    let events: Vec<serde_json::Value> = filter
+        .iter()
+        .map(|contract| match contract.event_keys.is_empty() {
+            true => json!({ "fromAddress": contract.address }),
+            false => json!({ "fromAddress": contract.address, "keys": contract.event_keys }),
+        })
+        .collect();
+    let config = json!({
+        "network": "starknet",
+        "filter": { "header": { "weak": true }, "events": events },
+        "sinkType": "webhook",
+        "sinkOptions": { "raw": true },
+    });
+    format!(
+        "export const config = {};\n\nexport default function ({{ header, events }}) {{\n  return {{ block_number: \
+         +header.blockNumber, events: events.length }};\n}}\n",
+        config
+    )
+}
+
+/// Projection of an indexer. The event density and the throughput come from a sample of the
+/// stream run with the filter, the log volume from the usage metered on the indexers of the same
+/// stream.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EstimateModel {
+    pub starting_block: u64,
+    pub ending_block: u64,
+    pub blocks: u64,
+    pub sample: Option<EventSample>,
+    pub estimated_events: Option<u64>,
+    pub blocks_per_second: Option<f64>,
+    pub estimated_running_hours: Option<f64>,
+    /// Indexers the log volume was measured on, none if nothing was metered on the stream
+    pub sampled_indexers: usize,
+    /// Whether the sampled indexers watch the contracts of the filter or only share the stream
+    pub filter_matched: bool,
+    pub estimated_log_bytes: Option<u64>,
+}
+
+/// Projects the processing of the blocks from the sample of the stream and the metered usage
+pub fn estimate(
+    starting_block: u64,
+    ending_block: u64,
+    sample: Option<EventSample>,
+    metered: &[UsageRecordModel],
+    filter_matched: bool,
+) -> EstimateModel {
+    let blocks = ending_block.saturating_sub(starting_block);
+    let blocks_processed: i64 = metered.iter().map(|record| record.blocks_processed).sum();
+    let log_bytes: i64 = metered.iter().map(|record| record.log_bytes).sum();
+    let mut sampled: Vec<_> = metered.iter().map(|record| record.indexer_id).collect();
+    sampled.sort();
+    sampled.dedup();
+
+    let mut model = EstimateModel {
+        starting_block,
+        ending_block,
+        blocks,
+        sample,
+        sampled_indexers: sampled.len(),
+        filter_matched: filter_matched && !sampled.is_empty(),
+        ..Default::default()
+    };
+    if let Some(sample) = sample.filter(|sample| sample.blocks() > 0) {
+        let events_per_block = sample.events as f64 / sample.blocks() as f64;
+        model.estimated_events = Some((events_per_block * blocks as f64).round() as u64);
+        // the sample ran through past blocks, the indexer isn't slowed down by the head of the stream
+        if sample.duration_ms > 0 {
+            let blocks_per_second = sample.blocks() as f64 / (sample.duration_ms as f64 / 1000.0);
+            model.blocks_per_second = Some(blocks_per_second);
+            model.estimated_running_hours = Some(blocks as f64 / blocks_per_second / 3600.0);
+        }
+    }
+    // logs are counted per block, the time the metered indexers spent idle doesn't matter
+    if blocks_processed > 0 {
+        model.estimated_log_bytes = Some((log_bytes as f64 / blocks_processed as f64 * blocks as f64) as u64);
+    }
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let record = |indexer_id: Uuid, running_seconds: i64, blocks_processed: i64| UsageRecordModel {
+            tenant_id: "tenant".into(),
+            indexer_id,
+            day: NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+            running_seconds,
+            blocks_processed,
+            script_bytes: 0,
+            log_bytes: blocks_processed * 10,
+            streamed_bytes: 0,
+            delivered_bytes: 0,
+        };
+        // idle at the head of the stream for a day, it doesn't change the throughput
+        let metered = [record(first, 3600, 3600), record(first, 86400, 0), record(second, 0, 0)];
+        let sample = EventSample { starting_block: 1000, ending_block: 2000, events: 250, duration_ms: 2000 };
+
+        let model = estimate(1000, 4600, Some(sample), &metered, true);
+        assert_eq!(model.blocks, 3600);
+        assert_eq!(model.estimated_events, Some(900));
+        assert_eq!(model.blocks_per_second, Some(500.0));
+        assert_eq!(model.estimated_running_hours, Some(3600.0 / 500.0 / 3600.0));
+        assert_eq!(model.sampled_indexers, 2);
+        assert!(model.filter_matched);
+        assert_eq!(model.estimated_log_bytes, Some(36000));
+
+        let model = estimate(1000, 4600, None, &[], true);
+        assert_eq!((model.sampled_indexers, model.filter_matched), (0, false));
+        assert_eq!((model.estimated_events, model.blocks_per_second, model.estimated_log_bytes), (None, None, None));
+    }
+
+    #[test]
+    fn test_record_sample() {
+        let mut sample = EventSample { starting_block: 100, ending_block: 100, ..Default::default() };
+        sample.record(&json!({ "block_number": 120, "events": 3 }));
+        sample.record(&json!([{ "block_number": 130, "events": 2 }, { "block_number": 125, "events": 1 }]));
+        assert_eq!((sample.events, sample.ending_block, sample.blocks()), (6, 131, 31));
+    }
+
+    #[test]
+    fn test_sample_script() {
+        let script = get_sample_script(&[
+            ContractFilter { address: "0x1".into(), event_keys: vec![] },
+            ContractFilter { address: "0x2\"});".into(), event_keys: vec!["0x3".into()] },
+        ]);
+        assert!(script.contains(r#"{"fromAddress":"0x1"}"#));
+        // the filter is written as JSON so it can't break out of the config
+        assert!(script.contains(r#"{"fromAddress":"0x2\"});","keys":["0x3"]}"#));
+        assert!(script.contains("events: events.length"));
+    }
+}
@@ -0,0 +1,173 @@
+use serde_json::{json, Value};
+use strum::IntoEnumIterator;
+
+use crate::domain::models::hook::HookStage;
+use crate::domain::models::indexer::IndexerStatus;
+
+/// Version of the payloads of the lifecycle webhooks, alerts and hooks, sent as `schema_version`
+/// in each of them. Fields are only added within a version, a new version is published next to
+/// the previous ones when a field changes or goes away.
+pub const EVENT_SCHEMA_VERSION: &str = "v1";
+
+/// Versions whose schema is published, oldest first
+pub const EVENT_SCHEMA_VERSIONS: &[&str] = &[EVENT_SCHEMA_VERSION];
+
+/// JSON schema of the events of a version, `None` if the version is unknown
+pub fn event_schema(version: &str) -> Option<Value> {
+    match version {
+        "v1" => Some(event_schema_v1()),
+        _ => None,
+    }
+}
+
+fn event_schema_v1() -> Value {
+    let statuses: Vec<String> = IndexerStatus::iter().map(|status| status.to_string()).collect();
+    let stages: Vec<String> = HookStage::iter().map(|stage| stage.to_string()).collect();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "/v1/schemas/events/v1",
+        "title": "Indexer service events",
+        "oneOf": [
+            { "$ref": "#/$defs/lifecycle_event" },
+            { "$ref": "#/$defs/alert_event" },
+            { "$ref": "#/$defs/hook_event" },
+        ],
+        "$defs": {
+            "schema_version": { "const": "v1" },
+            "uuid": { "type": "string", "format": "uuid" },
+            "timestamp": { "type": "string", "format": "date-time" },
+            "indexer_status": { "enum": statuses },
+            "lifecycle_event": {
+                "description": "Sent to the notification webhook when an indexer changes status",
+                "type": "object",
+                "required": ["schema_version", "indexer_id", "status", "happened_at"],
+                "properties": {
+                    "schema_version": { "$ref": "#/$defs/schema_version" },
+                    "indexer_id": { "$ref": "#/$defs/uuid" },
+                    "status": { "$ref": "#/$defs/indexer_status" },
+                    "happened_at": { "$ref": "#/$defs/timestamp" },
+                    "correlation_id": { "type": "string" },
+                    "reason": { "type": "string" },
+                },
+            },
+            "alert_event": {
+                "description": "Sent to the channels of an alert rule when it fires or resolves for an indexer",
+                "type": "object",
+                "required": ["schema_version", "rule_id", "rule_name", "indexer_id", "state", "condition", "happened_at"],
+                "properties": {
+                    "schema_version": { "$ref": "#/$defs/schema_version" },
+                    "rule_id": { "$ref": "#/$defs/uuid" },
+                    "rule_name": { "type": "string" },
+                    "indexer_id": { "$ref": "#/$defs/uuid" },
+                    "state": { "enum": ["firing", "resolved"] },
+                    "condition": {
+                        "oneOf": [
+                            {
+                                "type": "object",
+                                "required": ["metric", "status"],
+                                "properties": {
+                                    "metric": { "const": "status" },
+                                    "status": { "$ref": "#/$defs/indexer_status" },
+                                },
+                            },
+                            {
+                                "type": "object",
+                                "required": ["metric", "threshold"],
+                                "properties": {
+                                    "metric": { "const": "blocks_per_minute_below" },
+                                    "threshold": { "type": "number" },
+                                },
+                            },
+                        ],
+                    },
+                    "happened_at": { "$ref": "#/$defs/timestamp" },
+                },
+            },
+            "hook_event": {
+                "description": "Body of the HTTP hooks of an indexer",
+                "type": "object",
+                "required": ["schema_version", "indexer_id", "stage"],
+                "properties": {
+                    "schema_version": { "$ref": "#/$defs/schema_version" },
+                    "indexer_id": { "$ref": "#/$defs/uuid" },
+                    "stage": { "enum": stages },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::domain::models::alert_rule::{AlertCondition, AlertEvent, AlertState};
+    use crate::domain::models::hook::HookEvent;
+    use crate::domain::models::notification::LifecycleEvent;
+
+    /// Required fields of a definition of the schema
+    fn required(schema: &Value, definition: &str) -> Vec<String> {
+        serde_json::from_value(schema["$defs"][definition]["required"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_events_match_schema() {
+        let schema = event_schema(EVENT_SCHEMA_VERSION).unwrap();
+        let events = [
+            (
+                "lifecycle_event",
+                serde_json::to_value(LifecycleEvent {
+                    schema_version: EVENT_SCHEMA_VERSION.into(),
+                    indexer_id: Uuid::new_v4(),
+                    status: IndexerStatus::Running,
+                    happened_at: Utc::now(),
+                    correlation_id: Some("request-1".into()),
+                    reason: None,
+                })
+                .unwrap(),
+            ),
+            (
+                "alert_event",
+                serde_json::to_value(AlertEvent {
+                    schema_version: EVENT_SCHEMA_VERSION.into(),
+                    rule_id: Uuid::new_v4(),
+                    rule_name: "stalled".into(),
+                    indexer_id: Uuid::new_v4(),
+                    state: AlertState::Firing,
+                    condition: AlertCondition::BlocksPerMinuteBelow { threshold: 1.0 },
+                    happened_at: Utc::now(),
+                })
+                .unwrap(),
+            ),
+            (
+                "hook_event",
+                serde_json::to_value(HookEvent {
+                    schema_version: EVENT_SCHEMA_VERSION.into(),
+                    indexer_id: Uuid::new_v4(),
+                    stage: HookStage::PreStart,
+                })
+                .unwrap(),
+            ),
+        ];
+        for (definition, event) in events {
+            let properties = schema["$defs"][definition]["properties"].as_object().unwrap();
+            for field in required(&schema, definition) {
+                assert!(event.get(&field).is_some(), "{} is missing {}", definition, field);
+            }
+            for field in event.as_object().unwrap().keys() {
+                assert!(properties.contains_key(field), "{} isn't in the schema of {}", field, definition);
+            }
+            assert_eq!(event["schema_version"], EVENT_SCHEMA_VERSION);
+        }
+    }
+
+    #[test]
+    fn test_event_schema_versions() {
+        for version in EVENT_SCHEMA_VERSIONS {
+            assert_eq!(event_schema(version).unwrap()["$defs"]["schema_version"]["const"], *version);
+        }
+        assert!(event_schema("v0").is_none());
+    }
+}
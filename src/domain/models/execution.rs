@@ -0,0 +1,133 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::indexer::IndexerModel;
+
+/// Handle on what runs the sink of an indexer, as given by the execution backend which started it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExecutionRef {
+    /// `start_time` is the start time of the process in clock ticks since boot, pids are reused
+    /// once a process exits so the pid alone can point to another process
+    Pid { pid: u32, start_time: Option<u64> },
+}
+
+impl ExecutionRef {
+    pub fn pid(&self) -> Option<u32> {
+        match self {
+            Self::Pid { pid, .. } => Some(*pid),
+        }
+    }
+
+    /// Whether the process is the one which was started. An unknown start time on either side
+    /// isn't held against the process.
+    pub fn matches_process(&self, pid: u32, start_time: Option<u64>) -> bool {
+        match self {
+            Self::Pid { pid: expected, start_time: expected_start_time } => {
+                *expected == pid
+                    && match (expected_start_time, start_time) {
+                        (Some(expected), Some(start_time)) => *expected == start_time,
+                        _ => true,
+                    }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExecutionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pid { pid, .. } => write!(f, "pid {}", pid),
+        }
+    }
+}
+
+/// Execution a stop was decided for. A stop decided before the indexer got restarted would kill
+/// the new process, it's dropped unless the indexer still runs the expected execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StopExpectation {
+    pub execution_ref: Option<ExecutionRef>,
+    /// `execution_seq` of the indexer when the stop was decided
+    pub execution_seq: Option<i64>,
+}
+
+impl StopExpectation {
+    /// Expects the execution the indexer currently runs
+    pub fn current(indexer: &IndexerModel) -> Self {
+        Self { execution_ref: indexer.execution_ref.clone(), execution_seq: Some(indexer.execution_seq) }
+    }
+
+    /// Why the indexer doesn't run the expected execution anymore, `None` if it does
+    pub fn mismatch(&self, indexer: &IndexerModel) -> Option<String> {
+        if let Some(execution_seq) = self.execution_seq {
+            if execution_seq != indexer.execution_seq {
+                return Some(format!("expected execution {}, found {}", execution_seq, indexer.execution_seq));
+            }
+        }
+        match (&self.execution_ref, &indexer.execution_ref) {
+            (Some(expected), Some(execution_ref)) if expected != execution_ref => {
+                Some(format!("expected {}, found {}", expected, execution_ref))
+            }
+            (Some(expected), None) => Some(format!("expected {}, found none", expected)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Some(100), 42, Some(100), true)]
+    #[case(Some(100), 42, Some(200), false)]
+    #[case(Some(100), 43, Some(100), false)]
+    #[case(None, 42, Some(200), true)]
+    #[case(Some(100), 42, None, true)]
+    fn test_matches_process(
+        #[case] expected_start_time: Option<u64>,
+        #[case] pid: u32,
+        #[case] start_time: Option<u64>,
+        #[case] expected: bool,
+    ) {
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: expected_start_time };
+        assert_eq!(execution_ref.matches_process(pid, start_time), expected);
+    }
+
+    #[test]
+    fn test_stop_expectation_mismatch() {
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: Some(100) };
+        let indexer =
+            IndexerModel { execution_ref: Some(execution_ref.clone()), execution_seq: 3, ..Default::default() };
+        assert_eq!(StopExpectation::current(&indexer).mismatch(&indexer), None);
+        assert_eq!(StopExpectation::default().mismatch(&indexer), None);
+
+        // restarted since the stop was decided
+        let restarted = IndexerModel {
+            execution_ref: Some(ExecutionRef::Pid { pid: 43, start_time: Some(200) }),
+            execution_seq: 4,
+            ..Default::default()
+        };
+        assert!(StopExpectation::current(&indexer).mismatch(&restarted).is_some());
+        let expectation = StopExpectation { execution_ref: Some(execution_ref), execution_seq: None };
+        assert_eq!(expectation.mismatch(&restarted), Some("expected pid 42, found pid 43".into()));
+        assert_eq!(expectation.mismatch(&IndexerModel::default()), Some("expected pid 42, found none".into()));
+    }
+
+    #[test]
+    fn test_execution_ref_json() {
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: None };
+        assert_eq!(
+            serde_json::to_value(&execution_ref).unwrap(),
+            json!({"kind": "pid", "pid": 42, "start_time": null})
+        );
+        // rows migrated from the process id have no start time
+        assert_eq!(serde_json::from_value::<ExecutionRef>(json!({"kind": "pid", "pid": 42})).unwrap(), execution_ref);
+        assert_eq!(execution_ref.pid(), Some(42));
+        assert_eq!(execution_ref.to_string(), "pid 42");
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use uuid::Uuid;
+
+use crate::domain::models::gitops::{is_gitops_managed, plan_sync, GitOpsAction, GitOpsField, GitOpsManifest};
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FleetDiffAction {
+    Create,
+    /// Settings of a stopped indexer change, they are picked up at its next start
+    Update,
+    /// Settings of a running indexer change, it has to be restarted for them to take effect
+    Restart,
+    /// The indexer isn't declared anymore, it is stopped but kept
+    Archive,
+    /// The indexer was archived and is declared again, it is started again
+    Restore,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetDiffEntry {
+    pub name: String,
+    pub action: FleetDiffAction,
+    pub indexer_id: Option<Uuid>,
+    pub status: Option<IndexerStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<GitOpsField>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FleetDiffModel {
+    /// Set if the fleet doesn't match the manifest, CI checks can fail on it
+    pub drifted: bool,
+    pub entries: Vec<FleetDiffEntry>,
+}
+
+/// Changes the GitOps sync would make for the indexers to match the manifest, see `plan_sync`.
+/// Only the indexers the sync manages are compared. Scripts are only compared for the specs
+/// pinning a `script_sha256`, as they aren't fetched.
+pub fn diff_fleet(manifest: &GitOpsManifest, indexers: &[IndexerModel]) -> FleetDiffModel {
+    let managed: Vec<IndexerModel> =
+        indexers.iter().filter(|indexer_model| is_gitops_managed(indexer_model)).cloned().collect();
+    let by_name: HashMap<&str, &IndexerModel> = managed
+        .iter()
+        .filter(|indexer_model| indexer_model.standby_for.is_none() && indexer_model.backfill_for.is_none())
+        .filter_map(|indexer_model| indexer_model.indexer_id.as_deref().map(|name| (name, indexer_model)))
+        .collect();
+    let script_checksums: HashMap<String, String> = manifest
+        .indexers
+        .iter()
+        .filter_map(|spec| {
+            let checksum = match &spec.script_sha256 {
+                Some(checksum) => Some(checksum.to_lowercase()),
+                None => by_name.get(spec.name.as_str()).and_then(|indexer_model| indexer_model.script_checksum.clone()),
+            };
+            checksum.map(|checksum| (spec.name.clone(), checksum))
+        })
+        .collect();
+
+    let entries: Vec<FleetDiffEntry> = plan_sync(manifest, &managed, &script_checksums)
+        .into_iter()
+        .map(|change| {
+            let indexer_model =
+                change.indexer_id.and_then(|id| managed.iter().find(|indexer_model| indexer_model.id == id));
+            let status = indexer_model.map(|indexer_model| indexer_model.status);
+            let action = match change.action {
+                GitOpsAction::Create => FleetDiffAction::Create,
+                GitOpsAction::Update if status.is_some_and(|status| status.is_live()) => FleetDiffAction::Restart,
+                GitOpsAction::Update => FleetDiffAction::Update,
+                GitOpsAction::Archive => FleetDiffAction::Archive,
+                GitOpsAction::Restore => FleetDiffAction::Restore,
+            };
+            FleetDiffEntry { name: change.name, action, indexer_id: change.indexer_id, status, fields: change.fields }
+        })
+        .collect();
+
+    FleetDiffModel { drifted: !entries.is_empty(), entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::models::gitops::{GitOpsIndexerSpec, GITOPS_MANAGER};
+
+    fn indexer(name: &str, status: IndexerStatus) -> IndexerModel {
+        IndexerModel {
+            id: Uuid::new_v4(),
+            status,
+            indexer_id: Some(name.to_string()),
+            script_checksum: Some("abc".to_string()),
+            managed_by: Some(GITOPS_MANAGER.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn spec(name: &str) -> GitOpsIndexerSpec {
+        GitOpsIndexerSpec { name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_diff_fleet() {
+        let unchanged = indexer("unchanged", IndexerStatus::Running);
+        let new_script = indexer("new_script", IndexerStatus::Starting);
+        let new_priority = indexer("new_priority", IndexerStatus::Stopped);
+        let removed = indexer("removed", IndexerStatus::Running);
+        let readded = IndexerModel { archived_at: Some(Utc::now()), ..indexer("readded", IndexerStatus::Stopped) };
+        // only the indexers of the sync are compared
+        let unmanaged = IndexerModel { managed_by: None, ..indexer("unmanaged", IndexerStatus::Running) };
+        let manifest = GitOpsManifest {
+            indexers: vec![
+                GitOpsIndexerSpec { script_sha256: Some("ABC".into()), ..spec("unchanged") },
+                GitOpsIndexerSpec { script_sha256: Some("def".into()), ..spec("new_script") },
+                GitOpsIndexerSpec { priority: 1, ..spec("new_priority") },
+                spec("readded"),
+                spec("created"),
+            ],
+        };
+
+        let diff = diff_fleet(&manifest, &[unchanged, new_script, new_priority, removed, readded, unmanaged]);
+        assert!(diff.drifted);
+        let summary: Vec<(&str, FleetDiffAction, &[GitOpsField])> =
+            diff.entries.iter().map(|entry| (entry.name.as_str(), entry.action, entry.fields.as_slice())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("new_script", FleetDiffAction::Restart, &[GitOpsField::Script][..]),
+                ("new_priority", FleetDiffAction::Update, &[GitOpsField::Priority][..]),
+                ("readded", FleetDiffAction::Restore, &[][..]),
+                ("created", FleetDiffAction::Create, &[][..]),
+                ("removed", FleetDiffAction::Archive, &[][..]),
+            ]
+        );
+
+        let manifest = GitOpsManifest { indexers: vec![spec("unchanged")] };
+        let unmanaged = IndexerModel { managed_by: None, ..indexer("unmanaged", IndexerStatus::Running) };
+        assert!(!diff_fleet(&manifest, &[indexer("unchanged", IndexerStatus::Running), unmanaged]).drifted);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use uuid::Uuid;
+
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType};
+
+/// `managed_by` of the indexers created by the GitOps sync
+pub const GITOPS_MANAGER: &str = "gitops";
+
+/// Desired state of the fleet, read from the manifest at the root of the GitOps repository
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitOpsManifest {
+    pub indexers: Vec<GitOpsIndexerSpec>,
+}
+
+/// Indexer of the manifest. The name is used as the sink id of the indexer and identifies it
+/// across syncs, the script is a path relative to the root of the repository.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitOpsIndexerSpec {
+    pub name: String,
+    pub script: String,
+    pub script_sha256: Option<String>,
+    pub indexer_type: IndexerType,
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub output_bucket: Option<String>,
+    pub output_prefix: Option<String>,
+    pub starting_block: Option<i64>,
+    pub ending_block: Option<i64>,
+    pub stream_url: Option<String>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub script_params: BTreeMap<String, String>,
+    pub priority: i32,
+    pub tenant_id: Option<String>,
+}
+
+impl GitOpsIndexerSpec {
+    /// Fields of the create request of the indexer, the script being fetched from `script_url`
+    pub fn to_create_fields(&self, script_url: &str) -> serde_json::Map<String, serde_json::Value> {
+        let mut fields = serde_json::json!({
+            "indexer_id": self.name,
+            "script_url": script_url,
+            "script_sha256": self.script_sha256,
+            "indexer_type": self.indexer_type.to_string(),
+            "target_url": self.target_url,
+            "table_name": self.table_name,
+            "output_bucket": self.output_bucket,
+            "output_prefix": self.output_prefix,
+            "starting_block": self.starting_block,
+            "ending_block": self.ending_block,
+            "stream_url": self.stream_url,
+            "log_level": self.log_level.map(|log_level| log_level.to_string()),
+            "script_params": self.script_params,
+            "priority": self.priority,
+            "tenant_id": self.tenant_id,
+        });
+        fields.as_object_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Settings of the indexer which differ from the spec. Only the settings which can be updated
+    /// in place are compared, the others are kept from the creation of the indexer.
+    pub fn get_changed_fields(&self, indexer_model: &IndexerModel) -> Vec<GitOpsField> {
+        let mut changed = vec![];
+        if self.target_url.is_some() && self.target_url != indexer_model.target_url {
+            changed.push(GitOpsField::TargetUrl);
+        }
+        if self.stream_url.is_some() && self.stream_url != indexer_model.stream_url {
+            changed.push(GitOpsField::StreamUrl);
+        }
+        if self.log_level != indexer_model.log_level {
+            changed.push(GitOpsField::LogLevel);
+        }
+        if self.script_params != indexer_model.script_params {
+            changed.push(GitOpsField::ScriptParams);
+        }
+        if self.priority != indexer_model.priority {
+            changed.push(GitOpsField::Priority);
+        }
+        changed
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GitOpsField {
+    Script,
+    TargetUrl,
+    StreamUrl,
+    LogLevel,
+    ScriptParams,
+    Priority,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GitOpsAction {
+    Create,
+    Update,
+    /// The indexer was removed from the manifest, it is stopped but kept with its script
+    Archive,
+    /// The indexer was archived and is declared again, it is updated and started again
+    Restore,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GitOpsChange {
+    pub name: String,
+    pub action: GitOpsAction,
+    pub indexer_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<GitOpsField>,
+    /// Set when the change couldn't be applied
+    pub error: Option<String>,
+}
+
+/// Whether the indexer was created by the GitOps sync
+pub fn is_gitops_managed(indexer_model: &IndexerModel) -> bool {
+    indexer_model.managed_by.as_deref() == Some(GITOPS_MANAGER)
+}
+
+/// Changes needed for the managed indexers to match the manifest. `script_checksums` holds the
+/// checksum of the script of every spec, as fetched from the repository.
+pub fn plan_sync(
+    manifest: &GitOpsManifest,
+    managed: &[IndexerModel],
+    script_checksums: &HashMap<String, String>,
+) -> Vec<GitOpsChange> {
+    // standbys and backfills share the sink id of the indexer they were created from
+    let managed: Vec<&IndexerModel> = managed
+        .iter()
+        .filter(|indexer_model| indexer_model.standby_for.is_none() && indexer_model.backfill_for.is_none())
+        .collect();
+    let by_name: HashMap<&str, &IndexerModel> = managed
+        .iter()
+        .copied()
+        .filter_map(|indexer_model| indexer_model.indexer_id.as_deref().map(|name| (name, indexer_model)))
+        .collect();
+
+    let mut changes = vec![];
+    for spec in &manifest.indexers {
+        let Some(indexer_model) = by_name.get(spec.name.as_str()) else {
+            changes.push(GitOpsChange {
+                name: spec.name.clone(),
+                action: GitOpsAction::Create,
+                indexer_id: None,
+                fields: vec![],
+                error: None,
+            });
+            continue;
+        };
+        let mut fields = vec![];
+        if script_checksums.get(&spec.name) != indexer_model.script_checksum.as_ref() {
+            fields.push(GitOpsField::Script);
+        }
+        fields.extend(spec.get_changed_fields(indexer_model));
+        if indexer_model.archived_at.is_some() {
+            changes.push(GitOpsChange {
+                name: spec.name.clone(),
+                action: GitOpsAction::Restore,
+                indexer_id: Some(indexer_model.id),
+                fields,
+                error: None,
+            });
+        } else if !fields.is_empty() {
+            changes.push(GitOpsChange {
+                name: spec.name.clone(),
+                action: GitOpsAction::Update,
+                indexer_id: Some(indexer_model.id),
+                fields,
+                error: None,
+            });
+        }
+    }
+
+    for indexer_model in managed {
+        let Some(name) = &indexer_model.indexer_id else { continue };
+        let removed = !manifest.indexers.iter().any(|spec| &spec.name == name);
+        if removed && indexer_model.status.is_live() {
+            changes.push(GitOpsChange {
+                name: name.clone(),
+                action: GitOpsAction::Archive,
+                indexer_id: Some(indexer_model.id),
+                fields: vec![],
+                error: None,
+            });
+        }
+    }
+    changes
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitOpsSyncStatus {
+    pub enabled: bool,
+    pub repository_url: Option<String>,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// Set when the manifest or a script couldn't be read, nothing is changed then
+    pub last_error: Option<String>,
+    /// Checksum of the manifest of the last sync
+    pub manifest_checksum: Option<String>,
+    pub changes: Vec<GitOpsChange>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexer(name: &str, status: IndexerStatus, script_checksum: &str) -> IndexerModel {
+        IndexerModel {
+            id: Uuid::new_v4(),
+            status,
+            indexer_id: Some(name.to_string()),
+            script_checksum: Some(script_checksum.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn spec(name: &str) -> GitOpsIndexerSpec {
+        GitOpsIndexerSpec { name: name.to_string(), script: format!("{}.js", name), ..Default::default() }
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let unchanged = indexer("unchanged", IndexerStatus::Running, "a");
+        let new_script = indexer("new_script", IndexerStatus::Running, "a");
+        let new_priority = indexer("new_priority", IndexerStatus::Stopped, "a");
+        let removed = indexer("removed", IndexerStatus::Running, "a");
+        let standby = IndexerModel { standby_for: Some(removed.id), ..indexer("removed", IndexerStatus::Running, "a") };
+        let already_archived = indexer("already_archived", IndexerStatus::Stopped, "a");
+        let readded = IndexerModel { archived_at: Some(Utc::now()), ..indexer("readded", IndexerStatus::Stopped, "a") };
+        let manifest = GitOpsManifest {
+            indexers: vec![
+                spec("unchanged"),
+                spec("new_script"),
+                GitOpsIndexerSpec { priority: 10, ..spec("new_priority") },
+                spec("created"),
+                spec("readded"),
+            ],
+        };
+        let script_checksums: HashMap<String, String> = [
+            ("unchanged".to_string(), "a".to_string()),
+            ("new_script".to_string(), "b".to_string()),
+            ("new_priority".to_string(), "a".to_string()),
+            ("created".to_string(), "a".to_string()),
+            ("readded".to_string(), "a".to_string()),
+        ]
+        .into();
+
+        let changes = plan_sync(
+            &manifest,
+            &[
+                unchanged,
+                new_script.clone(),
+                new_priority.clone(),
+                removed.clone(),
+                already_archived,
+                standby,
+                readded.clone(),
+            ],
+            &script_checksums,
+        );
+        let summary: Vec<(&str, GitOpsAction, Option<Uuid>, &[GitOpsField])> = changes
+            .iter()
+            .map(|change| (change.name.as_str(), change.action, change.indexer_id, change.fields.as_slice()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("new_script", GitOpsAction::Update, Some(new_script.id), &[GitOpsField::Script][..]),
+                ("new_priority", GitOpsAction::Update, Some(new_priority.id), &[GitOpsField::Priority][..]),
+                ("created", GitOpsAction::Create, None, &[][..]),
+                ("readded", GitOpsAction::Restore, Some(readded.id), &[][..]),
+                ("removed", GitOpsAction::Archive, Some(removed.id), &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_create_fields() {
+        let spec = GitOpsIndexerSpec {
+            target_url: Some("https://example.com".into()),
+            script_params: [("NETWORK".to_string(), "mainnet".to_string())].into(),
+            ..spec("transfers")
+        };
+        let fields = spec.to_create_fields("https://raw.githubusercontent.com/org/repo/main/transfers.js");
+        assert_eq!(fields["indexer_id"], "transfers");
+        assert_eq!(fields["indexer_type"], "Webhook");
+        assert_eq!(fields["script_params"]["NETWORK"], "mainnet");
+        assert!(fields["table_name"].is_null());
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString, IntoEnumIterator};
+use uuid::Uuid;
+
+use crate::domain::models::audit::AuditLogModel;
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+use crate::domain::models::usage::UsageRecordModel;
+
+/// Prefix of the metrics counting the indexers in a status, e.g. `indexers.Running`
+const INDEXERS_METRIC_PREFIX: &str = "indexers.";
+/// Label value of the series aggregating the values above the series budget
+pub const OTHER_LABEL_VALUE: &str = "__other__";
+/// Label value of the indexers created without a tenant
+const NO_TENANT_LABEL_VALUE: &str = "__none__";
+
+/// Metrics served to the Grafana JSON datasource
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GrafanaMetric {
+    /// Indexers currently in the status, the service keeps no history of the counts
+    Indexers(IndexerStatus),
+    /// Metered usage of the indexers by day, see `UsageRecordModel`
+    BlocksProcessed,
+    RunningSeconds,
+    LogBytes,
+    StreamedBytes,
+    DeliveredBytes,
+}
+
+impl GrafanaMetric {
+    pub fn all() -> Vec<Self> {
+        let mut metrics: Vec<Self> = IndexerStatus::iter().map(Self::Indexers).collect();
+        metrics.extend([
+            Self::BlocksProcessed,
+            Self::RunningSeconds,
+            Self::LogBytes,
+            Self::StreamedBytes,
+            Self::DeliveredBytes,
+        ]);
+        metrics
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Indexers(status) => format!("{}{}", INDEXERS_METRIC_PREFIX, status),
+            Self::BlocksProcessed => "usage.blocks_processed".into(),
+            Self::RunningSeconds => "usage.running_seconds".into(),
+            Self::LogBytes => "usage.log_bytes".into(),
+            Self::StreamedBytes => "usage.streamed_bytes".into(),
+            Self::DeliveredBytes => "usage.delivered_bytes".into(),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some(status) = name.strip_prefix(INDEXERS_METRIC_PREFIX) {
+            return IndexerStatus::from_str(status).ok().map(Self::Indexers);
+        }
+        Self::all().into_iter().find(|metric| metric.name() == name)
+    }
+
+    /// Value of the usage metric in the record, `None` for the other metrics
+    fn usage(&self, record: &UsageRecordModel) -> Option<i64> {
+        match self {
+            Self::Indexers(_) => None,
+            Self::BlocksProcessed => Some(record.blocks_processed),
+            Self::RunningSeconds => Some(record.running_seconds),
+            Self::LogBytes => Some(record.log_bytes),
+            Self::StreamedBytes => Some(record.streamed_bytes),
+            Self::DeliveredBytes => Some(record.delivered_bytes),
+        }
+    }
+}
+
+/// Label the series of a metric are split by, e.g. `usage.blocks_processed{tenant="acme"}`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricLabel {
+    Indexer,
+    Tenant,
+    Type,
+}
+
+impl MetricLabel {
+    pub fn value(&self, indexer: &IndexerModel) -> String {
+        match self {
+            Self::Indexer => indexer.id.to_string(),
+            Self::Tenant => indexer.tenant_id.clone().unwrap_or_else(|| NO_TENANT_LABEL_VALUE.to_string()),
+            Self::Type => indexer.indexer_type.to_string(),
+        }
+    }
+
+    /// Name of the series of the metric for a value of the label
+    pub fn series_name(&self, metric: &str, value: &str) -> String {
+        format!("{}{{{}=\"{}\"}}", metric, self, value)
+    }
+}
+
+/// Indexers of each series of a metric split by a label
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LabelGroups {
+    pub groups: BTreeMap<String, Vec<Uuid>>,
+    /// Values of the label before the aggregation
+    pub label_values: usize,
+    /// Values aggregated into `__other__`
+    pub aggregated_values: usize,
+}
+
+/// Groups the indexers by their value of the label. Above `max_series` values, the values with the
+/// fewest indexers are aggregated into the `__other__` series so that a metric never has more than
+/// `max_series` series.
+pub fn group_by_label(label: MetricLabel, max_series: usize, indexers: &[IndexerModel]) -> LabelGroups {
+    let mut groups: BTreeMap<String, Vec<Uuid>> = BTreeMap::new();
+    for indexer in indexers {
+        groups.entry(label.value(indexer)).or_default().push(indexer.id);
+    }
+    let label_values = groups.len();
+    let max_series = max_series.max(1);
+    if label_values <= max_series {
+        return LabelGroups { groups, label_values, aggregated_values: 0 };
+    }
+
+    // the largest values keep their series, ties are broken by the value to keep the series stable
+    let mut values: Vec<(String, Vec<Uuid>)> = groups.into_iter().collect();
+    values
+        .sort_by(|(value, ids), (other_value, other_ids)| other_ids.len().cmp(&ids.len()).then(value.cmp(other_value)));
+    let aggregated = values.split_off(max_series - 1);
+    let aggregated_values = aggregated.len();
+    let mut groups: BTreeMap<String, Vec<Uuid>> = values.into_iter().collect();
+    groups.insert(OTHER_LABEL_VALUE.to_string(), aggregated.into_iter().flat_map(|(_, ids)| ids).collect());
+    LabelGroups { groups, label_values, aggregated_values }
+}
+
+/// Series served to the datasource with the current label and budget
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricSeriesReport {
+    /// Series are not split if not set
+    pub label: Option<MetricLabel>,
+    pub max_series: usize,
+    pub label_values: usize,
+    pub aggregated_label_values: usize,
+    pub series_per_metric: usize,
+    pub metrics: usize,
+    pub total_series: usize,
+}
+
+/// Entry of the metric list of the datasource
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GrafanaMetricOption {
+    pub label: String,
+    pub value: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct GrafanaRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl GrafanaRange {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from <= at && at < self.to
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct GrafanaTargetPayload {
+    /// Restricts the usage metrics to an indexer
+    pub indexer_id: Option<Uuid>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct GrafanaTarget {
+    pub target: String,
+    #[serde(default, rename = "refId")]
+    pub ref_id: Option<String>,
+    /// Panels built with older versions of the plugin send an empty string
+    #[serde(default, deserialize_with = "deserialize_payload")]
+    pub payload: GrafanaTargetPayload,
+}
+
+fn deserialize_payload<'de, D>(deserializer: D) -> Result<GrafanaTargetPayload, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Object(payload) => {
+            serde_json::from_value(serde_json::Value::Object(payload)).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(GrafanaTargetPayload::default()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct GrafanaQueryRequest {
+    pub range: GrafanaRange,
+    pub targets: Vec<GrafanaTarget>,
+}
+
+/// Series of a target, datapoints are `[value, timestamp in milliseconds]`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GrafanaSeries {
+    pub target: String,
+    #[serde(rename = "refId", skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct GrafanaAnnotationQuery {
+    /// Id of the indexer to annotate with, every indexer if empty
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct GrafanaAnnotationRequest {
+    pub range: GrafanaRange,
+    #[serde(default)]
+    pub annotation: GrafanaAnnotationQuery,
+}
+
+impl GrafanaAnnotationRequest {
+    /// Indexer the annotations are restricted to, the query must be empty or an indexer id
+    pub fn indexer_id(&self) -> Result<Option<Uuid>, String> {
+        match self.annotation.query.as_deref().map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(query) => Uuid::parse_str(query).map(Some).map_err(|_| format!("{} is not an indexer id", query)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GrafanaAnnotation {
+    /// Milliseconds since the epoch
+    pub time: i64,
+    pub title: String,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+impl From<&AuditLogModel> for GrafanaAnnotation {
+    fn from(log: &AuditLogModel) -> Self {
+        let mut text = match (log.from_status, log.to_status) {
+            (Some(from_status), Some(to_status)) => format!("{} -> {}", from_status, to_status),
+            (None, Some(to_status)) => format!("-> {}", to_status),
+            _ => String::new(),
+        };
+        if let Some(reason) = &log.reason {
+            if !text.is_empty() {
+                text.push_str(": ");
+            }
+            text.push_str(reason);
+        }
+        Self {
+            time: log.created_at.timestamp_millis(),
+            title: format!("{} of indexer {}", log.action, log.indexer_id),
+            text,
+            tags: vec![log.action.to_string(), log.severity.to_string(), log.indexer_id.to_string()],
+        }
+    }
+}
+
+/// Sums the usage metric of the records by day, each day is a datapoint at its midnight.
+/// Days which start out of the range are dropped.
+pub fn get_usage_datapoints(
+    metric: GrafanaMetric,
+    records: &[UsageRecordModel],
+    range: GrafanaRange,
+) -> Vec<(f64, i64)> {
+    let mut days = BTreeMap::new();
+    for record in records {
+        let Some(value) = metric.usage(record) else {
+            continue;
+        };
+        *days.entry(record.day).or_insert(0) += value;
+    }
+    days.into_iter()
+        .map(|(day, value)| (day.and_time(NaiveTime::MIN).and_utc(), value))
+        .filter(|(at, _)| range.contains(*at))
+        .map(|(at, value)| (value as f64, at.timestamp_millis()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+    use crate::domain::models::audit::{AuditAction, AuditSeverity};
+
+    #[test]
+    fn test_grafana_metric_names() {
+        for metric in GrafanaMetric::all() {
+            assert_eq!(GrafanaMetric::parse(&metric.name()), Some(metric));
+        }
+        assert_eq!(
+            GrafanaMetric::parse("indexers.FailedRunning"),
+            Some(GrafanaMetric::Indexers(IndexerStatus::FailedRunning))
+        );
+        assert_eq!(GrafanaMetric::parse("indexers.Unknown"), None);
+        assert_eq!(GrafanaMetric::parse("usage.script_bytes"), None);
+    }
+
+    #[test]
+    fn test_get_usage_datapoints() {
+        let record = |indexer_id: Uuid, day: u32, blocks_processed: i64| UsageRecordModel {
+            tenant_id: "tenant".into(),
+            indexer_id,
+            day: NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+            running_seconds: 60,
+            blocks_processed,
+            script_bytes: 0,
+            log_bytes: 0,
+            streamed_bytes: 0,
+            delivered_bytes: 0,
+        };
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let records = vec![record(first, 1, 10), record(second, 1, 5), record(first, 2, 7), record(first, 4, 1)];
+        let range = GrafanaRange {
+            from: Utc.with_ymd_and_hms(2025, 9, 1, 0, 0, 0).unwrap(),
+            to: Utc.with_ymd_and_hms(2025, 9, 3, 12, 0, 0).unwrap(),
+        };
+        let day = |day: u32| Utc.with_ymd_and_hms(2025, 9, day, 0, 0, 0).unwrap().timestamp_millis();
+
+        assert_eq!(
+            get_usage_datapoints(GrafanaMetric::BlocksProcessed, &records, range),
+            vec![(15.0, day(1)), (7.0, day(2))]
+        );
+        assert_eq!(
+            get_usage_datapoints(GrafanaMetric::RunningSeconds, &records, range),
+            vec![(120.0, day(1)), (60.0, day(2))]
+        );
+        assert!(get_usage_datapoints(GrafanaMetric::Indexers(IndexerStatus::Running), &records, range).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_label() {
+        let indexer = |tenant_id: Option<&str>| IndexerModel {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.map(String::from),
+            ..IndexerModel::default()
+        };
+        let indexers = vec![
+            indexer(Some("acme")),
+            indexer(Some("acme")),
+            indexer(Some("acme")),
+            indexer(Some("globex")),
+            indexer(Some("globex")),
+            indexer(Some("initech")),
+            indexer(None),
+        ];
+
+        let groups = group_by_label(MetricLabel::Tenant, 10, &indexers);
+        assert_eq!(groups.label_values, 4);
+        assert_eq!(groups.aggregated_values, 0);
+        assert_eq!(groups.groups["acme"].len(), 3);
+        assert_eq!(groups.groups["__none__"], vec![indexers[6].id]);
+
+        let groups = group_by_label(MetricLabel::Tenant, 3, &indexers);
+        assert_eq!(groups.label_values, 4);
+        assert_eq!(groups.aggregated_values, 2);
+        assert_eq!(groups.groups.keys().collect::<Vec<_>>(), vec!["__other__", "acme", "globex"]);
+        assert_eq!(groups.groups[OTHER_LABEL_VALUE], vec![indexers[6].id, indexers[5].id]);
+
+        // a budget of 0 still serves a series
+        let groups = group_by_label(MetricLabel::Indexer, 0, &indexers);
+        assert_eq!(groups.groups.len(), 1);
+        assert_eq!(groups.groups[OTHER_LABEL_VALUE].len(), indexers.len());
+
+        assert_eq!(MetricLabel::Tenant.series_name("usage.log_bytes", "acme"), r#"usage.log_bytes{tenant="acme"}"#);
+    }
+
+    #[rstest]
+    #[case(json!({ "range": { "from": "2025-09-01T00:00:00Z", "to": "2025-09-02T00:00:00Z" }, "annotation": { "query": "" } }), Ok(None))]
+    #[case(json!({ "range": { "from": "2025-09-01T00:00:00Z", "to": "2025-09-02T00:00:00Z" } }), Ok(None))]
+    #[case(json!({ "range": { "from": "2025-09-01T00:00:00Z", "to": "2025-09-02T00:00:00Z" }, "annotation": { "query": "abc" } }), Err(()))]
+    fn test_annotation_request_indexer_id(
+        #[case] request: serde_json::Value,
+        #[case] expected: Result<Option<Uuid>, ()>,
+    ) {
+        let request: GrafanaAnnotationRequest = serde_json::from_value(request).unwrap();
+        assert_eq!(request.indexer_id().map_err(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_query_request_payload() {
+        let indexer_id = Uuid::new_v4();
+        let request: GrafanaQueryRequest = serde_json::from_value(json!({
+            "range": { "from": "2025-09-01T00:00:00Z", "to": "2025-09-02T00:00:00Z" },
+            "targets": [
+                { "target": "usage.blocks_processed", "refId": "A", "payload": { "indexer_id": indexer_id } },
+                { "target": "indexers.Running", "refId": "B", "payload": "" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(request.targets[0].payload.indexer_id, Some(indexer_id));
+        assert_eq!(request.targets[1].payload, GrafanaTargetPayload::default());
+    }
+
+    #[test]
+    fn test_annotation_from_audit_log() {
+        let log = AuditLogModel {
+            id: Uuid::new_v4(),
+            indexer_id: Uuid::new_v4(),
+            action: AuditAction::StatusChange,
+            from_status: Some(IndexerStatus::Running),
+            to_status: Some(IndexerStatus::FailedRunning),
+            reason: Some("process exited".into()),
+            created_at: Utc.with_ymd_and_hms(2025, 9, 1, 0, 0, 0).unwrap(),
+            actor: None,
+            severity: AuditSeverity::Critical,
+            details: None,
+            correlation_id: None,
+        };
+        let annotation = GrafanaAnnotation::from(&log);
+        assert_eq!(annotation.time, log.created_at.timestamp_millis());
+        assert_eq!(annotation.text, "Running -> FailedRunning: process exited");
+        assert_eq!(annotation.tags, vec!["StatusChange".to_string(), "Critical".into(), log.indexer_id.to_string()]);
+    }
+}
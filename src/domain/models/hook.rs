@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+use uuid::Uuid;
+
+use crate::constants::indexers::{
+    DEFAULT_HOOK_TIMEOUT_SECONDS, MAX_HOOK_COMMAND_ARGS, MAX_HOOK_COMMAND_ARG_LENGTH, MAX_HOOK_TIMEOUT_SECONDS,
+};
+
+/// Hooks run around the lifecycle of an indexer, e.g. to create the downstream table before it
+/// starts or to flush caches once it stopped
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexerHooks {
+    pub pre_start: Option<LifecycleHook>,
+    pub post_stop: Option<LifecycleHook>,
+}
+
+impl IndexerHooks {
+    pub fn get(&self, stage: HookStage) -> Option<&LifecycleHook> {
+        match stage {
+            HookStage::PreStart => self.pre_start.as_ref(),
+            HookStage::PostStop => self.post_stop.as_ref(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleHook {
+    pub action: HookAction,
+    /// Falls back to the default hook timeout if not set, at most `MAX_HOOK_TIMEOUT_SECONDS`
+    pub timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub failure_policy: HookFailurePolicy,
+}
+
+impl LifecycleHook {
+    pub fn validate_timeout(&self) -> Result<(), String> {
+        if self.timeout_seconds.is_some_and(|seconds| seconds == 0 || seconds > MAX_HOOK_TIMEOUT_SECONDS) {
+            return Err(format!("timeout_seconds must be between 1 and {}", MAX_HOOK_TIMEOUT_SECONDS));
+        }
+        Ok(())
+    }
+
+    /// Hooks saved before the maximum was enforced are held to it as well
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECONDS).min(MAX_HOOK_TIMEOUT_SECONDS))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Signed POST of a `HookEvent`, any 2xx response is a success
+    Http { url: String },
+    /// Command from the allowlist of the service, run with `INDEXER_ID` and `HOOK_STAGE` set
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl HookAction {
+    /// Only the allowlisted commands can run, their args can't turn them into something else
+    /// through options (e.g. `sh -c`) or smuggle control characters
+    pub fn validate_args(args: &[String]) -> Result<(), String> {
+        if args.len() > MAX_HOOK_COMMAND_ARGS {
+            return Err(format!("at most {} args are allowed", MAX_HOOK_COMMAND_ARGS));
+        }
+        for arg in args {
+            if arg.len() > MAX_HOOK_COMMAND_ARG_LENGTH {
+                return Err(format!("args must be at most {} bytes", MAX_HOOK_COMMAND_ARG_LENGTH));
+            }
+            if arg.starts_with('-') {
+                return Err(format!("option {} is not allowed", arg));
+            }
+            if arg.chars().any(char::is_control) {
+                return Err("args can't contain control characters".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// Fails the operation. Only pre-start hooks can abort, post-stop hooks run once the indexer
+    /// is stopped and their failures are logged and audited like with `Continue`.
+    Abort,
+    #[default]
+    Continue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HookStage {
+    PreStart,
+    PostStop,
+}
+
+/// Body of the HTTP hooks
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HookEvent {
+    /// Version of the schema published at `/v1/schemas/events/<version>`
+    pub schema_version: String,
+    pub indexer_id: Uuid,
+    pub stage: HookStage,
+}
+
+/// Outcome of a hook, recorded in the details of its audit log
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HookResult {
+    pub stage: HookStage,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Status code or exit code with the error, if any
+    pub output: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_hooks() {
+        let hooks: IndexerHooks = serde_json::from_str(
+            r#"{
+                "pre_start": {"action": {"type": "command", "command": "create-table"}, "failure_policy": "abort"},
+                "post_stop": {"action": {"type": "http", "url": "https://example.com/flush"}, "timeout_seconds": 5}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hooks.get(HookStage::PreStart),
+            Some(&LifecycleHook {
+                action: HookAction::Command { command: "create-table".into(), args: vec![] },
+                timeout_seconds: None,
+                failure_policy: HookFailurePolicy::Abort,
+            })
+        );
+        assert_eq!(hooks.get(HookStage::PostStop).unwrap().failure_policy, HookFailurePolicy::Continue);
+        assert_eq!(serde_json::from_str::<IndexerHooks>("{}").unwrap(), IndexerHooks::default());
+    }
+
+    #[test]
+    fn test_validate_args() {
+        assert!(HookAction::validate_args(&[]).is_ok());
+        assert!(HookAction::validate_args(&["transfers".into(), "mainnet".into()]).is_ok());
+        assert!(HookAction::validate_args(&["-c".into(), "rm -rf /".into()]).is_err());
+        assert!(HookAction::validate_args(&["--output=/etc/passwd".into()]).is_err());
+        assert!(HookAction::validate_args(&["transfers\nmainnet".into()]).is_err());
+        assert!(HookAction::validate_args(&["a".repeat(MAX_HOOK_COMMAND_ARG_LENGTH + 1)]).is_err());
+        assert!(HookAction::validate_args(&vec!["a".to_string(); MAX_HOOK_COMMAND_ARGS + 1]).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let hook = |timeout_seconds: Option<u64>| LifecycleHook {
+            action: HookAction::Http { url: "https://example.com/flush".into() },
+            timeout_seconds,
+            failure_policy: HookFailurePolicy::Continue,
+        };
+        assert!(hook(None).validate_timeout().is_ok());
+        assert!(hook(Some(MAX_HOOK_TIMEOUT_SECONDS)).validate_timeout().is_ok());
+        assert!(hook(Some(0)).validate_timeout().is_err());
+        assert!(hook(Some(MAX_HOOK_TIMEOUT_SECONDS + 1)).validate_timeout().is_err());
+
+        assert_eq!(hook(None).timeout(), Duration::from_secs(DEFAULT_HOOK_TIMEOUT_SECONDS));
+        assert_eq!(hook(Some(5)).timeout(), Duration::from_secs(5));
+        assert_eq!(hook(Some(u64::MAX)).timeout(), Duration::from_secs(MAX_HOOK_TIMEOUT_SECONDS));
+    }
+}
@@ -1,31 +1,121 @@
+use std::collections::BTreeMap;
+
 use axum::extract::multipart::MultipartError;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
+use chrono::{DateTime, Utc};
 use object_store::Error;
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, EnumString};
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 use uuid::Uuid;
 
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::hook::{HookStage, IndexerHooks};
+use crate::domain::models::process_priority::ProcessPriority;
+use crate::domain::models::project::ProjectRole;
+use crate::domain::models::secret::SecretBackendKind;
+use crate::domain::models::sink_options::SinkOptions;
 use crate::domain::models::types::AxumErrorResponse;
+use crate::domain::models::upload::UploadError;
 use crate::grpc::apibara_sink_v1::GetStatusResponse;
 use crate::infra::errors::InfraError;
 
-#[derive(Clone, Default, Debug, PartialEq, EnumString, Serialize, Deserialize, Display, Copy)]
+#[derive(Clone, Default, Debug, PartialEq, EnumString, EnumIter, Serialize, Deserialize, Display, Copy)]
 pub enum IndexerStatus {
     #[default]
     Created,
+    /// Its process was started, it moves to `Running` once the sink processed a block or its
+    /// process survived the readiness window
+    Starting,
     Running,
     Stopped,
     FailedRunning,
     FailedStopping,
+    /// Never left `Created`, e.g. its start was lost. Set by the sweep of stale indexers.
+    Abandoned,
+    /// Targets a webhook destination the tenant requires an admin to approve first
+    PendingApproval,
+}
+
+impl IndexerStatus {
+    /// Statuses an admin is allowed to force an indexer into. We never force an indexer into
+    /// `Running` or `Created` as those must be backed by an actual start of the process, and
+    /// indexers pending approval only leave it once approved.
+    pub fn can_force_to(&self, new_status: IndexerStatus) -> bool {
+        if *self == new_status || *self == IndexerStatus::PendingApproval {
+            return false;
+        }
+        matches!(new_status, IndexerStatus::Stopped | IndexerStatus::FailedRunning | IndexerStatus::FailedStopping)
+    }
+
+    /// Whether a process was started for the indexer and is expected to run
+    pub fn is_live(&self) -> bool {
+        matches!(self, Self::Starting | Self::Running)
+    }
+
+    /// Statuses reporting that something went wrong with the indexer
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::FailedRunning | Self::FailedStopping)
+    }
+
+    /// Statuses an indexer is started from without checking on its process first
+    pub fn is_startable(&self) -> bool {
+        matches!(self, Self::Created | Self::Stopped | Self::FailedRunning | Self::Abandoned)
+    }
+
+    /// Current status of a value stored before the state machine rework. Transient statuses
+    /// were only set while the service was acting on the process, finding one means it never
+    /// completed.
+    pub fn from_legacy(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "created" => Some(Self::Created),
+            "running" => Some(Self::Running),
+            "stopped" => Some(Self::Stopped),
+            "failedrunning" | "failed_running" | "failed" | "starting" | "failedstarting" => Some(Self::FailedRunning),
+            "failedstopping" | "failed_stopping" | "stopping" => Some(Self::FailedStopping),
+            "abandoned" => Some(Self::Abandoned),
+            "pendingapproval" | "pending_approval" => Some(Self::PendingApproval),
+            _ => None,
+        }
+    }
+}
+
+impl IndexerType {
+    /// Current type of a value stored before the types were normalized
+    pub fn from_legacy(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "webhook" => Some(Self::Webhook),
+            "postgres" | "postgresql" => Some(Self::Postgres),
+            "parquet" => Some(Self::Parquet),
+            "console" => Some(Self::Console),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone, Default, Debug, PartialEq, EnumString, Serialize, Deserialize, Display)]
+#[derive(Clone, Default, Debug, PartialEq, EnumString, EnumIter, Serialize, Deserialize, Display)]
 pub enum IndexerType {
     #[default]
     Webhook,
     Postgres,
+    /// Writes the data as Parquet files to an S3 bucket
+    Parquet,
+    /// Prints the data to its stdout, which is kept by the service to debug scripts
+    Console,
+}
+
+/// Log level of the sink process, passed to the sink as `RUST_LOG`. Levels are ordered from the
+/// most severe.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, EnumString, Serialize, Deserialize, Display, Copy)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum IndexerLogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
@@ -33,13 +123,143 @@ pub struct IndexerModel {
     pub id: Uuid,
     pub status: IndexerStatus,
     pub indexer_type: IndexerType,
-    pub process_id: Option<i64>,
+    pub execution_ref: Option<ExecutionRef>,
+    /// Bumped each time a process is started for the indexer
+    pub execution_seq: i64,
     pub target_url: Option<String>,
     pub table_name: Option<String>,
     pub status_server_port: Option<i32>,
     pub custom_connection_string: Option<String>,
     pub starting_block: Option<i64>,
     pub indexer_id: Option<String>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub launch_config: Option<LaunchConfig>,
+    /// Set on passive replicas, the id of the indexer they take over from
+    pub standby_for: Option<Uuid>,
+    pub script_permissions: ScriptPermissions,
+    pub tenant_id: Option<String>,
+    /// Overrides the stream url of the script
+    pub stream_url: Option<String>,
+    /// The sink stops once it reaches this block, e.g. for backfills
+    pub ending_block: Option<i64>,
+    /// Set on indexers filling a gap in the deliveries of another indexer
+    pub backfill_for: Option<Uuid>,
+    /// Values of the `{{params.name}}` placeholders of the script, resolved at each start
+    pub script_params: BTreeMap<String, String>,
+    /// Sha256 of the script as uploaded, before its params are resolved
+    pub script_checksum: Option<String>,
+    pub hooks: IndexerHooks,
+    pub sink_options: Option<SinkOptions>,
+    /// Indexers with a higher priority are started first when the service starts
+    pub priority: i32,
+    pub process_priority: ProcessPriority,
+    /// Url the script was fetched from when it wasn't uploaded
+    pub script_source_url: Option<String>,
+    /// Project of the tenant the indexer is grouped in
+    pub project_id: Option<Uuid>,
+    /// Where the sinks writing files put them, as `s3://<bucket>/<prefix>`
+    pub output_location: Option<String>,
+    /// What created the indexer and keeps it in sync, e.g. `gitops`, not set for the indexers of
+    /// the API
+    pub managed_by: Option<String>,
+    /// When its manager stopped the indexer as it was removed from the desired state
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Set by the database on every change of the row
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Permissions granted to the script by the deno runtime of the sink, anything not listed is
+/// denied
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptPermissions {
+    pub allow_net: Vec<String>,
+    pub allow_read: Vec<String>,
+    pub allow_env: Vec<String>,
+}
+
+/// Where the sink starts streaming from when an indexer is started
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum StartPosition {
+    /// Resumes from the cursor persisted for the sink id, new sinks start at their starting block
+    #[default]
+    PersistedCursor,
+    /// Skips the backlog and starts at the head of the stream
+    Latest,
+    Block {
+        block: u64,
+    },
+}
+
+impl std::fmt::Display for StartPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PersistedCursor => write!(f, "persisted cursor"),
+            Self::Latest => write!(f, "latest block"),
+            Self::Block { block } => write!(f, "block {}", block),
+        }
+    }
+}
+
+/// Settings of an indexer recorded along with its events, the connection string is left out
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexerConfig {
+    pub indexer_type: IndexerType,
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub starting_block: Option<i64>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub launch_config: Option<LaunchConfig>,
+    pub script_permissions: ScriptPermissions,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    #[serde(default)]
+    pub script_params: BTreeMap<String, String>,
+    #[serde(default)]
+    pub hooks: IndexerHooks,
+    #[serde(default)]
+    pub process_priority: ProcessPriority,
+}
+
+impl From<&IndexerModel> for IndexerConfig {
+    fn from(value: &IndexerModel) -> Self {
+        Self {
+            indexer_type: value.indexer_type.clone(),
+            target_url: value.target_url.clone(),
+            table_name: value.table_name.clone(),
+            starting_block: value.starting_block,
+            log_level: value.log_level,
+            launch_config: value.launch_config.clone(),
+            script_permissions: value.script_permissions.clone(),
+            stream_url: value.stream_url.clone(),
+            ending_block: value.ending_block,
+            script_params: value.script_params.clone(),
+            hooks: value.hooks.clone(),
+            process_priority: value.process_priority,
+        }
+    }
+}
+
+/// State of an indexer at a point in time, reconstructed from its audit logs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexerStateModel {
+    pub indexer_id: Uuid,
+    pub at: DateTime<Utc>,
+    pub status: IndexerStatus,
+    pub status_changed_at: DateTime<Utc>,
+    pub config: Option<IndexerConfig>,
+    pub config_recorded_at: Option<DateTime<Utc>>,
+}
+
+/// Configuration a sink process was launched with. Secrets are not stored, the options are
+/// only kept as a checksum.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaunchConfig {
+    pub script_checksum: String,
+    pub options_checksum: String,
+    pub env: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
@@ -52,6 +272,17 @@ pub struct IndexerServerStatus {
     pub reason_: Option<String>,
 }
 
+impl IndexerServerStatus {
+    /// Whether the sink got past the block it started from
+    pub fn has_processed_block(&self) -> bool {
+        match (self.current_block, self.starting_block) {
+            (Some(current_block), Some(starting_block)) => current_block > starting_block,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
 impl From<GetStatusResponse> for IndexerServerStatus {
     fn from(value: GetStatusResponse) -> Self {
         Self {
@@ -64,7 +295,8 @@ impl From<GetStatusResponse> for IndexerServerStatus {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, IntoStaticStr)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum IndexerError {
     #[error("internal server error: {0}")]
     InternalServerError(String),
@@ -72,14 +304,34 @@ pub enum IndexerError {
     InfraError(InfraError),
     #[error("failed to read file from multipart request")]
     FailedToReadMultipartField(MultipartError),
-    #[error("unexpected field in multipart request : {0}")]
+    #[error("invalid request body : {0}")]
+    InvalidRequestBody(String),
+    #[error("unexpected field in create request : {0}")]
     UnexpectedMultipartField(String),
-    #[error("failed to build create indexer request")]
-    FailedToBuildCreateIndexerRequest,
+    #[error("field {0} is sent more than once")]
+    DuplicateMultipartField(String),
+    #[error("field {0} doesn't apply to {1} indexers")]
+    MultipartFieldNotApplicable(String, IndexerType),
+    #[error("missing fields: {}", .0.join(", "))]
+    MissingMultipartFields(Vec<String>),
+    #[error("invalid field {0}: {1}")]
+    InvalidMultipartField(String, String),
+    #[error("invalid script url {0}")]
+    InvalidScriptUrl(String),
+    #[error("failed to fetch script from {0}: {1}")]
+    FailedToFetchScript(String, String),
+    #[error("script checksum {1} doesn't match the pinned checksum {0}")]
+    ScriptChecksumMismatch(String, String),
+    #[error("invalid GitOps manifest {0}")]
+    InvalidGitOpsManifest(String),
     #[error("failed to create file : {0}")]
     FailedToCreateFile(std::io::Error),
+    #[error("failed to read file : {0}")]
+    FailedToReadFile(std::io::Error),
     #[error("failed to stop indexer : {0}")]
-    FailedToStopIndexer(i64),
+    FailedToStopIndexer(ExecutionRef),
+    #[error("{0} can't be managed by the process backend")]
+    UnsupportedExecution(ExecutionRef),
     #[error("failed to start indexer : {0} (id: {1})")]
     FailedToStartIndexer(String, String),
     #[error("failed to upload to object_store")]
@@ -88,14 +340,140 @@ pub enum IndexerError {
     FailedToGetFromStore(Error),
     #[error("failed to get collect bytes from object_store")]
     FailedToCollectBytesFromStore(Error),
+    #[error("failed to get upload : {0}")]
+    FailedToGetUpload(UploadError),
     #[error("invalid indexer status")]
     InvalidIndexerStatus(IndexerStatus),
+    #[error("cannot force indexer from {0} to {1}")]
+    InvalidForcedTransition(IndexerStatus, IndexerStatus),
+    #[error("a reason is required")]
+    MissingReason,
+    #[error("the status of indexer {0} changed from {1} in the meantime")]
+    StatusChanged(Uuid, IndexerStatus),
+    #[error("invalid target url {0}")]
+    InvalidTargetUrl(String),
+    #[error("target url {0} is not allowed")]
+    TargetUrlNotAllowed(String),
+    #[error("stream url {0} is not allowed")]
+    StreamUrlNotAllowed(String),
+    #[error("indexer {0} shares its sink with other indexers, stop it before changing its process priority")]
+    SharedSinkPriority(Uuid),
+    #[error("indexer {0} shares its sink with other indexers, it can only start from its persisted cursor")]
+    SharedSinkStartPosition(Uuid),
     #[error("failed to query db")]
     FailedToQueryDb(diesel::result::Error),
     #[error("invalid indexer type {0}")]
     InvalidIndexerType(String),
+    #[error("invalid script permissions {0}")]
+    InvalidScriptPermissions(String),
+    #[error("script permission {0} is not allowed")]
+    ScriptPermissionNotAllowed(String),
+    #[error("indexer type {0} is not allowed for tenant {1}")]
+    IndexerTypeNotAllowed(IndexerType, String),
+    #[error("target {0} is not approved for tenant {1}")]
+    TargetNotApproved(String, String),
+    #[error("secret backend {0} is not allowed for tenant {1}")]
+    SecretBackendNotAllowed(SecretBackendKind, String),
+    #[error("failed to resolve secret {0}: {1}")]
+    FailedToResolveSecret(String, String),
+    #[error("project {0} not found")]
+    ProjectNotFound(Uuid),
+    #[error("the {1} role on project {0} is required")]
+    ProjectAccessDenied(Uuid, ProjectRole),
+    #[error("project {0} belongs to another tenant than {1}")]
+    ProjectOfAnotherTenant(Uuid, String),
+    #[error("the caller can't act for tenant {0}")]
+    ForeignTenant(String),
+    #[error("the caller can't act on indexer {0}")]
+    IndexerAccessDenied(Uuid),
+    #[error("secret {0} is out of the secrets scope {1} of the indexer")]
+    SecretOutOfScope(String, String),
+    #[error("failed to get tenant settings : {0}")]
+    FailedToGetTenantSettings(InfraError),
+    #[error("indexer {0} already has a standby")]
+    StandbyAlreadyExists(Uuid),
+    #[error("indexer {0} is a standby")]
+    IndexerIsStandby(Uuid),
+    #[error("too many previews or estimates are running, try again later")]
+    TooManyPreviews,
+    #[error("tenant {0} reached its start limit, try again in {1}s")]
+    StartLimitReached(String, u64),
+    #[error("estimates run the filter on the stream, only tenants and admins can request them")]
+    EstimateNotAllowed,
+    #[error("invalid block range: {0}")]
+    InvalidBlockRange(String),
+    #[error("script params {0:?} are not set")]
+    MissingScriptParams(Vec<String>),
+    #[error("invalid script params {0}")]
+    InvalidScriptParams(String),
+    #[error("latest block of indexer {0} is unknown, no running indexer reports the head of its stream")]
+    LatestBlockUnknown(Uuid),
+    #[error("script of indexer {0} is missing from the store or doesn't match its checksum")]
+    ScriptMissing(Uuid),
+    #[error("invalid sink options: {0}")]
+    InvalidSinkOptions(String),
+    #[error("indexer {0} is a {1} indexer, only console indexers keep their output")]
+    NotAConsoleIndexer(Uuid, IndexerType),
+    #[error("no event schema for version {0}")]
+    EventSchemaNotFound(String),
+    #[error("invalid output location: {0}")]
+    InvalidOutputLocation(String),
+    #[error("invalid process priority: {0}")]
+    InvalidProcessPriority(String),
+    #[error("failed to apply the process priority: {0}")]
+    FailedToApplyProcessPriority(String),
+    #[error("invalid clone: {0}")]
+    InvalidClone(String),
+    #[error("invalid reconfiguration: {0}")]
+    InvalidReconfiguration(String),
+    #[error("indexer {0} was already started, it's {1}")]
+    CreationAlreadyComplete(Uuid, IndexerStatus),
+    #[error("invalid start token: {0}")]
+    InvalidStartToken(String),
+    #[error("start token is unknown, expired or already redeemed")]
+    StartTokenRejected,
+    #[error("start token {0} not found")]
+    StartTokenNotFound(Uuid),
+    #[error("invalid hooks {0}")]
+    InvalidHooks(String),
+    #[error("hook command {0} is not allowed")]
+    HookCommandNotAllowed(String),
+    #[error("{0} hook failed: {1}")]
+    HookFailed(HookStage, String),
+    #[error("no exit snapshot of indexer {0} at {1}")]
+    DiagnosticsNotFound(Uuid, i64),
+    #[error("invalid search query: {0}")]
+    InvalidSearchQuery(String),
+    #[error("invalid field selection {0}")]
+    InvalidFieldSelection(String),
+    #[error("invalid notification policy: {0}")]
+    InvalidNotificationPolicy(String),
+    #[error("indexer {0} has no notification policy")]
+    NotificationPolicyNotFound(Uuid),
+    #[error("invalid annotation: {0}")]
+    InvalidAnnotation(String),
+    #[error("annotation {0} not found")]
+    AnnotationNotFound(Uuid),
+    #[error("invalid export format: {0}")]
+    InvalidExportFormat(String),
+    #[error("invalid schedule query: {0}")]
+    InvalidScheduleQuery(String),
+    #[error("script has advisories at or above the blocking severity: {0}")]
+    VulnerableScript(String),
+    #[error("failed to query the advisories of the script: {0}")]
+    FailedToScanScript(String),
+    #[error("script of indexer {0} wasn't scanned")]
+    ScriptScanNotFound(Uuid),
+    #[error("no recorded state for indexer {0} at {1}")]
+    StateNotFound(Uuid, DateTime<Utc>),
+    #[error("invalid log level {0}")]
+    InvalidLogLevel(String),
     #[error("failed to serialize {0}")]
     FailedToSerialize(String),
+    #[error("warm start check failed: {0}")]
+    WarmStartFailed(String),
+    #[error("service can't start indexers: {}", .0.join(", "))]
+    Unschedulable(Vec<String>),
     #[error("indexer status server port not found")]
     IndexerStatusServerPortNotFound,
     #[error("failed to connect to gRPC server")]
@@ -113,13 +491,99 @@ impl From<diesel::result::Error> for IndexerError {
 impl IntoResponse for IndexerError {
     fn into_response(self) -> axum::response::Response {
         tracing::error!("Error: {:?}", self);
+        let retry_after = match &self {
+            Self::StartLimitReached(_, retry_after) => Some(*retry_after),
+            _ => None,
+        };
         let (status, err_msg) = match self {
+            Self::InfraError(InfraError::DatabaseBusy) | Self::FailedToGetTenantSettings(InfraError::DatabaseBusy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", InfraError::DatabaseBusy))
+            }
             Self::InfraError(db_error) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", db_error))
             }
+            Self::InvalidForcedTransition(_, _)
+            | Self::MissingReason
+            | Self::InvalidTargetUrl(_)
+            | Self::TargetUrlNotAllowed(_)
+            | Self::StreamUrlNotAllowed(_)
+            | Self::InvalidScriptPermissions(_)
+            | Self::ScriptPermissionNotAllowed(_)
+            | Self::IndexerTypeNotAllowed(_, _)
+            | Self::TargetNotApproved(_, _)
+            | Self::SecretBackendNotAllowed(_, _)
+            | Self::ProjectOfAnotherTenant(_, _)
+            | Self::SecretOutOfScope(_, _)
+            | Self::IndexerIsStandby(_)
+            | Self::InvalidBlockRange(_)
+            | Self::MissingScriptParams(_)
+            | Self::InvalidScriptParams(_)
+            | Self::InvalidHooks(_)
+            | Self::InvalidSinkOptions(_)
+            | Self::InvalidOutputLocation(_)
+            | Self::NotAConsoleIndexer(_, _)
+            | Self::InvalidReconfiguration(_)
+            | Self::InvalidClone(_)
+            | Self::InvalidProcessPriority(_)
+            | Self::InvalidSearchQuery(_)
+            | Self::InvalidFieldSelection(_)
+            | Self::InvalidNotificationPolicy(_)
+            | Self::InvalidAnnotation(_)
+            | Self::InvalidExportFormat(_)
+            | Self::InvalidScheduleQuery(_)
+            | Self::VulnerableScript(_)
+            | Self::InvalidRequestBody(_)
+            | Self::UnexpectedMultipartField(_)
+            | Self::DuplicateMultipartField(_)
+            | Self::MultipartFieldNotApplicable(_, _)
+            | Self::MissingMultipartFields(_)
+            | Self::InvalidMultipartField(_, _)
+            | Self::InvalidScriptUrl(_)
+            | Self::ScriptChecksumMismatch(_, _)
+            | Self::HookCommandNotAllowed(_)
+            | Self::InvalidStartToken(_)
+            | Self::CreationAlreadyComplete(_, _)
+            | Self::SharedSinkPriority(_)
+            | Self::SharedSinkStartPosition(_)
+            | Self::LatestBlockUnknown(_) => (StatusCode::BAD_REQUEST, format!("Bad request: {}", self)),
+            Self::FailedToGetUpload(
+                UploadError::SessionNotFound(_)
+                | UploadError::SessionCompleted(_)
+                | UploadError::MissingParts(_)
+                | UploadError::UploadNotComplete(_),
+            ) => (StatusCode::BAD_REQUEST, format!("Bad request: {}", self)),
+            Self::FailedToGetUpload(UploadError::AccessDenied(_)) => {
+                (StatusCode::FORBIDDEN, format!("Forbidden: {}", self))
+            }
+            Self::StartTokenRejected => (StatusCode::UNAUTHORIZED, format!("Unauthorized: {}", self)),
+            Self::TooManyPreviews | Self::StartLimitReached(_, _) => {
+                (StatusCode::TOO_MANY_REQUESTS, format!("Too many requests: {}", self))
+            }
+            Self::StandbyAlreadyExists(_) | Self::StatusChanged(_, _) => {
+                (StatusCode::CONFLICT, format!("Conflict: {}", self))
+            }
+            Self::ProjectAccessDenied(_, _)
+            | Self::ForeignTenant(_)
+            | Self::IndexerAccessDenied(_)
+            | Self::EstimateNotAllowed => (StatusCode::FORBIDDEN, format!("Forbidden: {}", self)),
+            Self::HookFailed(_, _)
+            | Self::FailedToFetchScript(_, _)
+            | Self::FailedToResolveSecret(_, _)
+            | Self::FailedToScanScript(_) => (StatusCode::BAD_GATEWAY, format!("Bad gateway: {}", self)),
+            Self::Unschedulable(_) | Self::WarmStartFailed(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", self))
+            }
+            Self::StateNotFound(_, _)
+            | Self::ScriptMissing(_)
+            | Self::DiagnosticsNotFound(_, _)
+            | Self::NotificationPolicyNotFound(_)
+            | Self::StartTokenNotFound(_)
+            | Self::ProjectNotFound(_)
+            | Self::EventSchemaNotFound(_)
+            | Self::ScriptScanNotFound(_) => (StatusCode::NOT_FOUND, format!("Not found: {}", self)),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", self)),
         };
-        (
+        let mut response = (
             status,
             Json(AxumErrorResponse {
                 resource: "IndexerModel".into(),
@@ -127,6 +591,93 @@ impl IntoResponse for IndexerError {
                 happened_at: chrono::Utc::now(),
             }),
         )
-            .into_response()
+            .into_response();
+        if let Some(retry_after) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_start_position() {
+        let parse = |body: &str| serde_json::from_str::<StartPosition>(body).unwrap();
+        assert_eq!(parse(r#"{"from": "persisted_cursor"}"#), StartPosition::PersistedCursor);
+        assert_eq!(parse(r#"{"from": "latest"}"#), StartPosition::Latest);
+        assert_eq!(parse(r#"{"from": "block", "block": 100}"#), StartPosition::Block { block: 100 });
+        assert!(serde_json::from_str::<StartPosition>(r#"{"from": "block"}"#).is_err());
+    }
+
+    #[rstest]
+    #[case(IndexerStatus::Running, IndexerStatus::Stopped, true)]
+    #[case(IndexerStatus::Running, IndexerStatus::FailedRunning, true)]
+    #[case(IndexerStatus::Stopped, IndexerStatus::FailedStopping, true)]
+    #[case(IndexerStatus::Stopped, IndexerStatus::Stopped, false)]
+    #[case(IndexerStatus::Stopped, IndexerStatus::Running, false)]
+    #[case(IndexerStatus::FailedRunning, IndexerStatus::Created, false)]
+    #[case(IndexerStatus::PendingApproval, IndexerStatus::Stopped, false)]
+    fn test_can_force_to(#[case] from: IndexerStatus, #[case] to: IndexerStatus, #[case] expected: bool) {
+        assert_eq!(from.can_force_to(to), expected);
+    }
+
+    #[rstest]
+    #[case(IndexerStatus::Created, true)]
+    #[case(IndexerStatus::Stopped, true)]
+    #[case(IndexerStatus::FailedRunning, true)]
+    #[case(IndexerStatus::Abandoned, true)]
+    #[case(IndexerStatus::Starting, false)]
+    #[case(IndexerStatus::Running, false)]
+    #[case(IndexerStatus::FailedStopping, false)]
+    #[case(IndexerStatus::PendingApproval, false)]
+    fn test_is_startable(#[case] status: IndexerStatus, #[case] expected: bool) {
+        assert_eq!(status.is_startable(), expected);
+    }
+
+    #[rstest]
+    #[case(IndexerStatus::Starting, true)]
+    #[case(IndexerStatus::Running, true)]
+    #[case(IndexerStatus::FailedRunning, false)]
+    #[case(IndexerStatus::FailedStopping, false)]
+    fn test_is_live(#[case] status: IndexerStatus, #[case] expected: bool) {
+        assert_eq!(status.is_live(), expected);
+    }
+
+    #[rstest]
+    #[case(Some(10), Some(11), true)]
+    #[case(Some(10), Some(10), false)]
+    #[case(None, Some(1), true)]
+    #[case(Some(10), None, false)]
+    fn test_has_processed_block(
+        #[case] starting_block: Option<u64>,
+        #[case] current_block: Option<u64>,
+        #[case] expected: bool,
+    ) {
+        let status = IndexerServerStatus { status: 0, starting_block, current_block, head_block: None, reason_: None };
+        assert_eq!(status.has_processed_block(), expected);
+    }
+
+    #[rstest]
+    #[case("running", Some(IndexerStatus::Running))]
+    #[case("Starting", Some(IndexerStatus::FailedRunning))]
+    #[case("STOPPING", Some(IndexerStatus::FailedStopping))]
+    #[case("Paused", None)]
+    fn test_status_from_legacy(#[case] value: &str, #[case] expected: Option<IndexerStatus>) {
+        assert_eq!(IndexerStatus::from_legacy(value), expected);
+    }
+
+    #[test]
+    fn test_start_limit_reached_tells_when_to_retry() {
+        let response = IndexerError::StartLimitReached("acme".into(), 12).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "12");
+
+        let response = IndexerError::TooManyPreviews.into_response();
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
     }
 }
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use strum_macros::Display;
+
+use crate::domain::models::indexer::{IndexerModel, LaunchConfig};
+
+/// How an update reached the process of the indexer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum UpdateDecision {
+    /// The effective launch config changed and the process was restarted with it
+    Restart,
+    /// The changes were stored without touching the process, either because they don't change
+    /// what it's launched with or because it isn't running. They apply from the next start.
+    HotApply,
+    /// Nothing differed from the current settings
+    NoOp,
+}
+
+impl UpdateDecision {
+    /// `updated` is whether any setting changed, `launch_changed` whether the effective launch
+    /// config changed with them
+    pub fn decide(updated: bool, launch_changed: bool, live: bool) -> Self {
+        match (updated, launch_changed && live) {
+            (_, true) => Self::Restart,
+            (true, false) => Self::HotApply,
+            (false, false) => Self::NoOp,
+        }
+    }
+}
+
+/// Response of an update, the indexer as updated along with what was done to apply it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexerUpdateModel {
+    #[serde(flatten)]
+    pub indexer: IndexerModel,
+    pub decision: UpdateDecision,
+    /// Parts of the launch config the update changed: `script`, `env` or `options`
+    pub launch_changes: Vec<String>,
+    /// Checksum of the effective launch config after the update, only computed when a setting
+    /// it depends on changed
+    pub launch_config_checksum: Option<String>,
+}
+
+impl LaunchConfig {
+    /// Checksum of everything the process is launched with, equal for two configs launching
+    /// the same process
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [&self.script_checksum, &self.options_checksum] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for (name, value) in &self.env {
+            hasher.update(name.as_bytes());
+            hasher.update([b'=']);
+            hasher.update(value.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(true, true, true, UpdateDecision::Restart)]
+    #[case(true, true, false, UpdateDecision::HotApply)]
+    #[case(true, false, true, UpdateDecision::HotApply)]
+    #[case(false, false, true, UpdateDecision::NoOp)]
+    fn test_decide(
+        #[case] updated: bool,
+        #[case] launch_changed: bool,
+        #[case] live: bool,
+        #[case] expected: UpdateDecision,
+    ) {
+        assert_eq!(UpdateDecision::decide(updated, launch_changed, live), expected);
+    }
+
+    #[test]
+    fn test_launch_config_checksum() {
+        let config = LaunchConfig {
+            script_checksum: "script".into(),
+            options_checksum: "options".into(),
+            env: BTreeMap::from([("RUST_LOG".to_string(), "info".to_string())]),
+        };
+        assert_eq!(config.checksum(), config.clone().checksum());
+
+        let mut debug = config.clone();
+        debug.env.insert("RUST_LOG".into(), "debug".into());
+        assert_ne!(config.checksum(), debug.checksum());
+        let shifted =
+            LaunchConfig { script_checksum: "scrip".into(), options_checksum: "toptions".into(), ..config.clone() };
+        assert_ne!(config.checksum(), shifted.checksum());
+    }
+}
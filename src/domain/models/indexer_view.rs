@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::indexer::{IndexerError, IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType};
+
+/// Columns of an indexer needed to list the fleet. The script, its params and the config blobs
+/// aren't loaded.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexerSummaryModel {
+    pub id: Uuid,
+    pub status: IndexerStatus,
+    pub indexer_type: IndexerType,
+    pub execution_ref: Option<ExecutionRef>,
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub status_server_port: Option<i32>,
+    pub starting_block: Option<i64>,
+    pub indexer_id: Option<String>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub standby_for: Option<Uuid>,
+    pub tenant_id: Option<String>,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    pub backfill_for: Option<Uuid>,
+    pub priority: i32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexerView {
+    #[default]
+    Full,
+    Summary,
+}
+
+/// Order of the listing by creation time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexerListQuery {
+    #[serde(default)]
+    pub view: IndexerView,
+    /// Comma separated fields to return, e.g. `id,status,tenant_id`
+    pub fields: Option<String>,
+    pub status: Option<IndexerStatus>,
+    pub indexer_type: Option<IndexerType>,
+    #[serde(default)]
+    pub order: ListOrder,
+    /// Every indexer is listed if not set
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Fields requested on a listing
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSelection(Vec<String>);
+
+impl FieldSelection {
+    /// Fields are those of the full indexer, the view only changes the default ones
+    pub fn parse(fields: &str) -> Result<Self, IndexerError> {
+        let known = field_names(&IndexerModel::default());
+        let mut selection: Vec<String> = vec![];
+        for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            if !known.iter().any(|known| known == field) {
+                return Err(IndexerError::InvalidFieldSelection(field.to_string()));
+            }
+            if !selection.iter().any(|selected| selected == field) {
+                selection.push(field.to_string());
+            }
+        }
+        if selection.is_empty() {
+            return Err(IndexerError::InvalidFieldSelection(fields.to_string()));
+        }
+        Ok(Self(selection))
+    }
+
+    /// Whether the summary rows have every requested field, in which case the full rows aren't
+    /// loaded
+    pub fn is_summary(&self) -> bool {
+        let summary = field_names(&IndexerSummaryModel::default());
+        self.0.iter().all(|field| summary.contains(field))
+    }
+
+    /// Keeps the requested fields of the serialized item
+    pub fn select<T: Serialize>(&self, item: &T) -> Result<Map<String, Value>, IndexerError> {
+        let Value::Object(mut object) =
+            serde_json::to_value(item).map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?
+        else {
+            return Err(IndexerError::FailedToSerialize("listed item is not an object".into()));
+        };
+        Ok(self.0.iter().filter_map(|field| object.remove_entry(field)).collect())
+    }
+}
+
+fn field_names<T: Serialize>(item: &T) -> Vec<String> {
+    match serde_json::to_value(item) {
+        Ok(Value::Object(object)) => object.keys().cloned().collect(),
+        _ => vec![],
+    }
+}
+
+impl From<IndexerModel> for IndexerSummaryModel {
+    fn from(value: IndexerModel) -> Self {
+        Self {
+            id: value.id,
+            status: value.status,
+            indexer_type: value.indexer_type,
+            execution_ref: value.execution_ref,
+            target_url: value.target_url,
+            table_name: value.table_name,
+            status_server_port: value.status_server_port,
+            starting_block: value.starting_block,
+            indexer_id: value.indexer_id,
+            log_level: value.log_level,
+            standby_for: value.standby_for,
+            tenant_id: value.tenant_id,
+            stream_url: value.stream_url,
+            ending_block: value.ending_block,
+            backfill_for: value.backfill_for,
+            priority: value.priority,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("id,status", Some(vec!["id", "status"]), true)]
+    #[case(" id , tenant_id,id,", Some(vec!["id", "tenant_id"]), true)]
+    #[case("id,launch_config", Some(vec!["id", "launch_config"]), false)]
+    #[case("id,unknown", None, false)]
+    #[case(",", None, false)]
+    fn test_parse_field_selection(#[case] fields: &str, #[case] expected: Option<Vec<&str>>, #[case] summary: bool) {
+        let selection = FieldSelection::parse(fields).ok();
+        assert_eq!(
+            selection.as_ref().map(|selection| selection.0.clone()),
+            expected.map(|expected| { expected.into_iter().map(String::from).collect::<Vec<_>>() })
+        );
+        if let Some(selection) = selection {
+            assert_eq!(selection.is_summary(), summary);
+        }
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let indexer_model = IndexerModel { tenant_id: Some("tenant".into()), ..Default::default() };
+        let selection = FieldSelection::parse("tenant_id,id").unwrap();
+
+        let selected = selection.select(&indexer_model).unwrap();
+        assert_eq!(selected.keys().collect::<Vec<_>>(), vec!["id", "tenant_id"]);
+        assert_eq!(selected["tenant_id"], "tenant");
+        // summary rows give the same fields
+        assert_eq!(selection.select(&IndexerSummaryModel::from(indexer_model)).unwrap(), selected);
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::indexer::IndexerModel;
+
+/// Columns of the inventory exports, in the order of `InventoryRow::to_csv_fields`
+pub const INVENTORY_COLUMNS: [&str; 21] = [
+    "id",
+    "status",
+    "indexer_type",
+    "tenant_id",
+    "priority",
+    "target_url",
+    "table_name",
+    "stream_url",
+    "starting_block",
+    "ending_block",
+    "current_block",
+    "sink_id",
+    "standby_for",
+    "backfill_for",
+    "log_level",
+    "script_checksum",
+    "script_source_url",
+    "script_params",
+    "hooks",
+    "sink_options",
+    "process_priority",
+];
+
+/// Indexer as exported for offline analysis. Indexers have no labels, the tenant is the only
+/// grouping exported. The connection string and the launch environment are left out as they may
+/// hold secrets.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InventoryRow {
+    pub id: Uuid,
+    pub status: String,
+    pub indexer_type: String,
+    pub tenant_id: Option<String>,
+    pub priority: i32,
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub stream_url: Option<String>,
+    pub starting_block: Option<i64>,
+    pub ending_block: Option<i64>,
+    /// Block the sink is at, only known for the running indexers
+    pub current_block: Option<u64>,
+    pub sink_id: Option<String>,
+    pub standby_for: Option<Uuid>,
+    pub backfill_for: Option<Uuid>,
+    pub log_level: Option<String>,
+    pub script_checksum: Option<String>,
+    pub script_source_url: Option<String>,
+    /// The config blobs are exported as JSON
+    pub script_params: String,
+    pub hooks: String,
+    pub sink_options: Option<String>,
+    pub process_priority: String,
+}
+
+impl InventoryRow {
+    pub fn new(indexer_model: &IndexerModel, current_block: Option<u64>) -> Self {
+        let to_json = |value: serde_json::Result<String>| value.unwrap_or_default();
+        Self {
+            id: indexer_model.id,
+            status: indexer_model.status.to_string(),
+            indexer_type: indexer_model.indexer_type.to_string(),
+            tenant_id: indexer_model.tenant_id.clone(),
+            priority: indexer_model.priority,
+            target_url: indexer_model.target_url.clone(),
+            table_name: indexer_model.table_name.clone(),
+            stream_url: indexer_model.stream_url.clone(),
+            starting_block: indexer_model.starting_block,
+            ending_block: indexer_model.ending_block,
+            current_block,
+            sink_id: indexer_model.indexer_id.clone(),
+            standby_for: indexer_model.standby_for,
+            backfill_for: indexer_model.backfill_for,
+            log_level: indexer_model.log_level.map(|log_level| log_level.to_string()),
+            script_checksum: indexer_model.script_checksum.clone(),
+            script_source_url: indexer_model.script_source_url.clone(),
+            script_params: to_json(serde_json::to_string(&indexer_model.script_params)),
+            hooks: to_json(serde_json::to_string(&indexer_model.hooks)),
+            sink_options: indexer_model.sink_options.as_ref().map(|options| to_json(serde_json::to_string(options))),
+            process_priority: to_json(serde_json::to_string(&indexer_model.process_priority)),
+        }
+    }
+
+    /// Fields of the row in the order of `INVENTORY_COLUMNS`, unset fields are empty
+    pub fn to_csv_fields(&self) -> [String; 21] {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        [
+            self.id.to_string(),
+            self.status.clone(),
+            self.indexer_type.clone(),
+            optional(self.tenant_id.clone()),
+            self.priority.to_string(),
+            optional(self.target_url.clone()),
+            optional(self.table_name.clone()),
+            optional(self.stream_url.clone()),
+            optional(self.starting_block.map(|block| block.to_string())),
+            optional(self.ending_block.map(|block| block.to_string())),
+            optional(self.current_block.map(|block| block.to_string())),
+            optional(self.sink_id.clone()),
+            optional(self.standby_for.map(|id| id.to_string())),
+            optional(self.backfill_for.map(|id| id.to_string())),
+            optional(self.log_level.clone()),
+            optional(self.script_checksum.clone()),
+            optional(self.script_source_url.clone()),
+            self.script_params.clone(),
+            self.hooks.clone(),
+            optional(self.sink_options.clone()),
+            self.process_priority.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::indexer::IndexerStatus;
+
+    #[test]
+    fn test_inventory_row() {
+        let indexer_model = IndexerModel {
+            status: IndexerStatus::Running,
+            tenant_id: Some("tenant".into()),
+            starting_block: Some(100),
+            custom_connection_string: Some("postgres://user:secret@db".into()),
+            script_params: [("contract".to_string(), "0x1".to_string())].into(),
+            ..Default::default()
+        };
+
+        let fields = InventoryRow::new(&indexer_model, Some(150)).to_csv_fields();
+        let field = |column: &str| fields[INVENTORY_COLUMNS.iter().position(|c| *c == column).unwrap()].as_str();
+        assert_eq!(field("status"), "Running");
+        assert_eq!(field("tenant_id"), "tenant");
+        assert_eq!((field("starting_block"), field("current_block"), field("ending_block")), ("100", "150", ""));
+        assert_eq!(field("script_params"), r#"{"contract":"0x1"}"#);
+        assert!(fields.iter().all(|field| !field.contains("secret")));
+    }
+}
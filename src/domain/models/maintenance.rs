@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, EnumString, Serialize, Deserialize, Display, Copy)]
+pub enum MaintenanceWindowKind {
+    /// Non urgent automated actions are deferred
+    Maintenance,
+    /// Only critical failure handling runs
+    QuietHours,
+}
+
+/// How urgent an automated action is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActionPriority {
+    /// e.g. restarting indexers which drifted from their config
+    Routine,
+    /// e.g. retrying a failed operation
+    Urgent,
+    /// e.g. failing over to a standby
+    Critical,
+}
+
+impl MaintenanceWindowKind {
+    /// Whether automated actions with this priority can run during the window
+    pub fn allows(&self, priority: ActionPriority) -> bool {
+        match self {
+            Self::Maintenance => priority >= ActionPriority::Urgent,
+            Self::QuietHours => priority == ActionPriority::Critical,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindowModel {
+    pub id: Uuid,
+    /// Windows without a tenant apply to every indexer
+    pub tenant_id: Option<String>,
+    pub kind: MaintenanceWindowKind,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(MaintenanceWindowKind::Maintenance, ActionPriority::Routine, false)]
+    #[case(MaintenanceWindowKind::Maintenance, ActionPriority::Urgent, true)]
+    #[case(MaintenanceWindowKind::Maintenance, ActionPriority::Critical, true)]
+    #[case(MaintenanceWindowKind::QuietHours, ActionPriority::Routine, false)]
+    #[case(MaintenanceWindowKind::QuietHours, ActionPriority::Urgent, false)]
+    #[case(MaintenanceWindowKind::QuietHours, ActionPriority::Critical, true)]
+    fn test_allows(#[case] kind: MaintenanceWindowKind, #[case] priority: ActionPriority, #[case] expected: bool) {
+        assert_eq!(kind.allows(priority), expected);
+    }
+}
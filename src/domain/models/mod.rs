@@ -1,2 +1,53 @@
+pub mod actor;
+pub mod alert_rule;
+pub mod annotation;
+pub mod approval;
+pub mod audit;
+pub mod capabilities;
+pub mod cleanup;
+pub mod console_output;
+pub mod contract;
+pub mod creation;
+pub mod data_migration;
+pub mod delivery;
+pub mod diagnostics;
+pub mod envelope;
+pub mod estimate;
+pub mod event_schema;
+pub mod execution;
+pub mod fleet_diff;
+pub mod gitops;
+pub mod grafana;
+pub mod hook;
 pub mod indexer;
+pub mod indexer_update;
+pub mod indexer_view;
+pub mod inventory;
+pub mod launch_command;
+pub mod maintenance;
+pub mod multiplexer;
+pub mod notification;
+pub mod output_location;
+pub mod process_priority;
+pub mod project;
+pub mod quarantine;
+pub mod reconfigure;
+pub mod runtime;
+pub mod scheduled_action;
+pub mod script_scan;
+pub mod script_search;
+pub mod script_sync;
+pub mod search;
+pub mod secret;
+pub mod simulation;
+pub mod sink_log;
+pub mod sink_options;
+pub mod stale_created;
+pub mod start_token;
+pub mod status_refresh;
+pub mod target_health;
+pub mod tenant;
 pub mod types;
+pub mod upload;
+pub mod usage;
+pub mod version;
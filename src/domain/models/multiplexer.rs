@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::execution::ExecutionRef;
+
+/// A single stream consumer shared by every webhook indexer running the same script
+/// from the same starting block.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiplexerGroup {
+    pub key: String,
+    pub execution_ref: Option<ExecutionRef>,
+    pub members: HashMap<Uuid, MultiplexerMember>,
+    /// Payloads the shared sink sent. A group which sent any can't be joined anymore, the new
+    /// member would miss the blocks before it joined.
+    #[serde(default)]
+    pub payloads: u64,
+}
+
+/// A logical indexer attached to a multiplexer group along with its delivery accounting.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiplexerMember {
+    pub target_url: String,
+    pub messages_forwarded: u64,
+    pub bytes_forwarded: u64,
+    pub failed_deliveries: u64,
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::constants::indexers::{DEFAULT_DUPLICATE_ALERT_WINDOW_SECONDS, DEFAULT_MAX_ALERTS_PER_HOUR};
+use crate::domain::models::indexer::IndexerStatus;
+
+/// HMAC key used to sign the lifecycle webhooks. Keys have a validity window so they can be
+/// rotated: during the overlap both the old and the new key sign the payloads.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SigningKey {
+    pub id: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.valid_from.map_or(true, |valid_from| valid_from <= at)
+            && self.valid_until.map_or(true, |valid_until| at < valid_until)
+    }
+}
+
+/// Payload of the lifecycle webhooks sent when an indexer changes status
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    /// Version of the schema published at `/v1/schemas/events/<version>`
+    pub schema_version: String,
+    pub indexer_id: Uuid,
+    pub status: IndexerStatus,
+    pub happened_at: DateTime<Utc>,
+    /// Id of the API request which caused the change, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Why the service changed the status by itself, e.g. `TargetGone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerifySignatureResponse {
+    pub valid: bool,
+    pub key_id: Option<String>,
+}
+
+/// Failure budget of the lifecycle webhooks of an indexer, so that an indexer flapping between
+/// running and failed doesn't page on every restart
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NotificationPolicy {
+    pub max_alerts_per_hour: u32,
+    /// The same status change, with the same reason, isn't sent again within this window
+    pub duplicate_window_seconds: u64,
+}
+
+impl Default for NotificationPolicy {
+    fn default() -> Self {
+        Self {
+            max_alerts_per_hour: DEFAULT_MAX_ALERTS_PER_HOUR,
+            duplicate_window_seconds: DEFAULT_DUPLICATE_ALERT_WINDOW_SECONDS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NotificationPolicyModel {
+    pub indexer_id: Uuid,
+    #[serde(flatten)]
+    pub policy: NotificationPolicy,
+    /// Whether the indexer overrides the default policy
+    pub overridden: bool,
+    /// Webhooks of the indexer suppressed since the service started
+    pub suppressed_alerts: u64,
+}
+
+/// Lifecycle webhooks recently sent for an indexer
+#[derive(Debug, Default)]
+pub struct AlertHistory {
+    sent: VecDeque<DateTime<Utc>>,
+    /// When each alert, identified by its status and reason, was last sent
+    last_sent: HashMap<String, DateTime<Utc>>,
+    pub suppressed: u64,
+}
+
+impl AlertHistory {
+    /// Whether the alert fits in the budget of the policy, it's counted as sent if it does
+    pub fn admit(&mut self, alert: &str, now: DateTime<Utc>, policy: &NotificationPolicy) -> bool {
+        let hour_ago = now - Duration::hours(1);
+        while self.sent.front().is_some_and(|sent_at| *sent_at <= hour_ago) {
+            self.sent.pop_front();
+        }
+        let duplicate_window = Duration::seconds(policy.duplicate_window_seconds as i64);
+        self.last_sent.retain(|_, sent_at| now - *sent_at < duplicate_window);
+
+        if self.last_sent.contains_key(alert) || self.sent.len() >= policy.max_alerts_per_hour as usize {
+            self.suppressed += 1;
+            return false;
+        }
+        self.sent.push_back(now);
+        self.last_sent.insert(alert.to_string(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_alert_history_admit() {
+        let policy = NotificationPolicy { max_alerts_per_hour: 3, duplicate_window_seconds: 1800 };
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 0, 0, 0).unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let mut history = AlertHistory::default();
+
+        assert!(history.admit("FailedRunning", at(0), &policy));
+        // duplicates are suppressed for 30 minutes
+        assert!(!history.admit("FailedRunning", at(10), &policy));
+        assert!(history.admit("Running", at(11), &policy));
+        assert!(history.admit("FailedRunning", at(31), &policy));
+        // the budget of the hour is spent
+        assert!(!history.admit("Stopped", at(40), &policy));
+        assert!(history.admit("Stopped", at(61), &policy));
+        assert_eq!(history.suppressed, 2);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const SCHEME: &str = "s3://";
+
+/// Bucket and prefix the files of a Parquet indexer are written under, stored as
+/// `s3://<bucket>/<prefix>`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputLocation {
+    pub bucket: String,
+    pub prefix: String,
+}
+
+impl OutputLocation {
+    /// The prefix defaults to the id of the indexer so that the indexers sharing a bucket don't
+    /// write over each other's files
+    pub fn new(bucket: &str, prefix: Option<&str>, indexer_id: Uuid) -> Result<Self, String> {
+        validate_bucket(bucket)?;
+        let prefix = match prefix.map(|prefix| prefix.trim_matches('/')) {
+            Some(prefix) => {
+                validate_prefix(prefix)?;
+                prefix.to_string()
+            }
+            None => indexer_id.to_string(),
+        };
+        Ok(Self { bucket: bucket.to_string(), prefix })
+    }
+}
+
+/// Bucket names as S3 accepts them, without the legacy uppercase names
+fn validate_bucket(bucket: &str) -> Result<(), String> {
+    let valid_chars = bucket.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-');
+    let valid_ends = bucket.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && bucket.ends_with(|c: char| c.is_ascii_alphanumeric());
+    if !(3..=63).contains(&bucket.len()) || !valid_chars || !valid_ends || bucket.contains("..") {
+        return Err(format!("invalid bucket name {}", bucket));
+    }
+    Ok(())
+}
+
+fn validate_prefix(prefix: &str) -> Result<(), String> {
+    if prefix.is_empty() {
+        return Err("the prefix must not be empty".into());
+    }
+    if prefix.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..") {
+        return Err(format!("invalid prefix {}", prefix));
+    }
+    Ok(())
+}
+
+impl fmt::Display for OutputLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}", SCHEME, self.bucket, self.prefix)
+    }
+}
+
+impl FromStr for OutputLocation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (bucket, prefix) = value
+            .strip_prefix(SCHEME)
+            .and_then(|location| location.split_once('/'))
+            .ok_or_else(|| format!("{} is not an s3://<bucket>/<prefix> location", value))?;
+        validate_bucket(bucket)?;
+        validate_prefix(prefix)?;
+        Ok(Self { bucket: bucket.to_string(), prefix: prefix.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("analytics", Some("starknet/transfers/"), Ok("s3://analytics/starknet/transfers"))]
+    #[case("analytics.prod-1", Some("/transfers"), Ok("s3://analytics.prod-1/transfers"))]
+    #[case("Analytics", None, Err(()))]
+    #[case("ab", None, Err(()))]
+    #[case("-analytics", None, Err(()))]
+    #[case("analytics", Some("transfers/../other"), Err(()))]
+    #[case("analytics", Some("transfers//daily"), Err(()))]
+    #[case("analytics", Some("/"), Err(()))]
+    fn test_output_location(#[case] bucket: &str, #[case] prefix: Option<&str>, #[case] expected: Result<&str, ()>) {
+        let location = OutputLocation::new(bucket, prefix, Uuid::new_v4());
+        assert_eq!(location.as_ref().map(|location| location.to_string()).map_err(|_| ()), expected.map(String::from));
+        if let Ok(location) = location {
+            assert_eq!(location.to_string().parse::<OutputLocation>(), Ok(location));
+        }
+    }
+
+    #[test]
+    fn test_default_prefix() {
+        let id = Uuid::new_v4();
+        let location = OutputLocation::new("analytics", None, id).unwrap();
+        assert_eq!(location.to_string(), format!("s3://analytics/{}", id));
+        assert!("gs://analytics/transfers".parse::<OutputLocation>().is_err());
+        assert!("s3://analytics".parse::<OutputLocation>().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::domain::models::indexer::IndexerError;
+
+/// CPU and IO scheduling of the sink process, e.g. to keep backfills from starving the latency
+/// sensitive indexers of a host. Anything not set keeps the defaults of the OS.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessPriority {
+    /// From -20 (highest priority) to 19 (lowest), lowering it below 0 needs `CAP_SYS_NICE`
+    pub nice: Option<i32>,
+    pub io_class: Option<IoClass>,
+    /// From 0 (highest priority) to 7 (lowest) in the best effort class
+    pub io_level: Option<u8>,
+}
+
+/// Realtime isn't supported, it would let an indexer starve the whole host
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum IoClass {
+    BestEffort,
+    /// Only gets disk time when no other process needs it
+    Idle,
+}
+
+pub const DEFAULT_NICE: i32 = 0;
+pub const DEFAULT_IO_LEVEL: u8 = 4;
+
+impl IoClass {
+    /// Class number of `ionice`
+    pub fn number(&self) -> u8 {
+        match self {
+            Self::BestEffort => 2,
+            Self::Idle => 3,
+        }
+    }
+}
+
+impl ProcessPriority {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), IndexerError> {
+        if let Some(nice) = self.nice {
+            if !(-20..=19).contains(&nice) {
+                return Err(IndexerError::InvalidProcessPriority(format!("nice {} is not between -20 and 19", nice)));
+            }
+        }
+        if let Some(io_level) = self.io_level {
+            if io_level > 7 {
+                return Err(IndexerError::InvalidProcessPriority(format!(
+                    "io level {} is not between 0 and 7",
+                    io_level
+                )));
+            }
+            if self.io_class == Some(IoClass::Idle) {
+                return Err(IndexerError::InvalidProcessPriority("the idle io class has no level".into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The io class if one applies, a level alone implies the best effort class
+    pub fn effective_io_class(&self) -> Option<IoClass> {
+        self.io_class.or(self.io_level.map(|_| IoClass::BestEffort))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_process_priority() {
+        assert!(ProcessPriority::default().validate().is_ok());
+        assert!(ProcessPriority { nice: Some(10), io_class: Some(IoClass::Idle), io_level: None }.validate().is_ok());
+        assert!(ProcessPriority { nice: Some(20), ..Default::default() }.validate().is_err());
+        assert!(ProcessPriority { io_level: Some(8), ..Default::default() }.validate().is_err());
+        assert!(ProcessPriority { io_class: Some(IoClass::Idle), io_level: Some(2), nice: None }.validate().is_err());
+    }
+
+    #[test]
+    fn test_effective_io_class() {
+        assert_eq!(ProcessPriority::default().effective_io_class(), None);
+        assert_eq!(
+            ProcessPriority { io_level: Some(7), ..Default::default() }.effective_io_class(),
+            Some(IoClass::BestEffort)
+        );
+        assert!(serde_json::from_str::<ProcessPriority>(r#"{"io_class": "realtime"}"#).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domain::models::actor::{ActorContext, ActorScope};
+use crate::domain::models::indexer::IndexerLogLevel;
+use crate::domain::models::types::AxumErrorResponse;
+use crate::infra::errors::InfraError;
+
+/// Role of an actor on a project, each role grants what the previous ones do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, EnumString, Serialize, Deserialize, Display)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectRole {
+    /// Reads the project and lists its indexers
+    Viewer,
+    /// Creates indexers in the project
+    Editor,
+    /// Changes the project and its bindings, deletes it
+    Owner,
+}
+
+/// Grants a role on the project to an actor, as declared with `x-admin-actor`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectRoleBinding {
+    pub subject: String,
+    pub role: ProjectRole,
+}
+
+/// Indexers of a tenant grouped the way its team organizes its pipelines. The defaults of the
+/// project apply to its indexers before the ones of the tenant.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectModel {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<IndexerLogLevel>,
+    /// Secret references of the indexers of the project must point under this path. If not set
+    /// they must point under the tenant of the project, or `UNSCOPED_SECRETS_PREFIX` if the
+    /// tenant is blank, see `get_secret_scope`.
+    pub secret_path_prefix: Option<String>,
+    pub role_bindings: Vec<ProjectRoleBinding>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectModel {
+    /// Role of the caller on the project. Admins are owners unless they name an actor and the
+    /// project has bindings, the callers of its tenant are editors until it has bindings and
+    /// viewers afterwards.
+    pub fn role_of(&self, context: &ActorContext) -> Option<ProjectRole> {
+        if context.has_scope(ActorScope::Admin) {
+            return match &context.actor_id {
+                Some(actor_id) if !self.role_bindings.is_empty() => self
+                    .role_bindings
+                    .iter()
+                    .filter(|binding| binding.subject == *actor_id)
+                    .map(|binding| binding.role)
+                    .max(),
+                _ => Some(ProjectRole::Owner),
+            };
+        }
+        match context.tenant_id.as_deref() == Some(self.tenant_id.as_str()) {
+            true if self.role_bindings.is_empty() => Some(ProjectRole::Editor),
+            true => Some(ProjectRole::Viewer),
+            false => None,
+        }
+    }
+
+    pub fn authorize(&self, context: &ActorContext, role: ProjectRole) -> Result<(), ProjectError> {
+        match self.role_of(context) {
+            Some(granted) if granted >= role => Ok(()),
+            _ => Err(ProjectError::Forbidden(self.id, role)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectRequest {
+    pub name: String,
+    /// Tenant of the caller if not set
+    pub tenant_id: Option<String>,
+    pub description: Option<String>,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<IndexerLogLevel>,
+    pub secret_path_prefix: Option<String>,
+    #[serde(default)]
+    pub role_bindings: Vec<ProjectRoleBinding>,
+}
+
+impl ProjectRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".into());
+        }
+        if self.secret_path_prefix.as_deref().is_some_and(|prefix| prefix.trim().is_empty()) {
+            return Err("secret_path_prefix must not be empty".into());
+        }
+        for (i, binding) in self.role_bindings.iter().enumerate() {
+            if binding.subject.trim().is_empty() {
+                return Err("role binding subjects must not be empty".into());
+            }
+            if self.role_bindings[..i].iter().any(|other| other.subject == binding.subject) {
+                return Err(format!("{} is bound more than once", binding.subject));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectError {
+    #[error("project {0} not found")]
+    NotFound(Uuid),
+    #[error("invalid project : {0}")]
+    InvalidProject(String),
+    #[error("the {1} role on project {0} is required")]
+    Forbidden(Uuid, ProjectRole),
+    #[error("project {0} still has {1} indexers")]
+    NotEmpty(Uuid, usize),
+    #[error("infra error : {0}")]
+    InfraError(InfraError),
+}
+
+impl IntoResponse for ProjectError {
+    fn into_response(self) -> axum::response::Response {
+        tracing::error!("Error: {:?}", self);
+        let (status, err_msg) = match self {
+            Self::NotFound(_) => (StatusCode::NOT_FOUND, format!("Not found: {}", self)),
+            Self::InvalidProject(_) | Self::NotEmpty(_, _) => {
+                (StatusCode::BAD_REQUEST, format!("Bad request: {}", self))
+            }
+            Self::Forbidden(_, _) => (StatusCode::FORBIDDEN, format!("Forbidden: {}", self)),
+            Self::InfraError(InfraError::DatabaseBusy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", self))
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", self)),
+        };
+        (
+            status,
+            Json(AxumErrorResponse { resource: "Project".into(), message: err_msg, happened_at: chrono::Utc::now() }),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn project(role_bindings: Vec<ProjectRoleBinding>) -> ProjectModel {
+        ProjectModel {
+            id: Uuid::new_v4(),
+            tenant_id: "acme".into(),
+            name: "pipelines".into(),
+            description: None,
+            default_stream_url: None,
+            default_log_level: None,
+            secret_path_prefix: Some("indexers/acme/".into()),
+            role_bindings,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn context(tenant_id: Option<&str>, admin: bool, actor_id: Option<&str>) -> ActorContext {
+        ActorContext {
+            tenant_id: tenant_id.map(String::from),
+            actor_id: actor_id.map(String::from),
+            scopes: if admin { vec![ActorScope::Admin] } else { vec![] },
+            request_id: None,
+            system: false,
+        }
+    }
+
+    #[rstest]
+    #[case(context(None, true, None), false, Some(ProjectRole::Owner))]
+    #[case(context(None, true, None), true, Some(ProjectRole::Owner))]
+    #[case(context(None, true, Some("alice")), false, Some(ProjectRole::Owner))]
+    #[case(context(None, true, Some("alice")), true, Some(ProjectRole::Editor))]
+    #[case(context(None, true, Some("bob")), true, None)]
+    #[case(context(Some("acme"), false, None), false, Some(ProjectRole::Editor))]
+    #[case(context(Some("acme"), false, None), true, Some(ProjectRole::Viewer))]
+    #[case(context(Some("other"), false, None), false, None)]
+    #[case(context(None, false, None), false, None)]
+    fn test_role_of(#[case] context: ActorContext, #[case] bound: bool, #[case] expected: Option<ProjectRole>) {
+        let bindings = match bound {
+            true => vec![
+                ProjectRoleBinding { subject: "alice".into(), role: ProjectRole::Editor },
+                ProjectRoleBinding { subject: "carol".into(), role: ProjectRole::Owner },
+            ],
+            false => vec![],
+        };
+        let project = project(bindings);
+        assert_eq!(project.role_of(&context), expected);
+        assert_eq!(project.authorize(&context, ProjectRole::Editor).is_ok(), expected >= Some(ProjectRole::Editor));
+    }
+
+    #[test]
+    fn test_validate_project_request() {
+        let binding = |subject: &str| ProjectRoleBinding { subject: subject.into(), role: ProjectRole::Owner };
+        let request = ProjectRequest {
+            name: "pipelines".into(),
+            tenant_id: None,
+            description: None,
+            default_stream_url: None,
+            default_log_level: None,
+            secret_path_prefix: None,
+            role_bindings: vec![binding("alice"), binding("bob")],
+        };
+        assert!(request.validate().is_ok());
+        let request = ProjectRequest { name: " ".into(), ..request };
+        assert!(request.validate().is_err());
+
+        let request = ProjectRequest {
+            name: "pipelines".into(),
+            role_bindings: vec![binding("alice"), binding("alice")],
+            ..request
+        };
+        assert!(request.validate().is_err());
+    }
+}
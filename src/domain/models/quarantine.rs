@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Indexer row which couldn't be read, e.g. holding a status written by a newer version of the
+/// service. It's left out of the listings until it's fixed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuarantinedIndexer {
+    pub indexer_id: Uuid,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub error: String,
+    /// First time the row was found unreadable
+    pub quarantined_at: DateTime<Utc>,
+}
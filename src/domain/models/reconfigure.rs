@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use uuid::Uuid;
+
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus, IndexerType};
+
+/// Fields which can be rewritten across the fleet
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReconfigureField {
+    StreamUrl,
+    TargetUrl,
+}
+
+impl ReconfigureField {
+    pub fn get<'a>(&self, indexer_model: &'a IndexerModel) -> Option<&'a String> {
+        match self {
+            Self::StreamUrl => indexer_model.stream_url.as_ref(),
+            Self::TargetUrl => indexer_model.target_url.as_ref(),
+        }
+    }
+}
+
+/// Indexers a reconfiguration applies to, anything not set matches every indexer
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexerSelector {
+    pub tenant_id: Option<String>,
+    pub indexer_type: Option<IndexerType>,
+    pub status: Option<IndexerStatus>,
+}
+
+impl IndexerSelector {
+    pub fn matches(&self, indexer_model: &IndexerModel) -> bool {
+        self.tenant_id.as_ref().map_or(true, |tenant_id| indexer_model.tenant_id.as_ref() == Some(tenant_id))
+            && self.indexer_type.as_ref().map_or(true, |indexer_type| indexer_model.indexer_type == *indexer_type)
+            && self.status.map_or(true, |status| indexer_model.status == status)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReconfigureChange {
+    pub indexer_id: Uuid,
+    pub status: IndexerStatus,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReconfigureModel {
+    pub field: ReconfigureField,
+    pub dry_run: bool,
+    /// Running indexers are restarted one at a time once the rollout starts
+    pub changes: Vec<ReconfigureChange>,
+}
+
+/// Returns the value of the field with every occurrence of `pattern` replaced, `None` if the
+/// field doesn't contain it
+pub fn replace_value(value: &str, pattern: &str, replacement: &str) -> Option<String> {
+    if pattern.is_empty() || !value.contains(pattern) {
+        return None;
+    }
+    Some(value.replace(pattern, replacement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_value() {
+        let value = "https://mainnet.starknet.a5a.ch";
+        assert_eq!(
+            replace_value(value, "a5a.ch", "dna.apibara.com"),
+            Some("https://mainnet.starknet.dna.apibara.com".to_string())
+        );
+        assert_eq!(replace_value(value, "sepolia", "mainnet"), None);
+        assert_eq!(replace_value(value, "", "anything"), None);
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        let indexer_model =
+            IndexerModel { tenant_id: Some("tenant".into()), status: IndexerStatus::Running, ..Default::default() };
+        assert!(IndexerSelector::default().matches(&indexer_model));
+        assert!(IndexerSelector { tenant_id: Some("tenant".into()), ..Default::default() }.matches(&indexer_model));
+        assert!(
+            !IndexerSelector { status: Some(IndexerStatus::Stopped), ..Default::default() }.matches(&indexer_model)
+        );
+        assert!(
+            !IndexerSelector { indexer_type: Some(IndexerType::Postgres), ..Default::default() }
+                .matches(&indexer_model)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use uuid::Uuid;
+
+/// Snapshot of the resources used by the service process itself. Values are `None` when they
+/// can't be read on the current platform.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeMetrics {
+    pub memory_rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+    pub max_open_fds: Option<u64>,
+    pub child_processes: Option<u64>,
+    /// Children which exited and are waiting to be reaped
+    pub zombie_processes: Option<u64>,
+    /// Processes left behind by the sinks which were reaped since the service started
+    pub reaped_orphans: u64,
+    /// Ratio of the memory of the host in use
+    pub host_memory_used_ratio: Option<f64>,
+    /// Indexers paused until the memory pressure on the host subsides
+    pub pressure_paused_indexers: Vec<Uuid>,
+    /// Lifecycle webhooks suppressed by the failure budgets since the service started
+    pub suppressed_notifications: u64,
+    pub tokio: TokioMetrics,
+    pub tasks: Vec<SupervisedTaskMetrics>,
+}
+
+/// Tokio runtime metrics, only available when the service is built with `--cfg tokio_unstable`
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokioMetrics {
+    pub workers: usize,
+    pub active_tasks: Option<usize>,
+    pub blocking_threads: Option<usize>,
+    pub idle_blocking_threads: Option<usize>,
+    pub blocking_queue_depth: Option<usize>,
+}
+
+/// Database connection pools, each subsystem gets its own so that one of them can't exhaust the
+/// connections the others need
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PoolName {
+    /// Requests of the API
+    #[default]
+    Api,
+    /// Tasks reacting to the sinks, e.g. when their process exits
+    Consumers,
+    /// Periodic jobs, e.g. the config drift check
+    Background,
+    /// Read only replica, only set if `DATABASE_REPLICA_URL` is
+    Replica,
+}
+
+/// State of a database connection pool along with the time spent waiting for connections
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DatabasePoolMetrics {
+    pub name: PoolName,
+    pub max_size: usize,
+    pub size: usize,
+    pub available: usize,
+    pub waiting: usize,
+    /// Share of the pool in use, waiting requests are not counted
+    pub saturation: f64,
+    pub acquired: u64,
+    pub timeouts: u64,
+    pub average_wait_ms: f64,
+    pub max_wait_ms: u64,
+}
+
+/// Restarts of a background task run by the supervisor
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SupervisedTaskMetrics {
+    pub name: String,
+    pub critical: bool,
+    pub restarts: u64,
+    pub crash_looping: bool,
+    pub last_panic: Option<String>,
+    pub last_panic_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessModel {
+    pub ready: bool,
+    pub crash_looping_tasks: Vec<String>,
+    /// Not set while the service refuses to start indexers, it keeps serving the running ones
+    pub schedulable: bool,
+    pub unschedulable_reasons: Vec<String>,
+}
+
+/// How the service starts when some of its infrastructure is missing
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum StartupMode {
+    /// Refuses to start, the default out of `DEV_ENV`
+    #[default]
+    Strict,
+    /// Falls back to local backends, e.g. the object store to a directory
+    Permissive,
+}
+
+/// What the service does with its bucket at startup when `STORAGE_BOOTSTRAP` is set, nothing
+/// is checked before the first use otherwise
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum StorageBootstrap {
+    /// Creates the bucket if it's missing and applies its tags and access policy
+    Create,
+    /// Only checks the bucket exists, e.g. when the service isn't allowed to change it
+    Verify,
+}
+
+/// Backend replaced by a local one as the service started in the permissive mode
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartupCompromise {
+    pub subsystem: String,
+    pub fallback: String,
+    /// Why the configured backend couldn't be used
+    pub reason: String,
+}
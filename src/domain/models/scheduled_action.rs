@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, FixedOffset, Offset, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, EnumString, Serialize, Deserialize, Display)]
+pub enum ScheduledActionKind {
+    Start,
+    Stop,
+    Restart,
+}
+
+/// Action to run on an indexer at a given time. Actions run at least once: one interrupted
+/// before it completes, e.g. by a crash, runs again once its lock expires.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledActionModel {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub kind: ScheduledActionKind,
+    pub run_at: DateTime<Utc>,
+    pub attempts: i32,
+    /// Set while the action runs
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Set once the action isn't retried anymore
+    pub failed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// Set on recurring actions, which move to their next run once they ran
+    pub cron: Option<String>,
+    /// Timezone the cron expression is evaluated in, UTC if not set
+    pub timezone: Option<String>,
+}
+
+impl ScheduledActionModel {
+    fn get_recurrence(&self) -> Option<(Schedule, Tz)> {
+        let schedule = parse_cron(self.cron.as_deref()?).ok()?;
+        let timezone = parse_timezone(self.timezone.as_deref().unwrap_or("UTC")).ok()?;
+        Some((schedule, timezone))
+    }
+
+    /// Next run of a recurring action after `now`, none for one-off actions
+    pub fn get_next_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (schedule, timezone) = self.get_recurrence()?;
+        get_next_occurrence(&schedule, timezone, now.max(self.run_at))
+    }
+
+    /// Up to `count` runs of the action, the pending one first. Actions which failed for good
+    /// don't run anymore.
+    pub fn get_planned_runs(&self, count: usize) -> Vec<DateTime<Utc>> {
+        if self.failed_at.is_some() || count == 0 {
+            return vec![];
+        }
+        let mut runs = vec![self.run_at];
+        if let Some((schedule, timezone)) = self.get_recurrence() {
+            let mut after = self.run_at;
+            while runs.len() < count {
+                let Some(next) = get_next_occurrence(&schedule, timezone, after) else {
+                    break;
+                };
+                runs.push(next);
+                after = next;
+            }
+        }
+        runs
+    }
+}
+
+/// Names and ranges of the fields of a cron expression, the seconds are optional
+const CRON_FIELDS: [(&str, &str); 6] = [
+    ("second", "0-59"),
+    ("minute", "0-59"),
+    ("hour", "0-23"),
+    ("day of month", "1-31"),
+    ("month", "1-12 or JAN-DEC"),
+    ("day of week", "SUN-SAT"),
+];
+
+/// Parses a cron expression of 5 fields: minute, hour, day of month, month and day of week, or
+/// of 6 with the seconds first. Days of the week are only accepted by name as their numbers
+/// differ between cron flavours, e.g. `1` is Monday for crontab but Sunday here.
+pub fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let mut fields: Vec<&str> = expression.split_whitespace().collect();
+    match fields.len() {
+        5 => fields.insert(0, "0"),
+        6 => (),
+        found => {
+            return Err(format!(
+                "cron expression \"{}\" has {} fields, expected 5: minute hour day-of-month month day-of-week",
+                expression, found
+            ));
+        }
+    }
+
+    let day_of_week = fields[5];
+    let numbered = day_of_week
+        .split(',')
+        .any(|item| item.split('/').next().is_some_and(|range| range.chars().any(|c| c.is_ascii_digit())));
+    if numbered {
+        return Err(format!("day of week \"{}\" must use day names, e.g. MON-FRI", day_of_week));
+    }
+    // each field is checked alone first so that the error points at the faulty one
+    for (i, ((name, range), field)) in CRON_FIELDS.iter().zip(&fields).enumerate() {
+        let mut alone = ["0", "*", "*", "*", "*", "*"];
+        alone[i] = *field;
+        if Schedule::from_str(&alone.join(" ")).is_err() {
+            return Err(format!("invalid {} \"{}\", expected {}", name, field, range));
+        }
+    }
+
+    let schedule = Schedule::from_str(&fields.join(" "))
+        .map_err(|e| format!("invalid cron expression \"{}\": {}", expression, e))?;
+    if schedule.upcoming(Utc).next().is_none() {
+        return Err(format!("cron expression \"{}\" never runs", expression));
+    }
+    Ok(schedule)
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    Tz::from_str(name).map_err(|_| format!("unknown timezone \"{}\", expected an IANA name e.g. Europe/Paris", name))
+}
+
+/// First occurrence after `after`. The expression is evaluated in the timezone so that e.g. a
+/// daily restart keeps its local time across DST changes.
+pub fn get_next_occurrence(schedule: &Schedule, timezone: Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    schedule.after(&after.with_timezone(&timezone)).next().map(|at| at.with_timezone(&Utc))
+}
+
+/// Time in the timezone, with its offset at that time
+pub fn to_timezone(at: DateTime<Utc>, timezone: Tz) -> DateTime<FixedOffset> {
+    let local = at.with_timezone(&timezone);
+    local.with_timezone(&local.offset().fix())
+}
+
+/// Run of a scheduled action, as previewed in the timezone of the caller
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlannedRunModel {
+    pub action_id: Uuid,
+    pub kind: ScheduledActionKind,
+    pub run_at: DateTime<FixedOffset>,
+    pub recurring: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SchedulePreviewModel {
+    pub indexer_id: Uuid,
+    pub timezone: String,
+    pub runs: Vec<PlannedRunModel>,
+}
+
+/// Delay before an action which failed `attempts` times runs again. The jitter spreads the
+/// retries of actions which failed together.
+pub fn get_retry_delay(attempts: i32, base_delay_seconds: i64, jitter_millis: i64) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::seconds(base_delay_seconds.saturating_mul(2i64.saturating_pow(exponent)))
+        + Duration::milliseconds(jitter_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(1, 0, Duration::seconds(30))]
+    #[case(2, 0, Duration::seconds(60))]
+    #[case(3, 250, Duration::seconds(120) + Duration::milliseconds(250))]
+    #[case(0, 0, Duration::seconds(30))]
+    fn test_get_retry_delay(#[case] attempts: i32, #[case] jitter_millis: i64, #[case] expected: Duration) {
+        assert_eq!(get_retry_delay(attempts, 30, jitter_millis), expected);
+    }
+
+    #[rstest]
+    #[case("0 9 * * MON-FRI", None)]
+    #[case("30 0 9 * * SUN", None)]
+    #[case("*/15 * * * *", None)]
+    #[case("0 9 * *", Some("has 4 fields"))]
+    #[case("0 9 * * 1-5", Some("must use day names"))]
+    #[case("0 25 * * *", Some("invalid hour \"25\""))]
+    #[case("0 9 * FOO *", Some("invalid month \"FOO\""))]
+    #[case("0 0 30 FEB *", Some("never runs"))]
+    fn test_parse_cron(#[case] expression: &str, #[case] error: Option<&str>) {
+        match (parse_cron(expression), error) {
+            (Ok(_), None) => (),
+            (Err(e), Some(error)) => assert!(e.contains(error), "{}", e),
+            (result, _) => panic!("unexpected result for {}: {:?}", expression, result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_get_planned_runs() {
+        // Paris moves from UTC+1 to UTC+2 on the 30th of March 2025
+        let run_at = DateTime::parse_from_rfc3339("2025-03-29T08:00:00Z").unwrap().with_timezone(&Utc);
+        let mut action = ScheduledActionModel {
+            id: Uuid::new_v4(),
+            indexer_id: Uuid::new_v4(),
+            kind: ScheduledActionKind::Restart,
+            run_at,
+            attempts: 0,
+            locked_until: None,
+            last_error: None,
+            failed_at: None,
+            created_at: run_at,
+            cron: Some("0 9 * * *".into()),
+            timezone: Some("Europe/Paris".into()),
+        };
+        let runs: Vec<String> = action.get_planned_runs(3).iter().map(|run| run.to_rfc3339()).collect();
+        assert_eq!(runs, vec!["2025-03-29T08:00:00+00:00", "2025-03-30T07:00:00+00:00", "2025-03-31T07:00:00+00:00"]);
+        assert_eq!(action.get_next_run(run_at).map(|run| run.to_rfc3339()), Some(runs[1].clone()));
+        assert_eq!(
+            to_timezone(action.get_planned_runs(2)[1], Tz::Europe__Paris).to_rfc3339(),
+            "2025-03-30T09:00:00+02:00"
+        );
+
+        action.cron = None;
+        assert_eq!(action.get_planned_runs(3), vec![run_at]);
+        assert_eq!(action.get_next_run(run_at), None);
+        action.failed_at = Some(run_at);
+        assert!(action.get_planned_runs(3).is_empty());
+    }
+}
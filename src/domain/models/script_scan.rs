@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+/// npm package imported by a script at an exact version
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptDependency {
+    pub name: String,
+    pub version: String,
+}
+
+/// Severities as rated by the GitHub advisory database. Advisories without a rating are
+/// `Unknown` and never block a deployment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, EnumString, Display, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum AdvisorySeverity {
+    #[default]
+    Unknown,
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+/// Advisory affecting a dependency of a script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptFinding {
+    pub package: String,
+    pub version: String,
+    pub advisory_id: String,
+    pub summary: Option<String>,
+    pub severity: AdvisorySeverity,
+}
+
+/// Findings of the scan of a script version, identified by its checksum
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptScanModel {
+    pub script_checksum: String,
+    pub dependencies: Vec<ScriptDependency>,
+    pub findings: Vec<ScriptFinding>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+impl ScriptScanModel {
+    /// Findings at or above the severity deployments are blocked at
+    pub fn get_blocking_findings(&self, block_severity: AdvisorySeverity) -> Vec<&ScriptFinding> {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity != AdvisorySeverity::Unknown && finding.severity >= block_severity)
+            .collect()
+    }
+}
+
+/// Vulnerability of the OSV API, only the fields the scan uses
+#[derive(Clone, Debug, Deserialize)]
+pub struct OsvVulnerability {
+    pub id: String,
+    pub summary: Option<String>,
+    pub database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OsvQueryResponse {
+    #[serde(default)]
+    pub vulns: Vec<OsvVulnerability>,
+}
+
+impl OsvVulnerability {
+    pub fn into_finding(self, dependency: &ScriptDependency) -> ScriptFinding {
+        let severity = self
+            .database_specific
+            .as_ref()
+            .and_then(|database_specific| database_specific.get("severity"))
+            .and_then(|severity| severity.as_str())
+            .and_then(|severity| severity.parse().ok())
+            .unwrap_or_default();
+        ScriptFinding {
+            package: dependency.name.clone(),
+            version: dependency.version.clone(),
+            advisory_id: self.id,
+            summary: self.summary,
+            severity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocking_findings() {
+        let dependency = ScriptDependency { name: "lodash".into(), version: "4.17.20".into() };
+        let response: OsvQueryResponse = serde_json::from_value(serde_json::json!({
+            "vulns": [
+                { "id": "GHSA-1", "summary": "Prototype pollution", "database_specific": { "severity": "CRITICAL" } },
+                { "id": "GHSA-2", "database_specific": { "severity": "MODERATE" } },
+                { "id": "OSV-3" },
+            ]
+        }))
+        .unwrap();
+        let findings: Vec<ScriptFinding> =
+            response.vulns.into_iter().map(|vulnerability| vulnerability.into_finding(&dependency)).collect();
+        assert_eq!(
+            findings.iter().map(|finding| finding.severity).collect::<Vec<_>>(),
+            vec![AdvisorySeverity::Critical, AdvisorySeverity::Moderate, AdvisorySeverity::Unknown]
+        );
+
+        let scan = ScriptScanModel {
+            script_checksum: "checksum".into(),
+            dependencies: vec![dependency],
+            findings,
+            scanned_at: Utc::now(),
+        };
+        let blocking = |severity| {
+            scan.get_blocking_findings(severity).iter().map(|finding| finding.advisory_id.as_str()).collect::<Vec<_>>()
+        };
+        assert_eq!(blocking(AdvisorySeverity::Critical), vec!["GHSA-1"]);
+        assert_eq!(blocking(AdvisorySeverity::Moderate), vec!["GHSA-1", "GHSA-2"]);
+        assert_eq!(blocking(AdvisorySeverity::Unknown), vec!["GHSA-1", "GHSA-2"]);
+        // the empty response of a package without advisories
+        assert!(serde_json::from_str::<OsvQueryResponse>("{}").unwrap().vulns.is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::indexer::IndexerModel;
+
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+#[derive(Debug, Deserialize)]
+pub struct ScriptSearchQuery {
+    pub q: String,
+}
+
+/// Indexer whose script matches the search, along with the matching part of the script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptSearchResult {
+    pub indexer: IndexerModel,
+    pub snippet: Option<String>,
+}
+
+/// Returns the part of the content around the first match of the query, or of one of its words,
+/// with the match highlighted. Matches are case insensitive.
+pub fn get_snippet(content: &str, query: &str, context: usize) -> Option<String> {
+    let lowercase = content.to_lowercase();
+    // lowercasing can change the byte length of some characters, offsets would then be wrong
+    if lowercase.len() != content.len() {
+        return None;
+    }
+    let terms = std::iter::once(query.trim()).chain(query.split_whitespace());
+    let (start, length) = terms
+        .filter(|term| !term.is_empty())
+        .find_map(|term| lowercase.find(&term.to_lowercase()).map(|start| (start, term.len())))?;
+    let end = start + length;
+
+    let mut from = start.saturating_sub(context);
+    while !content.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + context).min(content.len());
+    while !content.is_char_boundary(to) {
+        to += 1;
+    }
+    let snippet = format!(
+        "{}{}{}{}{}{}{}",
+        if from > 0 { "..." } else { "" },
+        &content[from..start],
+        HIGHLIGHT_START,
+        &content[start..end],
+        HIGHLIGHT_END,
+        &content[end..to],
+        if to < content.len() { "..." } else { "" },
+    );
+    // snippets are kept on a single line
+    Some(snippet.split_whitespace().collect::<Vec<&str>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_snippet() {
+        let content = "export const config = {\n  filter: { events: [{ fromAddress: \"0xABC123\" }] },\n};";
+        assert_eq!(
+            get_snippet(content, "0xabc", 10),
+            Some("...Address: \"<mark>0xABC</mark>123\" }] },...".to_string())
+        );
+        // falls back to the words of the query
+        assert_eq!(
+            get_snippet(content, "unknown config", 7),
+            Some("... const <mark>config</mark> = { ...".to_string())
+        );
+        assert_eq!(get_snippet(content, "transfer", 10), None);
+        assert_eq!(get_snippet(content, " ", 10), None);
+    }
+}
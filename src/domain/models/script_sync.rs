@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptSyncIssue {
+    /// The indexer has no script in the store
+    Missing,
+    /// The script in the store isn't the one recorded for the indexer
+    ChecksumMismatch,
+    /// The indexer was created before checksums were recorded
+    ChecksumNotRecorded,
+    /// The script in the store belongs to no indexer, these are only reported
+    Orphaned,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptSyncFix {
+    /// The recorded script was put back in the store from the local cache
+    RestoredFromCache,
+    /// The recorded script was fetched again from the url it was created from
+    RestoredFromSource,
+    /// The checksum of the script in the store was recorded
+    RecordedChecksum,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptSyncEntry {
+    pub indexer_id: Option<Uuid>,
+    pub key: String,
+    pub issue: ScriptSyncIssue,
+    pub recorded_checksum: Option<String>,
+    pub stored_checksum: Option<String>,
+    /// Not set if the issue wasn't fixed
+    pub fix: Option<ScriptSyncFix>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptSyncReport {
+    pub fix: bool,
+    pub checked: usize,
+    pub issues: Vec<ScriptSyncEntry>,
+}
+
+/// Compares the checksum recorded for an indexer with the one of the script in the store
+pub fn get_script_sync_issue(
+    recorded_checksum: Option<&str>,
+    stored_checksum: Option<&str>,
+) -> Option<ScriptSyncIssue> {
+    match (recorded_checksum, stored_checksum) {
+        (_, None) => Some(ScriptSyncIssue::Missing),
+        (None, Some(_)) => Some(ScriptSyncIssue::ChecksumNotRecorded),
+        (Some(recorded), Some(stored)) if recorded != stored => Some(ScriptSyncIssue::ChecksumMismatch),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_script_sync_issue() {
+        assert_eq!(get_script_sync_issue(Some("a"), Some("a")), None);
+        assert_eq!(get_script_sync_issue(Some("a"), Some("b")), Some(ScriptSyncIssue::ChecksumMismatch));
+        assert_eq!(get_script_sync_issue(Some("a"), None), Some(ScriptSyncIssue::Missing));
+        assert_eq!(get_script_sync_issue(None, None), Some(ScriptSyncIssue::Missing));
+        assert_eq!(get_script_sync_issue(None, Some("b")), Some(ScriptSyncIssue::ChecksumNotRecorded));
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::sink_log::SinkLogRecord;
+
+#[derive(Debug, Deserialize)]
+pub struct BlockSearchQuery {
+    pub block: i64,
+}
+
+/// What is known of a block across the indexers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockSearchModel {
+    pub block: i64,
+    /// Indexers which delivered the block, deliveries of backfill indexers count for the indexer
+    /// they fill
+    pub indexers: Vec<Uuid>,
+    /// Records of the sinks of this instance referencing the block, oldest first
+    pub logs: Vec<BlockLogRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockLogRecord {
+    pub indexer_id: Uuid,
+    #[serde(flatten)]
+    pub record: SinkLogRecord,
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+use crate::constants::runtime::UNSCOPED_SECRETS_PREFIX;
+
+/// Store a secret reference is resolved from, named after the scheme of its references
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretBackendKind {
+    /// Environment of the service, only enabled in dev
+    Env,
+    /// Files on the disk of the service, only enabled in dev
+    File,
+    /// AWS Secrets Manager, `aws-sm://<secret id>#<key>`
+    AwsSm,
+    /// KV v2 engine of HashiCorp Vault, `vault://<path>#<key>`
+    Vault,
+}
+
+impl SecretBackendKind {
+    /// Backends reading from the host of the service rather than from a secret store
+    pub fn is_local(&self) -> bool {
+        matches!(self, Self::Env | Self::File)
+    }
+}
+
+/// Value of a sink option or of an environment variable standing for a secret, e.g.
+/// `vault://indexers/acme#connection_string`. The secret replaces it when the sink is launched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretReference {
+    pub backend: SecretBackendKind,
+    pub path: String,
+    /// Field of the secret if it's a JSON object, the whole secret otherwise
+    pub key: Option<String>,
+}
+
+impl SecretReference {
+    /// `None` if the value isn't a reference to one of the backends
+    pub fn parse(value: &str) -> Option<Self> {
+        let (scheme, reference) = value.trim().split_once("://")?;
+        let backend = SecretBackendKind::iter().find(|backend| backend.to_string() == scheme)?;
+        let (path, key) = match reference.split_once('#') {
+            Some((path, key)) => (path, Some(key.to_string()).filter(|key| !key.is_empty())),
+            None => (reference, None),
+        };
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self { backend, path: path.to_string(), key })
+    }
+
+    /// Path without its leading slashes, `file:///a/b` and `file://a/b` read the same secret
+    pub fn relative_path(&self) -> &str {
+        self.path.trim_start_matches('/')
+    }
+
+    /// Whether the reference points under the scope, a path prefix such as `acme/`. Paths
+    /// stepping out of their prefix are never in scope.
+    pub fn is_in_scope(&self, scope: &str) -> bool {
+        let path = self.relative_path();
+        let scope = format!("{}/", scope.trim_matches('/'));
+        path.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+            && path.starts_with(&scope)
+    }
+
+    /// Extracts the field of the reference from the secret
+    pub fn select(&self, secret: String) -> Result<String, String> {
+        let Some(key) = &self.key else {
+            return Ok(secret);
+        };
+        let fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&secret).map_err(|_| "the secret is not a JSON object".to_string())?;
+        match fields.get(key) {
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            Some(value) => Ok(value.to_string()),
+            None => Err(format!("the secret has no field {}", key)),
+        }
+    }
+}
+
+/// Prefix the secret references of an indexer must be under. It is the secrets scope of its
+/// project if set, its tenant otherwise, and `UNSCOPED_SECRETS_PREFIX` for the other indexers.
+pub fn get_secret_scope(tenant_id: Option<&str>, project_secret_path_prefix: Option<&str>) -> String {
+    project_secret_path_prefix
+        .or(tenant_id)
+        .filter(|scope| !scope.trim_matches('/').is_empty())
+        .unwrap_or(UNSCOPED_SECRETS_PREFIX)
+        .to_string()
+}
+
+impl fmt::Display for SecretReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}", self.backend, self.path)?;
+        if let Some(key) = &self.key {
+            write!(f, "#{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("vault://indexers/acme#connection_string", Some((SecretBackendKind::Vault, "indexers/acme", Some("connection_string"))))]
+    #[case("aws-sm://prod/acme", Some((SecretBackendKind::AwsSm, "prod/acme", None)))]
+    #[case("env://APIBARA_AUTH_TOKEN", Some((SecretBackendKind::Env, "APIBARA_AUTH_TOKEN", None)))]
+    #[case("file:///run/secrets/acme.json#token", Some((SecretBackendKind::File, "/run/secrets/acme.json", Some("token"))))]
+    #[case("vault://#key", None)]
+    #[case("https://example.com/hook", None)]
+    #[case("postgres://user:password@db/acme", None)]
+    fn test_parse_secret_reference(
+        #[case] value: &str,
+        #[case] expected: Option<(SecretBackendKind, &str, Option<&str>)>,
+    ) {
+        let reference = SecretReference::parse(value);
+        assert_eq!(
+            reference.as_ref().map(|reference| (reference.backend, reference.path.as_str(), reference.key.as_deref())),
+            expected
+        );
+        if let Some(reference) = reference {
+            assert_eq!(reference.to_string(), value);
+        }
+    }
+
+    #[rstest]
+    #[case("vault://acme/db#password", "acme", true)]
+    #[case("vault://acme/db#password", "acme/", true)]
+    #[case("file:///acme/db.json", "acme", true)]
+    #[case("vault://acme-other/db#password", "acme", false)]
+    #[case("vault://acme/../other/db#password", "acme", false)]
+    #[case("vault://acme//db#password", "acme", false)]
+    #[case("env://DATABASE_URL", "acme", false)]
+    #[case("vault://indexers/acme/db", "indexers/acme/", true)]
+    fn test_is_in_scope(#[case] value: &str, #[case] scope: &str, #[case] expected: bool) {
+        assert_eq!(SecretReference::parse(value).unwrap().is_in_scope(scope), expected);
+    }
+
+    #[test]
+    fn test_get_secret_scope() {
+        assert_eq!(get_secret_scope(Some("acme"), Some("indexers/acme/")), "indexers/acme/");
+        assert_eq!(get_secret_scope(Some("acme"), None), "acme");
+        assert_eq!(get_secret_scope(None, None), UNSCOPED_SECRETS_PREFIX);
+        assert_eq!(get_secret_scope(Some("/"), None), UNSCOPED_SECRETS_PREFIX);
+    }
+
+    #[test]
+    fn test_select() {
+        let reference = SecretReference::parse("vault://indexers/acme#port").unwrap();
+        assert_eq!(reference.select(r#"{"port": 5432}"#.into()), Ok("5432".to_string()));
+        assert!(reference.select(r#"{"host": "db"}"#.into()).is_err());
+        assert!(reference.select("not json".into()).is_err());
+
+        let reference = SecretReference::parse("env://TOKEN").unwrap();
+        assert_eq!(reference.select("dna_token".into()), Ok("dna_token".to_string()));
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Block the canned payloads claim to come from
+pub const SAMPLE_BLOCK_NUMBER: u64 = 1;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SimulateDeliveryRequest {
+    /// Output of the script to deliver, e.g. the payload of a preview. A canned one is used if
+    /// not set.
+    pub payload: Option<Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedDeliveryModel {
+    pub target_url: String,
+    /// Body sent to the target
+    pub payload: Value,
+    /// `None` if no response was received
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    /// Truncated, non UTF-8 bytes are replaced
+    pub response_body: Option<String>,
+    /// Why no response was received
+    pub error: Option<String>,
+}
+
+/// Looks like the output of a script returning the events of a contract
+pub fn sample_script_output() -> Value {
+    json!([{
+        "block_number": SAMPLE_BLOCK_NUMBER,
+        "transaction_hash": "0x0",
+        "from_address": "0x0",
+        "keys": ["0x0"],
+        "data": ["0x0"],
+    }])
+}
+
+/// Body the webhook sink sends for the output of the script, which is wrapped with the cursors
+/// of the batch unless the sink sends it raw
+pub fn to_webhook_body(output: Value, block_number: u64, raw: bool) -> Value {
+    if raw {
+        return output;
+    }
+    json!({
+        "data": output,
+        "cursor": {"order_key": block_number.saturating_sub(1), "unique_key": "0x0"},
+        "end_cursor": {"order_key": block_number, "unique_key": "0x0"},
+        "finality": "DATA_STATUS_ACCEPTED",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_webhook_body() {
+        let output = sample_script_output();
+        assert_eq!(to_webhook_body(output.clone(), 10, true), output);
+
+        let body = to_webhook_body(output.clone(), 10, false);
+        assert_eq!(body["data"], output);
+        assert_eq!(body["end_cursor"]["order_key"], 10);
+        assert_eq!(body["cursor"]["order_key"], 9);
+    }
+}
@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::domain::models::indexer::IndexerLogLevel;
+
+/// Fields of the structured logs of the sinks which hold the block being processed
+const BLOCK_NUMBER_FIELDS: [&str; 2] = ["block_number", "block"];
+
+/// A line printed by a sink. Sinks log as JSON, lines which aren't are kept as their text.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SinkLogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: Option<IndexerLogLevel>,
+    pub message: String,
+    pub block_number: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SinkLogQuery {
+    /// Keeps the records at this level or more severe, records without a level are dropped
+    pub level: Option<IndexerLogLevel>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl SinkLogRecord {
+    /// `received_at` is used when the line has no timestamp of its own
+    pub fn parse(line: &str, received_at: DateTime<Utc>) -> Self {
+        let Ok(Value::Object(object)) = serde_json::from_str::<Value>(line) else {
+            return Self { timestamp: received_at, level: None, message: line.to_string(), block_number: None };
+        };
+        // `tracing` puts the message and the fields of the event under `fields`
+        let fields = match object.get("fields") {
+            Some(Value::Object(fields)) => fields,
+            _ => &object,
+        };
+        Self {
+            timestamp: object
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .unwrap_or(received_at),
+            level: object
+                .get("level")
+                .and_then(Value::as_str)
+                .and_then(|level| IndexerLogLevel::from_str(&level.to_lowercase()).ok()),
+            message: match fields.get("message") {
+                Some(Value::String(message)) => message.clone(),
+                _ => line.to_string(),
+            },
+            block_number: get_block_number(fields),
+        }
+    }
+}
+
+fn get_block_number(fields: &Map<String, Value>) -> Option<u64> {
+    BLOCK_NUMBER_FIELDS.iter().find_map(|field| match fields.get(*field)? {
+        Value::Number(number) => number.as_u64(),
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    })
+}
+
+impl SinkLogQuery {
+    pub fn matches(&self, record: &SinkLogRecord) -> bool {
+        if let Some(level) = self.level {
+            if !record.level.is_some_and(|record_level| record_level <= level) {
+                return false;
+            }
+        }
+        if self.from_block.is_none() && self.to_block.is_none() {
+            return true;
+        }
+        let Some(block_number) = record.block_number else {
+            return false;
+        };
+        !self.from_block.is_some_and(|from_block| block_number < from_block)
+            && !self.to_block.is_some_and(|to_block| block_number > to_block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_sink_log_record() {
+        let received_at = Utc::now();
+        let line = r#"{"timestamp":"2024-01-02T03:04:05Z","level":"WARN","fields":{"message":"retrying","block_number":42},"target":"apibara_sink_common"}"#;
+        let record = SinkLogRecord::parse(line, received_at);
+        assert_eq!(record.timestamp, DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap());
+        assert_eq!(record.level, Some(IndexerLogLevel::Warn));
+        assert_eq!(record.message, "retrying");
+        assert_eq!(record.block_number, Some(42));
+
+        let record = SinkLogRecord::parse(r#"{"level":"info","message":"started","block":"7"}"#, received_at);
+        assert_eq!(
+            (record.timestamp, record.level, record.block_number),
+            (received_at, Some(IndexerLogLevel::Info), Some(7))
+        );
+        assert_eq!(record.message, "started");
+
+        let record = SinkLogRecord::parse("plain text", received_at);
+        assert_eq!(
+            record,
+            SinkLogRecord { timestamp: received_at, level: None, message: "plain text".into(), block_number: None }
+        );
+    }
+
+    #[rstest]
+    #[case(Some(IndexerLogLevel::Warn), None, None, Some(IndexerLogLevel::Error), Some(1), true)]
+    #[case(Some(IndexerLogLevel::Warn), None, None, Some(IndexerLogLevel::Info), Some(1), false)]
+    #[case(Some(IndexerLogLevel::Warn), None, None, None, Some(1), false)]
+    #[case(None, Some(10), Some(20), None, Some(15), true)]
+    #[case(None, Some(10), Some(20), None, Some(21), false)]
+    #[case(None, Some(10), None, None, None, false)]
+    #[case(None, None, None, None, None, true)]
+    fn test_sink_log_query_matches(
+        #[case] level: Option<IndexerLogLevel>,
+        #[case] from_block: Option<u64>,
+        #[case] to_block: Option<u64>,
+        #[case] record_level: Option<IndexerLogLevel>,
+        #[case] block_number: Option<u64>,
+        #[case] expected: bool,
+    ) {
+        let query = SinkLogQuery { level, from_block, to_block, limit: None };
+        let record = SinkLogRecord { timestamp: Utc::now(), level: record_level, message: "".into(), block_number };
+        assert_eq!(query.matches(&record), expected);
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::indexers::MAX_STOP_FLUSH_TIMEOUT_SECONDS;
+use crate::domain::models::indexer::{IndexerError, IndexerType};
+
+/// Options specific to the sink of an indexer, tagged with the indexer type they apply to.
+/// Unknown options are rejected.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkOptions {
+    Webhook(WebhookOptions),
+    Postgres(PostgresOptions),
+    Parquet(ParquetOptions),
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookOptions {
+    /// Sends the payloads returned by the script as is instead of wrapping them with the cursors
+    pub raw: bool,
+    /// Extra headers of the requests, formatted as `name: value`
+    pub headers: Vec<String>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostgresOptions {
+    /// Stores the payloads as entities updated in place rather than as rows appended per block
+    pub entity_mode: bool,
+    pub no_tls: bool,
+    pub tls_certificate: Option<String>,
+    pub tls_accept_invalid_certificates: bool,
+    /// Stops interrupt the sink and wait up to this long for it to write its in-flight batch and
+    /// exit before killing it. The sink is only sent `SIGTERM` if not set.
+    pub flush_timeout_seconds: Option<u64>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParquetOptions {
+    /// Blocks written per file, the default of the sink if not set
+    pub batch_size: Option<u64>,
+}
+
+impl SinkOptions {
+    pub fn indexer_type(&self) -> IndexerType {
+        match self {
+            Self::Webhook(_) => IndexerType::Webhook,
+            Self::Postgres(_) => IndexerType::Postgres,
+            Self::Parquet(_) => IndexerType::Parquet,
+        }
+    }
+
+    /// Checks the options apply to the indexer type and don't conflict with each other
+    pub fn validate(&self, indexer_type: &IndexerType) -> Result<(), IndexerError> {
+        if self.indexer_type() != *indexer_type {
+            return Err(IndexerError::InvalidSinkOptions(format!(
+                "{} options don't apply to {} indexers",
+                self.indexer_type(),
+                indexer_type
+            )));
+        }
+        match self {
+            Self::Webhook(options) => {
+                let mut names = vec![];
+                for header in &options.headers {
+                    let name = match header.split_once(':') {
+                        Some((name, _)) if !name.trim().is_empty() => name.trim().to_lowercase(),
+                        _ => {
+                            return Err(IndexerError::InvalidSinkOptions(format!(
+                                "header {} isn't formatted as `name: value`",
+                                header
+                            )));
+                        }
+                    };
+                    if names.contains(&name) {
+                        return Err(IndexerError::InvalidSinkOptions(format!("header {} is set twice", name)));
+                    }
+                    names.push(name);
+                }
+            }
+            Self::Postgres(options) => {
+                if options.no_tls && (options.tls_certificate.is_some() || options.tls_accept_invalid_certificates) {
+                    return Err(IndexerError::InvalidSinkOptions("tls options conflict with no_tls".into()));
+                }
+                if options
+                    .flush_timeout_seconds
+                    .is_some_and(|seconds| seconds == 0 || seconds > MAX_STOP_FLUSH_TIMEOUT_SECONDS)
+                {
+                    return Err(IndexerError::InvalidSinkOptions(format!(
+                        "flush_timeout_seconds must be between 1 and {}",
+                        MAX_STOP_FLUSH_TIMEOUT_SECONDS
+                    )));
+                }
+            }
+            Self::Parquet(options) => {
+                if options.batch_size == Some(0) {
+                    return Err(IndexerError::InvalidSinkOptions("batch_size must be positive".into()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_sink_options() {
+        let options: SinkOptions = serde_json::from_str(r#"{"type": "webhook", "raw": true}"#).unwrap();
+        assert_eq!(options, SinkOptions::Webhook(WebhookOptions { raw: true, headers: vec![] }));
+        assert!(serde_json::from_str::<SinkOptions>(r#"{"type": "webhook", "entity_mode": true}"#).is_err());
+        assert!(serde_json::from_str::<SinkOptions>(r#"{"type": "kafka"}"#).is_err());
+    }
+
+    #[test]
+    fn test_validate_sink_options() {
+        let webhook = |headers: &[&str]| {
+            SinkOptions::Webhook(WebhookOptions {
+                raw: false,
+                headers: headers.iter().map(|h| h.to_string()).collect(),
+            })
+        };
+        assert!(webhook(&["Authorization: Bearer token"]).validate(&IndexerType::Webhook).is_ok());
+        assert!(webhook(&[]).validate(&IndexerType::Postgres).is_err());
+        assert!(webhook(&["no separator"]).validate(&IndexerType::Webhook).is_err());
+        assert!(webhook(&["X-Key: a", "x-key: b"]).validate(&IndexerType::Webhook).is_err());
+
+        let postgres = SinkOptions::Postgres(PostgresOptions {
+            no_tls: true,
+            tls_accept_invalid_certificates: true,
+            ..Default::default()
+        });
+        assert!(postgres.validate(&IndexerType::Postgres).is_err());
+
+        let postgres = |flush_timeout_seconds: u64| {
+            SinkOptions::Postgres(PostgresOptions {
+                flush_timeout_seconds: Some(flush_timeout_seconds),
+                ..Default::default()
+            })
+        };
+        assert!(postgres(30).validate(&IndexerType::Postgres).is_ok());
+        assert!(postgres(0).validate(&IndexerType::Postgres).is_err());
+        assert!(postgres(MAX_STOP_FLUSH_TIMEOUT_SECONDS + 1).validate(&IndexerType::Postgres).is_err());
+
+        let parquet = |batch_size| SinkOptions::Parquet(ParquetOptions { batch_size });
+        assert!(parquet(Some(1000)).validate(&IndexerType::Parquet).is_ok());
+        assert!(parquet(Some(0)).validate(&IndexerType::Parquet).is_err());
+        assert!(parquet(None).validate(&IndexerType::Webhook).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::constants::indexers::{DEFAULT_STALE_CREATED_AFTER_SECONDS, MAX_STALE_CREATED_REPLAYS};
+use crate::domain::models::tenant::TenantSettingsModel;
+
+/// What the sweep does with an indexer left in `Created`, e.g. when its start was lost
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumString, Display, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum StaleCreatedAction {
+    /// Starts the indexer again, it's abandoned once the replays are exhausted
+    #[default]
+    Replay,
+    Abandon,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaleCreatedStep {
+    Replay,
+    Abandon,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StaleCreatedPolicy {
+    pub after_seconds: u32,
+    pub action: StaleCreatedAction,
+}
+
+impl StaleCreatedPolicy {
+    /// Settings of the tenant of the indexer, the defaults apply to indexers without one
+    pub fn for_tenant(settings: Option<&TenantSettingsModel>) -> Self {
+        Self {
+            after_seconds: settings
+                .and_then(|settings| settings.stale_created_after_seconds)
+                .unwrap_or(DEFAULT_STALE_CREATED_AFTER_SECONDS),
+            action: settings.and_then(|settings| settings.stale_created_action).unwrap_or_default(),
+        }
+    }
+
+    /// Step due for an indexer in `Created` since `created_at`, whose start was replayed
+    /// `replays` times, the last one at `last_replay_at`. A replayed start gets the whole
+    /// threshold again.
+    pub fn next_step(
+        &self,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+        replays: u32,
+        last_replay_at: Option<DateTime<Utc>>,
+    ) -> Option<StaleCreatedStep> {
+        let since = last_replay_at.unwrap_or(created_at);
+        if now - since < chrono::Duration::seconds(self.after_seconds.into()) {
+            return None;
+        }
+        match self.action {
+            StaleCreatedAction::Replay if replays < MAX_STALE_CREATED_REPLAYS => Some(StaleCreatedStep::Replay),
+            _ => Some(StaleCreatedStep::Abandon),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(StaleCreatedAction::Replay, 59, 0, None, None)]
+    #[case(StaleCreatedAction::Replay, 60, 0, None, Some(StaleCreatedStep::Replay))]
+    #[case(StaleCreatedAction::Replay, 120, 1, Some(90), None)]
+    #[case(StaleCreatedAction::Replay, 120, 1, Some(60), Some(StaleCreatedStep::Replay))]
+    #[case(StaleCreatedAction::Replay, 600, MAX_STALE_CREATED_REPLAYS, Some(60), Some(StaleCreatedStep::Abandon))]
+    #[case(StaleCreatedAction::Abandon, 59, 0, None, None)]
+    #[case(StaleCreatedAction::Abandon, 60, 0, None, Some(StaleCreatedStep::Abandon))]
+    fn test_next_step(
+        #[case] action: StaleCreatedAction,
+        #[case] elapsed: i64,
+        #[case] replays: u32,
+        #[case] last_replay: Option<i64>,
+        #[case] expected: Option<StaleCreatedStep>,
+    ) {
+        let policy = StaleCreatedPolicy { after_seconds: 60, action };
+        let created_at = Utc::now();
+        let last_replay_at = last_replay.map(|seconds| created_at + Duration::seconds(seconds));
+        assert_eq!(
+            policy.next_step(created_at, created_at + Duration::seconds(elapsed), replays, last_replay_at),
+            expected
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Single use token an external orchestrator redeems to start an indexer without holding the
+/// admin key
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartTokenModel {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    /// Admin who minted the token, as declared with `x-admin-actor`
+    pub created_by: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returned once when the token is minted, only its hash is kept
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MintedStartTokenModel {
+    pub token: String,
+    #[serde(flatten)]
+    pub start_token: StartTokenModel,
+}
+
+pub fn generate_start_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+pub fn hash_start_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_start_token() {
+        let token = generate_start_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_start_token());
+        assert_eq!(hash_start_token(&token), hash_start_token(&token));
+        assert_ne!(hash_start_token(&token), token);
+        assert_eq!(hash_start_token("token"), "3c469e9d6c5875d37a43f353d4f88e61fcf812c66eee3457465a40b0da4153e0");
+    }
+}
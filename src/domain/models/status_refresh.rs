@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::indexer::IndexerStatus;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StatusRefreshRequest {
+    /// Indexers to check, every indexer if empty
+    #[serde(default)]
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusCorrection {
+    pub indexer_id: Uuid,
+    pub from_status: IndexerStatus,
+    pub to_status: IndexerStatus,
+}
+
+/// An indexer whose process couldn't be checked, its status is left as it is
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusRefreshFailure {
+    pub indexer_id: Uuid,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusRefreshReport {
+    pub checked: usize,
+    pub corrections: Vec<StatusCorrection>,
+    pub failures: Vec<StatusRefreshFailure>,
+}
+
+/// Whether the process of an indexer in this status is checked. Other statuses either have no
+/// process or one that already exited.
+pub fn is_refreshable(status: IndexerStatus) -> bool {
+    status.is_live() || status == IndexerStatus::FailedStopping
+}
+
+/// Status the indexer should have given whether its process is running, `None` if it's right
+pub fn get_status_correction(status: IndexerStatus, running: bool) -> Option<IndexerStatus> {
+    match (status, running) {
+        (IndexerStatus::Starting | IndexerStatus::Running, false) => Some(IndexerStatus::FailedRunning),
+        // the stop failed but the process exited since, or it didn't and the indexer still runs
+        (IndexerStatus::FailedStopping, false) => Some(IndexerStatus::Stopped),
+        (IndexerStatus::FailedStopping, true) => Some(IndexerStatus::Running),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(IndexerStatus::Running, true, None)]
+    #[case(IndexerStatus::Running, false, Some(IndexerStatus::FailedRunning))]
+    #[case(IndexerStatus::FailedStopping, true, Some(IndexerStatus::Running))]
+    #[case(IndexerStatus::FailedStopping, false, Some(IndexerStatus::Stopped))]
+    #[case(IndexerStatus::Stopped, false, None)]
+    fn test_get_status_correction(
+        #[case] status: IndexerStatus,
+        #[case] running: bool,
+        #[case] expected: Option<IndexerStatus>,
+    ) {
+        assert_eq!(get_status_correction(status, running), expected);
+    }
+}
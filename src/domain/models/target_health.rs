@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use crate::constants::indexers::{TARGET_GONE_MIN_RESPONSES, TARGET_GONE_STOP_AFTER_SECONDS};
+
+/// Reason recorded when an indexer is stopped because its target is gone
+pub const TARGET_GONE_REASON: &str = "TargetGone";
+
+/// Statuses meaning the target no longer exists
+pub fn is_gone_status(status: u16) -> bool {
+    status == 404 || status == 410
+}
+
+/// Returns the HTTP status a sink log line reports for a delivery, if any, e.g. `status code:
+/// 410`, `status=404` or `HTTP 410`. This is a best effort as the sinks don't log in a structured
+/// way.
+pub fn parse_response_status(line: &str) -> Option<u16> {
+    let lowercase = line.to_lowercase();
+    let words: Vec<&str> =
+        lowercase.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    words.windows(2).find_map(|pair| match pair {
+        ["status" | "code" | "http", code] => code.parse::<u16>().ok().filter(|code| (100..600).contains(code)),
+        [code, "gone"] if *code == "410" => Some(410),
+        _ => None,
+    })
+}
+
+/// Follows the responses of the target of an indexer to tell when it only answers that it's gone
+#[derive(Clone, Debug, Default)]
+pub struct TargetGoneDetector {
+    since: Option<Instant>,
+    last: Option<Instant>,
+    responses: usize,
+}
+
+impl TargetGoneDetector {
+    /// Records a response of the target, returns whether the target is considered gone. Any
+    /// other response, or no response for a whole period, starts over.
+    pub fn record(&mut self, now: Instant, status: u16) -> bool {
+        let period = Duration::from_secs(TARGET_GONE_STOP_AFTER_SECONDS);
+        let is_stale = self.last.is_some_and(|last| now.duration_since(last) > period);
+        if !is_gone_status(status) || is_stale {
+            *self = Self::default();
+        }
+        if !is_gone_status(status) {
+            return false;
+        }
+        let since = *self.since.get_or_insert(now);
+        self.last = Some(now);
+        self.responses += 1;
+        self.responses >= TARGET_GONE_MIN_RESPONSES && now.duration_since(since) >= period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("Webhook request failed, status code: 410", Some(410))]
+    #[case("delivery failed status=404 url=https://example.com", Some(404))]
+    #[case("HTTP 503 Service Unavailable", Some(503))]
+    #[case("target answered 410 Gone", Some(410))]
+    #[case("processed block 410", None)]
+    #[case("status: ok", None)]
+    fn test_parse_response_status(#[case] line: &str, #[case] status: Option<u16>) {
+        assert_eq!(parse_response_status(line), status);
+    }
+
+    #[test]
+    fn test_target_gone_detector() {
+        let now = Instant::now();
+        let period = Duration::from_secs(TARGET_GONE_STOP_AFTER_SECONDS);
+        let step = period / TARGET_GONE_MIN_RESPONSES as u32;
+
+        let mut detector = TargetGoneDetector::default();
+        for i in 0..TARGET_GONE_MIN_RESPONSES as u32 {
+            assert!(!detector.record(now + step * i, 410));
+        }
+        assert!(detector.record(now + period, 404));
+
+        // a successful delivery starts over
+        let mut detector = TargetGoneDetector::default();
+        for i in 0..TARGET_GONE_MIN_RESPONSES as u32 {
+            detector.record(now + step * i, 410);
+        }
+        assert!(!detector.record(now + period - step, 200));
+        assert!(!detector.record(now + period, 410));
+
+        // not enough responses
+        let mut detector = TargetGoneDetector::default();
+        assert!(!detector.record(now, 410));
+        assert!(!detector.record(now + period, 410));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::indexers::START_RATE_WINDOW_SECONDS;
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerType};
+use crate::domain::models::secret::SecretBackendKind;
+use crate::domain::models::stale_created::StaleCreatedAction;
+use crate::domain::models::types::AxumErrorResponse;
+use crate::infra::errors::InfraError;
+
+/// Defaults applied to the indexers created by a tenant, every setting can be overridden in the
+/// create request
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenantSettingsModel {
+    pub tenant_id: String,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<IndexerLogLevel>,
+    /// An empty list allows every indexer type
+    pub allowed_indexer_types: Vec<IndexerType>,
+    /// Starts of the indexers of the tenant allowed per minute, starts above it are deferred.
+    /// Unlimited if not set.
+    pub max_starts_per_minute: Option<u32>,
+    /// Time the indexers of the tenant can stay in `Created` before the sweep acts on them, the
+    /// default one if not set
+    pub stale_created_after_seconds: Option<u32>,
+    pub stale_created_action: Option<StaleCreatedAction>,
+    /// Webhook indexers targeting a destination missing from the approved ones of the tenant
+    /// wait in `PendingApproval` until an admin approves it
+    pub require_target_approval: bool,
+    /// Secret references of the indexers of the tenant must use this backend, any enabled one
+    /// can be used if not set
+    pub secret_backend: Option<SecretBackendKind>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TenantSettingsModel {
+    pub fn is_indexer_type_allowed(&self, indexer_type: &IndexerType) -> bool {
+        self.allowed_indexer_types.is_empty() || self.allowed_indexer_types.contains(indexer_type)
+    }
+}
+
+/// Key of a tenant, sent along with its id in the `x-tenant-key` header
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenantKeyModel {
+    pub tenant_id: String,
+    pub key: String,
+}
+
+/// Starts of the indexers of a tenant against its limit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenantQuotaModel {
+    pub tenant_id: String,
+    pub max_starts_per_minute: Option<u32>,
+    pub starts_last_minute: usize,
+    /// Starts waiting for the limit to allow them
+    pub deferred_starts: usize,
+}
+
+/// Starts of the indexers of a tenant over the last window
+#[derive(Clone, Debug, Default)]
+pub struct StartWindow {
+    starts: VecDeque<Instant>,
+    pub deferred: usize,
+}
+
+impl StartWindow {
+    /// Records the start if the limit allows it, otherwise returns how long to wait before
+    /// trying again
+    pub fn try_start(&mut self, now: Instant, max_starts_per_minute: Option<u32>) -> Result<(), Duration> {
+        let window = Duration::from_secs(START_RATE_WINDOW_SECONDS);
+        self.expire(now);
+        if let (Some(max_starts), Some(oldest)) = (max_starts_per_minute, self.starts.front()) {
+            if self.starts.len() >= max_starts as usize {
+                return Err(window.saturating_sub(now.duration_since(*oldest)));
+            }
+        }
+        self.starts.push_back(now);
+        Ok(())
+    }
+
+    pub fn starts(&mut self, now: Instant) -> usize {
+        self.expire(now);
+        self.starts.len()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let window = Duration::from_secs(START_RATE_WINDOW_SECONDS);
+        while self.starts.front().is_some_and(|start| now.duration_since(*start) >= window) {
+            self.starts.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TenantError {
+    #[error("settings of tenant {0} not found")]
+    SettingsNotFound(String),
+    #[error("invalid tenant settings : {0}")]
+    InvalidSettings(String),
+    #[error("invalid usage query : {0}")]
+    InvalidUsageQuery(String),
+    #[error("invalid target url : {0}")]
+    InvalidTargetUrl(String),
+    #[error("target {0} is not approved")]
+    ApprovedTargetNotFound(String),
+    #[error("the caller can't act for tenant {0}")]
+    Forbidden(String),
+    #[error("tenant keys are disabled, TENANT_KEY_SECRET is not set")]
+    TenantKeysDisabled,
+    #[error("infra error : {0}")]
+    InfraError(InfraError),
+}
+
+impl IntoResponse for TenantError {
+    fn into_response(self) -> axum::response::Response {
+        tracing::error!("Error: {:?}", self);
+        let (status, err_msg) = match self {
+            Self::SettingsNotFound(_) | Self::ApprovedTargetNotFound(_) => {
+                (StatusCode::NOT_FOUND, format!("Not found: {}", self))
+            }
+            Self::InvalidSettings(_)
+            | Self::InvalidUsageQuery(_)
+            | Self::InvalidTargetUrl(_)
+            | Self::TenantKeysDisabled => (StatusCode::BAD_REQUEST, format!("Bad request: {}", self)),
+            Self::Forbidden(_) => (StatusCode::FORBIDDEN, format!("Forbidden: {}", self)),
+            Self::InfraError(InfraError::DatabaseBusy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", self))
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", self)),
+        };
+        (
+            status,
+            Json(AxumErrorResponse {
+                resource: "TenantSettings".into(),
+                message: err_msg,
+                happened_at: chrono::Utc::now(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_indexer_type_allowed() {
+        let mut settings = TenantSettingsModel {
+            tenant_id: "tenant".into(),
+            default_stream_url: None,
+            default_log_level: None,
+            allowed_indexer_types: vec![],
+            max_starts_per_minute: None,
+            stale_created_after_seconds: None,
+            stale_created_action: None,
+            require_target_approval: false,
+            secret_backend: None,
+            updated_at: Utc::now(),
+        };
+        assert!(settings.is_indexer_type_allowed(&IndexerType::Postgres));
+
+        settings.allowed_indexer_types = vec![IndexerType::Webhook];
+        assert!(settings.is_indexer_type_allowed(&IndexerType::Webhook));
+        assert!(!settings.is_indexer_type_allowed(&IndexerType::Postgres));
+    }
+
+    #[test]
+    fn test_start_window() {
+        let now = Instant::now();
+        let mut window = StartWindow::default();
+        assert!(window.try_start(now, Some(2)).is_ok());
+        assert!(window.try_start(now + Duration::from_secs(10), Some(2)).is_ok());
+        assert_eq!(window.try_start(now + Duration::from_secs(20), Some(2)), Err(Duration::from_secs(40)));
+        assert_eq!(window.starts(now + Duration::from_secs(20)), 2);
+
+        // the first start left the window
+        assert!(window.try_start(now + Duration::from_secs(60), Some(2)).is_ok());
+        assert!(window.try_start(now + Duration::from_secs(60), None).is_ok());
+        assert_eq!(window.starts(now + Duration::from_secs(60)), 3);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::domain::models::types::AxumErrorResponse;
+use crate::infra::errors::InfraError;
+
+#[derive(Clone, Default, Debug, PartialEq, EnumString, Serialize, Deserialize, Display, Copy)]
+pub enum UploadStatus {
+    #[default]
+    InProgress,
+    Completed,
+}
+
+/// A resumable upload of a script bundle. Parts can be uploaded (and re-uploaded) in any
+/// order, the upload is only assembled once all the parts are registered. Sessions are
+/// persisted so uploads resume across restarts of the service, until they expire.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub id: Uuid,
+    /// Tenant of the caller who created the session, only its callers and the admins use it
+    pub tenant_id: Option<String>,
+    pub status: UploadStatus,
+    pub parts: BTreeMap<u32, UploadPart>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UploadPart {
+    pub part_number: u32,
+    pub size: usize,
+    pub sha256: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("upload session {0} not found")]
+    SessionNotFound(Uuid),
+    #[error("upload session {0} belongs to another tenant")]
+    AccessDenied(Uuid),
+    #[error("upload sessions belong to a tenant, set the tenant or act as an admin")]
+    TenantRequired,
+    #[error("upload session {0} is already completed")]
+    SessionCompleted(Uuid),
+    #[error("invalid part number {0}, parts start at 1")]
+    InvalidPartNumber(u32),
+    #[error("missing parts {0:?}")]
+    MissingParts(Vec<u32>),
+    #[error("upload session {0} is not completed yet")]
+    UploadNotComplete(Uuid),
+    #[error("failed to upload to object_store")]
+    FailedToUploadToStore(object_store::Error),
+    #[error("failed to get from object_store")]
+    FailedToGetFromStore(object_store::Error),
+    #[error("failed to query db: {0}")]
+    InfraError(InfraError),
+}
+
+impl IntoResponse for UploadError {
+    fn into_response(self) -> axum::response::Response {
+        tracing::error!("Error: {:?}", self);
+        let (status, err_msg) = match self {
+            Self::SessionNotFound(_) => (StatusCode::NOT_FOUND, format!("Not found: {}", self)),
+            Self::AccessDenied(_) | Self::TenantRequired => (StatusCode::FORBIDDEN, format!("Forbidden: {}", self)),
+            Self::InfraError(InfraError::DatabaseBusy) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Service unavailable: {}", InfraError::DatabaseBusy))
+            }
+            Self::SessionCompleted(_)
+            | Self::InvalidPartNumber(_)
+            | Self::MissingParts(_)
+            | Self::UploadNotComplete(_) => (StatusCode::BAD_REQUEST, format!("Bad request: {}", self)),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Internal server error: {}", self)),
+        };
+        (
+            status,
+            Json(AxumErrorResponse {
+                resource: "UploadSession".into(),
+                message: err_msg,
+                happened_at: chrono::Utc::now(),
+            }),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Usage of an indexer of a tenant on a day, as metered while it runs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecordModel {
+    pub tenant_id: String,
+    pub indexer_id: Uuid,
+    pub day: NaiveDate,
+    pub running_seconds: i64,
+    pub blocks_processed: i64,
+    pub script_bytes: i64,
+    /// Output of the sink, which ends up in the logs of the service
+    pub log_bytes: i64,
+    /// Received from the DNA stream, as reported by the sidecar of the sink
+    pub streamed_bytes: i64,
+    /// Sent to the target, as reported by the sidecar of the sink or relayed by the multiplexer
+    pub delivered_bytes: i64,
+}
+
+/// Calendar month usage is billed for, written `YYYY-MM`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BillingPeriod {
+    year: i32,
+    month: u32,
+}
+
+impl BillingPeriod {
+    pub fn current(now: DateTime<Utc>) -> Self {
+        Self { year: now.year(), month: now.month() }
+    }
+
+    pub fn parse(period: &str) -> Option<Self> {
+        let (year, month) = period.split_once('-')?;
+        let period = Self { year: year.parse().ok()?, month: month.parse().ok()? };
+        // the first day validates the month
+        NaiveDate::from_ymd_opt(period.year, period.month, 1)?;
+        Some(period)
+    }
+
+    /// First day of the period
+    pub fn start(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("billing period is validated")
+    }
+
+    /// First day after the period
+    pub fn end(&self) -> NaiveDate {
+        let (year, month) = if self.month == 12 { (self.year + 1, 1) } else { (self.year, self.month + 1) };
+        NaiveDate::from_ymd_opt(year, month, 1).expect("billing period is validated")
+    }
+}
+
+impl fmt::Display for BillingPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexerUsageModel {
+    pub indexer_id: Uuid,
+    pub running_hours: f64,
+    pub blocks_processed: i64,
+    /// Largest size of the script over the period
+    pub script_bytes: i64,
+    pub log_bytes: i64,
+    pub streamed_bytes: i64,
+    pub delivered_bytes: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenantUsageModel {
+    pub tenant_id: String,
+    pub period: String,
+    pub running_hours: f64,
+    pub blocks_processed: i64,
+    pub script_bytes: i64,
+    pub log_bytes: i64,
+    pub streamed_bytes: i64,
+    pub delivered_bytes: i64,
+    pub indexers: Vec<IndexerUsageModel>,
+}
+
+/// Network traffic of a sink since its previous report, sent by the sidecar accounting for it
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressReport {
+    pub streamed_bytes: u64,
+    pub delivered_bytes: u64,
+}
+
+/// Sums the daily records of the tenant over the period, per indexer and overall
+pub fn summarize_usage(tenant_id: String, period: BillingPeriod, records: &[UsageRecordModel]) -> TenantUsageModel {
+    let mut by_indexer: BTreeMap<Uuid, (i64, IndexerUsageModel)> = BTreeMap::new();
+    for record in records {
+        let (running_seconds, usage) = by_indexer
+            .entry(record.indexer_id)
+            .or_insert_with(|| (0, IndexerUsageModel { indexer_id: record.indexer_id, ..Default::default() }));
+        *running_seconds += record.running_seconds;
+        usage.blocks_processed += record.blocks_processed;
+        usage.script_bytes = usage.script_bytes.max(record.script_bytes);
+        usage.log_bytes += record.log_bytes;
+        usage.streamed_bytes += record.streamed_bytes;
+        usage.delivered_bytes += record.delivered_bytes;
+    }
+    let indexers: Vec<IndexerUsageModel> = by_indexer
+        .into_values()
+        .map(|(running_seconds, usage)| IndexerUsageModel { running_hours: running_seconds as f64 / 3600.0, ..usage })
+        .collect();
+
+    TenantUsageModel {
+        tenant_id,
+        period: period.to_string(),
+        running_hours: indexers.iter().map(|usage| usage.running_hours).sum(),
+        blocks_processed: indexers.iter().map(|usage| usage.blocks_processed).sum(),
+        script_bytes: indexers.iter().map(|usage| usage.script_bytes).sum(),
+        log_bytes: indexers.iter().map(|usage| usage.log_bytes).sum(),
+        streamed_bytes: indexers.iter().map(|usage| usage.streamed_bytes).sum(),
+        delivered_bytes: indexers.iter().map(|usage| usage.delivered_bytes).sum(),
+        indexers,
+    }
+}
+
+/// Blocks processed since the last sample, nothing for the sample where the cursor went
+/// backwards, e.g. on a restart from an earlier block
+pub fn get_processed_blocks(last_block: Option<u64>, current_block: Option<u64>) -> u64 {
+    match (last_block, current_block) {
+        (Some(last_block), Some(current_block)) => current_block.saturating_sub(last_block),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("2025-07", Some(("2025-07-01", "2025-08-01")))]
+    #[case("2025-12", Some(("2025-12-01", "2026-01-01")))]
+    #[case("2025-13", None)]
+    #[case("july", None)]
+    fn test_billing_period(#[case] period: &str, #[case] expected: Option<(&str, &str)>) {
+        let parsed = BillingPeriod::parse(period);
+        let bounds = parsed.map(|period| (period.start().to_string(), period.end().to_string()));
+        assert_eq!(bounds, expected.map(|(start, end)| (start.to_string(), end.to_string())));
+        if let Some(parsed) = parsed {
+            assert_eq!(parsed.to_string(), period);
+        }
+    }
+
+    #[test]
+    fn test_summarize_usage() {
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let record = |indexer_id: Uuid, day: u32, running_seconds: i64, script_bytes: i64| UsageRecordModel {
+            tenant_id: "tenant".into(),
+            indexer_id,
+            day: NaiveDate::from_ymd_opt(2025, 7, day).unwrap(),
+            running_seconds,
+            blocks_processed: 10,
+            script_bytes,
+            log_bytes: 100,
+            streamed_bytes: 1000,
+            delivered_bytes: running_seconds,
+        };
+        let records = [record(first, 1, 3600, 1000), record(first, 2, 1800, 2000), record(second, 1, 1800, 500)];
+
+        let usage = summarize_usage("tenant".into(), BillingPeriod::parse("2025-07").unwrap(), &records);
+        assert_eq!(usage.period, "2025-07");
+        assert_eq!(usage.running_hours, 2.0);
+        assert_eq!(usage.blocks_processed, 30);
+        assert_eq!(usage.script_bytes, 2500);
+        assert_eq!(usage.log_bytes, 300);
+        assert_eq!(usage.streamed_bytes, 3000);
+        assert_eq!(usage.delivered_bytes, 7200);
+        let first_usage = usage.indexers.iter().find(|usage| usage.indexer_id == first).unwrap();
+        assert_eq!((first_usage.running_hours, first_usage.script_bytes), (1.5, 2000));
+    }
+
+    #[rstest]
+    #[case(None, Some(100), 0)]
+    #[case(Some(100), Some(150), 50)]
+    #[case(Some(150), Some(100), 0)]
+    #[case(Some(150), None, 0)]
+    fn test_get_processed_blocks(#[case] last: Option<u64>, #[case] current: Option<u64>, #[case] expected: u64) {
+        assert_eq!(get_processed_blocks(last, current), expected);
+    }
+}
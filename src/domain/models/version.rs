@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::indexer::IndexerType;
+
+/// Build of the service and what it was built with, so that tools can check what a deployment
+/// supports before calling it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VersionModel {
+    pub version: String,
+    /// Not set if the service was built outside of the repository without `GIT_SHA`
+    pub git_sha: Option<String>,
+    pub build_time: Option<DateTime<Utc>>,
+    pub features: BuildFeatures,
+    pub indexer_types: Vec<IndexerType>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuildFeatures {
+    /// How sinks are run, `process` or `multiplexer`
+    pub execution_backend: String,
+    /// Not set as lifecycle changes are applied in the request, there is no queue
+    pub queue_backend: Option<String>,
+    /// Object store the scripts are kept in, `gcs` or `s3`
+    pub storage_backend: String,
+}
+
+impl VersionModel {
+    /// One line summary logged when the service starts
+    pub fn banner(&self) -> String {
+        format!(
+            "indexer-service {} ({}, built {}) with {} storage and {} execution",
+            self.version,
+            self.git_sha.as_deref().map(|sha| &sha[..sha.len().min(12)]).unwrap_or("unknown commit"),
+            self.build_time.map(|build_time| build_time.to_rfc3339()).unwrap_or_else(|| "at an unknown time".into()),
+            self.features.storage_backend,
+            self.features.execution_backend,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_banner() {
+        let version = VersionModel {
+            version: "0.1.0".into(),
+            git_sha: Some("9229cfc1737e255bfa17f41e26b4e0623023e3ed".into()),
+            build_time: Some(Utc.timestamp_opt(1_700_000_000, 0).unwrap()),
+            features: BuildFeatures {
+                execution_backend: "process".into(),
+                queue_backend: None,
+                storage_backend: "gcs".into(),
+            },
+            indexer_types: vec![IndexerType::Webhook, IndexerType::Postgres],
+        };
+        assert_eq!(
+            version.banner(),
+            "indexer-service 0.1.0 (9229cfc1737e, built 2023-11-14T22:13:20+00:00) with gcs storage and process \
+             execution"
+        );
+    }
+}
@@ -5,13 +5,26 @@ use diesel::ConnectionError;
 use serde_json::json;
 
 use crate::domain::models::indexer::IndexerError;
+use crate::infra::errors::InfraError;
 
 #[derive(Debug)]
 pub enum AppError {
     InternalServer,
     BodyParsing(String),
+    Unauthorized,
+    NotFound(String),
     Indexer(IndexerError),
     DbError(ConnectionError),
+    DatabaseBusy,
+}
+
+impl From<InfraError> for AppError {
+    fn from(value: InfraError) -> Self {
+        match value {
+            InfraError::DatabaseBusy => AppError::DatabaseBusy,
+            _ => AppError::InternalServer,
+        }
+    }
 }
 
 pub fn internal_error<E>(_err: E) -> AppError {
@@ -23,8 +36,11 @@ impl IntoResponse for AppError {
         let (status, err_msg) = match self {
             Self::InternalServer => (StatusCode::INTERNAL_SERVER_ERROR, String::from("Internal Server Error")),
             Self::BodyParsing(message) => (StatusCode::BAD_REQUEST, format!("Bad request error: {}", message)),
+            Self::Unauthorized => (StatusCode::UNAUTHORIZED, String::from("Unauthorized")),
+            Self::NotFound(resource) => (StatusCode::NOT_FOUND, format!("Not found: {}", resource)),
             Self::Indexer(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Indexer error: {}", err)),
             Self::DbError(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", err)),
+            Self::DatabaseBusy => (StatusCode::SERVICE_UNAVAILABLE, String::from("Database busy")),
         };
         (status, Json(json!({ "message": err_msg }))).into_response()
     }
@@ -0,0 +1,117 @@
+use axum::extract::State;
+use axum::Json;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use uuid::Uuid;
+
+use crate::domain::models::approval::normalize_target_url;
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::indexer::{IndexerConfig, IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::start_indexer::start_indexer_expecting;
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::indexers;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::approved_target_repository::{self, NewApprovedTargetDb};
+use crate::infra::repositories::audit_repository::{self, NewAuditLogDb};
+use crate::infra::repositories::indexer_repository::{IndexerDb, IndexerFilter, IndexerRepository, Repository};
+use crate::utils::event_bus::publish_event;
+use crate::utils::{AdminGuard, PathExtractor};
+use crate::AppState;
+
+pub async fn get_pending_approvals(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+) -> Result<Json<Vec<IndexerModel>>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let indexers = repository
+        .get_all(IndexerFilter { status: Some(IndexerStatus::PendingApproval.to_string()) })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(Json(indexers))
+}
+
+/// Approves the target of an indexer pending approval for its whole tenant and starts the
+/// indexer. Other indexers of the tenant pending on the same target still have to be approved
+/// one by one, they're started by their own approval.
+pub async fn approve_indexer(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<IndexerModel>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if indexer_model.status != IndexerStatus::PendingApproval {
+        return Err(IndexerError::InvalidIndexerStatus(indexer_model.status));
+    }
+    let (Some(tenant_id), Some(target_url)) = (indexer_model.tenant_id.clone(), indexer_model.target_url.as_deref())
+    else {
+        return Err(IndexerError::InternalServerError(format!("indexer {} has no tenant or target", id)));
+    };
+    let target_url = normalize_target_url(target_url).map_err(IndexerError::InvalidTargetUrl)?;
+
+    let context = &admin.context;
+    let connection = &mut get_connection(&state.pool).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+    let (approved_indexer, audit_log) = connection
+        .transaction::<_, IndexerError, _>(|conn| {
+            async move {
+                approved_target_repository::insert_with_connection(
+                    conn,
+                    NewApprovedTargetDb {
+                        tenant_id,
+                        target_url: target_url.clone(),
+                        approved_by: context.actor_id.clone(),
+                    },
+                )
+                .await
+                .map_err(IndexerError::InfraError)?;
+
+                // the indexer may have been deleted or approved concurrently
+                let approved_indexer: IndexerModel = diesel::update(indexers::table)
+                    .filter(indexers::id.eq(id))
+                    .filter(indexers::status.eq(IndexerStatus::PendingApproval.to_string()))
+                    .set(indexers::status.eq(IndexerStatus::Created.to_string()))
+                    .returning(IndexerDb::as_returning())
+                    .get_result::<IndexerDb>(conn)
+                    .await?
+                    .try_into()
+                    .map_err(|e| IndexerError::InfraError(InfraError::ParseError(e)))?;
+
+                let audit_log = audit_repository::insert_with_connection(
+                    conn,
+                    NewAuditLogDb {
+                        id: Uuid::new_v4(),
+                        indexer_id: id,
+                        action: AuditAction::TargetApproved.to_string(),
+                        from_status: Some(IndexerStatus::PendingApproval.to_string()),
+                        to_status: Some(IndexerStatus::Created.to_string()),
+                        reason: Some(format!("approved target {}", target_url)),
+                        actor: context.actor_id.clone(),
+                        severity: AuditSeverity::Info.to_string(),
+                        details: serde_json::to_value(IndexerConfig::from(&approved_indexer)).ok(),
+                        correlation_id: context.request_id.clone(),
+                    },
+                )
+                .await
+                .map_err(IndexerError::InfraError)?;
+
+                Ok((approved_indexer, audit_log))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    publish_event(audit_log);
+    tracing::info!("Approved the target of indexer {}", id);
+
+    // the approval stands if the start fails, the indexer is then handled as any indexer stuck
+    // in `Created`
+    if let Err(e) = start_indexer_expecting(context, id, IndexerStatus::Created).await {
+        tracing::warn!("Failed to start indexer {} after its approval: {}", id, e);
+        return Ok(Json(approved_indexer));
+    }
+    let started_indexer = repository.get(id).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(started_indexer))
+}
@@ -0,0 +1,120 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::audit::{AuditAction, AuditLogCursor, AuditLogPage, AuditSeverity};
+use crate::errors::AppError;
+use crate::infra::repositories::audit_repository::{AuditLogFilter, AuditRepository};
+use crate::utils::csv::to_csv_line;
+use crate::utils::{AdminGuard, QueryExtractor};
+use crate::AppState;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+/// CSV exports are meant for compliance extracts so they are not paginated as aggressively
+const MAX_EXPORT_SIZE: i64 = 10_000;
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditLogFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub indexer_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub actor: Option<String>,
+    pub severity: Option<AuditSeverity>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub format: AuditLogFormat,
+}
+
+pub async fn get_audit_logs(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<AuditLogQuery>,
+) -> Result<Response, AppError> {
+    let cursor = match query.cursor {
+        Some(cursor) => Some(
+            AuditLogCursor::decode(cursor.as_str())
+                .ok_or_else(|| AppError::BodyParsing(format!("invalid cursor {}", cursor)))?,
+        ),
+        None => None,
+    };
+    let max_limit = match query.format {
+        AuditLogFormat::Json => MAX_PAGE_SIZE,
+        AuditLogFormat::Csv => MAX_EXPORT_SIZE,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, max_limit);
+
+    let repository = AuditRepository::new(&state.pool);
+    // fetch one more log to know if there is a next page
+    let mut items = repository
+        .get_all(AuditLogFilter {
+            indexer_id: query.indexer_id,
+            action: query.action.map(|action| action.to_string()),
+            actor: query.actor,
+            severity: query.severity.map(|severity| severity.to_string()),
+            from: query.from,
+            to: query.to,
+            cursor,
+            limit: Some(limit + 1),
+        })
+        .await?;
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|item| AuditLogCursor::from(item).encode())
+    } else {
+        None
+    };
+
+    if query.format == AuditLogFormat::Json {
+        return Ok(Json(AuditLogPage { items, next_cursor }).into_response());
+    }
+
+    let mut csv = to_csv_line([
+        "id",
+        "indexer_id",
+        "action",
+        "severity",
+        "actor",
+        "from_status",
+        "to_status",
+        "reason",
+        "created_at",
+        "correlation_id",
+    ]);
+    for item in items {
+        csv.push_str(&to_csv_line([
+            item.id.to_string(),
+            item.indexer_id.to_string(),
+            item.action.to_string(),
+            item.severity.to_string(),
+            item.actor.unwrap_or_default(),
+            item.from_status.map(|status| status.to_string()).unwrap_or_default(),
+            item.to_status.map(|status| status.to_string()).unwrap_or_default(),
+            item.reason.unwrap_or_default(),
+            item.created_at.to_rfc3339(),
+            item.correlation_id.unwrap_or_default(),
+        ]));
+    }
+
+    let mut response = ([(header::CONTENT_TYPE, "text/csv")], csv).into_response();
+    if let Some(next_cursor) = next_cursor {
+        if let Ok(value) = next_cursor.parse() {
+            response.headers_mut().insert("x-next-cursor", value);
+        }
+    }
+    Ok(response)
+}
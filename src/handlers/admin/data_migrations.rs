@@ -0,0 +1,26 @@
+use axum::extract::State;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::domain::models::data_migration::DataMigrationReport;
+use crate::domain::models::indexer::IndexerError;
+use crate::infra::data_migrations::run_data_migrations;
+use crate::utils::{AdminGuard, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct DataMigrationQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Maps the legacy values of the indexers to the current ones, as done when the service starts.
+/// Useful after restoring old rows, the unmappable ones are listed.
+pub async fn migrate_data(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<DataMigrationQuery>,
+) -> Result<Json<DataMigrationReport>, IndexerError> {
+    let report = run_data_migrations(&state.pool, query.dry_run).await.map_err(IndexerError::InfraError)?;
+    Ok(Json(report))
+}
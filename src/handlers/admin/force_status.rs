@@ -0,0 +1,93 @@
+use axum::extract::State;
+use axum::Json;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::indexer::{IndexerConfig, IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::utils::lock_indexer;
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::indexers;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::audit_repository::{self, NewAuditLogDb};
+use crate::infra::repositories::indexer_repository::{IndexerDb, IndexerRepository, Repository};
+use crate::utils::event_bus::publish_event;
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ForceStatusRequest {
+    pub status: IndexerStatus,
+    pub reason: String,
+}
+
+/// Forces the status of an indexer without touching the underlying process. This is meant for
+/// manual interventions (e.g. the DB says Running but the machine was replaced), every call
+/// is recorded in the audit logs.
+pub async fn force_status(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<ForceStatusRequest>,
+) -> Result<Json<IndexerModel>, IndexerError> {
+    if request.reason.trim().is_empty() {
+        return Err(IndexerError::MissingReason);
+    }
+
+    let _lock = lock_indexer(id).await;
+    let repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let from_status = indexer_model.status;
+    if !from_status.can_force_to(request.status) {
+        return Err(IndexerError::InvalidForcedTransition(from_status, request.status));
+    }
+
+    let connection = &mut get_connection(&state.pool).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+    let (updated_indexer, audit_log) = connection
+        .transaction::<_, IndexerError, _>(|conn| {
+            async move {
+                // the lock only covers this instance, the status may have been changed by another one
+                let updated_indexer: IndexerModel = diesel::update(indexers::table)
+                    .filter(indexers::id.eq(id))
+                    .filter(indexers::status.eq(from_status.to_string()))
+                    .set(indexers::status.eq(request.status.to_string()))
+                    .returning(IndexerDb::as_returning())
+                    .get_result::<IndexerDb>(conn)
+                    .await
+                    .optional()?
+                    .ok_or(IndexerError::StatusChanged(id, from_status))?
+                    .try_into()
+                    .map_err(|e| IndexerError::InfraError(InfraError::ParseError(e)))?;
+
+                let audit_log = audit_repository::insert_with_connection(
+                    conn,
+                    NewAuditLogDb {
+                        id: Uuid::new_v4(),
+                        indexer_id: id,
+                        action: AuditAction::ForceStatus.to_string(),
+                        from_status: Some(from_status.to_string()),
+                        to_status: Some(request.status.to_string()),
+                        reason: Some(request.reason),
+                        actor: admin.context.actor_id,
+                        severity: AuditSeverity::Warning.to_string(),
+                        details: serde_json::to_value(IndexerConfig::from(&updated_indexer)).ok(),
+                        correlation_id: admin.context.request_id,
+                    },
+                )
+                .await
+                .map_err(IndexerError::InfraError)?;
+
+                Ok((updated_indexer, audit_log))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    publish_event(audit_log);
+    tracing::warn!("Indexer {} forced from {} to {}", id, from_status, updated_indexer.status);
+
+    Ok(Json(updated_indexer))
+}
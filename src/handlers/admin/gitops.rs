@@ -0,0 +1,46 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+
+use crate::config::config;
+use crate::domain::models::gitops::GitOpsSyncStatus;
+use crate::errors::AppError;
+use crate::handlers::indexers::gitops::{get_sync_status, request_sync};
+use crate::utils::signing::verify_hub_signature;
+use crate::utils::AdminGuard;
+use crate::AppState;
+
+const HUB_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+
+pub async fn get_gitops_status(State(_state): State<AppState>, _admin: AdminGuard) -> Json<GitOpsSyncStatus> {
+    Json(get_sync_status().await)
+}
+
+/// Syncs the indexers without waiting for the next poll of the repository
+pub async fn trigger_gitops_sync(State(_state): State<AppState>, _admin: AdminGuard) -> Result<StatusCode, AppError> {
+    if config().await.gitops().is_none() {
+        return Err(AppError::NotFound("gitops".into()));
+    }
+    request_sync();
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Push webhook of the repository, authenticated by its signature rather than the admin key so
+/// that it can be set up on the Git host
+pub async fn receive_gitops_webhook(
+    State(_state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, AppError> {
+    let config = config().await;
+    let Some(secret) = config.gitops().and_then(|gitops| gitops.webhook_secret.as_deref()) else {
+        return Err(AppError::NotFound("gitops webhook".into()));
+    };
+    let signature = headers.get(HUB_SIGNATURE_HEADER).and_then(|signature| signature.to_str().ok()).unwrap_or_default();
+    if !verify_hub_signature(secret, &body, signature) {
+        return Err(AppError::Unauthorized);
+    }
+    request_sync();
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,84 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::maintenance::{MaintenanceWindowKind, MaintenanceWindowModel};
+use crate::errors::AppError;
+use crate::infra::repositories::maintenance_repository::{
+    MaintenanceRepository, MaintenanceWindowFilter, NewMaintenanceWindowDb,
+};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMaintenanceWindowRequest {
+    pub tenant_id: Option<String>,
+    pub kind: MaintenanceWindowKind,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceWindowQuery {
+    pub tenant_id: Option<String>,
+    /// Ended windows are only returned if this is set
+    #[serde(default)]
+    pub include_past: bool,
+}
+
+pub async fn create_maintenance_window(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<CreateMaintenanceWindowRequest>,
+) -> Result<Json<MaintenanceWindowModel>, AppError> {
+    if request.ends_at <= request.starts_at {
+        return Err(AppError::BodyParsing("maintenance window must end after it starts".into()));
+    }
+
+    let mut repository = MaintenanceRepository::new(&state.pool);
+    let window = repository
+        .insert(NewMaintenanceWindowDb {
+            id: Uuid::new_v4(),
+            tenant_id: request.tenant_id,
+            kind: request.kind.to_string(),
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            reason: request.reason,
+        })
+        .await?;
+
+    Ok(Json(window))
+}
+
+pub async fn get_maintenance_windows(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<MaintenanceWindowQuery>,
+) -> Result<Json<Vec<MaintenanceWindowModel>>, AppError> {
+    let repository = MaintenanceRepository::new(&state.pool);
+    let windows = repository
+        .get_all(MaintenanceWindowFilter {
+            tenant_id: query.tenant_id,
+            ending_after: if query.include_past { None } else { Some(Utc::now()) },
+        })
+        .await?;
+
+    Ok(Json(windows))
+}
+
+pub async fn delete_maintenance_window(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut repository = MaintenanceRepository::new(&state.pool);
+    if !repository.delete(id).await? {
+        return Err(AppError::NotFound(format!("maintenance window {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,13 @@
+pub mod approvals;
+pub mod audit_logs;
+pub mod data_migrations;
+pub mod force_status;
+pub mod gitops;
+pub mod maintenance_windows;
+pub mod process_priority;
+pub mod quarantine;
+pub mod reconfigure;
+pub mod refresh_status;
+pub mod runtime;
+pub mod scheduled_actions;
+pub mod script_sync;
@@ -0,0 +1,55 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::domain::models::process_priority::ProcessPriority;
+use crate::handlers::indexers::indexer_types::apply_process_priority;
+use crate::handlers::indexers::multiplexer::multiplexer;
+use crate::handlers::indexers::utils::{lock_indexer, record_event_with_reason};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository, UpdateIndexerProcessPriorityDb};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Sets the CPU and IO priority of an indexer. It's applied to the sink right away if the
+/// indexer is running, without restarting it, and kept for its next starts. Running indexers
+/// sharing a multiplexed sink are refused, renicing it would change the priority of every member
+/// of the group. Once stopped they get their own sink on their next start.
+pub async fn update_process_priority(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(process_priority): JsonExtractor<ProcessPriority>,
+) -> Result<Json<IndexerModel>, IndexerError> {
+    process_priority.validate()?;
+
+    let _lock = lock_indexer(id).await;
+    let mut repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+
+    // a running sink is reniced first so that the DB doesn't report a priority it doesn't have
+    if indexer_model.status.is_live() {
+        if multiplexer().is_member(id).await {
+            return Err(IndexerError::SharedSinkPriority(id));
+        }
+        if let Some(process_id) = indexer_model.execution_ref.as_ref().and_then(ExecutionRef::pid) {
+            apply_process_priority(process_id, &process_priority).await?;
+        }
+    }
+
+    let updated_indexer = repository
+        .update_process_priority(UpdateIndexerProcessPriorityDb {
+            id,
+            process_priority: serde_json::to_value(process_priority).ok(),
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    let reason = format!("process priority set by {}", admin.context.actor_name());
+    record_event_with_reason(&admin.context, AuditAction::ConfigChange, None, None, &updated_indexer, Some(reason))
+        .await;
+
+    Ok(Json(updated_indexer))
+}
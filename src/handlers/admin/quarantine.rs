@@ -0,0 +1,21 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::quarantine::QuarantinedIndexer;
+use crate::infra::repositories::indexer_repository::{
+    get_quarantined_indexers, IndexerFilter, IndexerRepository, Repository,
+};
+use crate::utils::AdminGuard;
+use crate::AppState;
+
+/// Lists the indexers whose row can't be read. Every row is read again first so that fixed rows
+/// are released and new ones show up.
+pub async fn get_quarantined(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+) -> Result<Json<Vec<QuarantinedIndexer>>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?;
+    Ok(Json(get_quarantined_indexers()))
+}
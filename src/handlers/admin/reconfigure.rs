@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::config::config;
+use crate::constants::indexers::ROLLING_RESTART_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::reconfigure::{
+    replace_value, IndexerSelector, ReconfigureChange, ReconfigureField, ReconfigureModel,
+};
+use crate::handlers::indexers::update_indexer::restart_indexer;
+use crate::handlers::indexers::utils::record_event_with_reason;
+use crate::infra::repositories::indexer_repository::{
+    IndexerFilter, IndexerRepository, Repository, UpdateIndexerStreamUrlDb, UpdateIndexerTargetUrlDb,
+};
+use crate::utils::actor_context::spawn_with_context;
+use crate::utils::{AdminGuard, JsonExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ReconfigureRequest {
+    pub field: ReconfigureField,
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub replace: String,
+    #[serde(default)]
+    pub selector: IndexerSelector,
+    /// Changes are only applied when this is explicitly set to false
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+/// Replaces `match` with `replace` in a field of every indexer matching the selector, e.g. when
+/// the stream urls are rotated. The diff is returned without changing anything unless `dry_run`
+/// is false, the changes are then applied in the background and running indexers are restarted
+/// one at a time. The rollout stops at the first indexer failing to restart.
+pub async fn reconfigure(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<ReconfigureRequest>,
+) -> Result<Json<ReconfigureModel>, IndexerError> {
+    if request.pattern.is_empty() {
+        return Err(IndexerError::InvalidReconfiguration("match must not be empty".into()));
+    }
+
+    let repository = IndexerRepository::new(&state.pool);
+    let indexers = repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?;
+    let changes: Vec<ReconfigureChange> = indexers
+        .iter()
+        .filter(|indexer_model| request.selector.matches(indexer_model))
+        .filter_map(|indexer_model| {
+            let from = request.field.get(indexer_model)?;
+            let to = replace_value(from, &request.pattern, &request.replace)?;
+            Some(ReconfigureChange {
+                indexer_id: indexer_model.id,
+                status: indexer_model.status,
+                from: from.clone(),
+                to,
+            })
+        })
+        .collect();
+
+    if request.field == ReconfigureField::TargetUrl {
+        let config = config().await;
+        for change in &changes {
+            config.target_policy().validate(&change.to).await?;
+        }
+    }
+
+    if !request.dry_run {
+        let context = admin.context;
+        tracing::info!(
+            "Reconfiguring the {} of {} indexers, requested by {}",
+            request.field,
+            changes.len(),
+            context.actor_name()
+        );
+        spawn_with_context(
+            &context,
+            roll_out(context.clone(), request.field, request.pattern, request.replace, changes.clone()),
+        );
+    }
+
+    Ok(Json(ReconfigureModel { field: request.field, dry_run: request.dry_run, changes }))
+}
+
+async fn roll_out(
+    context: ActorContext,
+    field: ReconfigureField,
+    pattern: String,
+    replacement: String,
+    changes: Vec<ReconfigureChange>,
+) {
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    for change in changes {
+        let id = change.indexer_id;
+        if let Err(e) = apply_change(&context, &mut repository, field, &pattern, &replacement, id).await {
+            tracing::error!("Stopping the reconfiguration of the {}, indexer {} failed: {:?}", field, id, e);
+            return;
+        }
+    }
+    tracing::info!("Reconfiguration of the {} is done", field);
+}
+
+async fn apply_change(
+    context: &ActorContext,
+    repository: &mut IndexerRepository<'_>,
+    field: ReconfigureField,
+    pattern: &str,
+    replacement: &str,
+    id: uuid::Uuid,
+) -> Result<(), IndexerError> {
+    // the indexer may have changed since the diff was computed
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let Some(value) = field.get(&indexer_model).and_then(|value| replace_value(value, pattern, replacement)) else {
+        return Ok(());
+    };
+
+    let updated_indexer = match field {
+        ReconfigureField::StreamUrl => {
+            repository.update_stream_url(UpdateIndexerStreamUrlDb { id, stream_url: value }).await
+        }
+        ReconfigureField::TargetUrl => {
+            repository.update_target_url(UpdateIndexerTargetUrlDb { id, target_url: value }).await
+        }
+    }
+    .map_err(IndexerError::InfraError)?;
+    let reason = format!("{} reconfigured by {}", field, context.actor_name());
+    record_event_with_reason(context, AuditAction::ConfigChange, None, None, &updated_indexer, Some(reason)).await;
+
+    if updated_indexer.status.is_live() {
+        restart_indexer(context, id).await?;
+        tokio::time::sleep(Duration::from_secs(ROLLING_RESTART_INTERVAL_SECONDS)).await;
+    }
+    Ok(())
+}
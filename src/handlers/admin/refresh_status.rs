@@ -0,0 +1,98 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::domain::models::status_refresh::{
+    get_status_correction, is_refreshable, StatusCorrection, StatusRefreshFailure, StatusRefreshReport,
+    StatusRefreshRequest,
+};
+use crate::handlers::indexers::fail_indexer::fail_indexer;
+use crate::handlers::indexers::indexer_types::get_indexer_handler;
+use crate::handlers::indexers::utils::{lock_indexer, record_event_with_reason};
+use crate::infra::repositories::indexer_repository::{
+    IndexerFilter, IndexerRepository, Repository, UpdateIndexerStatusDb,
+};
+use crate::utils::{AdminGuard, JsonExtractor};
+use crate::AppState;
+
+/// Checks the process of the requested indexers, or of every indexer, and corrects the statuses
+/// which don't match. The reaper only notices the sinks this instance spawned, this also covers
+/// the ones left over by a restart or a failed stop.
+pub async fn refresh_status(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<StatusRefreshRequest>,
+) -> Result<Json<StatusRefreshReport>, IndexerError> {
+    let mut repository = IndexerRepository::new(&state.pool);
+    let indexers = match request.ids.is_empty() {
+        true => repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?,
+        false => {
+            let mut indexers = vec![];
+            for id in &request.ids {
+                indexers.push(repository.get(*id).await.map_err(IndexerError::InfraError)?);
+            }
+            indexers
+        }
+    };
+
+    let mut report = StatusRefreshReport::default();
+    for indexer_model in indexers.iter().filter(|indexer_model| is_refreshable(indexer_model.status)) {
+        report.checked += 1;
+        match refresh_indexer(&admin.context, &mut repository, indexer_model).await {
+            Ok(Some(correction)) => report.corrections.push(correction),
+            Ok(None) => (),
+            Err(e) => {
+                tracing::warn!("Failed to refresh the status of indexer {}: {:?}", indexer_model.id, e);
+                report.failures.push(StatusRefreshFailure { indexer_id: indexer_model.id, error: e.to_string() })
+            }
+        }
+    }
+
+    Ok(Json(report))
+}
+
+async fn refresh_indexer(
+    context: &ActorContext,
+    repository: &mut IndexerRepository<'_>,
+    indexer_model: &IndexerModel,
+) -> Result<Option<StatusCorrection>, IndexerError> {
+    let id = indexer_model.id;
+    let lock = lock_indexer(id).await;
+    // the status may have changed while waiting for the lock
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let from_status = indexer_model.status;
+    if !is_refreshable(from_status) {
+        return Ok(None);
+    }
+
+    let running = get_indexer_handler(&indexer_model.indexer_type).is_running(indexer_model.clone()).await?;
+    let Some(to_status) = get_status_correction(from_status, running) else {
+        return Ok(None);
+    };
+
+    if to_status == IndexerStatus::FailedRunning {
+        // same as when the exit of the sink is noticed, the standby takes over
+        drop(lock);
+        fail_indexer(context, id).await?;
+    } else {
+        let updated_indexer = repository
+            .update_status(UpdateIndexerStatusDb { id, status: to_status.to_string() })
+            .await
+            .map_err(IndexerError::InfraError)?;
+        let reason = format!("status refreshed by {}", context.actor_name());
+        record_event_with_reason(
+            context,
+            AuditAction::StatusChange,
+            Some(from_status),
+            Some(to_status),
+            &updated_indexer,
+            Some(reason),
+        )
+        .await;
+    }
+    tracing::warn!("Indexer {} corrected from {} to {}", id, from_status, to_status);
+
+    Ok(Some(StatusCorrection { indexer_id: id, from_status, to_status }))
+}
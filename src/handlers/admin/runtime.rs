@@ -0,0 +1,167 @@
+use std::fs;
+
+use axum::extract::State;
+use axum::Json;
+
+use crate::constants::runtime::{OPEN_FDS_WARNING_RATIO, RUNTIME_MONITOR_INTERVAL_SECONDS};
+use crate::domain::models::runtime::{DatabasePoolMetrics, PoolName, RuntimeMetrics, TokioMetrics};
+use crate::handlers::indexers::memory_pressure::{get_paused_indexers, host_memory_used_ratio};
+use crate::handlers::indexers::reaper::get_reaped_orphans;
+use crate::handlers::notifications::policy::get_suppressed_alerts;
+use crate::infra::db::pool::{get_all_pool_metrics, pool_metrics};
+use crate::utils::supervisor::get_supervised_task_metrics;
+use crate::utils::AdminGuard;
+use crate::AppState;
+
+pub async fn get_runtime_metrics(State(_state): State<AppState>, _admin: AdminGuard) -> Json<RuntimeMetrics> {
+    Json(collect_runtime_metrics())
+}
+
+pub async fn get_database_pool_metrics(State(state): State<AppState>, _admin: AdminGuard) -> Json<DatabasePoolMetrics> {
+    Json(pool_metrics(PoolName::Api, &state.pool))
+}
+
+pub async fn get_database_pools_metrics(
+    State(_state): State<AppState>,
+    _admin: AdminGuard,
+) -> Json<Vec<DatabasePoolMetrics>> {
+    Json(get_all_pool_metrics().await)
+}
+
+pub fn collect_runtime_metrics() -> RuntimeMetrics {
+    RuntimeMetrics {
+        memory_rss_bytes: memory_rss_bytes(),
+        open_fds: fs::read_dir("/proc/self/fd").ok().map(|entries| entries.count() as u64),
+        max_open_fds: max_open_fds(),
+        child_processes: child_processes(),
+        zombie_processes: get_zombie_children().map(|zombies| zombies.len() as u64),
+        reaped_orphans: get_reaped_orphans(),
+        host_memory_used_ratio: host_memory_used_ratio(),
+        pressure_paused_indexers: get_paused_indexers(),
+        suppressed_notifications: get_suppressed_alerts(),
+        tokio: tokio_metrics(),
+        tasks: get_supervised_task_metrics(),
+    }
+}
+
+/// Periodically checks the resources of the service and warns when we get close to the
+/// open files limit
+pub async fn monitor_runtime() {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(RUNTIME_MONITOR_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        let metrics = collect_runtime_metrics();
+        if let (Some(open_fds), Some(max_open_fds)) = (metrics.open_fds, metrics.max_open_fds) {
+            if open_fds as f64 >= max_open_fds as f64 * OPEN_FDS_WARNING_RATIO {
+                tracing::warn!("Service is nearing the open files limit: {}/{}", open_fds, max_open_fds);
+            }
+        }
+    }
+}
+
+fn memory_rss_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    // the value is reported in kB
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn max_open_fds() -> Option<u64> {
+    let limits = fs::read_to_string("/proc/self/limits").ok()?;
+    let line = limits.lines().find(|line| line.starts_with("Max open files"))?;
+    // columns are: name, soft limit, hard limit, units
+    line.trim_start_matches("Max open files").split_whitespace().next()?.parse::<u64>().ok()
+}
+
+/// `/proc/<pid>/stat` of the children of the service
+fn child_stats() -> Option<Vec<String>> {
+    let pid = std::process::id().to_string();
+    let stats = fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+        .filter(|stat| parent_pid(stat) == Some(pid.as_str()))
+        .collect();
+    Some(stats)
+}
+
+fn child_processes() -> Option<u64> {
+    child_stats().map(|stats| stats.len() as u64)
+}
+
+/// Children which exited but haven't been waited for yet
+pub fn get_zombie_children() -> Option<Vec<u32>> {
+    let zombies = child_stats()?
+        .iter()
+        .filter(|stat| process_state(stat) == Some("Z"))
+        .filter_map(|stat| stat.split_whitespace().next()?.parse().ok())
+        .collect();
+    Some(zombies)
+}
+
+/// The process name in `/proc/<pid>/stat` can contain spaces so we parse after the closing
+/// parenthesis: `<pid> (<name>) <state> <ppid> ...`
+fn parent_pid(stat: &str) -> Option<&str> {
+    stat.rsplit_once(')')?.1.split_whitespace().nth(1)
+}
+
+fn process_state(stat: &str) -> Option<&str> {
+    stat.rsplit_once(')')?.1.split_whitespace().next()
+}
+
+/// Start time of the process in clock ticks since boot, `None` if the process is gone
+pub fn get_process_start_time(pid: u32) -> Option<u64> {
+    start_time(&fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+fn start_time(stat: &str) -> Option<u64> {
+    stat.rsplit_once(')')?.1.split_whitespace().nth(19)?.parse().ok()
+}
+
+#[cfg(tokio_unstable)]
+fn tokio_metrics() -> TokioMetrics {
+    let metrics = tokio::runtime::Handle::current().metrics();
+    TokioMetrics {
+        workers: metrics.num_workers(),
+        active_tasks: Some(metrics.active_tasks_count()),
+        blocking_threads: Some(metrics.num_blocking_threads()),
+        idle_blocking_threads: Some(metrics.num_idle_blocking_threads()),
+        blocking_queue_depth: Some(metrics.blocking_queue_depth()),
+    }
+}
+
+#[cfg(not(tokio_unstable))]
+fn tokio_metrics() -> TokioMetrics {
+    TokioMetrics {
+        workers: std::thread::available_parallelism().map(|workers| workers.get()).unwrap_or(1),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_pid() {
+        assert_eq!(parent_pid("1234 (sink-webhook) S 42 1234 1234 0"), Some("42"));
+        assert_eq!(parent_pid("1234 (name with) spaces) R 7 1234"), Some("7"));
+        assert_eq!(parent_pid("garbage"), None);
+    }
+
+    #[test]
+    fn test_process_state() {
+        assert_eq!(process_state("1234 (sink-webhook) Z 42 1234 1234 0"), Some("Z"));
+        assert_eq!(process_state("1234 (name with) spaces) S 7 1234"), Some("S"));
+        assert_eq!(process_state("garbage"), None);
+    }
+
+    #[test]
+    fn test_start_time() {
+        let stat = "1234 (name with) spaces) S 7 1234 1234 0 -1 4194560 100 0 0 0 5 2 0 0 20 0 1 0 98765 1000 50";
+        assert_eq!(start_time(stat), Some(98765));
+        assert_eq!(start_time("1234 (sink-webhook) S 42"), None);
+        assert!(get_process_start_time(std::process::id()).is_some());
+    }
+}
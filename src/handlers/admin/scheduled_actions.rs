@@ -0,0 +1,105 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::scheduled_action::{
+    get_next_occurrence, parse_cron, parse_timezone, ScheduledActionKind, ScheduledActionModel,
+};
+use crate::errors::AppError;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::infra::repositories::scheduled_action_repository::{
+    NewScheduledActionDb, ScheduledActionFilter, ScheduledActionRepository,
+};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateScheduledActionRequest {
+    pub indexer_id: Uuid,
+    pub kind: ScheduledActionKind,
+    /// The first run of the cron expression by default
+    pub run_at: Option<DateTime<Utc>>,
+    /// Makes the action recurring, e.g. `0 9 * * MON-FRI`
+    pub cron: Option<String>,
+    /// IANA name of the timezone the cron expression is evaluated in, UTC by default
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledActionQuery {
+    pub indexer_id: Option<Uuid>,
+    /// Actions which failed too many times are only returned if this is set
+    #[serde(default)]
+    pub include_failed: bool,
+}
+
+pub async fn create_scheduled_action(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<CreateScheduledActionRequest>,
+) -> Result<Json<ScheduledActionModel>, AppError> {
+    if IndexerRepository::new(&state.pool).get(request.indexer_id).await.is_err() {
+        return Err(AppError::NotFound(format!("indexer {}", request.indexer_id)));
+    }
+
+    // checked here so that a recurring action can't fail on every run
+    let run_at = match &request.cron {
+        Some(cron) => {
+            let schedule = parse_cron(cron).map_err(AppError::BodyParsing)?;
+            let timezone =
+                parse_timezone(request.timezone.as_deref().unwrap_or("UTC")).map_err(AppError::BodyParsing)?;
+            match request.run_at {
+                Some(run_at) => run_at,
+                None => get_next_occurrence(&schedule, timezone, Utc::now())
+                    .ok_or_else(|| AppError::BodyParsing(format!("cron expression \"{}\" never runs", cron)))?,
+            }
+        }
+        None if request.timezone.is_some() => {
+            return Err(AppError::BodyParsing("timezone is only used with a cron expression".into()));
+        }
+        None => request.run_at.ok_or_else(|| AppError::BodyParsing("run_at or cron must be set".into()))?,
+    };
+
+    let mut repository = ScheduledActionRepository::new(&state.pool);
+    let action = repository
+        .insert(NewScheduledActionDb {
+            id: Uuid::new_v4(),
+            indexer_id: request.indexer_id,
+            kind: request.kind.to_string(),
+            run_at,
+            cron: request.cron,
+            timezone: request.timezone,
+        })
+        .await?;
+
+    Ok(Json(action))
+}
+
+pub async fn get_scheduled_actions(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<ScheduledActionQuery>,
+) -> Result<Json<Vec<ScheduledActionModel>>, AppError> {
+    let repository = ScheduledActionRepository::new(&state.pool);
+    let actions = repository
+        .get_all(ScheduledActionFilter { indexer_id: query.indexer_id, include_failed: query.include_failed })
+        .await?;
+
+    Ok(Json(actions))
+}
+
+pub async fn delete_scheduled_action(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut repository = ScheduledActionRepository::new(&state.pool);
+    if !repository.delete(id).await? {
+        return Err(AppError::NotFound(format!("scheduled action {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
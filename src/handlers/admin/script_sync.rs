@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::Json;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use object_store::path::Path;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::s3::{INDEXER_SERVICE_SCRIPTS_FOLDER, SCRIPT_SYNC_CONCURRENCY};
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::domain::models::script_sync::{
+    get_script_sync_issue, ScriptSyncEntry, ScriptSyncFix, ScriptSyncIssue, ScriptSyncReport,
+};
+use crate::handlers::indexers::utils::get_s3_script_key;
+use crate::infra::repositories::indexer_repository::{
+    get_quarantined_indexers, IndexerFilter, IndexerRepository, Repository, UpdateIndexerScriptChecksumDb,
+};
+use crate::utils::script_cache::{get_cached_script, get_script_checksum};
+use crate::utils::script_fetch::fetch_script;
+use crate::utils::{AdminGuard, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ScriptSyncQuery {
+    #[serde(default)]
+    pub fix: bool,
+}
+
+/// Checks that the script in the store of every indexer is the one recorded for it and lists the
+/// scripts no indexer owns. With `?fix=true`, missing checksums are recorded and scripts are only
+/// restored from a copy matching the recorded checksum: the url the script was fetched from, or
+/// the local cache for missing scripts. A script in the store which differs from the recorded one
+/// may be the newer one, it's only replaced from its url. Orphaned scripts are never deleted.
+pub async fn check_script_sync(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<ScriptSyncQuery>,
+) -> Result<Json<ScriptSyncReport>, IndexerError> {
+    let mut repository = IndexerRepository::new(&state.pool);
+    let indexers = repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?;
+
+    // the scripts are downloaded concurrently, the fixes then apply one by one
+    let stored_checksums: Vec<Result<Option<String>, IndexerError>> = stream::iter(&indexers)
+        .map(|indexer_model| get_stored_checksum(indexer_model.id))
+        .buffered(SCRIPT_SYNC_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut report = ScriptSyncReport { fix: query.fix, ..Default::default() };
+    for (indexer_model, stored_checksum) in indexers.iter().zip(stored_checksums) {
+        let stored_checksum = stored_checksum?;
+        report.checked += 1;
+        let issue = match get_script_sync_issue(indexer_model.script_checksum.as_deref(), stored_checksum.as_deref()) {
+            Some(issue) => issue,
+            None => continue,
+        };
+        let fix =
+            if query.fix { fix_script(&mut repository, indexer_model, &issue, &stored_checksum).await } else { None };
+        report.issues.push(ScriptSyncEntry {
+            indexer_id: Some(indexer_model.id),
+            key: get_s3_script_key(indexer_model.id),
+            issue,
+            recorded_checksum: indexer_model.script_checksum.clone(),
+            stored_checksum,
+            fix,
+        });
+    }
+
+    // scripts of the rows which can't be read aren't orphaned
+    let owned: HashSet<Uuid> = indexers
+        .iter()
+        .map(|indexer| indexer.id)
+        .chain(get_quarantined_indexers().iter().map(|indexer| indexer.indexer_id))
+        .collect();
+    let config = config().await;
+    let objects: Vec<_> = config
+        .object_store()
+        .list(Some(&Path::from(INDEXER_SERVICE_SCRIPTS_FOLDER)))
+        .try_collect()
+        .await
+        .map_err(IndexerError::FailedToGetFromStore)?;
+    for object in objects {
+        let indexer_id = object
+            .location
+            .filename()
+            .and_then(|filename| filename.strip_suffix(".js"))
+            .and_then(|id| Uuid::parse_str(id).ok());
+        if indexer_id.is_some_and(|id| owned.contains(&id)) {
+            continue;
+        }
+        report.issues.push(ScriptSyncEntry {
+            indexer_id,
+            key: object.location.to_string(),
+            issue: ScriptSyncIssue::Orphaned,
+            recorded_checksum: None,
+            stored_checksum: None,
+            fix: None,
+        });
+    }
+
+    Ok(Json(report))
+}
+
+async fn get_stored_checksum(id: Uuid) -> Result<Option<String>, IndexerError> {
+    let config = config().await;
+    match config.object_store().get(&Path::from(get_s3_script_key(id))).await {
+        Ok(data) => {
+            let script = data.bytes().await.map_err(IndexerError::FailedToCollectBytesFromStore)?;
+            Ok(Some(get_script_checksum(&script)))
+        }
+        Err(object_store::Error::NotFound { .. }) => Ok(None),
+        Err(e) => Err(IndexerError::FailedToGetFromStore(e)),
+    }
+}
+
+/// Failed fixes are logged and the issue is reported as not fixed
+async fn fix_script(
+    repository: &mut IndexerRepository<'_>,
+    indexer_model: &IndexerModel,
+    issue: &ScriptSyncIssue,
+    stored_checksum: &Option<String>,
+) -> Option<ScriptSyncFix> {
+    match issue {
+        ScriptSyncIssue::Missing | ScriptSyncIssue::ChecksumMismatch => {
+            let (script, fix) = get_recorded_script(indexer_model, issue).await?;
+            let config = config().await;
+            match config.object_store().put(&Path::from(get_s3_script_key(indexer_model.id)), script.into()).await {
+                Ok(_) => Some(fix),
+                Err(e) => {
+                    tracing::error!("Failed to restore the script of indexer {}: {:?}", indexer_model.id, e);
+                    None
+                }
+            }
+        }
+        ScriptSyncIssue::ChecksumNotRecorded => {
+            let update = repository
+                .update_script_checksum(UpdateIndexerScriptChecksumDb {
+                    id: indexer_model.id,
+                    script_checksum: stored_checksum.clone(),
+                })
+                .await;
+            match update {
+                Ok(_) => Some(ScriptSyncFix::RecordedChecksum),
+                Err(e) => {
+                    tracing::error!("Failed to record the script checksum of indexer {}: {:?}", indexer_model.id, e);
+                    None
+                }
+            }
+        }
+        ScriptSyncIssue::Orphaned => None,
+    }
+}
+
+/// Returns a copy of the script recorded for the indexer, `None` if no copy matches its checksum
+async fn get_recorded_script(indexer_model: &IndexerModel, issue: &ScriptSyncIssue) -> Option<(Bytes, ScriptSyncFix)> {
+    let checksum = indexer_model.script_checksum.as_deref()?;
+    if let Some(script_url) = &indexer_model.script_source_url {
+        match fetch_script(script_url).await {
+            Ok(script) if get_script_checksum(&script) == checksum => {
+                return Some((script, ScriptSyncFix::RestoredFromSource));
+            }
+            Ok(_) => tracing::warn!("Script of indexer {} changed at {}", indexer_model.id, script_url),
+            Err(e) => tracing::warn!("Failed to fetch the script of indexer {}: {:?}", indexer_model.id, e),
+        }
+    }
+    if *issue != ScriptSyncIssue::Missing {
+        return None;
+    }
+    // verified against the checksum when read
+    let script = get_cached_script(checksum).await?;
+    Some((script, ScriptSyncFix::RestoredFromCache))
+}
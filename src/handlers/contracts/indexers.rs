@@ -0,0 +1,21 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::contract::{normalize_felt, ContractError};
+use crate::domain::models::indexer::IndexerModel;
+use crate::infra::repositories::contract_repository::ContractRepository;
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Returns the indexers whose filter covers the contract, e.g. to know which indexers are
+/// affected by an upgrade of the contract
+pub async fn get_contract_indexers(
+    State(state): State<AppState>,
+    PathExtractor(address): PathExtractor<String>,
+) -> Result<Json<Vec<IndexerModel>>, ContractError> {
+    let address = normalize_felt(address.as_str()).ok_or(ContractError::InvalidAddress(address))?;
+    let repository = ContractRepository::new(&state.pool);
+    let indexers = repository.get_indexers_by_address(address.as_str()).await.map_err(ContractError::InfraError)?;
+
+    Ok(Json(indexers))
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::domain::models::audit::AuditEventFilter;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::event_bus::subscribe_events;
+use crate::utils::{AdminGuard, QueryExtractor};
+use crate::AppState;
+
+/// Streams the audit events of the fleet as they are recorded as Server-Sent Events, filtered
+/// by the query (`tenant_id`, `indexer_id`, `action`, `severity`). Events are sent as `audit`
+/// with the log as data, a `lagged` event gives the number of events missed by a slow client.
+/// Indexers have no namespace, the tenant is the only grouping.
+pub async fn stream_events(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(filter): QueryExtractor<AuditEventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // tenants of the indexers seen on this stream, only looked up when filtering on the tenant
+    let tenants: HashMap<Uuid, Option<String>> = HashMap::new();
+    let events = stream::unfold(
+        (subscribe_events(), filter, tenants, state),
+        |(mut receiver, filter, mut tenants, state)| async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(log) => {
+                        let tenant_id = match &filter.tenant_id {
+                            Some(_) => match tenants.get(&log.indexer_id) {
+                                Some(tenant_id) => tenant_id.clone(),
+                                None => {
+                                    let repository = IndexerRepository::new(&state.pool);
+                                    let tenant_id = repository
+                                        .get(log.indexer_id)
+                                        .await
+                                        .ok()
+                                        .and_then(|indexer_model| indexer_model.tenant_id);
+                                    tenants.insert(log.indexer_id, tenant_id.clone());
+                                    tenant_id
+                                }
+                            },
+                            None => None,
+                        };
+                        if !filter.matches(&log, tenant_id.as_deref()) {
+                            continue;
+                        }
+                        match Event::default().event("audit").id(log.id.to_string()).json_data(&log) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                tracing::error!("Failed to serialize audit event {}: {}", log.id, e);
+                                continue;
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => Event::default().event("lagged").data(missed.to_string()),
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), (receiver, filter, tenants, state)));
+            }
+        },
+    );
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
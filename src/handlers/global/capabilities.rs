@@ -0,0 +1,33 @@
+use axum::extract::State;
+use axum::Json;
+use strum::IntoEnumIterator;
+
+use crate::config::config;
+use crate::domain::models::capabilities::{CapabilitiesModel, SubsystemsModel};
+use crate::domain::models::indexer::IndexerType;
+use crate::handlers::global::version::execution_backend;
+use crate::utils::secrets::SecretResolver;
+use crate::AppState;
+
+pub async fn get_capabilities(State(_state): State<AppState>) -> Json<CapabilitiesModel> {
+    let config = config().await;
+    let admin = config.admin_api_key().is_some();
+    Json(CapabilitiesModel {
+        indexer_types: IndexerType::iter().collect(),
+        networks: None,
+        execution_backends: vec![execution_backend(&config).to_string()],
+        subsystems: SubsystemsModel {
+            secrets: !SecretResolver::new(config.secrets()).enabled_backends().is_empty(),
+            scheduling: admin,
+            templates: false,
+            admin,
+            multiplexer: config.multiplexer_enabled(),
+            script_search: config.script_search_enabled(),
+            notifications: config.notification_webhook_url().is_some(),
+            command_hooks: !config.hook_allowed_commands().is_empty(),
+            gitops: config.gitops().is_some(),
+        },
+        startup_mode: config.startup_mode(),
+        startup_compromises: config.startup_compromises().to_vec(),
+    })
+}
@@ -1,9 +1,115 @@
+use std::ffi::CString;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use axum::Json;
+use object_store::path::Path;
 
+use crate::config::config;
+use crate::constants::runtime::{
+    MIN_FREE_DISK_BYTES, OBJECT_STORE_CHECK_TIMEOUT_SECONDS, SELF_HEALTH_CHECK_INTERVAL_SECONDS,
+};
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::runtime::ReadinessModel;
+use crate::utils::supervisor::get_crash_looping_critical_tasks;
 use crate::AppState;
 
+/// Checks of the service which failed last time they ran, starts are refused while any fails
+static FAILING_CHECKS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+
+fn failing_checks() -> std::sync::MutexGuard<'static, Vec<String>> {
+    FAILING_CHECKS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
 pub async fn health_check(State(_state): State<AppState>) -> impl IntoResponse {
     StatusCode::OK
 }
+
+/// The service is alive but not ready while one of its critical background tasks keeps panicking
+pub async fn readiness_check(State(_state): State<AppState>) -> impl IntoResponse {
+    let crash_looping_tasks = get_crash_looping_critical_tasks();
+    let ready = crash_looping_tasks.is_empty();
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let unschedulable_reasons = failing_checks().clone();
+    (
+        status,
+        Json(ReadinessModel {
+            ready,
+            crash_looping_tasks,
+            schedulable: unschedulable_reasons.is_empty(),
+            unschedulable_reasons,
+        }),
+    )
+}
+
+/// Fails while the service can't start indexers. Indexers already running are left alone, only
+/// new starts are refused so that they are sent to a healthy instance.
+pub fn ensure_schedulable() -> Result<(), IndexerError> {
+    let reasons = failing_checks().clone();
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    Err(IndexerError::Unschedulable(reasons))
+}
+
+/// Periodically checks what starting an indexer needs: room on the disk for its script and
+/// access to the store the script is read from
+pub async fn monitor_self_health() {
+    let mut interval = tokio::time::interval(Duration::from_secs(SELF_HEALTH_CHECK_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        let mut reasons = Vec::new();
+        match free_disk_bytes(&std::env::temp_dir().to_string_lossy()) {
+            Some(free) if free < MIN_FREE_DISK_BYTES => {
+                reasons.push(format!("only {} bytes are free in the temporary directory", free))
+            }
+            _ => (),
+        }
+        if let Err(reason) = check_object_store().await {
+            reasons.push(reason);
+        }
+
+        let mut failing = failing_checks();
+        if reasons.is_empty() && !failing.is_empty() {
+            tracing::info!("Service is schedulable again");
+        } else if !reasons.is_empty() && *failing != reasons {
+            tracing::warn!("Service is unschedulable, starts are refused: {}", reasons.join(", "));
+        }
+        *failing = reasons;
+    }
+}
+
+fn free_disk_bytes(path: &str) -> Option<u64> {
+    let path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a valid C string and `stat` is only read if the call succeeds
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// The store is reachable if it answers, a missing object is fine
+async fn check_object_store() -> Result<(), String> {
+    let config = config().await;
+    let head = config.object_store().head(&Path::from("health-check"));
+    match tokio::time::timeout(Duration::from_secs(OBJECT_STORE_CHECK_TIMEOUT_SECONDS), head).await {
+        Ok(Ok(_)) | Ok(Err(object_store::Error::NotFound { .. })) => Ok(()),
+        Ok(Err(e)) => Err(format!("object store is unreachable: {}", e)),
+        Err(_) => Err("object store timed out".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_disk_bytes() {
+        assert!(free_disk_bytes(&std::env::temp_dir().to_string_lossy()).is_some());
+        assert_eq!(free_disk_bytes("/does/not/exist"), None);
+    }
+}
@@ -1 +1,5 @@
+pub mod capabilities;
 pub mod health;
+pub mod schemas;
+pub mod search;
+pub mod version;
@@ -0,0 +1,17 @@
+use axum::Json;
+use serde_json::Value;
+
+use crate::domain::models::event_schema::{event_schema, EVENT_SCHEMA_VERSIONS};
+use crate::domain::models::indexer::IndexerError;
+use crate::utils::PathExtractor;
+
+/// Versions of the schema of the events, the last one is the version of the events sent now
+pub async fn get_event_schema_versions() -> Json<Vec<&'static str>> {
+    Json(EVENT_SCHEMA_VERSIONS.to_vec())
+}
+
+/// JSON schema of the lifecycle webhooks, alerts and hooks of a version, so that consumers can
+/// validate the events against the `schema_version` they carry
+pub async fn get_event_schema(PathExtractor(version): PathExtractor<String>) -> Result<Json<Value>, IndexerError> {
+    event_schema(&version).map(Json).ok_or(IndexerError::EventSchemaNotFound(version))
+}
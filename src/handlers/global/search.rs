@@ -0,0 +1,28 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::search::{BlockSearchModel, BlockSearchQuery};
+use crate::handlers::indexers::logs::find_sink_logs;
+use crate::infra::repositories::delivery_repository::DeliveryRepository;
+use crate::utils::QueryExtractor;
+use crate::AppState;
+
+/// Correlates the deliveries recorded for the block with the records of the sinks referencing it.
+/// The service doesn't keep track of reorgs, the sinks roll them back on their own and the ones
+/// they log are found with the other records.
+pub async fn search_block(
+    State(state): State<AppState>,
+    QueryExtractor(query): QueryExtractor<BlockSearchQuery>,
+) -> Result<Json<BlockSearchModel>, IndexerError> {
+    if query.block < 0 {
+        return Err(IndexerError::InvalidBlockRange(format!("block {} is negative", query.block)));
+    }
+    let indexers = DeliveryRepository::new(&state.pool)
+        .get_indexers_delivering(query.block)
+        .await
+        .map_err(IndexerError::InfraError)?;
+    let logs = find_sink_logs(query.block as u64);
+
+    Ok(Json(BlockSearchModel { block: query.block, indexers, logs }))
+}
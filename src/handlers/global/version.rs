@@ -0,0 +1,46 @@
+use axum::extract::State;
+use axum::Json;
+use chrono::{TimeZone, Utc};
+use strum::IntoEnumIterator;
+
+use crate::config::{config, Config};
+use crate::domain::models::indexer::IndexerType;
+use crate::domain::models::version::{BuildFeatures, VersionModel};
+use crate::AppState;
+
+pub async fn get_version(State(_state): State<AppState>) -> Json<VersionModel> {
+    Json(get_version_model().await)
+}
+
+pub async fn get_version_model() -> VersionModel {
+    let config = config().await;
+    VersionModel {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: Some(env!("GIT_SHA")).filter(|sha| !sha.is_empty()).map(String::from),
+        build_time: env!("BUILD_TIMESTAMP")
+            .parse()
+            .ok()
+            .filter(|timestamp| *timestamp > 0)
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single()),
+        features: BuildFeatures {
+            execution_backend: execution_backend(&config).to_string(),
+            queue_backend: None,
+            storage_backend: storage_backend().to_string(),
+        },
+        indexer_types: IndexerType::iter().collect(),
+    }
+}
+
+pub fn execution_backend(config: &Config) -> &'static str {
+    if config.multiplexer_enabled() { "multiplexer" } else { "process" }
+}
+
+#[cfg(feature = "gcp")]
+fn storage_backend() -> &'static str {
+    "gcs"
+}
+
+#[cfg(feature = "aws")]
+fn storage_backend() -> &'static str {
+    "s3"
+}
@@ -0,0 +1,126 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::annotation::{interleave_history, AnnotationModel, HistoryEntry};
+use crate::domain::models::indexer::IndexerError;
+use crate::infra::repositories::annotation_repository::{AnnotationRepository, NewAnnotationDb};
+use crate::infra::repositories::audit_repository::{AuditLogFilter, AuditRepository};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::{JsonExtractor, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+const DEFAULT_HISTORY_SIZE: i64 = 100;
+const MAX_HISTORY_SIZE: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnotationRequest {
+    /// Markdown
+    pub body: String,
+    pub author: String,
+    /// Ticket of the incident the note is about
+    pub incident_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub limit: Option<i64>,
+}
+
+impl CreateAnnotationRequest {
+    fn validate(&self) -> Result<(), IndexerError> {
+        if self.body.trim().is_empty() {
+            return Err(IndexerError::InvalidAnnotation("the body is empty".into()));
+        }
+        if self.author.trim().is_empty() {
+            return Err(IndexerError::InvalidAnnotation("the author is empty".into()));
+        }
+        if let Some(incident_url) = &self.incident_url {
+            let is_http = url::Url::parse(incident_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !is_http {
+                return Err(IndexerError::InvalidAnnotation(format!("invalid incident url {}", incident_url)));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn create_annotation(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<CreateAnnotationRequest>,
+) -> Result<Json<AnnotationModel>, IndexerError> {
+    request.validate()?;
+    // make sure the indexer exists
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let annotation = AnnotationRepository::new(&state.pool)
+        .insert(NewAnnotationDb {
+            id: Uuid::new_v4(),
+            indexer_id: id,
+            body: request.body,
+            author: request.author.trim().to_string(),
+            incident_url: request.incident_url,
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(Json(annotation))
+}
+
+/// Annotations of the indexer from the most recent to the oldest
+pub async fn get_annotations(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<HistoryQuery>,
+) -> Result<Json<Vec<AnnotationModel>>, IndexerError> {
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_SIZE).clamp(1, MAX_HISTORY_SIZE);
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let annotations =
+        AnnotationRepository::new(&state.pool).get_all_by_indexer(id, limit).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(annotations))
+}
+
+pub async fn delete_annotation(
+    State(state): State<AppState>,
+    PathExtractor((id, annotation_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, IndexerError> {
+    let mut repository = AnnotationRepository::new(&state.pool);
+    if !repository.delete(id, annotation_id).await.map_err(IndexerError::InfraError)? {
+        return Err(IndexerError::AnnotationNotFound(annotation_id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Audit events and annotations of the indexer interleaved, from the most recent to the oldest
+pub async fn get_indexer_history(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<HistoryQuery>,
+) -> Result<Json<Vec<HistoryEntry>>, IndexerError> {
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_SIZE).clamp(1, MAX_HISTORY_SIZE);
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    // the most recent `limit` entries are among the most recent `limit` of each kind
+    let events = AuditRepository::new(&state.pool)
+        .get_all(AuditLogFilter {
+            indexer_id: Some(id),
+            action: None,
+            actor: None,
+            severity: None,
+            from: None,
+            to: None,
+            cursor: None,
+            limit: Some(limit),
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    let annotations =
+        AnnotationRepository::new(&state.pool).get_all_by_indexer(id, limit).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(interleave_history(events, annotations, limit as usize)))
+}
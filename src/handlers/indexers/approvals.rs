@@ -0,0 +1,43 @@
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::AsyncPgConnection;
+
+use crate::domain::models::approval::normalize_target_url;
+use crate::domain::models::indexer::IndexerError;
+use crate::infra::repositories::approved_target_repository::ApprovedTargetRepository;
+use crate::infra::repositories::tenant_repository::TenantRepository;
+
+/// Whether an indexer sending to the target has to wait for an admin approval: its tenant
+/// requires approvals and didn't approve the target yet
+pub async fn is_pending_approval(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: Option<&str>,
+    target_url: Option<&str>,
+) -> Result<bool, IndexerError> {
+    let (Some(tenant_id), Some(target_url)) = (tenant_id, target_url) else {
+        return Ok(false);
+    };
+    let settings =
+        TenantRepository::new(pool).get_settings(tenant_id).await.map_err(IndexerError::FailedToGetTenantSettings)?;
+    if !settings.is_some_and(|settings| settings.require_target_approval) {
+        return Ok(false);
+    }
+    let target_url = normalize_target_url(target_url).map_err(IndexerError::InvalidTargetUrl)?;
+    let approved = ApprovedTargetRepository::new(pool)
+        .is_approved(tenant_id, &target_url)
+        .await
+        .map_err(IndexerError::InfraError)?;
+    Ok(!approved)
+}
+
+/// Existing indexers can't be moved to an unapproved target, they would have to leave their
+/// status to wait for the approval
+pub async fn ensure_target_approved(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: Option<&str>,
+    target_url: &str,
+) -> Result<(), IndexerError> {
+    if is_pending_approval(pool, tenant_id, Some(target_url)).await? {
+        return Err(IndexerError::TargetNotApproved(target_url.to_string(), tenant_id.unwrap_or_default().to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use axum::extract::State;
+use axum::Json;
+use object_store::path::Path;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::{IndexerError, IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::output_location::OutputLocation;
+use crate::handlers::indexers::approvals::ensure_target_approved;
+use crate::handlers::indexers::hooks::validate_hooks;
+use crate::handlers::indexers::utils::{get_resolved_script, get_s3_script_key, record_event};
+use crate::infra::repositories::contract_repository::{ContractRepository, NewIndexerContractDb};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, NewIndexerDb, Repository};
+use crate::infra::repositories::tenant_repository::TenantRepository;
+use crate::utils::script_filter::extract_contract_filters;
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Fields of the copy which differ from the original indexer, anything not set is copied
+#[derive(Debug, Default, Deserialize)]
+pub struct CloneIndexerRequest {
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub custom_connection_string: Option<String>,
+    pub starting_block: Option<i64>,
+    pub ending_block: Option<i64>,
+    pub indexer_id: Option<String>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub tenant_id: Option<String>,
+    pub stream_url: Option<String>,
+    /// Prefix the copy writes its files under in the bucket of the original, its id if not set
+    pub output_prefix: Option<String>,
+    /// Replaces all the params of the script
+    pub script_params: Option<BTreeMap<String, String>>,
+}
+
+/// Duplicates an indexer with its script, e.g. to run the same indexer against another
+/// environment. The copy is left in the `Created` state and must be started explicitly.
+pub async fn clone_indexer(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<CloneIndexerRequest>,
+) -> Result<Json<IndexerModel>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let original = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let clone = insert_clone(&context, original, request, None).await?;
+
+    Ok(Json(clone))
+}
+
+/// Inserts a copy of the indexer in the `Created` state along with a copy of its script
+pub async fn insert_clone(
+    context: &ActorContext,
+    original: IndexerModel,
+    request: CloneIndexerRequest,
+    backfill_for: Option<Uuid>,
+) -> Result<IndexerModel, IndexerError> {
+    let config = config().await;
+    let id = original.id;
+
+    // moving the copy to another tenant mustn't bypass the indexer types allowed to it
+    if let Some(tenant_id) = &request.tenant_id {
+        let settings = TenantRepository::new(config.pool())
+            .get_settings(tenant_id.as_str())
+            .await
+            .map_err(IndexerError::FailedToGetTenantSettings)?;
+        if settings.is_some_and(|settings| !settings.is_indexer_type_allowed(&original.indexer_type)) {
+            return Err(IndexerError::IndexerTypeNotAllowed(original.indexer_type, tenant_id.clone()));
+        }
+    }
+
+    // the sink id of postgres indexers is their table, the copy only takes it if it has its own
+    let indexer_id = match (request.indexer_id, &original.indexer_type) {
+        (Some(indexer_id), _) => Some(indexer_id),
+        (None, IndexerType::Postgres) => request.table_name.clone(),
+        (None, IndexerType::Webhook | IndexerType::Parquet | IndexerType::Console) => None,
+    };
+    let table_name = request.table_name.or(original.table_name.clone());
+    let custom_connection_string = request.custom_connection_string.or(original.custom_connection_string.clone());
+    validate_clone_outputs(
+        &original,
+        indexer_id.as_deref(),
+        table_name.as_deref(),
+        custom_connection_string.as_deref(),
+    )
+    .map_err(IndexerError::InvalidClone)?;
+
+    // the copy may have other params, its contracts are the ones of its own resolved script
+    let mut resolved_model = original.clone();
+    if let Some(script_params) = request.script_params {
+        resolved_model.script_params = script_params;
+    }
+    let resolved_script = get_resolved_script(&resolved_model).await?;
+    let contract_filters = extract_contract_filters(&String::from_utf8_lossy(&resolved_script));
+
+    if request.target_url.is_some() && original.indexer_type != IndexerType::Webhook {
+        return Err(IndexerError::InvalidTargetUrl("only webhook indexers have a target url".into()));
+    }
+    let target_url = request.target_url.or(original.target_url);
+    let tenant_id = request.tenant_id.or(original.tenant_id);
+    if let Some(target_url) = &target_url {
+        config.target_policy().validate(target_url).await?;
+        ensure_target_approved(config.pool(), tenant_id.as_deref(), target_url).await?;
+    }
+    // the policy might have changed since the original was created
+    config.sandbox_policy().validate(&original.script_permissions)?;
+    if let Some(stream_url) = request.stream_url.as_ref().or(original.stream_url.as_ref()) {
+        config.stream_policy().validate(stream_url)?;
+    }
+    validate_hooks(&original.hooks).await?;
+
+    let clone_id = Uuid::new_v4();
+    if request.output_prefix.is_some() && original.output_location.is_none() {
+        return Err(IndexerError::InvalidOutputLocation("only parquet indexers have an output prefix".into()));
+    }
+    // the copy mustn't write over the files of the original
+    let output_location = original
+        .output_location
+        .as_deref()
+        .map(|location| {
+            let location = location.parse::<OutputLocation>()?;
+            OutputLocation::new(&location.bucket, request.output_prefix.as_deref(), clone_id)
+        })
+        .transpose()
+        .map_err(IndexerError::InvalidOutputLocation)?;
+    config
+        .object_store()
+        .copy(&Path::from(get_s3_script_key(id)), &Path::from(get_s3_script_key(clone_id)))
+        .await
+        .map_err(IndexerError::FailedToUploadToStore)?;
+
+    let mut repository = IndexerRepository::new(config.pool());
+    let clone = repository
+        .insert(NewIndexerDb {
+            id: clone_id,
+            status: IndexerStatus::Created.to_string(),
+            type_: original.indexer_type.to_string(),
+            target_url,
+            table_name,
+            status_server_port: None,
+            custom_connection_string,
+            starting_block: request.starting_block.or(original.starting_block),
+            indexer_id,
+            log_level: request.log_level.or(original.log_level).map(|log_level| log_level.to_string()),
+            standby_for: None,
+            script_permissions: serde_json::to_value(&original.script_permissions).ok(),
+            tenant_id,
+            stream_url: request.stream_url.or(original.stream_url),
+            ending_block: request.ending_block.or(original.ending_block),
+            backfill_for,
+            script_params: serde_json::to_value(&resolved_model.script_params).ok(),
+            script_checksum: original.script_checksum,
+            hooks: serde_json::to_value(&original.hooks).ok(),
+            sink_options: original.sink_options.as_ref().and_then(|options| serde_json::to_value(options).ok()),
+            priority: original.priority,
+            process_priority: serde_json::to_value(original.process_priority).ok(),
+            script_source_url: original.script_source_url,
+            project_id: original.project_id,
+            output_location: output_location.map(|location| location.to_string()),
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    ContractRepository::new(config.pool())
+        .insert(NewIndexerContractDb::from_filters(clone_id, contract_filters))
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    record_event(context, AuditAction::StatusChange, None, Some(IndexerStatus::Created), &clone).await;
+
+    Ok(clone)
+}
+
+/// The copy mustn't resume from the cursor of the original, nor write to the same table
+fn validate_clone_outputs(
+    original: &IndexerModel,
+    indexer_id: Option<&str>,
+    table_name: Option<&str>,
+    custom_connection_string: Option<&str>,
+) -> Result<(), String> {
+    if indexer_id.is_some_and(|indexer_id| Some(indexer_id) == original.indexer_id.as_deref()) {
+        return Err(format!("sink id {} is the one of the original", indexer_id.unwrap_or_default()));
+    }
+    if original.indexer_type == IndexerType::Postgres
+        && table_name == original.table_name.as_deref()
+        && custom_connection_string == original.custom_connection_string.as_deref()
+    {
+        return Err("a postgres copy needs a table_name or a custom_connection_string of its own".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_clone_outputs() {
+        let original = IndexerModel {
+            indexer_type: IndexerType::Postgres,
+            indexer_id: Some("transfers".into()),
+            table_name: Some("transfers".into()),
+            ..Default::default()
+        };
+        assert!(validate_clone_outputs(&original, None, Some("transfers"), None).is_err());
+        assert!(validate_clone_outputs(&original, Some("transfers"), Some("transfers_v2"), None).is_err());
+        assert!(validate_clone_outputs(&original, Some("transfers_v2"), Some("transfers_v2"), None).is_ok());
+        // the same table in another database
+        assert!(validate_clone_outputs(&original, None, Some("transfers"), Some("postgres://staging/db")).is_ok());
+
+        let webhook = IndexerModel { indexer_type: IndexerType::Webhook, ..Default::default() };
+        assert!(validate_clone_outputs(&webhook, None, None, None).is_ok());
+    }
+}
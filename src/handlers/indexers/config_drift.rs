@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::CONFIG_DRIFT_CHECK_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus, LaunchConfig};
+use crate::domain::models::maintenance::ActionPriority;
+use crate::handlers::indexers::indexer_types::{get_indexer_handler, get_launch_config};
+use crate::handlers::indexers::update_indexer::restart_indexer;
+use crate::handlers::indexers::utils::{get_resolved_script, is_action_deferred};
+use crate::infra::repositories::audit_repository::{AuditRepository, NewAuditLogDb};
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+
+/// Returns the parts of the launch config which differ from what is expected
+pub fn get_drifted_fields(launched: &LaunchConfig, expected: &LaunchConfig) -> Vec<&'static str> {
+    let mut fields = vec![];
+    if launched.script_checksum != expected.script_checksum {
+        fields.push("script");
+    }
+    if launched.env != expected.env {
+        fields.push("env");
+    }
+    if launched.options_checksum != expected.options_checksum {
+        fields.push("options");
+    }
+    fields
+}
+
+/// Periodically compares the config of every running indexer against what its process was
+/// launched with. Drifted indexers are reported once with a `ConfigDrift` audit log and
+/// restarted if `CONFIG_DRIFT_AUTO_RESTART` is set. Restarts are routine actions so they wait
+/// for the end of maintenance windows.
+pub async fn monitor_config_drift() {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CONFIG_DRIFT_CHECK_INTERVAL_SECONDS));
+    let mut reported: HashSet<Uuid> = HashSet::new();
+    let mut deferred: HashSet<Uuid> = HashSet::new();
+    loop {
+        interval.tick().await;
+        if let Err(e) = check_config_drift(&mut reported, &mut deferred).await {
+            tracing::error!("Failed to check indexers for config drift: {:?}", e);
+        }
+    }
+}
+
+async fn check_config_drift(reported: &mut HashSet<Uuid>, deferred: &mut HashSet<Uuid>) -> Result<(), IndexerError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.background_pool());
+    let indexers = repository
+        .get_all(IndexerFilter { status: Some(IndexerStatus::Running.to_string()) })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    let mut drifted = HashSet::new();
+    for indexer_model in indexers {
+        let id = indexer_model.id;
+        let fields = match get_config_drift(&indexer_model).await {
+            Ok(fields) => fields,
+            Err(e) => {
+                tracing::warn!("Failed to check config drift for indexer {}: {:?}", id, e);
+                continue;
+            }
+        };
+        if fields.is_empty() {
+            continue;
+        }
+
+        drifted.insert(id);
+        if deferred.contains(&id) {
+            if is_action_deferred(&indexer_model, ActionPriority::Routine).await {
+                continue;
+            }
+            deferred.remove(&id);
+            reported.remove(&id);
+            restart_drifted_indexer(indexer_model, reported).await;
+            continue;
+        }
+        if reported.contains(&id) {
+            continue;
+        }
+        tracing::warn!("Indexer {} drifted from its launch config: {}", id, fields.join(", "));
+        AuditRepository::new(config.background_pool())
+            .insert(NewAuditLogDb {
+                id: Uuid::new_v4(),
+                indexer_id: id,
+                action: AuditAction::ConfigDrift.to_string(),
+                from_status: None,
+                to_status: None,
+                reason: Some(format!("drifted fields: {}", fields.join(", "))),
+                actor: Some("system".to_string()),
+                severity: AuditSeverity::Warning.to_string(),
+                details: None,
+                correlation_id: None,
+            })
+            .await
+            .map_err(IndexerError::InfraError)?;
+
+        if config.config_drift_auto_restart() {
+            if is_action_deferred(&indexer_model, ActionPriority::Routine).await {
+                tracing::info!("Deferring the restart of drifted indexer {} until the maintenance window ends", id);
+                deferred.insert(id);
+                reported.insert(id);
+                continue;
+            }
+            restart_drifted_indexer(indexer_model, reported).await;
+            continue;
+        }
+        reported.insert(id);
+    }
+
+    // indexers which were fixed or stopped can be reported again
+    reported.retain(|id| drifted.contains(id));
+    deferred.retain(|id| drifted.contains(id));
+    Ok(())
+}
+
+async fn restart_drifted_indexer(indexer_model: IndexerModel, reported: &mut HashSet<Uuid>) {
+    let id = indexer_model.id;
+    tracing::info!("Restarting drifted indexer {}", id);
+    if let Err(e) = restart_indexer(&ActorContext::system(), id).await {
+        tracing::error!("Failed to restart drifted indexer {}: {:?}", id, e);
+        reported.insert(id);
+    }
+    // a successful restart records a fresh launch config
+}
+
+async fn get_config_drift(indexer_model: &IndexerModel) -> Result<Vec<&'static str>, IndexerError> {
+    // indexers started before launch configs were recorded can't be compared
+    let launched = match &indexer_model.launch_config {
+        Some(launched) => launched,
+        None => return Ok(vec![]),
+    };
+
+    let script = get_resolved_script(indexer_model).await?;
+
+    let indexer = get_indexer_handler(&indexer_model.indexer_type);
+    let expected = get_launch_config(indexer.as_ref(), indexer_model, &script);
+    Ok(get_drifted_fields(launched, &expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_drifted_fields() {
+        let launched = LaunchConfig {
+            script_checksum: "script".into(),
+            options_checksum: "options".into(),
+            env: BTreeMap::from([("STARTING_BLOCK".to_string(), "1".to_string())]),
+        };
+        assert!(get_drifted_fields(&launched, &launched.clone()).is_empty());
+
+        let mut expected = launched.clone();
+        expected.env.insert("RUST_LOG".into(), "debug".into());
+        expected.script_checksum = "other".into();
+        assert_eq!(get_drifted_fields(&launched, &expected), vec!["script", "env"]);
+    }
+
+    #[test]
+    fn test_launch_config_hashes_only_user_options() {
+        use sha2::{Digest, Sha256};
+
+        use crate::domain::models::indexer::{IndexerType, ScriptPermissions};
+
+        let indexer_model = IndexerModel {
+            indexer_type: IndexerType::Webhook,
+            target_url: Some("https://example.com".into()),
+            ..Default::default()
+        };
+        let handler = get_indexer_handler(&indexer_model.indexer_type);
+        // same checksum as before the service granted permissions on its own
+        let launch_config = get_launch_config(handler.as_ref(), &indexer_model, b"script");
+        assert_eq!(launch_config.options_checksum, hex::encode(Sha256::digest(b"--target-url\0https://example.com\0")));
+
+        let granted = IndexerModel {
+            script_permissions: ScriptPermissions { allow_net: vec!["example.com".into()], ..Default::default() },
+            ..indexer_model.clone()
+        };
+        assert_ne!(
+            get_launch_config(handler.as_ref(), &granted, b"script").options_checksum,
+            launch_config.options_checksum
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::constants::indexers::CONSOLE_OUTPUT_RECORDS_PER_INDEXER;
+use crate::domain::models::console_output::{ConsoleOutputQuery, ConsoleOutputRecord};
+use crate::domain::models::indexer::{IndexerError, IndexerType};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::{PathExtractor, QueryExtractor};
+use crate::AppState;
+
+/// Last data printed by the console sinks of this instance, by indexer
+static CONSOLE_OUTPUTS: OnceLock<Mutex<HashMap<Uuid, VecDeque<ConsoleOutputRecord>>>> = OnceLock::new();
+
+fn console_outputs() -> std::sync::MutexGuard<'static, HashMap<Uuid, VecDeque<ConsoleOutputRecord>>> {
+    CONSOLE_OUTPUTS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Keeps a line of the stdout of a console sink, the oldest records of the indexer are dropped
+/// once it has `CONSOLE_OUTPUT_RECORDS_PER_INDEXER` of them
+pub fn record_console_output(indexer_id: Uuid, line: &str) {
+    let Some(record) = ConsoleOutputRecord::parse(line, Utc::now()) else {
+        return;
+    };
+    let mut outputs = console_outputs();
+    let records = outputs.entry(indexer_id).or_default();
+    if records.len() == CONSOLE_OUTPUT_RECORDS_PER_INDEXER {
+        records.pop_front();
+    }
+    records.push_back(record);
+}
+
+pub fn forget_console_output(indexer_id: Uuid) {
+    console_outputs().remove(&indexer_id);
+}
+
+/// Data printed by the console sink of the indexer, oldest first, so that scripts can be checked
+/// before being pointed at a real target. Only the output of the sinks this instance ran is
+/// known.
+pub async fn get_console_output(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<ConsoleOutputQuery>,
+) -> Result<Json<Vec<ConsoleOutputRecord>>, IndexerError> {
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    if indexer_model.indexer_type != IndexerType::Console {
+        return Err(IndexerError::NotAConsoleIndexer(id, indexer_model.indexer_type));
+    }
+
+    let mut records: Vec<ConsoleOutputRecord> = match console_outputs().get(&id) {
+        Some(records) => records.iter().filter(|record| query.matches(record)).cloned().collect(),
+        None => vec![],
+    };
+    if let Some(limit) = query.limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+
+    Ok(Json(records))
+}
@@ -1,25 +1,53 @@
+use std::collections::BTreeMap;
 use std::net::TcpListener;
 use std::str::FromStr;
 
 use axum::body::Bytes;
-use axum::extract::{Multipart, State};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use diesel::SelectableHelper;
+use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::scoped_futures::ScopedFutureExt;
-use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use object_store::path::Path;
 use serde::Deserialize;
 use uuid::Uuid;
 
 use super::fail_indexer::fail_indexer;
 use super::start_indexer::start_indexer;
-use super::utils::query_status_server;
+use super::utils::{query_status_server, record_event};
 use crate::config::config;
-use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::creation::PartialCreationModel;
+use crate::domain::models::hook::IndexerHooks;
+use crate::domain::models::indexer::{
+    IndexerError, IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType, ScriptPermissions,
+};
+use crate::domain::models::output_location::OutputLocation;
+use crate::domain::models::process_priority::ProcessPriority;
+use crate::domain::models::project::ProjectRole;
+use crate::domain::models::sink_options::SinkOptions;
+use crate::handlers::indexers::approvals::is_pending_approval;
+use crate::handlers::indexers::hooks::validate_hooks;
+use crate::handlers::indexers::request_fields::CreateIndexerFields;
+use crate::handlers::indexers::script_scan::scan_script;
 use crate::handlers::indexers::utils::get_s3_script_key;
+use crate::handlers::uploads::sessions::get_completed_upload;
+use crate::infra::db::pool::get_connection;
 use crate::infra::db::schema::indexers;
 use crate::infra::errors::InfraError;
-use crate::infra::repositories::indexer_repository::{self, IndexerDb};
+use crate::infra::repositories::contract_repository::{self, NewIndexerContractDb};
+use crate::infra::repositories::indexer_repository::{self, IndexerDb, IndexerRepository, Repository};
+use crate::infra::repositories::project_repository::ProjectRepository;
+use crate::infra::repositories::tenant_repository::TenantRepository;
+use crate::utils::script_cache::get_script_checksum;
+use crate::utils::script_fetch::{fetch_script, verify_script_checksum};
+use crate::utils::script_filter::extract_contract_filters;
+use crate::utils::script_params::resolve_script_params;
+use crate::utils::PathExtractor;
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -27,9 +55,23 @@ pub struct CreateIndexerRequest {
     pub indexer_type: IndexerType,
     pub target_url: Option<String>,
     pub table_name: Option<String>,
+    pub output_bucket: Option<String>,
+    pub output_prefix: Option<String>,
     pub custom_connection_string: Option<String>,
     pub starting_block: Option<i64>,
     pub indexer_id: Option<String>,
+    pub log_level: Option<IndexerLogLevel>,
+    pub script_permissions: ScriptPermissions,
+    pub tenant_id: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    pub script_params: BTreeMap<String, String>,
+    pub hooks: IndexerHooks,
+    pub sink_options: Option<SinkOptions>,
+    pub priority: i32,
+    pub process_priority: ProcessPriority,
+    pub script_source_url: Option<String>,
     #[serde(skip)]
     pub data: Bytes,
     #[serde(skip)]
@@ -42,9 +84,23 @@ impl Default for CreateIndexerRequest {
             indexer_type: IndexerType::default(),
             target_url: None,
             table_name: None,
+            output_bucket: None,
+            output_prefix: None,
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            script_permissions: ScriptPermissions::default(),
+            tenant_id: None,
+            project_id: None,
+            stream_url: None,
+            ending_block: None,
+            script_params: BTreeMap::new(),
+            hooks: IndexerHooks::default(),
+            sink_options: None,
+            priority: 0,
+            process_priority: ProcessPriority::default(),
+            script_source_url: None,
             data: Bytes::new(),
             status_server_port: 1234,
         }
@@ -52,26 +108,6 @@ impl Default for CreateIndexerRequest {
 }
 
 impl CreateIndexerRequest {
-    /// Check if the request is ready to be processed
-    fn is_ready(&self) -> bool {
-        if self.data.is_empty() {
-            return false;
-        }
-        match self.indexer_type {
-            IndexerType::Postgres => {
-                if self.table_name.is_none() {
-                    return false;
-                }
-            }
-            IndexerType::Webhook => {
-                if self.target_url.is_none() {
-                    return false;
-                }
-            }
-        };
-        true
-    }
-
     /// Set a random available port for the gRPC status server
     fn set_random_port(&mut self) {
         // Bind to a random port
@@ -85,40 +121,53 @@ impl CreateIndexerRequest {
 }
 
 // not using From trait as we need async functions
-async fn build_create_indexer_request(request: &mut Multipart) -> Result<CreateIndexerRequest, IndexerError> {
-    let mut create_indexer_request = CreateIndexerRequest::default();
-    while let Some(field) = request.next_field().await.map_err(IndexerError::FailedToReadMultipartField)? {
-        let field_name = field.name().ok_or(IndexerError::InternalServerError("Failed to get field name".into()))?;
-        match field_name {
-            "script.js" => {
-                create_indexer_request.data = field.bytes().await.map_err(IndexerError::FailedToReadMultipartField)?
-            }
-            "target_url" => {
-                create_indexer_request.target_url =
-                    Some(field.text().await.map_err(IndexerError::FailedToReadMultipartField)?)
-            }
-            "table_name" => {
-                create_indexer_request.table_name =
-                    Some(field.text().await.map_err(IndexerError::FailedToReadMultipartField)?)
-            }
-            "indexer_type" => {
-                let field = field.text().await.map_err(IndexerError::FailedToReadMultipartField)?;
-                create_indexer_request.indexer_type =
-                    IndexerType::from_str(field.as_str()).map_err(|_| IndexerError::InvalidIndexerType(field))?
-            }
-            "starting_block" => {
-                let field = field.text().await.map_err(IndexerError::FailedToReadMultipartField)?;
-                create_indexer_request.starting_block = Some(
-                    field.parse().map_err(|_| IndexerError::InternalServerError("Invalid starting block".into()))?,
-                );
-            }
-            "indexer_id" => {
-                create_indexer_request.indexer_id =
-                    Some(field.text().await.map_err(IndexerError::FailedToReadMultipartField)?)
-            }
-            _ => return Err(IndexerError::UnexpectedMultipartField(field_name.to_string())),
-        };
+async fn build_create_indexer_request(
+    context: &ActorContext,
+    fields: CreateIndexerFields,
+) -> Result<CreateIndexerRequest, IndexerError> {
+    let mut create_indexer_request = CreateIndexerRequest {
+        indexer_type: fields.indexer_type()?,
+        target_url: fields.text("target_url")?,
+        table_name: fields.text("table_name")?,
+        custom_connection_string: fields.text("custom_connection_string")?,
+        output_bucket: fields.text("output_bucket")?,
+        output_prefix: fields.text("output_prefix")?,
+        starting_block: fields.parse("starting_block")?,
+        ending_block: fields.parse("ending_block")?,
+        priority: fields.parse("priority")?.unwrap_or_default(),
+        indexer_id: fields.text("indexer_id")?,
+        log_level: fields
+            .text("log_level")?
+            .map(|log_level| {
+                IndexerLogLevel::from_str(&log_level).map_err(|_| IndexerError::InvalidLogLevel(log_level))
+            })
+            .transpose()?,
+        tenant_id: fields.text("tenant_id")?,
+        project_id: fields.parse("project_id")?,
+        stream_url: fields.text("stream_url")?,
+        script_permissions: fields
+            .json("script_permissions", IndexerError::InvalidScriptPermissions)?
+            .unwrap_or_default(),
+        script_params: fields.json("script_params", IndexerError::InvalidScriptParams)?.unwrap_or_default(),
+        sink_options: fields.json("sink_options", IndexerError::InvalidSinkOptions)?,
+        process_priority: fields.json("process_priority", IndexerError::InvalidProcessPriority)?.unwrap_or_default(),
+        hooks: fields.json("hooks", IndexerError::InvalidHooks)?.unwrap_or_default(),
+        ..Default::default()
+    };
+    let pinned_checksum = fields.text("script_sha256")?;
+    create_indexer_request.script_source_url = fields.text("script_url")?;
+    create_indexer_request.data = match (fields.parse::<Uuid>("upload_id")?, &create_indexer_request.script_source_url)
+    {
+        (Some(upload_id), _) => {
+            get_completed_upload(context, upload_id).await.map_err(IndexerError::FailedToGetUpload)?
+        }
+        (None, Some(script_url)) => fetch_script(script_url).await?,
+        (None, None) => fields.bytes("script.js").unwrap_or_default(),
+    };
+    if create_indexer_request.data.is_empty() {
+        return Err(IndexerError::InvalidMultipartField("script.js".into(), "the script is empty".into()));
     }
+    verify_script_checksum(&create_indexer_request.data, pinned_checksum.as_deref())?;
 
     create_indexer_request.set_random_port();
 
@@ -127,22 +176,184 @@ async fn build_create_indexer_request(request: &mut Multipart) -> Result<CreateI
         create_indexer_request.indexer_id = create_indexer_request.table_name.clone();
     }
 
-    if !create_indexer_request.is_ready() {
-        return Err(IndexerError::FailedToBuildCreateIndexerRequest);
+    Ok(create_indexer_request)
+}
+
+/// Puts the indexer in the project, for the callers allowed to edit it, and fills the settings
+/// missing from the request with the defaults of the project
+async fn apply_project_settings(
+    context: &ActorContext,
+    pool: &Pool<AsyncPgConnection>,
+    create_indexer_request: &mut CreateIndexerRequest,
+    project_id: Uuid,
+) -> Result<(), IndexerError> {
+    let project = ProjectRepository::new(pool)
+        .get(project_id)
+        .await
+        .map_err(IndexerError::InfraError)?
+        .ok_or(IndexerError::ProjectNotFound(project_id))?;
+    project
+        .authorize(context, ProjectRole::Editor)
+        .map_err(|_| IndexerError::ProjectAccessDenied(project_id, ProjectRole::Editor))?;
+
+    match &create_indexer_request.tenant_id {
+        Some(tenant_id) if *tenant_id != project.tenant_id => {
+            return Err(IndexerError::ProjectOfAnotherTenant(project_id, tenant_id.clone()));
+        }
+        _ => create_indexer_request.tenant_id = Some(project.tenant_id),
+    }
+    if create_indexer_request.stream_url.is_none() {
+        create_indexer_request.stream_url = project.default_stream_url;
     }
+    if create_indexer_request.log_level.is_none() {
+        create_indexer_request.log_level = project.default_log_level;
+    }
+    Ok(())
+}
 
-    Ok(create_indexer_request)
+/// Fills the settings missing from the request with the defaults of the tenant
+async fn apply_tenant_settings(
+    pool: &Pool<AsyncPgConnection>,
+    create_indexer_request: &mut CreateIndexerRequest,
+    tenant_id: String,
+) -> Result<(), IndexerError> {
+    let repository = TenantRepository::new(pool);
+    let settings =
+        match repository.get_settings(tenant_id.as_str()).await.map_err(IndexerError::FailedToGetTenantSettings)? {
+            Some(settings) => settings,
+            None => return Ok(()),
+        };
+
+    if !settings.is_indexer_type_allowed(&create_indexer_request.indexer_type) {
+        return Err(IndexerError::IndexerTypeNotAllowed(create_indexer_request.indexer_type.clone(), tenant_id));
+    }
+    if create_indexer_request.stream_url.is_none() {
+        create_indexer_request.stream_url = settings.default_stream_url;
+    }
+    if create_indexer_request.log_level.is_none() {
+        create_indexer_request.log_level = settings.default_log_level;
+    }
+    Ok(())
 }
 
+/// An indexer which was stored but couldn't be started is answered with `207 Multi-Status` and
+/// the steps which completed, rather than an error leaving the caller unsure whether it exists
 pub async fn create_indexer(
     State(state): State<AppState>,
-    mut request: Multipart,
-) -> Result<Json<IndexerModel>, IndexerError> {
+    context: ActorContext,
+    fields: CreateIndexerFields,
+) -> Result<Response, IndexerError> {
+    let created_indexer = store_indexer(&context, &state.pool, fields).await?;
+    complete_or_report(&context, &state.pool, created_indexer).await
+}
+
+/// Retries the start of an indexer whose create was answered with `207 Multi-Status`
+pub async fn complete_create_indexer(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Response, IndexerError> {
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    if !context.can_act_for_owner(indexer_model.tenant_id.as_deref()) {
+        return Err(IndexerError::IndexerAccessDenied(id));
+    }
+    if indexer_model.status != IndexerStatus::Created {
+        return Err(IndexerError::CreationAlreadyComplete(id, indexer_model.status));
+    }
+    complete_or_report(&context, &state.pool, indexer_model).await
+}
+
+async fn complete_or_report(
+    context: &ActorContext,
+    pool: &Pool<AsyncPgConnection>,
+    created_indexer: IndexerModel,
+) -> Result<Response, IndexerError> {
+    let error = match complete_creation(context, &created_indexer).await {
+        Ok(()) => return Ok(Json(created_indexer).into_response()),
+        Err(e) => e,
+    };
+    tracing::error!("Indexer {} was created but couldn't be started: {:?}", created_indexer.id, error);
+    // the failed start may have moved it
+    let indexer_model = IndexerRepository::new(pool).get(created_indexer.id).await.unwrap_or(created_indexer);
+
+    Ok((StatusCode::MULTI_STATUS, Json(PartialCreationModel::start_failed(indexer_model, error.to_string())))
+        .into_response())
+}
+
+/// Creates and starts an indexer from the validated fields of a create request, whether they
+/// were sent to the API or read from somewhere else, e.g. a GitOps repository
+pub async fn create_indexer_from_fields(
+    context: &ActorContext,
+    pool: &Pool<AsyncPgConnection>,
+    fields: CreateIndexerFields,
+) -> Result<IndexerModel, IndexerError> {
+    let created_indexer = store_indexer(context, pool, fields).await?;
+    complete_creation(context, &created_indexer).await?;
+    Ok(created_indexer)
+}
+
+/// Records the indexer and stores its script, nothing is kept if either fails
+async fn store_indexer(
+    context: &ActorContext,
+    pool: &Pool<AsyncPgConnection>,
+    fields: CreateIndexerFields,
+) -> Result<IndexerModel, IndexerError> {
     let id = Uuid::new_v4();
-    let create_indexer_request = build_create_indexer_request(&mut request).await?;
+    let mut create_indexer_request = build_create_indexer_request(context, fields).await?;
+    // indexers belong to the tenant of the caller, only admins can name another one. Indexers of
+    // no tenant skip the approval of their target.
+    match &create_indexer_request.tenant_id {
+        Some(tenant_id) if !context.can_act_for_tenant(tenant_id) => {
+            return Err(IndexerError::ForeignTenant(tenant_id.clone()));
+        }
+        Some(_) => (),
+        None => create_indexer_request.tenant_id = context.tenant_id.clone(),
+    }
+    // the defaults of the project take precedence over the ones of its tenant
+    if let Some(project_id) = create_indexer_request.project_id {
+        apply_project_settings(context, pool, &mut create_indexer_request, project_id).await?;
+    }
+    if let Some(tenant_id) = create_indexer_request.tenant_id.clone() {
+        apply_tenant_settings(pool, &mut create_indexer_request, tenant_id).await?;
+    }
+
+    let config = config().await;
+    if let Some(target_url) = &create_indexer_request.target_url {
+        config.target_policy().validate(target_url).await?;
+    }
+    // checked once the defaults of the project and the tenant are applied
+    if let Some(stream_url) = &create_indexer_request.stream_url {
+        config.stream_policy().validate(stream_url)?;
+    }
+    config.sandbox_policy().validate(&create_indexer_request.script_permissions)?;
+    validate_hooks(&create_indexer_request.hooks).await?;
+    if let Some(sink_options) = &create_indexer_request.sink_options {
+        sink_options.validate(&create_indexer_request.indexer_type)?;
+    }
+    create_indexer_request.process_priority.validate()?;
+    let output_location = create_indexer_request
+        .output_bucket
+        .as_deref()
+        .map(|bucket| OutputLocation::new(bucket, create_indexer_request.output_prefix.as_deref(), id))
+        .transpose()
+        .map_err(IndexerError::InvalidOutputLocation)?;
+    // fails early rather than at the first start if the script references unknown params
+    let script = std::str::from_utf8(&create_indexer_request.data)
+        .map_err(|e| IndexerError::InvalidScriptParams(e.to_string()))?;
+    let resolved_script = resolve_script_params(script, &create_indexer_request.script_params)
+        .map_err(IndexerError::MissingScriptParams)?;
+    scan_script(pool, script).await?;
+    let pending_approval = is_pending_approval(
+        pool,
+        create_indexer_request.tenant_id.as_deref(),
+        create_indexer_request.target_url.as_deref(),
+    )
+    .await?;
+    let status = if pending_approval { IndexerStatus::PendingApproval } else { IndexerStatus::Created };
+
     let new_indexer_db = indexer_repository::NewIndexerDb {
         id,
-        status: IndexerStatus::Created.to_string(),
+        status: status.to_string(),
         type_: create_indexer_request.indexer_type.to_string(),
         target_url: create_indexer_request.target_url.clone(),
         table_name: create_indexer_request.table_name.clone(),
@@ -150,12 +361,62 @@ pub async fn create_indexer(
         custom_connection_string: create_indexer_request.custom_connection_string.clone(),
         starting_block: create_indexer_request.starting_block,
         indexer_id: create_indexer_request.indexer_id.clone(),
+        log_level: create_indexer_request.log_level.map(|log_level| log_level.to_string()),
+        standby_for: None,
+        script_permissions: serde_json::to_value(&create_indexer_request.script_permissions).ok(),
+        tenant_id: create_indexer_request.tenant_id.clone(),
+        stream_url: create_indexer_request.stream_url.clone(),
+        ending_block: create_indexer_request.ending_block,
+        backfill_for: None,
+        script_params: serde_json::to_value(&create_indexer_request.script_params).ok(),
+        script_checksum: Some(get_script_checksum(&create_indexer_request.data)),
+        hooks: serde_json::to_value(&create_indexer_request.hooks).ok(),
+        sink_options: create_indexer_request
+            .sink_options
+            .as_ref()
+            .and_then(|options| serde_json::to_value(options).ok()),
+        priority: create_indexer_request.priority,
+        process_priority: serde_json::to_value(create_indexer_request.process_priority).ok(),
+        script_source_url: create_indexer_request.script_source_url.clone(),
+        project_id: create_indexer_request.project_id,
+        output_location: output_location.map(|location| location.to_string()),
     };
 
-    let config = config().await;
+    // addresses may be params of the script
+    let contract_filters = extract_contract_filters(&resolved_script);
+    let new_contracts_db = NewIndexerContractDb::from_filters(id, contract_filters);
+
+    // the script is stored before the indexer is recorded, an indexer is never seen without its
+    // script and the DB connection isn't held during the upload
+    let location = Path::from(get_s3_script_key(id));
+    config
+        .object_store()
+        .put(&location, create_indexer_request.data.into())
+        .await
+        .map_err(IndexerError::FailedToUploadToStore)?;
+    let created_indexer = match insert_indexer(pool, new_indexer_db, new_contracts_db).await {
+        Ok(created_indexer) => created_indexer,
+        Err(e) => {
+            // the script would be left without an indexer
+            if let Err(e) = config.object_store().delete(&location).await {
+                tracing::error!("Failed to delete the script of indexer {} which wasn't created: {:?}", id, e);
+            }
+            return Err(e);
+        }
+    };
+
+    record_event(context, AuditAction::StatusChange, None, Some(status), &created_indexer).await;
+    Ok(created_indexer)
+}
 
-    let connection = &mut state.pool.get().await.expect("Failed to get a connection from the pool");
-    let created_indexer = connection
+/// Records the indexer along with its contracts
+async fn insert_indexer(
+    pool: &Pool<AsyncPgConnection>,
+    new_indexer_db: indexer_repository::NewIndexerDb,
+    new_contracts_db: Vec<NewIndexerContractDb>,
+) -> Result<IndexerModel, IndexerError> {
+    let connection = &mut get_connection(pool).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+    connection
         .transaction::<_, IndexerError, _>(|conn| {
             async move {
                 let created_indexer: IndexerModel = diesel::insert_into(indexers::table)
@@ -165,21 +426,25 @@ pub async fn create_indexer(
                     .await?
                     .try_into()
                     .map_err(|e| IndexerError::InfraError(InfraError::ParseError(e)))?;
-
-                let location = Path::from(get_s3_script_key(id));
-                config
-                    .object_store()
-                    .put(&location, create_indexer_request.data.into())
+                contract_repository::insert_with_connection(conn, new_contracts_db)
                     .await
-                    .map_err(IndexerError::FailedToUploadToStore)?;
+                    .map_err(IndexerError::InfraError)?;
 
                 Ok(created_indexer)
             }
             .scope_boxed()
         })
-        .await?;
+        .await
+}
+
+/// Starts the stored indexer unless it waits for the approval of its target
+async fn complete_creation(context: &ActorContext, created_indexer: &IndexerModel) -> Result<(), IndexerError> {
+    if created_indexer.status == IndexerStatus::PendingApproval {
+        tracing::info!("Indexer {} waits for the approval of its target", created_indexer.id);
+        return Ok(());
+    }
 
-    start_indexer(created_indexer.id).await?;
+    start_indexer(context, created_indexer.id).await?;
 
     // wait a bit for the indexer to start
     tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -189,8 +454,8 @@ pub async fn create_indexer(
     let server_port = created_indexer.status_server_port.ok_or(IndexerError::IndexerStatusServerPortNotFound)?;
     let server_status = query_status_server(server_port).await?;
     if server_status.status != 1 {
-        fail_indexer(created_indexer.id).await?;
+        fail_indexer(context, created_indexer.id).await?;
     }
 
-    Ok(Json(created_indexer))
+    Ok(())
 }
@@ -1,23 +1,154 @@
 use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::AsyncPgConnection;
+use futures_util::TryStreamExt;
+use object_store::path::Path;
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::domain::models::indexer::{IndexerError, IndexerStatus};
-use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
-use crate::utils::PathExtractor;
+use crate::config::config;
+use crate::constants::s3::INDEXER_SERVICE_DIAGNOSTICS_FOLDER;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::cleanup::{get_secret_names, CleanupPlanModel};
+use crate::domain::models::execution::StopExpectation;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::console::forget_console_output;
+use crate::handlers::indexers::indexer_types::get_indexer_handler;
+use crate::handlers::indexers::logs::forget_sink_logs;
+use crate::handlers::indexers::stop_indexer::stop_locked_indexer;
+use crate::handlers::indexers::utils::{get_s3_script_key, get_script_tmp_directory, lock_indexer};
+use crate::infra::repositories::indexer_repository::{count_dependent_rows, IndexerRepository, Repository};
+use crate::infra::repositories::scheduled_action_repository::{ScheduledActionFilter, ScheduledActionRepository};
+use crate::utils::{PathExtractor, QueryExtractor};
 use crate::AppState;
 
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteIndexerQuery {
+    /// Only reports what the delete would remove
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Stops the indexer first when it is still running instead of refusing the delete
+    #[serde(default)]
+    pub force: bool,
+}
+
 pub async fn delete_indexer(
     State(state): State<AppState>,
+    context: ActorContext,
     PathExtractor(id): PathExtractor<Uuid>,
-) -> Result<(), IndexerError> {
+    QueryExtractor(query): QueryExtractor<DeleteIndexerQuery>,
+) -> Result<Response, IndexerError> {
+    // held until the indexer is gone so that it isn't started again in between
+    let _lock = lock_indexer(id).await;
     let mut repository = IndexerRepository::new(&state.pool);
-    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let mut indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if query.force && !query.dry_run && indexer_model.status.is_live() {
+        stop_locked_indexer(&context, id, &StopExpectation::default(), Some("indexer deleted".into())).await?;
+        indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    }
+    if query.force && !query.dry_run && indexer_model.status == IndexerStatus::FailedStopping {
+        kill_indexer_process(&indexer_model).await?;
+    }
     match indexer_model.status {
         IndexerStatus::Stopped => (),
+        IndexerStatus::Abandoned => (),
+        IndexerStatus::PendingApproval => (),
+        // its process was killed above
+        IndexerStatus::FailedStopping if query.force => (),
+        status if query.force && (query.dry_run || !needs_stop(status)) => (),
         _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
     }
 
+    let plan = plan_cleanup(&state.pool, &indexer_model).await?;
+    if query.dry_run {
+        return Ok(Json(plan).into_response());
+    }
+
     repository.delete(id).await.map_err(IndexerError::InfraError)?;
+    execute_cleanup(&plan).await;
+    forget_sink_logs(id);
+    forget_console_output(id);
+
+    Ok(().into_response())
+}
+
+/// Whether a process may still run for an indexer in this status
+fn needs_stop(status: IndexerStatus) -> bool {
+    status.is_live() || status == IndexerStatus::FailedStopping
+}
+
+/// Stops the process an indexer failed to stop before, the stop API only takes live indexers.
+/// Nothing is done if the process already exited.
+async fn kill_indexer_process(indexer_model: &IndexerModel) -> Result<(), IndexerError> {
+    let indexer = get_indexer_handler(&indexer_model.indexer_type);
+    match indexer.stop(indexer_model.clone()).await {
+        Ok(()) => Ok(()),
+        Err(IndexerError::InternalServerError(error)) if error.contains("Cannot stop indexer that's not running") => {
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Lists what goes away with the indexer. The rows are deleted by the database along with the
+/// indexer, the objects and the files are removed by `execute_cleanup`.
+pub async fn plan_cleanup(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_model: &IndexerModel,
+) -> Result<CleanupPlanModel, IndexerError> {
+    let config = config().await;
+    let id = indexer_model.id;
+
+    let mut objects = vec![];
+    let script_key = Path::from(get_s3_script_key(id));
+    match config.object_store().head(&script_key).await {
+        Ok(_) => objects.push(script_key.to_string()),
+        Err(object_store::Error::NotFound { .. }) => (),
+        Err(e) => return Err(IndexerError::FailedToGetFromStore(e)),
+    }
+    let diagnostics: Vec<_> = config
+        .object_store()
+        .list(Some(&Path::from(format!("{}/{}", INDEXER_SERVICE_DIAGNOSTICS_FOLDER, id))))
+        .try_collect()
+        .await
+        .map_err(IndexerError::FailedToGetFromStore)?;
+    objects.extend(diagnostics.into_iter().map(|object| object.location.to_string()));
+
+    let script_file = get_script_tmp_directory(id);
+    let local_files = match tokio::fs::try_exists(&script_file).await {
+        Ok(true) => vec![script_file],
+        _ => vec![],
+    };
 
-    Ok(())
+    let scheduled_actions = ScheduledActionRepository::new(pool)
+        .get_all(ScheduledActionFilter { indexer_id: Some(id), include_failed: true })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(CleanupPlanModel {
+        indexer_id: id,
+        objects,
+        local_files,
+        rows: count_dependent_rows(pool, id).await.map_err(IndexerError::InfraError)?,
+        secrets: get_secret_names(indexer_model),
+        scheduled_actions: scheduled_actions.into_iter().map(|action| action.id).collect(),
+    })
+}
+
+/// Removes the objects and the files of a deleted indexer. The indexer is gone already, what
+/// can't be removed is only logged and found again by the script sync.
+async fn execute_cleanup(plan: &CleanupPlanModel) {
+    let config = config().await;
+    for key in &plan.objects {
+        if let Err(e) = config.object_store().delete(&Path::from(key.as_str())).await {
+            tracing::warn!("Failed to remove {} of deleted indexer {}: {}", key, plan.indexer_id, e);
+        }
+    }
+    for file in &plan.local_files {
+        if let Err(e) = tokio::fs::remove_file(file).await {
+            tracing::warn!("Failed to remove {} of deleted indexer {}: {}", file, plan.indexer_id, e);
+        }
+    }
 }
@@ -0,0 +1,91 @@
+use axum::extract::State;
+use axum::Json;
+use object_store::path::Path;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::s3::INDEXER_SERVICE_DIAGNOSTICS_FOLDER;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::diagnostics::ProcessExitSnapshot;
+use crate::domain::models::indexer::IndexerError;
+use crate::infra::repositories::audit_repository::{AuditRepository, NewAuditLogDb};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Snapshots are named after the time the process exited, in milliseconds
+pub fn get_s3_diagnostics_key(indexer_id: Uuid, exited_at_millis: i64) -> String {
+    format!("{}/{}/{}.json", INDEXER_SERVICE_DIAGNOSTICS_FOLDER, indexer_id, exited_at_millis)
+}
+
+/// Stores the snapshot of a sink which exited and records a `ProcessExit` audit log linking to
+/// it. The audit log is recorded even if the snapshot couldn't be stored.
+pub async fn record_process_exit(context: &ActorContext, snapshot: ProcessExitSnapshot) {
+    let config = config().await;
+    let exited_at_millis = snapshot.exited_at.timestamp_millis();
+    let location = Path::from(get_s3_diagnostics_key(snapshot.indexer_id, exited_at_millis));
+    let diagnostics_url = match serde_json::to_vec(&snapshot) {
+        Ok(payload) => match config.object_store().put(&location, payload.into()).await {
+            Ok(_) => Some(format!("/v1/indexers/{}/diagnostics/{}", snapshot.indexer_id, exited_at_millis)),
+            Err(e) => {
+                tracing::error!("Failed to store the exit snapshot of indexer {}: {:?}", snapshot.indexer_id, e);
+                None
+            }
+        },
+        Err(e) => {
+            tracing::error!("Failed to serialize the exit snapshot of indexer {}: {:?}", snapshot.indexer_id, e);
+            None
+        }
+    };
+
+    let severity = if snapshot.exit_code == Some(0) { AuditSeverity::Info } else { AuditSeverity::Warning };
+    let reason = match snapshot.exit_code {
+        Some(exit_code) => format!("process exited with code {}", exit_code),
+        None => "process killed by a signal".to_string(),
+    };
+    let insert = AuditRepository::new(config.consumers_pool())
+        .insert(NewAuditLogDb {
+            id: Uuid::new_v4(),
+            indexer_id: snapshot.indexer_id,
+            action: AuditAction::ProcessExit.to_string(),
+            from_status: None,
+            to_status: None,
+            reason: Some(reason),
+            actor: Some("system".to_string()),
+            severity: severity.to_string(),
+            details: Some(serde_json::json!({
+                "process_id": snapshot.process_id,
+                "exit_code": snapshot.exit_code,
+                "diagnostics_url": diagnostics_url,
+            })),
+            correlation_id: context.request_id.clone(),
+        })
+        .await;
+    if let Err(e) = insert {
+        tracing::error!("Failed to record the exit of indexer {}: {:?}", snapshot.indexer_id, e);
+    }
+}
+
+/// Only the tenant of the indexer and the admins can read its snapshots
+pub async fn get_indexer_diagnostics(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor((id, exited_at_millis)): PathExtractor<(Uuid, i64)>,
+) -> Result<Json<ProcessExitSnapshot>, IndexerError> {
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    if !context.can_act_for_owner(indexer_model.tenant_id.as_deref()) {
+        return Err(IndexerError::IndexerAccessDenied(id));
+    }
+    let config = config().await;
+    let location = Path::from(get_s3_diagnostics_key(id, exited_at_millis));
+    let data = match config.object_store().get(&location).await {
+        Ok(data) => data.bytes().await.map_err(IndexerError::FailedToCollectBytesFromStore)?,
+        Err(object_store::Error::NotFound { .. }) => {
+            return Err(IndexerError::DiagnosticsNotFound(id, exited_at_millis));
+        }
+        Err(e) => return Err(IndexerError::FailedToGetFromStore(e)),
+    };
+    let snapshot = serde_json::from_slice(&data).map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?;
+    Ok(Json(snapshot))
+}
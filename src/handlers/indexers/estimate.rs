@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+use tokio::process::Command;
+use tokio::sync::{mpsc, Mutex};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::{ESTIMATE_SAMPLE_BLOCKS, ESTIMATE_SAMPLE_DAYS, ESTIMATE_SAMPLE_TIMEOUT_SECONDS};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::contract::normalize_felt;
+use crate::domain::models::estimate::{estimate, get_sample_script, EstimateModel, EstimateRequest, EventSample};
+use crate::domain::models::indexer::IndexerError;
+use crate::handlers::indexers::preview::preview_slots;
+use crate::handlers::indexers::reaper::TrackedChild;
+use crate::handlers::indexers::start_indexer::get_stream_head;
+use crate::handlers::indexers::utils::get_script_tmp_directory;
+use crate::infra::repositories::contract_repository::ContractRepository;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::infra::repositories::usage_repository::UsageRepository;
+use crate::utils::env::get_environment_variable;
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Payloads of the running samples by sample id
+static SAMPLES: OnceLock<Mutex<HashMap<Uuid, mpsc::UnboundedSender<Bytes>>>> = OnceLock::new();
+
+fn samples() -> &'static Mutex<HashMap<Uuid, mpsc::UnboundedSender<Bytes>>> {
+    SAMPLES.get_or_init(Mutex::default)
+}
+
+/// Estimates the blocks and events an indexer would process and how long it would take. The
+/// filter is run on a window of the stream in the middle of the blocks to count its events and
+/// measure the throughput, the log volume comes from the usage metered over the last days on the
+/// indexers of the same stream. The sample runs a sink so only tenants and admins can estimate,
+/// and it shares the slots of the previews.
+pub async fn estimate_indexer(
+    State(state): State<AppState>,
+    context: ActorContext,
+    JsonExtractor(mut request): JsonExtractor<EstimateRequest>,
+) -> Result<Json<EstimateModel>, IndexerError> {
+    if !context.can_act_for_owner(context.tenant_id.as_deref()) {
+        return Err(IndexerError::EstimateNotAllowed);
+    }
+    if let Some(stream_url) = &request.stream_url {
+        config().await.stream_policy().validate(stream_url)?;
+    }
+    for contract in request.filter.iter_mut() {
+        contract.address = normalize_felt(&contract.address).ok_or_else(|| {
+            IndexerError::InvalidRequestBody(format!("invalid contract address {}", contract.address))
+        })?;
+    }
+    let ending_block = match request.ending_block {
+        Some(ending_block) => ending_block,
+        None => get_stream_head(&request.stream_url).await?.ok_or_else(|| {
+            IndexerError::InvalidBlockRange("the head of the stream is unknown, set the ending block".into())
+        })?,
+    };
+    if request.starting_block > ending_block {
+        return Err(IndexerError::InvalidBlockRange(format!(
+            "starting block {} is after ending block {}",
+            request.starting_block, ending_block
+        )));
+    }
+
+    let sample = {
+        let _slot = preview_slots().try_acquire().map_err(|_| IndexerError::TooManyPreviews)?;
+        let blocks = ending_block - request.starting_block;
+        let sample_start = request.starting_block + blocks.saturating_sub(ESTIMATE_SAMPLE_BLOCKS) / 2;
+        let sample_end = ending_block.min(sample_start + ESTIMATE_SAMPLE_BLOCKS);
+        match sample_end > sample_start {
+            true => Some(sample_stream(&request, sample_start, sample_end).await?),
+            false => None,
+        }
+    };
+
+    let repository = IndexerRepository::new(&state.pool);
+    let on_stream: HashSet<Uuid> = repository
+        .get_all(IndexerFilter { status: None })
+        .await
+        .map_err(IndexerError::InfraError)?
+        .into_iter()
+        .filter(|indexer_model| indexer_model.stream_url == request.stream_url)
+        .map(|indexer_model| indexer_model.id)
+        .collect();
+
+    let contract_repository = ContractRepository::new(&state.pool);
+    let mut watching = HashSet::new();
+    for contract in &request.filter {
+        let indexers =
+            contract_repository.get_indexers_by_address(&contract.address).await.map_err(IndexerError::InfraError)?;
+        watching.extend(indexers.into_iter().map(|indexer_model| indexer_model.id).filter(|id| on_stream.contains(id)));
+    }
+
+    let usage_repository = UsageRepository::new(&state.pool);
+    let since = Utc::now().date_naive() - chrono::Duration::days(ESTIMATE_SAMPLE_DAYS);
+    let mut metered = vec![];
+    if !watching.is_empty() {
+        let ids: Vec<Uuid> = watching.into_iter().collect();
+        metered = usage_repository.get_indexers_usage(&ids, since).await.map_err(IndexerError::InfraError)?;
+    }
+    // the indexers watching the contracts weren't metered, the stream gives a rougher log volume
+    let filter_matched = !metered.is_empty();
+    if metered.is_empty() {
+        let ids: Vec<Uuid> = on_stream.into_iter().collect();
+        metered = usage_repository.get_indexers_usage(&ids, since).await.map_err(IndexerError::InfraError)?;
+    }
+
+    Ok(Json(estimate(request.starting_block, ending_block, sample, &metered, filter_matched)))
+}
+
+/// Runs the filter of the request over the blocks in a webhook sink targeting the service, without
+/// any persistence. A sample which times out covers the blocks it reached.
+async fn sample_stream(
+    request: &EstimateRequest,
+    starting_block: u64,
+    ending_block: u64,
+) -> Result<EventSample, IndexerError> {
+    let config = config().await;
+    let sample_id = Uuid::new_v4();
+    let script_path = get_script_tmp_directory(sample_id);
+    tokio::fs::write(&script_path, get_sample_script(&request.filter))
+        .await
+        .map_err(IndexerError::FailedToCreateFile)?;
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    samples().lock().await.insert(sample_id, sender);
+
+    let mut args = vec![
+        "run".to_string(),
+        script_path.clone(),
+        "--auth-token".to_string(),
+        get_environment_variable("APIBARA_AUTH_TOKEN"),
+        "--target-url".to_string(),
+        config.internal_url(&format!("/internal/estimate/{}", sample_id)),
+        "--status-server-address".to_string(),
+        "127.0.0.1:0".to_string(),
+        "--starting-block".to_string(),
+        starting_block.to_string(),
+        "--ending-block".to_string(),
+        ending_block.to_string(),
+    ];
+    if let Some(stream_url) = &request.stream_url {
+        args.extend(["--stream-url".to_string(), stream_url.clone()]);
+    }
+
+    let started_at = Instant::now();
+    let mut sample = EventSample { starting_block, ending_block: starting_block, ..Default::default() };
+    let result = async {
+        let mut child = TrackedChild::spawn(
+            Command::new(format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-webhook"))
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .args(args)
+                .kill_on_drop(true),
+        )
+        .map_err(|e| IndexerError::FailedToStartIndexer(e.to_string(), sample_id.to_string()))?;
+
+        let timeout = tokio::time::sleep(tokio::time::Duration::from_secs(ESTIMATE_SAMPLE_TIMEOUT_SECONDS));
+        tokio::pin!(timeout);
+        // the sink exits once it reaches the ending block
+        let complete = loop {
+            tokio::select! {
+                Some(payload) = receiver.recv() => record_payload(&mut sample, &payload),
+                status = child.wait() => break status.is_ok_and(|status| status.success()),
+                _ = &mut timeout => break false,
+            }
+        };
+        Ok::<_, IndexerError>(complete)
+    }
+    .await;
+
+    samples().lock().await.remove(&sample_id);
+    if let Err(e) = tokio::fs::remove_file(&script_path).await {
+        tracing::warn!("Failed to remove the script of estimate sample {}: {:?}", sample_id, e);
+    }
+
+    let complete = result?;
+    while let Ok(payload) = receiver.try_recv() {
+        record_payload(&mut sample, &payload);
+    }
+    if complete {
+        sample.ending_block = ending_block;
+    }
+    sample.duration_ms = started_at.elapsed().as_millis() as u64;
+    Ok(sample)
+}
+
+fn record_payload(sample: &mut EventSample, payload: &Bytes) {
+    match serde_json::from_slice(payload) {
+        Ok(payload) => sample.record(&payload),
+        Err(e) => tracing::warn!("Estimate sample payload is not JSON: {}", e),
+    }
+}
+
+/// Receives the payloads of the sample sinks
+pub async fn receive_estimate_payload(
+    State(_state): State<AppState>,
+    PathExtractor(sample_id): PathExtractor<Uuid>,
+    body: Bytes,
+) -> StatusCode {
+    match samples().lock().await.get(&sample_id).map(|sender| sender.send(body)) {
+        Some(Ok(())) => StatusCode::OK,
+        _ => StatusCode::NOT_FOUND,
+    }
+}
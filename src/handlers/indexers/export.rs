@@ -0,0 +1,301 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{Bytes, StreamBody};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::AsyncPgConnection;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::constants::indexers::{INVENTORY_EXPORT_PAGE_SIZE, INVENTORY_EXPORT_STATUS_TIMEOUT_MILLIS};
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::domain::models::inventory::{InventoryRow, INVENTORY_COLUMNS};
+use crate::handlers::indexers::utils::query_status_server;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::utils::csv::to_csv_line;
+use crate::utils::{AdminGuard, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InventoryExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InventoryExportQuery {
+    #[serde(default)]
+    pub format: InventoryExportFormat,
+}
+
+/// Indexers of the inventory, loaded a page at a time
+struct InventoryPages {
+    pool: Arc<Pool<AsyncPgConnection>>,
+    after: Option<Uuid>,
+    done: bool,
+}
+
+impl InventoryPages {
+    async fn next_page(&mut self) -> Result<Option<Vec<InventoryRow>>, IndexerError> {
+        if self.done {
+            return Ok(None);
+        }
+        let repository = IndexerRepository::new(&self.pool);
+        let indexers = repository
+            .get_page(IndexerFilter { status: None }, self.after, INVENTORY_EXPORT_PAGE_SIZE)
+            .await
+            .map_err(IndexerError::InfraError)?;
+        self.done = (indexers.len() as i64) < INVENTORY_EXPORT_PAGE_SIZE;
+        let Some(last) = indexers.last() else {
+            return Ok(None);
+        };
+        self.after = Some(last.id);
+
+        let mut rows = Vec::with_capacity(indexers.len());
+        for indexer_model in &indexers {
+            rows.push(InventoryRow::new(indexer_model, get_current_block(indexer_model).await));
+        }
+        Ok(Some(rows))
+    }
+}
+
+/// Block reported by the status server of the running indexer, if it answers in time
+async fn get_current_block(indexer_model: &IndexerModel) -> Option<u64> {
+    if indexer_model.status != IndexerStatus::Running {
+        return None;
+    }
+    let port = indexer_model.status_server_port?;
+    let timeout = Duration::from_millis(INVENTORY_EXPORT_STATUS_TIMEOUT_MILLIS);
+    tokio::time::timeout(timeout, query_status_server(port)).await.ok()?.ok()?.current_block
+}
+
+/// The response is already under way when a page fails, the body is cut short so that the
+/// export can't be mistaken for a complete one
+fn export_error(e: IndexerError) -> io::Error {
+    tracing::error!("Failed to export the inventory: {}", e);
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+fn csv_stream(pages: InventoryPages) -> impl Stream<Item = Result<Bytes, io::Error>> {
+    let header = stream::once(async { Ok(Bytes::from(to_csv_line(INVENTORY_COLUMNS))) });
+    let rows = stream::unfold(pages, |mut pages| async move {
+        let chunk = match pages.next_page().await {
+            Ok(Some(rows)) => {
+                Ok(Bytes::from(rows.iter().map(|row| to_csv_line(row.to_csv_fields())).collect::<String>()))
+            }
+            Ok(None) => return None,
+            Err(e) => {
+                pages.done = true;
+                Err(export_error(e))
+            }
+        };
+        Some((chunk, pages))
+    });
+    header.chain(rows)
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use arrow_array::{ArrayRef, Int32Array, Int64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+    use axum::body::Bytes;
+    use futures_util::stream::{self, Stream};
+    use parquet::arrow::ArrowWriter;
+
+    use super::{export_error, InventoryPages};
+    use crate::domain::models::indexer::IndexerError;
+    use crate::domain::models::inventory::{InventoryRow, INVENTORY_COLUMNS};
+
+    /// Buffer the writer writes the row groups to, taken as soon as each one is written
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn take(&self) -> Bytes {
+            Bytes::from(std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner())))
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap_or_else(|e| e.into_inner()).write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Parquet type of the column, whether it's nullable and its values for the rows. The schema
+    /// and the batches are both built from `INVENTORY_COLUMNS` through here so they can't drift
+    /// apart.
+    fn to_column(name: &str, rows: &[InventoryRow]) -> Option<(DataType, bool, ArrayRef)> {
+        let strings = |nullable: bool, field: fn(&InventoryRow) -> Option<String>| -> (DataType, bool, ArrayRef) {
+            (DataType::Utf8, nullable, Arc::new(StringArray::from(rows.iter().map(field).collect::<Vec<_>>())))
+        };
+        let column = match name {
+            "id" => strings(false, |row| Some(row.id.to_string())),
+            "status" => strings(false, |row| Some(row.status.clone())),
+            "indexer_type" => strings(false, |row| Some(row.indexer_type.clone())),
+            "tenant_id" => strings(true, |row| row.tenant_id.clone()),
+            "priority" => (
+                DataType::Int32,
+                false,
+                Arc::new(Int32Array::from(rows.iter().map(|row| row.priority).collect::<Vec<_>>())),
+            ),
+            "target_url" => strings(true, |row| row.target_url.clone()),
+            "table_name" => strings(true, |row| row.table_name.clone()),
+            "stream_url" => strings(true, |row| row.stream_url.clone()),
+            "starting_block" => (
+                DataType::Int64,
+                true,
+                Arc::new(Int64Array::from(rows.iter().map(|row| row.starting_block).collect::<Vec<_>>())),
+            ),
+            "ending_block" => (
+                DataType::Int64,
+                true,
+                Arc::new(Int64Array::from(rows.iter().map(|row| row.ending_block).collect::<Vec<_>>())),
+            ),
+            "current_block" => (
+                DataType::UInt64,
+                true,
+                Arc::new(UInt64Array::from(rows.iter().map(|row| row.current_block).collect::<Vec<_>>())),
+            ),
+            "sink_id" => strings(true, |row| row.sink_id.clone()),
+            "standby_for" => strings(true, |row| row.standby_for.map(|id| id.to_string())),
+            "backfill_for" => strings(true, |row| row.backfill_for.map(|id| id.to_string())),
+            "log_level" => strings(true, |row| row.log_level.clone()),
+            "script_checksum" => strings(true, |row| row.script_checksum.clone()),
+            "script_source_url" => strings(true, |row| row.script_source_url.clone()),
+            "script_params" => strings(false, |row| Some(row.script_params.clone())),
+            "hooks" => strings(false, |row| Some(row.hooks.clone())),
+            "sink_options" => strings(true, |row| row.sink_options.clone()),
+            "process_priority" => strings(false, |row| Some(row.process_priority.clone())),
+            _ => return None,
+        };
+        Some(column)
+    }
+
+    fn schema() -> Result<SchemaRef, ArrowError> {
+        let fields = INVENTORY_COLUMNS
+            .iter()
+            .map(|name| {
+                let (data_type, nullable, _) = to_column(name, &[])
+                    .ok_or_else(|| ArrowError::SchemaError(format!("no parquet type for column {}", name)))?;
+                Ok(Field::new(*name, data_type, nullable))
+            })
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
+    fn to_record_batch(schema: &SchemaRef, rows: &[InventoryRow]) -> Result<RecordBatch, ArrowError> {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                to_column(field.name(), rows)
+                    .map(|(_, _, array)| array)
+                    .ok_or_else(|| ArrowError::SchemaError(format!("no parquet type for column {}", field.name())))
+            })
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+        RecordBatch::try_new(Arc::clone(schema), columns)
+    }
+
+    fn to_io_error(e: impl std::fmt::Display) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, e.to_string())
+    }
+
+    /// Each page is written as a row group and sent right away, the footer is sent last
+    pub(super) fn parquet_stream(
+        pages: InventoryPages,
+    ) -> Result<impl Stream<Item = Result<Bytes, io::Error>>, IndexerError> {
+        let buffer = SharedBuffer::default();
+        let schema = schema().map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?;
+        let writer = ArrowWriter::try_new(buffer.clone(), Arc::clone(&schema), None)
+            .map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?;
+
+        Ok(stream::unfold(Some((pages, writer)), move |state| {
+            let (buffer, schema) = (buffer.clone(), Arc::clone(&schema));
+            async move {
+                let (mut pages, mut writer) = state?;
+                match pages.next_page().await {
+                    Ok(Some(rows)) => {
+                        let written = to_record_batch(&schema, &rows)
+                            .map_err(to_io_error)
+                            .and_then(|batch| writer.write(&batch).and_then(|_| writer.flush()).map_err(to_io_error));
+                        match written {
+                            Ok(()) => Some((Ok(buffer.take()), Some((pages, writer)))),
+                            Err(e) => Some((Err(e), None)),
+                        }
+                    }
+                    Ok(None) => Some((writer.close().map(|_| buffer.take()).map_err(to_io_error), None)),
+                    Err(e) => Some((Err(export_error(e)), None)),
+                }
+            }
+        }))
+    }
+    #[cfg(test)]
+    mod tests {
+        use arrow_array::Array;
+        use uuid::Uuid;
+
+        use super::*;
+
+        #[test]
+        fn test_schema_follows_the_columns() {
+            let schema = schema().unwrap();
+            let names: Vec<&str> = schema.fields().iter().map(|field| field.name().as_str()).collect();
+            assert_eq!(names, INVENTORY_COLUMNS);
+
+            let rows = [
+                InventoryRow { id: Uuid::new_v4(), current_block: Some(150), ..Default::default() },
+                InventoryRow { id: Uuid::new_v4(), tenant_id: Some("tenant".into()), ..Default::default() },
+            ];
+            let batch = to_record_batch(&schema, &rows).unwrap();
+            assert_eq!((batch.num_rows(), batch.num_columns()), (2, INVENTORY_COLUMNS.len()));
+            let tenant_ids = batch.column_by_name("tenant_id").unwrap();
+            assert_eq!(tenant_ids.as_any().downcast_ref::<StringArray>().unwrap().value(1), "tenant");
+            let current_blocks = batch.column_by_name("current_block").unwrap();
+            assert_eq!(current_blocks.as_any().downcast_ref::<UInt64Array>().unwrap().value(0), 150);
+        }
+    }
+}
+
+fn attachment(content_type: &str, filename: &str, body: impl IntoResponse) -> Response {
+    let disposition = format!("attachment; filename=\"{}\"", filename);
+    ([(header::CONTENT_TYPE, content_type.to_string()), (header::CONTENT_DISPOSITION, disposition)], body)
+        .into_response()
+}
+
+/// Streams the whole inventory as CSV or Parquet. Indexers are loaded a page at a time so that
+/// the memory used doesn't grow with the fleet.
+pub async fn export_indexers(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    QueryExtractor(query): QueryExtractor<InventoryExportQuery>,
+) -> Result<Response, IndexerError> {
+    let pages = InventoryPages { pool: Arc::clone(&state.pool), after: None, done: false };
+    match query.format {
+        InventoryExportFormat::Csv => Ok(attachment("text/csv", "inventory.csv", StreamBody::new(csv_stream(pages)))),
+        #[cfg(feature = "parquet")]
+        InventoryExportFormat::Parquet => Ok(attachment(
+            "application/vnd.apache.parquet",
+            "inventory.parquet",
+            StreamBody::new(parquet_export::parquet_stream(pages)?),
+        )),
+        #[cfg(not(feature = "parquet"))]
+        InventoryExportFormat::Parquet => {
+            Err(IndexerError::InvalidExportFormat("the service is built without the parquet feature".into()))
+        }
+    }
+}
@@ -1,21 +1,59 @@
 use uuid::Uuid;
 
 use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::execution::StopExpectation;
 use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::handlers::indexers::standby::failover;
+use crate::handlers::indexers::utils::{lock_indexer, record_event};
+use crate::handlers::notifications::lifecycle::notify_status_change;
 use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository, UpdateIndexerStatusDb};
+use crate::utils::actor_context::spawn_with_context;
 
-pub async fn fail_indexer(id: Uuid) -> Result<(), IndexerError> {
+pub async fn fail_indexer(context: &ActorContext, id: Uuid) -> Result<(), IndexerError> {
+    fail_indexer_expecting(context, id, &StopExpectation::default()).await
+}
+
+/// Fails the indexer unless it doesn't run the expected execution anymore, e.g. the exit of a
+/// process replaced by a restart in the meantime
+pub async fn fail_indexer_expecting(
+    context: &ActorContext,
+    id: Uuid,
+    expected: &StopExpectation,
+) -> Result<(), IndexerError> {
+    let lock = lock_indexer(id).await;
     let config = config().await;
     let mut repository = IndexerRepository::new(config.pool());
     let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if let Some(mismatch) = expected.mismatch(&indexer_model) {
+        tracing::info!("Not failing indexer {}: {}", id, mismatch);
+        return Ok(());
+    }
     match indexer_model.status {
-        IndexerStatus::Running => (),
+        IndexerStatus::Starting | IndexerStatus::Running => (),
         _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
     }
-    repository
+    let updated_indexer = repository
         .update_status(UpdateIndexerStatusDb { id, status: IndexerStatus::FailedRunning.to_string() })
         .await
         .map_err(IndexerError::InfraError)?;
 
+    record_event(
+        context,
+        AuditAction::StatusChange,
+        Some(indexer_model.status),
+        Some(IndexerStatus::FailedRunning),
+        &updated_indexer,
+    )
+    .await;
+
+    spawn_with_context(context, notify_status_change(context.clone(), id, IndexerStatus::FailedRunning));
+    drop(lock);
+
+    if let Err(e) = failover(context, id).await {
+        tracing::error!("Failed to fail over indexer {}: {:?}", id, e);
+    }
+
     Ok(())
 }
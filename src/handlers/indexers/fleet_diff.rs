@@ -0,0 +1,34 @@
+use axum::extract::State;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::domain::models::fleet_diff::{diff_fleet, FleetDiffModel};
+use crate::domain::models::gitops::GitOpsManifest;
+use crate::domain::models::indexer::IndexerError;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::utils::{JsonExtractor, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct FleetDiffQuery {
+    /// Only the indexers of the tenant are compared if set
+    pub tenant_id: Option<String>,
+}
+
+/// Returns the changes the GitOps sync would make for the fleet to match the manifest, without
+/// changing anything
+pub async fn diff_indexers(
+    State(state): State<AppState>,
+    QueryExtractor(query): QueryExtractor<FleetDiffQuery>,
+    JsonExtractor(manifest): JsonExtractor<GitOpsManifest>,
+) -> Result<Json<FleetDiffModel>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let indexers: Vec<_> = repository
+        .get_all(IndexerFilter { status: None })
+        .await
+        .map_err(IndexerError::InfraError)?
+        .into_iter()
+        .filter(|indexer_model| query.tenant_id.is_none() || indexer_model.tenant_id == query.tenant_id)
+        .collect();
+    Ok(Json(diff_fleet(&manifest, &indexers)))
+}
@@ -0,0 +1,170 @@
+use axum::extract::State;
+use axum::Json;
+use object_store::path::Path;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::MAX_BACKFILL_INDEXERS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::delivery::{find_gaps, BlockRange, IndexerGapsModel};
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus, IndexerType};
+use crate::handlers::indexers::clone_indexer::{insert_clone, CloneIndexerRequest};
+use crate::handlers::indexers::indexer_types::DEFAULT_STARTING_BLOCK;
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::utils::{get_s3_script_key, lock_indexer, query_status_server};
+use crate::infra::repositories::delivery_repository::{DeliveryRepository, NewDeliveredRangeDb};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Records blocks delivered to the target of a webhook indexer. This is reported by the
+/// receiver of the payloads on behalf of the owner of the indexer, deliveries of backfill
+/// indexers count for the indexer they fill. Reporting a range again has no effect.
+pub async fn record_delivered_range(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(range): JsonExtractor<BlockRange>,
+) -> Result<Json<BlockRange>, IndexerError> {
+    if range.start_block > range.end_block {
+        return Err(IndexerError::InvalidBlockRange(format!(
+            "start block {} is after end block {}",
+            range.start_block, range.end_block
+        )));
+    }
+    let indexer_model = get_owned_indexer(&state, &context, id).await?;
+    if indexer_model.indexer_type != IndexerType::Webhook {
+        return Err(IndexerError::InvalidBlockRange("only webhook indexers record deliveries".into()));
+    }
+
+    let mut delivery_repository = DeliveryRepository::new(&state.pool);
+    let range = delivery_repository
+        .insert(NewDeliveredRangeDb {
+            id: Uuid::new_v4(),
+            indexer_id: id,
+            start_block: range.start_block,
+            end_block: range.end_block,
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(Json(range))
+}
+
+/// Returns the ranges which weren't delivered between the starting block of the indexer and
+/// the block it processed last
+pub async fn get_indexer_gaps(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<IndexerGapsModel>, IndexerError> {
+    let indexer_model = get_owned_indexer(&state, &context, id).await?;
+
+    Ok(Json(get_gaps(&state, &indexer_model).await?))
+}
+
+/// Launches an indexer for each gap, they stop once the end of their gap is reached. Gaps which
+/// already have a backfill reuse it, so a backfill can be retried. The backfills are all created
+/// or none is, they are started once they all exist.
+pub async fn backfill_indexer_gaps(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<Vec<IndexerModel>>, IndexerError> {
+    // concurrent backfills of the same indexer would both create the missing backfills
+    let _lock = lock_indexer(id).await;
+    let indexer_model = get_owned_indexer(&state, &context, id).await?;
+    let gaps = get_gaps(&state, &indexer_model).await?.gaps;
+    let mut repository = IndexerRepository::new(&state.pool);
+    let existing = repository.get_backfills(id).await.map_err(IndexerError::InfraError)?;
+
+    let sink_id = indexer_model.indexer_id.clone().unwrap_or_else(|| id.to_string());
+    let mut backfills = vec![];
+    let mut created = vec![];
+    for gap in gaps.into_iter().take(MAX_BACKFILL_INDEXERS) {
+        // a sink id of its own so it doesn't resume from the cursor of the indexer
+        let backfill_id = format!("{}-backfill-{}-{}", sink_id, gap.start_block, gap.end_block);
+        if let Some(backfill) = existing.iter().find(|backfill| backfill.indexer_id.as_ref() == Some(&backfill_id)) {
+            backfills.push(backfill.clone());
+            continue;
+        }
+        let request = CloneIndexerRequest {
+            starting_block: Some(gap.start_block),
+            ending_block: Some(gap.end_block),
+            indexer_id: Some(backfill_id),
+            ..Default::default()
+        };
+        match insert_clone(&context, indexer_model.clone(), request, Some(id)).await {
+            Ok(backfill) => {
+                created.push(backfill.id);
+                backfills.push(backfill);
+            }
+            Err(e) => {
+                delete_backfills(&mut repository, &created).await;
+                return Err(e);
+            }
+        }
+    }
+
+    let mut started = vec![];
+    for backfill in backfills {
+        if !backfill.status.is_live() {
+            start_indexer(&context, backfill.id).await?;
+        }
+        started.push(repository.get(backfill.id).await.map_err(IndexerError::InfraError)?);
+    }
+
+    Ok(Json(started))
+}
+
+async fn get_owned_indexer(state: &AppState, context: &ActorContext, id: Uuid) -> Result<IndexerModel, IndexerError> {
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    if !context.can_act_for_owner(indexer_model.tenant_id.as_deref()) {
+        return Err(IndexerError::IndexerAccessDenied(id));
+    }
+    Ok(indexer_model)
+}
+
+/// Undoes the backfills created before one of them failed, failures are only logged
+async fn delete_backfills(repository: &mut IndexerRepository<'_>, ids: &[Uuid]) {
+    let config = config().await;
+    for id in ids {
+        if let Err(e) = repository.delete(*id).await {
+            tracing::error!("Failed to delete backfill indexer {}: {:?}", id, e);
+            continue;
+        }
+        if let Err(e) = config.object_store().delete(&Path::from(get_s3_script_key(*id))).await {
+            tracing::error!("Failed to delete the script of backfill indexer {}: {:?}", id, e);
+        }
+    }
+}
+
+async fn get_gaps(state: &AppState, indexer_model: &IndexerModel) -> Result<IndexerGapsModel, IndexerError> {
+    if indexer_model.indexer_type != IndexerType::Webhook {
+        return Err(IndexerError::InvalidBlockRange("only webhook indexers record deliveries".into()));
+    }
+    let delivery_repository = DeliveryRepository::new(&state.pool);
+    let delivered =
+        delivery_repository.get_delivered_ranges(indexer_model.id).await.map_err(IndexerError::InfraError)?;
+
+    let head_block = match (indexer_model.status, indexer_model.status_server_port) {
+        (IndexerStatus::Running, Some(status_server_port)) => match query_status_server(status_server_port).await {
+            Ok(status) => status.current_block.map(|block| block as i64),
+            Err(e) => {
+                tracing::warn!("Failed to get the current block of indexer {}: {:?}", indexer_model.id, e);
+                None
+            }
+        },
+        _ => None,
+    }
+    // the last delivery is the best we know when the sink can't be queried
+    .or_else(|| delivered.iter().map(|range| range.end_block).max());
+
+    let start_block = indexer_model.starting_block.unwrap_or(DEFAULT_STARTING_BLOCK);
+    let gaps = match head_block {
+        Some(head_block) => find_gaps(start_block, head_block, &delivered),
+        None => vec![],
+    };
+
+    Ok(IndexerGapsModel { indexer_id: indexer_model.id, start_block, head_block, gaps })
+}
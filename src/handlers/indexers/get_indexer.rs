@@ -1,34 +1,151 @@
-use axum::extract::State;
+use axum::extract::{RawQuery, State};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use uuid::Uuid;
 
-use super::utils::query_status_server;
-use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerServerStatus};
-use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
-use crate::utils::PathExtractor;
+use super::utils::{get_resolved_script, query_status_server};
+use crate::domain::models::indexer::{IndexerConfig, IndexerError, IndexerServerStatus, IndexerStateModel};
+use crate::domain::models::indexer_view::{FieldSelection, IndexerListQuery, IndexerView, ListOrder};
+use crate::domain::models::launch_command::LaunchCommand;
+use crate::handlers::indexers::indexer_types::{get_indexer_handler, get_launch_command};
+use crate::infra::repositories::audit_repository::AuditRepository;
+use crate::infra::repositories::indexer_repository::{IndexerListing, IndexerRepository, Repository};
+use crate::utils::etag::{entity_tag, not_modified, with_etag, IfNoneMatch};
+use crate::utils::negotiation::{Negotiated, ResponseFormat};
+use crate::utils::{PathExtractor, QueryExtractor};
 use crate::AppState;
 
-pub async fn get_indexers(State(state): State<AppState>) -> Result<Json<Vec<IndexerModel>>, IndexerError> {
+#[derive(Debug, Deserialize)]
+pub struct IndexerStateQuery {
+    pub at: DateTime<Utc>,
+}
+
+/// Responses of the list and status endpoints are MessagePack if the `Accept` header asks for it.
+/// Full indexers are listed by default, `view=summary` leaves out the script and config blobs
+/// and `fields=` only returns the given fields. The blobs are only loaded when requested.
+/// Indexers are listed by creation time, filtered by `status` and `indexer_type` and paginated
+/// with `limit` and `offset`.
+///
+/// The listing is tagged with the version of the indexers, it isn't loaded again for a client
+/// sending the tag back with `If-None-Match` while nothing changed.
+pub async fn get_indexers(
+    State(state): State<AppState>,
+    format: ResponseFormat,
+    if_none_match: IfNoneMatch,
+    RawQuery(raw_query): RawQuery,
+    QueryExtractor(query): QueryExtractor<IndexerListQuery>,
+) -> Result<Response, IndexerError> {
     let repository = IndexerRepository::new(&state.pool);
-    let indexers = repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?;
+    // read before the listing, a change in between gets the next request a fresh listing
+    let version = repository.get_version().await.map_err(IndexerError::InfraError)?;
+    let etag = entity_tag(&[&version.to_string(), &format!("{:?}", format), raw_query.as_deref().unwrap_or_default()]);
+    if if_none_match.matches(&etag) {
+        return Ok(not_modified(&etag));
+    }
+
+    Ok(with_etag(list_indexers(&repository, format, query).await?, &etag))
+}
+
+async fn list_indexers(
+    repository: &IndexerRepository<'_>,
+    format: ResponseFormat,
+    query: IndexerListQuery,
+) -> Result<Response, IndexerError> {
+    let listing = IndexerListing {
+        status: query.status.map(|status| status.to_string()),
+        type_: query.indexer_type.map(|indexer_type| indexer_type.to_string()),
+        descending: query.order == ListOrder::Desc,
+        limit: query.limit.map(i64::from),
+        offset: query.offset.map(i64::from).unwrap_or_default(),
+    };
+    let Some(fields) = query.fields else {
+        return match query.view {
+            IndexerView::Full => {
+                let indexers = repository.get_listing(listing).await.map_err(IndexerError::InfraError)?;
+                Ok(Negotiated(format, indexers).into_response())
+            }
+            IndexerView::Summary => {
+                let summaries = repository.get_summary_listing(listing).await.map_err(IndexerError::InfraError)?;
+                Ok(Negotiated(format, summaries).into_response())
+            }
+        };
+    };
 
-    Ok(Json(indexers))
+    let selection = FieldSelection::parse(&fields)?;
+    let indexers = if selection.is_summary() {
+        let summaries = repository.get_summary_listing(listing).await.map_err(IndexerError::InfraError)?;
+        summaries.iter().map(|summary| selection.select(summary)).collect::<Result<Vec<_>, _>>()?
+    } else {
+        let indexers = repository.get_listing(listing).await.map_err(IndexerError::InfraError)?;
+        indexers.iter().map(|indexer_model| selection.select(indexer_model)).collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(Negotiated(format, indexers).into_response())
 }
 
+/// Tagged with the last change of the indexer, see `get_indexers`
 pub async fn get_indexer(
     State(state): State<AppState>,
+    if_none_match: IfNoneMatch,
     PathExtractor(id): PathExtractor<Uuid>,
-) -> Result<Json<IndexerModel>, IndexerError> {
+) -> Result<Response, IndexerError> {
     let repository = IndexerRepository::new(&state.pool);
+    let updated_at = repository.get_updated_at(id).await.map_err(IndexerError::InfraError)?;
+    let etag = get_indexer_etag(id, updated_at);
+    if if_none_match.matches(&etag) {
+        return Ok(not_modified(&etag));
+    }
+
     let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let etag = get_indexer_etag(id, indexer_model.updated_at);
+    Ok(with_etag(Json(indexer_model).into_response(), &etag))
+}
+
+fn get_indexer_etag(id: Uuid, updated_at: DateTime<Utc>) -> String {
+    entity_tag(&[&id.to_string(), &updated_at.timestamp_micros().to_string()])
+}
+
+/// Reconstructs the status and config of an indexer at a point in time from its audit logs
+pub async fn get_indexer_state(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<IndexerStateQuery>,
+) -> Result<Json<IndexerStateModel>, IndexerError> {
+    // make sure the indexer exists
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let audit_repository = AuditRepository::new(&state.pool);
+    let status_change = audit_repository
+        .get_last_status_change(id, query.at)
+        .await
+        .map_err(IndexerError::InfraError)?
+        .ok_or(IndexerError::StateNotFound(id, query.at))?;
+    let config_event = audit_repository.get_last_config(id, query.at).await.map_err(IndexerError::InfraError)?;
 
-    Ok(Json(indexer_model))
+    let (config, config_recorded_at) = match config_event {
+        Some(event) => (
+            event.details.and_then(|details| serde_json::from_value::<IndexerConfig>(details).ok()),
+            Some(event.created_at),
+        ),
+        None => (None, None),
+    };
+
+    Ok(Json(IndexerStateModel {
+        indexer_id: id,
+        at: query.at,
+        status: status_change.to_status.expect("status changes always have a target status"),
+        status_changed_at: status_change.created_at,
+        config,
+        config_recorded_at,
+    }))
 }
 
 pub async fn get_indexer_status(
     State(state): State<AppState>,
+    format: ResponseFormat,
     PathExtractor(id): PathExtractor<Uuid>,
-) -> Result<Json<IndexerServerStatus>, IndexerError> {
+) -> Result<Negotiated<IndexerServerStatus>, IndexerError> {
     let repository = IndexerRepository::new(&state.pool);
     let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
 
@@ -36,13 +153,14 @@ pub async fn get_indexer_status(
 
     let status_response = query_status_server(server_port).await?;
 
-    Ok(Json(status_response))
+    Ok(Negotiated(format, status_response))
 }
 
 pub async fn get_indexer_status_by_table_name(
     State(state): State<AppState>,
+    format: ResponseFormat,
     PathExtractor(table_name): PathExtractor<String>,
-) -> Result<Json<IndexerServerStatus>, IndexerError> {
+) -> Result<Negotiated<IndexerServerStatus>, IndexerError> {
     let repository = IndexerRepository::new(&state.pool);
     let indexer_model = repository.get_by_table_name(table_name).await.map_err(IndexerError::InfraError)?;
 
@@ -50,5 +168,22 @@ pub async fn get_indexer_status_by_table_name(
 
     let status_response = query_status_server(server_port).await?;
 
-    Ok(Json(status_response))
+    Ok(Negotiated(format, status_response))
+}
+
+/// Command line, environment and working directory the sink of the indexer is launched with,
+/// built the same way as when it starts. Running indexers were launched with it unless their
+/// config drifted. Secrets are redacted.
+pub async fn get_indexer_launch_command(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<LaunchCommand>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    let indexer = get_indexer_handler(&indexer_model.indexer_type);
+    let script = get_resolved_script(&indexer_model).await?;
+    let launch_args = indexer.launch_args(&indexer_model, &script).await?;
+
+    let command = get_launch_command(indexer.binary(), &indexer_model, None, &launch_args);
+    Ok(Json(command.redacted()))
 }
@@ -0,0 +1,389 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use axum::body::Bytes;
+use chrono::Utc;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::AsyncConnection;
+use object_store::path::Path;
+use tokio::sync::Notify;
+use url::Url;
+use uuid::Uuid;
+
+use crate::config::{config, GitOpsConfig};
+use crate::constants::indexers::ROLLING_RESTART_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::gitops::{
+    is_gitops_managed, plan_sync, GitOpsAction, GitOpsChange, GitOpsField, GitOpsIndexerSpec, GitOpsManifest,
+    GitOpsSyncStatus, GITOPS_MANAGER,
+};
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::handlers::indexers::approvals::ensure_target_approved;
+use crate::handlers::indexers::create_indexer::create_indexer_from_fields;
+use crate::handlers::indexers::request_fields::CreateIndexerFields;
+use crate::handlers::indexers::script_scan::scan_script;
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::stop_indexer::stop_indexer_with_reason;
+use crate::handlers::indexers::update_indexer::restart_indexer;
+use crate::handlers::indexers::utils::{
+    get_s3_script_key, get_script, lock_indexer, record_event_with_reason, resolve_script,
+};
+use crate::infra::db::pool::get_connection;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::contract_repository::{replace_with_connection, NewIndexerContractDb};
+use crate::infra::repositories::indexer_repository::{
+    update_settings_with_connection, IndexerFilter, IndexerRepository, Repository, UpdateIndexerManagementDb,
+    UpdateIndexerSettingsDb,
+};
+use crate::utils::script_cache::get_script_checksum;
+use crate::utils::script_fetch::{fetch_script, verify_script_checksum};
+use crate::utils::script_filter::extract_contract_filters;
+
+static SYNC_STATUS: OnceLock<Mutex<GitOpsSyncStatus>> = OnceLock::new();
+static SYNC_REQUESTED: OnceLock<Notify> = OnceLock::new();
+
+fn sync_status() -> std::sync::MutexGuard<'static, GitOpsSyncStatus> {
+    SYNC_STATUS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn sync_requested() -> &'static Notify {
+    SYNC_REQUESTED.get_or_init(Notify::new)
+}
+
+pub async fn get_sync_status() -> GitOpsSyncStatus {
+    let gitops = config().await.gitops().cloned();
+    GitOpsSyncStatus {
+        enabled: gitops.is_some(),
+        repository_url: gitops.map(|gitops| gitops.repository_url),
+        ..sync_status().clone()
+    }
+}
+
+/// Wakes the sync up before its next poll, e.g. when the repository was pushed to
+pub fn request_sync() {
+    sync_requested().notify_one();
+}
+
+/// Converges the indexers created from the GitOps repository to its manifest, polling the
+/// repository unless a sync is requested in the meantime. Only the indexers marked as created by
+/// the sync are managed, the others are never touched.
+pub async fn monitor_gitops() {
+    let Some(gitops) = config().await.gitops().cloned() else {
+        return;
+    };
+    let context = ActorContext::system();
+    loop {
+        let now = Utc::now();
+        match sync(&context, &gitops).await {
+            Ok((manifest_checksum, changes)) => {
+                let mut status = sync_status();
+                status.last_sync_at = Some(now);
+                status.last_success_at = Some(now);
+                status.last_error = None;
+                status.manifest_checksum = Some(manifest_checksum);
+                status.changes = changes;
+            }
+            Err(e) => {
+                tracing::error!("Failed to sync the indexers from {}: {:?}", gitops.repository_url, e);
+                let mut status = sync_status();
+                status.last_sync_at = Some(now);
+                status.last_error = Some(e.to_string());
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(gitops.poll_interval) => (),
+            _ = sync_requested().notified() => (),
+        }
+    }
+}
+
+/// Returns the checksum of the manifest and the changes applied. Nothing is changed unless the
+/// manifest and all the scripts could be read, so that a broken commit doesn't archive the fleet.
+async fn sync(context: &ActorContext, gitops: &GitOpsConfig) -> Result<(String, Vec<GitOpsChange>), IndexerError> {
+    let manifest_url = get_repository_file_url(gitops, &gitops.manifest_path)?;
+    let manifest_bytes = fetch_script(&manifest_url).await?;
+    let manifest: GitOpsManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| IndexerError::InvalidGitOpsManifest(format!("{}: {}", manifest_url, e)))?;
+    let mut names = HashSet::new();
+    if let Some(spec) = manifest.indexers.iter().find(|spec| !names.insert(spec.name.as_str())) {
+        return Err(IndexerError::InvalidGitOpsManifest(format!("indexer {} is declared twice", spec.name)));
+    }
+
+    let mut scripts: HashMap<String, (String, Bytes)> = HashMap::new();
+    for spec in &manifest.indexers {
+        let script_url = get_repository_file_url(gitops, &spec.script)?;
+        let script = fetch_script(&script_url).await?;
+        verify_script_checksum(&script, spec.script_sha256.as_deref())?;
+        scripts.insert(spec.name.clone(), (script_url, script));
+    }
+    let script_checksums: HashMap<String, String> =
+        scripts.iter().map(|(name, (_, script))| (name.clone(), get_script_checksum(script))).collect();
+
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let mut managed = vec![];
+    for indexer_model in repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)? {
+        if is_gitops_managed(&indexer_model) {
+            managed.push(indexer_model);
+        } else if is_unmarked_gitops_indexer(gitops, &manifest, &indexer_model) {
+            let indexer_model = repository
+                .update_management(UpdateIndexerManagementDb {
+                    id: indexer_model.id,
+                    managed_by: Some(GITOPS_MANAGER.to_string()),
+                    archived_at: None,
+                })
+                .await
+                .map_err(IndexerError::InfraError)?;
+            managed.push(indexer_model);
+        }
+    }
+
+    let mut changes = plan_sync(&manifest, &managed, &script_checksums);
+    for change in changes.iter_mut() {
+        let spec = manifest.indexers.iter().find(|spec| spec.name == change.name);
+        let result = match (change.action, spec, change.indexer_id) {
+            (GitOpsAction::Create, Some(spec), _) => create(context, spec, &scripts[&spec.name].0).await,
+            (GitOpsAction::Update, Some(spec), Some(id)) => {
+                update(context, spec, id, &change.fields, &scripts[&spec.name].1).await
+            }
+            (GitOpsAction::Archive, _, Some(id)) => archive(context, id).await,
+            (GitOpsAction::Restore, Some(spec), Some(id)) => {
+                restore(context, spec, id, &change.fields, &scripts[&spec.name].1).await
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to {} indexer {} from the GitOps repository: {:?}", change.action, change.name, e);
+            change.error = Some(e.to_string());
+        }
+    }
+    Ok((get_script_checksum(&manifest_bytes), changes))
+}
+
+/// Indexers created by the sync before it marked them, or which it failed to mark after creating
+/// them. They're only taken over once the operator opted in, and then only the indexers the
+/// manifest declares with a script of the repository, the other indexers are never changed by
+/// the sync.
+fn is_unmarked_gitops_indexer(gitops: &GitOpsConfig, manifest: &GitOpsManifest, indexer_model: &IndexerModel) -> bool {
+    gitops.adopt_unmarked
+        && indexer_model.managed_by.is_none()
+        && indexer_model.standby_for.is_none()
+        && indexer_model.backfill_for.is_none()
+        && indexer_model.script_source_url.as_ref().is_some_and(|url| url.starts_with(&gitops.repository_url))
+        && indexer_model.indexer_id.as_ref().is_some_and(|name| manifest.indexers.iter().any(|spec| &spec.name == name))
+}
+
+fn get_repository_file_url(gitops: &GitOpsConfig, path: &str) -> Result<String, IndexerError> {
+    let repository_url = Url::parse(&gitops.repository_url)
+        .map_err(|e| IndexerError::InvalidScriptUrl(format!("{}: {}", gitops.repository_url, e)))?;
+    let url = repository_url
+        .join(path.trim_start_matches('/'))
+        .map_err(|e| IndexerError::InvalidScriptUrl(format!("{}: {}", path, e)))?;
+    // paths like `../` would leave the repository
+    if !url.as_str().starts_with(&gitops.repository_url) {
+        return Err(IndexerError::InvalidScriptUrl(format!("{}: outside of the repository", path)));
+    }
+    Ok(url.to_string())
+}
+
+async fn create(context: &ActorContext, spec: &GitOpsIndexerSpec, script_url: &str) -> Result<(), IndexerError> {
+    let config = config().await;
+    let fields = CreateIndexerFields::from_json(spec.to_create_fields(script_url), true)?;
+    let indexer_model = create_indexer_from_fields(context, config.pool(), fields).await?;
+    IndexerRepository::new(config.pool())
+        .update_management(UpdateIndexerManagementDb {
+            id: indexer_model.id,
+            managed_by: Some(GITOPS_MANAGER.to_string()),
+            archived_at: None,
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    Ok(())
+}
+
+/// Stops the indexer and marks it as archived so that it's started again if it's declared again
+async fn archive(context: &ActorContext, id: Uuid) -> Result<(), IndexerError> {
+    stop_indexer_with_reason(context, id, Some("removed from the GitOps repository".into())).await?;
+    IndexerRepository::new(config().await.pool())
+        .update_management(UpdateIndexerManagementDb {
+            id,
+            managed_by: Some(GITOPS_MANAGER.to_string()),
+            archived_at: Some(Utc::now()),
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    Ok(())
+}
+
+/// Applies the changed settings of an archived indexer and starts it again
+async fn restore(
+    context: &ActorContext,
+    spec: &GitOpsIndexerSpec,
+    id: Uuid,
+    fields: &[GitOpsField],
+    script: &Bytes,
+) -> Result<(), IndexerError> {
+    if !fields.is_empty() {
+        update(context, spec, id, fields, script).await?;
+    }
+    IndexerRepository::new(config().await.pool())
+        .update_management(UpdateIndexerManagementDb {
+            id,
+            managed_by: Some(GITOPS_MANAGER.to_string()),
+            archived_at: None,
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    start_indexer(context, id).await
+}
+
+/// Applies the changed settings and restarts the indexer if it runs, one indexer at a time. Every
+/// setting is checked before anything is written, a rejected change leaves the indexer as is.
+async fn update(
+    context: &ActorContext,
+    spec: &GitOpsIndexerSpec,
+    id: Uuid,
+    fields: &[GitOpsField],
+    script: &Bytes,
+) -> Result<(), IndexerError> {
+    let config = config().await;
+    let lock = lock_indexer(id).await;
+    let indexer_model = IndexerRepository::new(config.pool()).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let mut settings = UpdateIndexerSettingsDb::default();
+    for field in fields {
+        match field {
+            GitOpsField::Script => {
+                scan_script(config.pool(), &String::from_utf8_lossy(script)).await?;
+                settings.script_checksum = Some(get_script_checksum(script));
+            }
+            GitOpsField::TargetUrl => {
+                let target_url = spec.target_url.clone().unwrap_or_default();
+                config.target_policy().validate(&target_url).await?;
+                ensure_target_approved(config.pool(), indexer_model.tenant_id.as_deref(), &target_url).await?;
+                settings.target_url = Some(target_url);
+            }
+            GitOpsField::StreamUrl => {
+                if let Some(stream_url) = &spec.stream_url {
+                    config.stream_policy().validate(stream_url)?;
+                }
+                settings.stream_url = Some(spec.stream_url.clone().unwrap_or_default());
+            }
+            GitOpsField::LogLevel => {
+                settings.log_level = Some(spec.log_level.map(|log_level| log_level.to_string()));
+            }
+            GitOpsField::ScriptParams => {
+                settings.script_params = serde_json::to_value(&spec.script_params).ok();
+            }
+            GitOpsField::Priority => settings.priority = Some(spec.priority),
+        }
+    }
+    // the contracts are registered from the script as it will run, so it must resolve with its params
+    let script_changed = fields.contains(&GitOpsField::Script);
+    let contracts = match script_changed || fields.contains(&GitOpsField::ScriptParams) {
+        true => {
+            let script = match script_changed {
+                true => script.clone(),
+                false => get_script(&indexer_model).await?,
+            };
+            let script_params = match fields.contains(&GitOpsField::ScriptParams) {
+                true => &spec.script_params,
+                false => &indexer_model.script_params,
+            };
+            let script = resolve_script(&script, script_params)?;
+            Some(NewIndexerContractDb::from_filters(id, extract_contract_filters(&String::from_utf8_lossy(&script))))
+        }
+        false => None,
+    };
+
+    // the script can't be part of the transaction, if the settings fail to be written the next
+    // sync uploads it again
+    if script_changed {
+        config
+            .object_store()
+            .put(&Path::from(get_s3_script_key(id)), script.clone().into())
+            .await
+            .map_err(IndexerError::FailedToUploadToStore)?;
+    }
+    let mut conn = get_connection(config.pool()).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+    let indexer_model = conn
+        .transaction::<_, InfraError, _>(|conn| {
+            async move {
+                let indexer_model = update_settings_with_connection(conn, id, settings).await?;
+                if let Some(contracts) = contracts {
+                    replace_with_connection(conn, id, contracts).await?;
+                }
+                Ok(indexer_model)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    let changed: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+    let reason = format!("{} synced from the GitOps repository", changed.join(", "));
+    record_event_with_reason(context, AuditAction::ConfigChange, None, None, &indexer_model, Some(reason)).await;
+    // the restart takes the lock again
+    drop(lock);
+
+    if indexer_model.status.is_live() {
+        restart_indexer(context, id).await?;
+        tokio::time::sleep(Duration::from_secs(ROLLING_RESTART_INTERVAL_SECONDS)).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("indexers.json", Ok("https://raw.githubusercontent.com/org/repo/main/indexers.json"))]
+    #[case("/scripts/transfers.js", Ok("https://raw.githubusercontent.com/org/repo/main/scripts/transfers.js"))]
+    #[case("../other/transfers.js", Err("invalid script url ../other/transfers.js: outside of the repository"))]
+    fn test_get_repository_file_url(#[case] path: &str, #[case] expected: Result<&str, &str>) {
+        let gitops = GitOpsConfig {
+            repository_url: "https://raw.githubusercontent.com/org/repo/main/".into(),
+            manifest_path: "indexers.json".into(),
+            poll_interval: Duration::from_secs(300),
+            webhook_secret: None,
+            adopt_unmarked: false,
+        };
+        let result = get_repository_file_url(&gitops, path).map_err(|e| e.to_string());
+        assert_eq!(result, expected.map(String::from).map_err(String::from));
+    }
+
+    #[test]
+    fn test_is_unmarked_gitops_indexer() {
+        let gitops = GitOpsConfig {
+            repository_url: "https://raw.githubusercontent.com/org/repo/main/".into(),
+            manifest_path: "indexers.json".into(),
+            poll_interval: Duration::from_secs(300),
+            webhook_secret: None,
+            adopt_unmarked: true,
+        };
+        let manifest =
+            GitOpsManifest { indexers: vec![GitOpsIndexerSpec { name: "transfers".into(), ..Default::default() }] };
+        let indexer_model = IndexerModel {
+            indexer_id: Some("transfers".into()),
+            script_source_url: Some("https://raw.githubusercontent.com/org/repo/main/transfers.js".into()),
+            ..Default::default()
+        };
+        assert!(is_unmarked_gitops_indexer(&gitops, &manifest, &indexer_model));
+
+        // indexers using a script of the repository aren't taken over unless they're declared
+        let undeclared = IndexerModel { indexer_id: Some("other".into()), ..indexer_model.clone() };
+        assert!(!is_unmarked_gitops_indexer(&gitops, &manifest, &undeclared));
+        let uploaded = IndexerModel { script_source_url: None, ..indexer_model.clone() };
+        assert!(!is_unmarked_gitops_indexer(&gitops, &manifest, &uploaded));
+        let managed = IndexerModel { managed_by: Some(GITOPS_MANAGER.into()), ..indexer_model.clone() };
+        assert!(!is_unmarked_gitops_indexer(&gitops, &manifest, &managed));
+
+        // nothing is taken over unless the operator opted in
+        let gitops = GitOpsConfig { adopt_unmarked: false, ..gitops };
+        assert!(!is_unmarked_gitops_indexer(&gitops, &manifest, &indexer_model));
+    }
+}
@@ -0,0 +1,166 @@
+use std::process::Stdio;
+use std::time::Instant;
+
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::event_schema::EVENT_SCHEMA_VERSION;
+use crate::domain::models::hook::{
+    HookAction, HookEvent, HookFailurePolicy, HookResult, HookStage, IndexerHooks, LifecycleHook,
+};
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::handlers::indexers::reaper::TrackedChild;
+use crate::infra::repositories::audit_repository::{AuditRepository, NewAuditLogDb};
+use crate::utils::actor_context::CORRELATION_ID_HEADER;
+use crate::utils::http::http_client;
+use crate::utils::signing::{sign_payload, SIGNATURE_HEADER};
+
+/// HTTP hooks go through the same policy as the targets, commands must be allowlisted
+pub async fn validate_hooks(hooks: &IndexerHooks) -> Result<(), IndexerError> {
+    let config = config().await;
+    for hook in [&hooks.pre_start, &hooks.post_stop].into_iter().flatten() {
+        hook.validate_timeout().map_err(IndexerError::InvalidHooks)?;
+        match &hook.action {
+            HookAction::Http { url } => config.target_policy().validate(url).await?,
+            HookAction::Command { command, args } => {
+                if !config.hook_allowed_commands().contains(command) {
+                    return Err(IndexerError::HookCommandNotAllowed(command.clone()));
+                }
+                HookAction::validate_args(args)
+                    .map_err(|e| IndexerError::InvalidHooks(format!("args of {}: {}", command, e)))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs the hook of the indexer for the stage if it has one and records its result. Fails only
+/// if a pre-start hook failed and its policy is to abort, post-stop hooks run once the indexer is
+/// already stopped so their failures are only logged and audited.
+pub async fn run_hook(
+    context: &ActorContext,
+    indexer_model: &IndexerModel,
+    stage: HookStage,
+) -> Result<(), IndexerError> {
+    let Some(hook) = indexer_model.hooks.get(stage) else {
+        return Ok(());
+    };
+
+    let started_at = Instant::now();
+    let timeout = hook.timeout();
+    let outcome = match tokio::time::timeout(timeout, execute_hook(context, indexer_model.id, stage, hook)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    };
+    let result = HookResult {
+        stage,
+        success: outcome.is_ok(),
+        duration_ms: started_at.elapsed().as_millis() as u64,
+        output: match &outcome {
+            Ok(output) => output.clone(),
+            Err(e) => Some(e.clone()),
+        },
+    };
+    record_hook_result(context, indexer_model, hook, &result).await;
+
+    match outcome {
+        Err(e) if stage == HookStage::PreStart && hook.failure_policy == HookFailurePolicy::Abort => {
+            Err(IndexerError::HookFailed(stage, e))
+        }
+        Err(e) => {
+            tracing::warn!("{} hook of indexer {} failed, continuing: {}", stage, indexer_model.id, e);
+            Ok(())
+        }
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Returns what the hook reported on success
+async fn execute_hook(
+    context: &ActorContext,
+    indexer_id: Uuid,
+    stage: HookStage,
+    hook: &LifecycleHook,
+) -> Result<Option<String>, String> {
+    match &hook.action {
+        HookAction::Http { url } => {
+            let config = config().await;
+            let payload =
+                serde_json::to_vec(&HookEvent { schema_version: EVENT_SCHEMA_VERSION.into(), indexer_id, stage })
+                    .map_err(|e| e.to_string())?;
+            // the url was checked against the target policy when the hook was saved, a redirect
+            // would get around it
+            let mut request =
+                http_client().without_redirects().post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+            if let Some(signature) = sign_payload(config.signing_keys(), &payload, chrono::Utc::now()) {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            if let Some(correlation_id) = &context.request_id {
+                request = request.header(CORRELATION_ID_HEADER, correlation_id);
+            }
+            let response = request.body(payload).send().await.map_err(|e| e.to_string())?;
+            let status = response.status();
+            if status.is_success() {
+                Ok(Some(format!("status {}", status.as_u16())))
+            } else {
+                Err(format!("status {}", status.as_u16()))
+            }
+        }
+        HookAction::Command { command, args } => {
+            // the allowlist may have changed since the hook was configured
+            if !config().await.hook_allowed_commands().contains(command) {
+                return Err(format!("command {} is not allowed", command));
+            }
+            let mut child = TrackedChild::spawn(
+                Command::new(command)
+                    .args(args)
+                    .env("INDEXER_ID", indexer_id.to_string())
+                    .env("HOOK_STAGE", stage.to_string())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true),
+            )
+            .map_err(|e| e.to_string())?;
+            let status = child.wait().await.map_err(|e| e.to_string())?;
+            match status.code() {
+                Some(0) => Ok(Some("exit code 0".into())),
+                Some(code) => Err(format!("exit code {}", code)),
+                None => Err("killed by a signal".into()),
+            }
+        }
+    }
+}
+
+async fn record_hook_result(
+    context: &ActorContext,
+    indexer_model: &IndexerModel,
+    hook: &LifecycleHook,
+    result: &HookResult,
+) {
+    let config = config().await;
+    let severity = if result.success { AuditSeverity::Info } else { AuditSeverity::Warning };
+    let reason = match result.success {
+        true => format!("{} hook succeeded", result.stage),
+        false => format!("{} hook failed, {}", result.stage, hook.failure_policy),
+    };
+    let insert = AuditRepository::new(config.pool())
+        .insert(NewAuditLogDb {
+            id: Uuid::new_v4(),
+            indexer_id: indexer_model.id,
+            action: AuditAction::LifecycleHook.to_string(),
+            from_status: None,
+            to_status: None,
+            reason: Some(reason),
+            actor: Some("system".to_string()),
+            severity: severity.to_string(),
+            details: serde_json::to_value(result).ok(),
+            correlation_id: context.request_id.clone(),
+        })
+        .await;
+    if let Err(e) = insert {
+        tracing::error!("Failed to record {} hook of indexer {}: {:?}", result.stage, indexer_model.id, e);
+    }
+}
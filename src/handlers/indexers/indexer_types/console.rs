@@ -0,0 +1,26 @@
+use axum::async_trait;
+
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::handlers::indexers::indexer_types::Indexer;
+use crate::utils::env::get_environment_variable;
+
+/// Prints the data returned by the script instead of sending it anywhere, the output is kept by
+/// the service for debugging scripts
+pub struct ConsoleIndexer;
+
+#[async_trait]
+impl Indexer for ConsoleIndexer {
+    async fn start(&self, indexer: &IndexerModel, starting_block: Option<u64>) -> Result<ExecutionRef, IndexerError> {
+        let id = self.start_common(self.binary(), indexer, starting_block, &self.launch_options(indexer)).await?;
+        Ok(id)
+    }
+
+    fn binary(&self) -> String {
+        format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-console")
+    }
+
+    fn launch_options(&self, _indexer: &IndexerModel) -> Vec<String> {
+        vec![]
+    }
+}
@@ -1,59 +1,91 @@
+pub mod console;
+pub mod parquet;
 pub mod postgres;
 pub mod webhook;
 
+use std::collections::BTreeMap;
 use std::process::Stdio;
+use std::time::{Duration, Instant};
 
 use axum::async_trait;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
 use shutil::pipe;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use uuid::Uuid;
 
+use crate::config::config;
+use crate::constants::indexers::{
+    PROCESS_OUTPUT_DRAIN_TIMEOUT_MILLIS, PROCESS_OUTPUT_TAIL_LINES, STOP_FLUSH_POLL_INTERVAL_MILLIS,
+    TARGET_GONE_STOP_AFTER_SECONDS,
+};
+use crate::domain::models::diagnostics::{OutputTail, ProcessExitSnapshot};
+use crate::domain::models::execution::{ExecutionRef, StopExpectation};
 use crate::domain::models::indexer::IndexerError::FailedToStopIndexer;
-use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerType};
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerType, LaunchConfig, ScriptPermissions};
+use crate::domain::models::launch_command::{redact_secrets, LaunchCommand};
+use crate::domain::models::process_priority::{IoClass, ProcessPriority, DEFAULT_IO_LEVEL, DEFAULT_NICE};
+use crate::domain::models::secret::{get_secret_scope, SecretReference};
+use crate::domain::models::target_health::{parse_response_status, TargetGoneDetector, TARGET_GONE_REASON};
+use crate::handlers::admin::runtime::get_process_start_time;
+use crate::handlers::indexers::console::record_console_output;
+use crate::handlers::indexers::diagnostics::record_process_exit;
+use crate::handlers::indexers::logs::record_sink_log;
+use crate::handlers::indexers::multiplexer::multiplexer;
+use crate::handlers::indexers::reaper::{handle_process_exit, track_process, untrack_process, TrackedChild};
+use crate::handlers::indexers::stop_indexer::stop_indexer_expecting;
 use crate::handlers::indexers::utils::get_script_tmp_directory;
+use crate::handlers::tenants::usage::record_log_bytes;
+use crate::infra::repositories::project_repository::ProjectRepository;
+use crate::infra::repositories::tenant_repository::TenantRepository;
+use crate::utils::actor_context::{current_actor_context, spawn_with_context};
 use crate::utils::env::get_environment_variable;
+use crate::utils::script_cache::get_script_checksum;
+use crate::utils::secrets::SecretResolver;
 
 pub const DEFAULT_STARTING_BLOCK: i64 = 1;
 
 #[async_trait]
 pub trait Indexer {
-    async fn start(&self, indexer: &IndexerModel) -> Result<u32, IndexerError>;
+    /// `starting_block` overrides the cursor persisted for the sink
+    async fn start(&self, indexer: &IndexerModel, starting_block: Option<u64>) -> Result<ExecutionRef, IndexerError>;
+
+    /// Path of the sink binary
+    fn binary(&self) -> String;
+
+    /// Sink specific arguments passed after the common ones
+    fn launch_options(&self, indexer: &IndexerModel) -> Vec<String>;
+
+    /// Sink specific arguments the indexer would be launched with now, which can differ from
+    /// the launch options when the sink is shared. `script` is the resolved script.
+    async fn launch_args(&self, indexer: &IndexerModel, _script: &[u8]) -> Result<Vec<String>, IndexerError> {
+        Ok(self.launch_options(indexer))
+    }
 
     #[allow(clippy::result_large_err)]
-    fn start_common(&self, binary: String, indexer: &IndexerModel, extra_args: &[&str]) -> Result<u32, IndexerError> {
-        let script_path = get_script_tmp_directory(indexer.id);
-        let auth_token = get_environment_variable("APIBARA_AUTH_TOKEN");
-        let redis_url = get_environment_variable("APIBARA_REDIS_URL");
-
-        let sink_id = indexer.indexer_id.clone().unwrap_or_else(|| indexer.id.to_string());
-        let status_server_address = format!("0.0.0.0:{port}", port = indexer.status_server_port.unwrap_or(1234));
-
-        let mut args = vec![
-            "run",
-            script_path.as_str(),
-            "--auth-token",
-            auth_token.as_str(),
-            "--persist-to-redis",
-            redis_url.as_str(),
-            "--sink-id",
-            sink_id.as_str(),
-            "--status-server-address",
-            status_server_address.as_str(),
-            "--allow-env-from-env",
-            "STARTING_BLOCK",
-        ];
-        args.extend_from_slice(extra_args);
-
-        let mut child_handle = Command::new(binary)
+    async fn start_common(
+        &self,
+        binary: String,
+        indexer: &IndexerModel,
+        starting_block: Option<u64>,
+        extra_args: &[String],
+    ) -> Result<ExecutionRef, IndexerError> {
+        let mut command = get_launch_command(binary, indexer, starting_block, extra_args);
+        resolve_secrets(indexer, &mut command).await?;
+        // the sink may print the secrets it was given, e.g. in its connection errors
+        let secrets = command.secrets();
+        let mut child_handle = Command::new(&command.program)
             // Silence  stdout and stderr
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .env("STARTING_BLOCK", indexer.starting_block.unwrap_or(DEFAULT_STARTING_BLOCK).to_string())
-            .args(args)
-            .spawn()
-            .map_err(|e| IndexerError::FailedToStartIndexer(e.to_string(), indexer.id.to_string()))?;
+            .envs(command.env)
+            .args(command.args)
+            .spawn().map_err(|e| IndexerError::FailedToStartIndexer(e.to_string(), indexer.id.to_string()))?;
 
         let id = child_handle.id().expect("Failed to get the child process id");
+        track_process(id);
+        let execution_ref = ExecutionRef::Pid { pid: id, start_time: get_process_start_time(id) };
 
         let stdout = child_handle.stdout.take().expect("child did not have a handle to stdout");
         let stderr = child_handle.stderr.take().expect("child did not have a handle to stderr");
@@ -62,51 +94,114 @@ pub trait Indexer {
         let mut stderr_reader = BufReader::new(stderr).lines();
 
         let indexer_id = indexer.id;
-        tokio::spawn(async move {
-            loop {
+        // only webhook sinks deliver to a target of their own, shared sinks deliver to the service
+        let has_own_target = indexer.indexer_type == IndexerType::Webhook && !multiplexer().is_member(indexer.id).await;
+        let mut target_gone = has_own_target.then(TargetGoneDetector::default);
+        let is_console = indexer.indexer_type == IndexerType::Console;
+        // the process outlives the request which started it, its exit is handled as the system
+        let context = current_actor_context().as_system();
+        let process_execution_ref = execution_ref.clone();
+        spawn_with_context(&context.clone(), async move {
+            let mut stdout_tail = OutputTail::new(PROCESS_OUTPUT_TAIL_LINES);
+            let mut stderr_tail = OutputTail::new(PROCESS_OUTPUT_TAIL_LINES);
+            let (exit_status, exited_at) = loop {
                 tokio::select! {
                     result = stdout_reader.next_line() => {
                         match result {
-                            Ok(Some(line)) => tracing::info!("[indexer-{}-stdout] {}", indexer_id, line),
+                            Ok(Some(line)) => {
+                                tracing::info!("[indexer-{}-stdout] {}", indexer_id, line);
+                                watch_target_response(indexer_id, &process_execution_ref, &line, &mut target_gone);
+                                record_sink_log(indexer_id, &line);
+                                if is_console {
+                                    record_console_output(indexer_id, &line);
+                                }
+                                record_log_bytes(indexer_id, line.len());
+                                stdout_tail.push(line);
+                            }
                             Err(_) => (), // we will break on .wait
                             _ => ()
                         }
                     }
                     result = stderr_reader.next_line() => {
                         match result {
-                            Ok(Some(line)) => tracing::info!("[indexer-{}-stderr] {}", indexer_id, line),
+                            Ok(Some(line)) => {
+                                tracing::info!("[indexer-{}-stderr] {}", indexer_id, line);
+                                watch_target_response(indexer_id, &process_execution_ref, &line, &mut target_gone);
+                                record_log_bytes(indexer_id, line.len());
+                                stderr_tail.push(line);
+                            }
                             Err(_) => (), // we will break on .wait
                             _ => ()
                         }
                     }
                     result = child_handle.wait() => {
-                        match result.unwrap().success() {
-                            true => {
-                                tracing::info!("Child process exited successfully {}", indexer_id);
-                                // TODO: stop indexer
-                            },
-                            false => {
-                                tracing::error!("Child process exited with an error {}", indexer_id);
-                                // let _ = start_indexer(indexer_id, 1).await;
+                        untrack_process(id);
+                        let exit_status = match result {
+                            Ok(exit_status) => exit_status,
+                            Err(e) => {
+                                tracing::error!("Failed to wait for the process of indexer {}: {:?}", indexer_id, e);
+                                return;
                             }
+                        };
+                        match exit_status.success() {
+                            true => tracing::info!("Child process exited successfully {}", indexer_id),
+                            false => tracing::error!("Child process exited with an error {}", indexer_id),
                         }
-                        break // child process exited
+                        break (exit_status, Utc::now()) // child process exited
                     }
                 };
-            }
+            };
+
+            // the last lines may still be buffered when the exit is noticed
+            let drain = async {
+                while let Ok(Some(line)) = stdout_reader.next_line().await {
+                    stdout_tail.push(line);
+                }
+                while let Ok(Some(line)) = stderr_reader.next_line().await {
+                    stderr_tail.push(line);
+                }
+            };
+            let _ = tokio::time::timeout(Duration::from_millis(PROCESS_OUTPUT_DRAIN_TIMEOUT_MILLIS), drain).await;
+
+            record_process_exit(
+                &context,
+                ProcessExitSnapshot {
+                    indexer_id,
+                    process_id: id,
+                    exit_code: exit_status.code(),
+                    exited_at,
+                    stdout: stdout_tail.into_lines().iter().map(|line| redact_secrets(line, &secrets)).collect(),
+                    stderr: stderr_tail.into_lines().iter().map(|line| redact_secrets(line, &secrets)).collect(),
+                },
+            )
+            .await;
+            handle_process_exit(&context, indexer_id, id, exit_status).await;
         });
 
-        Ok(id)
+        Ok(execution_ref)
     }
 
-    #[allow(clippy::result_large_err)]
     async fn stop(&self, indexer: IndexerModel) -> Result<(), IndexerError> {
-        let process_id = match indexer.process_id {
-            Some(process_id) => process_id,
+        self.stop_common(indexer).await
+    }
+
+    /// Time the sink is given to flush its in-flight batch once interrupted, the sink is
+    /// terminated right away if not set
+    fn flush_timeout(&self, _indexer: &IndexerModel) -> Option<Duration> {
+        None
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn stop_common(&self, indexer: IndexerModel) -> Result<(), IndexerError> {
+        let execution_ref = match indexer.execution_ref.clone() {
+            Some(execution_ref) => execution_ref,
             None => {
-                return Err(IndexerError::InternalServerError("Cannot stop indexer without process id".to_string()));
+                return Err(IndexerError::InternalServerError("Cannot stop indexer without execution ref".to_string()));
             }
         };
+        let Some(process_id) = execution_ref.pid() else {
+            return Err(IndexerError::UnsupportedExecution(execution_ref));
+        };
 
         if !self.is_running(indexer.clone()).await? {
             return Err(IndexerError::InternalServerError(format!(
@@ -115,34 +210,52 @@ pub trait Indexer {
             )));
         }
 
-        let is_success = Command::new("kill")
-            // Silence  stdout and stderr
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .args([
-                process_id.to_string().as_str(),
-            ])
-            .spawn()
-            .map_err(|_| IndexerError::FailedToStopIndexer(process_id))?
-            .wait()
-            .await
-            .unwrap()
-            .success();
+        let Some(flush_timeout) = self.flush_timeout(&indexer) else {
+            if !signal_process(process_id, "TERM").await {
+                return Err(FailedToStopIndexer(execution_ref));
+            }
+            return Ok(());
+        };
 
-        if !is_success {
-            return Err(FailedToStopIndexer(process_id));
+        // two phase stop, the sink finishes its batch and exits once interrupted, it's only
+        // killed if it doesn't exit in time
+        if !signal_process(process_id, "INT").await {
+            return Err(FailedToStopIndexer(execution_ref));
+        }
+        let deadline = Instant::now() + flush_timeout;
+        while Instant::now() < deadline {
+            if !self.is_running(indexer.clone()).await? {
+                tracing::info!("Indexer {} flushed its batch and exited", indexer.id);
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(STOP_FLUSH_POLL_INTERVAL_MILLIS)).await;
+        }
+        tracing::warn!(
+            "Indexer {} didn't exit {}s after it was interrupted, killing it, its last batch may be partially written",
+            indexer.id,
+            flush_timeout.as_secs()
+        );
+        if !signal_process(process_id, "KILL").await {
+            return Err(FailedToStopIndexer(execution_ref));
         }
         Ok(())
     }
     async fn is_running(&self, indexer: IndexerModel) -> Result<bool, IndexerError> {
-        let process_id = match indexer.process_id {
-            Some(process_id) => process_id,
+        let execution_ref = match indexer.execution_ref {
+            Some(execution_ref) => execution_ref,
             None => {
                 return Err(IndexerError::InternalServerError(
-                    "Cannot check running status for indexer without process id".to_string(),
+                    "Cannot check running status for indexer without execution ref".to_string(),
                 ));
             }
         };
+        let Some(process_id) = execution_ref.pid() else {
+            return Err(IndexerError::UnsupportedExecution(execution_ref));
+        };
+        // the pid is reused once the sink exits, another process with the same pid isn't the sink
+        if !execution_ref.matches_process(process_id, get_process_start_time(process_id)) {
+            return Ok(false);
+        }
 
         // Check if the process is running and not in the defunct state
         // `Z` state implies the zombie state where the process is technically
@@ -152,9 +265,298 @@ pub trait Indexer {
     }
 }
 
+/// Sends the signal to the process, e.g. `TERM`, returns whether it was delivered
+async fn signal_process(process_id: u32, signal: &str) -> bool {
+    let child = TrackedChild::spawn(Command::new("kill")
+            // Silence  stdout and stderr
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .args(["-s", signal, process_id.to_string().as_str()]));
+    match child {
+        Ok(mut child) => child.wait().await.is_ok_and(|status| status.success()),
+        Err(_) => false,
+    }
+}
+
+/// Stops the indexer once the responses of its target logged by the sink show that the target is
+/// gone. The detector is dropped after that so the indexer is only stopped once.
+fn watch_target_response(
+    indexer_id: Uuid,
+    execution_ref: &ExecutionRef,
+    line: &str,
+    target_gone: &mut Option<TargetGoneDetector>,
+) {
+    let (Some(detector), Some(status)) = (target_gone.as_mut(), parse_response_status(line)) else {
+        return;
+    };
+    if !detector.record(Instant::now(), status) {
+        return;
+    }
+    *target_gone = None;
+    stop_target_gone(indexer_id, execution_ref, status);
+}
+
+/// Stops the indexer whose target is gone, only if it still runs the given execution. Shared
+/// sinks don't see the responses of the targets, the multiplexer reports them instead.
+pub fn stop_target_gone(indexer_id: Uuid, execution_ref: &ExecutionRef, status: u16) {
+    let reason = format!(
+        "{}: the target answered {} for more than {}s",
+        TARGET_GONE_REASON, status, TARGET_GONE_STOP_AFTER_SECONDS
+    );
+    tracing::warn!("Stopping indexer {}, {}", indexer_id, reason);
+    let expected = StopExpectation { execution_ref: Some(execution_ref.clone()), execution_seq: None };
+    let context = current_actor_context().as_system();
+    spawn_with_context(&context.clone(), async move {
+        if let Err(e) = stop_indexer_expecting(&context, indexer_id, &expected, Some(reason)).await {
+            tracing::error!("Failed to stop indexer {} whose target is gone: {:?}", indexer_id, e);
+        }
+    });
+}
+
+/// Command line of the sink process, `extra_args` are the sink specific arguments
+pub fn get_launch_command(
+    binary: String,
+    indexer: &IndexerModel,
+    starting_block: Option<u64>,
+    extra_args: &[String],
+) -> LaunchCommand {
+    let sink_id = get_sink_id(indexer);
+    let status_server_address = format!("0.0.0.0:{port}", port = indexer.status_server_port.unwrap_or(1234));
+
+    // `nice` and `ionice` exec the sink so the process id is still the one of the sink
+    let mut command = get_priority_command(&indexer.process_priority);
+    command.push(binary);
+    command.extend([
+        "run".to_string(),
+        get_script_tmp_directory(indexer.id),
+        "--auth-token".to_string(),
+        get_environment_variable("APIBARA_AUTH_TOKEN"),
+        "--persist-to-redis".to_string(),
+        get_environment_variable("APIBARA_REDIS_URL"),
+        "--sink-id".to_string(),
+        sink_id,
+        "--status-server-address".to_string(),
+        status_server_address,
+    ]);
+    command.extend(get_common_options(indexer));
+    command.extend(extra_args.iter().cloned());
+    // not part of the launch config, restarts resume from the persisted cursor
+    if let Some(starting_block) = starting_block {
+        command.extend(["--starting-block".to_string(), starting_block.to_string()]);
+    }
+
+    LaunchCommand {
+        program: command.remove(0),
+        args: command,
+        env: get_launch_env(indexer),
+        working_directory: std::env::current_dir().ok().map(|path| path.display().to_string()),
+    }
+}
+
+/// Replaces the secret references of the command by the secrets, a reference is a whole option
+/// or environment variable value, or the value of a header option. The references of the
+/// indexers of a tenant must use the backend the tenant selected. Every reference must point
+/// under the secrets scope of the indexer, see `get_secret_scope`, so that the secrets of other
+/// tenants can't be sent to a target of the caller.
+async fn resolve_secrets(indexer: &IndexerModel, command: &mut LaunchCommand) -> Result<(), IndexerError> {
+    let references: Vec<(&mut String, Option<String>, SecretReference)> = command
+        .args
+        .iter_mut()
+        .chain(command.env.values_mut())
+        .filter_map(|value| {
+            let (header, reference) = find_secret_reference(value)?;
+            Some((value, header, reference))
+        })
+        .collect();
+    if references.is_empty() {
+        return Ok(());
+    }
+
+    let config = config().await;
+    let tenant_backend = match &indexer.tenant_id {
+        Some(tenant_id) => TenantRepository::new(config.pool())
+            .get_settings(tenant_id)
+            .await
+            .map_err(IndexerError::FailedToGetTenantSettings)?
+            .and_then(|settings| settings.secret_backend),
+        None => None,
+    };
+    let project = match indexer.project_id {
+        Some(project_id) => Some(
+            ProjectRepository::new(config.pool())
+                .get(project_id)
+                .await
+                .map_err(IndexerError::InfraError)?
+                .ok_or(IndexerError::ProjectNotFound(project_id))?,
+        ),
+        None => None,
+    };
+    let scope = get_secret_scope(
+        indexer.tenant_id.as_deref(),
+        project.as_ref().and_then(|project| project.secret_path_prefix.as_deref()),
+    );
+    let resolver = SecretResolver::new(config.secrets());
+    for (value, header, reference) in references {
+        if !reference.is_in_scope(&scope) {
+            return Err(IndexerError::SecretOutOfScope(reference.to_string(), scope));
+        }
+        if tenant_backend.is_some_and(|backend| backend != reference.backend) {
+            return Err(IndexerError::SecretBackendNotAllowed(
+                reference.backend,
+                indexer.tenant_id.clone().unwrap_or_default(),
+            ));
+        }
+        let secret = resolver
+            .resolve(&reference)
+            .await
+            .map_err(|e| IndexerError::FailedToResolveSecret(reference.to_string(), e))?;
+        *value = match header {
+            Some(header) => format!("{}: {}", header, secret),
+            None => secret,
+        };
+    }
+    Ok(())
+}
+
+/// Reference in the value, along with the name of the header if the value is a header option
+fn find_secret_reference(value: &str) -> Option<(Option<String>, SecretReference)> {
+    if let Some(reference) = SecretReference::parse(value) {
+        return Some((None, reference));
+    }
+    let (header, header_value) = value.split_once(':')?;
+    SecretReference::parse(header_value).map(|reference| (Some(header.trim().to_string()), reference))
+}
+
+/// Id the sink persists its cursor under, indexers sharing it share the cursor and its lock
+pub fn get_sink_id(indexer: &IndexerModel) -> String {
+    indexer.indexer_id.clone().unwrap_or_else(|| indexer.id.to_string())
+}
+
+/// Environment variables set on the sink process
+pub fn get_launch_env(indexer: &IndexerModel) -> BTreeMap<String, String> {
+    let mut env = BTreeMap::from([(
+        "STARTING_BLOCK".to_string(),
+        indexer.starting_block.unwrap_or(DEFAULT_STARTING_BLOCK).to_string(),
+    )]);
+    if let Some(log_level) = indexer.log_level {
+        env.insert("RUST_LOG".to_string(), log_level.to_string());
+    }
+    env
+}
+
+/// Options shared by every sink type
+pub fn get_common_options(indexer: &IndexerModel) -> Vec<String> {
+    let mut options = get_user_common_options(indexer);
+    options.extend(get_permission_args(indexer));
+    options
+}
+
+/// Common options set through the API, empty for the indexers which don't set them
+fn get_user_common_options(indexer: &IndexerModel) -> Vec<String> {
+    let mut options = vec![];
+    if let Some(stream_url) = &indexer.stream_url {
+        options.extend(["--stream-url".to_string(), stream_url.clone()]);
+    }
+    if let Some(ending_block) = indexer.ending_block {
+        options.extend(["--ending-block".to_string(), ending_block.to_string()]);
+    }
+    options
+}
+
+/// Command the sink is launched through to get its CPU and IO priority, empty if it keeps the
+/// defaults of the OS
+pub fn get_priority_command(priority: &ProcessPriority) -> Vec<String> {
+    let mut command = vec![];
+    if let Some(nice) = priority.nice {
+        command.extend(["nice".to_string(), "-n".to_string(), nice.to_string()]);
+    }
+    if let Some(io_class) = priority.effective_io_class() {
+        command.extend(["ionice".to_string(), "-c".to_string(), io_class.number().to_string()]);
+        if let Some(io_level) = priority.io_level {
+            command.extend(["-n".to_string(), io_level.to_string()]);
+        }
+    }
+    command
+}
+
+/// Changes the priority of a running sink, anything not set goes back to the defaults of the OS
+pub async fn apply_process_priority(process_id: u32, priority: &ProcessPriority) -> Result<(), IndexerError> {
+    let process_id = process_id.to_string();
+    let nice = priority.nice.unwrap_or(DEFAULT_NICE).to_string();
+    let io_class = priority.effective_io_class().unwrap_or(IoClass::BestEffort);
+    let mut ionice = vec!["-c".to_string(), io_class.number().to_string()];
+    if io_class == IoClass::BestEffort {
+        ionice.extend(["-n".to_string(), priority.io_level.unwrap_or(DEFAULT_IO_LEVEL).to_string()]);
+    }
+    ionice.extend(["-p".to_string(), process_id.clone()]);
+
+    for (program, args) in [("renice", vec!["-n".to_string(), nice, "-p".to_string(), process_id]), ("ionice", ionice)]
+    {
+        let output = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| IndexerError::FailedToApplyProcessPriority(format!("{}: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(IndexerError::FailedToApplyProcessPriority(format!(
+                "{}: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Deno permission flags of the script, the runtime denies anything which isn't granted
+pub fn get_permission_args(indexer: &IndexerModel) -> Vec<String> {
+    let permissions = &indexer.script_permissions;
+    let mut env = vec!["STARTING_BLOCK".to_string()];
+    env.extend(permissions.allow_env.iter().cloned());
+
+    let mut args = vec!["--allow-env-from-env".to_string(), env.join(",")];
+    if !permissions.allow_net.is_empty() {
+        args.extend(["--allow-net".to_string(), permissions.allow_net.join(",")]);
+    }
+    if !permissions.allow_read.is_empty() {
+        args.extend(["--allow-read".to_string(), permissions.allow_read.join(",")]);
+    }
+    args
+}
+
+/// Summary of what an indexer is launched with, used to detect when the process and the DB
+/// drift apart. Only the options set through the API are hashed, so that the options the service
+/// adds on its own don't make every running indexer drift when they change. The permissions
+/// granted to every script are such an option.
+pub fn get_launch_config(handler: &(dyn Indexer + Sync + Send), indexer: &IndexerModel, script: &[u8]) -> LaunchConfig {
+    get_launch_config_of_script(handler, indexer, get_script_checksum(script))
+}
+
+/// Same as `get_launch_config` for a script known by its checksum, e.g. the one the indexer was
+/// last launched with, so that it doesn't have to be downloaded again
+pub fn get_launch_config_of_script(
+    handler: &(dyn Indexer + Sync + Send),
+    indexer: &IndexerModel,
+    script_checksum: String,
+) -> LaunchConfig {
+    let mut options = get_user_common_options(indexer);
+    if indexer.script_permissions != ScriptPermissions::default() {
+        options.extend(get_permission_args(indexer));
+    }
+    let mut hasher = Sha256::new();
+    for option in options.into_iter().chain(handler.launch_options(indexer)) {
+        hasher.update(option.as_bytes());
+        hasher.update([0]);
+    }
+    LaunchConfig { script_checksum, options_checksum: hex::encode(hasher.finalize()), env: get_launch_env(indexer) }
+}
+
 pub fn get_indexer_handler(indexer_type: &IndexerType) -> Box<dyn Indexer + Sync + Send> {
     match indexer_type {
         IndexerType::Webhook => Box::new(webhook::WebhookIndexer {}),
         IndexerType::Postgres => Box::new(postgres::PostgresIndexer {}),
+        IndexerType::Parquet => Box::new(parquet::ParquetIndexer {}),
+        IndexerType::Console => Box::new(console::ConsoleIndexer {}),
     }
 }
@@ -0,0 +1,33 @@
+use axum::async_trait;
+
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::domain::models::sink_options::SinkOptions;
+use crate::handlers::indexers::indexer_types::Indexer;
+use crate::utils::env::get_environment_variable;
+
+pub struct ParquetIndexer;
+
+#[async_trait]
+impl Indexer for ParquetIndexer {
+    async fn start(&self, indexer: &IndexerModel, starting_block: Option<u64>) -> Result<ExecutionRef, IndexerError> {
+        let id = self.start_common(self.binary(), indexer, starting_block, &self.launch_options(indexer)).await?;
+        Ok(id)
+    }
+
+    fn binary(&self) -> String {
+        format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-parquet")
+    }
+
+    /// The sink writes straight to the bucket with the AWS credentials of the service
+    fn launch_options(&self, indexer: &IndexerModel) -> Vec<String> {
+        let output_location = indexer.output_location.as_ref().expect("`output_location` not set for parquet indexer");
+        let mut options = vec!["--output-dir".to_string(), output_location.clone()];
+        if let Some(SinkOptions::Parquet(parquet_options)) = &indexer.sink_options {
+            if let Some(batch_size) = parquet_options.batch_size {
+                options.extend(["--batch-size".to_string(), batch_size.to_string()]);
+            }
+        }
+        options
+    }
+}
@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use axum::async_trait;
 
+use crate::domain::models::execution::ExecutionRef;
 use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::domain::models::sink_options::{PostgresOptions, SinkOptions};
 use crate::handlers::indexers::indexer_types::Indexer;
 use crate::utils::env::get_environment_variable;
 
@@ -8,18 +12,56 @@ pub struct PostgresIndexer;
 
 #[async_trait]
 impl Indexer for PostgresIndexer {
-    async fn start(&self, indexer: &IndexerModel) -> Result<u32, IndexerError> {
-        let binary_file = format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-postgres");
+    async fn start(&self, indexer: &IndexerModel, starting_block: Option<u64>) -> Result<ExecutionRef, IndexerError> {
+        let id = self.start_common(self.binary(), indexer, starting_block, &self.launch_options(indexer)).await?;
+        Ok(id)
+    }
+
+    fn binary(&self) -> String {
+        format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-postgres")
+    }
+
+    fn launch_options(&self, indexer: &IndexerModel) -> Vec<String> {
         let postgres_connection_string = indexer
             .custom_connection_string
             .clone()
             .unwrap_or_else(|| get_environment_variable("APIBARA_POSTGRES_CONNECTION_STRING"));
         let table_name = indexer.table_name.as_ref().expect("`table_name` not set for postgres indexer");
-        let id = self.start_common(
-            binary_file,
-            indexer,
-            &["--connection-string", postgres_connection_string.as_str(), "--table-name", table_name.as_str()],
-        )?;
-        Ok(id)
+        let mut options = vec![
+            "--connection-string".to_string(),
+            postgres_connection_string,
+            "--table-name".to_string(),
+            table_name.clone(),
+        ];
+        if let Some(SinkOptions::Postgres(postgres_options)) = &indexer.sink_options {
+            options.extend(get_options_args(postgres_options));
+        }
+        options
+    }
+
+    fn flush_timeout(&self, indexer: &IndexerModel) -> Option<Duration> {
+        match &indexer.sink_options {
+            Some(SinkOptions::Postgres(postgres_options)) => {
+                postgres_options.flush_timeout_seconds.map(Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn get_options_args(options: &PostgresOptions) -> Vec<String> {
+    let mut args = vec![];
+    if options.entity_mode {
+        args.push("--entity-mode".to_string());
+    }
+    if options.no_tls {
+        args.push("--no-tls".to_string());
+    }
+    if let Some(tls_certificate) = &options.tls_certificate {
+        args.extend(["--tls-certificate".to_string(), tls_certificate.clone()]);
+    }
+    if options.tls_accept_invalid_certificates {
+        args.push("--tls-accept-invalid-certificates".to_string());
     }
+    args
 }
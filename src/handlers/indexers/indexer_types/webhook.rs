@@ -1,20 +1,109 @@
+use std::fs;
+
 use axum::async_trait;
 
+use crate::config::config;
+use crate::domain::models::execution::ExecutionRef;
 use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::domain::models::sink_options::{SinkOptions, WebhookOptions};
 use crate::handlers::indexers::indexer_types::Indexer;
+use crate::handlers::indexers::multiplexer::{get_group_key, multiplexer};
+use crate::handlers::indexers::utils::get_script_tmp_directory;
 use crate::utils::env::get_environment_variable;
 
 pub struct WebhookIndexer;
 
 #[async_trait]
 impl Indexer for WebhookIndexer {
-    async fn start(&self, indexer: &IndexerModel) -> Result<u32, IndexerError> {
-        let binary_file = format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-webhook");
-        let id = self.start_common(
-            binary_file,
-            indexer,
-            &["--target-url", indexer.target_url.clone().expect("`target_url` not set for webhook indexer").as_str()],
-        )?;
-        Ok(id)
+    async fn start(&self, indexer: &IndexerModel, starting_block: Option<u64>) -> Result<ExecutionRef, IndexerError> {
+        let binary_file = self.binary();
+        let target_url = indexer.target_url.clone().expect("`target_url` not set for webhook indexer");
+
+        let config = config().await;
+        // the policy may have changed since the indexer was created
+        config.target_policy().validate(target_url.as_str()).await?;
+
+        if !is_multiplexed(indexer).await {
+            let id = self.start_common(binary_file, indexer, starting_block, &self.launch_options(indexer)).await?;
+            return Ok(id);
+        }
+
+        // In multiplexer mode indexers sharing the same filter share a single sink which
+        // sends its payloads to the service, they are then fanned out to every target. Joining
+        // a running group keeps the position of its sink, so it can't be started from another block.
+        if starting_block.is_some() {
+            return Err(IndexerError::SharedSinkStartPosition(indexer.id));
+        }
+        let script = fs::read(get_script_tmp_directory(indexer.id)).map_err(IndexerError::FailedToReadFile)?;
+        let (key, execution_ref) =
+            multiplexer().join(&get_group_key(&script, indexer.starting_block), indexer.id, target_url).await;
+        if let Some(execution_ref) = execution_ref {
+            tracing::info!("Indexer {} joined multiplexer group {}", indexer.id, key);
+            return Ok(execution_ref);
+        }
+
+        let execution_ref =
+            self.start_common(binary_file, indexer, starting_block, &get_fan_out_options(&key).await).await?;
+        multiplexer().set_execution_ref(&key, execution_ref.clone()).await;
+        Ok(execution_ref)
+    }
+
+    fn binary(&self) -> String {
+        format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-webhook")
+    }
+
+    async fn launch_args(&self, indexer: &IndexerModel, script: &[u8]) -> Result<Vec<String>, IndexerError> {
+        if !is_multiplexed(indexer).await {
+            return Ok(self.launch_options(indexer));
+        }
+        let key = match multiplexer().get_group_key_of(indexer.id).await {
+            Some(key) => key,
+            None => get_group_key(script, indexer.starting_block),
+        };
+        Ok(get_fan_out_options(&key).await)
+    }
+
+    /// In multiplexer mode the sink targets the service instead, the target url is still what
+    /// identifies the indexer
+    fn launch_options(&self, indexer: &IndexerModel) -> Vec<String> {
+        let target_url = indexer.target_url.clone().expect("`target_url` not set for webhook indexer");
+        let mut options = vec!["--target-url".to_string(), target_url];
+        if let Some(SinkOptions::Webhook(webhook_options)) = &indexer.sink_options {
+            options.extend(get_options_args(webhook_options));
+        }
+        options
+    }
+
+    async fn stop(&self, indexer: IndexerModel) -> Result<(), IndexerError> {
+        // the shared sink must keep running as long as other indexers are using it
+        if let Some(remaining) = multiplexer().leave(indexer.id).await {
+            if remaining > 0 {
+                tracing::info!("Indexer {} left its multiplexer group, {} members remaining", indexer.id, remaining);
+                return Ok(());
+            }
+        }
+        self.stop_common(indexer).await
+    }
+}
+
+/// Options such as headers or the priority are specific to a sink so it can't be shared
+async fn is_multiplexed(indexer: &IndexerModel) -> bool {
+    config().await.multiplexer_enabled() && indexer.sink_options.is_none() && indexer.process_priority.is_default()
+}
+
+/// Shared sinks send their payloads to the service which fans them out
+async fn get_fan_out_options(key: &str) -> Vec<String> {
+    let fan_out_url = config().await.internal_url(&format!("/internal/multiplexer/{}", key));
+    vec!["--target-url".to_string(), fan_out_url]
+}
+
+fn get_options_args(options: &WebhookOptions) -> Vec<String> {
+    let mut args = vec![];
+    if options.raw {
+        args.push("--raw".to_string());
+    }
+    for header in &options.headers {
+        args.extend(["--header".to_string(), header.clone()]);
     }
+    args
 }
@@ -0,0 +1,73 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::constants::indexers::SINK_LOG_RECORDS_PER_INDEXER;
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::search::BlockLogRecord;
+use crate::domain::models::sink_log::{SinkLogQuery, SinkLogRecord};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::{PathExtractor, QueryExtractor};
+use crate::AppState;
+
+/// Last records printed by the sinks of this instance, by indexer
+static SINK_LOGS: OnceLock<Mutex<HashMap<Uuid, VecDeque<SinkLogRecord>>>> = OnceLock::new();
+
+fn sink_logs() -> std::sync::MutexGuard<'static, HashMap<Uuid, VecDeque<SinkLogRecord>>> {
+    SINK_LOGS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Parses a line of the stdout of a sink, the oldest records of the indexer are dropped once
+/// it has `SINK_LOG_RECORDS_PER_INDEXER` of them
+pub fn record_sink_log(indexer_id: Uuid, line: &str) {
+    let record = SinkLogRecord::parse(line, Utc::now());
+    let mut logs = sink_logs();
+    let records = logs.entry(indexer_id).or_default();
+    if records.len() == SINK_LOG_RECORDS_PER_INDEXER {
+        records.pop_front();
+    }
+    records.push_back(record);
+}
+
+pub fn forget_sink_logs(indexer_id: Uuid) {
+    sink_logs().remove(&indexer_id);
+}
+
+/// Records of the sinks of this instance referencing the block, oldest first
+pub fn find_sink_logs(block_number: u64) -> Vec<BlockLogRecord> {
+    let mut records: Vec<BlockLogRecord> = sink_logs()
+        .iter()
+        .flat_map(|(indexer_id, records)| {
+            records
+                .iter()
+                .filter(|record| record.block_number == Some(block_number))
+                .map(|record| BlockLogRecord { indexer_id: *indexer_id, record: record.clone() })
+        })
+        .collect();
+    records.sort_by_key(|record| record.record.timestamp);
+    records
+}
+
+/// Records of the sink of the indexer matching the query, oldest first. Only the records of the
+/// sinks this instance ran are known.
+pub async fn get_indexer_logs(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<SinkLogQuery>,
+) -> Result<Json<Vec<SinkLogRecord>>, IndexerError> {
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let mut records: Vec<SinkLogRecord> = match sink_logs().get(&id) {
+        Some(records) => records.iter().filter(|record| query.matches(record)).cloned().collect(),
+        None => vec![],
+    };
+    if let Some(limit) = query.limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+
+    Ok(Json(records))
+}
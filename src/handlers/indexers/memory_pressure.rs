@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::config::{config, MemoryPressureConfig};
+use crate::constants::runtime::MEMORY_PRESSURE_CHECK_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditLogModel;
+use crate::domain::models::execution::StopExpectation;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::multiplexer::multiplexer;
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::stop_indexer::stop_indexer_expecting;
+use crate::infra::repositories::audit_repository::AuditRepository;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+
+/// Stop reason recorded in the audit logs and the notifications
+pub const RESOURCE_PRESSURE_REASON: &str = "ResourcePressure";
+
+/// Indexers paused by the guard, they are only resumed by it if they are still stopped. The set
+/// is kept in memory and found again in the audit logs when the service starts, see
+/// `restore_paused_indexers`.
+static PAUSED_INDEXERS: OnceLock<Mutex<HashSet<Uuid>>> = OnceLock::new();
+
+fn paused_indexers() -> std::sync::MutexGuard<'static, HashSet<Uuid>> {
+    PAUSED_INDEXERS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn get_paused_indexers() -> Vec<Uuid> {
+    paused_indexers().iter().copied().collect()
+}
+
+/// Ratio of the memory of the host in use, from `/proc/meminfo`
+pub fn host_memory_used_ratio() -> Option<f64> {
+    parse_memory_used_ratio(&fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+fn parse_memory_used_ratio(meminfo: &str) -> Option<f64> {
+    let value = |name: &str| -> Option<u64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        line.trim_start_matches(name).split_whitespace().next()?.parse().ok()
+    };
+    let total = value("MemTotal:")?;
+    let available = value("MemAvailable:")?;
+    if total == 0 {
+        return None;
+    }
+    Some(1.0 - available as f64 / total as f64)
+}
+
+/// Pauses the running indexers with the lowest priority, one per check, while the memory used on
+/// the host is above the pause ratio, so that the sinks the kernel would kill under OOM are the
+/// ones that matter least. They are resumed highest priority first once the usage gets back under
+/// the resume ratio.
+pub async fn monitor_memory_pressure() {
+    let Some(memory_pressure) = config().await.memory_pressure() else {
+        return;
+    };
+    if let Err(e) = restore_paused_indexers().await {
+        tracing::error!("Failed to find the indexers paused before the service started: {:?}", e);
+    }
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(MEMORY_PRESSURE_CHECK_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        let Some(used_ratio) = host_memory_used_ratio() else {
+            continue;
+        };
+        if let Err(e) = relieve_pressure(memory_pressure, used_ratio).await {
+            tracing::error!("Failed to react to the memory pressure: {:?}", e);
+        }
+    }
+}
+
+/// Stopped indexers whose last stop was a pause of the guard, the pause being recorded as the
+/// reason of the stop
+async fn restore_paused_indexers() -> Result<(), IndexerError> {
+    let config = config().await;
+    let stopped = IndexerRepository::new(config.pool())
+        .get_all(IndexerFilter { status: Some(IndexerStatus::Stopped.to_string()) })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    let audit_repository = AuditRepository::new(config.pool());
+    for indexer in stopped {
+        let last_status_change =
+            audit_repository.get_last_status_change(indexer.id, Utc::now()).await.map_err(IndexerError::InfraError)?;
+        if last_status_change.is_some_and(is_pause) {
+            paused_indexers().insert(indexer.id);
+        }
+    }
+    Ok(())
+}
+
+fn is_pause(status_change: AuditLogModel) -> bool {
+    status_change.to_status == Some(IndexerStatus::Stopped)
+        && status_change.reason.as_deref() == Some(RESOURCE_PRESSURE_REASON)
+}
+
+async fn relieve_pressure(memory_pressure: MemoryPressureConfig, used_ratio: f64) -> Result<(), IndexerError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.background_pool());
+    let indexers = repository.get_all(IndexerFilter { status: None }).await.map_err(IndexerError::InfraError)?;
+
+    // indexers started by someone else since they were paused are no longer ours
+    let paused: HashSet<Uuid> = {
+        let mut paused = paused_indexers();
+        paused
+            .retain(|id| indexers.iter().any(|indexer| indexer.id == *id && indexer.status == IndexerStatus::Stopped));
+        paused.clone()
+    };
+
+    if used_ratio >= memory_pressure.pause_ratio {
+        let multiplexed: HashSet<Uuid> =
+            multiplexer().groups().await.into_iter().flat_map(|group| group.members.into_keys()).collect();
+        let Some(indexer) = get_next_to_pause(&indexers, &multiplexed) else {
+            tracing::warn!("Memory used on the host is at {:.0}% with no indexer left to pause", used_ratio * 100.0);
+            return Ok(());
+        };
+        tracing::warn!(
+            "Memory used on the host is at {:.0}%, pausing indexer {} with priority {}",
+            used_ratio * 100.0,
+            indexer.id,
+            indexer.priority
+        );
+        // the indexer may have been restarted since it was listed
+        stop_indexer_expecting(
+            &ActorContext::system(),
+            indexer.id,
+            &StopExpectation::current(indexer),
+            Some(RESOURCE_PRESSURE_REASON.to_string()),
+        )
+        .await?;
+        paused_indexers().insert(indexer.id);
+    } else if used_ratio < memory_pressure.resume_ratio {
+        let Some(indexer) = get_next_to_resume(&indexers, &paused) else {
+            return Ok(());
+        };
+        tracing::info!(
+            "Memory used on the host is back at {:.0}%, resuming indexer {}",
+            used_ratio * 100.0,
+            indexer.id
+        );
+        // kept paused if the start fails so that it's tried again
+        start_indexer(&ActorContext::system(), indexer.id).await?;
+        paused_indexers().remove(&indexer.id);
+    }
+    Ok(())
+}
+
+/// Running indexer with the lowest priority, standbys going first as their primary still runs.
+/// Members of a multiplexed sink are skipped, the shared process keeps running without them.
+fn get_next_to_pause<'a>(indexers: &'a [IndexerModel], multiplexed: &HashSet<Uuid>) -> Option<&'a IndexerModel> {
+    indexers
+        .iter()
+        .filter(|indexer| indexer.status == IndexerStatus::Running && !multiplexed.contains(&indexer.id))
+        .min_by_key(|indexer| (indexer.standby_for.is_none(), indexer.priority))
+}
+
+fn get_next_to_resume<'a>(indexers: &'a [IndexerModel], paused: &HashSet<Uuid>) -> Option<&'a IndexerModel> {
+    indexers
+        .iter()
+        .filter(|indexer| paused.contains(&indexer.id))
+        .max_by_key(|indexer| (indexer.standby_for.is_none(), indexer.priority))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::audit::AuditAction;
+
+    #[test]
+    fn test_parse_memory_used_ratio() {
+        let meminfo = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    4000000 kB\n";
+        assert_eq!(parse_memory_used_ratio(meminfo), Some(0.75));
+        assert_eq!(parse_memory_used_ratio("MemTotal:       16000000 kB\n"), None);
+    }
+
+    #[test]
+    fn test_is_pause() {
+        let status_change = |to_status: IndexerStatus, reason: Option<&str>| AuditLogModel {
+            id: Uuid::new_v4(),
+            indexer_id: Uuid::new_v4(),
+            action: AuditAction::StatusChange,
+            from_status: Some(IndexerStatus::Running),
+            to_status: Some(to_status),
+            reason: reason.map(String::from),
+            created_at: Utc::now(),
+            actor: None,
+            severity: Default::default(),
+            details: None,
+            correlation_id: None,
+        };
+        assert!(is_pause(status_change(IndexerStatus::Stopped, Some(RESOURCE_PRESSURE_REASON))));
+        assert!(!is_pause(status_change(IndexerStatus::Stopped, None)));
+        assert!(!is_pause(status_change(IndexerStatus::FailedStopping, Some(RESOURCE_PRESSURE_REASON))));
+    }
+
+    #[test]
+    fn test_next_to_pause_and_resume() {
+        let indexer = |status: IndexerStatus, priority: i32, standby_for: Option<Uuid>| IndexerModel {
+            id: Uuid::new_v4(),
+            status,
+            priority,
+            standby_for,
+            ..Default::default()
+        };
+        let urgent = indexer(IndexerStatus::Running, 10, None);
+        let background = indexer(IndexerStatus::Running, -5, None);
+        let standby = indexer(IndexerStatus::Running, 10, Some(urgent.id));
+        let stopped = indexer(IndexerStatus::Stopped, -10, None);
+        let indexers = vec![urgent.clone(), background.clone(), standby.clone(), stopped];
+
+        let multiplexed = HashSet::new();
+        assert_eq!(get_next_to_pause(&indexers, &multiplexed).map(|indexer| indexer.id), Some(standby.id));
+        assert_eq!(get_next_to_pause(&indexers[..2], &multiplexed).map(|indexer| indexer.id), Some(background.id));
+
+        // pausing a member of a shared sink frees nothing
+        let multiplexed = HashSet::from([standby.id, background.id]);
+        assert_eq!(get_next_to_pause(&indexers, &multiplexed).map(|indexer| indexer.id), Some(urgent.id));
+
+        let paused = HashSet::from([background.id, urgent.id]);
+        assert_eq!(get_next_to_resume(&indexers, &paused).map(|indexer| indexer.id), Some(urgent.id));
+    }
+}
@@ -1,8 +1,36 @@
+pub mod annotations;
+pub mod approvals;
+pub mod clone_indexer;
+pub mod config_drift;
+pub mod console;
 pub mod create_indexer;
 pub mod delete_indexer;
+pub mod diagnostics;
+pub mod estimate;
+pub mod export;
 pub mod fail_indexer;
+pub mod fleet_diff;
+pub mod gaps;
 pub mod get_indexer;
-mod indexer_types;
+pub mod gitops;
+pub mod hooks;
+pub mod indexer_types;
+pub mod logs;
+pub mod memory_pressure;
+pub mod multiplexer;
+pub mod preview;
+pub mod readiness;
+pub mod reaper;
+pub mod request_fields;
+pub mod scheduled_actions;
+pub mod script_scan;
+pub mod script_search;
+pub mod simulate_delivery;
+pub mod stale_created;
+pub mod standby;
 pub mod start_indexer;
+pub mod start_tokens;
 pub mod stop_indexer;
+pub mod update_indexer;
 pub mod utils;
+pub mod warm_start;
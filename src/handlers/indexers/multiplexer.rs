@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use futures_util::future::join_all;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::multiplexer::{MultiplexerGroup, MultiplexerMember};
+use crate::domain::models::target_health::TargetGoneDetector;
+use crate::handlers::indexers::indexer_types::stop_target_gone;
+use crate::handlers::tenants::usage::record_egress;
+use crate::utils::http::HttpClient;
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Keeps track of the shared stream consumers when the service runs in multiplexer mode.
+/// Webhook indexers with the same script and starting block join the same group, only one
+/// sink process is spawned per group and its payloads are fanned out to every member. Once the
+/// sink of a group moved past its starting block, indexers with the same key start a new group.
+#[derive(Default)]
+pub struct Multiplexer {
+    groups: RwLock<HashMap<String, MultiplexerGroup>>,
+    /// Responses of the targets of the members, the shared sink only sees the ones of the service
+    target_gone: Mutex<HashMap<Uuid, TargetGoneDetector>>,
+}
+
+static MULTIPLEXER: OnceLock<Multiplexer> = OnceLock::new();
+
+pub fn multiplexer() -> &'static Multiplexer {
+    MULTIPLEXER.get_or_init(Multiplexer::default)
+}
+
+/// The group key identifies a unique (network, filter) pair. Both are defined inside the
+/// script so we hash the script along with the starting block.
+pub fn get_group_key(script: &[u8], starting_block: Option<i64>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(script);
+    hasher.update(starting_block.unwrap_or_default().to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
+impl Multiplexer {
+    /// Adds the indexer to a group of the key which didn't send anything yet, a new one if there
+    /// is none. Returns the key of the group and its execution ref if a sink is already consuming
+    /// the stream.
+    pub async fn join(&self, key: &str, id: Uuid, target_url: String) -> (String, Option<ExecutionRef>) {
+        let mut groups = self.groups.write().await;
+        let joinable = groups
+            .values()
+            .find(|group| is_group_of_key(&group.key, key) && group.payloads == 0)
+            .map(|group| group.key.clone());
+        let group_key = joinable.unwrap_or_else(|| {
+            // the groups of the key which moved on keep running for their members
+            let mut group_key = key.to_string();
+            let mut generation = 1;
+            while groups.contains_key(&group_key) {
+                group_key = format!("{}-{}", key, generation);
+                generation += 1;
+            }
+            group_key
+        });
+        let group = groups
+            .entry(group_key.clone())
+            .or_insert_with(|| MultiplexerGroup { key: group_key.clone(), ..Default::default() });
+        group.members.insert(id, MultiplexerMember { target_url, ..Default::default() });
+        (group_key, group.execution_ref.clone())
+    }
+
+    /// Key of the group of the indexer
+    pub async fn get_group_key_of(&self, id: Uuid) -> Option<String> {
+        self.groups.read().await.values().find(|group| group.members.contains_key(&id)).map(|group| group.key.clone())
+    }
+
+    pub async fn set_execution_ref(&self, key: &str, execution_ref: ExecutionRef) {
+        if let Some(group) = self.groups.write().await.get_mut(key) {
+            group.execution_ref = Some(execution_ref);
+        }
+    }
+
+    /// Removes the indexer from its group. Returns the number of members left in the group
+    /// or `None` if the indexer wasn't multiplexed. Empty groups are dropped.
+    pub async fn leave(&self, id: Uuid) -> Option<usize> {
+        let mut groups = self.groups.write().await;
+        let key = groups.iter().find(|(_, group)| group.members.contains_key(&id)).map(|(key, _)| key.clone())?;
+        let group = groups.get_mut(&key)?;
+        group.members.remove(&id);
+        self.target_gone.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        let remaining = group.members.len();
+        if remaining == 0 {
+            groups.remove(&key);
+        }
+        Some(remaining)
+    }
+
+    /// Drops the groups whose shared sink is the process which exited and returns their members,
+    /// nothing consumes the stream for them anymore
+    pub async fn remove_sink(&self, process_id: u32) -> Vec<Uuid> {
+        let mut groups = self.groups.write().await;
+        let keys: Vec<String> = groups
+            .values()
+            .filter(|group| group.execution_ref.as_ref().and_then(ExecutionRef::pid) == Some(process_id))
+            .map(|group| group.key.clone())
+            .collect();
+        let members: Vec<Uuid> =
+            keys.iter().filter_map(|key| groups.remove(key)).flat_map(|group| group.members.into_keys()).collect();
+        let mut target_gone = self.target_gone.lock().unwrap_or_else(|e| e.into_inner());
+        for id in &members {
+            target_gone.remove(id);
+        }
+        members
+    }
+
+    pub async fn is_member(&self, id: Uuid) -> bool {
+        self.groups.read().await.values().any(|group| group.members.contains_key(&id))
+    }
+
+    pub async fn groups(&self) -> Vec<MultiplexerGroup> {
+        self.groups.read().await.values().cloned().collect()
+    }
+
+    /// Forwards the payload to every member of the group and updates their accounting. Members
+    /// whose target keeps answering that it's gone are stopped.
+    pub async fn fan_out(&self, client: &HttpClient, key: &str, body: Bytes) -> Result<(), StatusCode> {
+        let targets: Vec<(Uuid, String)> = match self.groups.write().await.get_mut(key) {
+            Some(group) => {
+                group.payloads += 1;
+                group.members.iter().map(|(id, member)| (*id, member.target_url.clone())).collect()
+            }
+            None => return Err(StatusCode::NOT_FOUND),
+        };
+        // the targets were checked against the target policy, a redirect would get around it
+        let client = client.without_redirects();
+
+        // a slow target doesn't hold back the others
+        let results = join_all(targets.into_iter().map(|(id, target_url)| {
+            let (client, body) = (&client, body.clone());
+            async move {
+                let response = client
+                    .post(target_url.as_str())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await;
+                let status = match response {
+                    Ok(response) => Some(response.status().as_u16()),
+                    Err(e) => {
+                        tracing::warn!("Failed to forward multiplexed payload to indexer {}: {}", id, e);
+                        None
+                    }
+                };
+                (id, status)
+            }
+        }))
+        .await;
+
+        let mut groups = self.groups.write().await;
+        if let Some(group) = groups.get_mut(key) {
+            for (id, status) in &results {
+                if let Some(member) = group.members.get_mut(id) {
+                    if status.is_some_and(|status| (200..300).contains(&status)) {
+                        member.messages_forwarded += 1;
+                        member.bytes_forwarded += body.len() as u64;
+                        record_egress(*id, 0, body.len() as u64);
+                    } else {
+                        member.failed_deliveries += 1;
+                    }
+                }
+            }
+            if let Some(execution_ref) = &group.execution_ref {
+                self.watch_target_responses(execution_ref, &results);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn watch_target_responses(&self, execution_ref: &ExecutionRef, results: &[(Uuid, Option<u16>)]) {
+        let now = Instant::now();
+        let mut target_gone = self.target_gone.lock().unwrap_or_else(|e| e.into_inner());
+        for (id, status) in results {
+            let Some(status) = *status else {
+                continue;
+            };
+            if target_gone.entry(*id).or_default().record(now, status) {
+                // stopped once, the detector starts over if the indexer joins again
+                target_gone.remove(id);
+                stop_target_gone(*id, execution_ref, status);
+            }
+        }
+    }
+}
+
+/// Groups of the key are the first one and the ones started after it moved on, `<key>-<n>`
+fn is_group_of_key(group_key: &str, key: &str) -> bool {
+    group_key == key || group_key.strip_prefix(key).and_then(|suffix| suffix.strip_prefix('-')).is_some()
+}
+
+pub async fn fan_out(
+    State(state): State<AppState>,
+    PathExtractor(key): PathExtractor<String>,
+    body: Bytes,
+) -> StatusCode {
+    match multiplexer().fan_out(&state.http, key.as_str(), body).await {
+        Ok(()) => StatusCode::OK,
+        Err(status) => status,
+    }
+}
+
+pub async fn get_multiplexer_groups(State(_state): State<AppState>) -> Json<Vec<MultiplexerGroup>> {
+    Json(multiplexer().groups().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_group_membership() {
+        let multiplexer = Multiplexer::default();
+        let key = get_group_key(b"script", Some(1));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(multiplexer.join(&key, first, "http://first".into()).await, (key.clone(), None));
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: Some(100) };
+        multiplexer.set_execution_ref(&key, execution_ref.clone()).await;
+        assert_eq!(multiplexer.join(&key, second, "http://second".into()).await, (key.clone(), Some(execution_ref)));
+        assert!(multiplexer.is_member(first).await);
+
+        assert_eq!(multiplexer.leave(first).await, Some(1));
+        assert!(!multiplexer.is_member(first).await);
+        assert_eq!(multiplexer.leave(second).await, Some(0));
+        assert_eq!(multiplexer.leave(second).await, None);
+        assert!(multiplexer.groups().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_late_members_start_a_new_group() {
+        let multiplexer = Multiplexer::default();
+        let key = get_group_key(b"script", Some(1));
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        multiplexer.join(&key, first, "http://127.0.0.1:1/first".into()).await;
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: Some(100) };
+        multiplexer.set_execution_ref(&key, execution_ref).await;
+        // the targets can't be reached, the payload still counts as sent
+        multiplexer.fan_out(&HttpClient::default(), &key, Bytes::from_static(b"{}")).await.unwrap();
+
+        let new_key = format!("{}-1", key);
+        assert_eq!(multiplexer.join(&key, second, "http://second".into()).await, (new_key.clone(), None));
+        assert_eq!(multiplexer.get_group_key_of(second).await, Some(new_key.clone()));
+        assert_eq!(multiplexer.join(&key, third, "http://third".into()).await, (new_key, None));
+        assert_eq!(multiplexer.get_group_key_of(first).await, Some(key));
+    }
+
+    #[tokio::test]
+    async fn test_groups_removed_with_their_sink() {
+        let multiplexer = Multiplexer::default();
+        let key = get_group_key(b"script", Some(1));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        multiplexer.join(&key, first, "http://first".into()).await;
+        multiplexer.join(&key, second, "http://second".into()).await;
+        multiplexer.set_execution_ref(&key, ExecutionRef::Pid { pid: 42, start_time: Some(100) }).await;
+
+        assert!(multiplexer.remove_sink(43).await.is_empty());
+        let mut members = multiplexer.remove_sink(42).await;
+        members.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(members, expected);
+        assert!(multiplexer.groups().await.is_empty());
+        // the next member starts a sink again
+        assert_eq!(multiplexer.join(&key, first, "http://first".into()).await, (key, None));
+    }
+
+    #[test]
+    fn test_is_group_of_key() {
+        assert!(is_group_of_key("abc", "abc"));
+        assert!(is_group_of_key("abc-2", "abc"));
+        assert!(!is_group_of_key("abcd", "abc"));
+        assert!(!is_group_of_key("abc", "abc-2"));
+    }
+
+    #[tokio::test]
+    async fn test_target_responses_followed_per_member() {
+        let multiplexer = Multiplexer::default();
+        let key = get_group_key(b"script", Some(1));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        multiplexer.join(&key, first, "http://first".into()).await;
+        multiplexer.join(&key, second, "http://second".into()).await;
+
+        let execution_ref = ExecutionRef::Pid { pid: 42, start_time: Some(100) };
+        multiplexer.watch_target_responses(&execution_ref, &[(first, Some(410)), (second, None)]);
+        let followed = |id: Uuid| multiplexer.target_gone.lock().unwrap().contains_key(&id);
+        assert!(followed(first));
+        assert!(!followed(second));
+
+        multiplexer.leave(first).await;
+        assert!(!followed(first));
+    }
+
+    #[test]
+    fn test_group_key_depends_on_starting_block() {
+        assert_eq!(get_group_key(b"script", Some(1)), get_group_key(b"script", Some(1)));
+        assert_ne!(get_group_key(b"script", Some(1)), get_group_key(b"script", Some(2)));
+        assert_ne!(get_group_key(b"script", Some(1)), get_group_key(b"other", Some(1)));
+    }
+}
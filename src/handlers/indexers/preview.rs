@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tokio::sync::{oneshot, Mutex, Semaphore};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::{MAX_CONCURRENT_PREVIEWS, PREVIEW_BLOCK_RANGE, PREVIEW_TIMEOUT_SECONDS};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::sink_options::{SinkOptions, WebhookOptions};
+use crate::handlers::indexers::indexer_types::{get_launch_env, get_permission_args};
+use crate::handlers::indexers::reaper::TrackedChild;
+use crate::handlers::indexers::start_indexer::get_latest_block;
+use crate::handlers::indexers::utils::{get_resolved_script, get_script_tmp_directory};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::env::get_environment_variable;
+use crate::utils::{OptionalJsonExtractor, PathExtractor};
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PreviewRequest {
+    pub starting_block: Option<u64>,
+    pub ending_block: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewModel {
+    pub starting_block: u64,
+    pub ending_block: u64,
+    /// First payload the script produced in the range, `None` if nothing matched its filter
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Payloads of the running previews by preview id
+static PREVIEWS: OnceLock<Mutex<HashMap<Uuid, oneshot::Sender<Bytes>>>> = OnceLock::new();
+
+fn previews() -> &'static Mutex<HashMap<Uuid, oneshot::Sender<Bytes>>> {
+    PREVIEWS.get_or_init(Mutex::default)
+}
+
+/// Bounds the sinks started by previews and by the samples of the estimates
+static PREVIEW_SLOTS: OnceLock<Semaphore> = OnceLock::new();
+
+pub fn preview_slots() -> &'static Semaphore {
+    PREVIEW_SLOTS.get_or_init(|| Semaphore::new(MAX_CONCURRENT_PREVIEWS))
+}
+
+/// Runs the script of the indexer over recent blocks and returns the first payload it would
+/// send. The script runs in a webhook sink of its own targeting the service, without any
+/// persistence so the cursor of the indexer isn't touched. The optional body selects the
+/// blocks, the last blocks of the stream are used by default. Only the tenant of the indexer and
+/// the admins can preview it, and only a few previews run at once.
+pub async fn preview_indexer(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    OptionalJsonExtractor(request): OptionalJsonExtractor<PreviewRequest>,
+) -> Result<Json<PreviewModel>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if !context.can_act_for_owner(indexer_model.tenant_id.as_deref()) {
+        return Err(IndexerError::IndexerAccessDenied(id));
+    }
+    let _slot = preview_slots().try_acquire().map_err(|_| IndexerError::TooManyPreviews)?;
+
+    let ending_block = match request.ending_block {
+        Some(ending_block) => ending_block,
+        None => get_latest_block(&indexer_model).await?,
+    };
+    let starting_block = request.starting_block.unwrap_or(ending_block.saturating_sub(PREVIEW_BLOCK_RANGE));
+    if starting_block > ending_block {
+        return Err(IndexerError::InvalidBlockRange(format!(
+            "starting block {} is after ending block {}",
+            starting_block, ending_block
+        )));
+    }
+
+    let config = config().await;
+    config.sandbox_policy().validate(&indexer_model.script_permissions)?;
+    let script = get_resolved_script(&indexer_model).await?;
+
+    let preview_id = Uuid::new_v4();
+    let script_path = get_script_tmp_directory(preview_id);
+    tokio::fs::write(&script_path, &script).await.map_err(IndexerError::FailedToCreateFile)?;
+
+    let (sender, receiver) = oneshot::channel();
+    previews().lock().await.insert(preview_id, sender);
+
+    let target_url = config.internal_url(&format!("/internal/preview/{}", preview_id));
+    let mut args = vec![
+        "run".to_string(),
+        script_path.clone(),
+        "--auth-token".to_string(),
+        get_environment_variable("APIBARA_AUTH_TOKEN"),
+        "--target-url".to_string(),
+        target_url,
+        // the sink always starts a status server, nothing queries the one of a preview so it
+        // binds any free port rather than one picked here which may be taken in between
+        "--status-server-address".to_string(),
+        "127.0.0.1:0".to_string(),
+        "--starting-block".to_string(),
+        starting_block.to_string(),
+        "--ending-block".to_string(),
+        ending_block.to_string(),
+    ];
+    if let Some(stream_url) = &indexer_model.stream_url {
+        args.extend(["--stream-url".to_string(), stream_url.clone()]);
+    }
+    args.extend(get_permission_args(&indexer_model));
+    // the payload is only wrapped with the cursors if the indexer doesn't send it raw
+    if let Some(SinkOptions::Webhook(WebhookOptions { raw: true, .. })) = &indexer_model.sink_options {
+        args.push("--raw".to_string());
+    }
+
+    let mut env = get_launch_env(&indexer_model);
+    env.insert("STARTING_BLOCK".to_string(), starting_block.to_string());
+
+    let result = async {
+        let mut child = TrackedChild::spawn(
+            Command::new(format!("{}/{}", get_environment_variable("BINARY_BASE_PATH"), "sink-webhook"))
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .envs(env)
+                .args(args)
+                .kill_on_drop(true),
+        )
+        .map_err(|e| IndexerError::FailedToStartIndexer(e.to_string(), id.to_string()))?;
+
+        let timeout = tokio::time::Duration::from_secs(PREVIEW_TIMEOUT_SECONDS);
+        let payload = tokio::select! {
+            payload = receiver => payload.ok(),
+            // the sink exits once it reaches the ending block
+            _ = child.wait() => None,
+            _ = tokio::time::sleep(timeout) => None,
+        };
+        Ok::<_, IndexerError>(payload)
+    }
+    .await;
+
+    previews().lock().await.remove(&preview_id);
+    if let Err(e) = tokio::fs::remove_file(&script_path).await {
+        tracing::warn!("Failed to remove the script of preview {}: {:?}", preview_id, e);
+    }
+
+    let payload = result?.map(|payload| serde_json::from_slice(&payload)).transpose().map_err(|e| {
+        IndexerError::InternalServerError(format!("preview payload of indexer {} is not JSON: {}", id, e))
+    })?;
+    Ok(Json(PreviewModel { starting_block, ending_block, payload }))
+}
+
+/// Receives the payloads of the preview sinks
+pub async fn receive_preview_payload(
+    State(_state): State<AppState>,
+    PathExtractor(preview_id): PathExtractor<Uuid>,
+    body: Bytes,
+) -> StatusCode {
+    match previews().lock().await.remove(&preview_id) {
+        Some(sender) => {
+            let _ = sender.send(body);
+            StatusCode::OK
+        }
+        None => StatusCode::NOT_FOUND,
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::READINESS_POLL_INTERVAL_MILLIS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::utils::{lock_indexer, query_status_server, record_event_with_reason};
+use crate::handlers::notifications::lifecycle::notify_status_change;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository, UpdateIndexerStatusDb};
+use crate::utils::actor_context::spawn_with_context;
+
+/// Moves the indexer to `Running` once its sink processed a block or its process survived the
+/// window. An indexer whose process exits before is failed by the exit handling, the execution it
+/// was started with is then no longer the current one and it's left alone.
+pub async fn watch_readiness(context: ActorContext, indexer_model: IndexerModel, window: Duration) {
+    let started_at = Instant::now();
+    let mut interval = tokio::time::interval(Duration::from_millis(READINESS_POLL_INTERVAL_MILLIS));
+    let reason = loop {
+        interval.tick().await;
+        if started_at.elapsed() >= window {
+            break format!("survived {}s", window.as_secs());
+        }
+        let Some(status_server_port) = indexer_model.status_server_port else {
+            continue;
+        };
+        // the status server isn't up until the sink connected to the stream
+        if query_status_server(status_server_port).await.is_ok_and(|status| status.has_processed_block()) {
+            break "processed its first block".to_string();
+        }
+    };
+
+    if let Err(e) = mark_running(&context, indexer_model.id, indexer_model.execution_seq, reason).await {
+        tracing::error!("Failed to move indexer {} to running: {:?}", indexer_model.id, e);
+    }
+}
+
+async fn mark_running(
+    context: &ActorContext,
+    id: Uuid,
+    execution_seq: i64,
+    reason: String,
+) -> Result<(), IndexerError> {
+    let _lock = lock_indexer(id).await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    // stopped, failed or restarted since
+    if indexer_model.status != IndexerStatus::Starting || indexer_model.execution_seq != execution_seq {
+        return Ok(());
+    }
+
+    let updated_indexer = repository
+        .update_status(UpdateIndexerStatusDb { id, status: IndexerStatus::Running.to_string() })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    record_event_with_reason(
+        context,
+        AuditAction::StatusChange,
+        Some(IndexerStatus::Starting),
+        Some(IndexerStatus::Running),
+        &updated_indexer,
+        Some(reason),
+    )
+    .await;
+    spawn_with_context(context, notify_status_change(context.clone(), id, IndexerStatus::Running));
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use tokio::process::{Child, Command};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::runtime::REAPER_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::execution::{ExecutionRef, StopExpectation};
+use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::handlers::admin::runtime::get_zombie_children;
+use crate::handlers::indexers::fail_indexer::fail_indexer_expecting;
+use crate::handlers::indexers::multiplexer::multiplexer;
+use crate::handlers::indexers::stop_indexer::update_indexer_state_expecting;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+
+/// Sinks whose exit is waited for by the task reading their output
+static TRACKED_PROCESSES: OnceLock<Mutex<HashSet<u32>>> = OnceLock::new();
+static REAPED_ORPHANS: AtomicU64 = AtomicU64::new(0);
+
+fn tracked_processes() -> std::sync::MutexGuard<'static, HashSet<u32>> {
+    TRACKED_PROCESSES.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn track_process(process_id: u32) {
+    tracked_processes().insert(process_id);
+}
+
+pub fn untrack_process(process_id: u32) {
+    tracked_processes().remove(&process_id);
+}
+
+/// Child process of the service other than a sink, e.g. a hook. It is tracked while the handle
+/// lives so that the reaper doesn't collect the exit status tokio waits for.
+pub struct TrackedChild {
+    child: Child,
+    process_id: Option<u32>,
+}
+
+impl TrackedChild {
+    pub fn spawn(command: &mut Command) -> std::io::Result<Self> {
+        let child = command.spawn()?;
+        let process_id = child.id();
+        if let Some(process_id) = process_id {
+            track_process(process_id);
+        }
+        Ok(Self { child, process_id })
+    }
+
+    pub async fn wait(&mut self) -> std::io::Result<ExitStatus> {
+        self.child.wait().await
+    }
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        if let Some(process_id) = self.process_id {
+            untrack_process(process_id);
+        }
+    }
+}
+
+/// Number of orphans reaped since the service started
+pub fn get_reaped_orphans() -> u64 {
+    REAPED_ORPHANS.load(Ordering::Relaxed)
+}
+
+/// Moves an indexer out of `Running` once its sink exited by itself. Nothing is done if the
+/// indexer was stopped in the meantime or runs another process already, e.g. after a restart.
+/// The execution is checked again under the lock of the indexer, it may have been restarted
+/// while we were waiting for it.
+pub async fn handle_process_exit(context: &ActorContext, indexer_id: Uuid, process_id: u32, exit_status: ExitStatus) {
+    // the members of a multiplexer group share the sink the first one started, the group goes
+    // away with it so that the next member starts a new sink instead of joining a dead one
+    let members = multiplexer().remove_sink(process_id).await;
+    handle_indexer_exit(context, indexer_id, process_id, exit_status).await;
+    for id in members.into_iter().filter(|id| *id != indexer_id) {
+        handle_indexer_exit(context, id, process_id, exit_status).await;
+    }
+}
+
+async fn handle_indexer_exit(context: &ActorContext, indexer_id: Uuid, process_id: u32, exit_status: ExitStatus) {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.consumers_pool());
+    let indexer_model = match repository.get(indexer_id).await {
+        Ok(indexer_model) => indexer_model,
+        Err(e) => {
+            tracing::error!("Failed to get indexer {} after its process exited: {:?}", indexer_id, e);
+            return;
+        }
+    };
+    if !indexer_model.status.is_live()
+        || indexer_model.execution_ref.as_ref().and_then(ExecutionRef::pid) != Some(process_id)
+    {
+        return;
+    }
+
+    let expected = StopExpectation::current(&indexer_model);
+    let result = match exit_status.success() {
+        true => update_indexer_state_expecting(context, indexer_id, IndexerStatus::Stopped, &expected).await,
+        false => fail_indexer_expecting(context, indexer_id, &expected).await,
+    };
+    match result {
+        // the indexer got stopped while we were waiting for its lock
+        Ok(()) | Err(IndexerError::InvalidIndexerStatus(_)) => (),
+        Err(e) => tracing::error!("Failed to update indexer {} after its process exited: {:?}", indexer_id, e),
+    }
+}
+
+/// Reaps the zombies no task is waiting for. Sinks are waited for by their own task but the
+/// processes they leave behind are reparented to the service when it runs as PID 1, e.g. in a
+/// container, and nobody collects their exit status otherwise. The children of the service are
+/// tracked, and a zombie is only reaped once it outlived a whole interval, so that the exit
+/// status of a child tokio is about to collect is never taken from it.
+pub async fn reap_orphans() {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(REAPER_INTERVAL_SECONDS));
+    let mut previous_zombies = HashSet::new();
+    loop {
+        interval.tick().await;
+        let zombies: HashSet<u32> = match get_zombie_children() {
+            Some(zombies) => zombies.into_iter().collect(),
+            None => continue,
+        };
+        let tracked = tracked_processes().clone();
+        let orphans: Vec<u32> = zombies
+            .iter()
+            .filter(|process_id| previous_zombies.contains(*process_id) && !tracked.contains(*process_id))
+            .copied()
+            .collect();
+        previous_zombies = zombies;
+        for process_id in orphans {
+            // SAFETY: only waits for a child which already exited, without blocking
+            let reaped = unsafe { libc::waitpid(process_id as libc::pid_t, std::ptr::null_mut(), libc::WNOHANG) };
+            if reaped == process_id as libc::pid_t {
+                REAPED_ORPHANS.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!("Reaped orphan process {}", process_id);
+            }
+        }
+    }
+}
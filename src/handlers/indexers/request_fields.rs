@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequest, Multipart};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::Request;
+use axum::{async_trait, Json};
+use base64::engine::general_purpose;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use strum_macros::EnumString;
+
+use crate::config::config;
+use crate::domain::models::indexer::{IndexerError, IndexerType};
+
+/// Field of the create request
+pub struct FieldSpec {
+    pub name: &'static str,
+    /// Indexer types the field applies to, every type if empty
+    pub indexer_types: &'static [IndexerType],
+}
+
+const fn field(name: &'static str) -> FieldSpec {
+    FieldSpec { name, indexer_types: &[] }
+}
+
+pub const CREATE_INDEXER_FIELDS: &[FieldSpec] = &[
+    field("script.js"),
+    // script uploaded beforehand through a resumable upload session
+    field("upload_id"),
+    // script fetched by the service from a url
+    field("script_url"),
+    // sha256 the script must match, whatever field it is sent as
+    field("script_sha256"),
+    field("indexer_type"),
+    FieldSpec { name: "target_url", indexer_types: &[IndexerType::Webhook] },
+    FieldSpec { name: "table_name", indexer_types: &[IndexerType::Postgres] },
+    // database the sink writes to, the one of the service if not set
+    FieldSpec { name: "custom_connection_string", indexer_types: &[IndexerType::Postgres] },
+    FieldSpec { name: "output_bucket", indexer_types: &[IndexerType::Parquet] },
+    // the files are written under the id of the indexer if not set
+    FieldSpec { name: "output_prefix", indexer_types: &[IndexerType::Parquet] },
+    field("starting_block"),
+    field("ending_block"),
+    field("priority"),
+    field("indexer_id"),
+    field("log_level"),
+    field("tenant_id"),
+    field("project_id"),
+    field("stream_url"),
+    field("script_permissions"),
+    field("script_params"),
+    field("sink_options"),
+    field("process_priority"),
+    field("hooks"),
+];
+
+/// Fields the script can be sent as, exactly one of them is needed
+const SCRIPT_FIELDS: &[&str] = &["script.js", "upload_id", "script_url"];
+
+/// Fields every indexer of a type needs on top of the script
+fn required_fields(indexer_type: &IndexerType) -> &'static [&'static str] {
+    match indexer_type {
+        IndexerType::Webhook => &["target_url"],
+        IndexerType::Postgres => &["table_name"],
+        IndexerType::Parquet => &["output_bucket"],
+        IndexerType::Console => &[],
+    }
+}
+
+/// Fields of the create request, sent as a multipart body or as a JSON object. The fields are all
+/// read before any of them is parsed so that they can be sent in any order, e.g. `indexer_type`
+/// after the fields depending on it.
+#[derive(Debug, Default)]
+pub struct CreateIndexerFields {
+    fields: BTreeMap<String, Bytes>,
+}
+
+/// How the script of a JSON create request is encoded
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ScriptEncoding {
+    #[default]
+    Plain,
+    Base64,
+}
+
+#[async_trait]
+impl<S> FromRequest<S, Body> for CreateIndexerFields
+where
+    S: Send + Sync,
+{
+    type Rejection = IndexerError;
+
+    async fn from_request(request: Request<Body>, state: &S) -> Result<Self, Self::Rejection> {
+        let strict = config().await.multipart_strict_mode();
+        let is_json = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with(mime::APPLICATION_JSON.as_ref()));
+
+        if is_json {
+            let Json(body) = Json::<serde_json::Map<String, Value>>::from_request(request, state)
+                .await
+                .map_err(|rejection| IndexerError::InvalidRequestBody(rejection.body_text()))?;
+            return Self::from_json(body, strict);
+        }
+
+        let mut multipart = Multipart::from_request(request, state)
+            .await
+            .map_err(|rejection| IndexerError::InvalidRequestBody(rejection.body_text()))?;
+        let mut fields = Self::default();
+        while let Some(field) = multipart.next_field().await.map_err(IndexerError::FailedToReadMultipartField)? {
+            let name = field.name().unwrap_or_default().to_string();
+            let value = field.bytes().await.map_err(IndexerError::FailedToReadMultipartField)?;
+            fields.insert(name, value, strict)?;
+        }
+
+        let indexer_type = fields.indexer_type()?;
+        fields.validate(&indexer_type, strict)?;
+        Ok(fields)
+    }
+}
+
+impl CreateIndexerFields {
+    /// Reads the fields from a JSON object, as if it was the body of a create request
+    pub fn from_json(body: serde_json::Map<String, Value>, strict: bool) -> Result<Self, IndexerError> {
+        let mut fields = Self::default();
+        fields.insert_json(body, strict)?;
+        let indexer_type = fields.indexer_type()?;
+        fields.validate(&indexer_type, strict)?;
+        Ok(fields)
+    }
+
+    fn insert(&mut self, name: String, value: Bytes, strict: bool) -> Result<(), IndexerError> {
+        if !CREATE_INDEXER_FIELDS.iter().any(|spec| spec.name == name) {
+            if strict {
+                return Err(IndexerError::UnexpectedMultipartField(name));
+            }
+            tracing::warn!("Ignoring unexpected field {} in the create request", name);
+            return Ok(());
+        }
+        if self.fields.contains_key(&name) {
+            return Err(IndexerError::DuplicateMultipartField(name));
+        }
+        self.fields.insert(name, value);
+        Ok(())
+    }
+
+    /// JSON bodies use the names of the multipart fields, except for the script which is sent as
+    /// `script` along with its `script_encoding`. Strings are taken as is, like multipart fields,
+    /// objects are kept as JSON.
+    fn insert_json(&mut self, mut body: serde_json::Map<String, Value>, strict: bool) -> Result<(), IndexerError> {
+        let encoding = match body.remove("script_encoding") {
+            Some(Value::String(encoding)) => ScriptEncoding::from_str(&encoding).map_err(|_| {
+                IndexerError::InvalidMultipartField("script_encoding".into(), format!("unknown encoding {}", encoding))
+            })?,
+            Some(_) => {
+                return Err(IndexerError::InvalidMultipartField(
+                    "script_encoding".into(),
+                    "expected plain or base64".into(),
+                ));
+            }
+            None => ScriptEncoding::default(),
+        };
+        if let Some(script) = body.remove("script") {
+            let script = match (script, encoding) {
+                (Value::String(script), ScriptEncoding::Plain) => Bytes::from(script),
+                (Value::String(script), ScriptEncoding::Base64) => Bytes::from(
+                    general_purpose::STANDARD
+                        .decode(script)
+                        .map_err(|e| IndexerError::InvalidMultipartField("script".into(), e.to_string()))?,
+                ),
+                _ => return Err(IndexerError::InvalidMultipartField("script".into(), "expected a string".into())),
+            };
+            self.insert("script.js".into(), script, strict)?;
+        }
+
+        for (name, value) in body {
+            let value = match value {
+                Value::Null => continue,
+                Value::String(value) => Bytes::from(value),
+                value => Bytes::from(value.to_string()),
+            };
+            self.insert(name, value, strict)?;
+        }
+        Ok(())
+    }
+
+    pub fn indexer_type(&self) -> Result<IndexerType, IndexerError> {
+        match self.text("indexer_type")? {
+            Some(indexer_type) => {
+                IndexerType::from_str(&indexer_type).map_err(|_| IndexerError::InvalidIndexerType(indexer_type))
+            }
+            None => Ok(IndexerType::default()),
+        }
+    }
+
+    /// Checks the fields against the ones the indexer type accepts. Fields of other types are
+    /// ignored unless `strict` is set.
+    fn validate(&mut self, indexer_type: &IndexerType, strict: bool) -> Result<(), IndexerError> {
+        let not_applicable: Vec<String> = CREATE_INDEXER_FIELDS
+            .iter()
+            .filter(|spec| !spec.indexer_types.is_empty() && !spec.indexer_types.contains(indexer_type))
+            .filter(|spec| self.fields.contains_key(spec.name))
+            .map(|spec| spec.name.to_string())
+            .collect();
+        for name in not_applicable {
+            if strict {
+                return Err(IndexerError::MultipartFieldNotApplicable(name, indexer_type.clone()));
+            }
+            tracing::warn!("Ignoring field {} which doesn't apply to {} indexers", name, indexer_type);
+            self.fields.remove(&name);
+        }
+
+        let script_fields: Vec<&str> =
+            SCRIPT_FIELDS.iter().copied().filter(|name| self.fields.contains_key(*name)).collect();
+        if let [first, second, ..] = script_fields.as_slice() {
+            return Err(IndexerError::InvalidMultipartField(
+                second.to_string(),
+                format!("the script is already sent as {}", first),
+            ));
+        }
+        let mut missing: Vec<String> = required_fields(indexer_type)
+            .iter()
+            .filter(|name| !self.fields.contains_key(**name))
+            .map(|name| name.to_string())
+            .collect();
+        if script_fields.is_empty() {
+            missing.insert(0, "script.js".into());
+        }
+        if !missing.is_empty() {
+            return Err(IndexerError::MissingMultipartFields(missing));
+        }
+        Ok(())
+    }
+
+    pub fn bytes(&self, name: &str) -> Option<Bytes> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn text(&self, name: &str) -> Result<Option<String>, IndexerError> {
+        self.fields
+            .get(name)
+            .map(|value| {
+                String::from_utf8(value.to_vec())
+                    .map_err(|_| IndexerError::InvalidMultipartField(name.into(), "not valid UTF-8".into()))
+            })
+            .transpose()
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<Option<T>, IndexerError>
+    where
+        T::Err: ToString,
+    {
+        self.text(name)?
+            .map(|value| {
+                value.parse().map_err(|e: T::Err| IndexerError::InvalidMultipartField(name.into(), e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// JSON fields are reported with the error of their own validation
+    pub fn json<T: DeserializeOwned>(
+        &self,
+        name: &str,
+        error: impl Fn(String) -> IndexerError,
+    ) -> Result<Option<T>, IndexerError> {
+        self.text(name)?.map(|value| serde_json::from_str(&value).map_err(|e| error(e.to_string()))).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn fields(names: &[&str]) -> CreateIndexerFields {
+        CreateIndexerFields {
+            fields: names.iter().map(|name| (name.to_string(), Bytes::from_static(b"value"))).collect(),
+        }
+    }
+
+    #[rstest]
+    #[case(&["script.js", "target_url"], IndexerType::Webhook, true, None)]
+    #[case(&["upload_id", "table_name"], IndexerType::Postgres, true, None)]
+    #[case(&["script_url", "script_sha256", "target_url"], IndexerType::Webhook, true, None)]
+    #[case(
+        &["upload_id", "script_url", "table_name"],
+        IndexerType::Postgres,
+        true,
+        Some("invalid field script_url: the script is already sent as upload_id")
+    )]
+    #[case(&["script.js"], IndexerType::Webhook, true, Some("missing fields: target_url"))]
+    #[case(&["table_name"], IndexerType::Postgres, true, Some("missing fields: script.js"))]
+    #[case(&[], IndexerType::Postgres, true, Some("missing fields: script.js, table_name"))]
+    #[case(
+        &["script.js", "upload_id", "target_url"],
+        IndexerType::Webhook,
+        true,
+        Some("invalid field upload_id: the script is already sent as script.js")
+    )]
+    #[case(
+        &["script.js", "target_url", "table_name"],
+        IndexerType::Webhook,
+        true,
+        Some("field table_name doesn't apply to Webhook indexers")
+    )]
+    #[case(&["script.js", "target_url", "table_name"], IndexerType::Webhook, false, None)]
+    #[case(&["script.js", "table_name", "custom_connection_string"], IndexerType::Postgres, true, None)]
+    #[case(
+        &["script.js", "target_url", "custom_connection_string"],
+        IndexerType::Webhook,
+        true,
+        Some("field custom_connection_string doesn't apply to Webhook indexers")
+    )]
+    #[case(&["script.js", "output_bucket", "output_prefix"], IndexerType::Parquet, true, None)]
+    #[case(&["script.js"], IndexerType::Console, true, None)]
+    #[case(&["script.js", "output_prefix"], IndexerType::Parquet, true, Some("missing fields: output_bucket"))]
+    #[case(
+        &["script.js", "target_url", "output_bucket"],
+        IndexerType::Webhook,
+        true,
+        Some("field output_bucket doesn't apply to Webhook indexers")
+    )]
+    fn test_validate(
+        #[case] names: &[&str],
+        #[case] indexer_type: IndexerType,
+        #[case] strict: bool,
+        #[case] expected_error: Option<&str>,
+    ) {
+        let mut fields = fields(names);
+        let result = fields.validate(&indexer_type, strict);
+        assert_eq!(result.err().map(|e| e.to_string()).as_deref(), expected_error);
+    }
+
+    #[test]
+    fn test_insert_json() {
+        let body = serde_json::json!({
+            "script": "Y29uc29sZS5sb2coMSk=",
+            "script_encoding": "base64",
+            "indexer_type": "Webhook",
+            "target_url": "https://example.com",
+            "starting_block": 100,
+            "script_params": { "NETWORK": "mainnet" },
+            "stream_url": null,
+        });
+        let mut fields = CreateIndexerFields::default();
+        fields.insert_json(body.as_object().unwrap().clone(), true).unwrap();
+        assert_eq!(fields.bytes("script.js"), Some(Bytes::from_static(b"console.log(1)")));
+        assert_eq!(fields.text("target_url").unwrap(), Some("https://example.com".to_string()));
+        assert_eq!(fields.parse::<i64>("starting_block").unwrap(), Some(100));
+        assert_eq!(fields.text("script_params").unwrap(), Some(r#"{"NETWORK":"mainnet"}"#.to_string()));
+        assert_eq!(fields.text("stream_url").unwrap(), None);
+
+        let body = serde_json::json!({ "script": "console.log(1)", "unknown": true });
+        let mut fields = CreateIndexerFields::default();
+        let error = fields.insert_json(body.as_object().unwrap().clone(), true).unwrap_err();
+        assert_eq!(error.to_string(), "unexpected field in create request : unknown");
+    }
+
+    #[test]
+    fn test_parse() {
+        let fields = CreateIndexerFields {
+            fields: BTreeMap::from([
+                ("starting_block".to_string(), Bytes::from_static(b"100")),
+                ("priority".to_string(), Bytes::from_static(b"high")),
+            ]),
+        };
+        assert_eq!(fields.parse::<i64>("starting_block").unwrap(), Some(100));
+        assert_eq!(fields.parse::<i64>("ending_block").unwrap(), None);
+        assert_eq!(
+            fields.parse::<i32>("priority").unwrap_err().to_string(),
+            "invalid field priority: invalid digit found in string"
+        );
+    }
+}
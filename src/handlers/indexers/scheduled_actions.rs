@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::{
+    SCHEDULED_ACTIONS_BATCH_SIZE, SCHEDULED_ACTIONS_LEASE_SECONDS, SCHEDULED_ACTIONS_MAX_ATTEMPTS,
+    SCHEDULED_ACTIONS_POLL_INTERVAL_MILLIS, SCHEDULED_ACTIONS_RETRY_DELAY_SECONDS, SCHEDULE_PREVIEW_DEFAULT_COUNT,
+    SCHEDULE_PREVIEW_MAX_COUNT,
+};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::domain::models::scheduled_action::{
+    get_retry_delay, parse_timezone, to_timezone, PlannedRunModel, SchedulePreviewModel, ScheduledActionKind,
+    ScheduledActionModel,
+};
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::stop_indexer::stop_indexer_with_reason;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::infra::repositories::scheduled_action_repository::{ScheduledActionFilter, ScheduledActionRepository};
+use crate::utils::{PathExtractor, QueryExtractor};
+use crate::AppState;
+
+const SCHEDULED_ACTION_REASON: &str = "scheduled action";
+
+/// Random delay up to `max_millis`, spreading the work of several instances and retries
+fn jitter_millis(max_millis: u64) -> u64 {
+    if max_millis == 0 {
+        return 0;
+    }
+    (Uuid::new_v4().as_u128() % max_millis as u128) as u64
+}
+
+/// Runs the scheduled actions once they are due
+pub async fn monitor_scheduled_actions() {
+    loop {
+        let delay = SCHEDULED_ACTIONS_POLL_INTERVAL_MILLIS + jitter_millis(SCHEDULED_ACTIONS_POLL_INTERVAL_MILLIS / 2);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        if let Err(e) = run_due_actions().await {
+            tracing::error!("Failed to run the scheduled actions: {:?}", e);
+        }
+    }
+}
+
+async fn run_due_actions() -> Result<(), IndexerError> {
+    let config = config().await;
+    let mut repository = ScheduledActionRepository::new(config.background_pool());
+    let actions = repository
+        .claim_due(Utc::now(), chrono::Duration::seconds(SCHEDULED_ACTIONS_LEASE_SECONDS), SCHEDULED_ACTIONS_BATCH_SIZE)
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    for action in actions {
+        // recurring actions aren't done once they ran, even if they failed for good
+        let next_run = action.get_next_run(Utc::now());
+        let result = match run_action(&action).await {
+            Ok(()) => match next_run {
+                Some(next_run) => repository.advance(action.id, next_run, None).await,
+                None => repository.delete(action.id).await.map(|_| ()),
+            },
+            Err(e) if action.attempts >= SCHEDULED_ACTIONS_MAX_ATTEMPTS => {
+                tracing::error!("Scheduled {} of indexer {} failed for good: {:?}", action.kind, action.indexer_id, e);
+                match next_run {
+                    Some(next_run) => repository.advance(action.id, next_run, Some(e.to_string())).await,
+                    None => repository.mark_failed(action.id, e.to_string()).await,
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Scheduled {} of indexer {} failed, retrying: {:?}", action.kind, action.indexer_id, e);
+                let delay = get_retry_delay(
+                    action.attempts,
+                    SCHEDULED_ACTIONS_RETRY_DELAY_SECONDS,
+                    jitter_millis(SCHEDULED_ACTIONS_RETRY_DELAY_SECONDS as u64 * 1000) as i64,
+                );
+                repository.reschedule(action.id, Utc::now() + delay, e.to_string()).await
+            }
+        };
+        // the action runs again once its lock expires
+        if let Err(e) = result {
+            tracing::error!("Failed to record the outcome of scheduled action {}: {:?}", action.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// An action may run more than once, running it again once it took effect does nothing
+async fn run_action(action: &ScheduledActionModel) -> Result<(), IndexerError> {
+    let context = ActorContext::system();
+    match action.kind {
+        ScheduledActionKind::Start => start_indexer(&context, action.indexer_id).await,
+        ScheduledActionKind::Stop => stop(&context, action.indexer_id).await,
+        ScheduledActionKind::Restart => {
+            stop(&context, action.indexer_id).await?;
+            start_indexer(&context, action.indexer_id).await
+        }
+    }
+}
+
+async fn stop(context: &ActorContext, indexer_id: Uuid) -> Result<(), IndexerError> {
+    match stop_indexer_with_reason(context, indexer_id, Some(SCHEDULED_ACTION_REASON.to_string())).await {
+        Err(IndexerError::InvalidIndexerStatus(IndexerStatus::Stopped)) => Ok(()),
+        result => result,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchedulePreviewQuery {
+    pub count: Option<usize>,
+    /// IANA name of the timezone the runs are returned in, UTC by default
+    pub timezone: Option<String>,
+}
+
+/// Next planned runs of the scheduled actions of the indexer, one-off and recurring ones
+/// together, in the timezone of the caller
+pub async fn get_schedule_preview(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<SchedulePreviewQuery>,
+) -> Result<Json<SchedulePreviewModel>, IndexerError> {
+    let count = query.count.unwrap_or(SCHEDULE_PREVIEW_DEFAULT_COUNT);
+    if count == 0 || count > SCHEDULE_PREVIEW_MAX_COUNT {
+        return Err(IndexerError::InvalidScheduleQuery(format!(
+            "count must be between 1 and {}",
+            SCHEDULE_PREVIEW_MAX_COUNT
+        )));
+    }
+    let timezone_name = query.timezone.unwrap_or_else(|| "UTC".to_string());
+    let timezone = parse_timezone(&timezone_name).map_err(IndexerError::InvalidScheduleQuery)?;
+
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let actions = ScheduledActionRepository::new(&state.pool)
+        .get_all(ScheduledActionFilter { indexer_id: Some(id), include_failed: false })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    let mut runs: Vec<_> = actions
+        .iter()
+        .flat_map(|action| action.get_planned_runs(count).into_iter().map(move |run_at| (run_at, action)))
+        .collect();
+    runs.sort_by_key(|(run_at, _)| *run_at);
+    let runs = runs
+        .into_iter()
+        .take(count)
+        .map(|(run_at, action)| PlannedRunModel {
+            action_id: action.id,
+            kind: action.kind,
+            run_at: to_timezone(run_at, timezone),
+            recurring: action.cron.is_some(),
+        })
+        .collect();
+
+    Ok(Json(SchedulePreviewModel { indexer_id: id, timezone: timezone_name, runs }))
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::AsyncPgConnection;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::config::{config, ScriptScanConfig};
+use crate::constants::indexers::{OSV_QUERY_TIMEOUT_SECONDS, SCRIPT_SCAN_MAX_AGE_SECONDS};
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::script_scan::{OsvQueryResponse, ScriptDependency, ScriptFinding, ScriptScanModel};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::infra::repositories::script_scan_repository::{NewScriptScanDb, ScriptScanRepository};
+use crate::utils::http::http_client;
+use crate::utils::script_cache::get_script_checksum;
+use crate::utils::script_dependencies::extract_dependencies;
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Checks the dependencies of the script against the OSV advisories and records the findings
+/// under the checksum of the script. A recent scan of the same script is reused. Every write of
+/// a script goes through it. If the advisories can't be queried the script is refused when
+/// scripts can be blocked, otherwise the findings just aren't recorded.
+pub async fn scan_script(
+    pool: &Pool<AsyncPgConnection>,
+    script: &str,
+) -> Result<Option<ScriptScanModel>, IndexerError> {
+    let config = config().await;
+    let Some(scan_config) = config.script_scan() else {
+        return Ok(None);
+    };
+
+    let script_checksum = get_script_checksum(script.as_bytes());
+    let mut repository = ScriptScanRepository::new(pool);
+    let scan = match repository.get(&script_checksum).await.map_err(IndexerError::InfraError)? {
+        Some(scan) if (Utc::now() - scan.scanned_at).num_seconds() < SCRIPT_SCAN_MAX_AGE_SECONDS => scan,
+        _ => {
+            let dependencies = extract_dependencies(script);
+            let findings = match query_advisories(scan_config, &dependencies).await {
+                Ok(findings) => findings,
+                Err(e) if scan_config.block_severity.is_some() => return Err(IndexerError::FailedToScanScript(e)),
+                Err(e) => {
+                    tracing::warn!("Failed to query the advisories of script {}: {}", script_checksum, e);
+                    return Ok(None);
+                }
+            };
+            repository
+                .upsert(NewScriptScanDb {
+                    script_checksum,
+                    dependencies: serde_json::to_value(&dependencies)
+                        .map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?,
+                    findings: serde_json::to_value(&findings)
+                        .map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?,
+                })
+                .await
+                .map_err(IndexerError::InfraError)?
+        }
+    };
+
+    if let Some(block_severity) = scan_config.block_severity {
+        let blocking = scan.get_blocking_findings(block_severity);
+        if !blocking.is_empty() {
+            let advisories: Vec<String> = blocking
+                .iter()
+                .map(|finding| format!("{} in {}@{}", finding.advisory_id, finding.package, finding.version))
+                .collect();
+            return Err(IndexerError::VulnerableScript(advisories.join(", ")));
+        }
+    }
+    Ok(Some(scan))
+}
+
+/// One query per dependency, the batch endpoint of OSV only returns the ids of the advisories
+async fn query_advisories(
+    scan_config: &ScriptScanConfig,
+    dependencies: &[ScriptDependency],
+) -> Result<Vec<ScriptFinding>, String> {
+    let url = format!("{}/v1/query", scan_config.osv_api_url.trim_end_matches('/'));
+    let mut findings = vec![];
+    for dependency in dependencies {
+        let response = http_client()
+            .post(&url)
+            .timeout(Duration::from_secs(OSV_QUERY_TIMEOUT_SECONDS))
+            .json(&json!({
+                "version": dependency.version,
+                "package": { "name": dependency.name, "ecosystem": "npm" },
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        let response: OsvQueryResponse = response.json().await.map_err(|e| e.to_string())?;
+        findings.extend(response.vulns.into_iter().map(|vulnerability| vulnerability.into_finding(dependency)));
+    }
+    Ok(findings)
+}
+
+/// Findings of the scan of the script the indexer runs
+pub async fn get_script_scan(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<ScriptScanModel>, IndexerError> {
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let Some(script_checksum) = indexer_model.script_checksum else {
+        return Err(IndexerError::ScriptScanNotFound(id));
+    };
+    let scan = ScriptScanRepository::new(&state.pool)
+        .get(&script_checksum)
+        .await
+        .map_err(IndexerError::InfraError)?
+        .ok_or(IndexerError::ScriptScanNotFound(id))?;
+
+    Ok(Json(scan))
+}
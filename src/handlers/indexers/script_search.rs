@@ -0,0 +1,89 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::config::config;
+use crate::constants::indexers::{
+    SCRIPT_INDEX_INTERVAL_SECONDS, SCRIPT_SEARCH_MAX_RESULTS, SCRIPT_SEARCH_SNIPPET_CONTEXT,
+};
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::script_search::{get_snippet, ScriptSearchQuery, ScriptSearchResult};
+use crate::handlers::indexers::utils::get_script;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::infra::repositories::script_index_repository::{NewScriptIndexDb, ScriptIndexRepository};
+use crate::utils::QueryExtractor;
+use crate::AppState;
+
+/// Periodically indexes the scripts of new indexers and those whose script changed, only run
+/// if `SCRIPT_SEARCH_ENABLED` is set
+pub async fn monitor_script_index() {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(SCRIPT_INDEX_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        match index_scripts().await {
+            Ok(0) => (),
+            Ok(indexed) => tracing::info!("Indexed {} scripts for search", indexed),
+            Err(e) => tracing::error!("Failed to index the scripts: {:?}", e),
+        }
+    }
+}
+
+/// Returns the number of scripts indexed. Scripts are indexed as uploaded, before their params
+/// are resolved.
+pub async fn index_scripts() -> Result<usize, IndexerError> {
+    let config = config().await;
+    let indexers = IndexerRepository::new(config.background_pool())
+        .get_all(IndexerFilter { status: None })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    let mut repository = ScriptIndexRepository::new(config.background_pool());
+    let checksums = repository.get_checksums().await.map_err(IndexerError::InfraError)?;
+
+    let mut indexed = 0;
+    for indexer_model in indexers {
+        // scripts without a checksum are only indexed once
+        if checksums.get(&indexer_model.id).is_some_and(|checksum| *checksum == indexer_model.script_checksum) {
+            continue;
+        }
+        let script = match get_script(&indexer_model).await {
+            Ok(script) => script,
+            Err(e) => {
+                tracing::warn!("Failed to get the script of indexer {} to index it: {:?}", indexer_model.id, e);
+                continue;
+            }
+        };
+        repository
+            .upsert(NewScriptIndexDb {
+                indexer_id: indexer_model.id,
+                script_checksum: indexer_model.script_checksum.clone(),
+                content: String::from_utf8_lossy(&script).replace('\0', ""),
+            })
+            .await
+            .map_err(IndexerError::InfraError)?;
+        indexed += 1;
+    }
+
+    Ok(indexed)
+}
+
+/// Finds the indexers whose script mentions the query, e.g. `?q=0xabc` for the indexers of a
+/// contract. Only scripts indexed by the background job are searched.
+pub async fn search_scripts(
+    State(state): State<AppState>,
+    QueryExtractor(query): QueryExtractor<ScriptSearchQuery>,
+) -> Result<Json<Vec<ScriptSearchResult>>, IndexerError> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(IndexerError::InvalidSearchQuery("the query is empty".into()));
+    }
+    let repository = ScriptIndexRepository::new(&state.pool);
+    let matches = repository.search(q, SCRIPT_SEARCH_MAX_RESULTS).await.map_err(IndexerError::InfraError)?;
+
+    let results = matches
+        .into_iter()
+        .map(|(indexer, content)| ScriptSearchResult {
+            indexer,
+            snippet: get_snippet(&content, q, SCRIPT_SEARCH_SNIPPET_CONTEXT),
+        })
+        .collect();
+    Ok(Json(results))
+}
@@ -0,0 +1,90 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::Json;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::SIMULATED_DELIVERY_RESPONSE_BODY_LIMIT;
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::simulation::{
+    sample_script_output, to_webhook_body, SimulateDeliveryRequest, SimulatedDeliveryModel, SAMPLE_BLOCK_NUMBER,
+};
+use crate::domain::models::sink_options::{SinkOptions, WebhookOptions};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Set on the simulated deliveries so that receivers can tell them from real ones
+pub const SIMULATION_HEADER: &str = "x-indexer-simulation";
+
+/// Sends a payload to the target of the indexer the way its sink would, with its headers, and
+/// reports how the target answered. The service can't run the script without a stream, the
+/// payload is a canned one unless the body gives the output of the script, e.g. from a preview.
+pub async fn simulate_delivery(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    body: Bytes,
+) -> Result<Json<SimulatedDeliveryModel>, IndexerError> {
+    let request: SimulateDeliveryRequest = if body.is_empty() {
+        SimulateDeliveryRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| IndexerError::InvalidRequestBody(e.to_string()))?
+    };
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let target_url = indexer_model
+        .target_url
+        .clone()
+        .ok_or_else(|| IndexerError::InvalidTargetUrl(format!("indexer {} has no target url", id)))?;
+    // the resolved addresses may have changed since the indexer was created
+    config().await.target_policy().validate(&target_url).await?;
+
+    let options = match &indexer_model.sink_options {
+        Some(SinkOptions::Webhook(options)) => options.clone(),
+        _ => WebhookOptions::default(),
+    };
+    let output = request.payload.unwrap_or_else(sample_script_output);
+    let payload = to_webhook_body(output, SAMPLE_BLOCK_NUMBER, options.raw);
+
+    // a redirect would reach a destination the target policy didn't check, and its answer is
+    // returned to the caller
+    let mut builder = state.http.without_redirects().post(&target_url).header(SIMULATION_HEADER, "true").json(&payload);
+    for header in &options.headers {
+        if let Some((name, value)) = header.split_once(':') {
+            builder = builder.header(name.trim(), value.trim());
+        }
+    }
+
+    let started_at = Instant::now();
+    let (status_code, response_body, error) = match builder.send().await {
+        Ok(response) => {
+            let status_code = response.status().as_u16();
+            let response_body = read_body_prefix(response, SIMULATED_DELIVERY_RESPONSE_BODY_LIMIT).await;
+            (Some(status_code), response_body, None)
+        }
+        Err(e) => (None, None, Some(e.to_string())),
+    };
+
+    Ok(Json(SimulatedDeliveryModel {
+        target_url,
+        payload,
+        status_code,
+        latency_ms: started_at.elapsed().as_millis() as u64,
+        response_body,
+        error,
+    }))
+}
+
+/// First `limit` bytes of the body, the rest is never read
+async fn read_body_prefix(mut response: reqwest::Response, limit: usize) -> Option<String> {
+    let mut body = Vec::new();
+    while body.len() < limit {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk[..chunk.len().min(limit - body.len())]),
+            Ok(None) => break,
+            Err(_) if body.is_empty() => return None,
+            Err(_) => break,
+        }
+    }
+    Some(String::from_utf8_lossy(&body).to_string())
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::STALE_CREATED_SWEEP_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::domain::models::stale_created::{StaleCreatedPolicy, StaleCreatedStep};
+use crate::handlers::indexers::start_indexer::start_indexer_expecting;
+use crate::handlers::indexers::utils::{lock_indexer, record_event_with_reason};
+use crate::handlers::notifications::lifecycle::notify_status_change_with_reason;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository, UpdateIndexerStatusDb};
+use crate::infra::repositories::tenant_repository::TenantRepository;
+use crate::utils::actor_context::spawn_with_context;
+
+/// Starts replayed by the sweep, with the time of the last one, per indexer still in `Created`
+static REPLAYED_STARTS: OnceLock<Mutex<HashMap<Uuid, (u32, DateTime<Utc>)>>> = OnceLock::new();
+
+fn replayed_starts() -> MutexGuard<'static, HashMap<Uuid, (u32, DateTime<Utc>)>> {
+    REPLAYED_STARTS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Looks for the indexers left in `Created`, e.g. when their start was lost, and replays their
+/// start or abandons them as set by their tenant
+pub async fn monitor_stale_created() {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(STALE_CREATED_SWEEP_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = sweep_stale_created().await {
+            tracing::error!("Failed to sweep the indexers stuck in Created: {:?}", e);
+        }
+    }
+}
+
+async fn sweep_stale_created() -> Result<(), IndexerError> {
+    let context = ActorContext::system();
+    let config = config().await;
+    let created = IndexerRepository::new(config.pool()).get_all_created().await.map_err(IndexerError::InfraError)?;
+    // indexers which left `Created` start over if they ever come back to it
+    replayed_starts().retain(|id, _| created.iter().any(|(indexer_model, _)| indexer_model.id == *id));
+
+    let tenant_repository = TenantRepository::new(config.pool());
+    let mut policies: HashMap<Option<String>, StaleCreatedPolicy> = HashMap::new();
+    let now = Utc::now();
+    for (indexer_model, created_at) in created {
+        let policy = match policies.get(&indexer_model.tenant_id) {
+            Some(policy) => *policy,
+            None => {
+                let settings = match &indexer_model.tenant_id {
+                    Some(tenant_id) => {
+                        tenant_repository.get_settings(tenant_id).await.map_err(IndexerError::InfraError)?
+                    }
+                    None => None,
+                };
+                let policy = StaleCreatedPolicy::for_tenant(settings.as_ref());
+                policies.insert(indexer_model.tenant_id.clone(), policy);
+                policy
+            }
+        };
+
+        let id = indexer_model.id;
+        let (replays, last_replay_at) = match replayed_starts().get(&id) {
+            Some((replays, last_replay_at)) => (*replays, Some(*last_replay_at)),
+            None => (0, None),
+        };
+        match policy.next_step(created_at, now, replays, last_replay_at) {
+            None => (),
+            Some(StaleCreatedStep::Replay) => {
+                tracing::warn!("Indexer {} is stuck in Created since {}, replaying its start", id, created_at);
+                replayed_starts().insert(id, (replays + 1, now));
+                // starts can wait for the quota of the tenant, the sweep doesn't
+                spawn_with_context(&context, async move {
+                    if let Err(e) = start_indexer_expecting(&ActorContext::system(), id, IndexerStatus::Created).await {
+                        tracing::error!("Failed to replay the start of indexer {}: {:?}", id, e);
+                    }
+                });
+            }
+            Some(StaleCreatedStep::Abandon) => {
+                let reason = match replays {
+                    0 => format!("stuck in Created since {}", created_at),
+                    _ => format!("stuck in Created since {}, after {} replayed starts", created_at, replays),
+                };
+                match abandon_indexer(&context, id, reason).await {
+                    // started in the meantime
+                    Ok(()) | Err(IndexerError::InvalidIndexerStatus(_)) => (),
+                    Err(e) => tracing::error!("Failed to abandon indexer {}: {:?}", id, e),
+                }
+                replayed_starts().remove(&id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves an indexer which never left `Created` to `Abandoned`. It can still be started or
+/// deleted.
+pub async fn abandon_indexer(context: &ActorContext, id: Uuid, reason: String) -> Result<(), IndexerError> {
+    let _lock = lock_indexer(id).await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if indexer_model.status != IndexerStatus::Created {
+        return Err(IndexerError::InvalidIndexerStatus(indexer_model.status));
+    }
+
+    let updated_indexer = repository
+        .update_status(UpdateIndexerStatusDb { id, status: IndexerStatus::Abandoned.to_string() })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    tracing::warn!("Abandoned indexer {}: {}", id, reason);
+    record_event_with_reason(
+        context,
+        AuditAction::StatusChange,
+        Some(IndexerStatus::Created),
+        Some(IndexerStatus::Abandoned),
+        &updated_indexer,
+        Some(reason.clone()),
+    )
+    .await;
+    spawn_with_context(
+        context,
+        notify_status_change_with_reason(context.clone(), id, IndexerStatus::Abandoned, Some(reason)),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::Json;
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel::{ExpressionMethods, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use object_store::path::Path;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::{FAILOVER_FAILURE_THRESHOLD, FAILOVER_WINDOW_SECONDS};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::utils::{get_s3_script_key, record_event, record_event_with_reason};
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::indexers;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::contract_repository::{self, ContractRepository, NewIndexerContractDb};
+use crate::infra::repositories::indexer_repository::{IndexerDb, IndexerRepository, NewIndexerDb, Repository};
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Failures of a primary over the last window
+#[derive(Debug, Default)]
+struct FailureWindow {
+    failures: VecDeque<Instant>,
+}
+
+impl FailureWindow {
+    /// Records a failure, returns whether the primary failed often enough to be failed over
+    fn record_failure(&mut self, now: Instant) -> bool {
+        let window = Duration::from_secs(FAILOVER_WINDOW_SECONDS);
+        self.failures.retain(|failure| now.duration_since(*failure) <= window);
+        self.failures.push_back(now);
+        self.failures.len() >= FAILOVER_FAILURE_THRESHOLD
+    }
+}
+
+/// Recent failures of the primaries which have a standby, by id of the primary
+static FAILURE_WINDOWS: OnceLock<Mutex<HashMap<Uuid, FailureWindow>>> = OnceLock::new();
+
+fn failure_windows() -> MutexGuard<'static, HashMap<Uuid, FailureWindow>> {
+    FAILURE_WINDOWS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Declares a passive replica of an indexer. The standby gets a copy of the script and the
+/// same sink id so it resumes from the cursor of the primary, it stays stopped until the
+/// primary fails. Two concurrent requests for the same primary are told apart by the unique
+/// index on `standby_for`, the second one is a conflict.
+pub async fn create_standby(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<IndexerModel>, IndexerError> {
+    let repository = IndexerRepository::new(&state.pool);
+    let primary = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if !context.can_act_for_owner(primary.tenant_id.as_deref()) {
+        return Err(IndexerError::IndexerAccessDenied(id));
+    }
+    if primary.standby_for.is_some() {
+        return Err(IndexerError::IndexerIsStandby(id));
+    }
+    if repository.get_standby(id).await.map_err(IndexerError::InfraError)?.is_some() {
+        return Err(IndexerError::StandbyAlreadyExists(id));
+    }
+
+    let standby_id = Uuid::new_v4();
+    let config = config().await;
+    config
+        .object_store()
+        .copy(&Path::from(get_s3_script_key(id)), &Path::from(get_s3_script_key(standby_id)))
+        .await
+        .map_err(IndexerError::FailedToUploadToStore)?;
+
+    // the standby runs the same script so it's affected by the same contracts
+    let contract_filters =
+        ContractRepository::new(&state.pool).get_by_indexer(id).await.map_err(IndexerError::InfraError)?;
+    let new_contracts_db = NewIndexerContractDb::from_filters(standby_id, contract_filters);
+    let new_indexer_db = NewIndexerDb {
+        id: standby_id,
+        status: IndexerStatus::Stopped.to_string(),
+        type_: primary.indexer_type.to_string(),
+        target_url: primary.target_url.clone(),
+        table_name: primary.table_name.clone(),
+        status_server_port: None,
+        custom_connection_string: primary.custom_connection_string.clone(),
+        starting_block: primary.starting_block,
+        indexer_id: Some(primary.indexer_id.clone().unwrap_or_else(|| primary.id.to_string())),
+        log_level: primary.log_level.map(|log_level| log_level.to_string()),
+        standby_for: Some(id),
+        script_permissions: serde_json::to_value(&primary.script_permissions).ok(),
+        tenant_id: primary.tenant_id.clone(),
+        stream_url: primary.stream_url.clone(),
+        ending_block: primary.ending_block,
+        backfill_for: None,
+        script_params: serde_json::to_value(&primary.script_params).ok(),
+        script_checksum: primary.script_checksum.clone(),
+        hooks: serde_json::to_value(&primary.hooks).ok(),
+        sink_options: primary.sink_options.as_ref().and_then(|options| serde_json::to_value(options).ok()),
+        priority: primary.priority,
+        process_priority: serde_json::to_value(primary.process_priority).ok(),
+        script_source_url: primary.script_source_url.clone(),
+        project_id: primary.project_id,
+        // takes over the files of the primary along with its cursor
+        output_location: primary.output_location.clone(),
+    };
+    let standby = insert_standby(&state.pool, new_indexer_db, new_contracts_db).await;
+    let standby = match standby {
+        Ok(standby) => standby,
+        Err(e) => {
+            if let Err(e) = config.object_store().delete(&Path::from(get_s3_script_key(standby_id))).await {
+                tracing::warn!("Failed to delete the script of the discarded standby {}: {:?}", standby_id, e);
+            }
+            return Err(match e {
+                InfraError::InternalServerError(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                    IndexerError::StandbyAlreadyExists(id)
+                }
+                e => IndexerError::InfraError(e),
+            });
+        }
+    };
+
+    record_event(&context, AuditAction::StatusChange, None, Some(IndexerStatus::Stopped), &standby).await;
+
+    Ok(Json(standby))
+}
+
+/// Records the standby along with its contracts
+async fn insert_standby(
+    pool: &Pool<AsyncPgConnection>,
+    new_indexer_db: NewIndexerDb,
+    new_contracts_db: Vec<NewIndexerContractDb>,
+) -> Result<IndexerModel, InfraError> {
+    let mut connection = get_connection(pool).await?;
+    connection
+        .transaction::<_, InfraError, _>(|conn| {
+            async move {
+                let standby: IndexerModel = diesel::insert_into(indexers::table)
+                    .values(new_indexer_db)
+                    .returning(IndexerDb::as_returning())
+                    .get_result::<IndexerDb>(conn)
+                    .await?
+                    .try_into()
+                    .map_err(InfraError::ParseError)?;
+                contract_repository::insert_with_connection(conn, new_contracts_db).await?;
+                Ok(standby)
+            }
+            .scope_boxed()
+        })
+        .await
+}
+
+/// Starts the standby of a failed indexer and swaps their roles so the failed indexer becomes
+/// the standby of its replacement. Does nothing if the indexer has no standby. A primary failing
+/// less than `FAILOVER_FAILURE_THRESHOLD` times within the window is restarted instead, if it
+/// restarts.
+pub async fn failover(context: &ActorContext, primary_id: Uuid) -> Result<(), IndexerError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.pool());
+    let standby_id = match repository.get_standby(primary_id).await.map_err(IndexerError::InfraError)? {
+        Some(standby) => standby.id,
+        None => return Ok(()),
+    };
+
+    let over_threshold = failure_windows().entry(primary_id).or_default().record_failure(Instant::now());
+    if !over_threshold {
+        tracing::warn!("Restarting failed indexer {} before failing over to its standby {}", primary_id, standby_id);
+        match start_indexer(context, primary_id).await {
+            Ok(()) => return Ok(()),
+            // e.g. its script is gone, the standby has its own copy
+            Err(e) => tracing::error!("Failed to restart indexer {}, failing over: {:?}", primary_id, e),
+        }
+    }
+    failure_windows().remove(&primary_id);
+
+    tracing::warn!("Failing over indexer {} to its standby {}", primary_id, standby_id);
+    let connection = &mut get_connection(config.pool()).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+    connection
+        .transaction::<_, IndexerError, _>(|conn| {
+            async move {
+                // the unique index on `standby_for` requires clearing the standby first
+                diesel::update(indexers::table)
+                    .filter(indexers::id.eq(standby_id))
+                    .set(indexers::standby_for.eq(None::<Uuid>))
+                    .execute(conn)
+                    .await?;
+                diesel::update(indexers::table)
+                    .filter(indexers::id.eq(primary_id))
+                    .set(indexers::standby_for.eq(Some(standby_id)))
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let standby = repository.get(standby_id).await.map_err(IndexerError::InfraError)?;
+    let reason = format!("took over from failed indexer {}", primary_id);
+    record_event_with_reason(&context.as_system(), AuditAction::Failover, None, None, &standby, Some(reason)).await;
+
+    start_indexer(context, standby_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_window() {
+        let mut window = FailureWindow::default();
+        let start = Instant::now();
+        for i in 0..FAILOVER_FAILURE_THRESHOLD as u64 - 1 {
+            assert!(!window.record_failure(start + Duration::from_secs(i)));
+        }
+        assert!(window.record_failure(start + Duration::from_secs(FAILOVER_FAILURE_THRESHOLD as u64)));
+
+        // failures older than the window don't count
+        let mut window = FailureWindow::default();
+        for i in 0..FAILOVER_FAILURE_THRESHOLD as u64 {
+            let later = start + Duration::from_secs(i * (FAILOVER_WINDOW_SECONDS + 1));
+            assert!(!window.record_failure(later));
+        }
+    }
+}
@@ -1,42 +1,152 @@
+use std::cmp::Reverse;
 use std::fs;
 use std::io::Write;
 
 // use aws_sdk_s3::primitives::AggregatedBytes;
 use axum::extract::State;
-use object_store::path::Path;
+use futures_util::future::join_all;
 use uuid::Uuid;
 
 use crate::config::config;
-use crate::domain::models::indexer::{IndexerError, IndexerStatus};
-use crate::handlers::indexers::indexer_types::get_indexer_handler;
-use crate::handlers::indexers::utils::{get_s3_script_key, get_script_tmp_directory};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::hook::HookStage;
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus, StartPosition};
+use crate::handlers::global::health::ensure_schedulable;
+use crate::handlers::indexers::hooks::run_hook;
+use crate::handlers::indexers::indexer_types::{get_indexer_handler, get_launch_config};
+use crate::handlers::indexers::readiness::watch_readiness;
+use crate::handlers::indexers::utils::{
+    get_resolved_script, get_script_tmp_directory, lock_indexer, query_status_server, record_event_with_reason,
+};
+use crate::handlers::indexers::warm_start::verify_warm_start;
+use crate::handlers::notifications::lifecycle::notify_status_change;
+use crate::handlers::tenants::quota::{take_start_slot, try_take_start_slot};
 use crate::infra::repositories::indexer_repository::{
-    IndexerFilter, IndexerRepository, Repository, UpdateIndexerStatusAndProcessIdDb,
+    IndexerFilter, IndexerRepository, Repository, UpdateIndexerStatusAndExecutionRefDb,
 };
+use crate::utils::actor_context::spawn_with_context;
 // use crate::utils::env::get_environment_variable;
-use crate::utils::PathExtractor;
+use crate::utils::{OptionalJsonExtractor, PathExtractor};
 use crate::AppState;
 
-pub async fn start_indexer(id: Uuid) -> Result<(), IndexerError> {
+pub async fn start_indexer(context: &ActorContext, id: Uuid) -> Result<(), IndexerError> {
+    start_indexer_at(context, id, StartPosition::default()).await
+}
+
+pub async fn start_indexer_at(context: &ActorContext, id: Uuid, position: StartPosition) -> Result<(), IndexerError> {
+    start_indexer_from(context, id, position, None, true).await
+}
+
+/// Start issued by a background task for an indexer it saw in `expected`. In strict mode the
+/// start is ignored if the indexer moved since, e.g. got stopped while the start was waiting.
+pub async fn start_indexer_expecting(
+    context: &ActorContext,
+    id: Uuid,
+    expected: IndexerStatus,
+) -> Result<(), IndexerError> {
+    start_indexer_from(context, id, StartPosition::default(), Some(expected), true).await
+}
+
+/// Starts at the start limit of the tenant are deferred if `defer` is set, otherwise they're
+/// rejected with the time to wait before trying again
+async fn start_indexer_from(
+    context: &ActorContext,
+    id: Uuid,
+    position: StartPosition,
+    expected: Option<IndexerStatus>,
+    defer: bool,
+) -> Result<(), IndexerError> {
     let config = config().await;
     let mut repository = IndexerRepository::new(config.pool());
-    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
-    let indexer = get_indexer_handler(&indexer_model.indexer_type);
-
-    match indexer_model.status {
-        IndexerStatus::Created => (),
-        IndexerStatus::Stopped => (),
-        IndexerStatus::FailedRunning => (),
-        IndexerStatus::Running => {
-            // it's possible that the indexer is in the running state but the process isn't running
-            // this can happen when the service restarts in an new machine but the process was still
-            // marked as running on the DB
-            if indexer.is_running(indexer_model.clone()).await? {
-                tracing::info!("Indexer is already running, id {}", indexer_model.id);
-                return Ok(());
+    let mut deferred = None;
+    // the lock isn't held while the start is deferred, the other operations on the indexer go on
+    // and the checks are made again once the tenant has a start left
+    let (_lock, indexer_model, indexer) = loop {
+        let lock = lock_indexer(id).await;
+        let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+        let indexer = get_indexer_handler(&indexer_model.indexer_type);
+
+        if config.strict_starts() {
+            let startable = match expected {
+                Some(expected) => indexer_model.status == expected,
+                None => indexer_model.status.is_startable(),
+            };
+            if !startable {
+                let reason = match expected {
+                    Some(expected) => format!("expected {}, found {}", expected, indexer_model.status),
+                    None => format!("can't start from {}", indexer_model.status),
+                };
+                tracing::warn!("Ignoring the start of indexer {}: {}", id, reason);
+                record_event_with_reason(
+                    context,
+                    AuditAction::IgnoredStart,
+                    Some(indexer_model.status),
+                    None,
+                    &indexer_model,
+                    Some(reason),
+                )
+                .await;
+                return Err(IndexerError::InvalidIndexerStatus(indexer_model.status));
+            }
+        }
+
+        match indexer_model.status {
+            IndexerStatus::Created => (),
+            IndexerStatus::Stopped => (),
+            IndexerStatus::FailedRunning => (),
+            IndexerStatus::Abandoned => (),
+            IndexerStatus::Starting | IndexerStatus::Running => {
+                // it's possible that the indexer is in the running state but the process isn't running
+                // this can happen when the service restarts in an new machine but the process was still
+                // marked as running on the DB
+                if indexer.is_running(indexer_model.clone()).await? {
+                    tracing::info!("Indexer is already running, id {}", indexer_model.id);
+                    // its readiness was watched by the previous run of the service
+                    if indexer_model.status == IndexerStatus::Starting {
+                        let window = config.readiness_window().unwrap_or_default();
+                        spawn_with_context(context, watch_readiness(context.as_system(), indexer_model, window));
+                    }
+                    return Ok(());
+                }
             }
+            _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
+        }
+
+        // started elsewhere while this instance can't run new sinks
+        ensure_schedulable()?;
+        // the policy may have changed since the indexer was created
+        config.sandbox_policy().validate(&indexer_model.script_permissions)?;
+
+        if !defer {
+            if let Err((tenant_id, wait)) = try_take_start_slot(&indexer_model).await {
+                return Err(IndexerError::StartLimitReached(tenant_id, wait.as_secs().max(1)));
+            }
+            break (lock, indexer_model, indexer);
+        }
+        match take_start_slot(&indexer_model, &mut deferred).await {
+            Ok(()) => break (lock, indexer_model, indexer),
+            Err(wait) => {
+                drop(lock);
+                tokio::time::sleep(wait).await;
+            }
+        }
+    };
+
+    if config.warm_start_checks() {
+        if let Err(e) = verify_warm_start(&indexer_model).await {
+            // the indexer keeps its status, it would have failed right after moving to running
+            record_event_with_reason(
+                context,
+                AuditAction::StartFailed,
+                Some(indexer_model.status),
+                None,
+                &indexer_model,
+                Some(e.to_string()),
+            )
+            .await;
+            return Err(e);
         }
-        _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
     }
 
     // let bucket_name = get_environment_variable("INDEXER_SERVICE_BUCKET");
@@ -50,50 +160,191 @@ pub async fn start_indexer(id: Uuid) -> Result<(), IndexerError> {
     //     .await
     //     .map_err(IndexerError::FailedToGetFromS3)?;
 
-    let data = config
-        .object_store()
-        .get(&Path::from(get_s3_script_key(id)))
-        .await
-        .map_err(IndexerError::FailedToGetFromStore)?;
-
-    let aggregated_bytes = data.bytes().await.map_err(IndexerError::FailedToCollectBytesFromStore)?;
+    let aggregated_bytes = match get_resolved_script(&indexer_model).await {
+        Ok(aggregated_bytes) => aggregated_bytes,
+        Err(IndexerError::ScriptMissing(id)) => {
+            // recorded so starts failing in the background (boot, restarts, failovers) can be told
+            // apart from crashes of the sink
+            record_event_with_reason(
+                context,
+                AuditAction::StartFailed,
+                Some(indexer_model.status),
+                None,
+                &indexer_model,
+                Some("script missing".into()),
+            )
+            .await;
+            return Err(IndexerError::ScriptMissing(id));
+        }
+        Err(e) => return Err(e),
+    };
 
     let mut file = fs::File::create(get_script_tmp_directory(id)).map_err(IndexerError::FailedToCreateFile)?;
     file.write_all(aggregated_bytes.to_vec().as_slice()).map_err(IndexerError::FailedToCreateFile)?;
 
-    let process_id = indexer.start(&indexer_model).await?.into();
+    // e.g. creates the table the indexer writes to
+    run_hook(context, &indexer_model, HookStage::PreStart).await?;
+
+    let starting_block = match position {
+        StartPosition::PersistedCursor => None,
+        StartPosition::Latest => Some(get_latest_block(&indexer_model).await?),
+        StartPosition::Block { block } => Some(block),
+    };
+    let execution_ref = indexer.start(&indexer_model, starting_block).await?;
+    let launch_config = get_launch_config(indexer.as_ref(), &indexer_model, &aggregated_bytes);
+    let status = match config.readiness_window() {
+        Some(_) => IndexerStatus::Starting,
+        None => IndexerStatus::Running,
+    };
 
-    repository
-        .update_status_and_process_id(UpdateIndexerStatusAndProcessIdDb {
+    let updated_indexer = repository
+        .update_status_and_execution_ref(UpdateIndexerStatusAndExecutionRefDb {
             id: indexer_model.id,
-            process_id,
-            status: IndexerStatus::Running.to_string(),
+            execution_ref: serde_json::to_value(execution_ref)
+                .map_err(|e| IndexerError::FailedToSerialize(e.to_string()))?,
+            status: status.to_string(),
+            launch_config: serde_json::to_value(launch_config).ok(),
         })
         .await
         .map_err(IndexerError::InfraError)?;
 
+    // recorded so reprocessing decisions can be audited
+    let reason = match starting_block {
+        Some(block) => format!("started from {} ({})", position, block),
+        None => format!("started from {}", position),
+    };
+    record_event_with_reason(
+        context,
+        AuditAction::StatusChange,
+        Some(indexer_model.status),
+        Some(status),
+        &updated_indexer,
+        Some(reason),
+    )
+    .await;
+    spawn_with_context(context, notify_status_change(context.clone(), indexer_model.id, status));
+    if let Some(window) = config.readiness_window() {
+        spawn_with_context(context, watch_readiness(context.as_system(), updated_indexer, window));
+    }
+
     Ok(())
 }
 
+/// The body is optional and selects the start position, e.g. `{"from": "block", "block": 100}`,
+/// indexers resume from their persisted cursor by default. Indexers sharing a multiplexed sink
+/// can only resume from it. Callers are told when to try again rather than kept waiting once the
+/// tenant reached its start limit.
 pub async fn start_indexer_api(
     State(_state): State<AppState>,
+    context: ActorContext,
     PathExtractor(id): PathExtractor<Uuid>,
+    OptionalJsonExtractor(position): OptionalJsonExtractor<StartPosition>,
 ) -> Result<(), IndexerError> {
-    start_indexer(id).await
+    let config = config().await;
+    let repository = IndexerRepository::new(config.pool());
+    // standbys are only started by a failover
+    if repository.get(id).await.map_err(IndexerError::InfraError)?.standby_for.is_some() {
+        return Err(IndexerError::IndexerIsStandby(id));
+    }
+    start_indexer_from(&context, id, position, None, false).await
 }
 
-pub async fn start_all_indexers() -> Result<(), IndexerError> {
+/// Returns the head of the stream of the indexer as reported by the running indexers
+/// connected to the same stream
+pub async fn get_latest_block(indexer_model: &IndexerModel) -> Result<u64, IndexerError> {
+    get_stream_head(&indexer_model.stream_url).await?.ok_or(IndexerError::LatestBlockUnknown(indexer_model.id))
+}
+
+/// Head of the stream as reported by the running indexers connected to it, if any
+pub async fn get_stream_head(stream_url: &Option<String>) -> Result<Option<u64>, IndexerError> {
     let config = config().await;
     let repository = IndexerRepository::new(config.pool());
-    let indexers = repository
+    let running = repository
         .get_all(IndexerFilter { status: Some(IndexerStatus::Running.to_string()) })
         .await
         .map_err(IndexerError::InfraError)?;
 
-    for indexer in indexers {
-        // TODO: update indexer status if start fails and not return
-        let _ = start_indexer(indexer.id).await;
+    let mut latest_block = None;
+    for other in running.iter().filter(|other| &other.stream_url == stream_url) {
+        let status_server_port = match other.status_server_port {
+            Some(status_server_port) => status_server_port,
+            None => continue,
+        };
+        match query_status_server(status_server_port).await {
+            Ok(status) => latest_block = latest_block.max(status.head_block),
+            Err(e) => tracing::warn!("Failed to get the head block from indexer {}: {:?}", other.id, e),
+        }
     }
+    Ok(latest_block)
+}
+
+/// Starts the indexers that were running before the service was stopped. They are started by
+/// priority in batches spread over time so that a reboot doesn't hit the stream and the
+/// database with every indexer at once.
+pub async fn start_all_indexers() -> Result<(), IndexerError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.pool());
+    // indexers which were still starting are started again as well
+    let indexers = repository
+        .get_all(IndexerFilter { status: None })
+        .await
+        .map_err(IndexerError::InfraError)?
+        .into_iter()
+        .filter(|indexer| indexer.status.is_live())
+        .collect();
+
+    let ramp_up = config.startup_ramp_up();
+    let indexers = get_startup_order(indexers);
+    tracing::info!(
+        "Starting {} indexers, {} every {}s",
+        indexers.len(),
+        ramp_up.batch_size,
+        ramp_up.interval.as_secs()
+    );
+    tokio::spawn(async move {
+        let context = ActorContext::system();
+        for (i, batch) in indexers.chunks(ramp_up.batch_size).enumerate() {
+            if i > 0 {
+                tokio::time::sleep(ramp_up.interval).await;
+            }
+            // TODO: update indexer status if start fails and not return
+            // stops issued while the indexers wait for their batch win
+            join_all(batch.iter().map(|indexer| start_indexer_expecting(&context, indexer.id, indexer.status))).await;
+        }
+        tracing::info!("All indexers were started");
+    });
 
     Ok(())
 }
+
+/// Highest priority first, standbys after their primaries
+fn get_startup_order(mut indexers: Vec<IndexerModel>) -> Vec<IndexerModel> {
+    indexers.sort_by_key(|indexer| (Reverse(indexer.priority), indexer.standby_for.is_some()));
+    indexers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_startup_order() {
+        let indexer = |priority: i32, standby_for: Option<Uuid>| IndexerModel {
+            id: Uuid::new_v4(),
+            priority,
+            standby_for,
+            ..Default::default()
+        };
+        let primary = indexer(0, None);
+        let standby = indexer(0, Some(primary.id));
+        let urgent = indexer(10, None);
+        let background = indexer(-5, None);
+
+        let order: Vec<Uuid> =
+            get_startup_order(vec![background.clone(), standby.clone(), primary.clone(), urgent.clone()])
+                .iter()
+                .map(|indexer| indexer.id)
+                .collect();
+        assert_eq!(order, vec![urgent.id, primary.id, standby.id, background.id]);
+    }
+}
@@ -0,0 +1,120 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::constants::indexers::{DEFAULT_START_TOKEN_TTL_SECONDS, MAX_START_TOKEN_TTL_SECONDS};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::indexer::IndexerError;
+use crate::domain::models::start_token::{
+    generate_start_token, hash_start_token, MintedStartTokenModel, StartTokenModel,
+};
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::utils::record_event_with_reason;
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::infra::repositories::start_token_repository::{NewStartTokenDb, StartTokenRepository};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+pub const START_TOKEN_HEADER: &str = "x-start-token";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateStartTokenRequest {
+    pub ttl_seconds: Option<i64>,
+}
+
+/// Mints a token starting the indexer once. The token is only returned by this call.
+pub async fn create_start_token(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<CreateStartTokenRequest>,
+) -> Result<Json<MintedStartTokenModel>, IndexerError> {
+    let ttl_seconds = request.ttl_seconds.unwrap_or(DEFAULT_START_TOKEN_TTL_SECONDS);
+    if !(1..=MAX_START_TOKEN_TTL_SECONDS).contains(&ttl_seconds) {
+        return Err(IndexerError::InvalidStartToken(format!(
+            "ttl of {}s is not between 1s and {}s",
+            ttl_seconds, MAX_START_TOKEN_TTL_SECONDS
+        )));
+    }
+    // make sure the indexer exists
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let token = generate_start_token();
+    let start_token = StartTokenRepository::new(&state.pool)
+        .insert(NewStartTokenDb {
+            id: Uuid::new_v4(),
+            indexer_id: id,
+            token_hash: hash_start_token(&token),
+            created_by: admin.context.actor_id.clone(),
+            expires_at: Utc::now() + Duration::seconds(ttl_seconds),
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(Json(MintedStartTokenModel { token, start_token }))
+}
+
+/// Tokens of the indexer from the most recent to the oldest, redeemed and expired ones included
+pub async fn get_start_tokens(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<Vec<StartTokenModel>>, IndexerError> {
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let start_tokens =
+        StartTokenRepository::new(&state.pool).get_all_by_indexer(id).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(start_tokens))
+}
+
+pub async fn delete_start_token(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor((id, token_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, IndexerError> {
+    let mut repository = StartTokenRepository::new(&state.pool);
+    if !repository.delete(id, token_id).await.map_err(IndexerError::InfraError)? {
+        return Err(IndexerError::StartTokenNotFound(token_id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Starts the indexer with the token of the `x-start-token` header. The token is spent even if
+/// the start then fails, a new one has to be minted to try again. Standbys are rejected before the
+/// token is redeemed, they are only started by a failover.
+pub async fn redeem_start_token(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    headers: HeaderMap,
+) -> Result<(), IndexerError> {
+    let token = headers
+        .get(START_TOKEN_HEADER)
+        .and_then(|token| token.to_str().ok())
+        .ok_or(IndexerError::StartTokenRejected)?;
+    let indexer_model = IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    if indexer_model.standby_for.is_some() {
+        return Err(IndexerError::IndexerIsStandby(id));
+    }
+    let start_token = StartTokenRepository::new(&state.pool)
+        .redeem(id, &hash_start_token(token))
+        .await
+        .map_err(IndexerError::InfraError)?
+        .ok_or(IndexerError::StartTokenRejected)?;
+
+    record_event_with_reason(
+        &context,
+        AuditAction::StartTokenRedeemed,
+        None,
+        None,
+        &indexer_model,
+        Some(format!("token {} minted by {}", start_token.id, start_token.created_by.as_deref().unwrap_or("admin"))),
+    )
+    .await;
+    start_indexer(&context, id).await
+}
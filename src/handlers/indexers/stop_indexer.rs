@@ -1,24 +1,95 @@
 use axum::extract::State;
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::execution::StopExpectation;
+use crate::domain::models::hook::HookStage;
 use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::handlers::indexers::hooks::run_hook;
 use crate::handlers::indexers::indexer_types::get_indexer_handler;
+use crate::handlers::indexers::utils::{lock_indexer, record_event, record_event_with_reason};
+use crate::handlers::notifications::lifecycle::notify_status_change_with_reason;
 use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository, UpdateIndexerStatusDb};
-use crate::utils::PathExtractor;
+use crate::utils::actor_context::spawn_with_context;
+use crate::utils::{PathExtractor, QueryExtractor};
 use crate::AppState;
 
+#[derive(Debug, Default, Deserialize)]
+pub struct StopIndexerQuery {
+    /// `execution_seq` of the indexer the caller saw, the stop does nothing if the indexer got
+    /// restarted since
+    pub execution_seq: Option<i64>,
+}
+
 pub async fn stop_indexer(
-    State(state): State<AppState>,
+    State(_state): State<AppState>,
+    context: ActorContext,
     PathExtractor(id): PathExtractor<Uuid>,
+    QueryExtractor(query): QueryExtractor<StopIndexerQuery>,
+) -> Result<(), IndexerError> {
+    let expected = StopExpectation { execution_ref: None, execution_seq: query.execution_seq };
+    stop_indexer_expecting(&context, id, &expected, None).await
+}
+
+/// `reason` is recorded and sent in the notification when the service stops the indexer by
+/// itself
+pub async fn stop_indexer_with_reason(
+    context: &ActorContext,
+    id: Uuid,
+    reason: Option<String>,
+) -> Result<(), IndexerError> {
+    stop_indexer_expecting(context, id, &StopExpectation::default(), reason).await
+}
+
+/// Stops the indexer unless it doesn't run the expected execution anymore, e.g. a stop decided
+/// for a process an operator replaced since. The stop is then only recorded.
+pub async fn stop_indexer_expecting(
+    context: &ActorContext,
+    id: Uuid,
+    expected: &StopExpectation,
+    reason: Option<String>,
+) -> Result<(), IndexerError> {
+    let _lock = lock_indexer(id).await;
+    stop_locked_indexer(context, id, expected, reason).await
+}
+
+/// `stop_indexer_expecting` for a caller already holding the lock of the indexer, e.g. to delete
+/// it right after
+pub async fn stop_locked_indexer(
+    context: &ActorContext,
+    id: Uuid,
+    expected: &StopExpectation,
+    reason: Option<String>,
 ) -> Result<(), IndexerError> {
-    let mut repository = IndexerRepository::new(&state.pool);
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
     let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if let Some(mismatch) = expected.mismatch(&indexer_model) {
+        tracing::warn!("Ignoring the stop of indexer {}: {}", id, mismatch);
+        let reason = match reason {
+            Some(reason) => format!("{} ({})", mismatch, reason),
+            None => mismatch,
+        };
+        record_event_with_reason(
+            context,
+            AuditAction::IgnoredStop,
+            Some(indexer_model.status),
+            None,
+            &indexer_model,
+            Some(reason),
+        )
+        .await;
+        return Ok(());
+    }
     match indexer_model.status {
-        IndexerStatus::Running => (),
+        IndexerStatus::Starting | IndexerStatus::Running => (),
         _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
     }
 
+    let from_status = indexer_model.status;
     let indexer = get_indexer_handler(&indexer_model.indexer_type);
 
     // Check if command failed because indexer was already stopped, in that case update status to
@@ -38,23 +109,49 @@ pub async fn stop_indexer(
         }
     };
 
-    repository
+    let updated_indexer = repository
         .update_status(UpdateIndexerStatusDb { id, status: new_status.to_string() })
         .await
         .map_err(IndexerError::InfraError)?;
 
+    record_event_with_reason(
+        context,
+        AuditAction::StatusChange,
+        Some(from_status),
+        Some(new_status),
+        &updated_indexer,
+        reason.clone(),
+    )
+    .await;
+    spawn_with_context(context, notify_status_change_with_reason(context.clone(), id, new_status, reason));
+
+    if new_status == IndexerStatus::Stopped {
+        run_hook(context, &updated_indexer, HookStage::PostStop).await?;
+    }
+
     Ok(())
 }
 
 /// Updates the status of an indexer to a new stopped state i.e. Stopped or FailedStopping
 /// This function is called when the indexer is already stopped and we want to update the status.
-/// It's triggered by the stop indexer queue which is called when indexer stops with a success
-/// status It's possible that the status was already updated to Stopped/FailStopping if the user
+/// It's triggered by the reaper when the process of the indexer exits with a success status.
+/// It's possible that the status was already updated to Stopped/FailStopping if the user
 /// called the /stop API. So we have `check_redundant_update_call` to avoid duplicate updates.
-pub async fn _update_indexer_state(id: Uuid, new_status: IndexerStatus) -> Result<(), IndexerError> {
+/// Nothing is updated if the indexer doesn't run the expected execution anymore.
+pub async fn update_indexer_state_expecting(
+    context: &ActorContext,
+    id: Uuid,
+    new_status: IndexerStatus,
+    expected: &StopExpectation,
+) -> Result<(), IndexerError> {
+    let _lock = lock_indexer(id).await;
     let config = config().await;
     let mut repository = IndexerRepository::new(config.pool());
     let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if let Some(mismatch) = expected.mismatch(&indexer_model) {
+        tracing::info!("Not moving indexer {} to {}: {}", id, new_status, mismatch);
+        return Ok(());
+    }
 
     let check_redundant_update_call = |current_status: &IndexerStatus, new_status: IndexerStatus, id: Uuid| {
         if *current_status == new_status {
@@ -67,7 +164,7 @@ pub async fn _update_indexer_state(id: Uuid, new_status: IndexerStatus) -> Resul
         )))
     };
     match indexer_model.status {
-        IndexerStatus::Running => (),
+        IndexerStatus::Starting | IndexerStatus::Running => (),
         IndexerStatus::Stopped => {
             check_redundant_update_call(&indexer_model.status, new_status, id)?;
         }
@@ -77,6 +174,7 @@ pub async fn _update_indexer_state(id: Uuid, new_status: IndexerStatus) -> Resul
         _ => return Err(IndexerError::InvalidIndexerStatus(indexer_model.status)),
     }
 
+    let from_status = indexer_model.status;
     let indexer = get_indexer_handler(&indexer_model.indexer_type);
 
     match indexer.is_running(indexer_model).await? {
@@ -88,10 +186,12 @@ pub async fn _update_indexer_state(id: Uuid, new_status: IndexerStatus) -> Resul
         }
     };
 
-    repository
+    let updated_indexer = repository
         .update_status(UpdateIndexerStatusDb { id, status: new_status.to_string() })
         .await
         .map_err(IndexerError::InfraError)?;
 
+    record_event(context, AuditAction::StatusChange, Some(from_status), Some(new_status), &updated_indexer).await;
+
     Ok(())
 }
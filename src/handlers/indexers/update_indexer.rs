@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use axum::extract::State;
+use axum::Json;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::AsyncConnection;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::AuditAction;
+use crate::domain::models::hook::HookStage;
+use crate::domain::models::indexer::{IndexerError, IndexerLogLevel, IndexerStatus, IndexerType};
+use crate::domain::models::indexer_update::{IndexerUpdateModel, UpdateDecision};
+use crate::handlers::indexers::approvals::ensure_target_approved;
+use crate::handlers::indexers::config_drift::get_drifted_fields;
+use crate::handlers::indexers::hooks::run_hook;
+use crate::handlers::indexers::indexer_types::{get_indexer_handler, get_launch_config_of_script};
+use crate::handlers::indexers::start_indexer::start_indexer;
+use crate::handlers::indexers::utils::{get_resolved_script, lock_indexer, record_event};
+use crate::infra::db::pool::get_connection;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::contract_repository::{replace_with_connection, NewIndexerContractDb};
+use crate::infra::repositories::indexer_repository::{
+    update_settings_with_connection, IndexerRepository, Repository, UpdateIndexerSettingsDb, UpdateIndexerStatusDb,
+};
+use crate::utils::script_cache::get_script_checksum;
+use crate::utils::script_filter::extract_contract_filters;
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateIndexerRequest {
+    /// Left as is if not set
+    pub log_level: Option<IndexerLogLevel>,
+    pub target_url: Option<String>,
+    /// Replaces all the params of the script
+    pub script_params: Option<BTreeMap<String, String>>,
+    /// Only used to order the starts of the indexers, doesn't restart the indexer
+    pub priority: Option<i32>,
+}
+
+/// Updates the runtime settings of an indexer. The sinks can't reload their settings so a
+/// running indexer is restarted, but only if the changes alter the effective launch config of
+/// its process. The response tells whether it was restarted.
+pub async fn update_indexer(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<UpdateIndexerRequest>,
+) -> Result<Json<IndexerUpdateModel>, IndexerError> {
+    let mut repository = IndexerRepository::new(&state.pool);
+    let original_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+
+    // every field is checked before anything is written, a rejected update changes nothing
+    let log_level = request.log_level.filter(|log_level| original_model.log_level != Some(*log_level));
+    let target_url = match request.target_url {
+        Some(_) if original_model.indexer_type != IndexerType::Webhook => {
+            return Err(IndexerError::InvalidTargetUrl("only webhook indexers have a target url".into()));
+        }
+        target_url => target_url.filter(|target_url| original_model.target_url.as_ref() != Some(target_url)),
+    };
+    if let Some(target_url) = &target_url {
+        let config = config().await;
+        config.target_policy().validate(target_url.as_str()).await?;
+        ensure_target_approved(&state.pool, original_model.tenant_id.as_deref(), target_url.as_str()).await?;
+    }
+    let script_params = request.script_params.filter(|script_params| original_model.script_params != *script_params);
+    let priority = request.priority.filter(|priority| original_model.priority != *priority);
+
+    let mut indexer_model = original_model.clone();
+    if let Some(log_level) = log_level {
+        indexer_model.log_level = Some(log_level);
+    }
+    if let Some(target_url) = &target_url {
+        indexer_model.target_url = Some(target_url.clone());
+    }
+    if let Some(script_params) = &script_params {
+        indexer_model.script_params = script_params.clone();
+    }
+    // the script is only downloaded when its params changed, it must still resolve, otherwise the
+    // indexer would fail at its next start
+    let scripts = match script_params.is_some() {
+        true => Some((get_resolved_script(&original_model).await?, get_resolved_script(&indexer_model).await?)),
+        false => None,
+    };
+
+    let updated = log_level.is_some() || target_url.is_some() || script_params.is_some();
+    let priority_updated = priority.is_some();
+    if updated || priority_updated {
+        let settings = UpdateIndexerSettingsDb {
+            log_level: log_level.map(|log_level| Some(log_level.to_string())),
+            target_url,
+            script_params: script_params.as_ref().and_then(|script_params| serde_json::to_value(script_params).ok()),
+            priority,
+            ..Default::default()
+        };
+        let contracts = scripts.as_ref().map(|(_, script)| {
+            NewIndexerContractDb::from_filters(id, extract_contract_filters(&String::from_utf8_lossy(script)))
+        });
+        let mut conn = get_connection(&state.pool).await.map_err(|e| IndexerError::InfraError(e.into()))?;
+        indexer_model = conn
+            .transaction::<_, InfraError, _>(|conn| {
+                async move {
+                    let indexer_model = update_settings_with_connection(conn, id, settings).await?;
+                    if let Some(contracts) = contracts {
+                        replace_with_connection(conn, id, contracts).await?;
+                    }
+                    Ok(indexer_model)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(IndexerError::InfraError)?;
+        record_event(&context, AuditAction::ConfigChange, None, None, &indexer_model).await;
+    }
+
+    // e.g. new params the script doesn't use don't change what the process runs
+    let (launch_changes, launch_config_checksum) = match updated {
+        true => {
+            let indexer = get_indexer_handler(&indexer_model.indexer_type);
+            // without new params the script is the one the indexer was last launched with
+            let script_checksums = match &scripts {
+                Some((original_script, script)) => {
+                    Some((get_script_checksum(original_script), get_script_checksum(script)))
+                }
+                None => original_model.launch_config.as_ref().map(|launch_config| {
+                    (launch_config.script_checksum.clone(), launch_config.script_checksum.clone())
+                }),
+            };
+            let (original_checksum, checksum) = script_checksums.clone().unwrap_or_default();
+            let original_config = get_launch_config_of_script(indexer.as_ref(), &original_model, original_checksum);
+            let launch_config = get_launch_config_of_script(indexer.as_ref(), &indexer_model, checksum);
+            // an indexer never launched has no known script to give the checksum of
+            (get_drifted_fields(&original_config, &launch_config), script_checksums.map(|_| launch_config.checksum()))
+        }
+        false => (vec![], None),
+    };
+    let decision =
+        UpdateDecision::decide(updated || priority_updated, !launch_changes.is_empty(), indexer_model.status.is_live());
+
+    if decision == UpdateDecision::Restart {
+        tracing::info!("Restarting indexer {} to apply the new {}", id, launch_changes.join(", "));
+        restart_indexer(&context, id).await?;
+        indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    }
+
+    Ok(Json(IndexerUpdateModel {
+        indexer: indexer_model,
+        decision,
+        launch_changes: launch_changes.into_iter().map(String::from).collect(),
+        launch_config_checksum,
+    }))
+}
+
+/// Stops a running indexer and starts it again with its current settings
+pub async fn restart_indexer(context: &ActorContext, id: Uuid) -> Result<(), IndexerError> {
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+
+    let lock = lock_indexer(id).await;
+    // another operation may have changed the indexer while we were waiting
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+    if !indexer_model.status.is_live() {
+        return Err(IndexerError::InvalidIndexerStatus(indexer_model.status));
+    }
+    let from_status = indexer_model.status;
+    let indexer = get_indexer_handler(&indexer_model.indexer_type);
+    indexer.stop(indexer_model).await?;
+    let updated_indexer = repository
+        .update_status(UpdateIndexerStatusDb { id, status: IndexerStatus::Stopped.to_string() })
+        .await
+        .map_err(IndexerError::InfraError)?;
+    record_event(context, AuditAction::StatusChange, Some(from_status), Some(IndexerStatus::Stopped), &updated_indexer)
+        .await;
+    run_hook(context, &updated_indexer, HookStage::PostStop).await?;
+    // the start takes the lock again
+    drop(lock);
+    start_indexer(context, id).await
+}
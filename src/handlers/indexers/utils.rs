@@ -1,16 +1,131 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use axum::body::Bytes;
+use object_store::path::Path;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 use uuid::Uuid;
 
+use crate::config::config;
+use crate::constants::indexers::{
+    SCRIPT_CACHE_CLEANUP_INTERVAL_SECONDS, SCRIPT_FETCH_MAX_ATTEMPTS, SCRIPT_FETCH_RETRY_DELAY_MILLIS,
+};
 use crate::constants::s3::INDEXER_SERVICE_SCRIPTS_FOLDER;
-use crate::domain::models::indexer::{IndexerError, IndexerServerStatus};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::audit::{AuditAction, AuditSeverity};
+use crate::domain::models::indexer::{IndexerConfig, IndexerError, IndexerModel, IndexerServerStatus, IndexerStatus};
+use crate::domain::models::maintenance::ActionPriority;
 use crate::grpc::apibara_sink_v1::status_client::StatusClient;
 use crate::grpc::apibara_sink_v1::GetStatusRequest;
+use crate::infra::repositories::audit_repository::{AuditRepository, NewAuditLogDb};
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::infra::repositories::maintenance_repository::MaintenanceRepository;
+use crate::utils::script_cache::{cache_script, get_cached_script, get_script_checksum, remove_unused_scripts};
+use crate::utils::script_params::resolve_script_params;
+
+/// Locks of the indexers with a lifecycle operation in progress
+static LIFECYCLE_LOCKS: OnceLock<Mutex<HashMap<Uuid, Arc<Mutex<()>>>>> = OnceLock::new();
+
+/// Waits for the lifecycle operations (start, stop, fail, restart) already running on the
+/// indexer so they're applied one at a time, in the order they were requested. Operations must
+/// read the status of the indexer once the lock is held, a duplicated request then finds the
+/// indexer already in the requested state.
+pub async fn lock_indexer(id: Uuid) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = LIFECYCLE_LOCKS.get_or_init(Mutex::default).lock().await;
+        // locks nobody holds or waits for anymore
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        Arc::clone(locks.entry(id).or_default())
+    };
+    // tokio mutexes are fair so waiters are served in order
+    lock.lock_owned().await
+}
 
 pub fn get_s3_script_key(id: Uuid) -> String {
     format!("{}/{}.js", INDEXER_SERVICE_SCRIPTS_FOLDER, id)
 }
 
 pub fn get_script_tmp_directory(id: Uuid) -> String {
-    format!("{}/{}.js", std::env::temp_dir().to_str().unwrap(), id)
+    std::env::temp_dir().join(format!("{}.js", id)).display().to_string()
+}
+
+/// Returns the script of the indexer as uploaded, from the local cache if it holds it. The store
+/// may not serve a script written just before, or still serve the previous one, so a missing
+/// script or one not matching the recorded checksum is read again a few times before giving up.
+pub async fn get_script(indexer_model: &IndexerModel) -> Result<Bytes, IndexerError> {
+    if let Some(checksum) = &indexer_model.script_checksum {
+        if let Some(script) = get_cached_script(checksum).await {
+            return Ok(script);
+        }
+    }
+
+    let config = config().await;
+    let location = Path::from(get_s3_script_key(indexer_model.id));
+    let mut attempt = 1;
+    loop {
+        let script = match config.object_store().get(&location).await {
+            Ok(data) => Some(data.bytes().await.map_err(IndexerError::FailedToCollectBytesFromStore)?),
+            Err(object_store::Error::NotFound { .. }) => None,
+            Err(e) => return Err(IndexerError::FailedToGetFromStore(e)),
+        };
+        // indexers created before checksums were recorded are only checked for existence
+        let is_expected = |script: &Bytes| {
+            indexer_model.script_checksum.as_ref().map_or(true, |checksum| *checksum == get_script_checksum(script))
+        };
+        match script {
+            Some(script) if is_expected(&script) => {
+                // only scripts verified against their checksum are cached
+                if let Some(checksum) = &indexer_model.script_checksum {
+                    if let Err(e) = cache_script(checksum, &script).await {
+                        tracing::warn!("Failed to cache the script of indexer {}: {:?}", indexer_model.id, e);
+                    }
+                }
+                return Ok(script);
+            }
+            _ if attempt >= SCRIPT_FETCH_MAX_ATTEMPTS => return Err(IndexerError::ScriptMissing(indexer_model.id)),
+            Some(_) => tracing::warn!("Script of indexer {} doesn't match its checksum, retrying", indexer_model.id),
+            None => tracing::warn!("Script of indexer {} not found, retrying", indexer_model.id),
+        }
+        tokio::time::sleep(Duration::from_millis(SCRIPT_FETCH_RETRY_DELAY_MILLIS * 2u64.pow(attempt - 1))).await;
+        attempt += 1;
+    }
+}
+
+/// Periodically removes the cached scripts no indexer uses anymore
+pub async fn monitor_script_cache() {
+    let mut interval = tokio::time::interval(Duration::from_secs(SCRIPT_CACHE_CLEANUP_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        let config = config().await;
+        let indexers =
+            match IndexerRepository::new(config.background_pool()).get_all(IndexerFilter { status: None }).await {
+                Ok(indexers) => indexers,
+                Err(e) => {
+                    tracing::error!("Failed to get the indexers to clean the script cache: {:?}", e);
+                    continue;
+                }
+            };
+        let used: HashSet<String> = indexers.into_iter().filter_map(|indexer| indexer.script_checksum).collect();
+        match remove_unused_scripts(&used).await {
+            Ok(0) => (),
+            Ok(removed) => tracing::info!("Removed {} unused scripts from the cache", removed),
+            Err(e) => tracing::error!("Failed to clean the script cache: {:?}", e),
+        }
+    }
+}
+
+/// Returns the script of the indexer as it's run by the sink, with its params resolved
+pub async fn get_resolved_script(indexer_model: &IndexerModel) -> Result<Bytes, IndexerError> {
+    let script = get_script(indexer_model).await?;
+    resolve_script(&script, &indexer_model.script_params)
+}
+
+/// Script as it's run with the given params
+pub fn resolve_script(script: &[u8], script_params: &BTreeMap<String, String>) -> Result<Bytes, IndexerError> {
+    let script = std::str::from_utf8(script).map_err(|e| IndexerError::InvalidScriptParams(e.to_string()))?;
+    let resolved = resolve_script_params(script, script_params).map_err(IndexerError::MissingScriptParams)?;
+    Ok(Bytes::from(resolved))
 }
 
 pub async fn query_status_server(server_port: i32) -> Result<IndexerServerStatus, IndexerError> {
@@ -30,3 +145,61 @@ pub async fn query_status_server(server_port: i32) -> Result<IndexerServerStatus
 
     Ok(status_response.into())
 }
+
+/// Records an event of the indexer done for the context along with a snapshot of its config so
+/// its state can be reconstructed later. Failures are only logged, they must not fail the
+/// operation itself.
+pub async fn record_event(
+    context: &ActorContext,
+    action: AuditAction,
+    from_status: Option<IndexerStatus>,
+    to_status: Option<IndexerStatus>,
+    indexer_model: &IndexerModel,
+) {
+    record_event_with_reason(context, action, from_status, to_status, indexer_model, None).await
+}
+
+pub async fn record_event_with_reason(
+    context: &ActorContext,
+    action: AuditAction,
+    from_status: Option<IndexerStatus>,
+    to_status: Option<IndexerStatus>,
+    indexer_model: &IndexerModel,
+    reason: Option<String>,
+) {
+    let config = config().await;
+    let result = AuditRepository::new(config.pool())
+        .insert(NewAuditLogDb {
+            id: Uuid::new_v4(),
+            indexer_id: indexer_model.id,
+            action: action.to_string(),
+            from_status: from_status.map(|status| status.to_string()),
+            to_status: to_status.map(|status| status.to_string()),
+            reason,
+            actor: context.actor_id.clone(),
+            severity: AuditSeverity::Info.to_string(),
+            details: serde_json::to_value(IndexerConfig::from(indexer_model)).ok(),
+            correlation_id: context.request_id.clone(),
+        })
+        .await;
+    if let Err(e) = result {
+        tracing::error!("Failed to record {} event for indexer {}: {:?}", action, indexer_model.id, e);
+    }
+}
+
+/// Whether an automated action on the indexer must wait for the end of a maintenance window or
+/// quiet hours. Actions run if the windows can't be checked.
+pub async fn is_action_deferred(indexer_model: &IndexerModel, priority: ActionPriority) -> bool {
+    let config = config().await;
+    let windows = match MaintenanceRepository::new(config.pool())
+        .get_active(indexer_model.tenant_id.as_deref(), chrono::Utc::now())
+        .await
+    {
+        Ok(windows) => windows,
+        Err(e) => {
+            tracing::error!("Failed to get the maintenance windows of indexer {}: {:?}", indexer_model.id, e);
+            return false;
+        }
+    };
+    windows.iter().any(|window| !window.kind.allows(priority))
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use url::Url;
+
+use crate::config::config;
+use crate::constants::indexers::WARM_START_CHECK_TIMEOUT_SECONDS;
+use crate::domain::models::indexer::{IndexerError, IndexerModel};
+use crate::handlers::indexers::indexer_types::get_sink_id;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+
+/// Checks what the sink needs right after it is launched before it is, so that an indexer which
+/// would fail straight away isn't moved to `Running`: the lock on the cursor of its sink id must
+/// be free and the cursor store and the stream must accept connections.
+pub async fn verify_warm_start(indexer_model: &IndexerModel) -> Result<(), IndexerError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.pool());
+    let running: Vec<IndexerModel> = repository
+        .get_all(IndexerFilter { status: None })
+        .await
+        .map_err(IndexerError::InfraError)?
+        .into_iter()
+        .filter(|indexer_model| indexer_model.status.is_live())
+        .collect();
+    if let Some(holder) = get_lock_holder(indexer_model, &running) {
+        return Err(IndexerError::WarmStartFailed(format!(
+            "sink id {} is locked by running indexer {}",
+            get_sink_id(indexer_model),
+            holder.id
+        )));
+    }
+
+    // the sink persists its cursor there, a missing url is reported by the launch itself
+    if let Ok(redis_url) = std::env::var("APIBARA_REDIS_URL") {
+        check_connection("cursor store", &redis_url).await?;
+    }
+    if let Some(stream_url) = &indexer_model.stream_url {
+        check_connection("stream", stream_url).await?;
+    }
+    Ok(())
+}
+
+/// Running indexer holding the lock of the sink id of the indexer. Standbys wait for the lock of
+/// their primary by design, so they are neither checked nor considered holders of their primary.
+fn get_lock_holder<'a>(indexer_model: &IndexerModel, running: &'a [IndexerModel]) -> Option<&'a IndexerModel> {
+    if indexer_model.standby_for.is_some() {
+        return None;
+    }
+    let sink_id = get_sink_id(indexer_model);
+    running.iter().find(|other| {
+        other.id != indexer_model.id && other.standby_for != Some(indexer_model.id) && get_sink_id(other) == sink_id
+    })
+}
+
+async fn check_connection(name: &str, url: &str) -> Result<(), IndexerError> {
+    let Some(address) = get_socket_address(url) else {
+        return Err(IndexerError::WarmStartFailed(format!("{} url {} has no host", name, url)));
+    };
+    let timeout = Duration::from_secs(WARM_START_CHECK_TIMEOUT_SECONDS);
+    match tokio::time::timeout(timeout, TcpStream::connect(&address)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(IndexerError::WarmStartFailed(format!("{} {} is unreachable: {}", name, address, e))),
+        Err(_) => Err(IndexerError::WarmStartFailed(format!(
+            "{} {} didn't accept a connection within {}s",
+            name, address, WARM_START_CHECK_TIMEOUT_SECONDS
+        ))),
+    }
+}
+
+/// `host:port` the url points to, the stream is served over gRPC which defaults to https
+fn get_socket_address(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let port = url.port().or(match url.scheme() {
+        "redis" | "rediss" => Some(6379),
+        _ => url.port_or_known_default(),
+    })?;
+    Some(format!("{}:{}", host, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::domain::models::indexer::IndexerStatus;
+
+    #[rstest]
+    #[case("redis://localhost:6380", Some("localhost:6380"))]
+    #[case("redis://localhost", Some("localhost:6379"))]
+    #[case("https://mainnet.starknet.a5a.ch", Some("mainnet.starknet.a5a.ch:443"))]
+    #[case("http://127.0.0.1:7171", Some("127.0.0.1:7171"))]
+    #[case("not a url", None)]
+    fn test_get_socket_address(#[case] url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(get_socket_address(url).as_deref(), expected);
+    }
+
+    #[test]
+    fn test_get_lock_holder() {
+        let indexer = |indexer_id: &str, standby_for: Option<Uuid>| IndexerModel {
+            id: Uuid::new_v4(),
+            status: IndexerStatus::Running,
+            indexer_id: Some(indexer_id.to_string()),
+            standby_for,
+            ..Default::default()
+        };
+        let primary = indexer("transfers", None);
+        let standby = indexer("transfers", Some(primary.id));
+        let other = indexer("swaps", None);
+
+        assert!(get_lock_holder(&primary, &[primary.clone(), standby.clone(), other.clone()]).is_none());
+        assert!(get_lock_holder(&standby, &[primary.clone()]).is_none());
+
+        let duplicate = indexer("transfers", None);
+        assert_eq!(get_lock_holder(&duplicate, &[primary.clone(), other]).map(|holder| holder.id), Some(primary.id));
+    }
+}
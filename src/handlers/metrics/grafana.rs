@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::MAX_GRAFANA_ANNOTATIONS;
+use crate::domain::models::grafana::{
+    get_usage_datapoints, group_by_label, GrafanaAnnotation, GrafanaAnnotationRequest, GrafanaMetric,
+    GrafanaMetricOption, GrafanaQueryRequest, GrafanaSeries, GrafanaTarget, LabelGroups, MetricSeriesReport,
+};
+use crate::domain::models::indexer::IndexerModel;
+use crate::errors::AppError;
+use crate::infra::repositories::audit_repository::{AuditLogFilter, AuditRepository};
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::infra::repositories::usage_repository::UsageRepository;
+use crate::utils::{AdminGuard, JsonExtractor};
+use crate::AppState;
+
+// Endpoints of the Grafana JSON datasource plugin, the datasource is configured with the
+// `x-admin-api-key` header so testing it from Grafana also checks the key.
+
+pub async fn grafana_health(_admin: AdminGuard) -> StatusCode {
+    StatusCode::OK
+}
+
+/// Metrics offered in the query editor, also answered on `/search` for the older plugin
+pub async fn get_grafana_metrics(_admin: AdminGuard) -> Json<Vec<GrafanaMetricOption>> {
+    let metrics = GrafanaMetric::all()
+        .into_iter()
+        .map(|metric| GrafanaMetricOption { label: metric.name(), value: metric.name() })
+        .collect();
+
+    Json(metrics)
+}
+
+pub async fn query_grafana_metrics(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<GrafanaQueryRequest>,
+) -> Result<Json<Vec<GrafanaSeries>>, AppError> {
+    let targets = request
+        .targets
+        .iter()
+        // panels send an empty target until a metric is picked
+        .filter(|target| !target.target.is_empty())
+        .map(|target| {
+            GrafanaMetric::parse(&target.target)
+                .map(|metric| (target, metric))
+                .ok_or_else(|| AppError::BodyParsing(format!("unknown metric {}", target.target)))
+        })
+        .collect::<Result<Vec<(&GrafanaTarget, GrafanaMetric)>, AppError>>()?;
+    if targets.is_empty() {
+        return Ok(Json(vec![]));
+    }
+
+    let indexers = IndexerRepository::new(&state.pool).get_all(IndexerFilter { status: None }).await?;
+    let labels = config().await.metric_labels();
+    // a single series per target unless the metrics are split by a label
+    let groups: Vec<(Option<String>, HashSet<Uuid>)> = match get_label_groups(&indexers).await {
+        Some(label_groups) => label_groups
+            .groups
+            .into_iter()
+            .map(|(value, indexer_ids)| (Some(value), indexer_ids.into_iter().collect()))
+            .collect(),
+        None => vec![(None, indexers.iter().map(|indexer_model| indexer_model.id).collect())],
+    };
+    let mut series = vec![];
+    for (target, metric) in targets {
+        let records = match metric {
+            GrafanaMetric::Indexers(_) => vec![],
+            _ => {
+                let indexer_ids: Vec<_> = indexers
+                    .iter()
+                    .map(|indexer_model| indexer_model.id)
+                    .filter(|id| target.payload.indexer_id.map_or(true, |indexer_id| indexer_id == *id))
+                    .collect();
+                UsageRepository::new(&state.pool)
+                    .get_indexers_usage(&indexer_ids, request.range.from.date_naive())
+                    .await?
+            }
+        };
+        for (value, indexer_ids) in &groups {
+            // a target restricted to an indexer only gets the series of the indexer
+            if target.payload.indexer_id.is_some_and(|indexer_id| !indexer_ids.contains(&indexer_id)) {
+                continue;
+            }
+            let datapoints = match metric {
+                // only the current count is known, it's charted at the end of the range
+                GrafanaMetric::Indexers(status) => {
+                    let at = request.range.to.min(Utc::now());
+                    let count = indexers
+                        .iter()
+                        .filter(|indexer_model| {
+                            indexer_model.status == status && indexer_ids.contains(&indexer_model.id)
+                        })
+                        .count();
+                    vec![(count as f64, at.timestamp_millis())]
+                }
+                _ => {
+                    let records: Vec<_> =
+                        records.iter().filter(|record| indexer_ids.contains(&record.indexer_id)).cloned().collect();
+                    get_usage_datapoints(metric, &records, request.range)
+                }
+            };
+            let name = match (labels.label, value) {
+                (Some(label), Some(value)) => label.series_name(&target.target, value),
+                _ => target.target.clone(),
+            };
+            series.push(GrafanaSeries { target: name, ref_id: target.ref_id.clone(), datapoints });
+        }
+    }
+
+    Ok(Json(series))
+}
+
+/// Series served by the datasource with the current label and budget, the series of a metric grow
+/// with the values of the label until the budget aggregates them
+pub async fn get_metric_series(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+) -> Result<Json<MetricSeriesReport>, AppError> {
+    let labels = config().await.metric_labels();
+    let indexers = IndexerRepository::new(&state.pool).get_all(IndexerFilter { status: None }).await?;
+    let metrics = GrafanaMetric::all().len();
+    let (label_values, aggregated_label_values, series_per_metric) = match get_label_groups(&indexers).await {
+        Some(label_groups) => (label_groups.label_values, label_groups.aggregated_values, label_groups.groups.len()),
+        None => (0, 0, 1),
+    };
+
+    Ok(Json(MetricSeriesReport {
+        label: labels.label,
+        max_series: labels.max_series,
+        label_values,
+        aggregated_label_values,
+        series_per_metric,
+        metrics,
+        total_series: metrics * series_per_metric,
+    }))
+}
+
+/// Groups of the series of the metrics, `None` if the metrics aren't split by a label
+async fn get_label_groups(indexers: &[IndexerModel]) -> Option<LabelGroups> {
+    let labels = config().await.metric_labels();
+    labels.label.map(|label| group_by_label(label, labels.max_series, indexers))
+}
+
+/// Annotates the panels with the audit events of the range
+pub async fn get_grafana_annotations(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<GrafanaAnnotationRequest>,
+) -> Result<Json<Vec<GrafanaAnnotation>>, AppError> {
+    let indexer_id = request.indexer_id().map_err(AppError::BodyParsing)?;
+    let logs = AuditRepository::new(&state.pool)
+        .get_all(AuditLogFilter {
+            indexer_id,
+            from: Some(request.range.from),
+            to: Some(request.range.to),
+            limit: Some(MAX_GRAFANA_ANNOTATIONS),
+            ..Default::default()
+        })
+        .await?;
+
+    Ok(Json(logs.iter().map(GrafanaAnnotation::from).collect()))
+}
@@ -1,2 +1,11 @@
+pub mod admin;
+pub mod contracts;
+pub mod events;
 pub mod global;
 pub mod indexers;
+pub mod metrics;
+pub mod notifications;
+pub mod projects;
+pub mod tenants;
+pub mod uploads;
+pub mod v2;
@@ -0,0 +1,98 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::alert_rule::{AlertChannel, AlertRuleModel, AlertRuleRequest};
+use crate::errors::AppError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::alert_rule_repository::{AlertRuleRepository, NewAlertRuleDb};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Checks the request and turns it into the row of the rule
+async fn get_new_alert_rule(state: &AppState, id: Uuid, request: AlertRuleRequest) -> Result<NewAlertRuleDb, AppError> {
+    request.validate().map_err(AppError::BodyParsing)?;
+    if let Some(indexer_id) = request.indexer_id {
+        match IndexerRepository::new(&state.pool).get(indexer_id).await {
+            Ok(_) => (),
+            Err(InfraError::NotFound) => return Err(AppError::NotFound(format!("indexer {}", indexer_id))),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    // alerts are sent from the service like the webhooks of the indexers
+    let config = config().await;
+    for channel in &request.channels {
+        if let AlertChannel::Webhook { url } = channel {
+            config.target_policy().validate(url).await.map_err(|e| AppError::BodyParsing(e.to_string()))?;
+        }
+    }
+
+    Ok(NewAlertRuleDb {
+        id,
+        name: request.name,
+        indexer_id: request.indexer_id,
+        condition: serde_json::to_value(request.condition).map_err(|e| AppError::BodyParsing(e.to_string()))?,
+        for_seconds: request.for_seconds as i64,
+        channels: serde_json::to_value(request.channels).map_err(|e| AppError::BodyParsing(e.to_string()))?,
+        enabled: request.enabled,
+    })
+}
+
+/// Alert rules are managed by the admins, the rules of every tenant and their webhooks are listed
+/// together
+pub async fn create_alert_rule(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<AlertRuleRequest>,
+) -> Result<Json<AlertRuleModel>, AppError> {
+    let rule = get_new_alert_rule(&state, Uuid::new_v4(), request).await?;
+    let rule = AlertRuleRepository::new(&state.pool).insert(rule).await?;
+
+    Ok(Json(rule))
+}
+
+pub async fn get_alert_rules(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+) -> Result<Json<Vec<AlertRuleModel>>, AppError> {
+    Ok(Json(AlertRuleRepository::new(&state.pool).get_all(false).await?))
+}
+
+pub async fn get_alert_rule(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<AlertRuleModel>, AppError> {
+    let rule = AlertRuleRepository::new(&state.pool).get(id).await?;
+
+    rule.map(Json).ok_or_else(|| AppError::NotFound(format!("alert rule {}", id)))
+}
+
+/// Replaces the rule, an alert it fired is resolved on the next evaluation if its condition
+/// doesn't hold anymore
+pub async fn update_alert_rule(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<AlertRuleRequest>,
+) -> Result<Json<AlertRuleModel>, AppError> {
+    let rule = get_new_alert_rule(&state, id, request).await?;
+    let rule = AlertRuleRepository::new(&state.pool).update(rule).await?;
+
+    rule.map(Json).ok_or_else(|| AppError::NotFound(format!("alert rule {}", id)))
+}
+
+pub async fn delete_alert_rule(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if !AlertRuleRepository::new(&state.pool).delete(id).await? {
+        return Err(AppError::NotFound(format!("alert rule {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
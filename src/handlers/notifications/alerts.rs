@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::ALERT_EVALUATION_INTERVAL_SECONDS;
+use crate::domain::models::alert_rule::{AlertChannel, AlertCondition, AlertEvaluation, AlertEvent};
+use crate::domain::models::event_schema::EVENT_SCHEMA_VERSION;
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+use crate::handlers::indexers::utils::query_status_server;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::alert_rule_repository::AlertRuleRepository;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::utils::http::http_client;
+use crate::utils::signing::{sign_payload, SIGNATURE_HEADER};
+
+/// State of the rules kept between evaluations
+#[derive(Default)]
+struct AlertEvaluator {
+    /// By rule and indexer
+    evaluations: HashMap<(Uuid, Uuid), AlertEvaluation>,
+    /// Block each running indexer was at on the last evaluation
+    block_samples: HashMap<Uuid, (u64, DateTime<Utc>)>,
+}
+
+static ALERT_EVALUATOR: OnceLock<Mutex<AlertEvaluator>> = OnceLock::new();
+
+fn alert_evaluator() -> std::sync::MutexGuard<'static, AlertEvaluator> {
+    ALERT_EVALUATOR.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Evaluates the enabled alert rules over the indexers and sends the alerts which fired or
+/// resolved to the channels of their rule
+pub async fn monitor_alert_rules() {
+    let mut interval = tokio::time::interval(Duration::from_secs(ALERT_EVALUATION_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = evaluate_alert_rules().await {
+            tracing::error!("Failed to evaluate the alert rules: {:?}", e);
+        }
+    }
+}
+
+async fn evaluate_alert_rules() -> Result<(), InfraError> {
+    let config = config().await;
+    let rules = AlertRuleRepository::new(config.background_pool()).get_all(true).await?;
+    if rules.is_empty() {
+        *alert_evaluator() = AlertEvaluator::default();
+        return Ok(());
+    }
+    let indexers = IndexerRepository::new(config.background_pool()).get_all(IndexerFilter { status: None }).await?;
+
+    let now = Utc::now();
+    let block_rates =
+        match rules.iter().any(|rule| matches!(rule.condition, AlertCondition::BlocksPerMinuteBelow { .. })) {
+            true => sample_block_rates(&indexers, now).await,
+            false => HashMap::new(),
+        };
+
+    let mut alerts = vec![];
+    {
+        let mut evaluator = alert_evaluator();
+        let mut evaluated = HashSet::new();
+        for rule in &rules {
+            for indexer_model in indexers.iter().filter(|indexer_model| rule.watches(indexer_model.id)) {
+                let key = (rule.id, indexer_model.id);
+                evaluated.insert(key);
+                let holds = rule.holds(indexer_model, block_rates.get(&indexer_model.id).copied());
+                if let Some(state) = evaluator.evaluations.entry(key).or_default().observe(holds, now, rule.for_seconds)
+                {
+                    let event = AlertEvent {
+                        schema_version: EVENT_SCHEMA_VERSION.into(),
+                        rule_id: rule.id,
+                        rule_name: rule.name.clone(),
+                        indexer_id: indexer_model.id,
+                        state,
+                        condition: rule.condition.clone(),
+                        happened_at: now,
+                    };
+                    alerts.push((event, rule.channels.clone()));
+                }
+            }
+        }
+        // rules which were deleted or disabled, and indexers which were deleted, start over
+        evaluator.evaluations.retain(|key, _| evaluated.contains(key));
+    }
+
+    for (event, channels) in alerts {
+        tracing::info!("Alert rule {} is {} for indexer {}", event.rule_name, event.state, event.indexer_id);
+        send_alert(&event, &channels).await;
+    }
+    Ok(())
+}
+
+/// Blocks per minute processed by the running indexers since the last evaluation. The first
+/// sample of an indexer has no rate.
+async fn sample_block_rates(indexers: &[IndexerModel], now: DateTime<Utc>) -> HashMap<Uuid, f64> {
+    let mut samples = HashMap::new();
+    for indexer_model in indexers.iter().filter(|indexer_model| indexer_model.status == IndexerStatus::Running) {
+        let Some(port) = indexer_model.status_server_port else {
+            continue;
+        };
+        if let Some(current_block) = query_status_server(port).await.ok().and_then(|status| status.current_block) {
+            samples.insert(indexer_model.id, (current_block, now));
+        }
+    }
+
+    let mut evaluator = alert_evaluator();
+    let rates = samples
+        .iter()
+        .filter_map(|(id, (current_block, sampled_at))| {
+            let (last_block, last_sampled_at) = evaluator.block_samples.get(id)?;
+            let minutes = (*sampled_at - *last_sampled_at).num_milliseconds() as f64 / 60_000.0;
+            (minutes > 0.0).then(|| (*id, current_block.saturating_sub(*last_block) as f64 / minutes))
+        })
+        .collect();
+    evaluator.block_samples = samples;
+    rates
+}
+
+/// Alerts are best effort, a channel which can't be reached only logs a warning
+async fn send_alert(event: &AlertEvent, channels: &[AlertChannel]) {
+    let config = config().await;
+    let payload = match serde_json::to_vec(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!("Failed to serialize the alert of rule {}: {}", event.rule_id, e);
+            return;
+        }
+    };
+    for channel in channels {
+        let url = match channel {
+            AlertChannel::Webhook { url } => url.as_str(),
+            AlertChannel::NotificationWebhook => match config.notification_webhook_url() {
+                Some(url) => url,
+                None => continue,
+            },
+        };
+        // the url was checked against the target policy when the rule was saved, a redirect would
+        // get around it
+        let mut request =
+            http_client().without_redirects().post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(signature) = sign_payload(config.signing_keys(), &payload, event.happened_at) {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        match request.body(payload.clone()).send().await.and_then(|response| response.error_for_status()) {
+            Ok(response) if response.status().is_redirection() => {
+                tracing::warn!("Alert of rule {} redirected by {}, redirects are not followed", event.rule_id, url);
+            }
+            Ok(_) => (),
+            Err(e) => tracing::warn!("Failed to send the alert of rule {} to {}: {}", event.rule_id, url, e),
+        }
+    }
+}
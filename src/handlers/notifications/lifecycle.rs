@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::event_schema::EVENT_SCHEMA_VERSION;
+use crate::domain::models::indexer::IndexerStatus;
+use crate::domain::models::notification::LifecycleEvent;
+use crate::handlers::notifications::policy::admit_alert;
+use crate::utils::actor_context::CORRELATION_ID_HEADER;
+use crate::utils::http::http_client;
+use crate::utils::signing::{sign_payload, SIGNATURE_HEADER};
+
+/// Sends a signed lifecycle webhook for the status change of an indexer if a notification
+/// webhook is configured and the indexer is within its failure budget. Notifications are best
+/// effort and never fail the caller.
+/// They're sent in the background so they take the context of the change
+pub async fn notify_status_change(context: ActorContext, indexer_id: Uuid, status: IndexerStatus) {
+    notify_status_change_with_reason(context, indexer_id, status, None).await
+}
+
+pub async fn notify_status_change_with_reason(
+    context: ActorContext,
+    indexer_id: Uuid,
+    status: IndexerStatus,
+    reason: Option<String>,
+) {
+    let config = config().await;
+    let Some(webhook_url) = config.notification_webhook_url() else {
+        return;
+    };
+
+    if !admit_alert(indexer_id, status, reason.as_deref()).await {
+        return;
+    }
+
+    let now = chrono::Utc::now();
+    let correlation_id = context.request_id;
+    let event = LifecycleEvent {
+        schema_version: EVENT_SCHEMA_VERSION.into(),
+        indexer_id,
+        status,
+        happened_at: now,
+        correlation_id: correlation_id.clone(),
+        reason,
+    };
+    let payload = match serde_json::to_vec(&event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!("Failed to serialize lifecycle event for indexer {}: {}", indexer_id, e);
+            return;
+        }
+    };
+
+    let mut request = http_client().post(webhook_url).header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(signature) = sign_payload(config.signing_keys(), &payload, now) {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    if let Some(correlation_id) = correlation_id {
+        request = request.header(CORRELATION_ID_HEADER, correlation_id);
+    }
+
+    if let Err(e) = request.body(payload).send().await {
+        tracing::warn!("Failed to send lifecycle webhook for indexer {}: {}", indexer_id, e);
+    }
+}
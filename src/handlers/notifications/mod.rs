@@ -0,0 +1,5 @@
+pub mod alert_rules;
+pub mod alerts;
+pub mod lifecycle;
+pub mod policy;
+pub mod signing_keys;
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::indexer::{IndexerError, IndexerStatus};
+use crate::domain::models::notification::{AlertHistory, NotificationPolicy, NotificationPolicyModel};
+use crate::infra::repositories::indexer_repository::{IndexerRepository, Repository};
+use crate::infra::repositories::notification_policy_repository::{
+    NewNotificationPolicyDb, NotificationPolicyRepository,
+};
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+static ALERT_HISTORIES: OnceLock<Mutex<HashMap<Uuid, AlertHistory>>> = OnceLock::new();
+
+fn alert_histories() -> std::sync::MutexGuard<'static, HashMap<Uuid, AlertHistory>> {
+    ALERT_HISTORIES.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the lifecycle webhook of the indexer fits in its failure budget. Only the failures
+/// count against the budget, routine changes such as a restart are always sent and don't use it
+/// up. The default policy applies if the policy of the indexer can't be read.
+pub async fn admit_alert(indexer_id: Uuid, status: IndexerStatus, reason: Option<&str>) -> bool {
+    if !status.is_failure() {
+        return true;
+    }
+    let config = config().await;
+    let policy = match NotificationPolicyRepository::new(config.background_pool()).get(indexer_id).await {
+        Ok(policy) => policy.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("Failed to get the notification policy of indexer {}: {:?}", indexer_id, e);
+            NotificationPolicy::default()
+        }
+    };
+    let alert = format!("{}:{}", status, reason.unwrap_or_default());
+    let admitted = alert_histories().entry(indexer_id).or_default().admit(&alert, Utc::now(), &policy);
+    if !admitted {
+        tracing::info!("Suppressed the {} webhook of indexer {}, it's over its failure budget", alert, indexer_id);
+    }
+    admitted
+}
+
+/// Webhooks suppressed since the service started, over every indexer
+pub fn get_suppressed_alerts() -> u64 {
+    alert_histories().values().map(|history| history.suppressed).sum()
+}
+
+fn suppressed_alerts(indexer_id: Uuid) -> u64 {
+    alert_histories().get(&indexer_id).map_or(0, |history| history.suppressed)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotificationPolicyRequest {
+    pub max_alerts_per_hour: u32,
+    pub duplicate_window_seconds: u64,
+}
+
+/// Policy applied to the webhooks of the indexer, the default one unless it's overridden
+pub async fn get_notification_policy(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<NotificationPolicyModel>, IndexerError> {
+    // make sure the indexer exists
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+    let policy = NotificationPolicyRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(NotificationPolicyModel {
+        indexer_id: id,
+        overridden: policy.is_some(),
+        policy: policy.unwrap_or_default(),
+        suppressed_alerts: suppressed_alerts(id),
+    }))
+}
+
+pub async fn update_notification_policy(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<UpdateNotificationPolicyRequest>,
+) -> Result<Json<NotificationPolicyModel>, IndexerError> {
+    if request.max_alerts_per_hour == 0 {
+        return Err(IndexerError::InvalidNotificationPolicy("max_alerts_per_hour must be positive".into()));
+    }
+    let duplicate_window_seconds = i32::try_from(request.duplicate_window_seconds)
+        .map_err(|_| IndexerError::InvalidNotificationPolicy("duplicate_window_seconds is too large".into()))?;
+    IndexerRepository::new(&state.pool).get(id).await.map_err(IndexerError::InfraError)?;
+
+    let policy = NotificationPolicyRepository::new(&state.pool)
+        .upsert(NewNotificationPolicyDb {
+            indexer_id: id,
+            max_alerts_per_hour: i32::try_from(request.max_alerts_per_hour).unwrap_or(i32::MAX),
+            duplicate_window_seconds,
+        })
+        .await
+        .map_err(IndexerError::InfraError)?;
+
+    Ok(Json(NotificationPolicyModel {
+        indexer_id: id,
+        policy,
+        overridden: true,
+        suppressed_alerts: suppressed_alerts(id),
+    }))
+}
+
+/// The indexer falls back to the default policy
+pub async fn delete_notification_policy(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<StatusCode, IndexerError> {
+    let mut repository = NotificationPolicyRepository::new(&state.pool);
+    if !repository.delete(id).await.map_err(IndexerError::InfraError)? {
+        return Err(IndexerError::NotificationPolicyNotFound(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
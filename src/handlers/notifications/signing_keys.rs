@@ -0,0 +1,37 @@
+use axum::extract::State;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::config::config;
+use crate::domain::models::notification::{SigningKey, VerifySignatureResponse};
+use crate::utils::signing::verify_signature;
+use crate::utils::JsonExtractor;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct VerifySignatureRequest {
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Lists the signing keys currently active, secrets are never serialized
+pub async fn get_signing_keys(State(_state): State<AppState>) -> Json<Vec<SigningKey>> {
+    let config = config().await;
+    let now = chrono::Utc::now();
+    Json(config.signing_keys().iter().filter(|key| key.is_active_at(now)).cloned().collect())
+}
+
+/// Helps webhook consumers test their verification by checking a payload and signature
+pub async fn verify(
+    State(_state): State<AppState>,
+    JsonExtractor(request): JsonExtractor<VerifySignatureRequest>,
+) -> Json<VerifySignatureResponse> {
+    let config = config().await;
+    let key_id = verify_signature(
+        config.signing_keys(),
+        request.payload.as_bytes(),
+        request.signature.as_str(),
+        chrono::Utc::now(),
+    );
+    Json(VerifySignatureResponse { valid: key_id.is_some(), key_id })
+}
@@ -0,0 +1,24 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::IndexerModel;
+use crate::domain::models::project::{ProjectError, ProjectRole};
+use crate::handlers::projects::manage::get_project_model;
+use crate::infra::repositories::project_repository::ProjectRepository;
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Indexers grouped in the project, for the callers allowed to view it
+pub async fn get_project_indexers(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<Vec<IndexerModel>>, ProjectError> {
+    let project = get_project_model(&state, id).await?;
+    project.authorize(&context, ProjectRole::Viewer)?;
+    let indexers = ProjectRepository::new(&state.pool).get_indexers(id).await.map_err(ProjectError::InfraError)?;
+
+    Ok(Json(indexers))
+}
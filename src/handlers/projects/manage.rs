@@ -0,0 +1,136 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::{ActorContext, ActorScope};
+use crate::domain::models::project::{ProjectError, ProjectModel, ProjectRequest, ProjectRole};
+use crate::infra::repositories::project_repository::{NewProjectDb, ProjectRepository};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+pub async fn get_project_model(state: &AppState, id: Uuid) -> Result<ProjectModel, ProjectError> {
+    ProjectRepository::new(&state.pool)
+        .get(id)
+        .await
+        .map_err(ProjectError::InfraError)?
+        .ok_or(ProjectError::NotFound(id))
+}
+
+/// Checks the request and turns it into the row of the project, names are unique within a
+/// tenant
+async fn get_new_project(
+    state: &AppState,
+    id: Uuid,
+    tenant_id: String,
+    request: ProjectRequest,
+) -> Result<NewProjectDb, ProjectError> {
+    request.validate().map_err(ProjectError::InvalidProject)?;
+    if let Some(stream_url) = &request.default_stream_url {
+        config().await.stream_policy().validate(stream_url).map_err(|e| ProjectError::InvalidProject(e.to_string()))?;
+    }
+    let name = request.name.trim().to_string();
+    let existing = ProjectRepository::new(&state.pool)
+        .get_by_name(tenant_id.as_str(), name.as_str())
+        .await
+        .map_err(ProjectError::InfraError)?;
+    if existing.is_some_and(|project| project.id != id) {
+        return Err(ProjectError::InvalidProject(format!("tenant {} already has a project named {}", tenant_id, name)));
+    }
+
+    Ok(NewProjectDb {
+        id,
+        tenant_id,
+        name,
+        description: request.description,
+        default_stream_url: request.default_stream_url,
+        default_log_level: request.default_log_level.map(|log_level| log_level.to_string()),
+        secret_path_prefix: request.secret_path_prefix,
+        role_bindings: serde_json::to_value(request.role_bindings)
+            .map_err(|e| ProjectError::InvalidProject(e.to_string()))?,
+    })
+}
+
+/// Projects of the tenant of the caller, admins not acting for a tenant list every project
+pub async fn get_projects(
+    State(state): State<AppState>,
+    context: ActorContext,
+) -> Result<Json<Vec<ProjectModel>>, ProjectError> {
+    if !context.has_scope(ActorScope::Admin) && context.tenant_id.is_none() {
+        return Ok(Json(vec![]));
+    }
+    let projects = ProjectRepository::new(&state.pool)
+        .get_all(context.tenant_id.as_deref())
+        .await
+        .map_err(ProjectError::InfraError)?;
+
+    Ok(Json(projects))
+}
+
+/// Creates a project for the tenant of the request, or the one of the caller
+pub async fn create_project(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    JsonExtractor(request): JsonExtractor<ProjectRequest>,
+) -> Result<Json<ProjectModel>, ProjectError> {
+    let tenant_id = request
+        .tenant_id
+        .clone()
+        .or(admin.context.tenant_id)
+        .ok_or_else(|| ProjectError::InvalidProject("tenant_id is required".into()))?;
+    let project = get_new_project(&state, Uuid::new_v4(), tenant_id, request).await?;
+    let project = ProjectRepository::new(&state.pool).insert(project).await.map_err(ProjectError::InfraError)?;
+
+    Ok(Json(project))
+}
+
+pub async fn get_project(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<ProjectModel>, ProjectError> {
+    let project = get_project_model(&state, id).await?;
+    project.authorize(&context, ProjectRole::Viewer)?;
+
+    Ok(Json(project))
+}
+
+/// Replaces the project, its tenant can't change. The indexers already created in it keep the
+/// defaults they were created with.
+pub async fn update_project(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<ProjectRequest>,
+) -> Result<Json<ProjectModel>, ProjectError> {
+    let project = get_project_model(&state, id).await?;
+    project.authorize(&context, ProjectRole::Owner)?;
+    if request.tenant_id.as_ref().is_some_and(|tenant_id| *tenant_id != project.tenant_id) {
+        return Err(ProjectError::InvalidProject("the tenant of a project can't change".into()));
+    }
+    let project = get_new_project(&state, id, project.tenant_id, request).await?;
+    let project = ProjectRepository::new(&state.pool).update(project).await.map_err(ProjectError::InfraError)?;
+
+    project.map(Json).ok_or(ProjectError::NotFound(id))
+}
+
+/// Only empty projects can be deleted, their indexers are deleted first
+pub async fn delete_project(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<StatusCode, ProjectError> {
+    let project = get_project_model(&state, id).await?;
+    project.authorize(&context, ProjectRole::Owner)?;
+    let mut repository = ProjectRepository::new(&state.pool);
+    let indexers = repository.count_indexers(id).await.map_err(ProjectError::InfraError)?;
+    if indexers > 0 {
+        return Err(ProjectError::NotEmpty(id, indexers));
+    }
+    if !repository.delete(id).await.map_err(ProjectError::InfraError)? {
+        return Err(ProjectError::NotFound(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
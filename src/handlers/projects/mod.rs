@@ -0,0 +1,2 @@
+pub mod indexers;
+pub mod manage;
@@ -0,0 +1,59 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::domain::models::approval::{normalize_target_url, ApprovedTargetModel};
+use crate::domain::models::tenant::TenantError;
+use crate::infra::repositories::approved_target_repository::{ApprovedTargetRepository, NewApprovedTargetDb};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ApprovedTargetRequest {
+    pub target_url: String,
+}
+
+pub async fn get_approved_targets(
+    State(state): State<AppState>,
+    PathExtractor(tenant_id): PathExtractor<String>,
+) -> Result<Json<Vec<ApprovedTargetModel>>, TenantError> {
+    let repository = ApprovedTargetRepository::new(&state.pool);
+    let approved_targets = repository.get_all(tenant_id.as_str()).await.map_err(TenantError::InfraError)?;
+
+    Ok(Json(approved_targets))
+}
+
+/// Approves the target ahead of the indexers, they're created without waiting for an approval
+pub async fn approve_target(
+    State(state): State<AppState>,
+    admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<ApprovedTargetRequest>,
+) -> Result<Json<ApprovedTargetModel>, TenantError> {
+    let target_url = normalize_target_url(&request.target_url).map_err(TenantError::InvalidTargetUrl)?;
+    let mut repository = ApprovedTargetRepository::new(&state.pool);
+    let approved_target = repository
+        .insert(NewApprovedTargetDb { tenant_id, target_url, approved_by: admin.context.actor_id })
+        .await
+        .map_err(TenantError::InfraError)?;
+
+    Ok(Json(approved_target))
+}
+
+/// Revokes the approval of the target for the next indexers, the indexers already sending to
+/// it keep running
+pub async fn revoke_target(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+    QueryExtractor(request): QueryExtractor<ApprovedTargetRequest>,
+) -> Result<StatusCode, TenantError> {
+    let target_url = normalize_target_url(&request.target_url).map_err(TenantError::InvalidTargetUrl)?;
+    let mut repository = ApprovedTargetRepository::new(&state.pool);
+    if !repository.delete(tenant_id.as_str(), target_url.as_str()).await.map_err(TenantError::InfraError)? {
+        return Err(TenantError::ApprovedTargetNotFound(target_url));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
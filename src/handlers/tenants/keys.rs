@@ -0,0 +1,19 @@
+use axum::Json;
+
+use crate::config::config;
+use crate::domain::models::tenant::{TenantError, TenantKeyModel};
+use crate::utils::signing::get_tenant_key;
+use crate::utils::{AdminGuard, PathExtractor};
+
+/// Returns the key the tenant acts with. The key is derived from `TENANT_KEY_SECRET`, it's the
+/// same on every call and changing the secret revokes the keys of every tenant.
+pub async fn get_tenant_key_api(
+    _admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+) -> Result<Json<TenantKeyModel>, TenantError> {
+    let config = config().await;
+    let secret = config.tenant_key_secret().ok_or(TenantError::TenantKeysDisabled)?;
+    let key = get_tenant_key(secret, &tenant_id);
+
+    Ok(Json(TenantKeyModel { tenant_id, key }))
+}
@@ -0,0 +1,5 @@
+pub mod approved_targets;
+pub mod keys;
+pub mod quota;
+pub mod settings;
+pub mod usage;
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::Json;
+
+use crate::config::config;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::IndexerModel;
+use crate::domain::models::tenant::{StartWindow, TenantError, TenantQuotaModel};
+use crate::infra::repositories::tenant_repository::TenantRepository;
+use crate::utils::PathExtractor;
+use crate::AppState;
+
+/// Recent starts of the indexers of every tenant
+static START_WINDOWS: OnceLock<Mutex<HashMap<String, StartWindow>>> = OnceLock::new();
+
+fn start_windows() -> std::sync::MutexGuard<'static, HashMap<String, StartWindow>> {
+    START_WINDOWS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Takes one of the starts the limit of the tenant of the indexer allows, for the starts made in
+/// the background (boot, restarts, failovers). They're deferred rather than rejected: when the
+/// limit is reached the start is counted in `deferred` and the time to wait before trying again
+/// is returned.
+pub async fn take_start_slot(indexer: &IndexerModel, deferred: &mut Option<DeferredStart>) -> Result<(), Duration> {
+    let (tenant_id, wait) = match try_take_start_slot(indexer).await {
+        Ok(()) => return Ok(()),
+        Err(limit) => limit,
+    };
+    if deferred.is_none() {
+        if let Some(window) = start_windows().get_mut(&tenant_id) {
+            window.deferred += 1;
+        }
+        tracing::info!(
+            "Deferring the start of indexer {} by {}s, tenant {} reached its start limit",
+            indexer.id,
+            wait.as_secs(),
+            tenant_id
+        );
+        *deferred = Some(DeferredStart(tenant_id));
+    }
+    Err(wait)
+}
+
+/// Takes one of the starts the limit of the tenant of the indexer allows, so that the automation
+/// of a tenant can't hog the starts of everyone else. When the limit is reached the tenant and
+/// the time to wait before trying again are returned.
+pub async fn try_take_start_slot(indexer: &IndexerModel) -> Result<(), (String, Duration)> {
+    let tenant_id = match &indexer.tenant_id {
+        Some(tenant_id) => tenant_id,
+        None => return Ok(()),
+    };
+    let config = config().await;
+    let max_starts_per_minute = match TenantRepository::new(config.pool()).get_settings(tenant_id).await {
+        Ok(settings) => settings.and_then(|settings| settings.max_starts_per_minute),
+        Err(e) => {
+            // not worth failing the start over
+            tracing::warn!("Failed to get the start limit of tenant {}: {:?}", tenant_id, e);
+            None
+        }
+    };
+
+    let mut windows = start_windows();
+    let window = windows.entry(tenant_id.clone()).or_default();
+    window.try_start(Instant::now(), max_starts_per_minute).map_err(|wait| (tenant_id.clone(), wait))
+}
+
+/// Counts a deferred start until it's made or given up on
+pub struct DeferredStart(String);
+
+impl Drop for DeferredStart {
+    fn drop(&mut self) {
+        if let Some(window) = start_windows().get_mut(&self.0) {
+            window.deferred -= 1;
+        }
+    }
+}
+
+pub async fn get_tenant_quota(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(tenant_id): PathExtractor<String>,
+) -> Result<Json<TenantQuotaModel>, TenantError> {
+    // tenants only see their own quota
+    if !context.can_act_for_tenant(&tenant_id) {
+        return Err(TenantError::Forbidden(tenant_id));
+    }
+    let repository = TenantRepository::new(&state.pool);
+    let max_starts_per_minute = repository
+        .get_settings(tenant_id.as_str())
+        .await
+        .map_err(TenantError::InfraError)?
+        .and_then(|settings| settings.max_starts_per_minute);
+
+    let (starts_last_minute, deferred_starts) = match start_windows().get_mut(&tenant_id) {
+        Some(window) => (window.starts(Instant::now()), window.deferred),
+        None => (0, 0),
+    };
+    Ok(Json(TenantQuotaModel { tenant_id, max_starts_per_minute, starts_last_minute, deferred_starts }))
+}
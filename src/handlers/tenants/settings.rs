@@ -0,0 +1,104 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::config::config;
+use crate::constants::indexers::MIN_STALE_CREATED_AFTER_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerType};
+use crate::domain::models::secret::SecretBackendKind;
+use crate::domain::models::stale_created::StaleCreatedAction;
+use crate::domain::models::tenant::{TenantError, TenantSettingsModel};
+use crate::infra::repositories::tenant_repository::{NewTenantSettingsDb, TenantRepository};
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTenantSettingsRequest {
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<IndexerLogLevel>,
+    #[serde(default)]
+    pub allowed_indexer_types: Vec<IndexerType>,
+    pub max_starts_per_minute: Option<u32>,
+    pub stale_created_after_seconds: Option<u32>,
+    pub stale_created_action: Option<StaleCreatedAction>,
+    #[serde(default)]
+    pub require_target_approval: bool,
+    pub secret_backend: Option<SecretBackendKind>,
+}
+
+pub async fn get_tenant_settings(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(tenant_id): PathExtractor<String>,
+) -> Result<Json<TenantSettingsModel>, TenantError> {
+    if !context.can_act_for_tenant(&tenant_id) {
+        return Err(TenantError::Forbidden(tenant_id));
+    }
+    let repository = TenantRepository::new(&state.pool);
+    let settings = repository
+        .get_settings(tenant_id.as_str())
+        .await
+        .map_err(TenantError::InfraError)?
+        .ok_or(TenantError::SettingsNotFound(tenant_id))?;
+
+    Ok(Json(settings))
+}
+
+/// Replaces the settings of the tenant. Existing indexers are not affected, the settings are
+/// only applied when creating indexers, apart from the handling of the indexers stuck in
+/// `Created`.
+pub async fn update_tenant_settings(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<UpdateTenantSettingsRequest>,
+) -> Result<Json<TenantSettingsModel>, TenantError> {
+    if request.max_starts_per_minute == Some(0) {
+        return Err(TenantError::InvalidSettings("max_starts_per_minute must be positive".into()));
+    }
+    if request.stale_created_after_seconds.is_some_and(|seconds| seconds < MIN_STALE_CREATED_AFTER_SECONDS) {
+        return Err(TenantError::InvalidSettings(format!(
+            "stale_created_after_seconds must be at least {}",
+            MIN_STALE_CREATED_AFTER_SECONDS
+        )));
+    }
+    if let Some(stream_url) = &request.default_stream_url {
+        config().await.stream_policy().validate(stream_url).map_err(|e| TenantError::InvalidSettings(e.to_string()))?;
+    }
+    let max_starts_per_minute = request.max_starts_per_minute.map(|max| i32::try_from(max).unwrap_or(i32::MAX));
+    let mut repository = TenantRepository::new(&state.pool);
+    let allowed_indexer_types = request.allowed_indexer_types.iter().map(|indexer_type| indexer_type.to_string());
+    let settings = repository
+        .upsert_settings(NewTenantSettingsDb {
+            tenant_id,
+            default_stream_url: request.default_stream_url,
+            default_log_level: request.default_log_level.map(|log_level| log_level.to_string()),
+            allowed_indexer_types: serde_json::Value::from(allowed_indexer_types.collect::<Vec<String>>()),
+            max_starts_per_minute,
+            stale_created_after_seconds: request
+                .stale_created_after_seconds
+                .map(|seconds| i32::try_from(seconds).unwrap_or(i32::MAX)),
+            stale_created_action: request.stale_created_action.map(|action| action.to_string()),
+            require_target_approval: request.require_target_approval,
+            secret_backend: request.secret_backend.map(|backend| backend.to_string()),
+        })
+        .await
+        .map_err(TenantError::InfraError)?;
+
+    Ok(Json(settings))
+}
+
+pub async fn delete_tenant_settings(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+) -> Result<StatusCode, TenantError> {
+    let mut repository = TenantRepository::new(&state.pool);
+    if !repository.delete_settings(tenant_id.as_str()).await.map_err(TenantError::InfraError)? {
+        return Err(TenantError::SettingsNotFound(tenant_id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
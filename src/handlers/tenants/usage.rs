@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use object_store::path::Path;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::indexers::USAGE_METERING_INTERVAL_SECONDS;
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+use crate::domain::models::tenant::TenantError;
+use crate::domain::models::usage::{
+    get_processed_blocks, summarize_usage, BillingPeriod, EgressReport, TenantUsageModel,
+};
+use crate::handlers::indexers::utils::{get_s3_script_key, query_status_server};
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::infra::repositories::usage_repository::{NewUsageRecordDb, UsageRepository};
+use crate::utils::csv::to_csv_line;
+use crate::utils::{AdminGuard, JsonExtractor, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+/// Samples kept between two meterings
+#[derive(Default)]
+struct UsageMeter {
+    /// Block each indexer was at on the last metering
+    last_blocks: HashMap<Uuid, u64>,
+    /// Output of each sink since the last metering
+    log_bytes: HashMap<Uuid, u64>,
+    /// Network traffic of each sink since the last metering
+    egress: HashMap<Uuid, EgressReport>,
+}
+
+static USAGE_METER: OnceLock<Mutex<UsageMeter>> = OnceLock::new();
+
+fn usage_meter() -> std::sync::MutexGuard<'static, UsageMeter> {
+    USAGE_METER.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Counts a line of output of the sink towards the usage of its indexer
+pub fn record_log_bytes(indexer_id: Uuid, bytes: usize) {
+    *usage_meter().log_bytes.entry(indexer_id).or_default() += bytes as u64;
+}
+
+/// Counts the traffic of the sink towards the usage of its indexer
+pub fn record_egress(indexer_id: Uuid, streamed_bytes: u64, delivered_bytes: u64) {
+    let mut meter = usage_meter();
+    let egress = meter.egress.entry(indexer_id).or_default();
+    egress.streamed_bytes += streamed_bytes;
+    egress.delivered_bytes += delivered_bytes;
+}
+
+/// Accounting of the traffic of a sink, sent by its sidecar. The sinks don't report the bytes
+/// they stream nor the ones they deliver so a sidecar sharing their network does.
+pub async fn receive_egress_report(
+    State(_state): State<AppState>,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(report): JsonExtractor<EgressReport>,
+) -> StatusCode {
+    record_egress(id, report.streamed_bytes, report.delivered_bytes);
+    StatusCode::NO_CONTENT
+}
+
+/// Meters the usage of the running indexers of every tenant into the record of the day: the
+/// time they ran, the blocks they processed, the size of their script, of their output and of
+/// their traffic.
+/// Indexers without a tenant aren't billed and aren't metered.
+pub async fn monitor_usage() {
+    let mut interval = tokio::time::interval(Duration::from_secs(USAGE_METERING_INTERVAL_SECONDS));
+    let mut last_metering = Instant::now();
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+        let elapsed = now.duration_since(last_metering);
+        last_metering = now;
+        if let Err(e) = meter_usage(elapsed).await {
+            tracing::error!("Failed to meter the usage of the tenants: {:?}", e);
+        }
+    }
+}
+
+async fn meter_usage(elapsed: Duration) -> Result<(), TenantError> {
+    let config = config().await;
+    let repository = IndexerRepository::new(config.background_pool());
+    let running: Vec<IndexerModel> = repository
+        .get_all(IndexerFilter { status: Some(IndexerStatus::Running.to_string()) })
+        .await
+        .map_err(TenantError::InfraError)?
+        .into_iter()
+        .filter(|indexer_model| indexer_model.tenant_id.is_some())
+        .collect();
+
+    // stopped indexers start from a new sample once running again
+    {
+        let mut meter = usage_meter();
+        meter.last_blocks.retain(|id, _| running.iter().any(|indexer_model| indexer_model.id == *id));
+        meter.log_bytes.retain(|id, _| running.iter().any(|indexer_model| indexer_model.id == *id));
+        meter.egress.retain(|id, _| running.iter().any(|indexer_model| indexer_model.id == *id));
+    }
+
+    let mut usage_repository = UsageRepository::new(config.background_pool());
+    let day = Utc::now().date_naive();
+    for indexer_model in running {
+        let current_block = match indexer_model.status_server_port {
+            Some(port) => query_status_server(port).await.ok().and_then(|status| status.current_block),
+            None => None,
+        };
+        let script_bytes = match config.object_store().head(&Path::from(get_s3_script_key(indexer_model.id))).await {
+            Ok(meta) => meta.size as i64,
+            Err(e) => {
+                tracing::warn!("Failed to get the size of the script of indexer {}: {:?}", indexer_model.id, e);
+                0
+            }
+        };
+        let (blocks_processed, log_bytes, egress) = {
+            let mut meter = usage_meter();
+            let last_block = match current_block {
+                Some(current_block) => meter.last_blocks.insert(indexer_model.id, current_block),
+                None => meter.last_blocks.get(&indexer_model.id).copied(),
+            };
+            let log_bytes = meter.log_bytes.remove(&indexer_model.id).unwrap_or_default();
+            let egress = meter.egress.remove(&indexer_model.id).unwrap_or_default();
+            (get_processed_blocks(last_block, current_block), log_bytes, egress)
+        };
+
+        let tenant_id = indexer_model.tenant_id.clone().unwrap_or_default();
+        if let Err(e) = usage_repository
+            .record(NewUsageRecordDb {
+                tenant_id,
+                indexer_id: indexer_model.id,
+                day,
+                running_seconds: elapsed.as_secs() as i64,
+                blocks_processed: blocks_processed as i64,
+                script_bytes,
+                log_bytes: log_bytes as i64,
+                streamed_bytes: egress.streamed_bytes as i64,
+                delivered_bytes: egress.delivered_bytes as i64,
+            })
+            .await
+        {
+            tracing::error!("Failed to record the usage of indexer {}: {:?}", indexer_model.id, e);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Billing period as `YYYY-MM`, the current month by default
+    pub period: Option<String>,
+    /// Only applies to the export
+    #[serde(default)]
+    pub format: UsageExportFormat,
+}
+
+impl UsageQuery {
+    fn billing_period(&self) -> Result<BillingPeriod, TenantError> {
+        match &self.period {
+            Some(period) => BillingPeriod::parse(period)
+                .ok_or_else(|| TenantError::InvalidUsageQuery(format!("period {} is not YYYY-MM", period))),
+            None => Ok(BillingPeriod::current(Utc::now())),
+        }
+    }
+}
+
+/// Usage of the tenant over the billing period, per indexer and overall
+pub async fn get_tenant_usage(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(tenant_id): PathExtractor<String>,
+    QueryExtractor(query): QueryExtractor<UsageQuery>,
+) -> Result<Json<TenantUsageModel>, TenantError> {
+    // tenants only see their own usage
+    if !context.can_act_for_tenant(&tenant_id) {
+        return Err(TenantError::Forbidden(tenant_id));
+    }
+    let period = query.billing_period()?;
+    let repository = UsageRepository::new(&state.pool);
+    let records =
+        repository.get_usage(&tenant_id, period.start(), period.end()).await.map_err(TenantError::InfraError)?;
+
+    Ok(Json(summarize_usage(tenant_id, period, &records)))
+}
+
+/// Daily records of the tenant over the billing period for chargeback, as JSON or CSV
+pub async fn export_tenant_usage(
+    State(state): State<AppState>,
+    _admin: AdminGuard,
+    PathExtractor(tenant_id): PathExtractor<String>,
+    QueryExtractor(query): QueryExtractor<UsageQuery>,
+) -> Result<Response, TenantError> {
+    let period = query.billing_period()?;
+    let repository = UsageRepository::new(&state.pool);
+    let records =
+        repository.get_usage(&tenant_id, period.start(), period.end()).await.map_err(TenantError::InfraError)?;
+
+    if query.format == UsageExportFormat::Json {
+        return Ok(Json(records).into_response());
+    }
+
+    let mut csv = to_csv_line([
+        "tenant_id",
+        "indexer_id",
+        "day",
+        "running_seconds",
+        "blocks_processed",
+        "script_bytes",
+        "log_bytes",
+        "streamed_bytes",
+        "delivered_bytes",
+    ]);
+    for record in records {
+        csv.push_str(&to_csv_line([
+            record.tenant_id,
+            record.indexer_id.to_string(),
+            record.day.to_string(),
+            record.running_seconds.to_string(),
+            record.blocks_processed.to_string(),
+            record.script_bytes.to_string(),
+            record.log_bytes.to_string(),
+            record.streamed_bytes.to_string(),
+            record.delivered_bytes.to_string(),
+        ]));
+    }
+    let disposition = format!("attachment; filename=\"usage-{}.csv\"", period);
+    Ok(([(header::CONTENT_TYPE, "text/csv".to_string()), (header::CONTENT_DISPOSITION, disposition)], csv)
+        .into_response())
+}
@@ -0,0 +1,244 @@
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::Json;
+use futures_util::StreamExt;
+use object_store::path::Path;
+use object_store::{ObjectStore, WriteMultipart};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::constants::s3::{
+    INDEXER_SERVICE_UPLOADS_FOLDER, UPLOAD_SESSION_CLEANUP_INTERVAL_SECONDS, UPLOAD_SESSION_TTL_SECONDS,
+};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::upload::{UploadError, UploadPart, UploadSession, UploadStatus};
+use crate::infra::repositories::upload_repository::{NewUploadPartDb, NewUploadSessionDb, UploadRepository};
+use crate::utils::{JsonExtractor, PathExtractor};
+use crate::AppState;
+
+/// Maximum number of parts being uploaded concurrently when assembling the upload
+const MAX_CONCURRENT_PARTS: usize = 8;
+
+pub fn get_upload_part_key(id: Uuid, part_number: u32) -> String {
+    format!("{}/{}/parts/{}", INDEXER_SERVICE_UPLOADS_FOLDER, id, part_number)
+}
+
+pub fn get_upload_key(id: Uuid) -> String {
+    format!("{}/{}.js", INDEXER_SERVICE_UPLOADS_FOLDER, id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteUploadRequest {
+    pub total_parts: u32,
+}
+
+/// Sessions belong to the tenant of the caller, callers of no tenant must be admins
+pub async fn create_upload_session(
+    State(state): State<AppState>,
+    context: ActorContext,
+) -> Result<Json<UploadSession>, UploadError> {
+    if !context.can_act_for_owner(context.tenant_id.as_deref()) {
+        return Err(UploadError::TenantRequired);
+    }
+    let session = UploadRepository::new(&state.pool)
+        .insert(NewUploadSessionDb {
+            id: Uuid::new_v4(),
+            tenant_id: context.tenant_id.clone(),
+            status: UploadStatus::InProgress.to_string(),
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(UPLOAD_SESSION_TTL_SECONDS),
+        })
+        .await
+        .map_err(UploadError::InfraError)?;
+    Ok(Json(session))
+}
+
+pub async fn get_upload_session(
+    State(_state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+) -> Result<Json<UploadSession>, UploadError> {
+    Ok(Json(get_owned_session(&context, id).await?))
+}
+
+/// Uploads a single part of the session. Uploading the same part number again overwrites it
+/// which allows clients to retry individual parts.
+pub async fn upload_part(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor((id, part_number)): PathExtractor<(Uuid, u32)>,
+    body: Bytes,
+) -> Result<Json<UploadPart>, UploadError> {
+    if part_number == 0 || i32::try_from(part_number).is_err() {
+        return Err(UploadError::InvalidPartNumber(part_number));
+    }
+    ensure_in_progress(&context, id).await?;
+
+    let part = NewUploadPartDb {
+        upload_id: id,
+        part_number: part_number as i32,
+        size: body.len() as i64,
+        sha256: hex::encode(Sha256::digest(&body)),
+    };
+
+    let config = config().await;
+    config
+        .object_store()
+        .put(&Path::from(get_upload_part_key(id, part_number)), body.into())
+        .await
+        .map_err(UploadError::FailedToUploadToStore)?;
+
+    let part = UploadRepository::new(&state.pool).upsert_part(part).await.map_err(UploadError::InfraError)?;
+
+    Ok(Json(part))
+}
+
+/// Assembles all the registered parts into the final object using a multipart upload. The
+/// session can only be completed once every part up to `total_parts` is registered.
+pub async fn complete_upload_session(
+    State(state): State<AppState>,
+    context: ActorContext,
+    PathExtractor(id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<CompleteUploadRequest>,
+) -> Result<Json<UploadSession>, UploadError> {
+    let session = ensure_in_progress(&context, id).await?;
+    let missing_parts: Vec<u32> =
+        (1..=request.total_parts).filter(|part_number| !session.parts.contains_key(part_number)).collect();
+    if request.total_parts == 0 || !missing_parts.is_empty() {
+        return Err(UploadError::MissingParts(missing_parts));
+    }
+
+    let config = config().await;
+    let store = config.object_store();
+    let upload =
+        store.put_multipart(&Path::from(get_upload_key(id))).await.map_err(UploadError::FailedToUploadToStore)?;
+    let mut writer = WriteMultipart::new(upload);
+    if let Err(e) = write_parts(store.as_ref(), &mut writer, id, request.total_parts).await {
+        if let Err(abort_error) = writer.abort().await {
+            tracing::warn!("Failed to abort the assembly of upload {}: {}", id, abort_error);
+        }
+        return Err(e);
+    }
+    writer.finish().await.map_err(UploadError::FailedToUploadToStore)?;
+
+    // the parts are only deleted once the session is completed, a concurrent completion that
+    // lost the race must not delete the parts the winner is still assembling
+    let mut repository = UploadRepository::new(&state.pool);
+    if !repository.complete(id, request.total_parts).await.map_err(UploadError::InfraError)? {
+        return Err(UploadError::SessionCompleted(id));
+    }
+
+    // staged parts are not needed anymore, failing to delete them is not fatal
+    for part_number in session.parts.keys() {
+        if let Err(e) = store.delete(&Path::from(get_upload_part_key(id, *part_number))).await {
+            tracing::warn!("Failed to delete part {} of upload {}: {}", part_number, id, e);
+        }
+    }
+
+    let session = repository.get(id).await.map_err(UploadError::InfraError)?.ok_or(UploadError::SessionNotFound(id))?;
+
+    Ok(Json(session))
+}
+
+/// Streams the staged parts in order into the multipart upload of the final object, the parts
+/// are never held in memory as a whole
+async fn write_parts(
+    store: &dyn ObjectStore,
+    writer: &mut WriteMultipart,
+    id: Uuid,
+    total_parts: u32,
+) -> Result<(), UploadError> {
+    for part_number in 1..=total_parts {
+        let mut chunks = store
+            .get(&Path::from(get_upload_part_key(id, part_number)))
+            .await
+            .map_err(UploadError::FailedToGetFromStore)?
+            .into_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(UploadError::FailedToGetFromStore)?;
+            writer.wait_for_capacity(MAX_CONCURRENT_PARTS).await.map_err(UploadError::FailedToUploadToStore)?;
+            writer.write(&chunk);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the content of a completed upload of the caller
+pub async fn get_completed_upload(context: &ActorContext, id: Uuid) -> Result<Bytes, UploadError> {
+    let session = get_owned_session(context, id).await?;
+    if session.status != UploadStatus::Completed {
+        return Err(UploadError::UploadNotComplete(id));
+    }
+
+    let config = config().await;
+    config
+        .object_store()
+        .get(&Path::from(get_upload_key(id)))
+        .await
+        .map_err(UploadError::FailedToGetFromStore)?
+        .bytes()
+        .await
+        .map_err(UploadError::FailedToGetFromStore)
+}
+
+/// Expired sessions are not found
+async fn get_owned_session(context: &ActorContext, id: Uuid) -> Result<UploadSession, UploadError> {
+    let config = config().await;
+    let session = UploadRepository::new(config.pool())
+        .get(id)
+        .await
+        .map_err(UploadError::InfraError)?
+        .ok_or(UploadError::SessionNotFound(id))?;
+    if !context.can_act_for_owner(session.tenant_id.as_deref()) {
+        return Err(UploadError::AccessDenied(id));
+    }
+    Ok(session)
+}
+
+async fn ensure_in_progress(context: &ActorContext, id: Uuid) -> Result<UploadSession, UploadError> {
+    let session = get_owned_session(context, id).await?;
+    match session.status {
+        UploadStatus::InProgress => Ok(session),
+        UploadStatus::Completed => Err(UploadError::SessionCompleted(id)),
+    }
+}
+
+/// Deletes the expired sessions along with their staged parts and their assembled upload,
+/// indexers created from an upload have their own copy of the script
+pub async fn remove_expired_uploads() -> Result<usize, UploadError> {
+    let config = config().await;
+    let store = config.object_store();
+    let mut repository = UploadRepository::new(config.background_pool());
+    let sessions = repository.get_expired().await.map_err(UploadError::InfraError)?;
+    for session in &sessions {
+        let keys = session
+            .parts
+            .keys()
+            .map(|part_number| get_upload_part_key(session.id, *part_number))
+            .chain(std::iter::once(get_upload_key(session.id)));
+        for key in keys {
+            match store.delete(&Path::from(key.clone())).await {
+                Ok(()) | Err(object_store::Error::NotFound { .. }) => (),
+                Err(e) => tracing::warn!("Failed to delete {} of expired upload {}: {}", key, session.id, e),
+            }
+        }
+        repository.delete(session.id).await.map_err(UploadError::InfraError)?;
+    }
+    Ok(sessions.len())
+}
+
+/// Periodically removes the expired upload sessions
+pub async fn monitor_upload_sessions() {
+    let mut interval = tokio::time::interval(Duration::from_secs(UPLOAD_SESSION_CLEANUP_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        match remove_expired_uploads().await {
+            Ok(0) => (),
+            Ok(removed) => tracing::info!("Removed {} expired upload sessions", removed),
+            Err(e) => tracing::error!("Failed to remove the expired upload sessions: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use crate::domain::models::envelope::{ErrorBody, ErrorEnvelope};
+use crate::domain::models::indexer::IndexerError;
+use crate::errors::AppError;
+
+/// Errors of the v2 handlers. They are built from the errors of the v1 handlers so both
+/// versions answer with the same status, only the body differs.
+#[derive(Debug)]
+pub struct V2Error {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl From<IndexerError> for V2Error {
+    fn from(value: IndexerError) -> Self {
+        let code = <&'static str>::from(&value);
+        let message = value.to_string();
+        Self { status: value.into_response().status(), code, message }
+    }
+}
+
+impl From<AppError> for V2Error {
+    fn from(value: AppError) -> Self {
+        let (code, message) = match &value {
+            AppError::BodyParsing(message) => ("BAD_REQUEST", message.clone()),
+            AppError::Unauthorized => ("UNAUTHORIZED", "unauthorized".to_string()),
+            AppError::NotFound(resource) => ("NOT_FOUND", format!("{} not found", resource)),
+            AppError::DatabaseBusy => ("DATABASE_BUSY", "database busy".to_string()),
+            _ => ("INTERNAL_SERVER_ERROR", "internal server error".to_string()),
+        };
+        Self { status: value.into_response().status(), code, message }
+    }
+}
+
+impl IntoResponse for V2Error {
+    fn into_response(self) -> Response {
+        let body = ErrorEnvelope { error: ErrorBody { code: self.code.to_string(), message: self.message } };
+        (self.status, Json(body)).into_response()
+    }
+}
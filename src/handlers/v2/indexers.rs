@@ -0,0 +1,56 @@
+use axum::extract::State;
+use axum::Json;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::envelope::{Envelope, PageInfo};
+use crate::domain::models::indexer::{IndexerError, IndexerModel, IndexerStatus};
+use crate::errors::AppError;
+use crate::handlers::v2::errors::V2Error;
+use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
+use crate::utils::{PathExtractor, QueryExtractor};
+use crate::AppState;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct IndexersPageQuery {
+    pub status: Option<IndexerStatus>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Paginated version of the v1 listing, indexers are ordered by id
+pub async fn get_indexers(
+    State(state): State<AppState>,
+    query: Result<QueryExtractor<IndexersPageQuery>, AppError>,
+) -> Result<Json<Envelope<Vec<IndexerModel>>>, V2Error> {
+    let QueryExtractor(query) = query?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let repository = IndexerRepository::new(&state.pool);
+    // fetch one more indexer to know if there is a next page
+    let filter = IndexerFilter { status: query.status.map(|status| status.to_string()) };
+    let mut indexers = repository.get_page(filter, query.cursor, limit + 1).await.map_err(IndexerError::InfraError)?;
+    let next_cursor = if indexers.len() as i64 > limit {
+        indexers.truncate(limit as usize);
+        indexers.last().map(|indexer| indexer.id.to_string())
+    } else {
+        None
+    };
+
+    Ok(Json(Envelope { data: indexers, page: Some(PageInfo { limit, next_cursor }) }))
+}
+
+pub async fn get_indexer(
+    State(state): State<AppState>,
+    id: Result<PathExtractor<Uuid>, AppError>,
+) -> Result<Json<Envelope<IndexerModel>>, V2Error> {
+    let PathExtractor(id) = id?;
+    let repository = IndexerRepository::new(&state.pool);
+    let indexer_model = repository.get(id).await.map_err(IndexerError::InfraError)?;
+
+    Ok(Json(Envelope::new(indexer_model)))
+}
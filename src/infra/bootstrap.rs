@@ -0,0 +1,133 @@
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, CreateBucketConfiguration, PublicAccessBlockConfiguration, Tag, Tagging,
+};
+use aws_sdk_s3::Client;
+
+use crate::constants::s3::BOOTSTRAP_BUCKET_TAGS;
+use crate::domain::models::runtime::StorageBootstrap;
+use crate::utils::env::try_get_environment_variable;
+
+/// Makes sure the bucket of the service exists before the object store is built on it. Every
+/// step can be run again against a bucket which is already set up.
+pub async fn bootstrap_s3_bucket(mode: StorageBootstrap) -> Result<(), String> {
+    let aws_region = try_get_environment_variable("AWS_REGION")?;
+    let bucket = try_get_environment_variable("INDEXER_SERVICE_BUCKET")?;
+    let client = get_s3_client(&aws_region).await;
+
+    match client.head_bucket().bucket(&bucket).send().await {
+        Ok(_) => tracing::info!("Bucket {} exists", bucket),
+        Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => {
+            if mode == StorageBootstrap::Verify {
+                return Err(format!("bucket {} doesn't exist", bucket));
+            }
+            create_bucket(&client, &bucket, &aws_region).await?;
+        }
+        Err(e) => return Err(format!("failed to check bucket {}: {}", bucket, e)),
+    }
+    if mode == StorageBootstrap::Create {
+        apply_bucket_settings(&client, &bucket).await?;
+    }
+    Ok(())
+}
+
+async fn get_s3_client(aws_region: &str) -> Client {
+    let shared_config = aws_config::from_env().region(Region::new(aws_region.to_string())).load().await;
+    let mut s3_config = aws_sdk_s3::config::Builder::from(&shared_config);
+    if let Ok(localstack_endpoint) = try_get_environment_variable("LOCALSTACK_ENDPOINT") {
+        s3_config = s3_config.endpoint_url(localstack_endpoint).force_path_style(true);
+    }
+    Client::from_conf(s3_config.build())
+}
+
+async fn create_bucket(client: &Client, bucket: &str, aws_region: &str) -> Result<(), String> {
+    let mut request = client.create_bucket().bucket(bucket);
+    // us-east-1 is the default location and can't be given as a constraint
+    if aws_region != "us-east-1" {
+        request = request.create_bucket_configuration(
+            CreateBucketConfiguration::builder()
+                .location_constraint(BucketLocationConstraint::from(aws_region))
+                .build(),
+        );
+    }
+    match request.send().await {
+        Ok(_) => {
+            tracing::info!("Created bucket {}", bucket);
+            Ok(())
+        }
+        // another instance starting at the same time created it
+        Err(e) if e.code() == Some("BucketAlreadyOwnedByYou") => Ok(()),
+        Err(e) => Err(format!("failed to create bucket {}: {}", bucket, e)),
+    }
+}
+
+/// Scripts and uploads are private. The tags are added to the ones the bucket already has, which
+/// are kept unless the bootstrap sets the same key.
+async fn apply_bucket_settings(client: &Client, bucket: &str) -> Result<(), String> {
+    let existing = match client.get_bucket_tagging().bucket(bucket).send().await {
+        Ok(output) => output
+            .tag_set()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tag| Some((tag.key()?.to_string(), tag.value()?.to_string())))
+            .collect(),
+        Err(e) if e.code() == Some("NoSuchTagSet") => vec![],
+        Err(e) => return Err(format!("failed to get the tags of bucket {}: {}", bucket, e)),
+    };
+    let tags = merge_tags(existing, BOOTSTRAP_BUCKET_TAGS)
+        .into_iter()
+        .map(|(key, value)| Tag::builder().key(key).value(value).build());
+    client
+        .put_bucket_tagging()
+        .bucket(bucket)
+        .tagging(Tagging::builder().set_tag_set(Some(tags.collect())).build())
+        .send()
+        .await
+        .map_err(|e| format!("failed to tag bucket {}: {}", bucket, e))?;
+    client
+        .put_public_access_block()
+        .bucket(bucket)
+        .public_access_block_configuration(
+            PublicAccessBlockConfiguration::builder()
+                .block_public_acls(true)
+                .ignore_public_acls(true)
+                .block_public_policy(true)
+                .restrict_public_buckets(true)
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| format!("failed to block public access to bucket {}: {}", bucket, e))?;
+    Ok(())
+}
+
+fn merge_tags(mut tags: Vec<(String, String)>, bootstrap_tags: &[(&str, &str)]) -> Vec<(String, String)> {
+    for (key, value) in bootstrap_tags {
+        match tags.iter_mut().find(|(existing, _)| existing == key) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => tags.push((key.to_string(), value.to_string())),
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_tags() {
+        let existing =
+            vec![("team".to_string(), "data".to_string()), ("managed-by".to_string(), "terraform".to_string())];
+        assert_eq!(
+            merge_tags(existing, &[("managed-by", "indexer-service"), ("service", "indexer-service")]),
+            [
+                ("team".to_string(), "data".to_string()),
+                ("managed-by".to_string(), "indexer-service".to_string()),
+                ("service".to_string(), "indexer-service".to_string()),
+            ]
+        );
+        assert_eq!(merge_tags(vec![], &[("service", "indexer-service")]).len(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+use crate::domain::models::data_migration::DataMigrationReport;
+use crate::domain::models::indexer::{IndexerStatus, IndexerType};
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::indexers;
+use crate::infra::errors::InfraError;
+
+/// Rewrites the values stored before the enums of the indexers changed, which would otherwise
+/// fail to parse when the rows are read. Schema migrations can't do it as the mapping lives in
+/// the code. Rows with values that can't be mapped are reported and left untouched.
+pub async fn run_data_migrations(
+    pool: &Pool<AsyncPgConnection>,
+    dry_run: bool,
+) -> Result<DataMigrationReport, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let rows: Vec<(Uuid, String, String)> =
+        indexers::table.select((indexers::id, indexers::status, indexers::type_)).load(&mut conn).await?;
+    let statuses: Vec<(Uuid, String)> = rows.iter().map(|(id, status, _)| (*id, status.clone())).collect();
+    let types: Vec<(Uuid, String)> = rows.into_iter().map(|(id, _, type_)| (id, type_)).collect();
+
+    let mut report = DataMigrationReport { dry_run, ..Default::default() };
+    report.plan_column(
+        "status",
+        &statuses,
+        |value| IndexerStatus::from_str(value).is_ok(),
+        |value| IndexerStatus::from_legacy(value).map(|status| status.to_string()),
+    );
+    report.plan_column(
+        "type",
+        &types,
+        |value| IndexerType::from_str(value).is_ok(),
+        |value| IndexerType::from_legacy(value).map(|indexer_type| indexer_type.to_string()),
+    );
+    if dry_run || report.migrated.is_empty() {
+        return Ok(report);
+    }
+
+    let migrated = report.migrated.clone();
+    conn.transaction::<_, InfraError, _>(|conn| {
+        async move {
+            for value in migrated {
+                let query = diesel::update(indexers::table);
+                match value.column.as_str() {
+                    "status" => {
+                        query
+                            .filter(indexers::status.eq(&value.from))
+                            .set(indexers::status.eq(&value.to))
+                            .execute(conn)
+                            .await?
+                    }
+                    _ => {
+                        query
+                            .filter(indexers::type_.eq(&value.from))
+                            .set(indexers::type_.eq(&value.to))
+                            .execute(conn)
+                            .await?
+                    }
+                };
+            }
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(report)
+}
@@ -1 +1,2 @@
+pub mod pool;
 pub mod schema;
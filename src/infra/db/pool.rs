@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Instant;
+
+use diesel_async::pooled_connection::deadpool::{Object, Pool, PoolError};
+use diesel_async::AsyncPgConnection;
+use strum::IntoEnumIterator;
+
+use crate::config::config;
+use crate::constants::db::REPLICA_RETRY_INTERVAL_SECONDS;
+use crate::domain::models::runtime::{DatabasePoolMetrics, PoolName};
+
+struct PoolCounters {
+    acquired: AtomicU64,
+    timeouts: AtomicU64,
+    total_wait_micros: AtomicU64,
+    max_wait_micros: AtomicU64,
+}
+
+impl PoolCounters {
+    const fn new() -> Self {
+        Self {
+            acquired: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            total_wait_micros: AtomicU64::new(0),
+            max_wait_micros: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Indexed by `PoolName`
+static POOL_COUNTERS: [PoolCounters; 4] =
+    [PoolCounters::new(), PoolCounters::new(), PoolCounters::new(), PoolCounters::new()];
+
+/// Gets a connection from the pool and records how long we waited for it, against the name the
+/// pool has in the config
+pub async fn get_connection(pool: &Pool<AsyncPgConnection>) -> Result<Object<AsyncPgConnection>, PoolError> {
+    let name = config().await.pool_name(pool);
+    let counters = &POOL_COUNTERS[name as usize];
+    let started_at = Instant::now();
+    let result = pool.get().await;
+    let waited = started_at.elapsed().as_micros() as u64;
+
+    match &result {
+        Ok(_) => {
+            counters.acquired.fetch_add(1, Ordering::Relaxed);
+            counters.total_wait_micros.fetch_add(waited, Ordering::Relaxed);
+            counters.max_wait_micros.fetch_max(waited, Ordering::Relaxed);
+        }
+        Err(PoolError::Timeout(_)) => {
+            counters.timeouts.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("Timed out waiting for a connection of the {} pool after {}ms", name, waited / 1000);
+        }
+        Err(_) => (),
+    }
+    result
+}
+
+/// Unix timestamp until which the replica is considered down
+static REPLICA_DOWN_UNTIL: AtomicI64 = AtomicI64::new(0);
+
+/// Gets a connection for read queries which can tolerate some replication lag. The replica is
+/// used if one is configured, reads fall back to `pool` while the replica can't be reached.
+pub async fn get_read_connection(pool: &Pool<AsyncPgConnection>) -> Result<Object<AsyncPgConnection>, PoolError> {
+    let config = config().await;
+    if let Some(replica_pool) = config.replica_pool() {
+        let now = chrono::Utc::now().timestamp();
+        if REPLICA_DOWN_UNTIL.load(Ordering::Relaxed) <= now {
+            match get_connection(replica_pool).await {
+                Ok(conn) => return Ok(conn),
+                Err(e) => {
+                    tracing::warn!("Read replica is unavailable, falling back to the primary: {:?}", e);
+                    REPLICA_DOWN_UNTIL.store(now + REPLICA_RETRY_INTERVAL_SECONDS, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+    get_connection(pool).await
+}
+
+/// Metrics of every pool set in the config
+pub async fn get_all_pool_metrics() -> Vec<DatabasePoolMetrics> {
+    let config = config().await;
+    PoolName::iter().filter_map(|name| Some(pool_metrics(name, config.named_pool(name)?))).collect()
+}
+
+pub fn pool_metrics(name: PoolName, pool: &Pool<AsyncPgConnection>) -> DatabasePoolMetrics {
+    let status = pool.status();
+    let counters = &POOL_COUNTERS[name as usize];
+    // `available` goes negative when requests are waiting for a connection
+    let available = status.available.max(0) as usize;
+    let waiting = (-status.available).max(0) as usize;
+    let acquired = counters.acquired.load(Ordering::Relaxed);
+    let total_wait_micros = counters.total_wait_micros.load(Ordering::Relaxed);
+
+    DatabasePoolMetrics {
+        name,
+        max_size: status.max_size,
+        size: status.size,
+        available,
+        waiting,
+        saturation: if status.max_size == 0 {
+            0.0
+        } else {
+            status.size.saturating_sub(available) as f64 / status.max_size as f64
+        },
+        acquired,
+        timeouts: counters.timeouts.load(Ordering::Relaxed),
+        average_wait_ms: if acquired == 0 { 0.0 } else { total_wait_micros as f64 / acquired as f64 / 1000.0 },
+        max_wait_ms: counters.max_wait_micros.load(Ordering::Relaxed) / 1000,
+    }
+}
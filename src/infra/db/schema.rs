@@ -1,17 +1,271 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    alert_rules (id) {
+        id -> Uuid,
+        name -> Varchar,
+        indexer_id -> Nullable<Uuid>,
+        condition -> Jsonb,
+        for_seconds -> Int8,
+        channels -> Jsonb,
+        enabled -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    approved_targets (tenant_id, target_url) {
+        tenant_id -> Varchar,
+        target_url -> Varchar,
+        approved_by -> Nullable<Varchar>,
+        approved_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audit_logs (id) {
+        id -> Uuid,
+        indexer_id -> Uuid,
+        action -> Varchar,
+        from_status -> Nullable<Varchar>,
+        to_status -> Nullable<Varchar>,
+        reason -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        actor -> Nullable<Varchar>,
+        severity -> Varchar,
+        details -> Nullable<Jsonb>,
+        correlation_id -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    delivered_ranges (id) {
+        id -> Uuid,
+        indexer_id -> Uuid,
+        start_block -> Int8,
+        end_block -> Int8,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    indexer_annotations (id) {
+        id -> Uuid,
+        indexer_id -> Uuid,
+        body -> Text,
+        author -> Varchar,
+        incident_url -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    indexer_contracts (indexer_id, address) {
+        indexer_id -> Uuid,
+        address -> Varchar,
+        event_keys -> Jsonb,
+    }
+}
+
 diesel::table! {
     indexers (id) {
         id -> Uuid,
         status -> Varchar,
         #[sql_name = "type"]
         type_ -> Varchar,
-        process_id -> Nullable<Int8>,
         target_url -> Nullable<Varchar>,
         table_name -> Nullable<Varchar>,
         status_server_port -> Nullable<Int4>,
         custom_connection_string -> Nullable<Varchar>,
         starting_block -> Nullable<Int8>,
         indexer_id -> Nullable<Varchar>,
+        log_level -> Nullable<Varchar>,
+        launch_config -> Nullable<Jsonb>,
+        standby_for -> Nullable<Uuid>,
+        script_permissions -> Nullable<Jsonb>,
+        tenant_id -> Nullable<Varchar>,
+        stream_url -> Nullable<Varchar>,
+        ending_block -> Nullable<Int8>,
+        backfill_for -> Nullable<Uuid>,
+        script_params -> Nullable<Jsonb>,
+        script_checksum -> Nullable<Varchar>,
+        hooks -> Nullable<Jsonb>,
+        sink_options -> Nullable<Jsonb>,
+        priority -> Int4,
+        process_priority -> Nullable<Jsonb>,
+        script_source_url -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        execution_ref -> Nullable<Jsonb>,
+        execution_seq -> Int8,
+        updated_at -> Timestamptz,
+        project_id -> Nullable<Uuid>,
+        output_location -> Nullable<Varchar>,
+        managed_by -> Nullable<Varchar>,
+        archived_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    maintenance_windows (id) {
+        id -> Uuid,
+        tenant_id -> Nullable<Varchar>,
+        kind -> Varchar,
+        starts_at -> Timestamptz,
+        ends_at -> Timestamptz,
+        reason -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    notification_policies (indexer_id) {
+        indexer_id -> Uuid,
+        max_alerts_per_hour -> Int4,
+        duplicate_window_seconds -> Int4,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    projects (id) {
+        id -> Uuid,
+        tenant_id -> Varchar,
+        name -> Varchar,
+        description -> Nullable<Varchar>,
+        default_stream_url -> Nullable<Varchar>,
+        default_log_level -> Nullable<Varchar>,
+        secret_path_prefix -> Nullable<Varchar>,
+        role_bindings -> Jsonb,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    scheduled_actions (id) {
+        id -> Uuid,
+        indexer_id -> Uuid,
+        kind -> Varchar,
+        run_at -> Timestamptz,
+        attempts -> Int4,
+        locked_until -> Nullable<Timestamptz>,
+        last_error -> Nullable<Varchar>,
+        failed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        cron -> Nullable<Varchar>,
+        timezone -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    // `content_tsv` is left out, it's generated from `content` and only used in raw queries
+    script_index (indexer_id) {
+        indexer_id -> Uuid,
+        script_checksum -> Nullable<Varchar>,
+        content -> Text,
+        indexed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    script_scans (script_checksum) {
+        script_checksum -> Varchar,
+        dependencies -> Jsonb,
+        findings -> Jsonb,
+        scanned_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    start_tokens (id) {
+        id -> Uuid,
+        indexer_id -> Uuid,
+        token_hash -> Varchar,
+        created_by -> Nullable<Varchar>,
+        expires_at -> Timestamptz,
+        redeemed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
     }
 }
+
+diesel::table! {
+    tenant_settings (tenant_id) {
+        tenant_id -> Varchar,
+        default_stream_url -> Nullable<Varchar>,
+        default_log_level -> Nullable<Varchar>,
+        allowed_indexer_types -> Jsonb,
+        updated_at -> Timestamptz,
+        max_starts_per_minute -> Nullable<Int4>,
+        stale_created_after_seconds -> Nullable<Int4>,
+        stale_created_action -> Nullable<Varchar>,
+        require_target_approval -> Bool,
+        secret_backend -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    tenant_usage (tenant_id, day, indexer_id) {
+        tenant_id -> Varchar,
+        indexer_id -> Uuid,
+        day -> Date,
+        running_seconds -> Int8,
+        blocks_processed -> Int8,
+        script_bytes -> Int8,
+        log_bytes -> Int8,
+        updated_at -> Timestamptz,
+        streamed_bytes -> Int8,
+        delivered_bytes -> Int8,
+    }
+}
+
+diesel::table! {
+    upload_parts (upload_id, part_number) {
+        upload_id -> Uuid,
+        part_number -> Int4,
+        size -> Int8,
+        sha256 -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    upload_sessions (id) {
+        id -> Uuid,
+        tenant_id -> Nullable<Varchar>,
+        status -> Varchar,
+        expires_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(alert_rules -> indexers (indexer_id));
+diesel::joinable!(delivered_ranges -> indexers (indexer_id));
+diesel::joinable!(indexer_annotations -> indexers (indexer_id));
+diesel::joinable!(indexer_contracts -> indexers (indexer_id));
+diesel::joinable!(indexers -> projects (project_id));
+diesel::joinable!(notification_policies -> indexers (indexer_id));
+diesel::joinable!(scheduled_actions -> indexers (indexer_id));
+diesel::joinable!(script_index -> indexers (indexer_id));
+diesel::joinable!(start_tokens -> indexers (indexer_id));
+diesel::joinable!(upload_parts -> upload_sessions (upload_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    alert_rules,
+    approved_targets,
+    audit_logs,
+    delivered_ranges,
+    indexer_annotations,
+    indexer_contracts,
+    indexers,
+    maintenance_windows,
+    notification_policies,
+    projects,
+    scheduled_actions,
+    script_index,
+    script_scans,
+    start_tokens,
+    tenant_settings,
+    tenant_usage,
+    upload_parts,
+    upload_sessions,
+);
@@ -10,6 +10,8 @@ pub enum InfraError {
     NotFound,
     #[error("pool error: {0}")]
     PoolError(PoolError),
+    #[error("database busy, no connection available")]
+    DatabaseBusy,
     #[error("parsing failed: {0}")]
     ParseError(ParseError),
 }
@@ -25,6 +27,9 @@ impl From<Error> for InfraError {
 
 impl From<PoolError> for InfraError {
     fn from(value: PoolError) -> Self {
-        Self::PoolError(value)
+        match value {
+            PoolError::Timeout(_) => InfraError::DatabaseBusy,
+            _ => InfraError::PoolError(value),
+        }
     }
 }
@@ -1,3 +1,6 @@
+#[cfg(feature = "aws")]
+pub mod bootstrap;
+pub mod data_migrations;
 pub mod db;
 pub mod errors;
 pub mod repositories;
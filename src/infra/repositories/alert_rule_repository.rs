@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::alert_rule::AlertRuleModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::alert_rules;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = alert_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AlertRuleDb {
+    pub id: Uuid,
+    pub name: String,
+    pub indexer_id: Option<Uuid>,
+    pub condition: serde_json::Value,
+    pub for_seconds: i64,
+    pub channels: serde_json::Value,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = alert_rules)]
+pub struct NewAlertRuleDb {
+    pub id: Uuid,
+    pub name: String,
+    pub indexer_id: Option<Uuid>,
+    pub condition: serde_json::Value,
+    pub for_seconds: i64,
+    pub channels: serde_json::Value,
+    pub enabled: bool,
+}
+
+pub struct AlertRuleRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl AlertRuleRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> AlertRuleRepository {
+        AlertRuleRepository { pool }
+    }
+
+    /// Returns the rules ordered by creation, only the enabled ones with `enabled_only`
+    pub async fn get_all(&self, enabled_only: bool) -> Result<Vec<AlertRuleModel>, InfraError> {
+        get_all(self.pool, enabled_only).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<AlertRuleModel>, InfraError> {
+        get(self.pool, id).await
+    }
+
+    pub async fn insert(&mut self, rule: NewAlertRuleDb) -> Result<AlertRuleModel, InfraError> {
+        insert(self.pool, rule).await
+    }
+
+    /// Replaces the rule with the same id, returns `None` if there's none
+    pub async fn update(&mut self, rule: NewAlertRuleDb) -> Result<Option<AlertRuleModel>, InfraError> {
+        update(self.pool, rule).await
+    }
+
+    /// Returns whether the rule existed
+    pub async fn delete(&mut self, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, id).await
+    }
+}
+
+async fn get_all(pool: &Pool<AsyncPgConnection>, enabled_only: bool) -> Result<Vec<AlertRuleModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = alert_rules::table.into_boxed::<diesel::pg::Pg>();
+    if enabled_only {
+        query = query.filter(alert_rules::enabled.eq(true));
+    }
+    let res: Vec<AlertRuleDb> = query
+        .order(alert_rules::created_at.asc())
+        .select(AlertRuleDb::as_select())
+        .load::<AlertRuleDb>(&mut conn)
+        .await?;
+
+    let rules: Vec<AlertRuleModel> = res
+        .into_iter()
+        .map(|rule_db| rule_db.try_into())
+        .collect::<Result<Vec<AlertRuleModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(rules)
+}
+
+async fn get(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<Option<AlertRuleModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = alert_rules::table
+        .filter(alert_rules::id.eq(id))
+        .select(AlertRuleDb::as_select())
+        .first::<AlertRuleDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(AlertRuleModel::try_from).transpose().map_err(InfraError::ParseError)
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, rule: NewAlertRuleDb) -> Result<AlertRuleModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(alert_rules::table)
+        .values(rule)
+        .returning(AlertRuleDb::as_returning())
+        .get_result(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update(pool: &Pool<AsyncPgConnection>, rule: NewAlertRuleDb) -> Result<Option<AlertRuleModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(alert_rules::table)
+        .filter(alert_rules::id.eq(rule.id))
+        .set((
+            alert_rules::name.eq(rule.name),
+            alert_rules::indexer_id.eq(rule.indexer_id),
+            alert_rules::condition.eq(rule.condition),
+            alert_rules::for_seconds.eq(rule.for_seconds),
+            alert_rules::channels.eq(rule.channels),
+            alert_rules::enabled.eq(rule.enabled),
+        ))
+        .returning(AlertRuleDb::as_returning())
+        .get_result::<AlertRuleDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(AlertRuleModel::try_from).transpose().map_err(InfraError::ParseError)
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(alert_rules::table.filter(alert_rules::id.eq(id))).execute(&mut conn).await?;
+
+    Ok(deleted > 0)
+}
+
+impl TryFrom<AlertRuleDb> for AlertRuleModel {
+    type Error = ParseError;
+    fn try_from(value: AlertRuleDb) -> Result<Self, Self::Error> {
+        let model = AlertRuleModel {
+            id: value.id,
+            name: value.name,
+            indexer_id: value.indexer_id,
+            condition: serde_json::from_value(value.condition).map_err(|_| ParseError::VariantNotFound)?,
+            for_seconds: u64::try_from(value.for_seconds).unwrap_or_default(),
+            channels: serde_json::from_value(value.channels).map_err(|_| ParseError::VariantNotFound)?,
+            enabled: value.enabled,
+            created_at: value.created_at,
+        };
+        Ok(model)
+    }
+}
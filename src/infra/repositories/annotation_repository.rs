@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::annotation::AnnotationModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::indexer_annotations;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = indexer_annotations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AnnotationDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub body: String,
+    pub author: String,
+    pub incident_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexer_annotations)]
+pub struct NewAnnotationDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub body: String,
+    pub author: String,
+    pub incident_url: Option<String>,
+}
+
+pub struct AnnotationRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl AnnotationRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> AnnotationRepository {
+        AnnotationRepository { pool }
+    }
+
+    pub async fn insert(&mut self, annotation: NewAnnotationDb) -> Result<AnnotationModel, InfraError> {
+        insert(self.pool, annotation).await
+    }
+
+    /// Annotations of the indexer from the most recent to the oldest
+    pub async fn get_all_by_indexer(&self, indexer_id: Uuid, limit: i64) -> Result<Vec<AnnotationModel>, InfraError> {
+        get_all_by_indexer(self.pool, indexer_id, limit).await
+    }
+
+    /// Returns whether the indexer had the annotation
+    pub async fn delete(&mut self, indexer_id: Uuid, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, indexer_id, id).await
+    }
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, annotation: NewAnnotationDb) -> Result<AnnotationModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(indexer_annotations::table)
+        .values(annotation)
+        .returning(AnnotationDb::as_returning())
+        .get_result::<AnnotationDb>(&mut conn)
+        .await?
+        .into();
+
+    Ok(res)
+}
+
+async fn get_all_by_indexer(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+    limit: i64,
+) -> Result<Vec<AnnotationModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<AnnotationDb> = indexer_annotations::table
+        .filter(indexer_annotations::indexer_id.eq(indexer_id))
+        .order((indexer_annotations::created_at.desc(), indexer_annotations::id.desc()))
+        .limit(limit)
+        .select(AnnotationDb::as_select())
+        .load::<AnnotationDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(AnnotationModel::from).collect())
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(
+        indexer_annotations::table
+            .filter(indexer_annotations::indexer_id.eq(indexer_id))
+            .filter(indexer_annotations::id.eq(id)),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    Ok(deleted > 0)
+}
+
+impl From<AnnotationDb> for AnnotationModel {
+    fn from(value: AnnotationDb) -> Self {
+        AnnotationModel {
+            id: value.id,
+            indexer_id: value.indexer_id,
+            body: value.body,
+            author: value.author,
+            incident_url: value.incident_url,
+            created_at: value.created_at,
+        }
+    }
+}
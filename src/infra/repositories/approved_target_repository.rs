@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::approval::ApprovedTargetModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::approved_targets;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = approved_targets)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApprovedTargetDb {
+    pub tenant_id: String,
+    pub target_url: String,
+    pub approved_by: Option<String>,
+    pub approved_at: DateTime<Utc>,
+}
+
+/// The target url is expected to be normalized, see `normalize_target_url`
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = approved_targets)]
+pub struct NewApprovedTargetDb {
+    pub tenant_id: String,
+    pub target_url: String,
+    pub approved_by: Option<String>,
+}
+
+pub struct ApprovedTargetRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ApprovedTargetRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ApprovedTargetRepository {
+        ApprovedTargetRepository { pool }
+    }
+
+    pub async fn get_all(&self, tenant_id: &str) -> Result<Vec<ApprovedTargetModel>, InfraError> {
+        get_all(self.pool, tenant_id).await
+    }
+
+    pub async fn is_approved(&self, tenant_id: &str, target_url: &str) -> Result<bool, InfraError> {
+        is_approved(self.pool, tenant_id, target_url).await
+    }
+
+    /// Approving a target again records the latest approval
+    pub async fn insert(&mut self, approved_target: NewApprovedTargetDb) -> Result<ApprovedTargetModel, InfraError> {
+        let mut conn = get_connection(self.pool).await?;
+        insert_with_connection(&mut conn, approved_target).await
+    }
+
+    /// Returns whether the target was approved. Indexers already targeting it are not affected.
+    pub async fn delete(&mut self, tenant_id: &str, target_url: &str) -> Result<bool, InfraError> {
+        delete(self.pool, tenant_id, target_url).await
+    }
+}
+
+async fn get_all(pool: &Pool<AsyncPgConnection>, tenant_id: &str) -> Result<Vec<ApprovedTargetModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<ApprovedTargetDb> = approved_targets::table
+        .filter(approved_targets::tenant_id.eq(tenant_id))
+        .order(approved_targets::target_url.asc())
+        .select(ApprovedTargetDb::as_select())
+        .load::<ApprovedTargetDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(ApprovedTargetModel::from).collect())
+}
+
+async fn is_approved(pool: &Pool<AsyncPgConnection>, tenant_id: &str, target_url: &str) -> Result<bool, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = approved_targets::table
+        .filter(approved_targets::tenant_id.eq(tenant_id))
+        .filter(approved_targets::target_url.eq(target_url))
+        .select(approved_targets::target_url)
+        .first::<String>(&mut conn)
+        .await
+        .optional()?;
+
+    Ok(res.is_some())
+}
+
+pub async fn insert_with_connection(
+    conn: &mut AsyncPgConnection,
+    approved_target: NewApprovedTargetDb,
+) -> Result<ApprovedTargetModel, InfraError> {
+    let res = diesel::insert_into(approved_targets::table)
+        .values(approved_target)
+        .on_conflict((approved_targets::tenant_id, approved_targets::target_url))
+        .do_update()
+        .set((
+            approved_targets::approved_by.eq(excluded(approved_targets::approved_by)),
+            approved_targets::approved_at.eq(diesel::dsl::now),
+        ))
+        .returning(ApprovedTargetDb::as_returning())
+        .get_result::<ApprovedTargetDb>(conn)
+        .await?
+        .into();
+
+    Ok(res)
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, tenant_id: &str, target_url: &str) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(
+        approved_targets::table
+            .filter(approved_targets::tenant_id.eq(tenant_id))
+            .filter(approved_targets::target_url.eq(target_url)),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    Ok(deleted > 0)
+}
+
+impl From<ApprovedTargetDb> for ApprovedTargetModel {
+    fn from(value: ApprovedTargetDb) -> Self {
+        ApprovedTargetModel {
+            tenant_id: value.tenant_id,
+            target_url: value.target_url,
+            approved_by: value.approved_by,
+            approved_at: value.approved_at,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable,
+    SelectableHelper,
+};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::audit::{AuditAction, AuditLogCursor, AuditLogModel, AuditSeverity};
+use crate::domain::models::indexer::IndexerStatus;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::audit_logs;
+use crate::infra::errors::InfraError;
+use crate::utils::event_bus::publish_event;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditLogDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub action: String,
+    pub from_status: Option<String>,
+    pub to_status: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub severity: String,
+    pub details: Option<serde_json::Value>,
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = audit_logs)]
+pub struct NewAuditLogDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub action: String,
+    pub from_status: Option<String>,
+    pub to_status: Option<String>,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+    pub severity: String,
+    pub details: Option<serde_json::Value>,
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct AuditLogFilter {
+    pub indexer_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    pub severity: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub cursor: Option<AuditLogCursor>,
+    pub limit: Option<i64>,
+}
+
+pub struct AuditRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl AuditRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> AuditRepository {
+        AuditRepository { pool }
+    }
+
+    /// The log is published to the live tail once recorded, logs inserted within a transaction
+    /// are published by the caller after the commit
+    pub async fn insert(&mut self, new_audit_log: NewAuditLogDb) -> Result<AuditLogModel, InfraError> {
+        let audit_log = insert(self.pool, new_audit_log).await?;
+        publish_event(audit_log.clone());
+        Ok(audit_log)
+    }
+
+    pub async fn get_all_by_indexer(&self, indexer_id: Uuid) -> Result<Vec<AuditLogModel>, InfraError> {
+        get_all_by_indexer(self.pool, indexer_id).await
+    }
+
+    pub async fn get_all(&self, filter: AuditLogFilter) -> Result<Vec<AuditLogModel>, InfraError> {
+        get_all(self.pool, filter).await
+    }
+
+    pub async fn get_last_status_change(
+        &self,
+        indexer_id: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<Option<AuditLogModel>, InfraError> {
+        get_last_status_change(self.pool, indexer_id, at).await
+    }
+
+    pub async fn get_last_config(
+        &self,
+        indexer_id: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<Option<AuditLogModel>, InfraError> {
+        get_last_config(self.pool, indexer_id, at).await
+    }
+}
+
+/// Inserts an audit log using an existing connection so it can be part of a transaction
+pub async fn insert_with_connection(
+    conn: &mut AsyncPgConnection,
+    new_audit_log: NewAuditLogDb,
+) -> Result<AuditLogModel, InfraError> {
+    let res = diesel::insert_into(audit_logs::table)
+        .values(new_audit_log)
+        .returning(AuditLogDb::as_returning())
+        .get_result(conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, new_audit_log: NewAuditLogDb) -> Result<AuditLogModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    insert_with_connection(&mut conn, new_audit_log).await
+}
+
+async fn get_all_by_indexer(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+) -> Result<Vec<AuditLogModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<AuditLogDb> = audit_logs::table
+        .filter(audit_logs::indexer_id.eq(indexer_id))
+        .order(audit_logs::created_at.asc())
+        .select(AuditLogDb::as_select())
+        .load::<AuditLogDb>(&mut conn)
+        .await?;
+
+    let audit_logs: Vec<AuditLogModel> = res
+        .into_iter()
+        .map(|audit_log_db| audit_log_db.try_into())
+        .collect::<Result<Vec<AuditLogModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(audit_logs)
+}
+
+/// Returns the audit logs matching the filter from the most recent to the oldest
+async fn get_all(pool: &Pool<AsyncPgConnection>, filter: AuditLogFilter) -> Result<Vec<AuditLogModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = audit_logs::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(indexer_id) = filter.indexer_id {
+        query = query.filter(audit_logs::indexer_id.eq(indexer_id));
+    }
+    if let Some(action) = filter.action {
+        query = query.filter(audit_logs::action.eq(action));
+    }
+    if let Some(actor) = filter.actor {
+        query = query.filter(audit_logs::actor.eq(actor));
+    }
+    if let Some(severity) = filter.severity {
+        query = query.filter(audit_logs::severity.eq(severity));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(audit_logs::created_at.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(audit_logs::created_at.lt(to));
+    }
+    if let Some(cursor) = filter.cursor {
+        query = query.filter(
+            audit_logs::created_at
+                .lt(cursor.created_at)
+                .or(audit_logs::created_at.eq(cursor.created_at).and(audit_logs::id.lt(cursor.id))),
+        );
+    }
+    if let Some(limit) = filter.limit {
+        query = query.limit(limit);
+    }
+    let res: Vec<AuditLogDb> = query
+        .order((audit_logs::created_at.desc(), audit_logs::id.desc()))
+        .select(AuditLogDb::as_select())
+        .load::<AuditLogDb>(&mut conn)
+        .await?;
+
+    let audit_logs: Vec<AuditLogModel> = res
+        .into_iter()
+        .map(|audit_log_db| audit_log_db.try_into())
+        .collect::<Result<Vec<AuditLogModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(audit_logs)
+}
+
+/// Returns the last log which changed the status of the indexer at or before `at`
+async fn get_last_status_change(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Option<AuditLogModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Option<AuditLogDb> = audit_logs::table
+        .filter(audit_logs::indexer_id.eq(indexer_id))
+        .filter(audit_logs::to_status.is_not_null())
+        .filter(audit_logs::created_at.le(at))
+        .order((audit_logs::created_at.desc(), audit_logs::id.desc()))
+        .select(AuditLogDb::as_select())
+        .first::<AuditLogDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(|audit_log_db| audit_log_db.try_into()).transpose().map_err(InfraError::ParseError)
+}
+
+/// Returns the last log holding a config snapshot of the indexer at or before `at`
+async fn get_last_config(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Option<AuditLogModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Option<AuditLogDb> = audit_logs::table
+        .filter(audit_logs::indexer_id.eq(indexer_id))
+        .filter(audit_logs::details.is_not_null())
+        .filter(audit_logs::created_at.le(at))
+        .order((audit_logs::created_at.desc(), audit_logs::id.desc()))
+        .select(AuditLogDb::as_select())
+        .first::<AuditLogDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(|audit_log_db| audit_log_db.try_into()).transpose().map_err(InfraError::ParseError)
+}
+
+impl TryFrom<AuditLogDb> for AuditLogModel {
+    type Error = ParseError;
+    fn try_from(value: AuditLogDb) -> Result<Self, Self::Error> {
+        let model = AuditLogModel {
+            id: value.id,
+            indexer_id: value.indexer_id,
+            action: AuditAction::from_str(value.action.as_str())?,
+            from_status: value.from_status.map(|status| IndexerStatus::from_str(status.as_str())).transpose()?,
+            to_status: value.to_status.map(|status| IndexerStatus::from_str(status.as_str())).transpose()?,
+            reason: value.reason,
+            created_at: value.created_at,
+            actor: value.actor,
+            severity: AuditSeverity::from_str(value.severity.as_str())?,
+            details: value.details,
+            correlation_id: value.correlation_id,
+        };
+        Ok(model)
+    }
+}
@@ -0,0 +1,139 @@
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::contract::ContractFilter;
+use crate::domain::models::indexer::IndexerModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::{indexer_contracts, indexers};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::indexer_repository::IndexerDb;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = indexer_contracts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct IndexerContractDb {
+    pub indexer_id: Uuid,
+    pub address: String,
+    pub event_keys: serde_json::Value,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexer_contracts)]
+pub struct NewIndexerContractDb {
+    pub indexer_id: Uuid,
+    pub address: String,
+    pub event_keys: serde_json::Value,
+}
+
+impl NewIndexerContractDb {
+    pub fn from_filters(indexer_id: Uuid, filters: Vec<ContractFilter>) -> Vec<Self> {
+        filters
+            .into_iter()
+            .map(|filter| NewIndexerContractDb {
+                indexer_id,
+                address: filter.address,
+                event_keys: serde_json::Value::from(filter.event_keys),
+            })
+            .collect()
+    }
+}
+
+pub struct ContractRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ContractRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ContractRepository {
+        ContractRepository { pool }
+    }
+
+    pub async fn insert(&mut self, contracts: Vec<NewIndexerContractDb>) -> Result<(), InfraError> {
+        let mut conn = get_connection(self.pool).await?;
+        insert_with_connection(&mut conn, contracts).await
+    }
+
+    pub async fn get_by_indexer(&self, indexer_id: Uuid) -> Result<Vec<ContractFilter>, InfraError> {
+        get_by_indexer(self.pool, indexer_id).await
+    }
+
+    pub async fn get_indexers_by_address(&self, address: &str) -> Result<Vec<IndexerModel>, InfraError> {
+        get_indexers_by_address(self.pool, address).await
+    }
+}
+
+/// Inserts contracts using an existing connection so they can be part of a transaction
+pub async fn insert_with_connection(
+    conn: &mut AsyncPgConnection,
+    contracts: Vec<NewIndexerContractDb>,
+) -> Result<(), InfraError> {
+    if contracts.is_empty() {
+        return Ok(());
+    }
+    diesel::insert_into(indexer_contracts::table).values(contracts).execute(conn).await?;
+    Ok(())
+}
+
+/// Replaces the contracts of the indexer, e.g. once its script or its params changed. The caller
+/// must run it in a transaction.
+pub async fn replace_with_connection(
+    conn: &mut AsyncPgConnection,
+    indexer_id: Uuid,
+    contracts: Vec<NewIndexerContractDb>,
+) -> Result<(), InfraError> {
+    diesel::delete(indexer_contracts::table.filter(indexer_contracts::indexer_id.eq(indexer_id))).execute(conn).await?;
+    insert_with_connection(conn, contracts).await
+}
+
+async fn get_by_indexer(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid) -> Result<Vec<ContractFilter>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<IndexerContractDb> = indexer_contracts::table
+        .filter(indexer_contracts::indexer_id.eq(indexer_id))
+        .order(indexer_contracts::address.asc())
+        .select(IndexerContractDb::as_select())
+        .load::<IndexerContractDb>(&mut conn)
+        .await?;
+
+    let filters: Vec<ContractFilter> = res
+        .into_iter()
+        .map(|contract_db| contract_db.try_into())
+        .collect::<Result<Vec<ContractFilter>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(filters)
+}
+
+/// `address` must be normalized
+async fn get_indexers_by_address(
+    pool: &Pool<AsyncPgConnection>,
+    address: &str,
+) -> Result<Vec<IndexerModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<IndexerDb> = indexers::table
+        .inner_join(indexer_contracts::table)
+        .filter(indexer_contracts::address.eq(address))
+        .select(IndexerDb::as_select())
+        .load::<IndexerDb>(&mut conn)
+        .await?;
+
+    let indexers: Vec<IndexerModel> = res
+        .into_iter()
+        .map(|indexer_db| indexer_db.try_into())
+        .collect::<Result<Vec<IndexerModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(indexers)
+}
+
+impl TryFrom<IndexerContractDb> for ContractFilter {
+    type Error = ParseError;
+    fn try_from(value: IndexerContractDb) -> Result<Self, Self::Error> {
+        Ok(ContractFilter {
+            address: value.address,
+            event_keys: serde_json::from_value(value.event_keys).map_err(|_| ParseError::VariantNotFound)?,
+        })
+    }
+}
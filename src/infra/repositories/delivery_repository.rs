@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::delivery::BlockRange;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::{delivered_ranges, indexers};
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = delivered_ranges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DeliveredRangeDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub start_block: i64,
+    pub end_block: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = delivered_ranges)]
+pub struct NewDeliveredRangeDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub start_block: i64,
+    pub end_block: i64,
+}
+
+pub struct DeliveryRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl DeliveryRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> DeliveryRepository {
+        DeliveryRepository { pool }
+    }
+
+    pub async fn insert(&mut self, delivered_range: NewDeliveredRangeDb) -> Result<BlockRange, InfraError> {
+        insert(self.pool, delivered_range).await
+    }
+
+    pub async fn get_delivered_ranges(&self, indexer_id: Uuid) -> Result<Vec<BlockRange>, InfraError> {
+        get_delivered_ranges(self.pool, indexer_id).await
+    }
+
+    pub async fn get_indexers_delivering(&self, block: i64) -> Result<Vec<Uuid>, InfraError> {
+        get_indexers_delivering(self.pool, block).await
+    }
+}
+
+/// Ranges reported again are ignored, the first report is kept
+async fn insert(
+    pool: &Pool<AsyncPgConnection>,
+    delivered_range: NewDeliveredRangeDb,
+) -> Result<BlockRange, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let range = BlockRange { start_block: delivered_range.start_block, end_block: delivered_range.end_block };
+    diesel::insert_into(delivered_ranges::table)
+        .values(delivered_range)
+        .on_conflict((delivered_ranges::indexer_id, delivered_ranges::start_block, delivered_ranges::end_block))
+        .do_nothing()
+        .execute(&mut conn)
+        .await?;
+
+    Ok(range)
+}
+
+/// Returns the ranges delivered by the indexer and by the indexers backfilling it
+async fn get_delivered_ranges(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid) -> Result<Vec<BlockRange>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let backfills = indexers::table.filter(indexers::backfill_for.eq(indexer_id)).select(indexers::id);
+    let res: Vec<DeliveredRangeDb> = delivered_ranges::table
+        .filter(delivered_ranges::indexer_id.eq(indexer_id).or(delivered_ranges::indexer_id.eq_any(backfills)))
+        .order(delivered_ranges::start_block.asc())
+        .select(DeliveredRangeDb::as_select())
+        .load::<DeliveredRangeDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(BlockRange::from).collect())
+}
+
+/// Returns the indexers which delivered the block, a backfill indexer is replaced by the indexer
+/// it fills
+async fn get_indexers_delivering(pool: &Pool<AsyncPgConnection>, block: i64) -> Result<Vec<Uuid>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<(Uuid, Option<Uuid>)> = delivered_ranges::table
+        .inner_join(indexers::table)
+        .filter(delivered_ranges::start_block.le(block).and(delivered_ranges::end_block.ge(block)))
+        .select((delivered_ranges::indexer_id, indexers::backfill_for))
+        .load::<(Uuid, Option<Uuid>)>(&mut conn)
+        .await?;
+
+    let mut indexer_ids: Vec<Uuid> =
+        res.into_iter().map(|(indexer_id, backfill_for)| backfill_for.unwrap_or(indexer_id)).collect();
+    indexer_ids.sort();
+    indexer_ids.dedup();
+    Ok(indexer_ids)
+}
+
+impl From<DeliveredRangeDb> for BlockRange {
+    fn from(value: DeliveredRangeDb) -> Self {
+        BlockRange { start_block: value.start_block, end_block: value.end_block }
+    }
+}
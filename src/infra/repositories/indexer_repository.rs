@@ -1,15 +1,28 @@
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
 
 use axum::async_trait;
-use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use chrono::{DateTime, Utc};
+use diesel::sql_types::BigInt;
+use diesel::{
+    AsChangeset, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, QueryableByName, Selectable,
+    SelectableHelper,
+};
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use strum::ParseError;
 use uuid::Uuid;
 
-use crate::domain::models::indexer::{IndexerModel, IndexerStatus, IndexerType};
-use crate::infra::db::schema::indexers;
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::indexer_view::IndexerSummaryModel;
+use crate::domain::models::quarantine::QuarantinedIndexer;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::{
+    alert_rules, delivered_ranges, indexer_annotations, indexer_contracts, indexers, notification_policies,
+    script_index,
+};
 use crate::infra::errors::InfraError;
 
 #[derive(Serialize, Queryable, Selectable)]
@@ -19,13 +32,57 @@ pub struct IndexerDb {
     pub id: Uuid,
     pub status: String,
     pub type_: String,
-    pub process_id: Option<i64>,
+    pub execution_ref: Option<serde_json::Value>,
+    pub execution_seq: i64,
     pub target_url: Option<String>,
     pub table_name: Option<String>,
     pub status_server_port: Option<i32>,
     pub custom_connection_string: Option<String>,
     pub starting_block: Option<i64>,
     pub indexer_id: Option<String>,
+    pub log_level: Option<String>,
+    pub launch_config: Option<serde_json::Value>,
+    pub standby_for: Option<Uuid>,
+    pub script_permissions: Option<serde_json::Value>,
+    pub tenant_id: Option<String>,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    pub backfill_for: Option<Uuid>,
+    pub script_params: Option<serde_json::Value>,
+    pub script_checksum: Option<String>,
+    pub hooks: Option<serde_json::Value>,
+    pub sink_options: Option<serde_json::Value>,
+    pub priority: i32,
+    pub process_priority: Option<serde_json::Value>,
+    pub script_source_url: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub output_location: Option<String>,
+    pub managed_by: Option<String>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Columns of the listings in their summary view
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = indexers)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct IndexerSummaryDb {
+    pub id: Uuid,
+    pub status: String,
+    pub type_: String,
+    pub execution_ref: Option<serde_json::Value>,
+    pub target_url: Option<String>,
+    pub table_name: Option<String>,
+    pub status_server_port: Option<i32>,
+    pub starting_block: Option<i64>,
+    pub indexer_id: Option<String>,
+    pub log_level: Option<String>,
+    pub standby_for: Option<Uuid>,
+    pub tenant_id: Option<String>,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    pub backfill_for: Option<Uuid>,
+    pub priority: i32,
 }
 
 #[derive(Deserialize)]
@@ -33,6 +90,22 @@ pub struct IndexerFilter {
     pub status: Option<String>,
 }
 
+/// Filters and page of the listings served by the API, ordered by creation time
+#[derive(Debug, Default)]
+pub struct IndexerListing {
+    pub status: Option<String>,
+    pub type_: Option<String>,
+    pub descending: bool,
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+impl IndexerListing {
+    fn is_full_scan(&self) -> bool {
+        self.status.is_none() && self.type_.is_none() && self.limit.is_none() && self.offset == 0
+    }
+}
+
 #[derive(Deserialize, Insertable)]
 #[diesel(table_name = indexers)]
 pub struct NewIndexerDb {
@@ -45,6 +118,22 @@ pub struct NewIndexerDb {
     pub custom_connection_string: Option<String>,
     pub starting_block: Option<i64>,
     pub indexer_id: Option<String>,
+    pub log_level: Option<String>,
+    pub standby_for: Option<Uuid>,
+    pub script_permissions: Option<serde_json::Value>,
+    pub tenant_id: Option<String>,
+    pub stream_url: Option<String>,
+    pub ending_block: Option<i64>,
+    pub backfill_for: Option<Uuid>,
+    pub script_params: Option<serde_json::Value>,
+    pub script_checksum: Option<String>,
+    pub hooks: Option<serde_json::Value>,
+    pub sink_options: Option<serde_json::Value>,
+    pub priority: i32,
+    pub process_priority: Option<serde_json::Value>,
+    pub script_source_url: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub output_location: Option<String>,
 }
 
 #[derive(Deserialize, Insertable)]
@@ -56,10 +145,81 @@ pub struct UpdateIndexerStatusDb {
 
 #[derive(Deserialize, Insertable)]
 #[diesel(table_name = indexers)]
-pub struct UpdateIndexerStatusAndProcessIdDb {
+pub struct UpdateIndexerStatusAndExecutionRefDb {
     pub id: Uuid,
     pub status: String,
-    pub process_id: i64,
+    pub execution_ref: serde_json::Value,
+    pub launch_config: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerLogLevelDb {
+    pub id: Uuid,
+    pub log_level: Option<String>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerTargetUrlDb {
+    pub id: Uuid,
+    pub target_url: String,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerStreamUrlDb {
+    pub id: Uuid,
+    pub stream_url: String,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerScriptParamsDb {
+    pub id: Uuid,
+    pub script_params: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerPriorityDb {
+    pub id: Uuid,
+    pub priority: i32,
+}
+
+/// Settings changed together by an update of the indexer, the ones not set are left as is. The
+/// log level is cleared when set to `Some(None)`.
+#[derive(Default, AsChangeset)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerSettingsDb {
+    pub log_level: Option<Option<String>>,
+    pub target_url: Option<String>,
+    pub stream_url: Option<String>,
+    pub script_params: Option<serde_json::Value>,
+    pub script_checksum: Option<String>,
+    pub priority: Option<i32>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerManagementDb {
+    pub id: Uuid,
+    pub managed_by: Option<String>,
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerProcessPriorityDb {
+    pub id: Uuid,
+    pub process_priority: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = indexers)]
+pub struct UpdateIndexerScriptChecksumDb {
+    pub id: Uuid,
+    pub script_checksum: Option<String>,
 }
 
 #[async_trait]
@@ -68,12 +228,42 @@ pub trait Repository {
     async fn insert(&mut self, new_indexer: NewIndexerDb) -> Result<IndexerModel, InfraError>;
     async fn get(&self, id: Uuid) -> Result<IndexerModel, InfraError>;
     async fn get_by_table_name(&self, table_name: String) -> Result<IndexerModel, InfraError>;
+    /// Cheaper than `get` to know if the indexer changed, the script and config blobs aren't loaded
+    async fn get_updated_at(&self, id: Uuid) -> Result<DateTime<Utc>, InfraError>;
+    /// Changes with every change to any indexer, including deletions
+    async fn get_version(&self) -> Result<i64, InfraError>;
     async fn get_all(&self, filter: IndexerFilter) -> Result<Vec<IndexerModel>, InfraError>;
+    async fn get_page(
+        &self,
+        filter: IndexerFilter,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<IndexerModel>, InfraError>;
+    async fn get_summaries(&self, filter: IndexerFilter) -> Result<Vec<IndexerSummaryModel>, InfraError>;
+    async fn get_listing(&self, listing: IndexerListing) -> Result<Vec<IndexerModel>, InfraError>;
+    async fn get_summary_listing(&self, listing: IndexerListing) -> Result<Vec<IndexerSummaryModel>, InfraError>;
+    async fn get_all_created(&self) -> Result<Vec<(IndexerModel, DateTime<Utc>)>, InfraError>;
     async fn update_status(&mut self, indexer: UpdateIndexerStatusDb) -> Result<IndexerModel, InfraError>;
-    async fn update_status_and_process_id(
+    async fn update_status_and_execution_ref(
+        &mut self,
+        indexer: UpdateIndexerStatusAndExecutionRefDb,
+    ) -> Result<IndexerModel, InfraError>;
+    async fn update_log_level(&mut self, indexer: UpdateIndexerLogLevelDb) -> Result<IndexerModel, InfraError>;
+    async fn update_target_url(&mut self, indexer: UpdateIndexerTargetUrlDb) -> Result<IndexerModel, InfraError>;
+    async fn update_stream_url(&mut self, indexer: UpdateIndexerStreamUrlDb) -> Result<IndexerModel, InfraError>;
+    async fn update_script_params(&mut self, indexer: UpdateIndexerScriptParamsDb) -> Result<IndexerModel, InfraError>;
+    async fn update_priority(&mut self, indexer: UpdateIndexerPriorityDb) -> Result<IndexerModel, InfraError>;
+    async fn update_management(&mut self, indexer: UpdateIndexerManagementDb) -> Result<IndexerModel, InfraError>;
+    async fn update_process_priority(
         &mut self,
-        indexer: UpdateIndexerStatusAndProcessIdDb,
+        indexer: UpdateIndexerProcessPriorityDb,
     ) -> Result<IndexerModel, InfraError>;
+    async fn update_script_checksum(
+        &mut self,
+        indexer: UpdateIndexerScriptChecksumDb,
+    ) -> Result<IndexerModel, InfraError>;
+    async fn get_standby(&self, primary_id: Uuid) -> Result<Option<IndexerModel>, InfraError>;
+    async fn get_backfills(&self, indexer_id: Uuid) -> Result<Vec<IndexerModel>, InfraError>;
 }
 
 pub struct IndexerRepository<'a> {
@@ -100,10 +290,43 @@ impl Repository for IndexerRepository<'_> {
         get_by_table_name(self.pool, table_name).await
     }
 
+    async fn get_updated_at(&self, id: Uuid) -> Result<DateTime<Utc>, InfraError> {
+        get_updated_at(self.pool, id).await
+    }
+
+    async fn get_version(&self) -> Result<i64, InfraError> {
+        get_version(self.pool).await
+    }
+
     async fn get_all(&self, filter: IndexerFilter) -> Result<Vec<IndexerModel>, InfraError> {
         get_all(self.pool, filter).await
     }
 
+    async fn get_page(
+        &self,
+        filter: IndexerFilter,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<IndexerModel>, InfraError> {
+        get_page(self.pool, filter, after, limit).await
+    }
+
+    async fn get_summaries(&self, filter: IndexerFilter) -> Result<Vec<IndexerSummaryModel>, InfraError> {
+        get_summaries(self.pool, filter).await
+    }
+
+    async fn get_listing(&self, listing: IndexerListing) -> Result<Vec<IndexerModel>, InfraError> {
+        get_listing(self.pool, listing).await
+    }
+
+    async fn get_summary_listing(&self, listing: IndexerListing) -> Result<Vec<IndexerSummaryModel>, InfraError> {
+        get_summary_listing(self.pool, listing).await
+    }
+
+    async fn get_all_created(&self) -> Result<Vec<(IndexerModel, DateTime<Utc>)>, InfraError> {
+        get_all_created(self.pool).await
+    }
+
     async fn update_status(&mut self, indexer: UpdateIndexerStatusDb) -> Result<IndexerModel, InfraError> {
         update_status(self.pool, indexer).await
     }
@@ -112,16 +335,92 @@ impl Repository for IndexerRepository<'_> {
         delete(self.pool, id).await
     }
 
-    async fn update_status_and_process_id(
+    async fn update_status_and_execution_ref(
+        &mut self,
+        indexer: UpdateIndexerStatusAndExecutionRefDb,
+    ) -> Result<IndexerModel, InfraError> {
+        update_status_and_execution_ref(self.pool, indexer).await
+    }
+
+    async fn update_log_level(&mut self, indexer: UpdateIndexerLogLevelDb) -> Result<IndexerModel, InfraError> {
+        update_log_level(self.pool, indexer).await
+    }
+
+    async fn update_target_url(&mut self, indexer: UpdateIndexerTargetUrlDb) -> Result<IndexerModel, InfraError> {
+        update_target_url(self.pool, indexer).await
+    }
+
+    async fn update_stream_url(&mut self, indexer: UpdateIndexerStreamUrlDb) -> Result<IndexerModel, InfraError> {
+        update_stream_url(self.pool, indexer).await
+    }
+
+    async fn update_script_params(&mut self, indexer: UpdateIndexerScriptParamsDb) -> Result<IndexerModel, InfraError> {
+        update_script_params(self.pool, indexer).await
+    }
+
+    async fn update_priority(&mut self, indexer: UpdateIndexerPriorityDb) -> Result<IndexerModel, InfraError> {
+        update_priority(self.pool, indexer).await
+    }
+
+    async fn update_management(&mut self, indexer: UpdateIndexerManagementDb) -> Result<IndexerModel, InfraError> {
+        update_management(self.pool, indexer).await
+    }
+
+    async fn update_process_priority(
+        &mut self,
+        indexer: UpdateIndexerProcessPriorityDb,
+    ) -> Result<IndexerModel, InfraError> {
+        update_process_priority(self.pool, indexer).await
+    }
+
+    async fn update_script_checksum(
         &mut self,
-        indexer: UpdateIndexerStatusAndProcessIdDb,
+        indexer: UpdateIndexerScriptChecksumDb,
     ) -> Result<IndexerModel, InfraError> {
-        update_status_and_process_id(self.pool, indexer).await
+        update_script_checksum(self.pool, indexer).await
+    }
+
+    async fn get_standby(&self, primary_id: Uuid) -> Result<Option<IndexerModel>, InfraError> {
+        get_standby(self.pool, primary_id).await
+    }
+
+    async fn get_backfills(&self, indexer_id: Uuid) -> Result<Vec<IndexerModel>, InfraError> {
+        get_backfills(self.pool, indexer_id).await
     }
 }
 
+async fn get_standby(pool: &Pool<AsyncPgConnection>, primary_id: Uuid) -> Result<Option<IndexerModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = indexers::table
+        .filter(indexers::standby_for.eq(primary_id))
+        .select(IndexerDb::as_select())
+        .first::<IndexerDb>(&mut conn)
+        .await
+        .optional()?
+        .map(|indexer_db| indexer_db.try_into())
+        .transpose()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn get_backfills(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid) -> Result<Vec<IndexerModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = indexers::table
+        .filter(indexers::backfill_for.eq(indexer_id))
+        .select(IndexerDb::as_select())
+        .load::<IndexerDb>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|indexer_db| indexer_db.try_into())
+        .collect::<Result<Vec<IndexerModel>, _>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
 async fn _insert(pool: &Pool<AsyncPgConnection>, new_indexer: NewIndexerDb) -> Result<IndexerModel, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     let res = diesel::insert_into(indexers::table)
         .values(new_indexer)
         .returning(IndexerDb::as_returning())
@@ -133,8 +432,10 @@ async fn _insert(pool: &Pool<AsyncPgConnection>, new_indexer: NewIndexerDb) -> R
     Ok(res)
 }
 
+/// Lifecycle operations decide on the status read here, the indexers are read from the primary
+/// so that they never see a stale status. Only the listings go to the replica.
 async fn get(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<IndexerModel, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     let res = indexers::table
         .filter(indexers::id.eq(id))
         .select(IndexerDb::as_select())
@@ -146,15 +447,45 @@ async fn get(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<IndexerModel, I
     Ok(res)
 }
 
+async fn get_updated_at(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<DateTime<Utc>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = indexers::table
+        .filter(indexers::id.eq(id))
+        .select(indexers::updated_at)
+        .get_result::<DateTime<Utc>>(&mut conn)
+        .await?;
+
+    Ok(res)
+}
+
+#[derive(QueryableByName)]
+struct IndexersVersionDb {
+    #[diesel(sql_type = BigInt)]
+    last_value: i64,
+}
+
+/// Read from the primary, a replica lagging behind would hand out a version older than the
+/// indexers the caller may have just changed
+async fn get_version(pool: &Pool<AsyncPgConnection>) -> Result<i64, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    // `last_value` is 1 both before and after the first `nextval`, only `is_called` tells them apart
+    let res: IndexersVersionDb =
+        diesel::sql_query("SELECT CASE WHEN is_called THEN last_value ELSE 0 END AS last_value FROM indexers_version")
+            .get_result(&mut conn)
+            .await?;
+
+    Ok(res.last_value)
+}
+
 async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<(), InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     diesel::delete(indexers::table.filter(indexers::id.eq(id))).execute(&mut conn).await?;
 
     Ok(())
 }
 
 async fn get_by_table_name(pool: &Pool<AsyncPgConnection>, table_name: String) -> Result<IndexerModel, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     let res = indexers::table
         .filter(indexers::table_name.eq(table_name))
         .select(IndexerDb::as_select())
@@ -167,30 +498,334 @@ async fn get_by_table_name(pool: &Pool<AsyncPgConnection>, table_name: String) -
 }
 
 async fn get_all(pool: &Pool<AsyncPgConnection>, filter: IndexerFilter) -> Result<Vec<IndexerModel>, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
+    let filter_is_empty = filter.status.is_none();
     let mut query = indexers::table.into_boxed::<diesel::pg::Pg>();
     if let Some(status) = filter.status {
         query = query.filter(indexers::status.eq(status));
     }
     let res: Vec<IndexerDb> = query.select(IndexerDb::as_select()).load::<IndexerDb>(&mut conn).await?;
 
+    Ok(read_listed_rows(res, filter_is_empty))
+}
+
+/// Indexers ordered by id, after the `after` one
+async fn get_page(
+    pool: &Pool<AsyncPgConnection>,
+    filter: IndexerFilter,
+    after: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<IndexerModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = indexers::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(status) = filter.status {
+        query = query.filter(indexers::status.eq(status));
+    }
+    if let Some(after) = after {
+        query = query.filter(indexers::id.gt(after));
+    }
+    let res: Vec<IndexerDb> = query
+        .order(indexers::id.asc())
+        .limit(limit)
+        .select(IndexerDb::as_select())
+        .load::<IndexerDb>(&mut conn)
+        .await?;
+
+    Ok(read_listed_rows(res, false))
+}
+
+/// Only the summary columns are loaded, rows which can't be read are skipped like in the full
+/// listing but the quarantine is left to it
+async fn get_summaries(
+    pool: &Pool<AsyncPgConnection>,
+    filter: IndexerFilter,
+) -> Result<Vec<IndexerSummaryModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = indexers::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(status) = filter.status {
+        query = query.filter(indexers::status.eq(status));
+    }
+    let res: Vec<IndexerSummaryDb> =
+        query.select(IndexerSummaryDb::as_select()).load::<IndexerSummaryDb>(&mut conn).await?;
+
+    Ok(res
+        .into_iter()
+        .filter_map(|summary_db| {
+            let id = summary_db.id;
+            IndexerSummaryModel::try_from(summary_db)
+                .map_err(|e| tracing::warn!("Skipping indexer {} which can't be read: {}", id, e))
+                .ok()
+        })
+        .collect())
+}
+
+/// Indexers still in `Created`, with the time they were created at. Rows which can't be read are
+/// skipped, they're quarantined by the full listing.
+async fn get_all_created(pool: &Pool<AsyncPgConnection>) -> Result<Vec<(IndexerModel, DateTime<Utc>)>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res: Vec<(IndexerDb, DateTime<Utc>)> = indexers::table
+        .filter(indexers::status.eq(IndexerStatus::Created.to_string()))
+        .order(indexers::created_at.asc())
+        .select((IndexerDb::as_select(), indexers::created_at))
+        .load::<(IndexerDb, DateTime<Utc>)>(&mut conn)
+        .await?;
+
+    Ok(res
+        .into_iter()
+        .filter_map(|(indexer_db, created_at)| {
+            let id = indexer_db.id;
+            IndexerModel::try_from(indexer_db)
+                .map(|indexer_model| (indexer_model, created_at))
+                .map_err(|e| tracing::warn!("Skipping indexer {} which can't be read: {}", id, e))
+                .ok()
+        })
+        .collect())
+}
+
+fn listing_query(listing: &IndexerListing) -> indexers::BoxedQuery<'static, diesel::pg::Pg> {
+    let mut query = indexers::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(status) = &listing.status {
+        query = query.filter(indexers::status.eq(status.clone()));
+    }
+    if let Some(type_) = &listing.type_ {
+        query = query.filter(indexers::type_.eq(type_.clone()));
+    }
+    // the id breaks the ties so that the pages don't overlap
+    query = match listing.descending {
+        true => query.order((indexers::created_at.desc(), indexers::id.desc())),
+        false => query.order((indexers::created_at.asc(), indexers::id.asc())),
+    };
+    if let Some(limit) = listing.limit {
+        query = query.limit(limit);
+    }
+    query.offset(listing.offset)
+}
+
+async fn get_listing(pool: &Pool<AsyncPgConnection>, listing: IndexerListing) -> Result<Vec<IndexerModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<IndexerDb> =
+        listing_query(&listing).select(IndexerDb::as_select()).load::<IndexerDb>(&mut conn).await?;
+
+    Ok(read_listed_rows(res, listing.is_full_scan()))
+}
+
+async fn get_summary_listing(
+    pool: &Pool<AsyncPgConnection>,
+    listing: IndexerListing,
+) -> Result<Vec<IndexerSummaryModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<IndexerSummaryDb> =
+        listing_query(&listing).select(IndexerSummaryDb::as_select()).load::<IndexerSummaryDb>(&mut conn).await?;
+
+    Ok(res
+        .into_iter()
+        .filter_map(|summary_db| {
+            let id = summary_db.id;
+            IndexerSummaryModel::try_from(summary_db)
+                .map_err(|e| tracing::warn!("Skipping indexer {} which can't be read: {}", id, e))
+                .ok()
+        })
+        .collect())
+}
+
+/// Rows left with values the data migrations couldn't map are quarantined rather than failing
+/// the whole listing. `full_scan` tells whether every row was listed.
+fn read_listed_rows(res: Vec<IndexerDb>, full_scan: bool) -> Vec<IndexerModel> {
+    let mut quarantined = Vec::new();
     let indexers: Vec<IndexerModel> = res
         .into_iter()
-        .map(|indexer_db| indexer_db.try_into())
-        .collect::<Result<Vec<IndexerModel>, ParseError>>()
-        .map_err(InfraError::ParseError)?;
+        .filter_map(|indexer_db| {
+            let (id, status, type_) = (indexer_db.id, indexer_db.status.clone(), indexer_db.type_.clone());
+            match IndexerModel::try_from(indexer_db) {
+                Ok(indexer_model) => Some(indexer_model),
+                Err(e) => {
+                    tracing::warn!("Skipping indexer {} which can't be read: {}", id, e);
+                    quarantined.push((id, status, type_, e.to_string()));
+                    None
+                }
+            }
+        })
+        .collect();
+    update_quarantine(indexers.iter().map(|indexer| indexer.id), quarantined, full_scan);
+
+    indexers
+}
 
-    Ok(indexers)
+/// Indexer rows found unreadable by the listings
+static QUARANTINE: OnceLock<Mutex<HashMap<Uuid, QuarantinedIndexer>>> = OnceLock::new();
+
+/// Rows which can be read again are released. A listing of every row replaces the quarantine
+/// so that rows deleted in the meantime are dropped too.
+fn update_quarantine(
+    readable: impl Iterator<Item = Uuid>,
+    unreadable: Vec<(Uuid, String, String, String)>,
+    full_scan: bool,
+) {
+    let mut quarantine = QUARANTINE.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner());
+    if full_scan {
+        let ids: Vec<Uuid> = unreadable.iter().map(|(id, ..)| *id).collect();
+        quarantine.retain(|id, _| ids.contains(id));
+    } else {
+        readable.for_each(|id| {
+            quarantine.remove(&id);
+        });
+    }
+    for (indexer_id, status, type_, error) in unreadable {
+        let quarantined_at =
+            quarantine.get(&indexer_id).map(|indexer| indexer.quarantined_at).unwrap_or_else(chrono::Utc::now);
+        quarantine.insert(indexer_id, QuarantinedIndexer { indexer_id, status, type_, error, quarantined_at });
+    }
+}
+
+/// Indexers left out of the listings because their row can't be read, as of the last listing
+pub fn get_quarantined_indexers() -> Vec<QuarantinedIndexer> {
+    let quarantine = QUARANTINE.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner());
+    let mut indexers: Vec<QuarantinedIndexer> = quarantine.values().cloned().collect();
+    indexers.sort_by_key(|indexer| indexer.quarantined_at);
+    indexers
+}
+
+/// Rows of the other tables deleted along with the indexer, by table. Scheduled actions are
+/// deleted too but listed by their repository.
+pub async fn count_dependent_rows(
+    pool: &Pool<AsyncPgConnection>,
+    id: Uuid,
+) -> Result<BTreeMap<String, i64>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let counts: [(&str, i64); 6] = [
+        (
+            "indexer_contracts",
+            indexer_contracts::table.filter(indexer_contracts::indexer_id.eq(id)).count().get_result(&mut conn).await?,
+        ),
+        (
+            "delivered_ranges",
+            delivered_ranges::table.filter(delivered_ranges::indexer_id.eq(id)).count().get_result(&mut conn).await?,
+        ),
+        (
+            "indexer_annotations",
+            indexer_annotations::table
+                .filter(indexer_annotations::indexer_id.eq(id))
+                .count()
+                .get_result(&mut conn)
+                .await?,
+        ),
+        (
+            "notification_policies",
+            notification_policies::table
+                .filter(notification_policies::indexer_id.eq(id))
+                .count()
+                .get_result(&mut conn)
+                .await?,
+        ),
+        ("alert_rules", alert_rules::table.filter(alert_rules::indexer_id.eq(id)).count().get_result(&mut conn).await?),
+        (
+            "script_index",
+            script_index::table.filter(script_index::indexer_id.eq(id)).count().get_result(&mut conn).await?,
+        ),
+    ];
+
+    Ok(counts.into_iter().filter(|(_, count)| *count > 0).map(|(table, count)| (table.to_string(), count)).collect())
 }
 
 async fn update_status(
     pool: &Pool<AsyncPgConnection>,
     indexer: UpdateIndexerStatusDb,
 ) -> Result<IndexerModel, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     let res = diesel::update(indexers::table)
         .filter(indexers::id.eq(indexer.id))
         .set(indexers::status.eq(indexer.status))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_status_and_execution_ref(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerStatusAndExecutionRefDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set((
+            indexers::status.eq(indexer.status),
+            indexers::execution_ref.eq(indexer.execution_ref),
+            indexers::execution_seq.eq(indexers::execution_seq + 1),
+            indexers::launch_config.eq(indexer.launch_config),
+        ))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_log_level(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerLogLevelDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::log_level.eq(indexer.log_level))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_target_url(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerTargetUrlDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::target_url.eq(indexer.target_url))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_stream_url(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerStreamUrlDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::stream_url.eq(indexer.stream_url))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_script_params(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerScriptParamsDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::script_params.eq(indexer.script_params))
+        .returning(IndexerDb::as_returning())
         .get_result::<IndexerDb>(&mut conn)
         .await?
         .try_into()
@@ -199,14 +834,85 @@ async fn update_status(
     Ok(res)
 }
 
-async fn update_status_and_process_id(
+/// Updates the settings using an existing connection so the update can be part of a transaction.
+/// At least one of the settings must be set.
+pub async fn update_settings_with_connection(
+    conn: &mut AsyncPgConnection,
+    id: Uuid,
+    settings: UpdateIndexerSettingsDb,
+) -> Result<IndexerModel, InfraError> {
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(id))
+        .set(settings)
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_priority(
     pool: &Pool<AsyncPgConnection>,
-    indexer: UpdateIndexerStatusAndProcessIdDb,
+    indexer: UpdateIndexerPriorityDb,
 ) -> Result<IndexerModel, InfraError> {
-    let mut conn = pool.get().await?;
+    let mut conn = get_connection(pool).await?;
     let res = diesel::update(indexers::table)
         .filter(indexers::id.eq(indexer.id))
-        .set((indexers::status.eq(indexer.status), indexers::process_id.eq(indexer.process_id)))
+        .set(indexers::priority.eq(indexer.priority))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_management(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerManagementDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set((indexers::managed_by.eq(indexer.managed_by), indexers::archived_at.eq(indexer.archived_at)))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_process_priority(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerProcessPriorityDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::process_priority.eq(indexer.process_priority))
+        .returning(IndexerDb::as_returning())
+        .get_result::<IndexerDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update_script_checksum(
+    pool: &Pool<AsyncPgConnection>,
+    indexer: UpdateIndexerScriptChecksumDb,
+) -> Result<IndexerModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(indexers::table)
+        .filter(indexers::id.eq(indexer.id))
+        .set(indexers::script_checksum.eq(indexer.script_checksum))
+        .returning(IndexerDb::as_returning())
         .get_result::<IndexerDb>(&mut conn)
         .await?
         .try_into()
@@ -223,12 +929,33 @@ impl TryFrom<NewIndexerDb> for IndexerModel {
             status: value.status,
             type_: value.type_,
             target_url: value.target_url,
-            process_id: None,
+            execution_ref: None,
+            execution_seq: 0,
             table_name: value.table_name,
             status_server_port: value.status_server_port,
             custom_connection_string: value.custom_connection_string,
             starting_block: value.starting_block,
             indexer_id: value.indexer_id,
+            log_level: value.log_level,
+            launch_config: None,
+            standby_for: value.standby_for,
+            script_permissions: value.script_permissions,
+            tenant_id: value.tenant_id,
+            stream_url: value.stream_url,
+            ending_block: value.ending_block,
+            backfill_for: value.backfill_for,
+            script_params: value.script_params,
+            script_checksum: value.script_checksum,
+            hooks: value.hooks,
+            sink_options: value.sink_options,
+            priority: value.priority,
+            process_priority: value.process_priority,
+            script_source_url: value.script_source_url,
+            project_id: value.project_id,
+            output_location: value.output_location,
+            managed_by: None,
+            archived_at: None,
+            updated_at: Utc::now(),
         }
         .try_into()?;
         Ok(model)
@@ -241,7 +968,9 @@ impl TryFrom<IndexerDb> for IndexerModel {
         let model = IndexerModel {
             id: value.id,
             status: IndexerStatus::from_str(value.status.as_str())?,
-            process_id: value.process_id,
+            // a ref we can't read is treated as unknown
+            execution_ref: value.execution_ref.and_then(|execution_ref| serde_json::from_value(execution_ref).ok()),
+            execution_seq: value.execution_seq,
             indexer_type: IndexerType::from_str(value.type_.as_str())?,
             target_url: value.target_url,
             table_name: value.table_name,
@@ -249,16 +978,74 @@ impl TryFrom<IndexerDb> for IndexerModel {
             custom_connection_string: value.custom_connection_string,
             starting_block: value.starting_block,
             indexer_id: value.indexer_id,
+            log_level: value.log_level.map(|log_level| IndexerLogLevel::from_str(log_level.as_str())).transpose()?,
+            // a launch config we can't read is treated as unknown
+            launch_config: value.launch_config.and_then(|launch_config| serde_json::from_value(launch_config).ok()),
+            standby_for: value.standby_for,
+            // permissions we can't read fall back to denying everything
+            script_permissions: value
+                .script_permissions
+                .and_then(|script_permissions| serde_json::from_value(script_permissions).ok())
+                .unwrap_or_default(),
+            tenant_id: value.tenant_id,
+            stream_url: value.stream_url,
+            ending_block: value.ending_block,
+            backfill_for: value.backfill_for,
+            script_params: value
+                .script_params
+                .and_then(|script_params| serde_json::from_value(script_params).ok())
+                .unwrap_or_default(),
+            script_checksum: value.script_checksum,
+            // hooks we can't read are skipped
+            hooks: value.hooks.and_then(|hooks| serde_json::from_value(hooks).ok()).unwrap_or_default(),
+            sink_options: value.sink_options.and_then(|sink_options| serde_json::from_value(sink_options).ok()),
+            priority: value.priority,
+            // a priority we can't read keeps the defaults of the OS
+            process_priority: value
+                .process_priority
+                .and_then(|process_priority| serde_json::from_value(process_priority).ok())
+                .unwrap_or_default(),
+            script_source_url: value.script_source_url,
+            project_id: value.project_id,
+            output_location: value.output_location,
+            managed_by: value.managed_by,
+            archived_at: value.archived_at,
+            updated_at: value.updated_at,
         };
         Ok(model)
     }
 }
 
+impl TryFrom<IndexerSummaryDb> for IndexerSummaryModel {
+    type Error = ParseError;
+    fn try_from(value: IndexerSummaryDb) -> Result<Self, Self::Error> {
+        Ok(IndexerSummaryModel {
+            id: value.id,
+            status: IndexerStatus::from_str(value.status.as_str())?,
+            indexer_type: IndexerType::from_str(value.type_.as_str())?,
+            execution_ref: value.execution_ref.and_then(|execution_ref| serde_json::from_value(execution_ref).ok()),
+            target_url: value.target_url,
+            table_name: value.table_name,
+            status_server_port: value.status_server_port,
+            starting_block: value.starting_block,
+            indexer_id: value.indexer_id,
+            log_level: value.log_level.map(|log_level| IndexerLogLevel::from_str(log_level.as_str())).transpose()?,
+            standby_for: value.standby_for,
+            tenant_id: value.tenant_id,
+            stream_url: value.stream_url,
+            ending_block: value.ending_block,
+            backfill_for: value.backfill_for,
+            priority: value.priority,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
 
     use super::*;
+    use crate::domain::models::execution::ExecutionRef;
 
     #[rstest]
     #[case("Created", Ok(IndexerStatus::Created))]
@@ -266,13 +1053,15 @@ mod tests {
     #[case("FailedRunning", Ok(IndexerStatus::FailedRunning))]
     #[case("Stopped", Ok(IndexerStatus::Stopped))]
     #[case("FailedStopping", Ok(IndexerStatus::FailedStopping))]
+    #[case("Abandoned", Ok(IndexerStatus::Abandoned))]
+    #[case("PendingApproval", Ok(IndexerStatus::PendingApproval))]
     #[case("InvalidStatus", Err(ParseError::VariantNotFound))]
     fn test_from_indexer_db_to_indexer_model_status(
         #[case] status: &'static str,
         #[case] expected_status: Result<IndexerStatus, ParseError>,
     ) {
         let id = Uuid::new_v4();
-        let process_id = Some(1234);
+        let execution_ref = ExecutionRef::Pid { pid: 1234, start_time: Some(42) };
         let target_url = "http://example.com";
         let indexer_type = "Webhook";
         let table_name = "test_table";
@@ -281,13 +1070,34 @@ mod tests {
             id,
             status: status.to_string(),
             type_: indexer_type.to_string(),
-            process_id,
+            execution_ref: Some(serde_json::to_value(&execution_ref).unwrap()),
+            execution_seq: 1,
             target_url: Some(target_url.to_string()),
             table_name: Some(table_name.into()),
             status_server_port: Some(1234),
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            launch_config: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+            managed_by: None,
+            archived_at: None,
+            updated_at: Utc::now(),
         };
 
         let indexer_model: Result<IndexerModel, ParseError> = indexer_db.try_into();
@@ -297,7 +1107,8 @@ mod tests {
                 assert_eq!(model.id, id);
                 assert_eq!(model.status, expected_status.unwrap());
                 assert_eq!(model.indexer_type, IndexerType::from_str(indexer_type).unwrap());
-                assert_eq!(model.process_id, process_id);
+                assert_eq!(model.execution_ref, Some(execution_ref));
+                assert_eq!(model.execution_seq, 1);
                 assert_eq!(model.target_url, Some(target_url.to_string()));
                 assert_eq!(model.table_name, Some(table_name.into()));
             }
@@ -315,7 +1126,7 @@ mod tests {
         #[case] expected_type: Result<IndexerType, ParseError>,
     ) {
         let id = Uuid::new_v4();
-        let process_id = Some(1234);
+        let execution_ref = ExecutionRef::Pid { pid: 1234, start_time: Some(42) };
         let target_url = "http://example.com";
         let status = "Created";
         let table_name = "test_table";
@@ -324,13 +1135,34 @@ mod tests {
             id,
             status: status.to_string(),
             type_: indexer_type.to_string(),
-            process_id,
+            execution_ref: Some(serde_json::to_value(&execution_ref).unwrap()),
+            execution_seq: 1,
             target_url: Some(target_url.to_string()),
             table_name: Some(table_name.into()),
             status_server_port: Some(1234),
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            launch_config: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+            managed_by: None,
+            archived_at: None,
+            updated_at: Utc::now(),
         };
 
         let indexer_model: Result<IndexerModel, ParseError> = indexer_db.try_into();
@@ -340,7 +1172,8 @@ mod tests {
                 assert_eq!(model.id, id);
                 assert_eq!(model.status, IndexerStatus::from_str(status).unwrap());
                 assert_eq!(model.indexer_type, expected_type.unwrap());
-                assert_eq!(model.process_id, process_id);
+                assert_eq!(model.execution_ref, Some(execution_ref));
+                assert_eq!(model.execution_seq, 1);
                 assert_eq!(model.target_url, Some(target_url.to_string()));
                 assert_eq!(model.table_name, Some(table_name.into()));
             }
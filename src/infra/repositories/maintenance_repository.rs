@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::maintenance::{MaintenanceWindowKind, MaintenanceWindowModel};
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::maintenance_windows;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = maintenance_windows)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MaintenanceWindowDb {
+    pub id: Uuid,
+    pub tenant_id: Option<String>,
+    pub kind: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = maintenance_windows)]
+pub struct NewMaintenanceWindowDb {
+    pub id: Uuid,
+    pub tenant_id: Option<String>,
+    pub kind: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct MaintenanceWindowFilter {
+    pub tenant_id: Option<String>,
+    /// Only returns the windows which haven't ended at this time
+    pub ending_after: Option<DateTime<Utc>>,
+}
+
+pub struct MaintenanceRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl MaintenanceRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> MaintenanceRepository {
+        MaintenanceRepository { pool }
+    }
+
+    pub async fn insert(&mut self, window: NewMaintenanceWindowDb) -> Result<MaintenanceWindowModel, InfraError> {
+        insert(self.pool, window).await
+    }
+
+    pub async fn get_all(&self, filter: MaintenanceWindowFilter) -> Result<Vec<MaintenanceWindowModel>, InfraError> {
+        get_all(self.pool, filter).await
+    }
+
+    pub async fn get_active(
+        &self,
+        tenant_id: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<MaintenanceWindowModel>, InfraError> {
+        get_active(self.pool, tenant_id, at).await
+    }
+
+    pub async fn delete(&mut self, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, id).await
+    }
+}
+
+async fn insert(
+    pool: &Pool<AsyncPgConnection>,
+    window: NewMaintenanceWindowDb,
+) -> Result<MaintenanceWindowModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(maintenance_windows::table)
+        .values(window)
+        .returning(MaintenanceWindowDb::as_returning())
+        .get_result(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+/// Returns the windows matching the filter ordered by start
+async fn get_all(
+    pool: &Pool<AsyncPgConnection>,
+    filter: MaintenanceWindowFilter,
+) -> Result<Vec<MaintenanceWindowModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = maintenance_windows::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(tenant_id) = filter.tenant_id {
+        query = query.filter(maintenance_windows::tenant_id.eq(tenant_id));
+    }
+    if let Some(ending_after) = filter.ending_after {
+        query = query.filter(maintenance_windows::ends_at.gt(ending_after));
+    }
+    let res: Vec<MaintenanceWindowDb> = query
+        .order(maintenance_windows::starts_at.asc())
+        .select(MaintenanceWindowDb::as_select())
+        .load::<MaintenanceWindowDb>(&mut conn)
+        .await?;
+
+    let windows: Vec<MaintenanceWindowModel> = res
+        .into_iter()
+        .map(|window_db| window_db.try_into())
+        .collect::<Result<Vec<MaintenanceWindowModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(windows)
+}
+
+/// Returns the windows active at `at` which apply to the tenant, including the global ones
+async fn get_active(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: Option<&str>,
+    at: DateTime<Utc>,
+) -> Result<Vec<MaintenanceWindowModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let mut query = maintenance_windows::table
+        .filter(maintenance_windows::starts_at.le(at))
+        .filter(maintenance_windows::ends_at.gt(at))
+        .into_boxed::<diesel::pg::Pg>();
+    query = match tenant_id {
+        Some(tenant_id) => {
+            query.filter(maintenance_windows::tenant_id.is_null().or(maintenance_windows::tenant_id.eq(tenant_id)))
+        }
+        None => query.filter(maintenance_windows::tenant_id.is_null()),
+    };
+    let res: Vec<MaintenanceWindowDb> =
+        query.select(MaintenanceWindowDb::as_select()).load::<MaintenanceWindowDb>(&mut conn).await?;
+
+    let windows: Vec<MaintenanceWindowModel> = res
+        .into_iter()
+        .map(|window_db| window_db.try_into())
+        .collect::<Result<Vec<MaintenanceWindowModel>, ParseError>>()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(windows)
+}
+
+/// Returns whether the window existed
+async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted =
+        diesel::delete(maintenance_windows::table.filter(maintenance_windows::id.eq(id))).execute(&mut conn).await?;
+
+    Ok(deleted > 0)
+}
+
+impl TryFrom<MaintenanceWindowDb> for MaintenanceWindowModel {
+    type Error = ParseError;
+    fn try_from(value: MaintenanceWindowDb) -> Result<Self, Self::Error> {
+        let model = MaintenanceWindowModel {
+            id: value.id,
+            tenant_id: value.tenant_id,
+            kind: MaintenanceWindowKind::from_str(value.kind.as_str())?,
+            starts_at: value.starts_at,
+            ends_at: value.ends_at,
+            reason: value.reason,
+            created_at: value.created_at,
+        };
+        Ok(model)
+    }
+}
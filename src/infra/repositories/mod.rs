@@ -1 +1,17 @@
+pub mod alert_rule_repository;
+pub mod annotation_repository;
+pub mod approved_target_repository;
+pub mod audit_repository;
+pub mod contract_repository;
+pub mod delivery_repository;
 pub mod indexer_repository;
+pub mod maintenance_repository;
+pub mod notification_policy_repository;
+pub mod project_repository;
+pub mod scheduled_action_repository;
+pub mod script_index_repository;
+pub mod script_scan_repository;
+pub mod start_token_repository;
+pub mod tenant_repository;
+pub mod upload_repository;
+pub mod usage_repository;
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::notification::NotificationPolicy;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::notification_policies;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = notification_policies)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NotificationPolicyDb {
+    pub indexer_id: Uuid,
+    pub max_alerts_per_hour: i32,
+    pub duplicate_window_seconds: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = notification_policies)]
+pub struct NewNotificationPolicyDb {
+    pub indexer_id: Uuid,
+    pub max_alerts_per_hour: i32,
+    pub duplicate_window_seconds: i32,
+}
+
+pub struct NotificationPolicyRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl NotificationPolicyRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> NotificationPolicyRepository {
+        NotificationPolicyRepository { pool }
+    }
+
+    /// Policy the indexer overrides the default one with, if any
+    pub async fn get(&self, indexer_id: Uuid) -> Result<Option<NotificationPolicy>, InfraError> {
+        get(self.pool, indexer_id).await
+    }
+
+    pub async fn upsert(&mut self, policy: NewNotificationPolicyDb) -> Result<NotificationPolicy, InfraError> {
+        upsert(self.pool, policy).await
+    }
+
+    /// Returns whether the indexer had a policy
+    pub async fn delete(&mut self, indexer_id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, indexer_id).await
+    }
+}
+
+async fn get(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid) -> Result<Option<NotificationPolicy>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = notification_policies::table
+        .filter(notification_policies::indexer_id.eq(indexer_id))
+        .select(NotificationPolicyDb::as_select())
+        .first::<NotificationPolicyDb>(&mut conn)
+        .await
+        .optional()?
+        .map(NotificationPolicy::from);
+
+    Ok(res)
+}
+
+async fn upsert(
+    pool: &Pool<AsyncPgConnection>,
+    policy: NewNotificationPolicyDb,
+) -> Result<NotificationPolicy, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(notification_policies::table)
+        .values(policy)
+        .on_conflict(notification_policies::indexer_id)
+        .do_update()
+        .set((
+            notification_policies::max_alerts_per_hour.eq(excluded(notification_policies::max_alerts_per_hour)),
+            notification_policies::duplicate_window_seconds
+                .eq(excluded(notification_policies::duplicate_window_seconds)),
+            notification_policies::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(NotificationPolicyDb::as_returning())
+        .get_result::<NotificationPolicyDb>(&mut conn)
+        .await?
+        .into();
+
+    Ok(res)
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(notification_policies::table.filter(notification_policies::indexer_id.eq(indexer_id)))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(deleted > 0)
+}
+
+impl From<NotificationPolicyDb> for NotificationPolicy {
+    fn from(value: NotificationPolicyDb) -> Self {
+        NotificationPolicy {
+            max_alerts_per_hour: value.max_alerts_per_hour as u32,
+            duplicate_window_seconds: value.duplicate_window_seconds as u64,
+        }
+    }
+}
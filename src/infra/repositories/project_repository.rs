@@ -0,0 +1,222 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerModel};
+use crate::domain::models::project::ProjectModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::{indexers, projects};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::indexer_repository::IndexerDb;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = projects)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ProjectDb {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<String>,
+    pub secret_path_prefix: Option<String>,
+    pub role_bindings: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = projects)]
+pub struct NewProjectDb {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<String>,
+    pub secret_path_prefix: Option<String>,
+    pub role_bindings: serde_json::Value,
+}
+
+pub struct ProjectRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ProjectRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ProjectRepository {
+        ProjectRepository { pool }
+    }
+
+    /// Projects ordered by tenant and name, only the ones of the tenant if one is given
+    pub async fn get_all(&self, tenant_id: Option<&str>) -> Result<Vec<ProjectModel>, InfraError> {
+        get_all(self.pool, tenant_id).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<ProjectModel>, InfraError> {
+        get(self.pool, id).await
+    }
+
+    pub async fn get_by_name(&self, tenant_id: &str, name: &str) -> Result<Option<ProjectModel>, InfraError> {
+        get_by_name(self.pool, tenant_id, name).await
+    }
+
+    pub async fn insert(&mut self, project: NewProjectDb) -> Result<ProjectModel, InfraError> {
+        insert(self.pool, project).await
+    }
+
+    /// Replaces the project with the same id, its tenant is kept. Returns `None` if there's none.
+    pub async fn update(&mut self, project: NewProjectDb) -> Result<Option<ProjectModel>, InfraError> {
+        update(self.pool, project).await
+    }
+
+    /// Returns whether the project existed. Projects still grouping indexers can't be deleted.
+    pub async fn delete(&mut self, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, id).await
+    }
+
+    pub async fn get_indexers(&self, id: Uuid) -> Result<Vec<IndexerModel>, InfraError> {
+        get_indexers(self.pool, id).await
+    }
+
+    pub async fn count_indexers(&self, id: Uuid) -> Result<usize, InfraError> {
+        count_indexers(self.pool, id).await
+    }
+}
+
+async fn get_all(pool: &Pool<AsyncPgConnection>, tenant_id: Option<&str>) -> Result<Vec<ProjectModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = projects::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(tenant_id) = tenant_id {
+        query = query.filter(projects::tenant_id.eq(tenant_id.to_string()));
+    }
+    let res: Vec<ProjectDb> = query
+        .order((projects::tenant_id.asc(), projects::name.asc()))
+        .select(ProjectDb::as_select())
+        .load::<ProjectDb>(&mut conn)
+        .await?;
+
+    res.into_iter()
+        .map(ProjectModel::try_from)
+        .collect::<Result<Vec<ProjectModel>, ParseError>>()
+        .map_err(InfraError::ParseError)
+}
+
+async fn get(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<Option<ProjectModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = projects::table
+        .filter(projects::id.eq(id))
+        .select(ProjectDb::as_select())
+        .first::<ProjectDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(ProjectModel::try_from).transpose().map_err(InfraError::ParseError)
+}
+
+async fn get_by_name(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: &str,
+    name: &str,
+) -> Result<Option<ProjectModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = projects::table
+        .filter(projects::tenant_id.eq(tenant_id))
+        .filter(projects::name.eq(name))
+        .select(ProjectDb::as_select())
+        .first::<ProjectDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(ProjectModel::try_from).transpose().map_err(InfraError::ParseError)
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, project: NewProjectDb) -> Result<ProjectModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(projects::table)
+        .values(project)
+        .returning(ProjectDb::as_returning())
+        .get_result(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn update(pool: &Pool<AsyncPgConnection>, project: NewProjectDb) -> Result<Option<ProjectModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(projects::table)
+        .filter(projects::id.eq(project.id))
+        .set((
+            projects::name.eq(project.name),
+            projects::description.eq(project.description),
+            projects::default_stream_url.eq(project.default_stream_url),
+            projects::default_log_level.eq(project.default_log_level),
+            projects::secret_path_prefix.eq(project.secret_path_prefix),
+            projects::role_bindings.eq(project.role_bindings),
+            projects::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(ProjectDb::as_returning())
+        .get_result::<ProjectDb>(&mut conn)
+        .await
+        .optional()?;
+
+    res.map(ProjectModel::try_from).transpose().map_err(InfraError::ParseError)
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(projects::table.filter(projects::id.eq(id))).execute(&mut conn).await?;
+
+    Ok(deleted > 0)
+}
+
+async fn get_indexers(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<Vec<IndexerModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<IndexerDb> = indexers::table
+        .filter(indexers::project_id.eq(id))
+        .order(indexers::id.asc())
+        .select(IndexerDb::as_select())
+        .load::<IndexerDb>(&mut conn)
+        .await?;
+
+    res.into_iter()
+        .map(IndexerModel::try_from)
+        .collect::<Result<Vec<IndexerModel>, ParseError>>()
+        .map_err(InfraError::ParseError)
+}
+
+async fn count_indexers(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<usize, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let count: i64 = indexers::table.filter(indexers::project_id.eq(id)).count().get_result(&mut conn).await?;
+
+    Ok(count as usize)
+}
+
+impl TryFrom<ProjectDb> for ProjectModel {
+    type Error = ParseError;
+    fn try_from(value: ProjectDb) -> Result<Self, Self::Error> {
+        let model = ProjectModel {
+            id: value.id,
+            tenant_id: value.tenant_id,
+            name: value.name,
+            description: value.description,
+            default_stream_url: value.default_stream_url,
+            default_log_level: value
+                .default_log_level
+                .map(|log_level| IndexerLogLevel::from_str(log_level.as_str()))
+                .transpose()?,
+            secret_path_prefix: value.secret_path_prefix,
+            role_bindings: serde_json::from_value(value.role_bindings).map_err(|_| ParseError::VariantNotFound)?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        };
+        Ok(model)
+    }
+}
@@ -0,0 +1,263 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+use uuid::Uuid;
+
+use crate::domain::models::scheduled_action::{ScheduledActionKind, ScheduledActionModel};
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::scheduled_actions;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = scheduled_actions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ScheduledActionDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub kind: String,
+    pub run_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub cron: Option<String>,
+    pub timezone: Option<String>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = scheduled_actions)]
+pub struct NewScheduledActionDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub kind: String,
+    pub run_at: DateTime<Utc>,
+    pub cron: Option<String>,
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ScheduledActionFilter {
+    pub indexer_id: Option<Uuid>,
+    /// Failed actions are only returned if this is set
+    pub include_failed: bool,
+}
+
+pub struct ScheduledActionRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ScheduledActionRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ScheduledActionRepository {
+        ScheduledActionRepository { pool }
+    }
+
+    pub async fn insert(&mut self, action: NewScheduledActionDb) -> Result<ScheduledActionModel, InfraError> {
+        insert(self.pool, action).await
+    }
+
+    pub async fn get_all(&self, filter: ScheduledActionFilter) -> Result<Vec<ScheduledActionModel>, InfraError> {
+        get_all(self.pool, filter).await
+    }
+
+    pub async fn claim_due(
+        &mut self,
+        now: DateTime<Utc>,
+        lease: Duration,
+        limit: i64,
+    ) -> Result<Vec<ScheduledActionModel>, InfraError> {
+        claim_due(self.pool, now, lease, limit).await
+    }
+
+    pub async fn reschedule(&mut self, id: Uuid, run_at: DateTime<Utc>, error: String) -> Result<(), InfraError> {
+        reschedule(self.pool, id, run_at, error).await
+    }
+
+    /// Moves a recurring action to its next run, with the error of the run which failed for good
+    /// if any
+    pub async fn advance(&mut self, id: Uuid, run_at: DateTime<Utc>, error: Option<String>) -> Result<(), InfraError> {
+        advance(self.pool, id, run_at, error).await
+    }
+
+    pub async fn mark_failed(&mut self, id: Uuid, error: String) -> Result<(), InfraError> {
+        mark_failed(self.pool, id, error).await
+    }
+
+    /// Completed and cancelled actions are deleted
+    pub async fn delete(&mut self, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, id).await
+    }
+}
+
+async fn insert(
+    pool: &Pool<AsyncPgConnection>,
+    action: NewScheduledActionDb,
+) -> Result<ScheduledActionModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(scheduled_actions::table)
+        .values(action)
+        .returning(ScheduledActionDb::as_returning())
+        .get_result(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+/// Returns the actions matching the filter ordered by the time they run at
+async fn get_all(
+    pool: &Pool<AsyncPgConnection>,
+    filter: ScheduledActionFilter,
+) -> Result<Vec<ScheduledActionModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let mut query = scheduled_actions::table.into_boxed::<diesel::pg::Pg>();
+    if let Some(indexer_id) = filter.indexer_id {
+        query = query.filter(scheduled_actions::indexer_id.eq(indexer_id));
+    }
+    if !filter.include_failed {
+        query = query.filter(scheduled_actions::failed_at.is_null());
+    }
+    let res: Vec<ScheduledActionDb> = query
+        .order(scheduled_actions::run_at.asc())
+        .select(ScheduledActionDb::as_select())
+        .load::<ScheduledActionDb>(&mut conn)
+        .await?;
+
+    res.into_iter()
+        .map(|action_db| action_db.try_into())
+        .collect::<Result<Vec<ScheduledActionModel>, ParseError>>()
+        .map_err(InfraError::ParseError)
+}
+
+/// Locks the actions due at `now` for `lease` and counts the attempt. Rows locked by another
+/// executor are skipped, so several instances can run actions without running one twice.
+async fn claim_due(
+    pool: &Pool<AsyncPgConnection>,
+    now: DateTime<Utc>,
+    lease: Duration,
+    limit: i64,
+) -> Result<Vec<ScheduledActionModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = conn
+        .transaction::<_, InfraError, _>(|conn| {
+            async move {
+                let ids: Vec<Uuid> = scheduled_actions::table
+                    .filter(scheduled_actions::run_at.le(now))
+                    .filter(scheduled_actions::failed_at.is_null())
+                    .filter(scheduled_actions::locked_until.is_null().or(scheduled_actions::locked_until.lt(now)))
+                    .order(scheduled_actions::run_at.asc())
+                    .limit(limit)
+                    .select(scheduled_actions::id)
+                    .for_update()
+                    .skip_locked()
+                    .load(conn)
+                    .await?;
+                let claimed: Vec<ScheduledActionDb> =
+                    diesel::update(scheduled_actions::table.filter(scheduled_actions::id.eq_any(ids)))
+                        .set((
+                            scheduled_actions::locked_until.eq(now + lease),
+                            scheduled_actions::attempts.eq(scheduled_actions::attempts + 1),
+                        ))
+                        .returning(ScheduledActionDb::as_returning())
+                        .get_results(conn)
+                        .await?;
+                Ok(claimed)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    res.into_iter()
+        .map(|action_db| action_db.try_into())
+        .collect::<Result<Vec<ScheduledActionModel>, ParseError>>()
+        .map_err(InfraError::ParseError)
+}
+
+async fn reschedule(
+    pool: &Pool<AsyncPgConnection>,
+    id: Uuid,
+    run_at: DateTime<Utc>,
+    error: String,
+) -> Result<(), InfraError> {
+    let mut conn = get_connection(pool).await?;
+    diesel::update(scheduled_actions::table.filter(scheduled_actions::id.eq(id)))
+        .set((
+            scheduled_actions::run_at.eq(run_at),
+            scheduled_actions::locked_until.eq(None::<DateTime<Utc>>),
+            scheduled_actions::last_error.eq(error),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn advance(
+    pool: &Pool<AsyncPgConnection>,
+    id: Uuid,
+    run_at: DateTime<Utc>,
+    error: Option<String>,
+) -> Result<(), InfraError> {
+    let mut conn = get_connection(pool).await?;
+    diesel::update(scheduled_actions::table.filter(scheduled_actions::id.eq(id)))
+        .set((
+            scheduled_actions::run_at.eq(run_at),
+            scheduled_actions::attempts.eq(0),
+            scheduled_actions::locked_until.eq(None::<DateTime<Utc>>),
+            scheduled_actions::last_error.eq(error),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn mark_failed(pool: &Pool<AsyncPgConnection>, id: Uuid, error: String) -> Result<(), InfraError> {
+    let mut conn = get_connection(pool).await?;
+    diesel::update(scheduled_actions::table.filter(scheduled_actions::id.eq(id)))
+        .set((
+            scheduled_actions::failed_at.eq(Utc::now()),
+            scheduled_actions::locked_until.eq(None::<DateTime<Utc>>),
+            scheduled_actions::last_error.eq(error),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns whether the action existed
+async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted =
+        diesel::delete(scheduled_actions::table.filter(scheduled_actions::id.eq(id))).execute(&mut conn).await?;
+
+    Ok(deleted > 0)
+}
+
+impl TryFrom<ScheduledActionDb> for ScheduledActionModel {
+    type Error = ParseError;
+    fn try_from(value: ScheduledActionDb) -> Result<Self, Self::Error> {
+        let model = ScheduledActionModel {
+            id: value.id,
+            indexer_id: value.indexer_id,
+            kind: ScheduledActionKind::from_str(value.kind.as_str())?,
+            run_at: value.run_at,
+            attempts: value.attempts,
+            locked_until: value.locked_until,
+            last_error: value.last_error,
+            failed_at: value.failed_at,
+            created_at: value.created_at,
+            cron: value.cron,
+            timezone: value.timezone,
+        };
+        Ok(model)
+    }
+}
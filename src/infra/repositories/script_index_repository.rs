@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use diesel::sql_types::{BigInt, Text};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, QueryDsl, QueryableByName, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::models::indexer::IndexerModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::{indexers, script_index};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::indexer_repository::IndexerDb;
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = script_index)]
+pub struct NewScriptIndexDb {
+    pub indexer_id: Uuid,
+    pub script_checksum: Option<String>,
+    pub content: String,
+}
+
+#[derive(QueryableByName)]
+#[diesel(table_name = script_index)]
+struct ScriptMatchDb {
+    indexer_id: Uuid,
+    content: String,
+}
+
+pub struct ScriptIndexRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ScriptIndexRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ScriptIndexRepository {
+        ScriptIndexRepository { pool }
+    }
+
+    /// Checksum of the script indexed for each indexer
+    pub async fn get_checksums(&self) -> Result<HashMap<Uuid, Option<String>>, InfraError> {
+        get_checksums(self.pool).await
+    }
+
+    pub async fn upsert(&mut self, script: NewScriptIndexDb) -> Result<(), InfraError> {
+        upsert(self.pool, script).await
+    }
+
+    /// Returns the indexers whose script matches the query along with their script
+    pub async fn search(&self, query: &str, limit: i64) -> Result<Vec<(IndexerModel, String)>, InfraError> {
+        search(self.pool, query, limit).await
+    }
+}
+
+async fn get_checksums(pool: &Pool<AsyncPgConnection>) -> Result<HashMap<Uuid, Option<String>>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<(Uuid, Option<String>)> =
+        script_index::table.select((script_index::indexer_id, script_index::script_checksum)).load(&mut conn).await?;
+
+    Ok(res.into_iter().collect())
+}
+
+async fn upsert(pool: &Pool<AsyncPgConnection>, script: NewScriptIndexDb) -> Result<(), InfraError> {
+    let mut conn = get_connection(pool).await?;
+    diesel::insert_into(script_index::table)
+        .values(script)
+        .on_conflict(script_index::indexer_id)
+        .do_update()
+        .set((
+            script_index::script_checksum.eq(excluded(script_index::script_checksum)),
+            script_index::content.eq(excluded(script_index::content)),
+            script_index::indexed_at.eq(diesel::dsl::now),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Words are matched through the full text index, other queries (e.g. part of an address) by
+/// substring through the trigram index
+async fn search(
+    pool: &Pool<AsyncPgConnection>,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<(IndexerModel, String)>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let matches: Vec<ScriptMatchDb> = diesel::sql_query(
+        "SELECT indexer_id, content FROM script_index WHERE content_tsv @@ plainto_tsquery('simple', $1) OR \
+         lower(content) LIKE $2 ESCAPE '\\' ORDER BY indexed_at DESC LIMIT $3",
+    )
+    .bind::<Text, _>(query)
+    .bind::<Text, _>(get_like_pattern(query))
+    .bind::<BigInt, _>(limit)
+    .load(&mut conn)
+    .await?;
+    let mut contents: HashMap<Uuid, String> =
+        matches.into_iter().map(|script_match| (script_match.indexer_id, script_match.content)).collect();
+
+    let res: Vec<IndexerDb> = indexers::table
+        .filter(indexers::id.eq_any(contents.keys().copied().collect::<Vec<Uuid>>()))
+        .select(IndexerDb::as_select())
+        .load::<IndexerDb>(&mut conn)
+        .await?;
+
+    // unreadable indexers are left out as they are in the listings
+    let indexers = res
+        .into_iter()
+        .filter_map(|indexer_db| IndexerModel::try_from(indexer_db).ok())
+        .filter_map(|indexer| contents.remove(&indexer.id).map(|content| (indexer, content)))
+        .collect();
+
+    Ok(indexers)
+}
+
+/// Pattern of the lowercase content containing the query, the wildcards of the query are escaped
+fn get_like_pattern(query: &str) -> String {
+    let escaped = query.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_like_pattern() {
+        assert_eq!(get_like_pattern("0x049D"), "%0x049d%");
+        assert_eq!(get_like_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+
+use crate::domain::models::script_scan::ScriptScanModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::script_scans;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = script_scans)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ScriptScanDb {
+    pub script_checksum: String,
+    pub dependencies: serde_json::Value,
+    pub findings: serde_json::Value,
+    pub scanned_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = script_scans)]
+pub struct NewScriptScanDb {
+    pub script_checksum: String,
+    pub dependencies: serde_json::Value,
+    pub findings: serde_json::Value,
+}
+
+pub struct ScriptScanRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl ScriptScanRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> ScriptScanRepository {
+        ScriptScanRepository { pool }
+    }
+
+    pub async fn get(&self, script_checksum: &str) -> Result<Option<ScriptScanModel>, InfraError> {
+        get(self.pool, script_checksum).await
+    }
+
+    /// Replaces the findings of an earlier scan of the script
+    pub async fn upsert(&mut self, scan: NewScriptScanDb) -> Result<ScriptScanModel, InfraError> {
+        upsert(self.pool, scan).await
+    }
+}
+
+async fn get(pool: &Pool<AsyncPgConnection>, script_checksum: &str) -> Result<Option<ScriptScanModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res = script_scans::table
+        .filter(script_scans::script_checksum.eq(script_checksum))
+        .select(ScriptScanDb::as_select())
+        .first::<ScriptScanDb>(&mut conn)
+        .await
+        .optional()?
+        .map(ScriptScanModel::try_from)
+        .transpose()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn upsert(pool: &Pool<AsyncPgConnection>, scan: NewScriptScanDb) -> Result<ScriptScanModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(script_scans::table)
+        .values(scan)
+        .on_conflict(script_scans::script_checksum)
+        .do_update()
+        .set((
+            script_scans::dependencies.eq(excluded(script_scans::dependencies)),
+            script_scans::findings.eq(excluded(script_scans::findings)),
+            script_scans::scanned_at.eq(diesel::dsl::now),
+        ))
+        .returning(ScriptScanDb::as_returning())
+        .get_result::<ScriptScanDb>(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+impl TryFrom<ScriptScanDb> for ScriptScanModel {
+    type Error = ParseError;
+    fn try_from(value: ScriptScanDb) -> Result<Self, Self::Error> {
+        Ok(ScriptScanModel {
+            script_checksum: value.script_checksum,
+            dependencies: serde_json::from_value(value.dependencies).map_err(|_| ParseError::VariantNotFound)?,
+            findings: serde_json::from_value(value.findings).map_err(|_| ParseError::VariantNotFound)?,
+            scanned_at: value.scanned_at,
+        })
+    }
+}
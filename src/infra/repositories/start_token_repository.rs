@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::start_token::StartTokenModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::start_tokens;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = start_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StartTokenDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub token_hash: String,
+    pub created_by: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The token is expected to be hashed, see `hash_start_token`
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = start_tokens)]
+pub struct NewStartTokenDb {
+    pub id: Uuid,
+    pub indexer_id: Uuid,
+    pub token_hash: String,
+    pub created_by: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct StartTokenRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl StartTokenRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> StartTokenRepository {
+        StartTokenRepository { pool }
+    }
+
+    pub async fn insert(&mut self, start_token: NewStartTokenDb) -> Result<StartTokenModel, InfraError> {
+        insert(self.pool, start_token).await
+    }
+
+    /// Tokens of the indexer from the most recent to the oldest
+    pub async fn get_all_by_indexer(&self, indexer_id: Uuid) -> Result<Vec<StartTokenModel>, InfraError> {
+        get_all_by_indexer(self.pool, indexer_id).await
+    }
+
+    /// Marks the token redeemed, returns `None` if the indexer has no such token or if it expired
+    /// or was already redeemed. Concurrent redemptions of a token can't both succeed.
+    pub async fn redeem(&mut self, indexer_id: Uuid, token_hash: &str) -> Result<Option<StartTokenModel>, InfraError> {
+        redeem(self.pool, indexer_id, token_hash).await
+    }
+
+    /// Returns whether the indexer had the token
+    pub async fn delete(&mut self, indexer_id: Uuid, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, indexer_id, id).await
+    }
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, start_token: NewStartTokenDb) -> Result<StartTokenModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(start_tokens::table)
+        .values(start_token)
+        .returning(StartTokenDb::as_returning())
+        .get_result::<StartTokenDb>(&mut conn)
+        .await?
+        .into();
+
+    Ok(res)
+}
+
+async fn get_all_by_indexer(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+) -> Result<Vec<StartTokenModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<StartTokenDb> = start_tokens::table
+        .filter(start_tokens::indexer_id.eq(indexer_id))
+        .order((start_tokens::created_at.desc(), start_tokens::id.desc()))
+        .select(StartTokenDb::as_select())
+        .load::<StartTokenDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(StartTokenModel::from).collect())
+}
+
+async fn redeem(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_id: Uuid,
+    token_hash: &str,
+) -> Result<Option<StartTokenModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::update(
+        start_tokens::table
+            .filter(start_tokens::indexer_id.eq(indexer_id))
+            .filter(start_tokens::token_hash.eq(token_hash))
+            .filter(start_tokens::redeemed_at.is_null())
+            .filter(start_tokens::expires_at.gt(diesel::dsl::now)),
+    )
+    .set(start_tokens::redeemed_at.eq(diesel::dsl::now))
+    .returning(StartTokenDb::as_returning())
+    .get_result::<StartTokenDb>(&mut conn)
+    .await
+    .optional()?;
+
+    Ok(res.map(StartTokenModel::from))
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, indexer_id: Uuid, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(
+        start_tokens::table.filter(start_tokens::indexer_id.eq(indexer_id)).filter(start_tokens::id.eq(id)),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    Ok(deleted > 0)
+}
+
+impl From<StartTokenDb> for StartTokenModel {
+    fn from(value: StartTokenDb) -> Self {
+        StartTokenModel {
+            id: value.id,
+            indexer_id: value.indexer_id,
+            created_by: value.created_by,
+            expires_at: value.expires_at,
+            redeemed_at: value.redeemed_at,
+            created_at: value.created_at,
+        }
+    }
+}
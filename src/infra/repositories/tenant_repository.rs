@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use strum::ParseError;
+
+use crate::domain::models::indexer::IndexerLogLevel;
+use crate::domain::models::secret::SecretBackendKind;
+use crate::domain::models::stale_created::StaleCreatedAction;
+use crate::domain::models::tenant::TenantSettingsModel;
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::tenant_settings;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = tenant_settings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TenantSettingsDb {
+    pub tenant_id: String,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<String>,
+    pub allowed_indexer_types: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+    pub max_starts_per_minute: Option<i32>,
+    pub stale_created_after_seconds: Option<i32>,
+    pub stale_created_action: Option<String>,
+    pub require_target_approval: bool,
+    pub secret_backend: Option<String>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = tenant_settings)]
+pub struct NewTenantSettingsDb {
+    pub tenant_id: String,
+    pub default_stream_url: Option<String>,
+    pub default_log_level: Option<String>,
+    pub allowed_indexer_types: serde_json::Value,
+    pub max_starts_per_minute: Option<i32>,
+    pub stale_created_after_seconds: Option<i32>,
+    pub stale_created_action: Option<String>,
+    pub require_target_approval: bool,
+    pub secret_backend: Option<String>,
+}
+
+pub struct TenantRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl TenantRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> TenantRepository {
+        TenantRepository { pool }
+    }
+
+    pub async fn get_settings(&self, tenant_id: &str) -> Result<Option<TenantSettingsModel>, InfraError> {
+        get_settings(self.pool, tenant_id).await
+    }
+
+    pub async fn upsert_settings(&mut self, settings: NewTenantSettingsDb) -> Result<TenantSettingsModel, InfraError> {
+        upsert_settings(self.pool, settings).await
+    }
+
+    pub async fn delete_settings(&mut self, tenant_id: &str) -> Result<bool, InfraError> {
+        delete_settings(self.pool, tenant_id).await
+    }
+}
+
+async fn get_settings(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: &str,
+) -> Result<Option<TenantSettingsModel>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = tenant_settings::table
+        .filter(tenant_settings::tenant_id.eq(tenant_id))
+        .select(TenantSettingsDb::as_select())
+        .first::<TenantSettingsDb>(&mut conn)
+        .await
+        .optional()?
+        .map(|settings_db| settings_db.try_into())
+        .transpose()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+async fn upsert_settings(
+    pool: &Pool<AsyncPgConnection>,
+    settings: NewTenantSettingsDb,
+) -> Result<TenantSettingsModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(tenant_settings::table)
+        .values(settings)
+        .on_conflict(tenant_settings::tenant_id)
+        .do_update()
+        .set((
+            tenant_settings::default_stream_url.eq(excluded(tenant_settings::default_stream_url)),
+            tenant_settings::default_log_level.eq(excluded(tenant_settings::default_log_level)),
+            tenant_settings::allowed_indexer_types.eq(excluded(tenant_settings::allowed_indexer_types)),
+            tenant_settings::max_starts_per_minute.eq(excluded(tenant_settings::max_starts_per_minute)),
+            tenant_settings::stale_created_after_seconds.eq(excluded(tenant_settings::stale_created_after_seconds)),
+            tenant_settings::stale_created_action.eq(excluded(tenant_settings::stale_created_action)),
+            tenant_settings::require_target_approval.eq(excluded(tenant_settings::require_target_approval)),
+            tenant_settings::secret_backend.eq(excluded(tenant_settings::secret_backend)),
+            tenant_settings::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(TenantSettingsDb::as_returning())
+        .get_result(&mut conn)
+        .await?
+        .try_into()
+        .map_err(InfraError::ParseError)?;
+
+    Ok(res)
+}
+
+/// Returns whether the tenant had settings
+async fn delete_settings(pool: &Pool<AsyncPgConnection>, tenant_id: &str) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(tenant_settings::table.filter(tenant_settings::tenant_id.eq(tenant_id)))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(deleted > 0)
+}
+
+impl TryFrom<TenantSettingsDb> for TenantSettingsModel {
+    type Error = ParseError;
+    fn try_from(value: TenantSettingsDb) -> Result<Self, Self::Error> {
+        let model = TenantSettingsModel {
+            tenant_id: value.tenant_id,
+            default_stream_url: value.default_stream_url,
+            default_log_level: value
+                .default_log_level
+                .map(|log_level| IndexerLogLevel::from_str(log_level.as_str()))
+                .transpose()?,
+            allowed_indexer_types: serde_json::from_value(value.allowed_indexer_types)
+                .map_err(|_| ParseError::VariantNotFound)?,
+            max_starts_per_minute: value.max_starts_per_minute.map(|max| max as u32),
+            stale_created_after_seconds: value.stale_created_after_seconds.map(|seconds| seconds as u32),
+            stale_created_action: value
+                .stale_created_action
+                .map(|action| StaleCreatedAction::from_str(action.as_str()))
+                .transpose()?,
+            require_target_approval: value.require_target_approval,
+            secret_backend: value
+                .secret_backend
+                .map(|backend| SecretBackendKind::from_str(backend.as_str()))
+                .transpose()?,
+            updated_at: value.updated_at,
+        };
+        Ok(model)
+    }
+}
@@ -0,0 +1,224 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::upload::{UploadPart, UploadSession, UploadStatus};
+use crate::infra::db::pool::get_connection;
+use crate::infra::db::schema::{upload_parts, upload_sessions};
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = upload_sessions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UploadSessionDb {
+    pub id: Uuid,
+    pub tenant_id: Option<String>,
+    pub status: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = upload_sessions)]
+pub struct NewUploadSessionDb {
+    pub id: Uuid,
+    pub tenant_id: Option<String>,
+    pub status: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = upload_parts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UploadPartDb {
+    pub upload_id: Uuid,
+    pub part_number: i32,
+    pub size: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = upload_parts)]
+pub struct NewUploadPartDb {
+    pub upload_id: Uuid,
+    pub part_number: i32,
+    pub size: i64,
+    pub sha256: String,
+}
+
+pub struct UploadRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl UploadRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> UploadRepository {
+        UploadRepository { pool }
+    }
+
+    pub async fn insert(&mut self, session: NewUploadSessionDb) -> Result<UploadSession, InfraError> {
+        insert(self.pool, session).await
+    }
+
+    /// Returns the session with its parts, `None` once it expired
+    pub async fn get(&self, id: Uuid) -> Result<Option<UploadSession>, InfraError> {
+        get(self.pool, id).await
+    }
+
+    /// Registers the part, a part uploaded again replaces the previous one
+    pub async fn upsert_part(&mut self, part: NewUploadPartDb) -> Result<UploadPart, InfraError> {
+        upsert_part(self.pool, part).await
+    }
+
+    /// Marks the session completed and drops the parts after `total_parts`. Returns whether the
+    /// session was still in progress, concurrent completions can't both succeed.
+    pub async fn complete(&mut self, id: Uuid, total_parts: u32) -> Result<bool, InfraError> {
+        complete(self.pool, id, total_parts).await
+    }
+
+    /// Sessions which expired, with their parts
+    pub async fn get_expired(&self) -> Result<Vec<UploadSession>, InfraError> {
+        get_expired(self.pool).await
+    }
+
+    /// Deletes the session along with its parts, returns whether it existed
+    pub async fn delete(&mut self, id: Uuid) -> Result<bool, InfraError> {
+        delete(self.pool, id).await
+    }
+}
+
+async fn insert(pool: &Pool<AsyncPgConnection>, session: NewUploadSessionDb) -> Result<UploadSession, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(upload_sessions::table)
+        .values(session)
+        .returning(UploadSessionDb::as_returning())
+        .get_result::<UploadSessionDb>(&mut conn)
+        .await?;
+
+    get_upload_session(res, vec![])
+}
+
+async fn get(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<Option<UploadSession>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let Some(session) = upload_sessions::table
+        .filter(upload_sessions::id.eq(id))
+        .filter(upload_sessions::expires_at.gt(diesel::dsl::now))
+        .select(UploadSessionDb::as_select())
+        .first::<UploadSessionDb>(&mut conn)
+        .await
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let parts = upload_parts::table
+        .filter(upload_parts::upload_id.eq(id))
+        .select(UploadPartDb::as_select())
+        .load::<UploadPartDb>(&mut conn)
+        .await?;
+
+    get_upload_session(session, parts).map(Some)
+}
+
+async fn upsert_part(pool: &Pool<AsyncPgConnection>, part: NewUploadPartDb) -> Result<UploadPart, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(upload_parts::table)
+        .values(part)
+        .on_conflict((upload_parts::upload_id, upload_parts::part_number))
+        .do_update()
+        .set((
+            upload_parts::size.eq(excluded(upload_parts::size)),
+            upload_parts::sha256.eq(excluded(upload_parts::sha256)),
+        ))
+        .returning(UploadPartDb::as_returning())
+        .get_result::<UploadPartDb>(&mut conn)
+        .await?;
+
+    Ok(res.into())
+}
+
+async fn complete(pool: &Pool<AsyncPgConnection>, id: Uuid, total_parts: u32) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let completed = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let updated = diesel::update(
+                    upload_sessions::table
+                        .filter(upload_sessions::id.eq(id))
+                        .filter(upload_sessions::status.eq(UploadStatus::InProgress.to_string())),
+                )
+                .set(upload_sessions::status.eq(UploadStatus::Completed.to_string()))
+                .execute(conn)
+                .await?;
+                if updated == 0 {
+                    return Ok(false);
+                }
+                diesel::delete(
+                    upload_parts::table
+                        .filter(upload_parts::upload_id.eq(id))
+                        .filter(upload_parts::part_number.gt(total_parts as i32)),
+                )
+                .execute(conn)
+                .await?;
+                Ok(true)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(completed)
+}
+
+async fn get_expired(pool: &Pool<AsyncPgConnection>) -> Result<Vec<UploadSession>, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let sessions: Vec<UploadSessionDb> = upload_sessions::table
+        .filter(upload_sessions::expires_at.le(diesel::dsl::now))
+        .select(UploadSessionDb::as_select())
+        .load::<UploadSessionDb>(&mut conn)
+        .await?;
+    let ids: Vec<Uuid> = sessions.iter().map(|session| session.id).collect();
+    let mut parts: Vec<UploadPartDb> = upload_parts::table
+        .filter(upload_parts::upload_id.eq_any(ids))
+        .select(UploadPartDb::as_select())
+        .load::<UploadPartDb>(&mut conn)
+        .await?;
+
+    sessions
+        .into_iter()
+        .map(|session| {
+            let (session_parts, rest) = parts.drain(..).partition(|part| part.upload_id == session.id);
+            parts = rest;
+            get_upload_session(session, session_parts)
+        })
+        .collect()
+}
+
+async fn delete(pool: &Pool<AsyncPgConnection>, id: Uuid) -> Result<bool, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let deleted = diesel::delete(upload_sessions::table.filter(upload_sessions::id.eq(id))).execute(&mut conn).await?;
+
+    Ok(deleted > 0)
+}
+
+fn get_upload_session(session: UploadSessionDb, parts: Vec<UploadPartDb>) -> Result<UploadSession, InfraError> {
+    Ok(UploadSession {
+        id: session.id,
+        tenant_id: session.tenant_id,
+        status: UploadStatus::from_str(&session.status).map_err(InfraError::ParseError)?,
+        parts: parts.into_iter().map(|part| (part.part_number as u32, UploadPart::from(part))).collect(),
+        expires_at: session.expires_at,
+        created_at: session.created_at,
+    })
+}
+
+impl From<UploadPartDb> for UploadPart {
+    fn from(value: UploadPartDb) -> Self {
+        UploadPart { part_number: value.part_number as u32, size: value.size as usize, sha256: value.sha256 }
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable, Selectable, SelectableHelper};
+use diesel_async::pooled_connection::deadpool::Pool;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::usage::UsageRecordModel;
+use crate::infra::db::pool::{get_connection, get_read_connection};
+use crate::infra::db::schema::tenant_usage;
+use crate::infra::errors::InfraError;
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = tenant_usage)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UsageRecordDb {
+    pub tenant_id: String,
+    pub indexer_id: Uuid,
+    pub day: NaiveDate,
+    pub running_seconds: i64,
+    pub blocks_processed: i64,
+    pub script_bytes: i64,
+    pub log_bytes: i64,
+    pub updated_at: DateTime<Utc>,
+    pub streamed_bytes: i64,
+    pub delivered_bytes: i64,
+}
+
+/// Usage metered since the last sample, added to the record of the day. The script size is a
+/// gauge and replaces the recorded one.
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = tenant_usage)]
+pub struct NewUsageRecordDb {
+    pub tenant_id: String,
+    pub indexer_id: Uuid,
+    pub day: NaiveDate,
+    pub running_seconds: i64,
+    pub blocks_processed: i64,
+    pub script_bytes: i64,
+    pub log_bytes: i64,
+    pub streamed_bytes: i64,
+    pub delivered_bytes: i64,
+}
+
+pub struct UsageRepository<'a> {
+    pool: &'a Pool<AsyncPgConnection>,
+}
+
+impl UsageRepository<'_> {
+    pub fn new(pool: &Pool<AsyncPgConnection>) -> UsageRepository {
+        UsageRepository { pool }
+    }
+
+    pub async fn record(&mut self, usage: NewUsageRecordDb) -> Result<UsageRecordModel, InfraError> {
+        record(self.pool, usage).await
+    }
+
+    /// Records of the tenant from `from` included to `to` excluded
+    pub async fn get_usage(
+        &self,
+        tenant_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UsageRecordModel>, InfraError> {
+        get_usage(self.pool, tenant_id, from, to).await
+    }
+
+    /// Records of the indexers since `from` included, whatever their tenant
+    pub async fn get_indexers_usage(
+        &self,
+        indexer_ids: &[Uuid],
+        from: NaiveDate,
+    ) -> Result<Vec<UsageRecordModel>, InfraError> {
+        get_indexers_usage(self.pool, indexer_ids, from).await
+    }
+}
+
+async fn record(pool: &Pool<AsyncPgConnection>, usage: NewUsageRecordDb) -> Result<UsageRecordModel, InfraError> {
+    let mut conn = get_connection(pool).await?;
+    let res = diesel::insert_into(tenant_usage::table)
+        .values(usage)
+        .on_conflict((tenant_usage::tenant_id, tenant_usage::day, tenant_usage::indexer_id))
+        .do_update()
+        .set((
+            tenant_usage::running_seconds.eq(tenant_usage::running_seconds + excluded(tenant_usage::running_seconds)),
+            tenant_usage::blocks_processed
+                .eq(tenant_usage::blocks_processed + excluded(tenant_usage::blocks_processed)),
+            tenant_usage::script_bytes.eq(excluded(tenant_usage::script_bytes)),
+            tenant_usage::log_bytes.eq(tenant_usage::log_bytes + excluded(tenant_usage::log_bytes)),
+            tenant_usage::streamed_bytes.eq(tenant_usage::streamed_bytes + excluded(tenant_usage::streamed_bytes)),
+            tenant_usage::delivered_bytes.eq(tenant_usage::delivered_bytes + excluded(tenant_usage::delivered_bytes)),
+            tenant_usage::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(UsageRecordDb::as_returning())
+        .get_result::<UsageRecordDb>(&mut conn)
+        .await?
+        .into();
+
+    Ok(res)
+}
+
+async fn get_usage(
+    pool: &Pool<AsyncPgConnection>,
+    tenant_id: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<UsageRecordModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<UsageRecordDb> = tenant_usage::table
+        .filter(tenant_usage::tenant_id.eq(tenant_id))
+        .filter(tenant_usage::day.ge(from))
+        .filter(tenant_usage::day.lt(to))
+        .order((tenant_usage::day.asc(), tenant_usage::indexer_id.asc()))
+        .select(UsageRecordDb::as_select())
+        .load::<UsageRecordDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(UsageRecordModel::from).collect())
+}
+
+async fn get_indexers_usage(
+    pool: &Pool<AsyncPgConnection>,
+    indexer_ids: &[Uuid],
+    from: NaiveDate,
+) -> Result<Vec<UsageRecordModel>, InfraError> {
+    let mut conn = get_read_connection(pool).await?;
+    let res: Vec<UsageRecordDb> = tenant_usage::table
+        .filter(tenant_usage::indexer_id.eq_any(indexer_ids))
+        .filter(tenant_usage::day.ge(from))
+        .order((tenant_usage::day.asc(), tenant_usage::indexer_id.asc()))
+        .select(UsageRecordDb::as_select())
+        .load::<UsageRecordDb>(&mut conn)
+        .await?;
+
+    Ok(res.into_iter().map(UsageRecordModel::from).collect())
+}
+
+impl From<UsageRecordDb> for UsageRecordModel {
+    fn from(value: UsageRecordDb) -> Self {
+        UsageRecordModel {
+            tenant_id: value.tenant_id,
+            indexer_id: value.indexer_id,
+            day: value.day,
+            running_seconds: value.running_seconds,
+            blocks_processed: value.blocks_processed,
+            script_bytes: value.script_bytes,
+            log_bytes: value.log_bytes,
+            streamed_bytes: value.streamed_bytes,
+            delivered_bytes: value.delivered_bytes,
+        }
+    }
+}
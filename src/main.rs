@@ -3,17 +3,35 @@ extern crate core;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use diesel::ConnectionError;
-use diesel_async::async_connection_wrapper::AsyncConnectionWrapper;
+use axum::Router;
+use diesel::{Connection, ConnectionError, PgConnection};
 use diesel_async::pooled_connection::deadpool::Pool;
 use diesel_async::AsyncPgConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use errors::AppError;
 
-use crate::config::{config, establish_connection};
+use crate::config::{config, TlsConfig};
 use crate::errors::internal_error;
+use crate::handlers::admin::runtime::monitor_runtime;
+use crate::handlers::global::health::monitor_self_health;
+use crate::handlers::global::version::get_version_model;
+use crate::handlers::indexers::config_drift::monitor_config_drift;
+use crate::handlers::indexers::gitops::monitor_gitops;
+use crate::handlers::indexers::memory_pressure::monitor_memory_pressure;
+use crate::handlers::indexers::reaper::reap_orphans;
+use crate::handlers::indexers::scheduled_actions::monitor_scheduled_actions;
+use crate::handlers::indexers::script_search::monitor_script_index;
+use crate::handlers::indexers::stale_created::monitor_stale_created;
 use crate::handlers::indexers::start_indexer::start_all_indexers;
-use crate::routes::app_router;
+use crate::handlers::indexers::utils::monitor_script_cache;
+use crate::handlers::notifications::alerts::monitor_alert_rules;
+use crate::handlers::tenants::usage::monitor_usage;
+use crate::handlers::uploads::sessions::monitor_upload_sessions;
+use crate::infra::data_migrations::run_data_migrations;
+use crate::routes::{app_router, internal_router};
+use crate::utils::http::{init_http_client, HttpClient};
+use crate::utils::supervisor::supervise;
+use crate::utils::tls::{load_server_config, serve_tls};
 
 /// gRPC clients
 mod grpc;
@@ -43,6 +61,8 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
 #[derive(Clone)]
 pub struct AppState {
     pool: Arc<Pool<AsyncPgConnection>>,
+    /// Client of the outbound calls, see `OutboundHttpConfig`
+    http: HttpClient,
 }
 
 #[tokio::main]
@@ -52,40 +72,93 @@ async fn main() -> Result<(), AppError> {
     let config = config().await;
 
     run_migrations(config.db_url().to_string()).await.map_err(AppError::DbError)?;
+    let report = run_data_migrations(config.pool(), false).await.map_err(AppError::from)?;
+    for value in &report.migrated {
+        tracing::info!("Migrated {} {} from {} to {}", value.rows, value.column, value.from, value.to);
+    }
+    for row in &report.unmappable {
+        tracing::warn!("Indexer {} has an unknown {} {}, it is skipped", row.indexer_id, row.column, row.value);
+    }
 
-    let state = AppState { pool: Arc::clone(config.pool()) };
-
-    let app = app_router(state.clone()).with_state(state);
+    tracing::info!("{}", get_version_model().await.banner());
 
-    let host = config.server_host();
-    let port = config.server_port();
+    let state = AppState { pool: Arc::clone(config.pool()), http: init_http_client(config.outbound_http()) };
 
-    let address = format!("{}:{}", host, port);
+    let app = app_router(state.clone()).with_state(state.clone());
+    let internal_app = internal_router(state.clone()).with_state(state);
 
-    let socket_addr: SocketAddr = address.parse().expect("Failed to parse socket address");
+    let socket_addr = get_socket_addr(config.server_host(), config.server_port());
+    let internal_socket_addr = get_socket_addr(config.internal_server_host(), config.internal_server_port());
 
-    tracing::info!("listening on http://{}", socket_addr);
+    let scheme = if config.tls().is_some() { "https" } else { "http" };
+    tracing::info!("listening on {}://{}", scheme, socket_addr);
+    let scheme = if config.internal_tls().is_some() { "https" } else { "http" };
+    tracing::info!("listening for internal callbacks on {}://{}", scheme, internal_socket_addr);
 
     if !config.is_dev() {
         // start all indexers that were running before the service was stopped
         start_all_indexers().await.map_err(AppError::Indexer)?;
     }
 
-    axum::Server::bind(&socket_addr).serve(app.into_make_service()).await.map_err(internal_error)?;
+    supervise("runtime-monitor", false, monitor_runtime);
+    // drifted indexers are only restarted by this task
+    supervise("config-drift-monitor", true, monitor_config_drift);
+    supervise("script-cache-cleanup", false, monitor_script_cache);
+    supervise("upload-session-cleanup", false, monitor_upload_sessions);
+    supervise("process-reaper", false, reap_orphans);
+    supervise("self-health-monitor", false, monitor_self_health);
+    // scheduled actions only run through this task
+    supervise("scheduled-actions", true, monitor_scheduled_actions);
+    supervise("usage-metering", false, monitor_usage);
+    supervise("alert-rules", false, monitor_alert_rules);
+    if !config.is_dev() {
+        // replays starts, like the boot does
+        supervise("stale-created-sweep", false, monitor_stale_created);
+    }
+    if config.memory_pressure().is_some() {
+        supervise("memory-pressure-guard", false, monitor_memory_pressure);
+    }
+    if config.gitops().is_some() {
+        supervise("gitops-sync", false, monitor_gitops);
+    }
+    if config.script_search_enabled() {
+        supervise("script-indexer", false, monitor_script_index);
+    }
+
+    tokio::try_join!(
+        serve(socket_addr, app, config.tls()),
+        serve(internal_socket_addr, internal_app, config.internal_tls())
+    )?;
 
     Ok(())
 }
 
+fn get_socket_addr(host: &str, port: u16) -> SocketAddr {
+    format!("{}:{}", host, port).parse().expect("Failed to parse socket address")
+}
+
+async fn serve(socket_addr: SocketAddr, app: Router, tls: Option<&TlsConfig>) -> Result<(), AppError> {
+    match tls {
+        Some(tls) => {
+            let server_config =
+                load_server_config(tls).unwrap_or_else(|e| panic!("Failed to load the TLS config: {}", e));
+            serve_tls(socket_addr, app, server_config).await.map_err(internal_error)
+        }
+        None => axum::Server::bind(&socket_addr).serve(app.into_make_service()).await.map_err(internal_error),
+    }
+}
+
 fn init_tracing() {
     tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).with_target(false).init();
 }
 
+/// Migrations run on a blocking connection, libpq negotiates TLS on its own
 async fn run_migrations(db_url: String) -> Result<(), ConnectionError> {
-    let async_connection = establish_connection(db_url.as_str()).await?;
-    let mut async_wrapper: AsyncConnectionWrapper<AsyncPgConnection> = AsyncConnectionWrapper::from(async_connection);
-    let _ = tokio::task::spawn_blocking(move || {
-        async_wrapper.run_pending_migrations(MIGRATIONS).unwrap();
+    tokio::task::spawn_blocking(move || {
+        let mut connection = PgConnection::establish(&db_url)?;
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        Ok(())
     })
-    .await;
-    Ok(())
+    .await
+    .map_err(|e| ConnectionError::BadConnection(e.to_string()))?
 }
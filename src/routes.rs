@@ -1,44 +1,274 @@
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::{delete, get, post};
-use axum::Router;
+use axum::routing::{delete, get, post, put};
+use axum::{middleware, Router};
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::handlers::global::health::health_check;
-use crate::handlers::indexers::create_indexer::create_indexer;
+use crate::handlers::admin::approvals::{approve_indexer, get_pending_approvals};
+use crate::handlers::admin::audit_logs::get_audit_logs;
+use crate::handlers::admin::data_migrations::migrate_data;
+use crate::handlers::admin::force_status::force_status;
+use crate::handlers::admin::gitops::{get_gitops_status, receive_gitops_webhook, trigger_gitops_sync};
+use crate::handlers::admin::maintenance_windows::{
+    create_maintenance_window, delete_maintenance_window, get_maintenance_windows,
+};
+use crate::handlers::admin::process_priority::update_process_priority;
+use crate::handlers::admin::quarantine::get_quarantined;
+use crate::handlers::admin::reconfigure::reconfigure;
+use crate::handlers::admin::refresh_status::refresh_status;
+use crate::handlers::admin::runtime::{get_database_pool_metrics, get_database_pools_metrics, get_runtime_metrics};
+use crate::handlers::admin::scheduled_actions::{
+    create_scheduled_action, delete_scheduled_action, get_scheduled_actions,
+};
+use crate::handlers::admin::script_sync::check_script_sync;
+use crate::handlers::contracts::indexers::get_contract_indexers;
+use crate::handlers::events::stream::stream_events;
+use crate::handlers::global::capabilities::get_capabilities;
+use crate::handlers::global::health::{health_check, readiness_check};
+use crate::handlers::global::schemas::{get_event_schema, get_event_schema_versions};
+use crate::handlers::global::search::search_block;
+use crate::handlers::global::version::get_version;
+use crate::handlers::indexers::annotations::{
+    create_annotation, delete_annotation, get_annotations, get_indexer_history,
+};
+use crate::handlers::indexers::clone_indexer::clone_indexer;
+use crate::handlers::indexers::console::get_console_output;
+use crate::handlers::indexers::create_indexer::{complete_create_indexer, create_indexer};
 use crate::handlers::indexers::delete_indexer::delete_indexer;
+use crate::handlers::indexers::diagnostics::get_indexer_diagnostics;
+use crate::handlers::indexers::estimate::{estimate_indexer, receive_estimate_payload};
+use crate::handlers::indexers::export::export_indexers;
+use crate::handlers::indexers::fleet_diff::diff_indexers;
+use crate::handlers::indexers::gaps::{backfill_indexer_gaps, get_indexer_gaps, record_delivered_range};
 use crate::handlers::indexers::get_indexer::{
-    get_indexer, get_indexer_status, get_indexer_status_by_table_name, get_indexers,
+    get_indexer, get_indexer_launch_command, get_indexer_state, get_indexer_status, get_indexer_status_by_table_name,
+    get_indexers,
 };
+use crate::handlers::indexers::logs::get_indexer_logs;
+use crate::handlers::indexers::multiplexer::{fan_out, get_multiplexer_groups};
+use crate::handlers::indexers::preview::{preview_indexer, receive_preview_payload};
+use crate::handlers::indexers::scheduled_actions::get_schedule_preview;
+use crate::handlers::indexers::script_scan::get_script_scan;
+use crate::handlers::indexers::script_search::search_scripts;
+use crate::handlers::indexers::simulate_delivery::simulate_delivery;
+use crate::handlers::indexers::standby::create_standby;
 use crate::handlers::indexers::start_indexer::start_indexer_api;
+use crate::handlers::indexers::start_tokens::{
+    create_start_token, delete_start_token, get_start_tokens, redeem_start_token,
+};
 use crate::handlers::indexers::stop_indexer::stop_indexer;
+use crate::handlers::indexers::update_indexer::update_indexer;
+use crate::handlers::metrics::grafana::{
+    get_grafana_annotations, get_grafana_metrics, get_metric_series, grafana_health, query_grafana_metrics,
+};
+use crate::handlers::notifications::alert_rules::{
+    create_alert_rule, delete_alert_rule, get_alert_rule, get_alert_rules, update_alert_rule,
+};
+use crate::handlers::notifications::policy::{
+    delete_notification_policy, get_notification_policy, update_notification_policy,
+};
+use crate::handlers::notifications::signing_keys::{get_signing_keys, verify};
+use crate::handlers::projects::indexers::get_project_indexers;
+use crate::handlers::projects::manage::{create_project, delete_project, get_project, get_projects, update_project};
+use crate::handlers::tenants::approved_targets::{approve_target, get_approved_targets, revoke_target};
+use crate::handlers::tenants::keys::get_tenant_key_api;
+use crate::handlers::tenants::quota::get_tenant_quota;
+use crate::handlers::tenants::settings::{delete_tenant_settings, get_tenant_settings, update_tenant_settings};
+use crate::handlers::tenants::usage::{export_tenant_usage, get_tenant_usage, receive_egress_report};
+use crate::handlers::uploads::sessions::{
+    complete_upload_session, create_upload_session, get_upload_session, upload_part,
+};
+use crate::handlers::v2;
+use crate::utils::actor_context::actor_context_middleware;
+use crate::utils::tls::require_client_certificate;
 use crate::AppState;
 
+/// v1 routes keep their response shapes for existing clients. v2 routes wrap every response in
+/// an envelope and answer errors with a code, they're added as endpoints move to the new shapes.
 pub fn app_router(state: AppState) -> Router<AppState> {
     Router::new()
         .nest("/", global_routes(state.clone()))
-        .nest("/v1/indexers", indexers_routes(state))
+        .nest("/v1/indexers", indexers_routes(state.clone()))
+        .nest("/v1/uploads", uploads_routes(state.clone()))
+        .nest("/v1/notifications", notifications_routes(state.clone()))
+        .nest("/v1/alert-rules", alert_rules_routes(state.clone()))
+        .nest("/v1/metrics/query", grafana_routes(state.clone()))
+        .nest("/v1/tenants", tenants_routes(state.clone()))
+        .nest("/v1/projects", projects_routes(state.clone()))
+        .nest("/v1/contracts", contracts_routes(state.clone()))
+        .nest("/v1/events", events_routes(state.clone()))
+        .nest("/v1/admin", admin_routes(state.clone()))
+        .nest("/v2/indexers", v2_indexers_routes(state))
         .fallback(handler_404)
+        .layer(middleware::from_fn(actor_context_middleware))
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any))
 }
 
+/// Callbacks of the sinks and sidecars, served on the internal listener only so that they never
+/// reach the public interface
+pub fn internal_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .nest("/internal", internal_routes(state))
+        .fallback(handler_404)
+        .layer(middleware::from_fn(actor_context_middleware))
+}
+
 async fn handler_404() -> impl IntoResponse {
     (StatusCode::NOT_FOUND, "The requested resource was not found")
 }
 
 fn indexers_routes(state: AppState) -> Router<AppState> {
     Router::new()
-        .route("/", post(create_indexer))
+        .route("/", get(get_indexers).post(create_indexer))
         .route("/indexers", get(get_indexers))
+        .route("/diff", post(diff_indexers))
+        .route("/estimate", post(estimate_indexer))
+        .route("/export", get(export_indexers))
+        .route("/multiplexer", get(get_multiplexer_groups))
+        .route("/search-scripts", get(search_scripts))
         .route("/stop/:id", post(stop_indexer))
         .route("/start/:id", post(start_indexer_api))
         .route("/delete/:id", delete(delete_indexer))
-        .route("/:id", get(get_indexer))
+        .route("/:id", get(get_indexer).patch(update_indexer).delete(delete_indexer))
+        .route("/:id/state", get(get_indexer_state))
+        .route("/:id/history", get(get_indexer_history))
+        .route("/:id/annotations", get(get_annotations).post(create_annotation))
+        .route("/:id/schedule/next", get(get_schedule_preview))
+        .route("/:id/script/advisories", get(get_script_scan))
+        .route("/:id/annotations/:annotation_id", delete(delete_annotation))
+        .route("/:id/config", get(get_indexer_launch_command))
+        .route("/:id/logs", get(get_indexer_logs))
+        .route("/:id/console", get(get_console_output))
+        .route("/:id/diagnostics/:exited_at", get(get_indexer_diagnostics))
+        .route("/:id/standby", post(create_standby))
+        .route("/:id/create/complete", post(complete_create_indexer))
+        .route("/:id/tokens", get(get_start_tokens).post(create_start_token))
+        .route("/:id/tokens/redeem", post(redeem_start_token))
+        .route("/:id/tokens/:token_id", delete(delete_start_token))
+        .route("/:id/clone", post(clone_indexer))
+        .route("/:id/deliveries", post(record_delivered_range))
+        .route("/:id/gaps", get(get_indexer_gaps))
+        .route("/:id/gaps/backfill", post(backfill_indexer_gaps))
+        .route("/:id/preview", post(preview_indexer))
+        .route("/:id/simulate-delivery", post(simulate_delivery))
+        .route(
+            "/:id/notification-policy",
+            get(get_notification_policy).put(update_notification_policy).delete(delete_notification_policy),
+        )
         .route("/status/:id", get(get_indexer_status))
         .route("/status/table/:table_name", get(get_indexer_status_by_table_name))
         .with_state(state)
 }
 
+fn v2_indexers_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(v2::indexers::get_indexers))
+        .route("/:id", get(v2::indexers::get_indexer))
+        .with_state(state)
+}
+
 fn global_routes(state: AppState) -> Router<AppState> {
-    Router::new().route("/health", get(health_check)).with_state(state)
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/ready", get(readiness_check))
+        .route("/v1/version", get(get_version))
+        .route("/v1/capabilities", get(get_capabilities))
+        .route("/v1/search", get(search_block))
+        .route("/v1/schemas/events", get(get_event_schema_versions))
+        .route("/v1/schemas/events/:version", get(get_event_schema))
+        .with_state(state)
+}
+
+fn uploads_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_upload_session))
+        .route("/:id", get(get_upload_session))
+        .route("/:id/parts/:part_number", put(upload_part))
+        .route("/:id/complete", post(complete_upload_session))
+        .with_state(state)
+}
+
+fn notifications_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/signing-keys", get(get_signing_keys)).route("/verify", post(verify)).with_state(state)
+}
+
+fn alert_rules_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_alert_rules).post(create_alert_rule))
+        .route("/:id", get(get_alert_rule).put(update_alert_rule).delete(delete_alert_rule))
+        .with_state(state)
+}
+
+/// Grafana JSON datasource, the datasource url is `/v1/metrics/query`
+fn grafana_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(grafana_health))
+        .route("/metrics", post(get_grafana_metrics))
+        .route("/search", post(get_grafana_metrics))
+        .route("/query", post(query_grafana_metrics))
+        .route("/annotations", post(get_grafana_annotations))
+        .with_state(state)
+}
+
+fn tenants_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/:id/settings", get(get_tenant_settings).put(update_tenant_settings).delete(delete_tenant_settings))
+        .route("/:id/key", get(get_tenant_key_api))
+        .route("/:id/quota", get(get_tenant_quota))
+        .route("/:id/approved-targets", get(get_approved_targets).post(approve_target).delete(revoke_target))
+        .route("/:id/usage", get(get_tenant_usage))
+        .route("/:id/usage/export", get(export_tenant_usage))
+        .with_state(state)
+}
+
+fn projects_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_projects).post(create_project))
+        .route("/:id", get(get_project).put(update_project).delete(delete_project))
+        .route("/:id/indexers", get(get_project_indexers))
+        .with_state(state)
+}
+
+fn contracts_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/:address/indexers", get(get_contract_indexers)).with_state(state)
+}
+
+fn events_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/stream", get(stream_events)).with_state(state)
+}
+
+fn admin_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/indexers/:id/force-status", post(force_status))
+        .route("/indexers/:id/process-priority", put(update_process_priority))
+        .route("/runtime", get(get_runtime_metrics))
+        .route("/database-pool", get(get_database_pool_metrics))
+        .route("/database-pools", get(get_database_pools_metrics))
+        .route("/metric-series", get(get_metric_series))
+        .route("/audit-logs", get(get_audit_logs))
+        .route("/maintenance-windows", get(get_maintenance_windows).post(create_maintenance_window))
+        .route("/maintenance-windows/:id", delete(delete_maintenance_window))
+        .route("/reconfigure", post(reconfigure))
+        .route("/refresh-status", post(refresh_status))
+        .route("/data-migrations", post(migrate_data))
+        .route("/quarantined-indexers", get(get_quarantined))
+        .route("/script-sync", post(check_script_sync))
+        .route("/scheduled-actions", get(get_scheduled_actions).post(create_scheduled_action))
+        .route("/scheduled-actions/:id", delete(delete_scheduled_action))
+        .route("/gitops", get(get_gitops_status))
+        .route("/gitops/sync", post(trigger_gitops_sync))
+        .route("/gitops/webhook", post(receive_gitops_webhook))
+        .route("/approvals", get(get_pending_approvals))
+        .route("/approvals/:id", post(approve_indexer))
+        .with_state(state)
+}
+
+fn internal_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/multiplexer/:key", post(fan_out))
+        .route("/preview/:id", post(receive_preview_payload))
+        .route("/estimate/:id", post(receive_estimate_payload))
+        .route("/egress/:id", post(receive_egress_report))
+        .layer(middleware::from_fn(require_client_certificate))
+        .with_state(state)
 }
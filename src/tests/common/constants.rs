@@ -3,3 +3,7 @@ pub const WEHBHOOK_URL: &str = "https://webhook.site/bc2ca42e-a8b2-43cf-b95c-779
 pub const TABLE_NAME: &str = "test_table";
 pub const WORKING_APIBARA_SCRIPT: &str = "./src/tests/scripts/test.js";
 pub const BROKEN_APIBARA_SCRIPT: &str = "./src/tests/scripts/broken_indexer.js";
+pub const TEST_ADMIN_API_KEY: &str = "test_admin_api_key";
+pub const TEST_SIGNING_KEY_ID: &str = "test_key";
+pub const TEST_SIGNING_KEY_SECRET: &str = "test_key_secret";
+pub const TEST_TENANT_KEY_SECRET: &str = "test_tenant_key_secret";
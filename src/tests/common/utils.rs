@@ -3,6 +3,7 @@ use std::process::Stdio;
 
 use axum::http;
 use axum::http::{Request, Response, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
 use diesel::{Connection, PgConnection, RunQueryDsl};
 use hyper::client::HttpConnector;
 use hyper::{Body, Client};
@@ -13,9 +14,12 @@ use tokio::process::Command;
 use uuid::Uuid;
 
 use crate::config::config;
+use crate::domain::models::execution::ExecutionRef;
 use crate::domain::models::indexer::{IndexerModel, IndexerType};
+use crate::handlers::indexers::start_tokens::START_TOKEN_HEADER;
 use crate::infra::repositories::indexer_repository::{IndexerFilter, IndexerRepository, Repository};
-use crate::tests::common::constants::{TABLE_NAME, WEHBHOOK_URL};
+use crate::tests::common::constants::{TABLE_NAME, TEST_ADMIN_API_KEY, WEHBHOOK_URL};
+use crate::utils::custom_extractors::admin_extractor::ADMIN_API_KEY_HEADER;
 
 /// Clears the database in the specified db_url. It first closes all connections
 /// to that database as without it we get an error. The db_url must be the root db url
@@ -192,8 +196,9 @@ pub async fn get_indexer_by_table_name(table_name: &str) -> IndexerModel {
     repository.get_by_table_name(table_name.to_string()).await.unwrap()
 }
 
-/// Check if a process is running using the process id
-pub async fn is_process_running(process_id: i64) -> bool {
+/// Check if the process of an indexer is running
+pub async fn is_process_running(execution_ref: &ExecutionRef) -> bool {
+    let process_id = execution_ref.pid().expect("indexer isn't run by a process");
     Command::new("ps")
         // Silence  stdout and stderr
         .stdout(Stdio::null())
@@ -209,3 +214,279 @@ pub async fn is_process_running(process_id: i64) -> bool {
         .unwrap()
         .success()
 }
+
+/// Sends a request to force the status of the indexer.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - id: The id of the indexer
+/// - body: The json body of the request
+/// - api_key: The admin api key, if any
+/// - addr: The address of the server to send the request to
+pub async fn send_force_status_request(
+    client: Client<HttpConnector>,
+    id: Uuid,
+    body: serde_json::Value,
+    api_key: Option<&str>,
+    addr: SocketAddr,
+) -> Response<Body> {
+    let mut request = Request::builder()
+        .method(http::Method::POST)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .uri(format!("http://{}/v1/admin/indexers/{}/force-status", addr, id));
+    if let Some(api_key) = api_key {
+        request = request.header(ADMIN_API_KEY_HEADER, api_key);
+    }
+    client.request(request.body(Body::from(body.to_string())).unwrap()).await.unwrap()
+}
+
+/// Sends a fleet-wide reconfiguration request with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - body: The json body of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_reconfigure_request(
+    client: Client<HttpConnector>,
+    body: serde_json::Value,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/admin/reconfigure", addr))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to refresh the status of indexers with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - body: The json body of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_refresh_status_request(
+    client: Client<HttpConnector>,
+    body: serde_json::Value,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/admin/refresh-status", addr))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request of the Grafana JSON datasource with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - path: The endpoint of the datasource, e.g. `query`
+/// - body: The json body of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_grafana_request(
+    client: Client<HttpConnector>,
+    path: &str,
+    body: serde_json::Value,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/metrics/query/{}", addr, path))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to check the scripts in the store with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - query: The query string of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_script_sync_request(client: Client<HttpConnector>, query: &str, addr: SocketAddr) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::POST)
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/admin/script-sync?{}", addr, query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to export the inventory of indexers with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - query: The query string of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_export_indexers_request(
+    client: Client<HttpConnector>,
+    query: &str,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::GET)
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/indexers/export?{}", addr, query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to set the process priority of an indexer with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - id: The id of the indexer
+/// - body: The json body of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_update_process_priority_request(
+    client: Client<HttpConnector>,
+    id: Uuid,
+    body: serde_json::Value,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::PUT)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .uri(format!("http://{}/v1/admin/indexers/{}/process-priority", addr, id))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to list the audit logs with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - query: The query string of the request
+/// - addr: The address of the server to send the request to
+pub async fn send_get_audit_logs_request(
+    client: Client<HttpConnector>,
+    query: &str,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/admin/audit-logs?{}", addr, query))
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to get the series of the metrics with the admin api key.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - addr: The address of the server to send the request to
+pub async fn send_get_metric_series_request(client: Client<HttpConnector>, addr: SocketAddr) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/admin/metric-series", addr))
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to get the state of an indexer at a point in time.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - id: The id of the indexer
+/// - at: The point in time to reconstruct the state at
+/// - addr: The address of the server to send the request to
+pub async fn send_get_indexer_state_request(
+    client: Client<HttpConnector>,
+    id: Uuid,
+    at: DateTime<Utc>,
+    addr: SocketAddr,
+) -> Response<Body> {
+    let at = at.to_rfc3339_opts(SecondsFormat::Micros, true);
+    client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/indexers/{}/state?at={}", addr, id, at))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Sends a request to mint a start token for an indexer.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - id: The id of the indexer
+/// - api_key: The admin api key, if any
+/// - addr: The address of the server to send the request to
+pub async fn send_create_start_token_request(
+    client: Client<HttpConnector>,
+    id: Uuid,
+    api_key: Option<&str>,
+    addr: SocketAddr,
+) -> Response<Body> {
+    let mut request = Request::builder()
+        .method(http::Method::POST)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .uri(format!("http://{}/v1/indexers/{}/tokens", addr, id));
+    if let Some(api_key) = api_key {
+        request = request.header(ADMIN_API_KEY_HEADER, api_key);
+    }
+    client.request(request.body(Body::from("{}")).unwrap()).await.unwrap()
+}
+
+/// Sends a request to start an indexer with a start token.
+/// Arguments
+/// - client: The hyper client to use to send the request
+/// - id: The id of the indexer
+/// - token: The start token
+/// - addr: The address of the server to send the request to
+pub async fn send_redeem_start_token_request(
+    client: Client<HttpConnector>,
+    id: Uuid,
+    token: &str,
+    addr: SocketAddr,
+) -> Response<Body> {
+    client
+        .request(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("http://{}/v1/indexers/{}/tokens/redeem", addr, id))
+                .header(START_TOKEN_HEADER, token)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
@@ -1,9 +1,39 @@
 use crate::config::{config, config_force_init};
-use crate::domain::models::indexer::{IndexerStatus, IndexerType};
+use crate::domain::models::alert_rule::{AlertChannel, AlertCondition};
+use crate::domain::models::contract::ContractFilter;
+use crate::domain::models::delivery::BlockRange;
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerStatus, IndexerType};
+use crate::domain::models::notification::NotificationPolicy;
+use crate::domain::models::project::{ProjectRole, ProjectRoleBinding};
+use crate::domain::models::scheduled_action::ScheduledActionKind;
+use crate::domain::models::script_scan::AdvisorySeverity;
+use crate::domain::models::secret::SecretBackendKind;
+use crate::domain::models::stale_created::StaleCreatedAction;
+use crate::domain::models::upload::UploadStatus;
+use crate::infra::data_migrations::run_data_migrations;
+use crate::infra::repositories::alert_rule_repository::{AlertRuleRepository, NewAlertRuleDb};
+use crate::infra::repositories::annotation_repository::{AnnotationRepository, NewAnnotationDb};
+use crate::infra::repositories::approved_target_repository::{ApprovedTargetRepository, NewApprovedTargetDb};
+use crate::infra::repositories::contract_repository::{ContractRepository, NewIndexerContractDb};
+use crate::infra::repositories::delivery_repository::{DeliveryRepository, NewDeliveredRangeDb};
 use crate::infra::repositories::indexer_repository::{
-    IndexerFilter, IndexerRepository, NewIndexerDb, Repository, UpdateIndexerStatusAndProcessIdDb,
-    UpdateIndexerStatusDb,
+    get_quarantined_indexers, IndexerFilter, IndexerListing, IndexerRepository, NewIndexerDb, Repository,
+    UpdateIndexerLogLevelDb, UpdateIndexerScriptParamsDb, UpdateIndexerStatusAndExecutionRefDb, UpdateIndexerStatusDb,
 };
+use crate::infra::repositories::maintenance_repository::{MaintenanceRepository, NewMaintenanceWindowDb};
+use crate::infra::repositories::notification_policy_repository::{
+    NewNotificationPolicyDb, NotificationPolicyRepository,
+};
+use crate::infra::repositories::project_repository::{NewProjectDb, ProjectRepository};
+use crate::infra::repositories::scheduled_action_repository::{
+    NewScheduledActionDb, ScheduledActionFilter, ScheduledActionRepository,
+};
+use crate::infra::repositories::script_index_repository::{NewScriptIndexDb, ScriptIndexRepository};
+use crate::infra::repositories::script_scan_repository::{NewScriptScanDb, ScriptScanRepository};
+use crate::infra::repositories::tenant_repository::{NewTenantSettingsDb, TenantRepository};
+use crate::infra::repositories::upload_repository::{NewUploadPartDb, NewUploadSessionDb, UploadRepository};
+use crate::infra::repositories::usage_repository::{NewUsageRecordDb, UsageRepository};
 
 #[tokio::test]
 async fn test_get_indexer() {
@@ -26,6 +56,22 @@ async fn test_get_indexer() {
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
         })
         .await
         .unwrap();
@@ -37,10 +83,53 @@ async fn test_get_indexer() {
     assert_eq!(inserted.status, IndexerStatus::Created);
     assert_eq!(inserted.indexer_type, IndexerType::Webhook);
     assert_eq!(inserted.target_url, Some("https://example.com".to_string()));
-    assert_eq!(inserted.process_id, None);
+    assert_eq!(inserted.execution_ref, None);
     assert_eq!(inserted.table_name, None);
 }
 
+#[tokio::test]
+async fn test_version_changes_with_the_indexers() {
+    config_force_init().await;
+    let config = config().await;
+
+    let mut repository = IndexerRepository::new(config.pool());
+    let version = repository.get_version().await.unwrap();
+
+    repository
+        .insert(NewIndexerDb {
+            id: uuid::Uuid::new_v4(),
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    // holds for the first change of the indexers as well
+    assert!(repository.get_version().await.unwrap() > version);
+}
+
 #[tokio::test]
 async fn test_insert_indexer() {
     config_force_init().await;
@@ -60,6 +149,22 @@ async fn test_insert_indexer() {
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
         })
         .await
         .unwrap();
@@ -68,7 +173,7 @@ async fn test_insert_indexer() {
     assert_eq!(inserted.status, IndexerStatus::Created);
     assert_eq!(inserted.indexer_type, IndexerType::Webhook);
     assert_eq!(inserted.target_url, Some("https://example.com".to_string()));
-    assert_eq!(inserted.process_id, None);
+    assert_eq!(inserted.execution_ref, None);
     assert_eq!(inserted.table_name, None);
 }
 
@@ -91,6 +196,22 @@ async fn test_update_status() {
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
         })
         .await
         .unwrap();
@@ -103,7 +224,7 @@ async fn test_update_status() {
 }
 
 #[tokio::test]
-async fn test_update_status_and_process_id() {
+async fn test_update_status_and_execution_ref() {
     config_force_init().await;
     let config = config().await;
     let mut repository = IndexerRepository::new(config.pool());
@@ -121,22 +242,43 @@ async fn test_update_status_and_process_id() {
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
         })
         .await
         .unwrap();
 
     // Update status in DB
+    let execution_ref = ExecutionRef::Pid { pid: 1234, start_time: Some(42) };
     let updated = repository
-        .update_status_and_process_id(UpdateIndexerStatusAndProcessIdDb {
+        .update_status_and_execution_ref(UpdateIndexerStatusAndExecutionRefDb {
             id,
             status: "Running".to_string(),
-            process_id: 1234,
+            execution_ref: serde_json::to_value(&execution_ref).unwrap(),
+            launch_config: None,
         })
         .await
         .unwrap();
 
     assert_eq!(updated.id, id);
     assert_eq!(updated.status, IndexerStatus::Running);
+    assert_eq!(updated.execution_ref, Some(execution_ref));
+    // each start gets the next sequence
+    assert_eq!(updated.execution_seq, 1);
 }
 
 #[tokio::test]
@@ -159,6 +301,22 @@ async fn test_get_all_indexers() {
                 custom_connection_string: None,
                 starting_block: None,
                 indexer_id: None,
+                log_level: None,
+                standby_for: None,
+                script_permissions: None,
+                tenant_id: None,
+                stream_url: None,
+                ending_block: None,
+                backfill_for: None,
+                script_params: None,
+                script_checksum: None,
+                hooks: None,
+                sink_options: None,
+                priority: 0,
+                process_priority: None,
+                script_source_url: None,
+                project_id: None,
+                output_location: None,
             })
             .await
             .unwrap();
@@ -176,6 +334,22 @@ async fn test_get_all_indexers() {
             custom_connection_string: None,
             starting_block: None,
             indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
         })
         .await
         .unwrap();
@@ -194,4 +368,1004 @@ async fn test_get_all_indexers() {
     let indexers = repository.get_all(IndexerFilter { status: Some("Running".to_string()) }).await.unwrap();
 
     assert_eq!(indexers.len(), 1);
+
+    // Summaries are filtered the same way
+    let summaries = repository.get_summaries(IndexerFilter { status: Some("Running".to_string()) }).await.unwrap();
+
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].id, id);
+    assert_eq!(summaries[0].target_url, Some("https://example.com".to_string()));
+
+    // Listings are ordered by creation time and paginated
+    let listing =
+        IndexerListing { status: Some("Created".to_string()), limit: Some(2), offset: 4, ..Default::default() };
+    assert_eq!(repository.get_listing(listing).await.unwrap().len(), 1);
+    let listing = IndexerListing { descending: true, limit: Some(1), ..Default::default() };
+    let indexers = repository.get_listing(listing).await.unwrap();
+    assert_eq!(indexers.iter().map(|indexer| indexer.id).collect::<Vec<_>>(), vec![id]);
+    let listing = IndexerListing { type_: Some("Postgres".to_string()), ..Default::default() };
+    assert!(repository.get_summary_listing(listing).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_update_log_level() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+
+    // Insert in DB
+    let _ = repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    // Update log level in DB
+    let updated = repository
+        .update_log_level(UpdateIndexerLogLevelDb { id, log_level: Some("debug".to_string()) })
+        .await
+        .unwrap();
+
+    assert_eq!(updated.id, id);
+    assert_eq!(updated.log_level, Some(IndexerLogLevel::Debug));
+}
+
+#[tokio::test]
+async fn test_update_script_params() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+
+    let _ = repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: Some(serde_json::json!({"address": "0x1"})),
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(repository.get(id).await.unwrap().script_params.get("address"), Some(&"0x1".to_string()));
+
+    let updated = repository
+        .update_script_params(UpdateIndexerScriptParamsDb {
+            id,
+            script_params: Some(serde_json::json!({"address": "0x2"})),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(updated.script_params.get("address"), Some(&"0x2".to_string()));
+}
+
+#[tokio::test]
+async fn test_get_standby() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let (primary_id, standby_id) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+
+    for (id, standby_for) in [(primary_id, None), (standby_id, Some(primary_id))] {
+        repository
+            .insert(NewIndexerDb {
+                id,
+                status: "Stopped".to_string(),
+                type_: "Webhook".to_string(),
+                target_url: Some("https://example.com".to_string()),
+                table_name: None,
+                status_server_port: None,
+                custom_connection_string: None,
+                starting_block: None,
+                indexer_id: Some(primary_id.to_string()),
+                log_level: None,
+                standby_for,
+                script_permissions: None,
+                tenant_id: None,
+                stream_url: None,
+                ending_block: None,
+                backfill_for: None,
+                script_params: None,
+                script_checksum: None,
+                hooks: None,
+                sink_options: None,
+                priority: 0,
+                process_priority: None,
+                script_source_url: None,
+                project_id: None,
+                output_location: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let standby = repository.get_standby(primary_id).await.unwrap().unwrap();
+    assert_eq!(standby.id, standby_id);
+    assert_eq!(standby.standby_for, Some(primary_id));
+    assert!(repository.get_standby(standby_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_data_migrations() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+
+    // the row is stored but can't be read back
+    let inserted = repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Starting".to_string(),
+            type_: "webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await;
+    assert!(inserted.is_err());
+
+    let report = run_data_migrations(config.pool(), true).await.unwrap();
+    assert!(report.migrated.iter().any(|value| value.from == "Starting" && value.to == "FailedRunning"));
+    assert!(repository.get(id).await.is_err());
+
+    run_data_migrations(config.pool(), false).await.unwrap();
+    let indexer = repository.get(id).await.unwrap();
+    assert_eq!(indexer.status, IndexerStatus::FailedRunning);
+    assert_eq!(indexer.indexer_type, IndexerType::Webhook);
+}
+
+#[tokio::test]
+async fn test_quarantine() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+
+    // the row is stored but can't be read back
+    let inserted = repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Paused".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await;
+    assert!(inserted.is_err());
+
+    let indexers = repository.get_all(IndexerFilter { status: None }).await.unwrap();
+    assert!(indexers.iter().all(|indexer| indexer.id != id));
+    let quarantined = get_quarantined_indexers();
+    let indexer = quarantined.iter().find(|indexer| indexer.indexer_id == id).unwrap();
+    assert_eq!(indexer.status, "Paused");
+
+    repository.delete(id).await.unwrap();
+    repository.get_all(IndexerFilter { status: None }).await.unwrap();
+    assert!(get_quarantined_indexers().iter().all(|indexer| indexer.indexer_id != id));
+}
+
+#[tokio::test]
+async fn test_script_search() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: Some("checksum".to_string()),
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    let mut script_repository = ScriptIndexRepository::new(config.pool());
+    script_repository
+        .upsert(NewScriptIndexDb {
+            indexer_id: id,
+            script_checksum: Some("checksum".to_string()),
+            content: "export const config = { filter: { events: [{ fromAddress: \"0x0ABCDEF\" }] } };".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(script_repository.get_checksums().await.unwrap().get(&id), Some(&Some("checksum".to_string())));
+
+    // by word and by part of an address
+    for query in ["filter", "0x0abc"] {
+        let matches = script_repository.search(query, 10).await.unwrap();
+        assert!(matches.iter().any(|(indexer, _)| indexer.id == id), "no match for {}", query);
+    }
+    // wildcards of the query are matched literally
+    for query in ["transfer", "0x0_bc", "0x0%def"] {
+        let matches = script_repository.search(query, 10).await.unwrap();
+        assert!(matches.iter().all(|(indexer, _)| indexer.id != id), "unexpected match for {}", query);
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_settings() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = TenantRepository::new(config.pool());
+    let tenant_id = uuid::Uuid::new_v4().to_string();
+
+    assert!(repository.get_settings(tenant_id.as_str()).await.unwrap().is_none());
+
+    for log_level in ["info", "debug"] {
+        repository
+            .upsert_settings(NewTenantSettingsDb {
+                tenant_id: tenant_id.clone(),
+                default_stream_url: Some("https://mainnet.starknet.a5a.ch".to_string()),
+                default_log_level: Some(log_level.to_string()),
+                allowed_indexer_types: serde_json::json!(["Webhook"]),
+                max_starts_per_minute: Some(30),
+                stale_created_after_seconds: Some(300),
+                stale_created_action: Some("abandon".to_string()),
+                require_target_approval: true,
+                secret_backend: Some("vault".to_string()),
+            })
+            .await
+            .unwrap();
+    }
+
+    let settings = repository.get_settings(tenant_id.as_str()).await.unwrap().unwrap();
+    assert_eq!(settings.default_log_level, Some(IndexerLogLevel::Debug));
+    assert_eq!(settings.allowed_indexer_types, vec![IndexerType::Webhook]);
+    assert_eq!(settings.max_starts_per_minute, Some(30));
+    assert_eq!(settings.stale_created_after_seconds, Some(300));
+    assert_eq!(settings.stale_created_action, Some(StaleCreatedAction::Abandon));
+    assert!(settings.require_target_approval);
+    assert_eq!(settings.secret_backend, Some(SecretBackendKind::Vault));
+
+    assert!(repository.delete_settings(tenant_id.as_str()).await.unwrap());
+    assert!(!repository.delete_settings(tenant_id.as_str()).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_contracts() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let mut contract_repository = ContractRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    let address = format!("0x{:0>64}", "49d");
+
+    repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+    let filters = vec![ContractFilter { address: address.clone(), event_keys: vec![format!("0x{:0>64}", "99cd")] }];
+    contract_repository.insert(NewIndexerContractDb::from_filters(id, filters.clone())).await.unwrap();
+
+    assert_eq!(contract_repository.get_by_indexer(id).await.unwrap(), filters);
+    let indexers = contract_repository.get_indexers_by_address(address.as_str()).await.unwrap();
+    assert_eq!(indexers.iter().map(|indexer| indexer.id).collect::<Vec<_>>(), vec![id]);
+
+    // the registry is cleaned up with the indexer
+    repository.delete(id).await.unwrap();
+    assert!(contract_repository.get_indexers_by_address(address.as_str()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_active_maintenance_windows() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = MaintenanceRepository::new(config.pool());
+    let now = chrono::Utc::now();
+    let hour = chrono::Duration::hours(1);
+
+    // a global window, a window of another tenant and a window which already ended
+    for (tenant_id, starts_at, ends_at) in [
+        (None, now - hour, now + hour),
+        (Some("other"), now - hour, now + hour),
+        (Some("tenant"), now - hour * 2, now - hour),
+    ] {
+        repository
+            .insert(NewMaintenanceWindowDb {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: tenant_id.map(|tenant_id| tenant_id.to_string()),
+                kind: "Maintenance".to_string(),
+                starts_at,
+                ends_at,
+                reason: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let windows = repository.get_active(Some("tenant"), now).await.unwrap();
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0].tenant_id, None);
+    assert_eq!(repository.get_active(Some("other"), now).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_get_delivered_ranges() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let mut delivery_repository = DeliveryRepository::new(config.pool());
+    let (id, backfill_id) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+
+    for (id, backfill_for) in [(id, None), (backfill_id, Some(id))] {
+        repository
+            .insert(NewIndexerDb {
+                id,
+                status: "Stopped".to_string(),
+                type_: "Webhook".to_string(),
+                target_url: Some("https://example.com".to_string()),
+                table_name: None,
+                status_server_port: None,
+                custom_connection_string: None,
+                starting_block: None,
+                indexer_id: None,
+                log_level: None,
+                standby_for: None,
+                script_permissions: None,
+                tenant_id: None,
+                stream_url: None,
+                ending_block: None,
+                backfill_for,
+                script_params: None,
+                script_checksum: None,
+                hooks: None,
+                sink_options: None,
+                priority: 0,
+                process_priority: None,
+                script_source_url: None,
+                project_id: None,
+                output_location: None,
+            })
+            .await
+            .unwrap();
+    }
+    // the range reported twice is only recorded once
+    for (indexer_id, start_block, end_block) in [(id, 1, 10), (id, 21, 30), (backfill_id, 11, 20), (id, 1, 10)] {
+        delivery_repository
+            .insert(NewDeliveredRangeDb { id: uuid::Uuid::new_v4(), indexer_id, start_block, end_block })
+            .await
+            .unwrap();
+    }
+
+    // deliveries of the backfill count for the indexer it fills
+    let ranges = delivery_repository.get_delivered_ranges(id).await.unwrap();
+    assert_eq!(
+        ranges,
+        vec![
+            BlockRange { start_block: 1, end_block: 10 },
+            BlockRange { start_block: 11, end_block: 20 },
+            BlockRange { start_block: 21, end_block: 30 },
+        ]
+    );
+    assert_eq!(delivery_repository.get_delivered_ranges(backfill_id).await.unwrap().len(), 1);
+    let backfills = repository.get_backfills(id).await.unwrap();
+    assert_eq!(backfills.iter().map(|backfill| backfill.id).collect::<Vec<_>>(), vec![backfill_id]);
+
+    let delivering = delivery_repository.get_indexers_delivering(15).await.unwrap();
+    assert!(delivering.contains(&id));
+    assert!(!delivering.contains(&backfill_id));
+}
+
+#[tokio::test]
+async fn test_scheduled_actions() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    let now = chrono::Utc::now();
+    let lease = chrono::Duration::seconds(60);
+    let mut action_repository = ScheduledActionRepository::new(config.pool());
+    let due = action_repository
+        .insert(NewScheduledActionDb {
+            id: uuid::Uuid::new_v4(),
+            indexer_id: id,
+            kind: ScheduledActionKind::Start.to_string(),
+            run_at: now - chrono::Duration::seconds(1),
+            cron: None,
+            timezone: None,
+        })
+        .await
+        .unwrap();
+    let later = action_repository
+        .insert(NewScheduledActionDb {
+            id: uuid::Uuid::new_v4(),
+            indexer_id: id,
+            kind: ScheduledActionKind::Stop.to_string(),
+            run_at: now + chrono::Duration::hours(1),
+            cron: Some("0 9 * * MON-FRI".to_string()),
+            timezone: Some("Europe/Paris".to_string()),
+        })
+        .await
+        .unwrap();
+
+    // only the due action is claimed, and only once while it's locked
+    let claimed = action_repository.claim_due(now, lease, 10).await.unwrap();
+    assert_eq!(claimed.iter().map(|action| action.id).collect::<Vec<_>>(), vec![due.id]);
+    assert_eq!(claimed[0].attempts, 1);
+    assert!(action_repository.claim_due(now, lease, 10).await.unwrap().is_empty());
+
+    // an expired lock means the executor was lost, the action runs again
+    let claimed = action_repository.claim_due(now + lease + chrono::Duration::seconds(1), lease, 10).await.unwrap();
+    assert_eq!(claimed[0].attempts, 2);
+
+    action_repository.mark_failed(due.id, "boom".to_string()).await.unwrap();
+    let filter = |include_failed| ScheduledActionFilter { indexer_id: Some(id), include_failed };
+    let pending = action_repository.get_all(filter(false)).await.unwrap();
+    assert_eq!(pending.iter().map(|action| action.id).collect::<Vec<_>>(), vec![later.id]);
+    assert_eq!(action_repository.get_all(filter(true)).await.unwrap().len(), 2);
+    assert_eq!(pending[0].timezone.as_deref(), Some("Europe/Paris"));
+
+    // recurring actions start over at their next run
+    let next_run = now + chrono::Duration::days(1);
+    action_repository.advance(later.id, next_run, Some("boom".to_string())).await.unwrap();
+    let advanced = action_repository.get_all(filter(false)).await.unwrap();
+    assert_eq!((advanced[0].run_at.timestamp(), advanced[0].attempts), (next_run.timestamp(), 0));
+
+    assert!(action_repository.delete(later.id).await.unwrap());
+    assert!(!action_repository.delete(later.id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_tenant_usage() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = UsageRepository::new(config.pool());
+    let tenant_id = uuid::Uuid::new_v4().to_string();
+    let indexer_id = uuid::Uuid::new_v4();
+    let day = |day: u32| chrono::NaiveDate::from_ymd_opt(2025, 7, day).unwrap();
+
+    // two samples of the same day and one of the next month
+    for (day, script_bytes) in [(day(1), 100), (day(1), 200), (day(1) + chrono::Duration::days(31), 200)] {
+        repository
+            .record(NewUsageRecordDb {
+                tenant_id: tenant_id.clone(),
+                indexer_id,
+                day,
+                running_seconds: 300,
+                blocks_processed: 10,
+                script_bytes,
+                log_bytes: 50,
+                streamed_bytes: 1000,
+                delivered_bytes: 400,
+            })
+            .await
+            .unwrap();
+    }
+
+    let records = repository.get_usage(tenant_id.as_str(), day(1), day(31)).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(
+        (records[0].running_seconds, records[0].blocks_processed, records[0].script_bytes, records[0].log_bytes),
+        (600, 20, 200, 100)
+    );
+    assert_eq!((records[0].streamed_bytes, records[0].delivered_bytes), (2000, 800));
+}
+
+#[tokio::test]
+async fn test_notification_policies() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    let mut policy_repository = NotificationPolicyRepository::new(config.pool());
+    assert!(policy_repository.get(id).await.unwrap().is_none());
+
+    for max_alerts_per_hour in [5, 10] {
+        policy_repository
+            .upsert(NewNotificationPolicyDb { indexer_id: id, max_alerts_per_hour, duplicate_window_seconds: 600 })
+            .await
+            .unwrap();
+    }
+    let policy = policy_repository.get(id).await.unwrap().unwrap();
+    assert_eq!(policy, NotificationPolicy { max_alerts_per_hour: 10, duplicate_window_seconds: 600 });
+
+    // policies are deleted with their indexer
+    repository.delete(id).await.unwrap();
+    assert!(policy_repository.get(id).await.unwrap().is_none());
+    assert!(!policy_repository.delete(id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_annotations() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    repository
+        .insert(NewIndexerDb {
+            id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap();
+
+    let mut annotation_repository = AnnotationRepository::new(config.pool());
+    let mut ids = vec![];
+    for body in ["Stream outage", "Restarted once the stream was back"] {
+        let annotation = annotation_repository
+            .insert(NewAnnotationDb {
+                id: uuid::Uuid::new_v4(),
+                indexer_id: id,
+                body: body.to_string(),
+                author: "ops".to_string(),
+                incident_url: Some("https://tickets.example.com/INC-42".to_string()),
+            })
+            .await
+            .unwrap();
+        ids.push(annotation.id);
+    }
+
+    let annotations = annotation_repository.get_all_by_indexer(id, 10).await.unwrap();
+    assert_eq!(annotations.len(), 2);
+    assert_eq!(annotation_repository.get_all_by_indexer(id, 1).await.unwrap().len(), 1);
+
+    // annotations are only deleted through their indexer
+    assert!(!annotation_repository.delete(uuid::Uuid::new_v4(), ids[0]).await.unwrap());
+    assert!(annotation_repository.delete(id, ids[0]).await.unwrap());
+    let annotations = annotation_repository.get_all_by_indexer(id, 10).await.unwrap();
+    assert_eq!(annotations.iter().map(|annotation| annotation.id).collect::<Vec<_>>(), vec![ids[1]]);
+}
+
+#[tokio::test]
+async fn test_script_scans() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = ScriptScanRepository::new(config.pool());
+    let script_checksum = uuid::Uuid::new_v4().to_string();
+    assert!(repository.get(&script_checksum).await.unwrap().is_none());
+
+    let dependencies = serde_json::json!([{ "name": "lodash", "version": "4.17.20" }]);
+    repository
+        .upsert(NewScriptScanDb {
+            script_checksum: script_checksum.clone(),
+            dependencies: dependencies.clone(),
+            findings: serde_json::json!([]),
+        })
+        .await
+        .unwrap();
+    // a rescan replaces the findings
+    let scan = repository
+        .upsert(NewScriptScanDb {
+            script_checksum: script_checksum.clone(),
+            dependencies,
+            findings: serde_json::json!([{
+                "package": "lodash",
+                "version": "4.17.20",
+                "advisory_id": "GHSA-35jh-r3h4-6jhm",
+                "summary": "Command injection",
+                "severity": "high",
+            }]),
+        })
+        .await
+        .unwrap();
+    assert_eq!(scan.findings.len(), 1);
+
+    let stored = repository.get(&script_checksum).await.unwrap().unwrap();
+    assert_eq!(stored, scan);
+    assert_eq!(stored.dependencies[0].name, "lodash");
+    assert_eq!(stored.get_blocking_findings(AdvisorySeverity::Critical).len(), 0);
+    assert_eq!(stored.get_blocking_findings(AdvisorySeverity::High).len(), 1);
+}
+
+#[tokio::test]
+async fn test_approved_targets() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = ApprovedTargetRepository::new(config.pool());
+    let tenant_id = uuid::Uuid::new_v4().to_string();
+    let target_url = "https://example.com/hooks";
+    assert!(!repository.is_approved(&tenant_id, target_url).await.unwrap());
+
+    for approved_by in [None, Some("ops".to_string())] {
+        repository
+            .insert(NewApprovedTargetDb {
+                tenant_id: tenant_id.clone(),
+                target_url: target_url.to_string(),
+                approved_by,
+            })
+            .await
+            .unwrap();
+    }
+    // approving again keeps a single entry with the latest approval
+    let approved_targets = repository.get_all(&tenant_id).await.unwrap();
+    assert_eq!(approved_targets.len(), 1);
+    assert_eq!(approved_targets[0].approved_by, Some("ops".to_string()));
+    assert!(repository.is_approved(&tenant_id, target_url).await.unwrap());
+    assert!(!repository.is_approved("other-tenant", target_url).await.unwrap());
+
+    assert!(repository.delete(&tenant_id, target_url).await.unwrap());
+    assert!(!repository.delete(&tenant_id, target_url).await.unwrap());
+    assert!(!repository.is_approved(&tenant_id, target_url).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_alert_rules() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = AlertRuleRepository::new(config.pool());
+    let id = uuid::Uuid::new_v4();
+    let condition = AlertCondition::Status { status: IndexerStatus::FailedRunning };
+    let new_rule = |enabled: bool| NewAlertRuleDb {
+        id,
+        name: "failed".to_string(),
+        indexer_id: None,
+        condition: serde_json::to_value(&condition).unwrap(),
+        for_seconds: 300,
+        channels: serde_json::to_value(vec![AlertChannel::NotificationWebhook]).unwrap(),
+        enabled,
+    };
+
+    let inserted = repository.insert(new_rule(true)).await.unwrap();
+    assert_eq!(inserted.condition, condition);
+    assert_eq!(inserted.for_seconds, 300);
+    assert_eq!(repository.get(id).await.unwrap(), Some(inserted));
+    assert!(repository.get_all(true).await.unwrap().iter().any(|rule| rule.id == id));
+
+    let updated = repository.update(new_rule(false)).await.unwrap().unwrap();
+    assert!(!updated.enabled);
+    assert!(!repository.get_all(true).await.unwrap().iter().any(|rule| rule.id == id));
+    assert!(repository.get_all(false).await.unwrap().iter().any(|rule| rule.id == id));
+
+    assert!(repository.delete(id).await.unwrap());
+    assert!(!repository.delete(id).await.unwrap());
+    assert_eq!(repository.update(new_rule(true)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_projects() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = ProjectRepository::new(config.pool());
+    let mut indexer_repository = IndexerRepository::new(config.pool());
+    let tenant_id = uuid::Uuid::new_v4().to_string();
+    let new_project = |id: uuid::Uuid, name: &str| NewProjectDb {
+        id,
+        tenant_id: tenant_id.clone(),
+        name: name.to_string(),
+        description: None,
+        default_stream_url: Some("https://mainnet.starknet.a5a.ch".to_string()),
+        default_log_level: Some("debug".to_string()),
+        secret_path_prefix: Some("indexers/acme/".to_string()),
+        role_bindings: serde_json::json!([{"subject": "alice", "role": "owner"}]),
+    };
+
+    let id = uuid::Uuid::new_v4();
+    repository.insert(new_project(id, "pipelines")).await.unwrap();
+    let project = repository.get_by_name(tenant_id.as_str(), "pipelines").await.unwrap().unwrap();
+    assert_eq!(project.id, id);
+    assert_eq!(project.default_log_level, Some(IndexerLogLevel::Debug));
+    assert_eq!(project.role_bindings, vec![ProjectRoleBinding { subject: "alice".into(), role: ProjectRole::Owner }]);
+
+    let project = repository.update(new_project(id, "analytics")).await.unwrap().unwrap();
+    assert_eq!(project.name, "analytics");
+    assert!(repository.update(new_project(uuid::Uuid::new_v4(), "missing")).await.unwrap().is_none());
+    let projects = repository.get_all(Some(tenant_id.as_str())).await.unwrap();
+    assert_eq!(projects.iter().map(|project| project.id).collect::<Vec<_>>(), vec![id]);
+
+    let indexer_id = uuid::Uuid::new_v4();
+    indexer_repository
+        .insert(NewIndexerDb {
+            id: indexer_id,
+            status: "Created".to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some("https://example.com".to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: Some(tenant_id.clone()),
+            stream_url: None,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: Some(id),
+        })
+        .await
+        .unwrap();
+    let indexers = repository.get_indexers(id).await.unwrap();
+    assert_eq!(indexers.iter().map(|indexer| indexer.id).collect::<Vec<_>>(), vec![indexer_id]);
+    assert_eq!(indexers[0].project_id, Some(id));
+    assert_eq!(repository.count_indexers(id).await.unwrap(), 1);
+
+    indexer_repository.delete(indexer_id).await.unwrap();
+    assert_eq!(repository.count_indexers(id).await.unwrap(), 0);
+    assert!(repository.delete(id).await.unwrap());
+    assert!(repository.get(id).await.unwrap().is_none());
+    assert!(!repository.delete(id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_upload_sessions() {
+    config_force_init().await;
+    let config = config().await;
+    let mut repository = UploadRepository::new(config.pool());
+
+    let id = uuid::Uuid::new_v4();
+    let session = repository
+        .insert(NewUploadSessionDb {
+            id,
+            tenant_id: Some("acme".into()),
+            status: UploadStatus::InProgress.to_string(),
+            expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+        })
+        .await
+        .unwrap();
+    assert!(session.parts.is_empty());
+
+    let part = |part_number: i32, sha256: &str| NewUploadPartDb {
+        upload_id: id,
+        part_number,
+        size: 10,
+        sha256: sha256.into(),
+    };
+    repository.upsert_part(part(1, "first")).await.unwrap();
+    repository.upsert_part(part(2, "second")).await.unwrap();
+    repository.upsert_part(part(3, "extra")).await.unwrap();
+    // a part uploaded again replaces the previous one
+    repository.upsert_part(part(1, "retried")).await.unwrap();
+
+    let session = repository.get(id).await.unwrap().unwrap();
+    assert_eq!(session.tenant_id.as_deref(), Some("acme"));
+    assert_eq!(session.parts.len(), 3);
+    assert_eq!(session.parts[&1].sha256, "retried");
+
+    assert!(repository.complete(id, 2).await.unwrap());
+    assert!(!repository.complete(id, 2).await.unwrap());
+    let session = repository.get(id).await.unwrap().unwrap();
+    assert_eq!(session.status, UploadStatus::Completed);
+    assert_eq!(session.parts.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+
+    // expired sessions are not found anymore and are left to the cleanup
+    let expired_id = uuid::Uuid::new_v4();
+    repository
+        .insert(NewUploadSessionDb {
+            id: expired_id,
+            tenant_id: None,
+            status: UploadStatus::InProgress.to_string(),
+            expires_at: chrono::Utc::now() - chrono::Duration::seconds(1),
+        })
+        .await
+        .unwrap();
+    repository
+        .upsert_part(NewUploadPartDb { upload_id: expired_id, part_number: 1, size: 1, sha256: "expired".into() })
+        .await
+        .unwrap();
+    assert!(repository.get(expired_id).await.unwrap().is_none());
+    let expired = repository.get_expired().await.unwrap();
+    let expired_session = expired.iter().find(|session| session.id == expired_id).unwrap();
+    assert_eq!(expired_session.parts.len(), 1);
+    assert!(!expired.iter().any(|session| session.id == id));
+
+    assert!(repository.delete(expired_id).await.unwrap());
+    assert!(!repository.get_expired().await.unwrap().iter().any(|session| session.id == expired_id));
 }
@@ -0,0 +1,475 @@
+use std::net::SocketAddr;
+
+use hyper::StatusCode;
+use object_store::path::Path;
+use rstest::rstest;
+use serde_json::json;
+
+use crate::config::config;
+use crate::domain::models::audit::{AuditAction, AuditLogPage};
+use crate::domain::models::execution::ExecutionRef;
+use crate::domain::models::grafana::{GrafanaMetric, GrafanaSeries, MetricSeriesReport};
+use crate::domain::models::indexer::{IndexerModel, IndexerStateModel, IndexerStatus};
+use crate::domain::models::inventory::INVENTORY_COLUMNS;
+use crate::domain::models::process_priority::{IoClass, ProcessPriority};
+use crate::domain::models::reconfigure::ReconfigureModel;
+use crate::domain::models::script_sync::{ScriptSyncFix, ScriptSyncIssue, ScriptSyncReport};
+use crate::domain::models::status_refresh::{StatusCorrection, StatusRefreshReport};
+use crate::handlers::admin::runtime::get_process_start_time;
+use crate::handlers::indexers::utils::get_s3_script_key;
+use crate::infra::repositories::audit_repository::AuditRepository;
+use crate::infra::repositories::indexer_repository::{
+    IndexerRepository, NewIndexerDb, Repository, UpdateIndexerStatusAndExecutionRefDb,
+};
+use crate::tests::common::constants::{TEST_ADMIN_API_KEY, WEHBHOOK_URL};
+use crate::tests::common::utils::{
+    get_indexer, send_export_indexers_request, send_force_status_request, send_get_audit_logs_request,
+    send_get_indexer_state_request, send_get_metric_series_request, send_grafana_request, send_reconfigure_request,
+    send_refresh_status_request, send_script_sync_request, send_update_process_priority_request,
+};
+use crate::tests::server::common::setup_server;
+use crate::utils::csv::to_csv_line;
+use crate::utils::script_cache::{cache_script, get_script_checksum};
+
+async fn insert_indexer(status: IndexerStatus) -> IndexerModel {
+    insert_indexer_with_stream(status, None).await
+}
+
+async fn insert_indexer_with_stream(status: IndexerStatus, stream_url: Option<String>) -> IndexerModel {
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    repository
+        .insert(NewIndexerDb {
+            id: uuid::Uuid::new_v4(),
+            status: status.to_string(),
+            type_: "Webhook".to_string(),
+            target_url: Some(WEHBHOOK_URL.to_string()),
+            table_name: None,
+            status_server_port: None,
+            custom_connection_string: None,
+            starting_block: None,
+            indexer_id: None,
+            log_level: None,
+            standby_for: None,
+            script_permissions: None,
+            tenant_id: None,
+            stream_url,
+            ending_block: None,
+            backfill_for: None,
+            script_params: None,
+            script_checksum: None,
+            hooks: None,
+            sink_options: None,
+            priority: 0,
+            process_priority: None,
+            script_source_url: None,
+            project_id: None,
+            output_location: None,
+        })
+        .await
+        .unwrap()
+}
+
+#[rstest]
+#[tokio::test]
+async fn force_status_requires_admin_key(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Running).await;
+
+    let body = json!({ "status": "Stopped", "reason": "machine replaced" });
+    let response = send_force_status_request(client.clone(), indexer.id, body.clone(), None, addr).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = send_force_status_request(client.clone(), indexer.id, body, Some("wrong_key"), addr).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let indexer = get_indexer(indexer.id).await;
+    assert_eq!(indexer.status, IndexerStatus::Running);
+}
+
+#[rstest]
+#[tokio::test]
+async fn force_status_is_audited(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Running).await;
+
+    let body = json!({ "status": "Stopped", "reason": "machine replaced" });
+    let response = send_force_status_request(client.clone(), indexer.id, body, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let indexer = get_indexer(indexer.id).await;
+    assert_eq!(indexer.status, IndexerStatus::Stopped);
+
+    let config = config().await;
+    let audit_logs = AuditRepository::new(config.pool()).get_all_by_indexer(indexer.id).await.unwrap();
+    assert_eq!(audit_logs.len(), 1);
+    assert_eq!(audit_logs[0].action, AuditAction::ForceStatus);
+    assert_eq!(audit_logs[0].from_status, Some(IndexerStatus::Running));
+    assert_eq!(audit_logs[0].to_status, Some(IndexerStatus::Stopped));
+    assert_eq!(audit_logs[0].reason, Some("machine replaced".to_string()));
+}
+
+#[rstest]
+#[tokio::test]
+async fn force_status_rejects_invalid_requests(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Stopped).await;
+
+    // cannot force an indexer into running
+    let body = json!({ "status": "Running", "reason": "please" });
+    let response = send_force_status_request(client.clone(), indexer.id, body, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // reason is mandatory
+    let body = json!({ "status": "FailedStopping", "reason": " " });
+    let response = send_force_status_request(client.clone(), indexer.id, body, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let indexer = get_indexer(indexer.id).await;
+    assert_eq!(indexer.status, IndexerStatus::Stopped);
+}
+
+#[rstest]
+#[tokio::test]
+async fn audit_logs_are_paginated(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Running).await;
+
+    for (status, reason) in [("Stopped", "first"), ("FailedStopping", "second"), ("FailedRunning", "third")] {
+        let body = json!({ "status": status, "reason": reason });
+        let response =
+            send_force_status_request(client.clone(), indexer.id, body, Some(TEST_ADMIN_API_KEY), addr).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let query = format!("indexer_id={}&action=ForceStatus&limit=2", indexer.id);
+    let response = send_get_audit_logs_request(client.clone(), query.as_str(), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: AuditLogPage = serde_json::from_slice(&body).unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items[0].reason, Some("third".to_string()));
+    assert_eq!(page.items[1].reason, Some("second".to_string()));
+
+    let query = format!("indexer_id={}&limit=2&cursor={}", indexer.id, page.next_cursor.unwrap());
+    let response = send_get_audit_logs_request(client.clone(), query.as_str(), addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: AuditLogPage = serde_json::from_slice(&body).unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].reason, Some("first".to_string()));
+    assert_eq!(page.next_cursor, None);
+
+    // csv export has a header and a line per log
+    let query = format!("indexer_id={}&format=csv", indexer.id);
+    let response = send_get_audit_logs_request(client.clone(), query.as_str(), addr).await;
+    assert_eq!(response.headers()[hyper::header::CONTENT_TYPE], "text/csv");
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(String::from_utf8(body.to_vec()).unwrap().lines().count(), 4);
+}
+
+#[rstest]
+#[tokio::test]
+async fn indexer_state_is_reconstructed_from_audit_logs(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Running).await;
+
+    // nothing was recorded yet
+    let before = chrono::Utc::now();
+    let response = send_get_indexer_state_request(client.clone(), indexer.id, before, addr).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = json!({ "status": "Stopped", "reason": "machine replaced" });
+    let response = send_force_status_request(client.clone(), indexer.id, body, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = send_get_indexer_state_request(client.clone(), indexer.id, chrono::Utc::now(), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let state: IndexerStateModel = serde_json::from_slice(&body).unwrap();
+    assert_eq!(state.status, IndexerStatus::Stopped);
+    assert_eq!(state.config.unwrap().target_url, Some(WEHBHOOK_URL.to_string()));
+
+    // the past is not rewritten
+    let response = send_get_indexer_state_request(client.clone(), indexer.id, before, addr).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[rstest]
+#[tokio::test]
+async fn reconfigure_dry_run_then_apply(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let old_stream = format!("https://{}.old-dna.example.com", uuid::Uuid::new_v4());
+    let indexer = insert_indexer_with_stream(IndexerStatus::Stopped, Some(old_stream.clone())).await;
+
+    let body = json!({ "field": "stream_url", "match": "old-dna", "replace": "new-dna" });
+    let response = send_reconfigure_request(client.clone(), body, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let reconfiguration: ReconfigureModel = serde_json::from_slice(&body).unwrap();
+    assert!(reconfiguration.dry_run);
+    let change = reconfiguration.changes.iter().find(|change| change.indexer_id == indexer.id).unwrap();
+    assert_eq!(change.to, old_stream.replace("old-dna", "new-dna"));
+    // nothing is applied by a dry run
+    assert_eq!(get_indexer(indexer.id).await.stream_url, Some(old_stream.clone()));
+
+    let body = json!({ "field": "stream_url", "match": "old-dna", "replace": "new-dna", "dry_run": false });
+    let response = send_reconfigure_request(client.clone(), body, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // applied in the background
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    assert_eq!(get_indexer(indexer.id).await.stream_url, Some(old_stream.replace("old-dna", "new-dna")));
+}
+
+#[rstest]
+#[tokio::test]
+async fn update_process_priority_of_stopped_indexer(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Stopped).await;
+
+    let body = json!({ "nice": 10, "io_class": "idle" });
+    let response = send_update_process_priority_request(client.clone(), indexer.id, body, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        get_indexer(indexer.id).await.process_priority,
+        ProcessPriority { nice: Some(10), io_class: Some(IoClass::Idle), io_level: None }
+    );
+
+    let body = json!({ "nice": 42 });
+    let response = send_update_process_priority_request(client, indexer.id, body, addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn script_sync_reports_and_fixes_issues(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let config = config().await;
+    let indexer = insert_indexer(IndexerStatus::Stopped).await;
+    let orphan_key = get_s3_script_key(uuid::Uuid::new_v4());
+    config.object_store().put(&Path::from(orphan_key.as_str()), "orphan".into()).await.unwrap();
+
+    let response = send_script_sync_request(client.clone(), "", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: ScriptSyncReport = serde_json::from_slice(&body).unwrap();
+    let entry = report.issues.iter().find(|entry| entry.indexer_id == Some(indexer.id)).unwrap();
+    assert_eq!(entry.issue, ScriptSyncIssue::Missing);
+    let entry = report.issues.iter().find(|entry| entry.key == orphan_key).unwrap();
+    assert_eq!(entry.issue, ScriptSyncIssue::Orphaned);
+
+    let script = "export default function transform() {}";
+    config.object_store().put(&Path::from(get_s3_script_key(indexer.id)), script.into()).await.unwrap();
+    let response = send_script_sync_request(client.clone(), "fix=true", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: ScriptSyncReport = serde_json::from_slice(&body).unwrap();
+    let entry = report.issues.iter().find(|entry| entry.indexer_id == Some(indexer.id)).unwrap();
+    assert_eq!(entry.issue, ScriptSyncIssue::ChecksumNotRecorded);
+    assert_eq!(entry.fix, Some(ScriptSyncFix::RecordedChecksum));
+    let checksum = get_script_checksum(script.as_bytes());
+    assert_eq!(get_indexer(indexer.id).await.script_checksum, Some(checksum.clone()));
+
+    // the cached copy could be the stale one, a script which changed in the store is kept
+    cache_script(&checksum, script.as_bytes()).await.unwrap();
+    let changed_script = "export default function transform() { return []; }";
+    config.object_store().put(&Path::from(get_s3_script_key(indexer.id)), changed_script.into()).await.unwrap();
+    let response = send_script_sync_request(client.clone(), "fix=true", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: ScriptSyncReport = serde_json::from_slice(&body).unwrap();
+    let entry = report.issues.iter().find(|entry| entry.indexer_id == Some(indexer.id)).unwrap();
+    assert_eq!(entry.issue, ScriptSyncIssue::ChecksumMismatch);
+    assert_eq!(entry.fix, None);
+    let stored = config.object_store().get(&Path::from(get_s3_script_key(indexer.id))).await.unwrap();
+    assert_eq!(stored.bytes().await.unwrap(), changed_script.as_bytes());
+
+    // a missing script is restored from the cache, which verifies the checksum
+    config.object_store().delete(&Path::from(get_s3_script_key(indexer.id))).await.unwrap();
+    let response = send_script_sync_request(client, "fix=true", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: ScriptSyncReport = serde_json::from_slice(&body).unwrap();
+    let entry = report.issues.iter().find(|entry| entry.indexer_id == Some(indexer.id)).unwrap();
+    assert_eq!(entry.issue, ScriptSyncIssue::Missing);
+    assert_eq!(entry.fix, Some(ScriptSyncFix::RestoredFromCache));
+}
+
+#[rstest]
+#[tokio::test]
+async fn inventory_export_as_csv(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let indexer = insert_indexer(IndexerStatus::Stopped).await;
+
+    let response = send_export_indexers_request(client, "format=csv", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[hyper::header::CONTENT_TYPE], "text/csv");
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    assert!(csv.starts_with(to_csv_line(INVENTORY_COLUMNS).as_str()));
+    assert!(csv.lines().any(|line| line.starts_with(&format!("{},Stopped,", indexer.id))));
+}
+
+#[cfg(feature = "parquet")]
+#[rstest]
+#[tokio::test]
+async fn inventory_export_as_parquet(#[future] setup_server: SocketAddr) {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    insert_indexer(IndexerStatus::Stopped).await;
+
+    let response = send_export_indexers_request(client, "format=parquet", addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[hyper::header::CONTENT_TYPE], "application/vnd.apache.parquet");
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(body).unwrap();
+    let names: Vec<String> = reader.schema().fields().iter().map(|field| field.name().clone()).collect();
+    assert_eq!(names, INVENTORY_COLUMNS);
+    let rows: usize = reader.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum();
+    assert!(rows >= 1);
+}
+
+#[cfg(not(feature = "parquet"))]
+#[rstest]
+#[tokio::test]
+async fn inventory_export_as_parquet_requires_the_feature(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let response = send_export_indexers_request(client, "format=parquet", addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn refresh_status_corrects_statuses(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let config = config().await;
+    let mut repository = IndexerRepository::new(config.pool());
+    // the process of the tests stands in for the sinks, the start time tells a reused pid apart
+    let pid = std::process::id();
+    let mut indexers = vec![];
+    for (status, start_time) in [
+        (IndexerStatus::Running, Some(0)),
+        (IndexerStatus::FailedStopping, get_process_start_time(pid)),
+        (IndexerStatus::Running, get_process_start_time(pid)),
+    ] {
+        let indexer = insert_indexer(IndexerStatus::Created).await;
+        let execution_ref = ExecutionRef::Pid { pid, start_time };
+        repository
+            .update_status_and_execution_ref(UpdateIndexerStatusAndExecutionRefDb {
+                id: indexer.id,
+                status: status.to_string(),
+                execution_ref: serde_json::to_value(execution_ref).unwrap(),
+                launch_config: None,
+            })
+            .await
+            .unwrap();
+        indexers.push(indexer.id);
+    }
+
+    let response = send_refresh_status_request(client, json!({ "ids": indexers }), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: StatusRefreshReport = serde_json::from_slice(&body).unwrap();
+    assert_eq!(report.checked, 3);
+    assert!(report.failures.is_empty());
+    assert_eq!(
+        report.corrections,
+        vec![
+            StatusCorrection {
+                indexer_id: indexers[0],
+                from_status: IndexerStatus::Running,
+                to_status: IndexerStatus::FailedRunning,
+            },
+            StatusCorrection {
+                indexer_id: indexers[1],
+                from_status: IndexerStatus::FailedStopping,
+                to_status: IndexerStatus::Running,
+            },
+        ]
+    );
+    assert_eq!(get_indexer(indexers[0]).await.status, IndexerStatus::FailedRunning);
+    assert_eq!(get_indexer(indexers[1]).await.status, IndexerStatus::Running);
+    assert_eq!(get_indexer(indexers[2]).await.status, IndexerStatus::Running);
+}
+
+#[rstest]
+#[tokio::test]
+async fn grafana_query_counts_indexers(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    insert_indexer(IndexerStatus::Running).await;
+    let range = json!({ "from": "2025-09-01T00:00:00Z", "to": "2025-09-02T00:00:00Z" });
+
+    let response = send_grafana_request(
+        client.clone(),
+        "query",
+        json!({
+            "range": range,
+            "targets": [{ "target": "indexers.Running", "refId": "A" }, { "target": "", "refId": "B" }],
+        }),
+        addr,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let series: Vec<GrafanaSeries> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].ref_id.as_deref(), Some("A"));
+    // the count is charted at the end of the range
+    let (count, at) = series[0].datapoints[0];
+    assert!(count >= 1.0);
+    assert_eq!(at, 1756771200000);
+
+    let response = send_grafana_request(
+        client,
+        "query",
+        json!({ "range": range, "targets": [{ "target": "indexers.Unknown" }] }),
+        addr,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_metric_series(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    insert_indexer(IndexerStatus::Running).await;
+
+    let response = send_get_metric_series_request(client, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: MetricSeriesReport = serde_json::from_slice(&body).unwrap();
+    // the tests don't split the metrics by a label
+    assert_eq!(report.label, None);
+    assert_eq!(report.series_per_metric, 1);
+    assert_eq!(report.total_series, GrafanaMetric::all().len());
+}
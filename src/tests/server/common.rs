@@ -3,29 +3,55 @@ use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
 
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use hyper::{Body, Request};
 use mpart_async::client::MultipartRequest;
 use rstest::{fixture, rstest};
 use tokio::process::Command;
 
 use crate::config::{config, config_force_init};
-use crate::domain::models::indexer::{IndexerModel, IndexerStatus};
+use crate::domain::models::actor::ActorContext;
+use crate::domain::models::capabilities::CapabilitiesModel;
+use crate::domain::models::cleanup::CleanupPlanModel;
+use crate::domain::models::creation::{CreationStep, PartialCreationModel};
+use crate::domain::models::indexer::{IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::runtime::{ReadinessModel, StartupMode};
+use crate::domain::models::search::BlockSearchModel;
+use crate::domain::models::start_token::MintedStartTokenModel;
+use crate::domain::models::tenant::TenantKeyModel;
 use crate::domain::models::types::AxumErrorResponse;
+use crate::domain::models::upload::{UploadError, UploadSession, UploadStatus};
+use crate::domain::models::version::VersionModel;
 use crate::handlers::indexers::fail_indexer::fail_indexer;
+use crate::handlers::indexers::logs::record_sink_log;
+use crate::handlers::indexers::utils::get_s3_script_key;
+use crate::handlers::uploads::sessions::get_completed_upload;
+use crate::infra::repositories::indexer_repository::{
+    IndexerRepository, Repository, UpdateIndexerPriorityDb, UpdateIndexerStatusDb,
+};
+use crate::infra::repositories::start_token_repository::StartTokenRepository;
+use crate::infra::repositories::upload_repository::UploadRepository;
 use crate::routes::app_router;
-use crate::tests::common::constants::{BROKEN_APIBARA_SCRIPT, WEHBHOOK_URL, WORKING_APIBARA_SCRIPT};
+use crate::tests::common::constants::{
+    BROKEN_APIBARA_SCRIPT, TEST_ADMIN_API_KEY, TEST_TENANT_KEY_SECRET, WEHBHOOK_URL, WORKING_APIBARA_SCRIPT,
+};
 use crate::tests::common::utils::{
-    get_indexer, get_indexers, is_process_running, send_create_indexer_request, send_create_webhook_indexer_request,
-    send_delete_indexer_request, send_start_indexer_request, send_stop_indexer_request,
+    assert_store_contains_key, get_indexer, get_indexers, is_process_running, send_create_indexer_request,
+    send_create_start_token_request, send_create_webhook_indexer_request, send_delete_indexer_request,
+    send_redeem_start_token_request, send_start_indexer_request, send_stop_indexer_request,
 };
+use crate::utils::actor_context::{CORRELATION_ID_HEADER, TENANT_ID_HEADER, TENANT_KEY_HEADER};
+use crate::utils::custom_extractors::admin_extractor::ADMIN_API_KEY_HEADER;
+use crate::utils::http::init_http_client;
+use crate::utils::negotiation::MESSAGE_PACK_CONTENT_TYPE;
+use crate::utils::signing::get_tenant_key;
 use crate::AppState;
 
 #[fixture]
 pub async fn setup_server() -> SocketAddr {
     config_force_init().await;
     let config = config().await;
-    let state = AppState { pool: Arc::clone(config.pool()) };
+    let state = AppState { pool: Arc::clone(config.pool()), http: init_http_client(config.outbound_http()) };
     let app = app_router(state.clone()).with_state(state);
 
     let listener = TcpListener::bind("0.0.0.0:0".parse::<SocketAddr>().unwrap()).unwrap();
@@ -55,6 +81,27 @@ async fn not_found(#[future] setup_server: SocketAddr) {
     assert_eq!(&body[..], b"The requested resource was not found");
 }
 
+#[rstest]
+#[tokio::test]
+async fn internal_routes_not_public(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/internal/preview/{}", addr, uuid::Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 #[rstest]
 #[tokio::test]
 async fn health(#[future] setup_server: SocketAddr) {
@@ -73,6 +120,195 @@ async fn health(#[future] setup_server: SocketAddr) {
     assert!(body.is_empty());
 }
 
+#[rstest]
+#[tokio::test]
+async fn ready(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/ready", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let readiness: ReadinessModel = serde_json::from_slice(&body).unwrap();
+    assert!(readiness.ready);
+    assert!(readiness.schedulable);
+}
+
+#[rstest]
+#[tokio::test]
+async fn version(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/version", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let version: VersionModel = serde_json::from_slice(&body).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        version.indexer_types,
+        vec![IndexerType::Webhook, IndexerType::Postgres, IndexerType::Parquet, IndexerType::Console]
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn event_schema(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/schemas/events/v1", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(schema["$id"], "/v1/schemas/events/v1");
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/schemas/events/v0", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[rstest]
+#[tokio::test]
+async fn capabilities(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/capabilities", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let capabilities: CapabilitiesModel = serde_json::from_slice(&body).unwrap();
+    assert_eq!(capabilities.execution_backends, vec!["process".to_string()]);
+    // the test config sets an admin key
+    assert!(capabilities.subsystems.admin);
+    assert!(!capabilities.subsystems.multiplexer);
+    // the tests resolve references from their environment
+    assert!(capabilities.subsystems.secrets);
+    assert_eq!(capabilities.startup_mode, StartupMode::Strict);
+    assert!(capabilities.startup_compromises.is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn search_block(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let deliveries_request = |admin_api_key: Option<&str>| {
+        let mut request = Request::builder()
+            .method(axum::http::Method::POST)
+            .uri(format!("http://{}/v1/indexers/{}/deliveries", addr, indexer.id))
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(admin_api_key) = admin_api_key {
+            request = request.header(ADMIN_API_KEY_HEADER, admin_api_key);
+        }
+        request.body(Body::from(r#"{"start_block":900100,"end_block":900200}"#)).unwrap()
+    };
+    // indexers of no tenant only take the reports of the admins
+    let response = client.request(deliveries_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    // reporting the same range again is a no-op
+    for _ in 0..2 {
+        let response = client.request(deliveries_request(Some(TEST_ADMIN_API_KEY))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    record_sink_log(indexer.id, r#"{"level":"warn","message":"retrying","block_number":900150}"#);
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/search?block=900150", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let search: BlockSearchModel = serde_json::from_slice(&body).unwrap();
+    assert!(search.indexers.contains(&indexer.id));
+    assert!(search.logs.iter().any(|log| log.indexer_id == indexer.id && log.record.message == "retrying"));
+}
+
+#[rstest]
+#[tokio::test]
+async fn list_indexers_as_message_pack(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/indexers/indexers", addr))
+                .header(header::ACCEPT, MESSAGE_PACK_CONTENT_TYPE)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], MESSAGE_PACK_CONTENT_TYPE);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    // other tests create indexers concurrently, only the decoding is checked
+    rmp_serde::from_slice::<Vec<IndexerModel>>(&body).unwrap();
+}
+
+#[rstest]
+#[tokio::test]
+async fn correlation_id_is_returned(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/health", addr))
+                .header(CORRELATION_ID_HEADER, "deploy-42")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers().get(CORRELATION_ID_HEADER).unwrap(), "deploy-42");
+
+    // a new id is generated when the caller doesn't send one
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/health", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(response.headers().get(CORRELATION_ID_HEADER).is_some());
+}
+
 #[rstest]
 #[tokio::test]
 async fn create_indexer_fails_no_script(#[future] setup_server: SocketAddr) {
@@ -87,10 +323,10 @@ async fn create_indexer_fails_no_script(#[future] setup_server: SocketAddr) {
     mpart.add_field("target_url", WEHBHOOK_URL);
     let response = send_create_indexer_request(client.clone(), mpart, addr).await;
 
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
     let body: AxumErrorResponse = serde_json::from_slice(&body).unwrap();
-    assert_eq!(body.message, "Internal server error: failed to build create indexer request")
+    assert_eq!(body.message, "Bad request: missing fields: script.js")
 }
 
 #[rstest]
@@ -116,7 +352,7 @@ async fn start_indexer(#[future] setup_server: SocketAddr) {
     assert_eq!(indexer.status, IndexerStatus::Running);
 
     // check the process is actually up
-    assert!(is_process_running(indexer.process_id.unwrap()).await,);
+    assert!(is_process_running(indexer.execution_ref.as_ref().unwrap()).await,);
 }
 
 #[rstest]
@@ -142,7 +378,7 @@ async fn start_two_indexers(#[future] setup_server: SocketAddr) {
     assert_eq!(indexer.status, IndexerStatus::Running);
 
     // check the process is actually up
-    assert!(is_process_running(indexer.process_id.unwrap()).await);
+    assert!(is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
 
     // Create another indexer
     let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
@@ -159,7 +395,7 @@ async fn start_two_indexers(#[future] setup_server: SocketAddr) {
     assert_eq!(indexer.status, IndexerStatus::Running);
 
     // check the process is actually up
-    assert!(is_process_running(indexer.process_id.unwrap()).await);
+    assert!(is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
 
     let indexers = get_indexers().await;
     assert_eq!(indexers.len(), 2);
@@ -186,7 +422,7 @@ async fn failed_running_indexer(#[future] setup_server: SocketAddr) {
     tokio::time::sleep(Duration::from_secs(2)).await;
 
     // fail the indexer
-    assert!(fail_indexer(body.id).await.is_ok());
+    assert!(fail_indexer(&ActorContext::system(), body.id).await.is_ok());
 
     // check indexer is present in DB in failed running state state
     let indexer = get_indexer(body.id).await;
@@ -194,7 +430,162 @@ async fn failed_running_indexer(#[future] setup_server: SocketAddr) {
     assert_eq!(indexer.status, IndexerStatus::FailedRunning);
 
     // check the process has exited
-    assert!(!is_process_running(indexer.process_id.unwrap()).await);
+    assert!(!is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_indexer_with_token(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    // minting requires the admin key
+    let response = send_create_start_token_request(client.clone(), indexer.id, None, addr).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = send_create_start_token_request(client.clone(), indexer.id, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let minted: MintedStartTokenModel = serde_json::from_slice(&body).unwrap();
+    assert_eq!(minted.start_token.indexer_id, indexer.id);
+    assert!(minted.start_token.redeemed_at.is_none());
+
+    let response = send_redeem_start_token_request(client.clone(), indexer.id, "not-a-token", addr).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = send_redeem_start_token_request(client.clone(), indexer.id, &minted.token, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(get_indexer(indexer.id).await.status.is_live());
+
+    // tokens are single use
+    let response = send_redeem_start_token_request(client.clone(), indexer.id, &minted.token, addr).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    send_stop_indexer_request(client.clone(), indexer.id, addr).await;
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_token_is_kept_when_redeemed_for_a_standby(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+    let response = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/v1/indexers/{}/standby", addr, indexer.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let standby: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let response = send_create_start_token_request(client.clone(), standby.id, Some(TEST_ADMIN_API_KEY), addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let minted: MintedStartTokenModel = serde_json::from_slice(&body).unwrap();
+
+    let response = send_redeem_start_token_request(client.clone(), standby.id, &minted.token, addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let start_tokens = StartTokenRepository::new(config().await.pool()).get_all_by_indexer(standby.id).await.unwrap();
+    assert_eq!(start_tokens.len(), 1);
+    assert!(start_tokens[0].redeemed_at.is_none());
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_indexer_reports_failed_start(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // the pre-start hook can't be reached and aborts the start
+    let hooks = r#"{"pre_start":{"action":{"type":"http","url":"http://127.0.0.1:1/hook"},"failure_policy":"abort"}}"#;
+    let mut mpart = MultipartRequest::default();
+    mpart.add_file("script.js", WORKING_APIBARA_SCRIPT);
+    mpart.add_field("target_url", WEHBHOOK_URL);
+    mpart.add_field("indexer_type", IndexerType::Webhook.to_string().as_str());
+    mpart.add_field("hooks", hooks);
+    let response = send_create_indexer_request(client.clone(), mpart, addr).await;
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let report: PartialCreationModel = serde_json::from_slice(&body).unwrap();
+    let steps: Vec<(CreationStep, bool)> = report.steps.iter().map(|step| (step.step, step.completed)).collect();
+    assert_eq!(
+        steps,
+        vec![(CreationStep::Recorded, true), (CreationStep::ScriptStored, true), (CreationStep::Started, false)]
+    );
+    assert_store_contains_key(&get_s3_script_key(report.indexer.id)).await;
+
+    // the hook failed before the start, the indexer was never started and can be retried
+    let response = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/v1/indexers/{}/create/complete", addr, report.indexer.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    assert_eq!(get_indexer(report.indexer.id).await.status, IndexerStatus::Created);
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_indexer_not_modified(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let get = |etag: Option<HeaderValue>| {
+        let mut request = Request::builder().uri(format!("http://{}/v1/indexers/{}", addr, indexer.id));
+        if let Some(etag) = etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        client.request(request.body(Body::empty()).unwrap())
+    };
+    let response = get(None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[header::ETAG].clone();
+
+    let response = get(Some(etag.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag);
+
+    // any change of the indexer changes its tag
+    let config = config().await;
+    IndexerRepository::new(config.pool())
+        .update_priority(UpdateIndexerPriorityDb { id: indexer.id, priority: 5 })
+        .await
+        .unwrap();
+    let response = get(Some(etag.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()[header::ETAG], etag);
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/indexers/indexers", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(response.headers().contains_key(header::ETAG));
 }
 
 // Ignoring this test case as it's flaky. Works locally fails on github actions.
@@ -226,6 +617,39 @@ async fn stop_indexer(#[future] setup_server: SocketAddr) {
     assert_eq!(indexer.status, IndexerStatus::Stopped);
 }
 
+#[rstest]
+#[tokio::test]
+async fn stop_indexer_of_previous_execution(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    send_start_indexer_request(client.clone(), body.id, addr).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let execution_seq = get_indexer(body.id).await.execution_seq;
+
+    // a stop decided before the last start is dropped
+    let response = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/v1/indexers/stop/{}?execution_seq={}", addr, body.id, execution_seq - 1))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(get_indexer(body.id).await.status, IndexerStatus::Running);
+
+    send_stop_indexer_request(client.clone(), body.id, addr).await;
+    assert_eq!(get_indexer(body.id).await.status, IndexerStatus::Stopped);
+}
+
 // Ignoring this test case as it's flaky. Works locally fails on github actions.
 #[rstest]
 #[tokio::test]
@@ -255,7 +679,7 @@ async fn failed_stop_indexer(#[future] setup_server: SocketAddr) {
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .args([
-                indexer.process_id.unwrap().to_string().as_str(),
+                indexer.execution_ref.unwrap().pid().unwrap().to_string().as_str(),
             ])
             .spawn()
             .expect("Could not stop the webhook indexer")
@@ -357,6 +781,44 @@ async fn delete_indexer_test_works_only_when_stopped(#[future] setup_server: Soc
     assert_eq!(indexers.len(), 0);
 }
 
+#[rstest]
+#[tokio::test]
+async fn test_delete_indexer_dry_run(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+    send_stop_indexer_request(client.clone(), body.id, addr).await;
+
+    let send_delete = |query: &'static str| {
+        client.request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{}/v1/indexers/{}{}", addr, body.id, query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    let response = send_delete("?dry_run=true").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let plan: CleanupPlanModel = serde_json::from_slice(&response_body).unwrap();
+    let script_key = get_s3_script_key(body.id);
+    assert_eq!(plan.objects, vec![script_key.clone()]);
+    // nothing was removed
+    assert_eq!(get_indexers().await.len(), 1);
+    assert_store_contains_key(&script_key).await;
+
+    let response = send_delete("").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(get_indexers().await.len(), 0);
+    let config = config().await;
+    assert!(config.object_store().head(&object_store::path::Path::from(script_key)).await.is_err());
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_delete_indexer_fail_if_not_stopped(#[future] setup_server: SocketAddr) {
@@ -391,3 +853,432 @@ async fn test_delete_indexer_fail_if_not_stopped(#[future] setup_server: SocketA
     assert_eq!(indexer.id, body.id);
     assert_eq!(indexer.status, IndexerStatus::Running);
 }
+
+#[rstest]
+#[tokio::test]
+async fn test_force_delete_running_indexer(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // Create indexer
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    // start the indexer
+    send_start_indexer_request(client.clone(), body.id, addr).await;
+    let indexer = get_indexer(body.id).await;
+    assert_eq!(indexer.status, IndexerStatus::Running);
+
+    // delete the indexer, stopping it first
+    let response = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{}/v1/indexers/{}?force=true", addr, body.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // check the process, the indexer and its script are gone
+    assert!(!is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
+    assert_eq!(get_indexers().await.len(), 0);
+    let config = config().await;
+    let script_key = get_s3_script_key(body.id);
+    assert!(config.object_store().head(&object_store::path::Path::from(script_key)).await.is_err());
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_force_delete_failed_stopping_indexer(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // Create indexer
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    // start the indexer and mark it as failed to stop while its process still runs
+    send_start_indexer_request(client.clone(), body.id, addr).await;
+    let config = config().await;
+    IndexerRepository::new(config.pool())
+        .update_status(UpdateIndexerStatusDb { id: body.id, status: IndexerStatus::FailedStopping.to_string() })
+        .await
+        .unwrap();
+    let indexer = get_indexer(body.id).await;
+    assert!(is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
+
+    // the delete is refused without force
+    let response = send_delete_indexer_request(client.clone(), body.id, addr).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    // the process is stopped along with the delete
+    let response = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{}/v1/indexers/{}?force=true", addr, body.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(!is_process_running(indexer.execution_ref.as_ref().unwrap()).await);
+    assert_eq!(get_indexers().await.len(), 0);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_create_indexer_for_another_tenant_is_forbidden(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let mut mpart = MultipartRequest::default();
+    mpart.add_file("script.js", WORKING_APIBARA_SCRIPT);
+    mpart.add_field("target_url", WEHBHOOK_URL);
+    mpart.add_field("indexer_type", IndexerType::Webhook.to_string().as_str());
+    mpart.add_field("tenant_id", "acme");
+    let response = send_create_indexer_request(client.clone(), mpart, addr).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(get_indexers().await.is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_preview_indexer_requires_access(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // indexers of no tenant can only be previewed by the admins
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/v1/indexers/{}/preview", addr, body.id))
+                .header(TENANT_ID_HEADER, "acme")
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, "acme"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_complete_create_indexer_requires_access(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // indexers of no tenant can only be completed by the admins
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/v1/indexers/{}/create/complete", addr, body.id))
+                .header(TENANT_ID_HEADER, "acme")
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, "acme"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_create_standby_requires_access(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // indexers of no tenant can only be replicated by the admins
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/v1/indexers/{}/standby", addr, body.id))
+                .header(TENANT_ID_HEADER, "acme")
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, "acme"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let standby = IndexerRepository::new(config().await.pool()).get_standby(body.id).await.unwrap();
+    assert!(standby.is_none());
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_indexer_diagnostics_require_access(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    // indexers of no tenant are left to the admins
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/indexers/{}/diagnostics/0", addr, body.id))
+                .header(TENANT_ID_HEADER, "acme")
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, "acme"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // the admins get through to the snapshots, there is none at this time
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/indexers/{}/diagnostics/0", addr, body.id))
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_create_indexer_with_stream_not_allowed(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let mut mpart = MultipartRequest::default();
+    mpart.add_file("script.js", WORKING_APIBARA_SCRIPT);
+    mpart.add_field("target_url", WEHBHOOK_URL);
+    mpart.add_field("indexer_type", IndexerType::Webhook.to_string().as_str());
+    mpart.add_field("stream_url", "http://169.254.169.254/latest");
+    let response = send_create_indexer_request(client.clone(), mpart, addr).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_tenant_settings_require_access(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/tenants/globex/settings", addr))
+                .header(TENANT_ID_HEADER, "acme")
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, "acme"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_tenant_requires_its_key(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let request = |key: Option<String>| {
+        let mut request = Request::builder()
+            .uri(format!("http://{}/v1/tenants/acme/settings", addr))
+            .header(TENANT_ID_HEADER, "acme");
+        if let Some(key) = key {
+            request = request.header(TENANT_KEY_HEADER, key);
+        }
+        client.request(request.body(Body::empty()).unwrap())
+    };
+    // the id alone or with the key of another tenant proves nothing
+    assert_eq!(request(None).await.unwrap().status(), StatusCode::FORBIDDEN);
+    let key = get_tenant_key(TEST_TENANT_KEY_SECRET, "globex");
+    assert_eq!(request(Some(key)).await.unwrap().status(), StatusCode::FORBIDDEN);
+
+    // the key is handed out by the admins
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/tenants/acme/key", addr))
+                .header(ADMIN_API_KEY_HEADER, TEST_ADMIN_API_KEY)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let tenant_key: TenantKeyModel = serde_json::from_slice(&body).unwrap();
+    let response = request(Some(tenant_key.key)).await.unwrap();
+    assert_ne!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client
+        .request(Request::builder().uri(format!("http://{}/v1/tenants/acme/key", addr)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_tenant_quota_requires_the_tenant(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let request = |tenant_id: &str| {
+        client.request(
+            Request::builder()
+                .uri(format!("http://{}/v1/tenants/acme/quota", addr))
+                .header(TENANT_ID_HEADER, tenant_id)
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, tenant_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    assert_eq!(request("globex").await.unwrap().status(), StatusCode::FORBIDDEN);
+    assert_eq!(request("acme").await.unwrap().status(), StatusCode::OK);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_estimate_requires_tenant_or_admin(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri(format!("http://{}/v1/indexers/estimate", addr))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"starting_block":0,"ending_block":100}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_concurrent_standbys_conflict(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let standby_request = || {
+        client.request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/v1/indexers/{}/standby", addr, indexer.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    let (first, second) = tokio::join!(standby_request(), standby_request());
+    let mut statuses = vec![first.unwrap().status(), second.unwrap().status()];
+    statuses.sort();
+    assert_eq!(statuses, vec![StatusCode::OK, StatusCode::CONFLICT]);
+
+    let standby = IndexerRepository::new(config().await.pool()).get_standby(indexer.id).await.unwrap();
+    assert!(standby.is_some());
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_upload_sessions_belong_to_their_tenant(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let request = |method: &str, path: String, tenant_id: Option<&str>, body: Body| {
+        let mut request = Request::builder().method(method).uri(format!("http://{}/v1/uploads{}", addr, path));
+        if let Some(tenant_id) = tenant_id {
+            request = request
+                .header(TENANT_ID_HEADER, tenant_id)
+                .header(TENANT_KEY_HEADER, get_tenant_key(TEST_TENANT_KEY_SECRET, tenant_id));
+        }
+        client.request(request.header(header::CONTENT_TYPE, "application/json").body(body).unwrap())
+    };
+
+    // sessions need an owner
+    let response = request("POST", "/".into(), None, Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = request("POST", "/".into(), Some("acme"), Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let session: UploadSession = serde_json::from_slice(&body).unwrap();
+    assert_eq!(session.tenant_id.as_deref(), Some("acme"));
+
+    let response =
+        request("PUT", format!("/{}/parts/1", session.id), Some("acme"), Body::from("console.log(1)")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = request("GET", format!("/{}", session.id), Some("globex"), Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response =
+        request("PUT", format!("/{}/parts/2", session.id), Some("globex"), Body::from("console.log(2)")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // the upload can't be used before its session is completed
+    let context = ActorContext { tenant_id: Some("acme".into()), ..Default::default() };
+    let upload = get_completed_upload(&context, session.id).await;
+    assert!(matches!(upload, Err(UploadError::UploadNotComplete(id)) if id == session.id));
+
+    let response =
+        request("POST", format!("/{}/complete", session.id), Some("acme"), Body::from(r#"{"total_parts":1}"#))
+            .await
+            .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the session survives the process, it's read back from the database
+    let stored = UploadRepository::new(config().await.pool()).get(session.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, UploadStatus::Completed);
+    assert_eq!(stored.parts.len(), 1);
+    assert_eq!(get_completed_upload(&context, session.id).await.unwrap(), "console.log(1)");
+}
@@ -1,3 +1,6 @@
+mod admin;
 pub mod common;
+mod notifications;
 mod postgres;
+mod v2;
 mod webhook;
@@ -0,0 +1,99 @@
+use std::net::SocketAddr;
+
+use axum::http;
+use hyper::{Body, Request, StatusCode};
+use rstest::rstest;
+use serde_json::json;
+
+use crate::config::config;
+use crate::domain::models::notification::VerifySignatureResponse;
+use crate::tests::common::constants::{TEST_ADMIN_API_KEY, TEST_SIGNING_KEY_ID};
+use crate::tests::server::common::setup_server;
+use crate::utils::custom_extractors::admin_extractor::ADMIN_API_KEY_HEADER;
+use crate::utils::signing::sign_payload;
+
+#[rstest]
+#[tokio::test]
+async fn signing_keys_do_not_expose_secrets(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/v1/notifications/signing-keys", addr))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, json!([{ "id": TEST_SIGNING_KEY_ID, "valid_from": null, "valid_until": null }]));
+}
+
+#[rstest]
+#[tokio::test]
+async fn verify_signature(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    let config = config().await;
+    let signature = sign_payload(config.signing_keys(), b"{\"hello\":\"world\"}", chrono::Utc::now()).unwrap();
+
+    for (payload, expected) in [("{\"hello\":\"world\"}", true), ("{\"hello\":\"there\"}", false)] {
+        let response = client
+            .request(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .uri(format!("http://{}/v1/notifications/verify", addr))
+                    .body(Body::from(json!({ "payload": payload, "signature": signature }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: VerifySignatureResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body.valid, expected);
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn alert_rules_require_the_admin_key(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let request = |method: http::Method, api_key: Option<&str>, body: Body| {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("http://{}/v1/alert-rules", addr))
+            .header(http::header::CONTENT_TYPE, "application/json");
+        if let Some(api_key) = api_key {
+            request = request.header(ADMIN_API_KEY_HEADER, api_key);
+        }
+        client.request(request.body(body).unwrap())
+    };
+    let rule = json!({
+        "name": "failed",
+        "condition": { "metric": "status", "status": "FailedRunning" },
+        "channels": [{ "kind": "notification_webhook" }],
+    });
+
+    let response = request(http::Method::GET, None, Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = request(http::Method::POST, None, Body::from(rule.to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = request(http::Method::POST, Some(TEST_ADMIN_API_KEY), Body::from(rule.to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = request(http::Method::GET, Some(TEST_ADMIN_API_KEY), Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,60 @@
+use std::net::SocketAddr;
+
+use hyper::{Body, Request, StatusCode};
+use rstest::rstest;
+
+use crate::domain::models::envelope::{Envelope, ErrorEnvelope};
+use crate::domain::models::indexer::IndexerModel;
+use crate::tests::common::constants::WORKING_APIBARA_SCRIPT;
+use crate::tests::common::utils::send_create_webhook_indexer_request;
+use crate::tests::server::common::setup_server;
+
+async fn get(addr: SocketAddr, path: &str) -> (StatusCode, hyper::body::Bytes) {
+    let response = hyper::Client::new()
+        .request(Request::builder().uri(format!("http://{}{}", addr, path)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    (status, hyper::body::to_bytes(response.into_body()).await.unwrap())
+}
+
+#[rstest]
+#[tokio::test]
+async fn list_indexers_is_paginated(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+    for _ in 0..2 {
+        let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let (status, body) = get(addr, "/v2/indexers?limit=1").await;
+    assert_eq!(status, StatusCode::OK);
+    let first_page: Envelope<Vec<IndexerModel>> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(first_page.data.len(), 1);
+    let page = first_page.page.unwrap();
+    assert_eq!(page.limit, 1);
+    let cursor = page.next_cursor.unwrap();
+
+    let (status, body) = get(addr, &format!("/v2/indexers?limit=1&cursor={}", cursor)).await;
+    assert_eq!(status, StatusCode::OK);
+    let second_page: Envelope<Vec<IndexerModel>> = serde_json::from_slice(&body).unwrap();
+    assert!(second_page.data[0].id > first_page.data[0].id);
+
+    let (status, body) = get(addr, &format!("/v2/indexers/{}", first_page.data[0].id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let indexer: Envelope<IndexerModel> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(indexer.data.id, first_page.data[0].id);
+}
+
+#[rstest]
+#[tokio::test]
+async fn errors_have_a_code(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let (status, body) = get(addr, "/v2/indexers/not-a-uuid").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: ErrorEnvelope = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.error.code, "BAD_REQUEST");
+}
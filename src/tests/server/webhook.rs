@@ -1,16 +1,22 @@
-use std::net::SocketAddr;
+use std::net::{SocketAddr, TcpListener};
 
-use hyper::StatusCode;
+use axum::http::{HeaderMap, Method};
+use axum::routing::post;
+use axum::Router;
+use hyper::{Body, Request, StatusCode};
 use mpart_async::client::MultipartRequest;
 use rstest::rstest;
 
 use crate::config::config;
-use crate::domain::models::indexer::{IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::indexer::{IndexerLogLevel, IndexerModel, IndexerStatus, IndexerType};
+use crate::domain::models::simulation::SimulatedDeliveryModel;
 use crate::domain::models::types::AxumErrorResponse;
+use crate::handlers::indexers::simulate_delivery::SIMULATION_HEADER;
 use crate::handlers::indexers::utils::get_s3_script_key;
 use crate::tests::common::constants::{WEHBHOOK_URL, WORKING_APIBARA_SCRIPT};
 use crate::tests::common::utils::{
     assert_store_contains_key, get_indexer, send_create_indexer_request, send_create_webhook_indexer_request,
+    send_stop_indexer_request,
 };
 use crate::tests::server::common::setup_server;
 
@@ -56,8 +62,115 @@ async fn create_webhook_indexer_fails_no_target_url(#[future] setup_server: Sock
     mpart.add_field("indexer_type", "Webhook");
     let response = send_create_indexer_request(client.clone(), mpart, addr).await;
 
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
     let body: AxumErrorResponse = serde_json::from_slice(&body).unwrap();
-    assert_eq!(body.message, "Internal server error: failed to build create indexer request")
+    assert_eq!(body.message, "Bad request: missing fields: target_url")
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_webhook_indexer_fails_invalid_target_url(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // Create indexer
+    let mut mpart = MultipartRequest::default();
+
+    mpart.add_file("script.js", WORKING_APIBARA_SCRIPT);
+    mpart.add_field("indexer_type", "Webhook");
+    mpart.add_field("target_url", "ftp://example.com");
+    let response = send_create_indexer_request(client.clone(), mpart, addr).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: AxumErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body.message, "Bad request: invalid target url ftp://example.com: unsupported scheme")
+}
+
+#[rstest]
+#[tokio::test]
+async fn update_webhook_indexer_with_invalid_target_url_changes_nothing(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    let response = send_create_webhook_indexer_request(client.clone(), WORKING_APIBARA_SCRIPT, addr).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    // the log level is valid but the target url isn't, so none of them is written
+    let response = client
+        .request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("http://{}/v1/indexers/{}", addr, indexer.id))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"log_level":"trace","target_url":"ftp://example.com"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let indexer = get_indexer(indexer.id).await;
+    assert_ne!(indexer.log_level, Some(IndexerLogLevel::Trace));
+    assert_eq!(indexer.target_url, Some(WEHBHOOK_URL.into()));
+}
+
+#[rstest]
+#[tokio::test]
+async fn simulate_delivery(#[future] setup_server: SocketAddr) {
+    let addr = setup_server.await;
+
+    let client = hyper::Client::new();
+
+    // receiver of the target, answers the simulated deliveries with the header set by the sink options
+    let receiver = Router::new().route(
+        "/hook",
+        post(|headers: HeaderMap| async move {
+            match (headers.get(SIMULATION_HEADER), headers.get("x-receiver")) {
+                (Some(_), Some(value)) => (StatusCode::ACCEPTED, value.to_str().unwrap().to_string()),
+                _ => (StatusCode::OK, String::new()),
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let receiver_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener).unwrap().serve(receiver.into_make_service()).await.unwrap();
+    });
+
+    let mut mpart = MultipartRequest::default();
+    mpart.add_file("script.js", WORKING_APIBARA_SCRIPT);
+    mpart.add_field("target_url", &format!("http://{}/hook", receiver_addr));
+    mpart.add_field("indexer_type", IndexerType::Webhook.to_string().as_str());
+    mpart.add_field("sink_options", r#"{"type":"webhook","headers":["x-receiver: under-development"]}"#);
+    let response = send_create_indexer_request(client.clone(), mpart, addr).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let indexer: IndexerModel = serde_json::from_slice(&body).unwrap();
+
+    let response = client
+        .request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("http://{}/v1/indexers/{}/simulate-delivery", addr, indexer.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let delivery: SimulatedDeliveryModel = serde_json::from_slice(&body).unwrap();
+    assert_eq!(delivery.status_code, Some(202));
+    assert_eq!(delivery.response_body.as_deref(), Some("under-development"));
+    assert!(delivery.error.is_none());
+    // the payload is wrapped with the cursors as the sink doesn't send it raw
+    assert!(delivery.payload.get("end_cursor").is_some());
+
+    send_stop_indexer_request(client.clone(), indexer.id, addr).await;
 }
@@ -0,0 +1,128 @@
+use std::future::Future;
+
+use axum::http::{HeaderMap, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use subtle::ConstantTimeEq;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::actor::{ActorContext, ActorScope};
+use crate::utils::custom_extractors::admin_extractor::{ADMIN_ACTOR_HEADER, ADMIN_API_KEY_HEADER};
+use crate::utils::signing::verify_tenant_key;
+
+/// Header carrying the correlation id of a request, returned in every response
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+/// Header carrying the tenant the caller acts for
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+/// Header carrying the key of the tenant, handed out by the admins through `/v1/tenants/:id/key`
+pub const TENANT_KEY_HEADER: &str = "x-tenant-key";
+const MAX_CORRELATION_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static ACTOR_CONTEXT: ActorContext;
+}
+
+/// Context of the work the current task does. Service functions take the context as an argument,
+/// this is only for the code under them which doesn't (the processes of the sinks, their hooks).
+pub fn current_actor_context() -> ActorContext {
+    ACTOR_CONTEXT.try_with(ActorContext::clone).unwrap_or_else(|_| ActorContext::system())
+}
+
+/// Ids sent by the callers are kept so that they can trace their own requests, anything which
+/// doesn't look like an id is replaced
+fn is_valid_correlation_id(correlation_id: &str) -> bool {
+    !correlation_id.is_empty()
+        && correlation_id.len() <= MAX_CORRELATION_ID_LENGTH
+        && correlation_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn get_header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Builds the context of a request from its headers. The admin scope requires the admin api key,
+/// the actor is only trusted along with it. The tenant is only trusted along with its key or the
+/// admin api key.
+pub async fn get_actor_context(headers: &HeaderMap) -> ActorContext {
+    let config = config().await;
+    let admin = matches!(
+        (config.admin_api_key(), get_header(headers, ADMIN_API_KEY_HEADER)),
+        (Some(expected), Some(provided)) if bool::from(provided.as_bytes().ct_eq(expected.as_bytes()))
+    );
+    let tenant_id =
+        get_header(headers, TENANT_ID_HEADER).filter(|tenant_id| !tenant_id.is_empty()).filter(|tenant_id| {
+            admin
+                || matches!(
+                    (config.tenant_key_secret(), get_header(headers, TENANT_KEY_HEADER)),
+                    (Some(secret), Some(key)) if verify_tenant_key(secret, tenant_id, key)
+                )
+        });
+    ActorContext {
+        tenant_id: tenant_id.map(String::from),
+        actor_id: get_header(headers, ADMIN_ACTOR_HEADER).filter(|_| admin).map(String::from),
+        scopes: if admin { vec![ActorScope::Admin] } else { vec![] },
+        request_id: Some(
+            get_header(headers, CORRELATION_ID_HEADER)
+                .filter(|value| is_valid_correlation_id(value))
+                .map(String::from)
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        ),
+        system: false,
+    }
+}
+
+/// Builds the context of the request for its handler, see `ActorContext`. Its correlation id ends
+/// up in its logs, the audit logs and the notifications it causes, including those of the tasks
+/// it spawns through `spawn_with_context`.
+pub async fn actor_context_middleware<B>(mut request: Request<B>, next: Next<B>) -> Response {
+    let context = get_actor_context(request.headers()).await;
+    let correlation_id = context.request_id.clone().unwrap_or_default();
+    request.extensions_mut().insert(context.clone());
+
+    let span = tracing::info_span!("request", correlation_id = %correlation_id);
+    let mut response = ACTOR_CONTEXT.scope(context, next.run(request).instrument(span)).await;
+    if let Ok(value) = HeaderValue::from_str(&correlation_id) {
+        response.headers_mut().insert(CORRELATION_ID_HEADER, value);
+    }
+    response
+}
+
+/// Spawns a task doing work for the context
+pub fn spawn_with_context<F>(context: &ActorContext, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match &context.request_id {
+        Some(correlation_id) => {
+            let span = tracing::info_span!("task", correlation_id = %correlation_id);
+            tokio::spawn(ACTOR_CONTEXT.scope(context.clone(), future.instrument(span)))
+        }
+        None => tokio::spawn(ACTOR_CONTEXT.scope(context.clone(), future)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_correlation_id() {
+        assert!(is_valid_correlation_id(&Uuid::new_v4().to_string()));
+        assert!(is_valid_correlation_id("deploy_42"));
+        assert!(!is_valid_correlation_id(""));
+        assert!(!is_valid_correlation_id("id\nInjected: header"));
+        assert!(!is_valid_correlation_id(&"a".repeat(MAX_CORRELATION_ID_LENGTH + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_with_context() {
+        assert_eq!(current_actor_context(), ActorContext::system());
+        let context = ActorContext { request_id: Some("request-1".into()), ..Default::default() };
+        let spawned = spawn_with_context(&context, async { current_actor_context() }).await.unwrap();
+        assert_eq!(spawned, context);
+    }
+}
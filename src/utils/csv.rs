@@ -0,0 +1,28 @@
+/// Builds a CSV line from the fields, quoting the fields when required (RFC 4180)
+pub fn to_csv_line<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let fields: Vec<String> = fields.into_iter().map(|field| escape_csv_field(field.as_ref())).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv_line() {
+        assert_eq!(to_csv_line(["a", "b"]), "a,b\r\n");
+        assert_eq!(to_csv_line(["a,b", "say \"hi\"", ""]), "\"a,b\",\"say \"\"hi\"\"\",\r\n");
+    }
+}
@@ -0,0 +1,24 @@
+use std::convert::Infallible;
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::domain::models::actor::ActorContext;
+use crate::utils::actor_context::get_actor_context;
+
+/// Context built by `actor_context_middleware`, routes outside of it build their own
+#[async_trait]
+impl<S> FromRequestParts<S> for ActorContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<ActorContext>() {
+            Some(context) => Ok(context.clone()),
+            None => Ok(get_actor_context(&parts.headers).await),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::domain::models::actor::{ActorContext, ActorScope};
+use crate::errors::AppError;
+
+pub const ADMIN_API_KEY_HEADER: &str = "x-admin-api-key";
+pub const ADMIN_ACTOR_HEADER: &str = "x-admin-actor";
+
+/// Guards routes requiring the admin scope. The request must carry the `x-admin-api-key`
+/// header matching the `ADMIN_API_KEY` of the service. The optional `x-admin-actor` header
+/// identifies who is acting and is recorded in the audit logs.
+#[derive(Debug)]
+pub struct AdminGuard {
+    pub context: ActorContext,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminGuard
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let context = match ActorContext::from_request_parts(parts, state).await {
+            Ok(context) => context,
+            Err(e) => match e {},
+        };
+        match context.has_scope(ActorScope::Admin) {
+            true => Ok(AdminGuard { context }),
+            false => Err(AppError::Unauthorized),
+        }
+    }
+}
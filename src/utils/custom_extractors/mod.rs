@@ -1,2 +1,6 @@
+pub mod actor_extractor;
+pub mod admin_extractor;
 pub mod json_extractor;
+pub mod optional_json_extractor;
 pub mod path_extractor;
+pub mod query_extractor;
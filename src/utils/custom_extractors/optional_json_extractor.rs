@@ -0,0 +1,32 @@
+use axum::body::{Bytes, HttpBody};
+use axum::extract::FromRequest;
+use axum::http::Request;
+use axum::{async_trait, BoxError};
+use serde::de::DeserializeOwned;
+
+use crate::errors::AppError;
+
+/// JSON body of the requests it is optional for, e.g. the options of an action. An empty body is
+/// the default of the type, anything else must deserialize into it.
+#[derive(Debug)]
+pub struct OptionalJsonExtractor<T>(pub T);
+
+#[async_trait]
+impl<S, B, T> FromRequest<S, B> for OptionalJsonExtractor<T>
+where
+    T: DeserializeOwned + Default,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state).await.map_err(|e| AppError::BodyParsing(e.to_string()))?;
+        if body.is_empty() {
+            return Ok(Self(T::default()));
+        }
+        serde_json::from_slice(&body).map(Self).map_err(|e| AppError::BodyParsing(e.to_string()))
+    }
+}
@@ -0,0 +1,14 @@
+use axum::extract::rejection::QueryRejection;
+use axum_macros::FromRequestParts;
+
+use crate::errors::AppError;
+
+#[derive(FromRequestParts, Debug)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct QueryExtractor<T>(pub T);
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BodyParsing(rejection.to_string())
+    }
+}
@@ -1,3 +1,7 @@
 pub fn get_environment_variable(name: &str) -> String {
     std::env::var(name).unwrap_or_else(|_| panic!("{} is not set", name))
 }
+
+pub fn try_get_environment_variable(name: &str) -> Result<String, String> {
+    std::env::var(name).map_err(|_| format!("{} is not set", name))
+}
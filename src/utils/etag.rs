@@ -0,0 +1,93 @@
+use std::convert::Infallible;
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+
+/// Strong entity tag of a representation built from the parts it depends on, e.g. the version of
+/// the resource and the format it's rendered in
+pub fn entity_tag(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("\"{}\"", &hex::encode(hasher.finalize())[..32])
+}
+
+/// Entity tags of the `If-None-Match` header of the request
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IfNoneMatch(Option<String>);
+
+impl IfNoneMatch {
+    pub fn new(value: Option<String>) -> Self {
+        Self(value)
+    }
+
+    /// Weak comparison, as required for `If-None-Match`
+    pub fn matches(&self, etag: &str) -> bool {
+        let Some(value) = &self.0 else {
+            return false;
+        };
+        let etag = etag.trim_start_matches("W/");
+        value.split(',').map(str::trim).any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+    }
+}
+
+/// Answer to a request whose `If-None-Match` matches `etag`
+pub fn not_modified(etag: &str) -> Response {
+    with_etag(StatusCode::NOT_MODIFIED.into_response(), etag)
+}
+
+pub fn with_etag(mut response: Response, etag: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfNoneMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts.headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok());
+        Ok(IfNoneMatch(value.map(str::to_string)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_entity_tag() {
+        let etag = entity_tag(&["1", "json"]);
+        assert_eq!(etag.len(), 34);
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag, entity_tag(&["1", "json"]));
+        assert_ne!(etag, entity_tag(&["2", "json"]));
+        // parts are delimited
+        assert_ne!(entity_tag(&["1", "2"]), entity_tag(&["12", ""]));
+    }
+
+    #[rstest]
+    #[case(None, false)]
+    #[case(Some("\"abc\""), true)]
+    #[case(Some("W/\"abc\""), true)]
+    #[case(Some("\"xyz\", \"abc\""), true)]
+    #[case(Some("*"), true)]
+    #[case(Some("\"xyz\""), false)]
+    #[case(Some("abc"), false)]
+    fn test_if_none_match(#[case] value: Option<&str>, #[case] expected: bool) {
+        assert_eq!(IfNoneMatch::new(value.map(str::to_string)).matches("\"abc\""), expected);
+    }
+}
@@ -0,0 +1,23 @@
+use std::sync::OnceLock;
+
+use tokio::sync::broadcast;
+
+use crate::constants::runtime::EVENT_BUS_CAPACITY;
+use crate::domain::models::audit::AuditLogModel;
+
+/// Audit events of the fleet as they are recorded, for the subscribers to follow them live
+static EVENT_BUS: OnceLock<broadcast::Sender<AuditLogModel>> = OnceLock::new();
+
+fn event_bus() -> &'static broadcast::Sender<AuditLogModel> {
+    EVENT_BUS.get_or_init(|| broadcast::channel(EVENT_BUS_CAPACITY).0)
+}
+
+/// Sends the event to the current subscribers, it is dropped if there are none
+pub fn publish_event(event: AuditLogModel) {
+    let _ = event_bus().send(event);
+}
+
+/// Subscribers lagging more than the capacity of the bus miss the oldest events
+pub fn subscribe_events() -> broadcast::Receiver<AuditLogModel> {
+    event_bus().subscribe()
+}
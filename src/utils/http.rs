@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::redirect::Policy;
+use reqwest::{Method, RequestBuilder, Url};
+
+use crate::config::OutboundHttpConfig;
+use crate::constants::runtime::{DEFAULT_OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS, DEFAULT_OUTBOUND_HTTP_TIMEOUT_SECONDS};
+
+static HTTP_CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+/// Client of the outbound calls of the service, built from `OutboundHttpConfig` so that every call
+/// goes through the same proxy, trusts the same roots and shares the same connection pool
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    /// Same settings without following redirects, for the calls to urls checked by the target
+    /// policy. A redirect would reach a destination the policy never saw.
+    no_redirect_client: reqwest::Client,
+    timeout: Duration,
+    destination_timeouts: Vec<(String, Duration)>,
+}
+
+impl HttpClient {
+    pub fn new(config: &OutboundHttpConfig) -> Result<Self, String> {
+        Ok(Self {
+            client: Self::build(config, Policy::default())?,
+            no_redirect_client: Self::build(config, Policy::none())?,
+            timeout: config.timeout,
+            destination_timeouts: config.destination_timeouts.clone(),
+        })
+    }
+
+    fn build(config: &OutboundHttpConfig, redirect: Policy) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder().connect_timeout(config.connect_timeout).redirect(redirect);
+        if let Some(proxy_url) = &config.proxy_url {
+            let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| format!("Invalid proxy {}: {}", proxy_url, e))?;
+            let no_proxy = config.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string);
+            builder = builder.proxy(proxy.no_proxy(no_proxy));
+        }
+        if let Some(ca_cert_path) = &config.ca_cert_path {
+            let pem = std::fs::read(ca_cert_path).map_err(|e| format!("Failed to read {}: {}", ca_cert_path, e))?;
+            let certificate = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid certificate {}: {}", ca_cert_path, e))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(pool_max_idle_per_host) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        if let Some(pool_idle_timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        builder.build().map_err(|e| format!("Failed to build the HTTP client: {}", e))
+    }
+
+    /// Client which returns redirects as responses instead of following them
+    pub fn without_redirects(&self) -> Self {
+        Self { client: self.no_redirect_client.clone(), ..self.clone() }
+    }
+
+    /// The timeout of the destination applies unless the request sets its own
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let timeout = Url::parse(url)
+            .ok()
+            .and_then(|url| get_destination_timeout(&self.destination_timeouts, url.host_str()?))
+            .unwrap_or(self.timeout);
+        self.client.request(method, url).timeout(timeout)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        let build = |redirect: Policy| {
+            reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(DEFAULT_OUTBOUND_HTTP_CONNECT_TIMEOUT_SECONDS))
+                .redirect(redirect)
+                .build()
+                .expect("Failed to build the HTTP client")
+        };
+        Self {
+            client: build(Policy::default()),
+            no_redirect_client: build(Policy::none()),
+            timeout: Duration::from_secs(DEFAULT_OUTBOUND_HTTP_TIMEOUT_SECONDS),
+            destination_timeouts: vec![],
+        }
+    }
+}
+
+/// Builds the shared client, the service refuses to start with a client it can't build
+pub fn init_http_client(config: &OutboundHttpConfig) -> HttpClient {
+    let client = HttpClient::new(config).unwrap_or_else(|e| panic!("{}", e));
+    HTTP_CLIENT.get_or_init(|| client).clone()
+}
+
+/// Shared client for the outbound calls of the service, for the code which isn't handed the one
+/// of `AppState`. It has the default settings if used before `init_http_client`, e.g. in tests.
+pub fn http_client() -> &'static HttpClient {
+    HTTP_CLIENT.get_or_init(HttpClient::default)
+}
+
+/// Timeout of the host, a host also covers its subdomains and the most specific one wins
+fn get_destination_timeout(destination_timeouts: &[(String, Duration)], host: &str) -> Option<Duration> {
+    let host = host.to_lowercase();
+    destination_timeouts
+        .iter()
+        .filter(|(destination, _)| {
+            host == *destination || host.strip_suffix(destination.as_str()).is_some_and(|prefix| prefix.ends_with('.'))
+        })
+        .max_by_key(|(destination, _)| destination.len())
+        .map(|(_, timeout)| *timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("hooks.example.com", Some(5))]
+    #[case("eu.hooks.example.com", Some(5))]
+    #[case("api.example.com", Some(20))]
+    #[case("HOOKS.EXAMPLE.COM", Some(5))]
+    #[case("notexample.com", None)]
+    #[case("example.org", None)]
+    fn test_get_destination_timeout(#[case] host: &str, #[case] expected: Option<u64>) {
+        let destination_timeouts = vec![
+            ("example.com".to_string(), Duration::from_secs(20)),
+            ("hooks.example.com".into(), Duration::from_secs(5)),
+        ];
+        assert_eq!(get_destination_timeout(&destination_timeouts, host), expected.map(Duration::from_secs));
+    }
+}
@@ -1,6 +1,27 @@
+pub use custom_extractors::admin_extractor::AdminGuard;
 pub use custom_extractors::json_extractor::JsonExtractor;
+pub use custom_extractors::optional_json_extractor::OptionalJsonExtractor;
 pub use custom_extractors::path_extractor::PathExtractor;
+pub use custom_extractors::query_extractor::QueryExtractor;
 
-mod custom_extractors;
+pub mod actor_context;
+pub mod csv;
+pub mod custom_extractors;
 pub mod env;
+pub mod etag;
+pub mod event_bus;
+pub mod http;
+pub mod negotiation;
+pub mod sandbox_policy;
+pub mod script_cache;
+pub mod script_dependencies;
+pub mod script_fetch;
+pub mod script_filter;
+pub mod script_params;
+pub mod secrets;
 pub mod serde;
+pub mod signing;
+pub mod stream_policy;
+pub mod supervisor;
+pub mod target_policy;
+pub mod tls;
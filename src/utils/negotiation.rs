@@ -0,0 +1,94 @@
+use std::convert::Infallible;
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{async_trait, Json};
+use serde::Serialize;
+
+pub const MESSAGE_PACK_CONTENT_TYPE: &str = "application/msgpack";
+const MESSAGE_PACK_MEDIA_TYPES: [&str; 3] =
+    [MESSAGE_PACK_CONTENT_TYPE, "application/x-msgpack", "application/vnd.msgpack"];
+
+/// Format of the response requested through the `Accept` header, JSON unless a supported binary
+/// format is listed before it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .filter_map(|media_range| media_range.split(';').next())
+            .map(|media_type| media_type.trim().to_lowercase())
+            .find_map(|media_type| match media_type.as_str() {
+                "application/json" | "application/*" | "*/*" => Some(ResponseFormat::Json),
+                media_type if MESSAGE_PACK_MEDIA_TYPES.contains(&media_type) => Some(ResponseFormat::MessagePack),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ResponseFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts.headers.get(header::ACCEPT).and_then(|value| value.to_str().ok()).unwrap_or_default();
+        Ok(ResponseFormat::from_accept(accept))
+    }
+}
+
+/// Response serialized in the format the client asked for, e.g.
+/// `Negotiated(format, indexers)` with `format` extracted from the request
+pub struct Negotiated<T>(pub ResponseFormat, pub T);
+
+impl<T> IntoResponse for Negotiated<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+        let mut response = match format {
+            ResponseFormat::Json => Json(value).into_response(),
+            ResponseFormat::MessagePack => match rmp_serde::to_vec_named(&value) {
+                Ok(bytes) => ([(header::CONTENT_TYPE, MESSAGE_PACK_CONTENT_TYPE)], bytes).into_response(),
+                Err(e) => {
+                    tracing::error!("Failed to serialize the response to MessagePack: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            },
+        };
+        // caches must not serve a response in the format another client asked for
+        response.headers_mut().insert(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("", ResponseFormat::Json)]
+    #[case("application/json", ResponseFormat::Json)]
+    #[case("application/msgpack", ResponseFormat::MessagePack)]
+    #[case("application/x-msgpack;q=0.9, application/json;q=0.8", ResponseFormat::MessagePack)]
+    #[case("application/json, application/msgpack", ResponseFormat::Json)]
+    #[case("text/html, */*", ResponseFormat::Json)]
+    #[case("text/html", ResponseFormat::Json)]
+    fn test_from_accept(#[case] accept: &str, #[case] format: ResponseFormat) {
+        assert_eq!(ResponseFormat::from_accept(accept), format);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::domain::models::indexer::{IndexerError, ScriptPermissions};
+
+/// Restricts the permissions indexers can grant to their script. Scripts run in the deno
+/// runtime embedded in the sinks which denies network, file system and environment access
+/// unless explicitly allowed. The stream and the target are reached by the sink itself so
+/// scripts don't need any permission by default.
+#[derive(Clone, Debug, Default)]
+pub struct SandboxPolicy {
+    pub allowed_net_hosts: Vec<String>,
+    pub allowed_read_paths: Vec<String>,
+    pub allowed_env_vars: Vec<String>,
+}
+
+impl SandboxPolicy {
+    pub fn validate(&self, permissions: &ScriptPermissions) -> Result<(), IndexerError> {
+        let checks = [
+            ("net", &permissions.allow_net, &self.allowed_net_hosts),
+            ("read", &permissions.allow_read, &self.allowed_read_paths),
+            ("env", &permissions.allow_env, &self.allowed_env_vars),
+        ];
+        for (kind, requested, allowed) in checks {
+            if let Some(denied) = requested.iter().find(|item| !allowed.contains(item)) {
+                return Err(IndexerError::ScriptPermissionNotAllowed(format!("{}:{}", kind, denied)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let policy = SandboxPolicy {
+            allowed_net_hosts: vec!["api.coingecko.com".into()],
+            allowed_read_paths: vec![],
+            allowed_env_vars: vec!["NETWORK".into()],
+        };
+
+        assert!(policy.validate(&ScriptPermissions::default()).is_ok());
+        assert!(
+            policy
+                .validate(&ScriptPermissions {
+                    allow_net: vec!["api.coingecko.com".into()],
+                    allow_env: vec!["NETWORK".into()],
+                    ..Default::default()
+                })
+                .is_ok()
+        );
+        assert!(matches!(
+            policy.validate(&ScriptPermissions { allow_net: vec!["169.254.169.254".into()], ..Default::default() }),
+            Err(IndexerError::ScriptPermissionNotAllowed(_))
+        ));
+        assert!(matches!(
+            policy.validate(&ScriptPermissions { allow_read: vec!["/etc".into()], ..Default::default() }),
+            Err(IndexerError::ScriptPermissionNotAllowed(_))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use axum::body::Bytes;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::constants::indexers::SCRIPT_CACHE_FOLDER;
+
+const CACHED_SCRIPT_EXTENSION: &str = "js";
+
+pub fn get_script_checksum(script: &[u8]) -> String {
+    hex::encode(Sha256::digest(script))
+}
+
+fn get_cache_directory() -> PathBuf {
+    std::env::temp_dir().join(SCRIPT_CACHE_FOLDER)
+}
+
+fn get_cache_path(checksum: &str) -> PathBuf {
+    get_cache_directory().join(format!("{}.{}", checksum, CACHED_SCRIPT_EXTENSION))
+}
+
+/// Returns the script cached for the checksum. An entry which doesn't match its checksum is
+/// removed and reported as a miss.
+pub async fn get_cached_script(checksum: &str) -> Option<Bytes> {
+    let path = get_cache_path(checksum);
+    let script = tokio::fs::read(&path).await.ok()?;
+    if get_script_checksum(&script) != checksum {
+        tracing::warn!("Cached script {} is corrupted, removing it", checksum);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            tracing::error!("Failed to remove cached script {}: {:?}", checksum, e);
+        }
+        return None;
+    }
+    Some(Bytes::from(script))
+}
+
+/// Caches a script under its checksum. It's written to a temporary file first so readers never
+/// see a partially written script.
+pub async fn cache_script(checksum: &str, script: &[u8]) -> std::io::Result<()> {
+    let directory = get_cache_directory();
+    tokio::fs::create_dir_all(&directory).await?;
+    let tmp_path = directory.join(format!("{}.tmp", Uuid::new_v4()));
+    tokio::fs::write(&tmp_path, script).await?;
+    tokio::fs::rename(&tmp_path, get_cache_path(checksum)).await
+}
+
+/// Removes the cached scripts whose checksum isn't in `keep`, returns how many were removed
+pub async fn remove_unused_scripts(keep: &HashSet<String>) -> std::io::Result<usize> {
+    let mut entries = match tokio::fs::read_dir(get_cache_directory()).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut removed = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let checksum = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(checksum) => checksum,
+            None => continue,
+        };
+        // temporary files are only left over by interrupted writes
+        let is_cached_script = path.extension().is_some_and(|extension| extension == CACHED_SCRIPT_EXTENSION);
+        if is_cached_script && keep.contains(checksum) {
+            continue;
+        }
+        tokio::fs::remove_file(&path).await?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_script() {
+        let script = format!("// {}", Uuid::new_v4());
+        let checksum = get_script_checksum(script.as_bytes());
+        assert_eq!(get_cached_script(&checksum).await, None);
+
+        cache_script(&checksum, script.as_bytes()).await.unwrap();
+        assert_eq!(get_cached_script(&checksum).await, Some(Bytes::from(script)));
+
+        // corrupted entries are dropped
+        tokio::fs::write(get_cache_path(&checksum), "corrupted").await.unwrap();
+        assert_eq!(get_cached_script(&checksum).await, None);
+        assert!(!get_cache_path(&checksum).exists());
+    }
+}
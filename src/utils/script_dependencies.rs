@@ -0,0 +1,108 @@
+use crate::domain::models::script_scan::ScriptDependency;
+
+/// Prefixes of the specifiers resolving to npm packages, as written in the imports of the
+/// scripts or in their import maps
+const NPM_SPECIFIER_PREFIXES: [&str; 6] = [
+    "npm:",
+    "https://esm.sh/",
+    "https://esm.run/",
+    "https://cdn.skypack.dev/",
+    "https://unpkg.com/",
+    "https://cdn.jsdelivr.net/npm/",
+];
+
+/// npm packages the script imports at an exact version. Bundles are scanned as text: every
+/// string literal is read, so the entries of inlined import maps are found along with the
+/// imports. Packages imported without a version or at a range can't be matched to advisories
+/// and are skipped.
+pub fn extract_dependencies(script: &str) -> Vec<ScriptDependency> {
+    let mut dependencies: Vec<ScriptDependency> = vec![];
+    let mut rest = script;
+    while let Some(offset) = rest.find(['"', '\'', '`']) {
+        let Some((literal, remaining)) = read_string_literal(&rest[offset..]) else {
+            break;
+        };
+        if let Some(dependency) = parse_specifier(literal) {
+            if !dependencies.contains(&dependency) {
+                dependencies.push(dependency);
+            }
+        }
+        rest = remaining;
+    }
+    dependencies
+}
+
+/// e.g. `npm:@scope/name@1.2.3/path` or `https://esm.sh/name@1.2.3?target=deno`
+fn parse_specifier(specifier: &str) -> Option<ScriptDependency> {
+    let package = NPM_SPECIFIER_PREFIXES.iter().find_map(|prefix| specifier.strip_prefix(prefix))?;
+    let package = package.split(['?', '#']).next()?;
+    // the `@` of a scope isn't the one of the version
+    let (scope, unscoped) = match package.strip_prefix('@') {
+        Some(scoped) => {
+            let (scope, unscoped) = scoped.split_once('/')?;
+            (Some(scope), unscoped)
+        }
+        None => (None, package),
+    };
+    let (name, version) = unscoped.split('/').next()?.split_once('@')?;
+    let version = version.trim_start_matches(['v', '=']);
+    let exact = version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || ['.', '-', '+'].contains(&c));
+    if name.is_empty() || !exact {
+        return None;
+    }
+    let name = match scope {
+        Some(scope) => format!("@{}/{}", scope, name),
+        None => name.to_string(),
+    };
+    Some(ScriptDependency { name: name.to_lowercase(), version: version.to_string() })
+}
+
+fn read_string_literal(text: &str) -> Option<(&str, &str)> {
+    let quote = text.chars().next().filter(|c| ['"', '\'', '`'].contains(c))?;
+    let end = text[1..].find(quote)? + 1;
+    Some((&text[1..end], &text[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("npm:lodash@4.17.20", Some(("lodash", "4.17.20")))]
+    #[case("npm:@apibara/indexer@0.2.1/starknet", Some(("@apibara/indexer", "0.2.1")))]
+    #[case("https://esm.sh/Axios@v1.6.0?target=deno", Some(("axios", "1.6.0")))]
+    #[case("https://cdn.jsdelivr.net/npm/@scope/pkg@2.0.0-beta.1/index.js", Some(("@scope/pkg", "2.0.0-beta.1")))]
+    #[case("npm:lodash", None)]
+    #[case("npm:lodash@^4.17.0", None)]
+    #[case("https://deno.land/x/oak@v12.6.1/mod.ts", None)]
+    #[case("./local.js", None)]
+    fn test_parse_specifier(#[case] specifier: &str, #[case] expected: Option<(&str, &str)>) {
+        assert_eq!(
+            parse_specifier(specifier),
+            expected.map(|(name, version)| ScriptDependency { name: name.into(), version: version.into() })
+        );
+    }
+
+    #[test]
+    fn test_extract_dependencies() {
+        let script = r#"
+            import { hash } from "npm:starknet@5.24.3";
+            import lodash from 'https://esm.sh/lodash@4.17.20';
+            const map = { imports: { "lodash": "npm:lodash@4.17.20", "local": "./local.js" } };
+            const utils = await import(`npm:@scope/utils@1.0.0`);
+        "#;
+        let dependencies: Vec<(String, String)> =
+            extract_dependencies(script).into_iter().map(|dependency| (dependency.name, dependency.version)).collect();
+        assert_eq!(
+            dependencies,
+            vec![
+                ("starknet".to_string(), "5.24.3".to_string()),
+                ("lodash".to_string(), "4.17.20".to_string()),
+                ("@scope/utils".to_string(), "1.0.0".to_string()),
+            ]
+        );
+    }
+}
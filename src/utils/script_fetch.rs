@@ -0,0 +1,117 @@
+use axum::body::Bytes;
+use url::Url;
+
+use crate::config::config;
+use crate::constants::indexers::{SCRIPT_URL_FETCH_TIMEOUT_SECONDS, SCRIPT_URL_MAX_BYTES};
+use crate::domain::models::indexer::IndexerError;
+use crate::utils::http::http_client;
+use crate::utils::script_cache::get_script_checksum;
+
+/// Returns the url the raw script is served at. Links to a file on GitHub are rewritten to its
+/// raw content, other urls are fetched as is.
+pub fn get_raw_script_url(script_url: &str) -> Result<Url, IndexerError> {
+    let url = Url::parse(script_url).map_err(|e| IndexerError::InvalidScriptUrl(format!("{}: {}", script_url, e)))?;
+    if url.scheme() != "https" {
+        return Err(IndexerError::InvalidScriptUrl(format!("{}: only https is supported", script_url)));
+    }
+    if url.host_str() != Some("github.com") {
+        return Ok(url);
+    }
+    // https://github.com/{owner}/{repo}/blob/{ref}/{path}
+    let segments: Vec<&str> = url.path_segments().map(|segments| segments.collect()).unwrap_or_default();
+    match segments.as_slice() {
+        [owner, repo, "blob", rest @ ..] if rest.len() >= 2 => {
+            let raw = format!("https://raw.githubusercontent.com/{}/{}/{}", owner, repo, rest.join("/"));
+            Url::parse(&raw).map_err(|e| IndexerError::InvalidScriptUrl(format!("{}: {}", script_url, e)))
+        }
+        _ => Ok(url),
+    }
+}
+
+/// Checks the script against the sha256 it was pinned to, if any, whatever its source
+pub fn verify_script_checksum(script: &[u8], pinned_checksum: Option<&str>) -> Result<(), IndexerError> {
+    let pinned_checksum = match pinned_checksum {
+        Some(pinned_checksum) => pinned_checksum,
+        None => return Ok(()),
+    };
+    let checksum = get_script_checksum(script);
+    if !checksum.eq_ignore_ascii_case(pinned_checksum) {
+        return Err(IndexerError::ScriptChecksumMismatch(pinned_checksum.to_string(), checksum));
+    }
+    Ok(())
+}
+
+/// Downloads the script of an indexer created from a url. The url goes through the target policy
+/// like webhook targets, and the download is bounded in time and size since the service holds the
+/// whole script in memory. Redirects aren't followed since their destination wasn't validated.
+pub async fn fetch_script(script_url: &str) -> Result<Bytes, IndexerError> {
+    let url = get_raw_script_url(script_url)?;
+    config().await.target_policy().validate(url.as_str()).await?;
+
+    let failed = |e: String| IndexerError::FailedToFetchScript(script_url.to_string(), e);
+    let mut response = http_client()
+        .without_redirects()
+        .get(url.as_str())
+        .timeout(std::time::Duration::from_secs(SCRIPT_URL_FETCH_TIMEOUT_SECONDS))
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    let status = response.status();
+    if status.is_redirection() {
+        return Err(failed(format!("redirected with status {}, use the final url", status.as_u16())));
+    }
+    if !status.is_success() {
+        return Err(failed(format!("status {}", status.as_u16())));
+    }
+    let too_large = || failed(format!("the script is larger than {} bytes", SCRIPT_URL_MAX_BYTES));
+    if response.content_length().is_some_and(|length| length > SCRIPT_URL_MAX_BYTES as u64) {
+        return Err(too_large());
+    }
+
+    // the announced length can't be trusted, e.g. with chunked responses
+    let mut script = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| failed(e.to_string()))? {
+        if script.len() + chunk.len() > SCRIPT_URL_MAX_BYTES {
+            return Err(too_large());
+        }
+        script.extend_from_slice(&chunk);
+    }
+
+    Ok(Bytes::from(script))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("https://example.com/script.js", Ok("https://example.com/script.js"))]
+    #[case(
+        "https://github.com/astraly-labs/indexers/blob/main/src/transfers.js",
+        Ok("https://raw.githubusercontent.com/astraly-labs/indexers/main/src/transfers.js")
+    )]
+    #[case("https://github.com/astraly-labs/indexers", Ok("https://github.com/astraly-labs/indexers"))]
+    #[case(
+        "http://example.com/script.js",
+        Err("invalid script url http://example.com/script.js: only https is supported")
+    )]
+    fn test_get_raw_script_url(#[case] script_url: &str, #[case] expected: Result<&str, &str>) {
+        let result = get_raw_script_url(script_url).map(|url| url.to_string()).map_err(|e| e.to_string());
+        assert_eq!(result, expected.map(String::from).map_err(String::from));
+    }
+
+    #[test]
+    fn test_verify_script_checksum() {
+        let script = b"console.log(1)";
+        let checksum = get_script_checksum(script);
+        assert!(verify_script_checksum(script, None).is_ok());
+        assert!(verify_script_checksum(script, Some(&checksum)).is_ok());
+        assert!(verify_script_checksum(script, Some(&checksum.to_uppercase())).is_ok());
+        assert!(matches!(
+            verify_script_checksum(b"console.log(2)", Some(&checksum)),
+            Err(IndexerError::ScriptChecksumMismatch(_, _))
+        ));
+    }
+}
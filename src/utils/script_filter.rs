@@ -0,0 +1,117 @@
+use crate::domain::models::contract::{normalize_felt, ContractFilter};
+
+/// Extracts the contracts covered by the event filters of a script. Scripts aren't evaluated so
+/// only the addresses and keys written as hex literals are found, e.g. keys computed with
+/// `hash.getSelectorFromName` are skipped.
+pub fn extract_contract_filters(script: &str) -> Vec<ContractFilter> {
+    let mut filters: Vec<ContractFilter> = vec![];
+    for (position, _) in script.match_indices("fromAddress") {
+        let address = match read_value(&script[position + "fromAddress".len()..])
+            .and_then(|value| read_string_literal(value).map(|(literal, _)| literal).and_then(normalize_felt))
+        {
+            Some(address) => address,
+            None => continue,
+        };
+
+        // the keys are in the same event filter object, before or after the address
+        let start = script[..position].rfind('{').map(|start| start + 1).unwrap_or(0);
+        let end = script[position..].find('}').map(|end| position + end).unwrap_or(script.len());
+        let event_keys = extract_keys(&script[start..end]);
+
+        match filters.iter_mut().find(|filter| filter.address == address) {
+            Some(filter) => {
+                for key in event_keys {
+                    if !filter.event_keys.contains(&key) {
+                        filter.event_keys.push(key);
+                    }
+                }
+            }
+            None => filters.push(ContractFilter { address, event_keys }),
+        }
+    }
+    filters
+}
+
+fn extract_keys(object: &str) -> Vec<String> {
+    let mut keys = vec![];
+    for (position, _) in object.match_indices("keys") {
+        let value = match read_value(&object[position + "keys".len()..]) {
+            Some(value) if value.starts_with('[') => value,
+            _ => continue,
+        };
+        let list = &value[1..value.find(']').unwrap_or(value.len())];
+        let mut rest = list;
+        while let Some(offset) = rest.find(['"', '\'', '`']) {
+            match read_string_literal(&rest[offset..]) {
+                Some((literal, remaining)) => {
+                    if let Some(key) = normalize_felt(literal) {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                    rest = remaining;
+                }
+                None => break,
+            }
+        }
+    }
+    keys
+}
+
+/// Skips the `:` following a property name, returns `None` if this isn't a property
+fn read_value(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    let text = text.strip_prefix(['"', '\'']).unwrap_or(text).trim_start();
+    Some(text.strip_prefix(':')?.trim_start())
+}
+
+/// Reads a string literal at the start of `text`, returns it with what follows it
+fn read_string_literal(text: &str) -> Option<(&str, &str)> {
+    let quote = text.chars().next().filter(|c| ['"', '\'', '`'].contains(c))?;
+    let end = text[1..].find(quote)? + 1;
+    Some((&text[1..end], &text[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::utils::script_params::resolve_script_params;
+
+    const ETH: &str = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+    const TRANSFER: &str = "0x0099cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9";
+
+    #[test]
+    fn test_extract_contract_filters() {
+        let script = include_str!("../tests/scripts/test.js");
+        assert_eq!(extract_contract_filters(script), vec![ContractFilter { address: ETH.into(), event_keys: vec![] }]);
+
+        let script = r#"
+            const filter = {
+              events: [
+                { keys: ["0x99cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"], fromAddress: "0x049D36570D4e46f48e99674bd3fcc84644DdD6b96F7C741B1562B82f9e004dC7" },
+                { fromAddress: '0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7', keys: [SELECTOR] },
+                { fromAddress: ADDRESS },
+              ],
+            };
+        "#;
+        assert_eq!(
+            extract_contract_filters(script),
+            vec![ContractFilter { address: ETH.into(), event_keys: vec![TRANSFER.into()] }]
+        );
+    }
+
+    #[test]
+    fn test_extract_contract_filters_of_resolved_script() {
+        let script = r#"const filter = { events: [{ fromAddress: "{{params.token}}" }] };"#;
+        assert_eq!(extract_contract_filters(script), vec![]);
+
+        let params = BTreeMap::from([("token".to_string(), ETH.to_string())]);
+        let resolved = resolve_script_params(script, &params).unwrap();
+        assert_eq!(
+            extract_contract_filters(&resolved),
+            vec![ContractFilter { address: ETH.into(), event_keys: vec![] }]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+const PLACEHOLDER_START: &str = "{{";
+const PLACEHOLDER_END: &str = "}}";
+const PARAMS_PREFIX: &str = "params.";
+
+/// Replaces the `{{params.name}}` placeholders of a script with the params of the indexer.
+/// Other `{{...}}` sequences are left untouched. Returns the names of the params the script
+/// references but which aren't set.
+pub fn resolve_script_params(script: &str, params: &BTreeMap<String, String>) -> Result<String, Vec<String>> {
+    let mut resolved = String::with_capacity(script.len());
+    let mut missing = vec![];
+    let mut rest = script;
+    while let Some(start) = rest.find(PLACEHOLDER_START) {
+        let after_start = &rest[start + PLACEHOLDER_START.len()..];
+        let end = match after_start.find(PLACEHOLDER_END) {
+            Some(end) => end,
+            None => break,
+        };
+        resolved.push_str(&rest[..start]);
+        match after_start[..end].trim().strip_prefix(PARAMS_PREFIX) {
+            Some(name) => match params.get(name) {
+                Some(value) => resolved.push_str(value),
+                None => {
+                    if !missing.iter().any(|missing| missing == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            },
+            None => resolved.push_str(&rest[start..start + PLACEHOLDER_START.len() + end + PLACEHOLDER_END.len()]),
+        }
+        rest = &after_start[end + PLACEHOLDER_END.len()..];
+    }
+    resolved.push_str(rest);
+
+    if missing.is_empty() { Ok(resolved) } else { Err(missing) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_script_params() {
+        let params =
+            BTreeMap::from([("address".to_string(), "0x1234".to_string()), ("block".to_string(), "100".to_string())]);
+        let script = r#"const address = "{{params.address}}"; const start = {{ params.block }}; `{{other}}`"#;
+        assert_eq!(
+            resolve_script_params(script, &params).unwrap(),
+            r#"const address = "0x1234"; const start = 100; `{{other}}`"#
+        );
+        assert_eq!(resolve_script_params("no params {{", &params).unwrap(), "no params {{");
+    }
+
+    #[test]
+    fn test_resolve_script_params_missing() {
+        let script = "{{params.address}} {{params.block}} {{params.address}}";
+        assert_eq!(
+            resolve_script_params(script, &BTreeMap::new()).unwrap_err(),
+            vec!["address".to_string(), "block".to_string()]
+        );
+    }
+}
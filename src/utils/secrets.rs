@@ -0,0 +1,337 @@
+use std::path::{Component, Path, PathBuf};
+
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::{AwsSecretsManagerConfig, SecretsConfig, VaultConfig};
+use crate::constants::runtime::SECRETS_ENV_PREFIX;
+use crate::domain::models::secret::{SecretBackendKind, SecretReference};
+use crate::utils::http::http_client;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Store the secrets of a scheme are read from
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    fn kind(&self) -> SecretBackendKind;
+
+    /// Secret stored at the path, the field of the reference is selected afterwards
+    async fn get(&self, path: &str) -> Result<String, String>;
+}
+
+/// `env://acme/token`, the `INDEXER_SECRET_ACME_TOKEN` variable of the environment of the
+/// service. The other variables, e.g. `DATABASE_URL`, can't be referenced.
+pub struct EnvSecretBackend;
+
+pub fn get_secret_variable_name(path: &str) -> String {
+    let name: String = path
+        .trim_start_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("{}{}", SECRETS_ENV_PREFIX, name)
+}
+
+#[async_trait]
+impl SecretBackend for EnvSecretBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Env
+    }
+
+    async fn get(&self, path: &str) -> Result<String, String> {
+        let name = get_secret_variable_name(path);
+        std::env::var(&name).map_err(|_| format!("{} is not set", name))
+    }
+}
+
+/// `file:///acme/token`, a file under the secrets directory of the service, e.g. a mounted
+/// secret. Nothing outside of the directory can be read.
+pub struct FileSecretBackend {
+    pub root: PathBuf,
+}
+
+impl FileSecretBackend {
+    fn get_file_path(&self, path: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(format!("{} is not a path under the secrets directory", path));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl SecretBackend for FileSecretBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::File
+    }
+
+    async fn get(&self, path: &str) -> Result<String, String> {
+        let file_path = self.get_file_path(path)?;
+        let secret =
+            tokio::fs::read_to_string(&file_path).await.map_err(|e| format!("failed to read {}: {}", path, e))?;
+        // mounted secrets usually end with a newline which isn't part of the secret
+        Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+pub struct VaultSecretBackend {
+    pub config: VaultConfig,
+}
+
+#[derive(Deserialize)]
+struct VaultResponse {
+    data: VaultResponseData,
+}
+
+#[derive(Deserialize)]
+struct VaultResponseData {
+    data: serde_json::Map<String, serde_json::Value>,
+}
+
+#[async_trait]
+impl SecretBackend for VaultSecretBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Vault
+    }
+
+    /// Latest version of the secret in the KV v2 engine, as a JSON object of its fields
+    async fn get(&self, path: &str) -> Result<String, String> {
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.config.address.trim_end_matches('/'),
+            self.config.mount,
+            path.trim_start_matches('/')
+        );
+        let mut request = http_client().get(&url).header("X-Vault-Token", &self.config.token);
+        if let Some(namespace) = &self.config.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request.send().await.map_err(|e| format!("failed to reach Vault: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Vault answered {}", response.status()));
+        }
+        let response: VaultResponse = response.json().await.map_err(|e| format!("invalid Vault response: {}", e))?;
+        Ok(serde_json::Value::Object(response.data.data).to_string())
+    }
+}
+
+/// Secrets Manager is called with a SigV4 signed request rather than through the SDK, only
+/// `GetSecretValue` is needed
+pub struct AwsSecretsManagerBackend {
+    pub config: AwsSecretsManagerConfig,
+}
+
+#[derive(Deserialize)]
+struct GetSecretValueResponse {
+    #[serde(rename = "SecretString")]
+    secret_string: Option<String>,
+}
+
+#[async_trait]
+impl SecretBackend for AwsSecretsManagerBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::AwsSm
+    }
+
+    async fn get(&self, path: &str) -> Result<String, String> {
+        let endpoint = self
+            .config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://secretsmanager.{}.amazonaws.com", self.config.region));
+        let host = Url::parse(&endpoint)
+            .ok()
+            .and_then(|url| {
+                Some(format!("{}{}", url.host_str()?, url.port().map(|port| format!(":{}", port)).unwrap_or_default()))
+            })
+            .ok_or_else(|| format!("invalid Secrets Manager endpoint {}", endpoint))?;
+        let body = serde_json::json!({ "SecretId": path }).to_string();
+        let headers = sign_get_secret_value(&self.config, &host, &body, Utc::now());
+
+        let mut request = http_client().post(&endpoint).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| format!("failed to reach Secrets Manager: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(format!("Secrets Manager answered {}: {}", status, response.text().await.unwrap_or_default()));
+        }
+        let response: GetSecretValueResponse =
+            response.json().await.map_err(|e| format!("invalid Secrets Manager response: {}", e))?;
+        response.secret_string.ok_or_else(|| "binary secrets are not supported".to_string())
+    }
+}
+
+/// Headers of a `GetSecretValue` request, with its SigV4 `authorization`
+fn sign_get_secret_value(
+    config: &AwsSecretsManagerConfig,
+    host: &str,
+    body: &str,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    // sorted by name as SigV4 requires
+    let mut headers = vec![
+        ("content-type", "application/x-amz-json-1.1".to_string()),
+        ("host", host.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(session_token) = &config.session_token {
+        headers.push(("x-amz-security-token", session_token.clone()));
+    }
+    headers.push(("x-amz-target", "secretsmanager.GetSecretValue".to_string()));
+
+    let canonical_headers: String =
+        headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body.as_bytes()))
+    );
+    let scope = format!("{}/{}/secretsmanager/aws4_request", date, config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signing_key = get_signing_key(&config.secret_access_key, &date, &config.region, "secretsmanager");
+    let signature = hex::encode(hmac(&signing_key, &string_to_sign));
+
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            config.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    // the client sets the host from the url
+    headers.retain(|(name, _)| *name != "host");
+    headers
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn get_signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let date_key = hmac(format!("AWS4{}", secret_access_key).as_bytes(), date);
+    let region_key = hmac(&date_key, region);
+    let service_key = hmac(&region_key, service);
+    hmac(&service_key, "aws4_request")
+}
+
+/// Backends enabled by the config, a reference to another backend can't be resolved
+pub struct SecretResolver {
+    backends: Vec<Box<dyn SecretBackend>>,
+}
+
+impl SecretResolver {
+    pub fn new(config: &SecretsConfig) -> Self {
+        let mut backends: Vec<Box<dyn SecretBackend>> = vec![];
+        if config.local_backends {
+            backends.push(Box::new(EnvSecretBackend));
+            backends.push(Box::new(FileSecretBackend { root: PathBuf::from(&config.file_root) }));
+        }
+        if let Some(aws_secrets_manager) = &config.aws_secrets_manager {
+            backends.push(Box::new(AwsSecretsManagerBackend { config: aws_secrets_manager.clone() }));
+        }
+        if let Some(vault) = &config.vault {
+            backends.push(Box::new(VaultSecretBackend { config: vault.clone() }));
+        }
+        Self { backends }
+    }
+
+    pub fn enabled_backends(&self) -> Vec<SecretBackendKind> {
+        self.backends.iter().map(|backend| backend.kind()).collect()
+    }
+
+    pub async fn resolve(&self, reference: &SecretReference) -> Result<String, String> {
+        let backend = self
+            .backends
+            .iter()
+            .find(|backend| backend.kind() == reference.backend)
+            .ok_or_else(|| format!("the {} backend is not enabled", reference.backend))?;
+        reference.select(backend.get(&reference.path).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_get_signing_key() {
+        // example of the SigV4 documentation
+        let signing_key = get_signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex::encode(signing_key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[test]
+    fn test_sign_get_secret_value() {
+        let config = AwsSecretsManagerConfig {
+            region: "eu-west-1".into(),
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "secret".into(),
+            session_token: Some("session".into()),
+            endpoint: None,
+        };
+        let now = Utc.with_ymd_and_hms(2025, 9, 1, 12, 0, 0).unwrap();
+        let headers = sign_get_secret_value(&config, "secretsmanager.eu-west-1.amazonaws.com", "{}", now);
+        let header = |name: &str| headers.iter().find(|(header, _)| *header == name).map(|(_, value)| value.as_str());
+
+        assert_eq!(header("x-amz-date"), Some("20250901T120000Z"));
+        assert_eq!(header("x-amz-target"), Some("secretsmanager.GetSecretValue"));
+        assert_eq!(header("host"), None);
+        let authorization = header("authorization").unwrap();
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20250901/eu-west-1/secretsmanager/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date;x-amz-security-token;x-amz-target, Signature="
+        ));
+        // the signature covers the body
+        assert_ne!(
+            sign_get_secret_value(&config, "secretsmanager.eu-west-1.amazonaws.com", r#"{"SecretId":"a"}"#, now),
+            headers
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        std::env::set_var("INDEXER_SECRET_ACME_TEST_SECRET_RESOLVER", r#"{"token": "dna_token"}"#);
+        std::env::set_var("TEST_SECRET_RESOLVER_SERVICE", "service secret");
+        let config = SecretsConfig {
+            local_backends: true,
+            file_root: std::env::temp_dir().display().to_string(),
+            vault: None,
+            aws_secrets_manager: None,
+        };
+        let resolver = SecretResolver::new(&config);
+
+        let reference = SecretReference::parse("env://acme/test_secret_resolver#token").unwrap();
+        assert_eq!(resolver.resolve(&reference).await, Ok("dna_token".to_string()));
+        // the variables of the service can't be read
+        let reference = SecretReference::parse("env://TEST_SECRET_RESOLVER_SERVICE").unwrap();
+        assert!(resolver.resolve(&reference).await.is_err());
+        let reference = SecretReference::parse("file:///../etc/passwd").unwrap();
+        assert!(resolver.resolve(&reference).await.is_err());
+        let reference = SecretReference::parse("vault://indexers/acme#token").unwrap();
+        assert!(resolver.resolve(&reference).await.is_err());
+
+        let resolver = SecretResolver::new(&SecretsConfig { local_backends: false, ..config });
+        assert!(resolver.enabled_backends().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::models::notification::SigningKey;
+
+pub const SIGNATURE_HEADER: &str = "x-indexer-signature";
+/// Maximum age of a signature accepted by the verification
+pub const SIGNATURE_TOLERANCE_SECONDS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn compute_signature(secret: &str, timestamp: i64, payload: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Signs the payload with every key active at `now`. The signature has the format
+/// `t=<unix timestamp>,<key id>=<hex hmac>,...` where the hmac is computed over
+/// `<unix timestamp>.<payload>`.
+pub fn sign_payload(keys: &[SigningKey], payload: &[u8], now: DateTime<Utc>) -> Option<String> {
+    let timestamp = now.timestamp();
+    let signatures: Vec<String> = keys
+        .iter()
+        .filter(|key| key.is_active_at(now))
+        .map(|key| format!("{}={}", key.id, compute_signature(key.secret.as_str(), timestamp, payload)))
+        .collect();
+    if signatures.is_empty() {
+        return None;
+    }
+    Some(format!("t={},{}", timestamp, signatures.join(",")))
+}
+
+/// Verifies a signature built by `sign_payload`, returns the id of the key that matched
+pub fn verify_signature(keys: &[SigningKey], payload: &[u8], signature: &str, now: DateTime<Utc>) -> Option<String> {
+    let mut parts = signature.split(',');
+    let timestamp: i64 = parts.next()?.strip_prefix("t=")?.parse().ok()?;
+    if (now.timestamp() - timestamp).abs() > SIGNATURE_TOLERANCE_SECONDS {
+        return None;
+    }
+
+    parts.filter_map(|part| part.split_once('=')).find_map(|(key_id, provided)| {
+        let key = keys.iter().find(|key| key.id == key_id && key.is_active_at(now))?;
+        let mut mac = HmacSha256::new_from_slice(key.secret.as_bytes()).ok()?;
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        // constant time comparison
+        mac.verify_slice(&hex::decode(provided).ok()?).ok()?;
+        Some(key.id.clone())
+    })
+}
+
+/// Verifies the `sha256=<hex hmac>` signature Git hosts send along with their webhooks, the hmac
+/// being computed over the payload only
+pub fn verify_hub_signature(secret: &str, payload: &[u8], signature: &str) -> bool {
+    let Some(provided) = signature.strip_prefix("sha256=").and_then(|provided| hex::decode(provided).ok()) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(payload);
+    // constant time comparison
+    mac.verify_slice(&provided).is_ok()
+}
+
+/// Key a tenant proves who it is with, the hmac of its id. Keys are derived rather than stored,
+/// changing the secret revokes all of them.
+pub fn get_tenant_key(secret: &str, tenant_id: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(b"tenant.");
+    mac.update(tenant_id.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub fn verify_tenant_key(secret: &str, tenant_id: &str, key: &str) -> bool {
+    let Ok(provided) = hex::decode(key) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(b"tenant.");
+    mac.update(tenant_id.as_bytes());
+    // constant time comparison
+    mac.verify_slice(&provided).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn key(id: &str, valid_from: Option<DateTime<Utc>>, valid_until: Option<DateTime<Utc>>) -> SigningKey {
+        SigningKey { id: id.into(), secret: format!("{}_secret", id), valid_from, valid_until }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let now = Utc::now();
+        let keys = vec![key("old", None, Some(now + Duration::hours(1))), key("new", Some(now), None)];
+
+        let signature = sign_payload(&keys, b"payload", now).unwrap();
+        assert!(signature.contains("old=") && signature.contains("new="));
+        assert_eq!(verify_signature(&keys, b"payload", signature.as_str(), now), Some("old".into()));
+        assert_eq!(verify_signature(&keys, b"tampered", signature.as_str(), now), None);
+
+        // once the old key expired only the new key verifies
+        let later = now + Duration::hours(2);
+        let signature = sign_payload(&keys, b"payload", later).unwrap();
+        assert!(!signature.contains("old="));
+        assert_eq!(verify_signature(&keys, b"payload", signature.as_str(), later), Some("new".into()));
+    }
+
+    #[test]
+    fn test_verify_rejects_stale_signature() {
+        let now = Utc::now();
+        let keys = vec![key("key", None, None)];
+        let signature = sign_payload(&keys, b"payload", now - Duration::hours(1)).unwrap();
+        assert_eq!(verify_signature(&keys, b"payload", signature.as_str(), now), None);
+    }
+
+    #[test]
+    fn test_verify_hub_signature() {
+        let mut mac = HmacSha256::new_from_slice(b"secret").unwrap();
+        mac.update(b"payload");
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        assert!(verify_hub_signature("secret", b"payload", &signature));
+        assert!(!verify_hub_signature("secret", b"tampered", &signature));
+        assert!(!verify_hub_signature("other", b"payload", &signature));
+        assert!(!verify_hub_signature("secret", b"payload", signature.trim_start_matches("sha256=")));
+    }
+
+    #[test]
+    fn test_verify_tenant_key() {
+        let key = get_tenant_key("secret", "acme");
+        assert!(verify_tenant_key("secret", "acme", &key));
+        assert!(!verify_tenant_key("secret", "globex", &key));
+        assert!(!verify_tenant_key("other", "acme", &key));
+        assert!(!verify_tenant_key("secret", "acme", "not-hex"));
+    }
+}
@@ -0,0 +1,51 @@
+use url::Url;
+
+use crate::domain::models::indexer::IndexerError;
+use crate::utils::target_policy::matches_domain;
+
+/// Streams indexers can read from. Sinks connect to their stream from the network of the service
+/// so the stream must be on one of the allowed domains, `*.` prefixed domains match their
+/// subdomains.
+#[derive(Clone, Debug, Default)]
+pub struct StreamPolicy {
+    pub allowed_domains: Vec<String>,
+}
+
+impl StreamPolicy {
+    pub fn validate(&self, stream_url: &str) -> Result<(), IndexerError> {
+        let url =
+            Url::parse(stream_url).map_err(|e| IndexerError::StreamUrlNotAllowed(format!("{}: {}", stream_url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(IndexerError::StreamUrlNotAllowed(format!("{}: unsupported scheme", stream_url)));
+        }
+        let allowed = url.domain().is_some_and(|domain| {
+            let domain = domain.to_lowercase();
+            self.allowed_domains.iter().any(|allowed| matches_domain(&domain, allowed))
+        });
+        if !allowed {
+            return Err(IndexerError::StreamUrlNotAllowed(stream_url.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("https://mainnet.starknet.a5a.ch", true)]
+    #[case("https://MAINNET.starknet.a5a.ch:443", true)]
+    #[case("http://dna.internal.example.com", true)]
+    #[case("https://a5a.ch.evil.com", false)]
+    #[case("https://10.0.0.1", false)]
+    #[case("https://localhost:7171", false)]
+    #[case("file:///etc/passwd", false)]
+    #[case("not a url", false)]
+    fn test_validate(#[case] stream_url: &str, #[case] allowed: bool) {
+        let policy = StreamPolicy { allowed_domains: vec!["*.a5a.ch".into(), "dna.internal.example.com".into()] };
+        assert_eq!(policy.validate(stream_url).is_ok(), allowed);
+    }
+}
@@ -0,0 +1,178 @@
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures_util::FutureExt;
+use tokio::task::JoinHandle;
+
+use crate::constants::runtime::{
+    CRASH_LOOP_MAX_PANICS, CRASH_LOOP_WINDOW_SECONDS, SUPERVISOR_MAX_RESTART_DELAY_MILLIS,
+    SUPERVISOR_RESTART_DELAY_MILLIS,
+};
+use crate::domain::models::runtime::SupervisedTaskMetrics;
+
+/// Panics of a task over the last window
+#[derive(Debug, Default)]
+struct CrashLoopBreaker {
+    panics: VecDeque<Instant>,
+}
+
+impl CrashLoopBreaker {
+    fn record_panic(&mut self, now: Instant) {
+        self.panics.push_back(now);
+        if self.panics.len() > CRASH_LOOP_MAX_PANICS {
+            self.panics.pop_front();
+        }
+    }
+
+    /// The task is crash looping until it stops panicking for a whole window
+    fn is_tripped(&self, now: Instant) -> bool {
+        let window = Duration::from_secs(CRASH_LOOP_WINDOW_SECONDS);
+        self.panics.len() >= CRASH_LOOP_MAX_PANICS
+            && self.panics.front().is_some_and(|first| now.duration_since(*first) <= window)
+    }
+}
+
+#[derive(Debug, Default)]
+struct SupervisedTask {
+    critical: bool,
+    restarts: u64,
+    last_panic: Option<String>,
+    last_panic_at: Option<DateTime<Utc>>,
+    breaker: CrashLoopBreaker,
+}
+
+/// Background tasks run by the supervisor, by name
+static SUPERVISED_TASKS: OnceLock<Mutex<BTreeMap<&'static str, SupervisedTask>>> = OnceLock::new();
+
+fn supervised_tasks() -> MutexGuard<'static, BTreeMap<&'static str, SupervisedTask>> {
+    SUPERVISED_TASKS.get_or_init(Mutex::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Spawns a background task which is restarted, with a growing delay, whenever it panics. A
+/// task which returns isn't restarted. Readiness fails while a `critical` task is crash looping.
+pub fn supervise<F, Fut>(name: &'static str, critical: bool, task: F) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    supervised_tasks().insert(name, SupervisedTask { critical, ..Default::default() });
+    tokio::spawn(async move {
+        let mut delay = Duration::from_millis(SUPERVISOR_RESTART_DELAY_MILLIS);
+        loop {
+            let started_at = Instant::now();
+            let panic = match AssertUnwindSafe(task()).catch_unwind().await {
+                Ok(()) => {
+                    tracing::info!("Background task {} finished", name);
+                    return;
+                }
+                Err(panic) => get_panic_message(panic.as_ref()),
+            };
+            // a task which ran fine for a while starts over with the shortest delay
+            if started_at.elapsed() > Duration::from_secs(CRASH_LOOP_WINDOW_SECONDS) {
+                delay = Duration::from_millis(SUPERVISOR_RESTART_DELAY_MILLIS);
+            }
+
+            let crash_looping = {
+                let mut tasks = supervised_tasks();
+                let supervised_task = tasks.entry(name).or_default();
+                supervised_task.restarts += 1;
+                supervised_task.last_panic = Some(panic.clone());
+                supervised_task.last_panic_at = Some(Utc::now());
+                supervised_task.breaker.record_panic(Instant::now());
+                supervised_task.breaker.is_tripped(Instant::now())
+            };
+            if crash_looping {
+                tracing::error!("Background task {} is crash looping: {}", name, panic);
+            } else {
+                tracing::error!("Background task {} panicked, restarting it in {:?}: {}", name, delay, panic);
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(Duration::from_millis(SUPERVISOR_MAX_RESTART_DELAY_MILLIS));
+        }
+    })
+}
+
+fn get_panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+pub fn get_supervised_task_metrics() -> Vec<SupervisedTaskMetrics> {
+    let now = Instant::now();
+    supervised_tasks()
+        .iter()
+        .map(|(name, task)| SupervisedTaskMetrics {
+            name: name.to_string(),
+            critical: task.critical,
+            restarts: task.restarts,
+            crash_looping: task.breaker.is_tripped(now),
+            last_panic: task.last_panic.clone(),
+            last_panic_at: task.last_panic_at,
+        })
+        .collect()
+}
+
+/// Critical tasks which keep panicking, the service isn't ready while there are any
+pub fn get_crash_looping_critical_tasks() -> Vec<String> {
+    get_supervised_task_metrics()
+        .into_iter()
+        .filter(|task| task.critical && task.crash_looping)
+        .map(|task| task.name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_crash_loop_breaker() {
+        let now = Instant::now();
+        let window = Duration::from_secs(CRASH_LOOP_WINDOW_SECONDS);
+        let mut breaker = CrashLoopBreaker::default();
+        for i in 0..CRASH_LOOP_MAX_PANICS as u32 {
+            assert!(!breaker.is_tripped(now));
+            breaker.record_panic(now + Duration::from_secs(i.into()));
+        }
+        assert!(breaker.is_tripped(now + Duration::from_secs(10)));
+        // recovers once the panics leave the window
+        assert!(!breaker.is_tripped(now + window + Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_supervise_restarts_panicking_tasks() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let task_attempts = Arc::clone(&attempts);
+        supervise("test-panicking-task", false, move || {
+            let attempts = Arc::clone(&task_attempts);
+            async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("task failed");
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let metrics = get_supervised_task_metrics();
+        let task = metrics.iter().find(|task| task.name == "test-panicking-task").unwrap();
+        assert_eq!(task.restarts, 2);
+        assert_eq!(task.last_panic.as_deref(), Some("task failed"));
+        assert!(!task.crash_looping);
+    }
+}
@@ -0,0 +1,230 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use url::{Host, Url};
+
+use crate::domain::models::indexer::IndexerError;
+
+/// IPv4/IPv6 network in the CIDR notation, e.g. `10.0.0.0/8`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cidr {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let address = IpAddr::from_str(address.trim()).map_err(|e| format!("invalid CIDR {}: {}", s, e))?;
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().map_err(|e| format!("invalid CIDR {}: {}", s, e))?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(format!("invalid CIDR {}: prefix is too long", s));
+        }
+        Ok(Self { address, prefix })
+    }
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Restricts the targets indexers can send data to, preventing indexers from reaching
+/// internal services (SSRF). Domains match themselves and their subdomains.
+///
+/// Hostnames are resolved and every resolved address is checked against the denied CIDRs and
+/// the private ranges, targets on the allowlists may be private. In strict mode targets must
+/// also match the allowlists when those are set.
+#[derive(Clone, Debug, Default)]
+pub struct TargetPolicy {
+    pub allowed_domains: Vec<String>,
+    pub denied_domains: Vec<String>,
+    pub allowed_cidrs: Vec<Cidr>,
+    pub denied_cidrs: Vec<Cidr>,
+    pub strict: bool,
+}
+
+pub(crate) fn matches_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim_start_matches("*.");
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// IPv4 addresses mapped in IPv6, e.g. `::ffff:10.0.0.1`, are checked as IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn is_private(ip: &IpAddr) -> bool {
+    match canonical(*ip) {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                // "this network" (0.0.0.0/8) and the shared address space of carrier NATs (100.64.0.0/10)
+                || first == 0
+                || (first == 100 && (second & 0xc0) == 64)
+        }
+        // unique local (fc00::/7) and link local (fe80::/10) addresses
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+impl TargetPolicy {
+    pub async fn validate(&self, target_url: &str) -> Result<(), IndexerError> {
+        let url =
+            Url::parse(target_url).map_err(|e| IndexerError::InvalidTargetUrl(format!("{}: {}", target_url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(IndexerError::InvalidTargetUrl(format!("{}: unsupported scheme", target_url)));
+        }
+        let not_allowed = || IndexerError::TargetUrlNotAllowed(target_url.to_string());
+
+        let (domain, addresses) = match url.host() {
+            Some(Host::Domain(domain)) => (Some(domain.to_lowercase()), vec![]),
+            Some(Host::Ipv4(ip)) => (None, vec![IpAddr::V4(ip)]),
+            Some(Host::Ipv6(ip)) => (None, vec![IpAddr::V6(ip)]),
+            None => return Err(IndexerError::InvalidTargetUrl(format!("{}: missing host", target_url))),
+        };
+
+        let addresses: Vec<IpAddr> = match &domain {
+            Some(domain) => {
+                if self.denied_domains.iter().any(|denied| matches_domain(domain, denied)) {
+                    return Err(not_allowed());
+                }
+                let port = url.port_or_known_default().unwrap_or(80);
+                tokio::net::lookup_host((domain.as_str(), port))
+                    .await
+                    .map_err(|e| IndexerError::InvalidTargetUrl(format!("{}: {}", target_url, e)))?
+                    .map(|address| canonical(address.ip()))
+                    .collect()
+            }
+            None => addresses.into_iter().map(canonical).collect(),
+        };
+
+        if addresses.iter().any(|ip| self.denied_cidrs.iter().any(|cidr| cidr.contains(ip))) {
+            return Err(not_allowed());
+        }
+
+        let domain_allowed = domain
+            .as_ref()
+            .map_or(false, |domain| self.allowed_domains.iter().any(|allowed| matches_domain(domain, allowed)));
+        let addresses_allowed =
+            !addresses.is_empty() && addresses.iter().all(|ip| self.allowed_cidrs.iter().any(|cidr| cidr.contains(ip)));
+        if domain_allowed || addresses_allowed {
+            return Ok(());
+        }
+        let has_allowlist = !self.allowed_domains.is_empty() || !self.allowed_cidrs.is_empty();
+        if (self.strict && has_allowlist) || addresses.iter().any(is_private) {
+            return Err(not_allowed());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("10.0.0.0/8", "10.1.2.3", true)]
+    #[case("10.0.0.0/8", "11.1.2.3", false)]
+    #[case("192.168.1.7", "192.168.1.7", true)]
+    #[case("0.0.0.0/0", "8.8.8.8", true)]
+    #[case("fc00::/7", "fd12::1", true)]
+    #[case("fc00::/7", "10.1.2.3", false)]
+    fn test_cidr_contains(#[case] cidr: &str, #[case] ip: &str, #[case] expected: bool) {
+        let cidr = Cidr::from_str(cidr).unwrap();
+        assert_eq!(cidr.contains(&IpAddr::from_str(ip).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_invalid_cidr() {
+        assert!(Cidr::from_str("10.0.0.0/33").is_err());
+        assert!(Cidr::from_str("not an ip").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_denylist() {
+        let policy = TargetPolicy {
+            denied_domains: vec!["internal.example.com".into()],
+            denied_cidrs: vec![Cidr::from_str("169.254.0.0/16").unwrap()],
+            ..Default::default()
+        };
+        assert!(policy.validate("https://api.example.com/hook").await.is_ok());
+        assert!(policy.validate("https://internal.example.com/hook").await.is_err());
+        assert!(policy.validate("https://db.internal.example.com/hook").await.is_err());
+        assert!(policy.validate("http://169.254.169.254/latest").await.is_err());
+        assert!(policy.validate("ftp://example.com").await.is_err());
+    }
+
+    #[rstest]
+    #[case("10.0.0.1", true)]
+    #[case("169.254.169.254", true)]
+    #[case("100.64.0.1", true)]
+    #[case("100.127.255.254", true)]
+    #[case("100.128.0.1", false)]
+    #[case("0.1.2.3", true)]
+    #[case("::ffff:10.0.0.1", true)]
+    #[case("::ffff:8.8.8.8", false)]
+    #[case("fd12::1", true)]
+    #[case("8.8.8.8", false)]
+    fn test_is_private(#[case] ip: &str, #[case] expected: bool) {
+        assert_eq!(is_private(&IpAddr::from_str(ip).unwrap()), expected);
+    }
+
+    #[tokio::test]
+    async fn test_private_targets() {
+        // private addresses are denied whatever the mode, including behind a hostname
+        let policy = TargetPolicy::default();
+        assert!(policy.validate("http://10.0.0.1/hook").await.is_err());
+        assert!(policy.validate("http://[::ffff:169.254.169.254]/latest").await.is_err());
+        assert!(policy.validate("http://localhost:8080/hook").await.is_err());
+
+        let policy = TargetPolicy { allowed_cidrs: vec![Cidr::from_str("127.0.0.0/8").unwrap()], ..Default::default() };
+        assert!(policy.validate("http://127.0.0.1:8080/hook").await.is_ok());
+        // the allowlist is only required in strict mode
+        assert!(policy.validate("http://8.8.8.8").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode() {
+        let policy = TargetPolicy { strict: true, ..Default::default() };
+        assert!(policy.validate("http://127.0.0.1:8080").await.is_err());
+        assert!(policy.validate("http://[::1]:8080").await.is_err());
+        assert!(policy.validate("http://8.8.8.8").await.is_ok());
+
+        let policy = TargetPolicy { strict: true, allowed_domains: vec!["example.com".into()], ..Default::default() };
+        assert!(policy.validate("http://8.8.8.8").await.is_err());
+    }
+}
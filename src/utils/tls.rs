@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use hyper::server::conn::Http;
+use hyper::service::{service_fn, Service};
+use hyper::Body;
+use rustls::server::AllowAnyAnonymousOrAuthenticatedClient;
+use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use rustls_pemfile::Item;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+use crate::config::{config, TlsConfig};
+use crate::errors::AppError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("failed to read {0}: {1}")]
+    FailedToRead(String, std::io::Error),
+    #[error("no certificate in {0}")]
+    MissingCertificate(String),
+    #[error("no private key in {0}")]
+    MissingPrivateKey(String),
+    #[error("invalid certificate in {0}: {1}")]
+    InvalidCertificate(String, String),
+    #[error("invalid TLS config: {0}")]
+    InvalidConfig(rustls::Error),
+}
+
+/// Set on the requests of the connections which presented a certificate signed by the client CA
+#[derive(Clone, Copy, Debug)]
+pub struct ClientCertificate;
+
+fn read_pem(path: &str) -> Result<Vec<Item>, TlsError> {
+    let file = File::open(path).map_err(|e| TlsError::FailedToRead(path.to_string(), e))?;
+    rustls_pemfile::read_all(&mut BufReader::new(file)).map_err(|e| TlsError::FailedToRead(path.to_string(), e))
+}
+
+fn read_certificates(path: &str) -> Result<Vec<Certificate>, TlsError> {
+    let certificates: Vec<Certificate> = read_pem(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::X509Certificate(certificate) => Some(Certificate(certificate)),
+            _ => None,
+        })
+        .collect();
+    if certificates.is_empty() {
+        return Err(TlsError::MissingCertificate(path.to_string()));
+    }
+    Ok(certificates)
+}
+
+fn read_private_key(path: &str) -> Result<PrivateKey, TlsError> {
+    read_pem(path)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| TlsError::MissingPrivateKey(path.to_string()))
+}
+
+/// HTTP/2 is negotiated through ALPN, clients which don't support it get HTTP/1.1. Once a client
+/// CA is set, certificates presented by the clients are verified against it, but clients without
+/// one are still served the public routes.
+pub fn load_server_config(tls: &TlsConfig) -> Result<ServerConfig, TlsError> {
+    let certificates = read_certificates(&tls.cert_path)?;
+    let key = read_private_key(&tls.key_path)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let mut server_config = match &tls.client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in read_certificates(client_ca_path)? {
+                roots
+                    .add(&certificate)
+                    .map_err(|e| TlsError::InvalidCertificate(client_ca_path.clone(), e.to_string()))?;
+            }
+            builder
+                .with_client_cert_verifier(AllowAnyAnonymousOrAuthenticatedClient::new(roots))
+                .with_single_cert(certificates, key)
+        }
+        None => builder.with_no_client_auth().with_single_cert(certificates, key),
+    }
+    .map_err(TlsError::InvalidConfig)?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(server_config)
+}
+
+/// Serves the router over TLS, the handshake and the requests of each connection are handled in
+/// a task of their own
+pub async fn serve_tls(socket_addr: SocketAddr, app: Router, server_config: ServerConfig) -> std::io::Result<()> {
+    let listener = TcpListener::bind(socket_addr).await?;
+    let acceptor = TlsAcceptor::from(Arc::new(server_config));
+    let protocol = Arc::new(Http::new());
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                // e.g. too many open files, retrying right away would spin
+                tracing::error!("Failed to accept a connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let (acceptor, protocol, mut router) = (acceptor.clone(), Arc::clone(&protocol), app.clone());
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+            let client_certificate = stream.get_ref().1.peer_certificates().is_some();
+            let service = service_fn(move |mut request: Request<Body>| {
+                if client_certificate {
+                    request.extensions_mut().insert(ClientCertificate);
+                }
+                router.call(request)
+            });
+            if let Err(e) = protocol.serve_connection(stream, service).await {
+                tracing::debug!("Failed to serve the connection of {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Internal routes are only served to the clients with a certificate signed by the client CA
+/// once one is set and the internal listener uses TLS. Otherwise they're served to anyone who
+/// can reach the internal listener.
+pub async fn require_client_certificate<B>(request: Request<B>, next: Next<B>) -> Response {
+    let requires_certificate = config().await.internal_tls().is_some_and(|tls| tls.client_ca_path.is_some());
+    if requires_certificate && request.extensions().get::<ClientCertificate>().is_none() {
+        return AppError::Unauthorized.into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pem() {
+        let dir = std::env::temp_dir().join(format!("tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cert.pem");
+        std::fs::write(&path, "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(read_certificates(path).unwrap(), vec![Certificate(vec![0, 0, 0])]);
+        assert!(matches!(read_private_key(path), Err(TlsError::MissingPrivateKey(_))));
+        let missing = dir.join("missing.pem");
+        assert!(matches!(read_certificates(missing.to_str().unwrap()), Err(TlsError::FailedToRead(_, _))));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}